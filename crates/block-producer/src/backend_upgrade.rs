@@ -0,0 +1,386 @@
+//! Register new backends at runtime
+//!
+//! Backend switches are announced by the signal cell on L1, so every node activates a switch at
+//! the same height. The signal cell keeps the whole history of announced switches, a node which
+//! is catching up registers switches below its L1 tip before applying the blocks after them.
+//!
+//! Backends must be staged before they are announced, either in the config or by the admin RPC.
+//! The validator of every staged backend is verified against its deployment cell on L1.
+//! Registered switches are saved, the file is a cache of the signal cell and is rebuilt from L1
+//! if it's missing.
+
+use std::{convert::TryInto, fs, path::Path, sync::Arc};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use async_trait::async_trait;
+use gw_config::{BackendSwitchConfig, BackendUpgradeConfig, StagedBackendConfig};
+use gw_generator::{
+    backend_manage::{load_registered_backend_switches, save_registered_backend_switches},
+    Generator,
+};
+use gw_rpc_client::rpc_client::RPCClient;
+use gw_rpc_server::registry::BackendUpgradeRPC;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{packed::Script, prelude::*};
+use tokio::sync::Mutex;
+
+/// A backend switch announced by the signal cell
+///
+/// Data of the signal cell is a list of switches in increasing height order:
+/// `(switch_height(u64 LE) | n(u32 LE) | validator_script_type_hash(32 bytes) * n) *`
+#[derive(Debug, PartialEq, Eq)]
+pub struct BackendUpgradeSignal {
+    pub switch_height: u64,
+    pub validator_script_type_hashes: Vec<[u8; 32]>,
+}
+
+impl BackendUpgradeSignal {
+    pub fn parse_signals(mut data: &[u8]) -> Result<Vec<Self>> {
+        let mut signals: Vec<BackendUpgradeSignal> = Vec::new();
+        while !data.is_empty() {
+            ensure!(data.len() >= 12, "truncated signal cell data");
+            let switch_height = u64::from_le_bytes(data[..8].try_into()?);
+            let n = u32::from_le_bytes(data[8..12].try_into()?) as usize;
+            data = &data[12..];
+            ensure!(
+                n.checked_mul(32).map(|len| data.len() >= len) == Some(true),
+                "truncated signal cell data at switch height {}",
+                switch_height
+            );
+            if let Some(last) = signals.last() {
+                ensure!(
+                    switch_height > last.switch_height,
+                    "signal cell switch heights aren't increasing, {} after {}",
+                    switch_height,
+                    last.switch_height
+                );
+            }
+            let validator_script_type_hashes = data[..n * 32]
+                .chunks_exact(32)
+                .map(|hash| hash.try_into())
+                .collect::<Result<_, _>>()?;
+            data = &data[n * 32..];
+            signals.push(BackendUpgradeSignal {
+                switch_height,
+                validator_script_type_hashes,
+            });
+        }
+        Ok(signals)
+    }
+}
+
+struct UpgraderState {
+    // backends staged by the admin RPC
+    rpc_staged_backends: Vec<StagedBackendConfig>,
+    registered_switches: Vec<BackendSwitchConfig>,
+}
+
+pub struct BackendUpgrader {
+    config: BackendUpgradeConfig,
+    signal_cell_type: Script,
+    signal_cell_lock: Script,
+    rpc_client: RPCClient,
+    generator: Arc<Generator>,
+    store: Store,
+    // the lock also serializes syncs and stagings
+    state: Mutex<UpgraderState>,
+}
+
+impl BackendUpgrader {
+    pub fn create(
+        config: BackendUpgradeConfig,
+        rpc_client: RPCClient,
+        generator: Arc<Generator>,
+        store: Store,
+    ) -> Result<Self> {
+        let signal_cell_type = config.signal_cell_type_script.clone().into();
+        let signal_cell_lock = config.signal_cell_lock_script.clone().into();
+        let registered_switches =
+            load_registered_backend_switches(&config.registered_switches_path)?;
+        let rpc_staged_backends = load_staged_backends(&config.staged_backends_path)?;
+
+        Ok(BackendUpgrader {
+            config,
+            signal_cell_type,
+            signal_cell_lock,
+            rpc_client,
+            generator,
+            store,
+            state: Mutex::new(UpgraderState {
+                rpc_staged_backends,
+                registered_switches,
+            }),
+        })
+    }
+
+    /// Register the switches announced by the signal cell on L1
+    ///
+    /// Must be called before applying L1 blocks, switches below the L1 tip are registered while
+    /// catching up.
+    pub async fn sync_switches(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+
+        let signal_cell = match self
+            .rpc_client
+            .query_cell_by_lock_and_type_script(
+                self.signal_cell_lock.clone(),
+                self.signal_cell_type.clone(),
+            )
+            .await?
+        {
+            Some(cell) => cell,
+            None => return Ok(()),
+        };
+        // anyone can create a cell with the type script, only the owner can announce upgrades
+        ensure!(
+            signal_cell.output.lock().hash() == self.signal_cell_lock.hash(),
+            "signal cell {:?} isn't owned by the signal cell lock",
+            signal_cell.out_point
+        );
+        let signals = BackendUpgradeSignal::parse_signals(&signal_cell.data)?;
+
+        let backend_manage = self.generator.backend_manage();
+        for signal in signals {
+            let registered = state
+                .registered_switches
+                .iter()
+                .find(|switch| switch.switch_height == signal.switch_height);
+            if let Some(registered) = registered {
+                let registered_hashes = registered
+                    .backends
+                    .iter()
+                    .map(|backend| backend.validator_script_type_hash.0);
+                ensure!(
+                    registered_hashes.eq(signal.validator_script_type_hashes.iter().copied()),
+                    "signal cell changes the registered backend switch at height {}",
+                    signal.switch_height
+                );
+                continue;
+            }
+            if matches!(backend_manage.last_switch_height(), Some(height) if signal.switch_height <= height)
+            {
+                // already in the config
+                continue;
+            }
+
+            let mut backends = Vec::with_capacity(signal.validator_script_type_hashes.len());
+            for hash in signal.validator_script_type_hashes {
+                let staged = self
+                    .config
+                    .staged_backends
+                    .iter()
+                    .chain(state.rpc_staged_backends.iter())
+                    .find(|staged| staged.backend.validator_script_type_hash.0 == hash)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "backend {} announced at height {} isn't staged",
+                            hex::encode(hash),
+                            signal.switch_height
+                        )
+                    })?;
+                self.verify_backend(staged).await?;
+                backends.push(staged.backend.clone());
+            }
+
+            let tip_number: u64 = self.store.get_tip_block()?.raw().number().unpack();
+            if signal.switch_height <= tip_number {
+                log::error!(
+                    "[backend upgrade] backend switch at height {} is registered after block {}, blocks from the switch height were executed with the previous backends",
+                    signal.switch_height,
+                    tip_number
+                );
+            }
+
+            let switch = BackendSwitchConfig {
+                switch_height: signal.switch_height,
+                backends,
+            };
+            backend_manage.register_backend_switch(switch.clone())?;
+            state.registered_switches.push(switch);
+            save_registered_backend_switches(
+                &self.config.registered_switches_path,
+                &state.registered_switches,
+            )?;
+
+            log::info!(
+                "[backend upgrade] register backend switch at height {}",
+                signal.switch_height
+            );
+        }
+
+        Ok(())
+    }
+
+    pub async fn stage_backends(&self, backends: Vec<StagedBackendConfig>) -> Result<()> {
+        let mut state = self.state.lock().await;
+
+        for staged in backends.iter() {
+            self.verify_backend(staged).await?;
+        }
+
+        for staged in backends {
+            let hash = &staged.backend.validator_script_type_hash;
+            state
+                .rpc_staged_backends
+                .retain(|s| &s.backend.validator_script_type_hash != hash);
+            log::info!("[backend upgrade] stage backend {}", hash);
+            state.rpc_staged_backends.push(staged);
+        }
+        save_staged_backends(
+            &self.config.staged_backends_path,
+            &state.rpc_staged_backends,
+        )
+    }
+
+    // the validator must be the same as the deployment cell on L1
+    async fn verify_backend(&self, staged: &StagedBackendConfig) -> Result<()> {
+        let StagedBackendConfig {
+            backend,
+            validator_script_type,
+        } = staged;
+        if validator_script_type.hash() != backend.validator_script_type_hash {
+            bail!(
+                "validator script type hash mismatch, expected {}, got {}",
+                backend.validator_script_type_hash,
+                validator_script_type.hash()
+            );
+        }
+
+        let validator = fs::read(&backend.validator_path).with_context(|| {
+            format!(
+                "load validator from {}",
+                backend.validator_path.to_string_lossy()
+            )
+        })?;
+        let deployment_cell = self
+            .rpc_client
+            .query_cell_by_type_script(validator_script_type.clone().into())
+            .await?
+            .ok_or_else(|| {
+                anyhow!(
+                    "validator {} deployment cell not found",
+                    backend.validator_script_type_hash
+                )
+            })?;
+        if deployment_cell.data.as_ref() != validator.as_slice() {
+            bail!(
+                "validator {} doesn't match the deployment cell",
+                backend.validator_script_type_hash
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BackendUpgradeRPC for BackendUpgrader {
+    async fn stage_backends(&self, backends: Vec<StagedBackendConfig>) -> Result<()> {
+        // binaries of the admin RPC must be put into the backends dir by the operator
+        let backends_dir = self.config.backends_dir.canonicalize().with_context(|| {
+            format!(
+                "backends dir {}",
+                self.config.backends_dir.to_string_lossy()
+            )
+        })?;
+        for staged in backends.iter() {
+            check_in_dir(&backends_dir, &staged.backend.validator_path)?;
+            check_in_dir(&backends_dir, &staged.backend.generator_path)?;
+        }
+        BackendUpgrader::stage_backends(self, backends).await
+    }
+}
+
+fn load_staged_backends(path: &Path) -> Result<Vec<StagedBackendConfig>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read(path)
+        .with_context(|| format!("load staged backends from {}", path.to_string_lossy()))?;
+    let backends = serde_json::from_slice(&content)
+        .with_context(|| format!("parse staged backends from {}", path.to_string_lossy()))?;
+    Ok(backends)
+}
+
+fn save_staged_backends(path: &Path, backends: &[StagedBackendConfig]) -> Result<()> {
+    let content = serde_json::to_vec_pretty(backends)?;
+    // write to a temporary file first, so a crash never leaves a partial file
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("save staged backends to {}", path.to_string_lossy()))?;
+    Ok(())
+}
+
+// `dir` must be canonicalized, symlinks and `..` in `path` are resolved before the check
+fn check_in_dir(dir: &Path, path: &Path) -> Result<()> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("backend binary {}", path.to_string_lossy()))?;
+    ensure!(
+        canonical.starts_with(dir),
+        "backend binary {} isn't in the backends dir {}",
+        path.to_string_lossy(),
+        dir.to_string_lossy()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_in_dir, BackendUpgradeSignal};
+
+    fn encode_signal(switch_height: u64, hashes: &[[u8; 32]]) -> Vec<u8> {
+        let mut data = switch_height.to_le_bytes().to_vec();
+        data.extend_from_slice(&(hashes.len() as u32).to_le_bytes());
+        for hash in hashes {
+            data.extend_from_slice(hash);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_backend_upgrade_signals() {
+        let mut data = encode_signal(42, &[[1u8; 32], [2u8; 32]]);
+        data.extend(encode_signal(100, &[[3u8; 32]]));
+        let signals = BackendUpgradeSignal::parse_signals(&data).unwrap();
+        assert_eq!(
+            signals,
+            vec![
+                BackendUpgradeSignal {
+                    switch_height: 42,
+                    validator_script_type_hashes: vec![[1u8; 32], [2u8; 32]],
+                },
+                BackendUpgradeSignal {
+                    switch_height: 100,
+                    validator_script_type_hashes: vec![[3u8; 32]],
+                },
+            ]
+        );
+        assert!(BackendUpgradeSignal::parse_signals(&[]).unwrap().is_empty());
+
+        assert!(BackendUpgradeSignal::parse_signals(&data[..data.len() - 1]).is_err());
+        assert!(BackendUpgradeSignal::parse_signals(&data[..11]).is_err());
+
+        // heights must increase
+        let mut data = encode_signal(100, &[[1u8; 32]]);
+        data.extend(encode_signal(100, &[[2u8; 32]]));
+        assert!(BackendUpgradeSignal::parse_signals(&data).is_err());
+    }
+
+    #[test]
+    fn test_check_in_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("backends");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("generator"), "generator").unwrap();
+        std::fs::write(root.path().join("outside"), "outside").unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        assert!(check_in_dir(&dir, &dir.join("generator")).is_ok());
+        assert!(check_in_dir(&dir, &dir.join("../outside")).is_err());
+        assert!(check_in_dir(&dir, &root.path().join("outside")).is_err());
+        // not found
+        assert!(check_in_dir(&dir, &dir.join("validator")).is_err());
+    }
+}
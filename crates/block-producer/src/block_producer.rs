@@ -32,8 +32,13 @@ use gw_types::{
     prelude::*,
 };
 use gw_utils::{
-    fee::fill_tx_fee_with_local, genesis_info::CKBGenesisInfo, local_cells::LocalCellsManager,
-    query_rollup_cell, since::Since, transaction_skeleton::TransactionSkeleton, wallet::Wallet,
+    fee::fill_tx_fee_with_local,
+    genesis_info::CKBGenesisInfo,
+    local_cells::LocalCellsManager,
+    query_rollup_cell,
+    signer::{build_signer, Signer},
+    since::Since,
+    transaction_skeleton::TransactionSkeleton,
 };
 use std::{collections::HashSet, sync::Arc, time::Instant};
 use tokio::sync::Mutex;
@@ -72,7 +77,7 @@ pub struct BlockProducer {
     store: Store,
     chain: Arc<Mutex<Chain>>,
     generator: Arc<Generator>,
-    wallet: Wallet,
+    signer: Arc<dyn Signer>,
     config: BlockProducerConfig,
     rpc_client: RPCClient,
     ckb_genesis_info: CKBGenesisInfo,
@@ -106,8 +111,8 @@ impl BlockProducer {
             contracts_dep_manager,
         } = args;
 
-        let wallet = match config.wallet_config {
-            Some(ref c) => Wallet::from_config(c).with_context(|| "init wallet")?,
+        let signer = match config.wallet_config {
+            Some(ref c) => build_signer(c).with_context(|| "init signer")?,
             None => bail!("no wallet config for block producer"),
         };
 
@@ -116,7 +121,7 @@ impl BlockProducer {
             generator,
             chain,
             rpc_client,
-            wallet,
+            signer,
             ckb_genesis_info,
             config,
             tests_control,
@@ -168,7 +173,7 @@ impl BlockProducer {
             smt.root().to_owned()
         };
         let param = ProduceBlockParam {
-            stake_cell_owner_lock_hash: self.wallet.lock_script().hash().into(),
+            stake_cell_owner_lock_hash: self.signer.lock_script().hash().into(),
             reverted_block_root,
             rollup_config_hash: self.rollup_config_hash,
             block_param,
@@ -321,7 +326,7 @@ impl BlockProducer {
             &block,
             &contracts_dep,
             &self.rpc_client,
-            self.wallet.lock_script().to_owned(),
+            self.signer.lock_script().to_owned(),
             local_cells_manager,
         )
         .await?;
@@ -449,7 +454,7 @@ impl BlockProducer {
         fill_tx_fee_with_local(
             &mut tx_skeleton,
             &self.rpc_client.indexer,
-            self.wallet.lock_script().to_owned(),
+            self.signer.lock_script().to_owned(),
             local_cells_manager,
             self.config.fee_rate,
         )
@@ -460,7 +465,7 @@ impl BlockProducer {
             "check duplicated inputs"
         );
         // sign
-        let tx = self.wallet.sign_tx_skeleton(tx_skeleton)?;
+        let tx = self.signer.sign_tx_skeleton(tx_skeleton).await?;
         ensure!(
             (tx.as_slice().len() as u64) < MAX_BLOCK_BYTES,
             TransactionSizeError::TransactionTooLarge
@@ -301,9 +301,10 @@ async fn apply_msg(client: &mut BlockSyncClient, msg: BlockSync) -> Result<()> {
                 let mut mem_pool = mem_pool.lock().await;
                 let _guard = span.enter();
                 let mem_block_config = mem_pool.config();
+                let mem_block_number: u64 = mem_pool.mem_block().block_info().number().unpack();
                 *mem_pool.cycles_pool_mut() = CyclesPool::new(
                     mem_block_config.max_cycles_limit,
-                    mem_block_config.syscall_cycles.clone(),
+                    mem_block_config.syscall_cycles_at(mem_block_number).clone(),
                 );
 
                 let result = mem_pool.push_transaction(tx);
@@ -24,6 +24,8 @@ use gw_types::{
 use tokio::sync::Mutex;
 use tracing::instrument;
 
+use crate::backend_upgrade::BackendUpgrader;
+
 #[derive(thiserror::Error, Debug)]
 #[error("chain updater query l1 tx {tx_hash} error {source}")]
 pub struct QueryL1TxError {
@@ -46,6 +48,7 @@ pub struct ChainUpdater {
     rpc_client: RPCClient,
     rollup_context: RollupContext,
     rollup_type_script: ckb_types::packed::Script,
+    backend_upgrader: Option<Arc<BackendUpgrader>>,
 }
 
 impl ChainUpdater {
@@ -54,6 +57,7 @@ impl ChainUpdater {
         rpc_client: RPCClient,
         rollup_context: RollupContext,
         rollup_type_script: Script,
+        backend_upgrader: Option<Arc<BackendUpgrader>>,
     ) -> ChainUpdater {
         let rollup_type_script =
             ckb_types::packed::Script::new_unchecked(rollup_type_script.as_bytes());
@@ -63,6 +67,7 @@ impl ChainUpdater {
             rpc_client,
             rollup_context,
             rollup_type_script,
+            backend_upgrader,
         }
     }
 
@@ -78,6 +83,11 @@ impl ChainUpdater {
         let rollup_action = self.extract_rollup_action(&tx)?;
         let context = match rollup_action.to_enum() {
             RollupActionUnion::RollupSubmitBlock(submitted) => {
+                // the block may be after a backend switch announced on L1
+                if let Some(ref backend_upgrader) = self.backend_upgrader {
+                    backend_upgrader.sync_switches().await?;
+                }
+
                 let l2block = submitted.block();
                 let (deposit_info_vec, asset_type_scripts) =
                     self.extract_deposit_requests(&tx).await?;
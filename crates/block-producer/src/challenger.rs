@@ -34,8 +34,8 @@ use gw_types::packed::{
 use gw_types::prelude::{Pack, Unpack};
 use gw_utils::fee::fill_tx_fee;
 use gw_utils::genesis_info::CKBGenesisInfo;
+use gw_utils::signer::Signer;
 use gw_utils::transaction_skeleton::TransactionSkeleton;
-use gw_utils::wallet::Wallet;
 use tokio::sync::Mutex;
 use tracing::instrument;
 
@@ -51,7 +51,7 @@ const TRANSACTION_FAILED_TO_RESOLVE_ERROR: &str = "TransactionFailedToResolve";
 pub struct Challenger {
     rollup_context: RollupContext,
     rpc_client: RPCClient,
-    wallet: Wallet,
+    signer: Arc<dyn Signer>,
     config: BlockProducerConfig,
     ckb_genesis_info: CKBGenesisInfo,
     builtin_load_data: HashMap<H256, CellDep>,
@@ -67,7 +67,7 @@ pub struct Challenger {
 pub struct ChallengerNewArgs {
     pub rollup_context: RollupContext,
     pub rpc_client: RPCClient,
-    pub wallet: Wallet,
+    pub signer: Arc<dyn Signer>,
     pub config: BlockProducerConfig,
     pub debug_config: DebugConfig,
     pub builtin_load_data: HashMap<H256, CellDep>,
@@ -84,7 +84,7 @@ impl Challenger {
         let ChallengerNewArgs {
             rollup_context,
             rpc_client,
-            wallet,
+            signer,
             config,
             debug_config,
             builtin_load_data,
@@ -99,7 +99,7 @@ impl Challenger {
         Self {
             rollup_context,
             rpc_client,
-            wallet,
+            signer,
             config,
             debug_config,
             ckb_genesis_info,
@@ -247,7 +247,7 @@ impl Challenger {
         tx_skeleton.outputs_mut().push(challenge_cell);
 
         let challenger_lock_dep = self.ckb_genesis_info.sighash_dep();
-        let challenger_lock = self.wallet.lock_script().to_owned();
+        let challenger_lock = self.signer.lock_script().to_owned();
         tx_skeleton.cell_deps_mut().push(challenger_lock_dep);
         fill_tx_fee(
             &mut tx_skeleton,
@@ -257,7 +257,7 @@ impl Challenger {
         )
         .await?;
 
-        let tx = self.wallet.sign_tx_skeleton(tx_skeleton)?;
+        let tx = self.signer.sign_tx_skeleton(tx_skeleton).await?;
 
         if let Err(err) = self.dry_run_transaction(&tx, "challenge block").await {
             utils::dump_transaction(&self.debug_config.debug_tx_dump_path, &self.rpc_client, &tx)
@@ -293,7 +293,7 @@ impl Challenger {
 
         let challenge_cell = to_cell_info(challenge_cell);
         let burn_lock = self.config.challenger_config.burn_lock.clone().into();
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
         let mut cancel_output = gw_challenge::cancel_challenge::build_output(
             &self.rollup_context,
             prev_state,
@@ -502,7 +502,7 @@ impl Challenger {
         tx_skeleton.outputs_mut().extend(revert_output.burn_cells);
 
         let challenger_lock_dep = self.ckb_genesis_info.sighash_dep();
-        let challenger_lock = self.wallet.lock_script().to_owned();
+        let challenger_lock = self.signer.lock_script().to_owned();
         tx_skeleton.cell_deps_mut().push(challenger_lock_dep);
         fill_tx_fee(
             &mut tx_skeleton,
@@ -512,7 +512,7 @@ impl Challenger {
         )
         .await?;
 
-        let tx = self.wallet.sign_tx_skeleton(tx_skeleton)?;
+        let tx = self.signer.sign_tx_skeleton(tx_skeleton).await?;
 
         if let Err(err) = self.dry_run_transaction(&tx, "revert block").await {
             utils::dump_transaction(&self.debug_config.debug_tx_dump_path, &self.rpc_client, &tx)
@@ -545,7 +545,7 @@ impl Challenger {
         }
 
         let challenger_lock_dep = self.ckb_genesis_info.sighash_dep();
-        let challenger_lock = self.wallet.lock_script().to_owned();
+        let challenger_lock = self.signer.lock_script().to_owned();
         tx_skeleton.cell_deps_mut().push(challenger_lock_dep);
         fill_tx_fee(
             &mut tx_skeleton,
@@ -555,7 +555,7 @@ impl Challenger {
         )
         .await?;
 
-        self.wallet.sign_tx_skeleton(tx_skeleton)
+        self.signer.sign_tx_skeleton(tx_skeleton).await
     }
 
     async fn build_cancel_tx(
@@ -636,7 +636,7 @@ impl Challenger {
         tx_skeleton.outputs_mut().extend(cancel_output.burn_cells);
 
         // Signature verification needs an owner cell
-        if !has_lock_cell(&tx_skeleton, self.wallet.lock_script()) {
+        if !has_lock_cell(&tx_skeleton, self.signer.lock_script()) {
             let spent_inputs = verifier_context.spent_inputs;
 
             let owner_input = self
@@ -655,7 +655,7 @@ impl Challenger {
             *tx_skeleton.cell_deps_mut() = deps.into_iter().cloned().collect();
         }
 
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
         fill_tx_fee(
             &mut tx_skeleton,
             &self.rpc_client.indexer,
@@ -663,7 +663,7 @@ impl Challenger {
             self.config.fee_rate,
         )
         .await?;
-        self.wallet.sign_tx_skeleton(tx_skeleton)
+        self.signer.sign_tx_skeleton(tx_skeleton).await
     }
 
     async fn query_owner_cell_for_verifier(
@@ -672,7 +672,7 @@ impl Challenger {
         spent_inputs: Option<HashSet<OutPoint>>,
     ) -> Result<InputCellInfo> {
         let rpc_client = &self.rpc_client;
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
 
         if let Ok(Some(cell)) = rpc_client.query_owner_cell(owner_lock, spent_inputs).await {
             return Ok(to_input_cell_info(cell));
@@ -686,7 +686,7 @@ impl Challenger {
         .await
         .with_context(|| format!("wait for tx committed 0x{}", to_hex(&verifier_tx_hash)))??;
 
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
         let cell = {
             let query = rpc_client.query_owner_cell(owner_lock, None).await?;
             query.ok_or_else(|| anyhow!("can't find an owner cell for verifier"))?
@@ -1,7 +1,7 @@
 use crate::types::ChainEvent;
 use gw_utils::genesis_info::CKBGenesisInfo;
 use gw_utils::transaction_skeleton::TransactionSkeleton;
-use gw_utils::{fee::fill_tx_fee, wallet::Wallet};
+use gw_utils::{fee::fill_tx_fee, signer::Signer};
 
 use anyhow::{anyhow, Result};
 use ckb_types::prelude::{Builder, Entity};
@@ -59,7 +59,7 @@ type ConsumedVerifiers = Arc<Mutex<Vec<(Verifier, Option<H256>)>>>;
 pub struct Cleaner {
     rpc_client: RPCClient,
     ckb_genesis_info: CKBGenesisInfo,
-    wallet: Wallet,
+    signer: Arc<dyn Signer>,
     consumed_verifiers: ConsumedVerifiers,
     fee_rate: u64,
 }
@@ -68,13 +68,13 @@ impl Cleaner {
     pub fn new(
         rpc_client: RPCClient,
         ckb_genesis_info: CKBGenesisInfo,
-        wallet: Wallet,
+        signer: Arc<dyn Signer>,
         fee_rate: u64,
     ) -> Self {
         Cleaner {
             rpc_client,
             ckb_genesis_info,
-            wallet,
+            signer,
             consumed_verifiers: Arc::new(Mutex::new(Vec::new())),
             fee_rate,
         }
@@ -222,7 +222,7 @@ impl Cleaner {
         }
 
         // Verifier cell need an owner cell to unlock
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
         let rpc_client = &self.rpc_client;
         let owner_input = {
             let query = rpc_client.query_owner_cell(owner_lock, None).await?;
@@ -235,7 +235,7 @@ impl Cleaner {
             .inputs_mut()
             .push(to_input_cell_info(owner_input));
 
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
         fill_tx_fee(
             &mut tx_skeleton,
             &self.rpc_client.indexer,
@@ -243,7 +243,7 @@ impl Cleaner {
             self.fee_rate,
         )
         .await?;
-        self.wallet.sign_tx_skeleton(tx_skeleton)
+        self.signer.sign_tx_skeleton(tx_skeleton).await
     }
 }
 
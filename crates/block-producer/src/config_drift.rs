@@ -0,0 +1,708 @@
+//! Startup validation of the config against the on-chain rollup state
+//!
+//! The rollup config hash is compared with the rollup cell global state, the rollup config is
+//! compared field by field with the rollup config cell found in the cell deps of the transaction
+//! which created the rollup cell. Contract scripts are compared with the rollup config and their
+//! cell deps are checked, with or without a block producer config. Backends of the latest
+//! switch, either in the config or registered by backend upgrades, must be allowed contracts.
+
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use ckb_fixed_hash::H256;
+use gw_config::{BackendSwitchConfig, Config, ConfigDriftAction, ContractTypeScriptConfig};
+use gw_generator::backend_manage::load_registered_backend_switches;
+use gw_jsonrpc_types::{blockchain::Script, godwoken};
+use gw_rpc_client::{contract::ContractsCellDepManager, rpc_client::RPCClient};
+use gw_types::{
+    core::DepType,
+    offchain::{global_state_from_slice, CellInfo, CellStatus},
+    packed::{Byte32, RollupConfig},
+    prelude::*,
+};
+use serde_json::Value;
+
+const PREFIX: &str = "consensus.contract_type_scripts";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDrift {
+    /// Path of the drifted item in the config
+    pub path: String,
+    pub config: String,
+    pub on_chain: String,
+}
+
+impl ConfigDrift {
+    fn new(
+        path: impl Into<String>,
+        config: impl fmt::Display,
+        on_chain: impl fmt::Display,
+    ) -> Self {
+        ConfigDrift {
+            path: path.into(),
+            config: config.to_string(),
+            on_chain: on_chain.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: config {}, on-chain {}",
+            self.path, self.config, self.on_chain
+        )
+    }
+}
+
+/// Refuse to start or warn if the config drifts from the on-chain rollup state
+pub async fn check_config_drift(
+    config: &Config,
+    rpc_client: &RPCClient,
+    contracts_dep_manager: Option<&ContractsCellDepManager>,
+) -> Result<()> {
+    let drifts = collect_config_drifts(config, rpc_client, contracts_dep_manager).await?;
+    if drifts.is_empty() {
+        return Ok(());
+    }
+
+    for drift in drifts.iter() {
+        log::warn!("[config drift] {}", drift);
+    }
+    match config.config_drift {
+        ConfigDriftAction::Warn => Ok(()),
+        ConfigDriftAction::Refuse => bail!(
+            "{} config drifts against the on-chain rollup state, \
+            set `config_drift = \"warn\"` to start anyway",
+            drifts.len()
+        ),
+    }
+}
+
+pub async fn collect_config_drifts(
+    config: &Config,
+    rpc_client: &RPCClient,
+    contracts_dep_manager: Option<&ContractsCellDepManager>,
+) -> Result<Vec<ConfigDrift>> {
+    let mut drifts = Vec::new();
+    let rollup_config: RollupConfig = config.genesis.rollup_config.clone().into();
+
+    let rollup_cell = rpc_client
+        .query_rollup_cell()
+        .await?
+        .ok_or_else(|| anyhow!("can't find rollup cell"))?;
+    let global_state = global_state_from_slice(&rollup_cell.data)
+        .map_err(|_| anyhow!("global state unpacking error"))?;
+    let rollup_config_hash = to_h256(&global_state.rollup_config_hash());
+    if H256(rollup_config.hash()) != rollup_config_hash {
+        drifts.push(ConfigDrift::new(
+            "genesis.rollup_config hash",
+            hex(&H256(rollup_config.hash())),
+            hex(&rollup_config_hash),
+        ));
+    }
+
+    if let Some(ref block_producer_config) = config.block_producer {
+        let cell_dep = &block_producer_config.rollup_config_cell_dep;
+        let rollup_config_cell = rpc_client
+            .get_cell(cell_dep.out_point.clone().into())
+            .await?
+            .and_then(|cell_with_status| cell_with_status.cell)
+            .ok_or_else(|| anyhow!("can't find rollup config cell"))?;
+        let cell_dep_config = RollupConfig::from_slice(&rollup_config_cell.data)?;
+        if H256(cell_dep_config.hash()) != rollup_config_hash {
+            drifts.push(ConfigDrift::new(
+                "block_producer.rollup_config_cell_dep hash",
+                hex(&H256(cell_dep_config.hash())),
+                hex(&rollup_config_hash),
+            ));
+        }
+    }
+
+    // Without the rollup config cell, only the hash is comparable
+    let on_chain_config = match query_rollup_config(rpc_client, &rollup_cell, &rollup_config_hash)
+        .await?
+    {
+        Some(on_chain_config) => {
+            diff_rollup_config(&rollup_config, &on_chain_config, &mut drifts)?;
+            on_chain_config
+        }
+        None => {
+            log::warn!("[config drift] can't find the rollup config cell, compare its hash only");
+            rollup_config.clone()
+        }
+    };
+
+    let rollup_type_code_hash = match rollup_cell.output.type_().to_opt() {
+        Some(type_) => to_h256(&type_.code_hash()),
+        None => bail!("rollup cell without type script"),
+    };
+    let scripts = &config.consensus.contract_type_scripts;
+    diff_contract_scripts(
+        scripts,
+        &on_chain_config,
+        &rollup_type_code_hash,
+        &mut drifts,
+    );
+
+    // Cell deps are queried by the contract type scripts when not given
+    let queried_dep_manager;
+    let contracts_dep_manager = match contracts_dep_manager {
+        Some(contracts_dep_manager) => Some(contracts_dep_manager),
+        None => match ContractsCellDepManager::build(rpc_client.clone(), scripts.clone()).await {
+            Ok(contracts_dep_manager) => {
+                queried_dep_manager = contracts_dep_manager;
+                Some(&queried_dep_manager)
+            }
+            Err(err) => {
+                drifts.push(ConfigDrift::new(
+                    "consensus.contract_type_scripts cell deps",
+                    "contract cells",
+                    format!("{:#}", err),
+                ));
+                None
+            }
+        },
+    };
+    if let Some(contracts_dep_manager) = contracts_dep_manager {
+        diff_contract_cell_deps(contracts_dep_manager, rpc_client, &mut drifts).await?;
+    }
+
+    let registered_switches = match config.backend_upgrade {
+        Some(ref backend_upgrade) => {
+            load_registered_backend_switches(&backend_upgrade.registered_switches_path)?
+        }
+        None => Vec::new(),
+    };
+    diff_backends(
+        &config.backend_switches,
+        &registered_switches,
+        &on_chain_config,
+        &mut drifts,
+    );
+
+    Ok(drifts)
+}
+
+/// The rollup config cell is a cell dep of the transaction which created the rollup cell
+async fn query_rollup_config(
+    rpc_client: &RPCClient,
+    rollup_cell: &CellInfo,
+    rollup_config_hash: &H256,
+) -> Result<Option<RollupConfig>> {
+    let tx_hash = rollup_cell.out_point.tx_hash().unpack();
+    let tx = match rpc_client.ckb.get_transaction(tx_hash).await? {
+        Some(tx) => tx,
+        None => return Ok(None),
+    };
+    for dep in tx.raw().cell_deps() {
+        if dep.dep_type() != DepType::Code.into() {
+            continue;
+        }
+        let cell_with_status = rpc_client.get_cell(dep.out_point()).await?;
+        let cell = match cell_with_status.and_then(|cell_with_status| cell_with_status.cell) {
+            Some(cell) => cell,
+            None => continue,
+        };
+        if let Ok(rollup_config) = RollupConfig::from_slice(&cell.data) {
+            if &H256(rollup_config.hash()) == rollup_config_hash {
+                return Ok(Some(rollup_config));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Backends of the latest switch must be allowed contracts, older switches only replay history
+fn diff_backends(
+    backend_switches: &[BackendSwitchConfig],
+    registered_switches: &[BackendSwitchConfig],
+    on_chain_config: &RollupConfig,
+    drifts: &mut Vec<ConfigDrift>,
+) {
+    let latest = backend_switches
+        .iter()
+        .map(|switch| ("backend_switches", switch))
+        .chain(
+            registered_switches
+                .iter()
+                .map(|switch| ("backend_upgrade.registered_switches", switch)),
+        )
+        .max_by_key(|(_, switch)| switch.switch_height);
+    let (path, switch) = match latest {
+        Some(latest) => latest,
+        None => return,
+    };
+    let allowed_contracts: Vec<_> = {
+        let type_hashes = on_chain_config.allowed_contract_type_hashes();
+        type_hashes
+            .into_iter()
+            .map(|th| to_h256(&th.hash()))
+            .collect()
+    };
+    for (index, backend) in switch.backends.iter().enumerate() {
+        let type_hash = &backend.validator_script_type_hash;
+        if !allowed_contracts.contains(type_hash) {
+            drifts.push(ConfigDrift::new(
+                format!(
+                    "{}[height {}].backends[{}].validator_script_type_hash",
+                    path, switch.switch_height, index
+                ),
+                hex(type_hash),
+                "not in allowed_contract_type_hashes",
+            ));
+        }
+    }
+}
+
+fn diff_rollup_config(
+    config: &RollupConfig,
+    on_chain: &RollupConfig,
+    drifts: &mut Vec<ConfigDrift>,
+) -> Result<()> {
+    let to_json_fields = |rollup_config: &RollupConfig| -> Result<_> {
+        let json = godwoken::RollupConfig::from(rollup_config.clone());
+        match serde_json::to_value(json)? {
+            Value::Object(fields) => Ok(fields),
+            _ => bail!("rollup config isn't a json object"),
+        }
+    };
+    let config = to_json_fields(config)?;
+    let on_chain = to_json_fields(on_chain)?;
+
+    for (field, value) in config.iter() {
+        let on_chain_value = on_chain.get(field).unwrap_or(&Value::Null);
+        if value == on_chain_value {
+            continue;
+        }
+
+        let path = format!("genesis.rollup_config.{}", field);
+        match (value, on_chain_value) {
+            (Value::Array(items), Value::Array(on_chain_items)) => {
+                let only_in = |a: &Vec<Value>, b: &Vec<Value>| -> Vec<Value> {
+                    a.iter().filter(|item| !b.contains(item)).cloned().collect()
+                };
+                let config_only = only_in(items, on_chain_items);
+                let on_chain_only = only_in(on_chain_items, items);
+                if config_only.is_empty() && on_chain_only.is_empty() {
+                    drifts.push(ConfigDrift::new(path + " order", value, on_chain_value));
+                } else {
+                    drifts.push(ConfigDrift::new(
+                        path + " items",
+                        Value::Array(config_only),
+                        Value::Array(on_chain_only),
+                    ));
+                }
+            }
+            _ => drifts.push(ConfigDrift::new(path, value, on_chain_value)),
+        }
+    }
+
+    Ok(())
+}
+
+fn diff_contract_scripts(
+    scripts: &ContractTypeScriptConfig,
+    on_chain_config: &RollupConfig,
+    rollup_type_code_hash: &H256,
+    drifts: &mut Vec<ConfigDrift>,
+) {
+    let type_hashes = [
+        (
+            "state_validator",
+            &scripts.state_validator,
+            rollup_type_code_hash.to_owned(),
+        ),
+        (
+            "deposit_lock",
+            &scripts.deposit_lock,
+            to_h256(&on_chain_config.deposit_script_type_hash()),
+        ),
+        (
+            "stake_lock",
+            &scripts.stake_lock,
+            to_h256(&on_chain_config.stake_script_type_hash()),
+        ),
+        (
+            "custodian_lock",
+            &scripts.custodian_lock,
+            to_h256(&on_chain_config.custodian_script_type_hash()),
+        ),
+        (
+            "withdrawal_lock",
+            &scripts.withdrawal_lock,
+            to_h256(&on_chain_config.withdrawal_script_type_hash()),
+        ),
+        (
+            "challenge_lock",
+            &scripts.challenge_lock,
+            to_h256(&on_chain_config.challenge_script_type_hash()),
+        ),
+        (
+            "l1_sudt",
+            &scripts.l1_sudt,
+            to_h256(&on_chain_config.l1_sudt_script_type_hash()),
+        ),
+    ];
+    for (name, script, on_chain_hash) in type_hashes.iter() {
+        if &script.hash() != on_chain_hash {
+            let path = format!("{}.{} hash", PREFIX, name);
+            drifts.push(ConfigDrift::new(
+                path,
+                hex(&script.hash()),
+                hex(on_chain_hash),
+            ));
+        }
+    }
+
+    let allowed_eoas: Vec<_> = {
+        let type_hashes = on_chain_config.allowed_eoa_type_hashes();
+        type_hashes
+            .into_iter()
+            .map(|th| to_h256(&th.hash()))
+            .collect()
+    };
+    for (hash, script) in scripts.allowed_eoa_scripts.iter() {
+        let path = format!("{}.allowed_eoa_scripts.{}", PREFIX, hex(hash));
+        if &script.hash() != hash {
+            drifts.push(ConfigDrift::new(
+                path + " hash",
+                hex(&script.hash()),
+                hex(hash),
+            ));
+        } else if !allowed_eoas.contains(hash) {
+            drifts.push(ConfigDrift::new(path, "allowed", "not allowed"));
+        }
+    }
+    let allowed_contracts: Vec<_> = {
+        let type_hashes = on_chain_config.allowed_contract_type_hashes();
+        type_hashes
+            .into_iter()
+            .map(|th| to_h256(&th.hash()))
+            .collect()
+    };
+    for (hash, script) in scripts.allowed_contract_scripts.iter() {
+        let path = format!("{}.allowed_contract_scripts.{}", PREFIX, hex(hash));
+        if &script.hash() != hash {
+            drifts.push(ConfigDrift::new(
+                path + " hash",
+                hex(&script.hash()),
+                hex(hash),
+            ));
+        } else if !allowed_contracts.contains(hash) {
+            drifts.push(ConfigDrift::new(path, "allowed", "not allowed"));
+        }
+    }
+}
+
+async fn diff_contract_cell_deps(
+    contracts_dep_manager: &ContractsCellDepManager,
+    rpc_client: &RPCClient,
+    drifts: &mut Vec<ConfigDrift>,
+) -> Result<()> {
+    let scripts = contracts_dep_manager.load_scripts();
+
+    // Cell deps are queried by type scripts, they drift if the contract cells are consumed since
+    let deps = contracts_dep_manager.load();
+    let mut cell_deps = vec![
+        (
+            "state_validator".to_string(),
+            &scripts.state_validator,
+            &deps.rollup_cell_type,
+        ),
+        (
+            "deposit_lock".to_string(),
+            &scripts.deposit_lock,
+            &deps.deposit_cell_lock,
+        ),
+        (
+            "stake_lock".to_string(),
+            &scripts.stake_lock,
+            &deps.stake_cell_lock,
+        ),
+        (
+            "custodian_lock".to_string(),
+            &scripts.custodian_lock,
+            &deps.custodian_cell_lock,
+        ),
+        (
+            "withdrawal_lock".to_string(),
+            &scripts.withdrawal_lock,
+            &deps.withdrawal_cell_lock,
+        ),
+        (
+            "challenge_lock".to_string(),
+            &scripts.challenge_lock,
+            &deps.challenge_cell_lock,
+        ),
+        ("l1_sudt".to_string(), &scripts.l1_sudt, &deps.l1_sudt_type),
+        ("omni_lock".to_string(), &scripts.omni_lock, &deps.omni_lock),
+    ];
+    for (hash, script) in scripts.allowed_eoa_scripts.iter() {
+        if let Some(dep) = deps.allowed_eoa_locks.get(hash) {
+            let name = format!("allowed_eoa_scripts.{}", hex(hash));
+            cell_deps.push((name, script, dep));
+        }
+    }
+    for (hash, script) in scripts.allowed_contract_scripts.iter() {
+        if let Some(dep) = deps.allowed_contract_types.get(hash) {
+            let name = format!("allowed_contract_scripts.{}", hex(hash));
+            cell_deps.push((name, script, dep));
+        }
+    }
+    for (name, script, dep) in cell_deps {
+        let cell_with_status = rpc_client.get_cell(dep.out_point.clone().into()).await?;
+        let on_chain = match cell_with_status {
+            Some(cell_with_status) if cell_with_status.status != CellStatus::Live => {
+                format!("{:?} cell", cell_with_status.status)
+            }
+            Some(cell_with_status) => match cell_with_status.cell {
+                Some(cell) => match cell.output.type_().to_opt() {
+                    Some(type_) if H256(type_.hash()) == script.hash() => continue,
+                    Some(type_) => format!("type hash {}", hex(&H256(type_.hash()))),
+                    None => "cell without type script".to_string(),
+                },
+                None => "unknown cell".to_string(),
+            },
+            None => "unknown cell".to_string(),
+        };
+        let path = format!("{}.{} cell dep", PREFIX, name);
+        drifts.push(ConfigDrift::new(
+            path,
+            display_script_hash(script),
+            on_chain,
+        ));
+    }
+
+    Ok(())
+}
+
+fn display_script_hash(script: &Script) -> String {
+    format!("type hash {}", hex(&script.hash()))
+}
+
+fn hex(hash: &H256) -> String {
+    format!("0x{:x}", hash)
+}
+
+fn to_h256(byte32: &Byte32) -> H256 {
+    H256(byte32.unpack())
+}
+
+#[cfg(test)]
+mod tests {
+    use ckb_fixed_hash::H256;
+    use gw_config::{BackendConfig, BackendSwitchConfig, ContractTypeScriptConfig};
+    use gw_jsonrpc_types::{
+        blockchain::{Script, ScriptHashType},
+        ckb_jsonrpc_types::JsonBytes,
+    };
+    use gw_types::core::{AllowedContractType, AllowedEoaType};
+    use gw_types::packed::{AllowedTypeHash, RollupConfig};
+    use gw_types::prelude::*;
+
+    use super::{diff_backends, diff_contract_scripts, diff_rollup_config, ConfigDrift};
+
+    fn script(args: u8) -> Script {
+        Script {
+            code_hash: H256::default(),
+            hash_type: ScriptHashType::Type,
+            args: JsonBytes::from_vec(vec![args]),
+        }
+    }
+
+    fn sorted(mut drifts: Vec<ConfigDrift>) -> Vec<ConfigDrift> {
+        drifts.sort_by(|a, b| a.path.cmp(&b.path));
+        drifts
+    }
+
+    #[test]
+    fn test_diff_rollup_config() {
+        let eth = AllowedTypeHash::new(AllowedEoaType::Eth, [1u8; 32]);
+        let multisig = AllowedTypeHash::new(AllowedEoaType::Multisig, [2u8; 32]);
+        let config = RollupConfig::new_builder()
+            .finality_blocks(100u64.pack())
+            .allowed_eoa_type_hashes(vec![eth.clone()].pack())
+            .build();
+        let on_chain = config
+            .clone()
+            .as_builder()
+            .finality_blocks(64u64.pack())
+            .allowed_eoa_type_hashes(vec![eth, multisig].pack())
+            .build();
+
+        let mut drifts = Vec::new();
+        diff_rollup_config(&config, &config, &mut drifts).unwrap();
+        assert!(drifts.is_empty());
+
+        diff_rollup_config(&config, &on_chain, &mut drifts).unwrap();
+        drifts.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(drifts.len(), 2);
+
+        let eoa_drift = &drifts[0];
+        assert_eq!(
+            eoa_drift.path,
+            "genesis.rollup_config.allowed_eoa_type_hashes items"
+        );
+        assert_eq!(eoa_drift.config, "[]");
+        assert!(eoa_drift.on_chain.contains(&"02".repeat(32)));
+
+        let finality_drift = &drifts[1];
+        assert_eq!(finality_drift.path, "genesis.rollup_config.finality_blocks");
+        assert_eq!(finality_drift.config, "\"0x64\"");
+        assert_eq!(finality_drift.on_chain, "\"0x40\"");
+    }
+
+    #[test]
+    fn test_diff_contract_scripts() {
+        // Scripts of the rollup contracts, the rollup config records their type hashes
+        let contract = script(0);
+        let contract_hash = contract.hash().0.pack();
+        let (eth, multisig, not_allowed_eoa) = (script(1), script(2), script(3));
+        let (polyjuice, not_allowed_contract) = (script(4), script(5));
+        let mut scripts = ContractTypeScriptConfig {
+            state_validator: contract.clone(),
+            deposit_lock: contract.clone(),
+            stake_lock: contract.clone(),
+            custodian_lock: contract.clone(),
+            withdrawal_lock: contract.clone(),
+            challenge_lock: contract.clone(),
+            l1_sudt: contract.clone(),
+            omni_lock: contract.clone(),
+            ..Default::default()
+        };
+        for eoa in [&eth, &multisig, &not_allowed_eoa] {
+            scripts.allowed_eoa_scripts.insert(eoa.hash(), eoa.clone());
+        }
+        for contract in [&polyjuice, &not_allowed_contract] {
+            let hash = contract.hash();
+            scripts
+                .allowed_contract_scripts
+                .insert(hash, contract.clone());
+        }
+        let on_chain = RollupConfig::new_builder()
+            .deposit_script_type_hash(contract_hash.clone())
+            .stake_script_type_hash(contract_hash.clone())
+            .custodian_script_type_hash(contract_hash.clone())
+            .withdrawal_script_type_hash(contract_hash.clone())
+            .challenge_script_type_hash(contract_hash.clone())
+            .l1_sudt_script_type_hash(contract_hash)
+            .allowed_eoa_type_hashes(
+                vec![
+                    AllowedTypeHash::new(AllowedEoaType::Eth, eth.hash()),
+                    AllowedTypeHash::new(AllowedEoaType::Multisig, multisig.hash()),
+                ]
+                .pack(),
+            )
+            .allowed_contract_type_hashes(
+                vec![AllowedTypeHash::new(
+                    AllowedContractType::Polyjuice,
+                    polyjuice.hash(),
+                )]
+                .pack(),
+            )
+            .build();
+
+        let mut drifts = Vec::new();
+        diff_contract_scripts(&scripts, &on_chain, &contract.hash(), &mut drifts);
+        let drifts = sorted(drifts);
+        assert_eq!(drifts.len(), 2, "{:?}", drifts);
+        let prefix = "consensus.contract_type_scripts";
+        assert_eq!(
+            drifts[0].path,
+            format!(
+                "{}.allowed_contract_scripts.0x{:x}",
+                prefix,
+                not_allowed_contract.hash()
+            )
+        );
+        assert_eq!(drifts[0].on_chain, "not allowed");
+        assert_eq!(
+            drifts[1].path,
+            format!(
+                "{}.allowed_eoa_scripts.0x{:x}",
+                prefix,
+                not_allowed_eoa.hash()
+            )
+        );
+        assert_eq!(drifts[1].on_chain, "not allowed");
+
+        // The key doesn't match the script, state validator isn't the rollup type script
+        scripts
+            .allowed_eoa_scripts
+            .insert(not_allowed_eoa.hash(), eth.clone());
+        scripts.allowed_contract_scripts.clear();
+        let mut drifts = Vec::new();
+        diff_contract_scripts(&scripts, &on_chain, &H256([9u8; 32]), &mut drifts);
+        let drifts = sorted(drifts);
+        assert_eq!(drifts.len(), 2, "{:?}", drifts);
+        assert_eq!(
+            drifts[0].path,
+            format!(
+                "{}.allowed_eoa_scripts.0x{:x} hash",
+                prefix,
+                not_allowed_eoa.hash()
+            )
+        );
+        assert_eq!(drifts[0].config, format!("0x{:x}", eth.hash()));
+        assert_eq!(drifts[1].path, format!("{}.state_validator hash", prefix));
+        assert_eq!(drifts[1].on_chain, format!("0x{:x}", H256([9u8; 32])));
+    }
+
+    #[test]
+    fn test_diff_backends() {
+        let backend = |type_hash: [u8; 32]| BackendConfig {
+            validator_script_type_hash: H256(type_hash),
+            ..Default::default()
+        };
+        let switches = vec![
+            // Older switches only replay history
+            BackendSwitchConfig {
+                switch_height: 0,
+                backends: vec![backend([3u8; 32])],
+            },
+            BackendSwitchConfig {
+                switch_height: 100,
+                backends: vec![backend([1u8; 32]), backend([2u8; 32])],
+            },
+        ];
+        let on_chain = RollupConfig::new_builder()
+            .allowed_contract_type_hashes(
+                vec![AllowedTypeHash::new(
+                    AllowedContractType::Polyjuice,
+                    [1u8; 32],
+                )]
+                .pack(),
+            )
+            .build();
+
+        let mut drifts = Vec::new();
+        diff_backends(&switches, &[], &on_chain, &mut drifts);
+        assert_eq!(drifts.len(), 1, "{:?}", drifts);
+        assert_eq!(
+            drifts[0].path,
+            "backend_switches[height 100].backends[1].validator_script_type_hash"
+        );
+        assert_eq!(drifts[0].config, format!("0x{}", "02".repeat(32)));
+
+        let mut drifts = Vec::new();
+        diff_backends(&switches[..1], &[], &on_chain, &mut drifts);
+        assert_eq!(drifts.len(), 1, "{:?}", drifts);
+        diff_backends(&[], &[], &on_chain, &mut drifts);
+        assert_eq!(drifts.len(), 1, "{:?}", drifts);
+
+        // A switch registered by backend upgrades replaces the config ones
+        let registered = vec![BackendSwitchConfig {
+            switch_height: 200,
+            backends: vec![backend([1u8; 32]), backend([4u8; 32])],
+        }];
+        let mut drifts = Vec::new();
+        diff_backends(&switches, &registered, &on_chain, &mut drifts);
+        assert_eq!(drifts.len(), 1, "{:?}", drifts);
+        assert_eq!(
+            drifts[0].path,
+            "backend_upgrade.registered_switches[height 200].backends[1].validator_script_type_hash"
+        );
+    }
+}
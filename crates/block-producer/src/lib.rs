@@ -1,8 +1,10 @@
+pub mod backend_upgrade;
 pub mod block_producer;
 pub mod block_sync_client;
 pub mod chain_updater;
 pub mod challenger;
 pub mod cleaner;
+pub mod config_drift;
 pub mod custodian;
 pub mod debugger;
 pub mod deposit;
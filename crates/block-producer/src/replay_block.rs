@@ -108,11 +108,11 @@ impl ReplayBlock {
 
             // build call context
             // NOTICE users only allowed to send HandleMessage CallType txs
-            generator.execute_transaction(
+            generator.execute_l2transaction(
                 &chain_view,
                 &mut state,
                 &block_info,
-                &raw_tx,
+                &tx,
                 L2TX_MAX_CYCLES,
                 None,
             )?;
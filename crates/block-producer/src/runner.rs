@@ -1,9 +1,11 @@
 use crate::{
+    backend_upgrade::BackendUpgrader,
     block_producer::{BlockProducer, BlockProducerCreateArgs},
     block_sync_client::{block_sync_client_protocol, BlockSyncClient, P2PStream},
     chain_updater::ChainUpdater,
     challenger::{Challenger, ChallengerNewArgs},
     cleaner::Cleaner,
+    config_drift::check_config_drift,
     psc::{PSCContext, ProduceSubmitConfirm},
     test_mode_control::TestModeControl,
     types::ChainEvent,
@@ -16,11 +18,12 @@ use gw_chain::chain::Chain;
 use gw_challenge::offchain::{OffChainMockContext, OffChainMockContextBuildArgs};
 use gw_ckb_hardfork::{GLOBAL_CURRENT_EPOCH_NUMBER, GLOBAL_HARDFORK_SWITCH, GLOBAL_VM_VERSION};
 use gw_common::{blake2b::new_blake2b, registry_address::RegistryAddress, H256};
-use gw_config::{BlockProducerConfig, Config, NodeMode};
-use gw_db::migrate::open_or_create_db;
+use gw_config::{BlockProducerConfig, Config, NodeMode, StoreEngine};
 use gw_dynamic_config::manager::DynamicConfigManager;
 use gw_generator::{
-    account_lock_manage::{secp256k1::Secp256k1Eth, AccountLockManage},
+    account_lock_manage::{
+        multisig::Secp256k1Multisig, secp256k1::Secp256k1Eth, webauthn::WebAuthn, AccountLockManage,
+    },
     backend_manage::BackendManage,
     genesis::init_genesis,
     ArcSwap, Generator,
@@ -37,7 +40,7 @@ use gw_rpc_client::{
     indexer_client::CKBIndexerClient, rpc_client::RPCClient,
 };
 use gw_rpc_server::{
-    registry::{Registry, RegistryArgs},
+    registry::{BackendUpgradeRPC, Registry, RegistryArgs},
     server::start_jsonrpc_server,
 };
 use gw_store::Store;
@@ -50,7 +53,8 @@ use gw_types::{
 };
 use gw_utils::{
     exponential_backoff::ExponentialBackoff, genesis_info::CKBGenesisInfo, liveness::Liveness,
-    local_cells::LocalCellsManager, since::EpochNumberWithFraction, wallet::Wallet,
+    local_cells::LocalCellsManager, signer::build_signer, since::EpochNumberWithFraction,
+    wallet::Wallet,
 };
 use semver::Version;
 use std::{
@@ -73,6 +77,7 @@ struct ChainTaskContext {
     challenger: Option<Challenger>,
     withdrawal_unlocker: Option<FinalizedWithdrawalUnlocker>,
     cleaner: Option<Arc<Cleaner>>,
+    backend_upgrader: Option<Arc<BackendUpgrader>>,
 }
 
 struct ChainTaskRunStatus {
@@ -202,6 +207,12 @@ impl ChainTask {
                 }
             }
 
+            if let Some(ref backend_upgrader) = ctx.backend_upgrader {
+                if let Err(err) = backend_upgrader.sync_switches().await {
+                    log::error!("[backend upgrade] {:#}", err);
+                }
+            }
+
             // update global hardfork info
             let hardfork_switch = self.rpc_client.get_hardfork_switch().await?;
             let rfc0032_epoch_number = hardfork_switch.rfc_0032();
@@ -330,25 +341,30 @@ impl BaseInitComponents {
         if !skip_config_check {
             check_ckb_version(&rpc_client).await?;
             // TODO: check ckb indexer version
+            check_config_drift(config, &rpc_client, contracts_dep_manager.as_ref()).await?;
             if NodeMode::ReadOnly != config.node_mode {
                 let block_producer_config =
                     opt_block_producer_config.ok_or_else(|| anyhow!("not set block producer"))?;
-                check_rollup_config_cell(block_producer_config, &rollup_config, &rpc_client)
-                    .await?;
                 check_locks(block_producer_config, &rollup_config)?;
             }
         }
 
         // Open store
         let timer = Instant::now();
-        let store = if config.store.path.as_os_str().is_empty() {
+        let store = if config.store.path.as_os_str().is_empty()
+            && config.store.engine != StoreEngine::Memory
+        {
             log::warn!("config.store.path is blank, using temporary store");
             Store::open_tmp().with_context(|| "init store")?
         } else {
-            Store::new(open_or_create_db(&config.store)?)
+            Store::open(&config.store)?
         };
         let elapsed_ms = timer.elapsed().as_millis();
-        log::debug!("Open rocksdb costs: {}ms.", elapsed_ms);
+        log::debug!(
+            "Open {:?} store costs: {}ms.",
+            config.store.engine,
+            elapsed_ms
+        );
 
         let secp_data: Bytes = {
             let out_point = config.genesis.secp_data_dep.out_point.clone();
@@ -384,7 +400,13 @@ impl BaseInitComponents {
         let rollup_config_hash: H256 = rollup_config.hash().into();
         let generator = {
             let backend_manage = BackendManage::from_config(config.backend_switches.clone())
-                .with_context(|| "config backends")?;
+                .with_context(|| "config backends")?
+                .with_aot_cache_dir(config.aot_cache_dir.clone());
+            if let Some(ref backend_upgrade) = config.backend_upgrade {
+                backend_manage
+                    .register_saved_backend_switches(&backend_upgrade.registered_switches_path)
+                    .with_context(|| "saved backend switches")?;
+            }
             let mut account_lock_manage = AccountLockManage::default();
             let allowed_eoa_type_hashes = rollup_config.as_reader().allowed_eoa_type_hashes();
             let eth_lock_script_type_hash = allowed_eoa_type_hashes
@@ -395,6 +417,24 @@ impl BaseInitComponents {
                 eth_lock_script_type_hash.hash().unpack(),
                 Box::new(Secp256k1Eth::default()),
             );
+            if let Some(multisig_lock_script_type_hash) = allowed_eoa_type_hashes
+                .iter()
+                .find(|th| th.type_().to_entity() == AllowedEoaType::Multisig.into())
+            {
+                account_lock_manage.register_lock_algorithm(
+                    multisig_lock_script_type_hash.hash().unpack(),
+                    Box::new(Secp256k1Multisig::default()),
+                );
+            }
+            if let Some(webauthn_lock_script_type_hash) = allowed_eoa_type_hashes
+                .iter()
+                .find(|th| th.type_().to_entity() == AllowedEoaType::WebAuthn.into())
+            {
+                account_lock_manage.register_lock_algorithm(
+                    webauthn_lock_script_type_hash.hash().unpack(),
+                    Box::new(WebAuthn::default()),
+                );
+            }
             Arc::new(Generator::new(
                 backend_manage,
                 account_lock_manage,
@@ -446,6 +486,11 @@ impl BaseInitComponents {
         block_producer_config: &BlockProducerConfig,
     ) -> Result<Option<OffChainMockContext>> {
         let wallet = match block_producer_config.wallet_config {
+            // Mock txs are only verified offchain, sign them by a throwaway key
+            Some(ref c) if c.remote_signer.is_some() => {
+                Wallet::random_secp256k1(c.lock.clone().into())
+                    .with_context(|| "offchain init wallet")?
+            }
             Some(ref c) => Wallet::from_config(c).with_context(|| "offchain init wallet")?,
             None => return Ok(None),
         };
@@ -502,12 +547,12 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         None
     };
 
-    let (mem_pool, wallet, offchain_mock_context) = match config.block_producer.as_ref() {
+    let (mem_pool, signer, offchain_mock_context) = match config.block_producer.as_ref() {
         Some(block_producer_config) => {
-            let opt_wallet = block_producer_config
+            let opt_signer = block_producer_config
                 .wallet_config
                 .as_ref()
-                .map(|c| Wallet::from_config(c).with_context(|| "init block producer wallet"))
+                .map(|c| build_signer(c).with_context(|| "init block producer signer"))
                 .transpose()?;
             let opt_offchain_mock_context = base
                 .init_offchain_mock_context(block_producer_config)
@@ -542,7 +587,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
                         .with_context(|| "create mem-pool")?,
                 ))
             };
-            (Some(mem_pool), opt_wallet, opt_offchain_mock_context)
+            (Some(mem_pool), opt_signer, opt_offchain_mock_context)
         }
         None => (None, None, None),
     };
@@ -584,114 +629,125 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         .with_context(|| "create chain")?,
     ));
 
+    // Backend upgrade, switches announced on L1 are registered before syncing blocks
+    let backend_upgrader = match config.backend_upgrade {
+        Some(ref backend_upgrade) => {
+            let upgrader = BackendUpgrader::create(
+                backend_upgrade.clone(),
+                rpc_client.clone(),
+                generator.clone(),
+                store.clone(),
+            )
+            .with_context(|| "create backend upgrader")?;
+            upgrader
+                .sync_switches()
+                .await
+                .with_context(|| "sync backend switches")?;
+            Some(Arc::new(upgrader))
+        }
+        None => None,
+    };
+
     // create chain updater
     let chain_updater = ChainUpdater::new(
         Arc::clone(&chain),
         rpc_client.clone(),
         rollup_context.clone(),
         rollup_type_script.clone(),
+        backend_upgrader.clone(),
     );
 
-    let (block_producer, challenger, test_mode_control, withdrawal_unlocker, cleaner) = match config
-        .node_mode
-    {
-        NodeMode::ReadOnly => (None, None, None, None, None),
-        mode => {
-            let block_producer_config = config
-                .block_producer
-                .clone()
-                .ok_or_else(|| anyhow!("must provide block producer config in mode: {:?}", mode))?;
-            let contracts_dep_manager =
-                contracts_dep_manager.ok_or_else(|| anyhow!("must build contracts dep"))?;
-            let wallet =
-                wallet.ok_or_else(|| anyhow!("wallet must be enabled in mode: {:?}", mode))?;
-            let offchain_mock_context = {
-                let ctx = offchain_mock_context;
-                let msg = "offchain mock require block producer config and wallet in mode: ";
-                ctx.ok_or_else(|| anyhow!("{} {:?}", msg, mode))?
-            };
-            let tests_control = if let NodeMode::Test = config.node_mode {
-                Some(TestModeControl::new(rpc_client.clone(), store.clone()))
-            } else {
-                None
-            };
-
-            let unlocker_wallet = match block_producer_config.withdrawal_unlocker_wallet_config {
-                Some(ref wallet_config) => {
-                    Wallet::from_config(wallet_config).with_context(|| "unlocker wallet")?
-                }
-                None => {
-                    log::info!("[unlock withdrawal] reuse block producer wallet");
+    let (block_producer, challenger, test_mode_control, withdrawal_unlocker, cleaner) =
+        match config.node_mode {
+            NodeMode::ReadOnly => (None, None, None, None, None),
+            mode => {
+                let block_producer_config = config.block_producer.clone().ok_or_else(|| {
+                    anyhow!("must provide block producer config in mode: {:?}", mode)
+                })?;
+                let contracts_dep_manager =
+                    contracts_dep_manager.ok_or_else(|| anyhow!("must build contracts dep"))?;
+                let signer =
+                    signer.ok_or_else(|| anyhow!("wallet must be enabled in mode: {:?}", mode))?;
+                let offchain_mock_context = {
+                    let ctx = offchain_mock_context;
+                    let msg = "offchain mock require block producer config and wallet in mode: ";
+                    ctx.ok_or_else(|| anyhow!("{} {:?}", msg, mode))?
+                };
+                let tests_control = if let NodeMode::Test = config.node_mode {
+                    Some(TestModeControl::new(rpc_client.clone(), store.clone()))
+                } else {
+                    None
+                };
 
-                    match block_producer_config.wallet_config {
-                        Some(ref c) => Wallet::from_config(c).with_context(|| "unlocker wallet")?,
-                        None => bail!("no wallet config for withdrawal unlocker"),
+                let unlocker_signer = match block_producer_config.withdrawal_unlocker_wallet_config
+                {
+                    Some(ref wallet_config) => {
+                        build_signer(wallet_config).with_context(|| "unlocker signer")?
                     }
-                }
-            };
+                    None => {
+                        log::info!("[unlock withdrawal] reuse block producer wallet");
 
-            let withdrawal_unlocker = FinalizedWithdrawalUnlocker::new(
-                rpc_client.clone(),
-                ckb_genesis_info.clone(),
-                contracts_dep_manager.clone(),
-                unlocker_wallet,
-                config.debug.clone(),
-                block_producer_config.fee_rate,
-            );
+                        Arc::clone(&signer)
+                    }
+                };
 
-            let cleaner = Arc::new(Cleaner::new(
-                rpc_client.clone(),
-                ckb_genesis_info.clone(),
-                wallet,
-                block_producer_config.fee_rate,
-            ));
+                let withdrawal_unlocker = FinalizedWithdrawalUnlocker::new(
+                    rpc_client.clone(),
+                    ckb_genesis_info.clone(),
+                    contracts_dep_manager.clone(),
+                    unlocker_signer,
+                    config.debug.clone(),
+                    block_producer_config.fee_rate,
+                );
 
-            let challenger_wallet = match block_producer_config.wallet_config {
-                Some(ref c) => Wallet::from_config(c).with_context(|| "challenger wallet")?,
-                None => bail!("no wallet config for block producer"),
-            };
+                let cleaner = Arc::new(Cleaner::new(
+                    rpc_client.clone(),
+                    ckb_genesis_info.clone(),
+                    Arc::clone(&signer),
+                    block_producer_config.fee_rate,
+                ));
 
-            // Challenger
-            let args = ChallengerNewArgs {
-                rollup_context,
-                rpc_client: rpc_client.clone(),
-                wallet: challenger_wallet,
-                config: block_producer_config.clone(),
-                debug_config: config.debug.clone(),
-                builtin_load_data,
-                ckb_genesis_info: ckb_genesis_info.clone(),
-                chain: Arc::clone(&chain),
-                tests_control: tests_control.clone(),
-                cleaner: Arc::clone(&cleaner),
-                offchain_mock_context,
-                contracts_dep_manager: contracts_dep_manager.clone(),
-            };
-            let challenger = Challenger::new(args);
-
-            // Block Producer
-            let create_args = BlockProducerCreateArgs {
-                rollup_config_hash,
-                store: store.clone(),
-                generator: generator.clone(),
-                chain: Arc::clone(&chain),
-                rpc_client: rpc_client.clone(),
-                ckb_genesis_info,
-                config: block_producer_config,
-                tests_control: tests_control.clone(),
-                contracts_dep_manager,
-            };
-            let block_producer =
-                BlockProducer::create(create_args).with_context(|| "init block producer")?;
-
-            (
-                Some(block_producer),
-                Some(challenger),
-                tests_control,
-                Some(withdrawal_unlocker),
-                Some(cleaner),
-            )
-        }
-    };
+                // Challenger
+                let args = ChallengerNewArgs {
+                    rollup_context,
+                    rpc_client: rpc_client.clone(),
+                    signer,
+                    config: block_producer_config.clone(),
+                    debug_config: config.debug.clone(),
+                    builtin_load_data,
+                    ckb_genesis_info: ckb_genesis_info.clone(),
+                    chain: Arc::clone(&chain),
+                    tests_control: tests_control.clone(),
+                    cleaner: Arc::clone(&cleaner),
+                    offchain_mock_context,
+                    contracts_dep_manager: contracts_dep_manager.clone(),
+                };
+                let challenger = Challenger::new(args);
+
+                // Block Producer
+                let create_args = BlockProducerCreateArgs {
+                    rollup_config_hash,
+                    store: store.clone(),
+                    generator: generator.clone(),
+                    chain: Arc::clone(&chain),
+                    rpc_client: rpc_client.clone(),
+                    ckb_genesis_info,
+                    config: block_producer_config,
+                    tests_control: tests_control.clone(),
+                    contracts_dep_manager,
+                };
+                let block_producer =
+                    BlockProducer::create(create_args).with_context(|| "init block producer")?;
+
+                (
+                    Some(block_producer),
+                    Some(challenger),
+                    tests_control,
+                    Some(withdrawal_unlocker),
+                    Some(cleaner),
+                )
+            }
+        };
 
     // Wait for graceful shutdown complete.
     let (shutdown_completed_send, mut shutdown_completed_recv) = mpsc::channel(1);
@@ -732,6 +788,11 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
     // RPC registry
     let polyjuice_sender_recover = {
         let opt_wallet = match config.block_producer.as_ref().map(|c| &c.wallet_config) {
+            Some(Some(c)) if c.remote_signer.is_some() => {
+                log::info!("[tx from zero] remote signer can't sign l2 txs for sender creator");
+
+                None
+            }
             Some(Some(c)) => {
                 log::info!("[tx from zero] use block producer wallet");
 
@@ -751,6 +812,9 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
         mem_pool: mem_pool.clone(),
         generator,
         tests_rpc_impl: test_mode_control.map(Box::new),
+        backend_upgrade_rpc_impl: backend_upgrader
+            .clone()
+            .map(|upgrader| upgrader as Arc<dyn BackendUpgradeRPC + Send + Sync>),
         rollup_config,
         chain_config: config.chain.to_owned(),
         consensus_config: config.consensus.to_owned(),
@@ -866,6 +930,7 @@ pub async fn run(config: Config, skip_config_check: bool) -> Result<()> {
                     challenger,
                     withdrawal_unlocker,
                     cleaner,
+                    backend_upgrader,
                 };
                 let mut backoff = ExponentialBackoff::new(Duration::from_secs(1));
                 let mut chain_task = ChainTask::create(
@@ -988,56 +1053,6 @@ async fn check_ckb_version(rpc_client: &RPCClient) -> Result<()> {
     Ok(())
 }
 
-async fn check_rollup_config_cell(
-    block_producer_config: &BlockProducerConfig,
-    rollup_config: &RollupConfig,
-    rpc_client: &RPCClient,
-) -> Result<()> {
-    let rollup_config_cell = rpc_client
-        .get_cell(
-            block_producer_config
-                .rollup_config_cell_dep
-                .out_point
-                .clone()
-                .into(),
-        )
-        .await?
-        .and_then(|cell_with_status| cell_with_status.cell)
-        .ok_or_else(|| anyhow!("can't find rollup config cell"))?;
-    let cell_data = RollupConfig::from_slice(&rollup_config_cell.data)?;
-    let eoa_set = rollup_config
-        .allowed_eoa_type_hashes()
-        .into_iter()
-        .map(|th| th.hash())
-        .collect::<Vec<_>>();
-    let contract_set = rollup_config
-        .allowed_contract_type_hashes()
-        .into_iter()
-        .map(|th| th.hash())
-        .collect::<Vec<_>>();
-    let unregistered_eoas = cell_data
-        .allowed_eoa_type_hashes()
-        .into_iter()
-        .map(|th| th.hash())
-        .filter(|item| !eoa_set.contains(item))
-        .collect::<Vec<_>>();
-    let unregistered_contracts = cell_data
-        .allowed_contract_type_hashes()
-        .into_iter()
-        .map(|th| th.hash())
-        .filter(|item| !contract_set.contains(item))
-        .collect::<Vec<_>>();
-    if !unregistered_eoas.is_empty() || !unregistered_contracts.is_empty() {
-        return Err(anyhow!(
-            "Unregistered eoa type hashes: {:#?}, \
-            unregistered contract type hashes: {:#?}",
-            unregistered_eoas,
-            unregistered_contracts
-        ));
-    }
-    Ok(())
-}
-
 fn check_locks(
     block_producer_config: &BlockProducerConfig,
     rollup_config: &RollupConfig,
@@ -209,7 +209,9 @@ pub fn unlock_to_owner(
 
     let mut withdrawal_inputs = vec![];
     let mut withdrawal_witness = vec![];
-    let mut unlocked_to_owner_outputs = vec![];
+    let mut unlocked_to_owner_outputs: Vec<(CellOutput, Bytes)> = vec![];
+    // (owner lock hash, sudt type hash) => index of unlocked to owner output
+    let mut merged_outputs: HashMap<(H256, Option<H256>, Bytes), usize> = HashMap::new();
 
     let unlock_via_finalize_witness = {
         let unlock_args = UnlockWithdrawalViaFinalize::new_builder().build();
@@ -259,11 +261,44 @@ pub fn unlock_to_owner(
             }
         };
 
-        // Switch to owner lock
-        let output = withdrawal_cell.output.as_builder().lock(owner_lock).build();
-
         withdrawal_inputs.push(withdrawal_input);
         withdrawal_witness.push(unlock_via_finalize_witness.clone());
+
+        // Merge withdrawals to the same owner lock, sudt and extra data into one output
+        let merge_key = {
+            let type_ = withdrawal_cell.output.type_().to_opt();
+            let extra_data = match type_ {
+                Some(_) => withdrawal_cell
+                    .data
+                    .slice(withdrawal_cell.data.len().min(16)..),
+                None => withdrawal_cell.data.clone(),
+            };
+            (
+                owner_lock.hash().into(),
+                type_.map(|s| s.hash().into()),
+                extra_data,
+            )
+        };
+        if let Some(&idx) = merged_outputs.get(&merge_key) {
+            let (output, data) = &mut unlocked_to_owner_outputs[idx];
+            match merge_withdrawal_output((&*output, &*data), &withdrawal_cell) {
+                Ok(merged) => {
+                    *output = merged.0;
+                    *data = merged.1;
+                    continue;
+                }
+                Err(err) => {
+                    log::error!("[unlock withdrawal] unexpected merge failed {}", err);
+                    withdrawal_inputs.pop();
+                    withdrawal_witness.pop();
+                    continue;
+                }
+            }
+        }
+
+        // Switch to owner lock
+        let output = withdrawal_cell.output.as_builder().lock(owner_lock).build();
+        merged_outputs.insert(merge_key, unlocked_to_owner_outputs.len());
         unlocked_to_owner_outputs.push((output, withdrawal_cell.data));
     }
 
@@ -294,6 +329,52 @@ pub fn unlock_to_owner(
     }))
 }
 
+// Merge a withdrawal cell into an output which has the same owner lock and type,
+// the sUDT amount is summed up, the extra data must be the same and is kept as is
+fn merge_withdrawal_output(
+    (output, data): (&CellOutput, &Bytes),
+    withdrawal_cell: &CellInfo,
+) -> Result<(CellOutput, Bytes)> {
+    let capacity: u64 = output.capacity().unpack();
+    let withdrawal_capacity: u64 = withdrawal_cell.output.capacity().unpack();
+    let capacity = capacity
+        .checked_add(withdrawal_capacity)
+        .ok_or_else(|| anyhow!("capacity overflow"))?;
+    let output = output
+        .clone()
+        .as_builder()
+        .capacity(capacity.pack())
+        .build();
+
+    if output.type_().to_opt().is_none() {
+        if data != &withdrawal_cell.data {
+            return Err(anyhow!("withdrawal cell data mismatch"));
+        }
+        return Ok((output, data.clone()));
+    }
+
+    let split_sudt_data = |data: &Bytes| -> Result<(u128, Bytes)> {
+        if data.len() < 16 {
+            return Err(anyhow!("invalid sudt data"));
+        }
+        let mut buf = [0u8; 16];
+        buf.copy_from_slice(&data[..16]);
+        Ok((u128::from_le_bytes(buf), data.slice(16..)))
+    };
+    let (amount, extra_data) = split_sudt_data(data)?;
+    let (withdrawal_amount, withdrawal_extra_data) = split_sudt_data(&withdrawal_cell.data)?;
+    if extra_data != withdrawal_extra_data {
+        return Err(anyhow!("withdrawal sudt extra data mismatch"));
+    }
+    let amount = amount
+        .checked_add(withdrawal_amount)
+        .ok_or_else(|| anyhow!("sudt amount overflow"))?;
+
+    let mut merged_data = amount.to_le_bytes().to_vec();
+    merged_data.extend_from_slice(&extra_data);
+    Ok((output, merged_data.into()))
+}
+
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
@@ -301,6 +382,7 @@ mod test {
 
     use gw_common::{h256_ext::H256Ext, H256};
     use gw_config::ContractsCellDep;
+    use gw_types::bytes::Bytes;
     use gw_types::core::{DepType, ScriptHashType};
     use gw_types::offchain::{CellInfo, CollectedCustodianCells, InputCellInfo};
     use gw_types::packed::{
@@ -595,4 +677,156 @@ mod test {
             CellDep::from(contracts_dep.l1_sudt_type).as_slice(),
         );
     }
+
+    #[test]
+    fn test_unlock_to_owner_merge_withdrawals() {
+        let last_finalized_block_number = 100u64;
+        let global_state = GlobalState::new_builder()
+            .last_finalized_block_number(last_finalized_block_number.pack())
+            .build();
+
+        let rollup_type = Script::new_builder()
+            .code_hash(H256::from_u32(1).pack())
+            .build();
+
+        let rollup_cell = CellInfo {
+            data: global_state.as_bytes(),
+            out_point: OutPoint::new_builder()
+                .tx_hash(H256::from_u32(2).pack())
+                .build(),
+            output: CellOutput::new_builder()
+                .type_(Some(rollup_type.clone()).pack())
+                .build(),
+        };
+
+        let sudt_script = Script::new_builder()
+            .code_hash(H256::from_u32(3).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(vec![4u8; 32].pack())
+            .build();
+
+        let rollup_context = RollupContext {
+            rollup_script_hash: rollup_type.hash().into(),
+            rollup_config: RollupConfig::new_builder()
+                .withdrawal_script_type_hash(H256::from_u32(5).pack())
+                .l1_sudt_script_type_hash(sudt_script.code_hash())
+                .finality_blocks(1u64.pack())
+                .build(),
+        };
+
+        let owner_lock = Script::new_builder()
+            .code_hash(H256::from_u32(8).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(vec![9u8; 32].pack())
+            .build();
+
+        let build_withdrawal_with_extra_data =
+            |idx: u32, sudt: Option<(Script, u128)>, extra_data: &[u8]| {
+                let lock_args = WithdrawalLockArgs::new_builder()
+                    .owner_lock_hash(owner_lock.hash().pack())
+                    .withdrawal_block_hash(H256::from_u32(idx).pack())
+                    .withdrawal_block_number((last_finalized_block_number - 1).pack())
+                    .build();
+
+                let mut args = rollup_type.hash().to_vec();
+                args.extend_from_slice(&lock_args.as_bytes());
+                args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+                args.extend_from_slice(&owner_lock.as_bytes());
+
+                let lock = Script::new_builder().args(args.pack()).build();
+                let (type_, mut data) = match sudt {
+                    Some((script, amount)) => (Some(script), amount.to_le_bytes().to_vec()),
+                    None => (None, vec![]),
+                };
+                data.extend_from_slice(extra_data);
+                CellInfo {
+                    output: CellOutput::new_builder()
+                        .capacity(1000u64.pack())
+                        .type_(type_.pack())
+                        .lock(lock)
+                        .build(),
+                    data: data.into(),
+                    out_point: OutPoint::new_builder()
+                        .tx_hash(H256::from_u32(idx).pack())
+                        .build(),
+                }
+            };
+        let build_withdrawal = |idx: u32, sudt: Option<(Script, u128)>| {
+            build_withdrawal_with_extra_data(idx, sudt, &[])
+        };
+
+        let unlocked = unlock_to_owner(
+            rollup_cell.clone(),
+            &rollup_context,
+            &ContractsCellDep::default(),
+            vec![
+                build_withdrawal(10, Some((sudt_script.clone(), 100))),
+                build_withdrawal(11, None),
+                build_withdrawal(12, Some((sudt_script.clone(), 200))),
+            ],
+        )
+        .expect("unlock")
+        .expect("some unlocked");
+
+        assert_eq!(unlocked.inputs.len(), 3);
+        assert_eq!(unlocked.witness_args.len(), 3);
+        assert_eq!(unlocked.outputs.len(), 2, "merge sudt withdrawals");
+
+        let expected_sudt_output = CellOutput::new_builder()
+            .capacity(2000u64.pack())
+            .type_(Some(sudt_script.clone()).pack())
+            .lock(owner_lock.clone())
+            .build();
+        let (output, data) = unlocked.outputs.first().unwrap().to_owned();
+        assert_eq!(expected_sudt_output.as_slice(), output.as_slice());
+        assert_eq!(300u128.pack().as_bytes(), data);
+
+        let expected_ckb_output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(owner_lock.clone())
+            .build();
+        let (output, data) = unlocked.outputs.get(1).unwrap().to_owned();
+        assert_eq!(expected_ckb_output.as_slice(), output.as_slice());
+        assert!(data.is_empty());
+
+        // Withdrawals with different extra data are never merged, the extra data is kept
+        let unlocked = unlock_to_owner(
+            rollup_cell,
+            &rollup_context,
+            &ContractsCellDep::default(),
+            vec![
+                build_withdrawal_with_extra_data(20, Some((sudt_script.clone(), 100)), &[1, 2]),
+                build_withdrawal_with_extra_data(21, Some((sudt_script.clone(), 200)), &[3]),
+                build_withdrawal_with_extra_data(22, Some((sudt_script, 300)), &[1, 2]),
+                build_withdrawal_with_extra_data(23, None, &[4]),
+                build_withdrawal_with_extra_data(24, None, &[5]),
+            ],
+        )
+        .expect("unlock")
+        .expect("some unlocked");
+
+        assert_eq!(unlocked.inputs.len(), 5);
+        assert_eq!(unlocked.outputs.len(), 4);
+
+        let outputs_data: Vec<_> = unlocked
+            .outputs
+            .iter()
+            .map(|(_, data)| data.clone())
+            .collect();
+        let mut expected_merged_data = 400u128.to_le_bytes().to_vec();
+        expected_merged_data.extend_from_slice(&[1, 2]);
+        let mut expected_data = 200u128.to_le_bytes().to_vec();
+        expected_data.extend_from_slice(&[3]);
+        assert_eq!(
+            outputs_data,
+            vec![
+                Bytes::from(expected_merged_data),
+                Bytes::from(expected_data),
+                Bytes::from(vec![4]),
+                Bytes::from(vec![5]),
+            ]
+        );
+        let (output, _) = unlocked.outputs.first().unwrap();
+        assert_eq!(output.capacity().unpack(), 2000u64);
+    }
 }
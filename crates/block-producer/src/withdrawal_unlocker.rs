@@ -14,8 +14,8 @@ use gw_types::packed::{OutPoint, Transaction};
 use gw_types::prelude::{Pack, Unpack};
 use gw_utils::fee::fill_tx_fee;
 use gw_utils::genesis_info::CKBGenesisInfo;
+use gw_utils::signer::Signer;
 use gw_utils::transaction_skeleton::TransactionSkeleton;
-use gw_utils::wallet::Wallet;
 use tracing::instrument;
 
 use crate::types::ChainEvent;
@@ -37,7 +37,7 @@ impl FinalizedWithdrawalUnlocker {
         rpc_client: RPCClient,
         ckb_genesis_info: CKBGenesisInfo,
         contracts_dep_manager: ContractsCellDepManager,
-        wallet: Wallet,
+        signer: Arc<dyn Signer>,
         debug_config: DebugConfig,
         fee_rate: u64,
     ) -> Self {
@@ -45,7 +45,7 @@ impl FinalizedWithdrawalUnlocker {
             rpc_client,
             ckb_genesis_info,
             contracts_dep_manager,
-            wallet,
+            signer,
             fee_rate,
         );
 
@@ -216,7 +216,7 @@ struct DefaultUnlocker {
     rpc_client: RPCClient,
     ckb_genesis_info: CKBGenesisInfo,
     contracts_dep_manager: ContractsCellDepManager,
-    wallet: Wallet,
+    signer: Arc<dyn Signer>,
     fee_rate: u64,
 }
 
@@ -227,14 +227,14 @@ impl DefaultUnlocker {
         rpc_client: RPCClient,
         ckb_genesis_info: CKBGenesisInfo,
         contracts_dep_manager: ContractsCellDepManager,
-        wallet: Wallet,
+        signer: Arc<dyn Signer>,
         fee_rate: u64,
     ) -> Self {
         DefaultUnlocker {
             rpc_client,
             ckb_genesis_info,
             contracts_dep_manager,
-            wallet,
+            signer,
             fee_rate,
         }
     }
@@ -272,7 +272,7 @@ impl BuildUnlockWithdrawalToOwner for DefaultUnlocker {
         let owner_lock_dep = self.ckb_genesis_info.sighash_dep();
         tx_skeleton.cell_deps_mut().push(owner_lock_dep);
 
-        let owner_lock = self.wallet.lock_script().to_owned();
+        let owner_lock = self.signer.lock_script().to_owned();
         fill_tx_fee(
             &mut tx_skeleton,
             &self.rpc_client.indexer,
@@ -280,6 +280,6 @@ impl BuildUnlockWithdrawalToOwner for DefaultUnlocker {
             self.fee_rate,
         )
         .await?;
-        self.wallet.sign_tx_skeleton(tx_skeleton)
+        self.signer.sign_tx_skeleton(tx_skeleton).await
     }
 }
@@ -1011,14 +1011,19 @@ impl Chain {
             );
         }
 
+        // access lists of txs executed by the mem pool of this node
+        self.store
+            .prefetch_state(&db.get_block_access_keys(&l2block)?);
+
         // process transactions
         // TODO: run offchain validator before send challenge, to make sure the block is bad
         let generator = &self.generator;
-        let (withdrawal_receipts, prev_txs_state, tx_receipts) = match generator
+        let (withdrawal_receipts, prev_txs_state, tx_receipts, tx_access_lists) = match generator
             .verify_and_apply_block(db, &chain_view, args, &self.skipped_invalid_block_list)
         {
             ApplyBlockResult::Success {
                 tx_receipts,
+                tx_access_lists,
                 prev_txs_state,
                 withdrawal_receipts,
                 offchain_used_cycles,
@@ -1029,7 +1034,12 @@ impl Chain {
                     tx_receipts.len(),
                     offchain_used_cycles
                 );
-                (withdrawal_receipts, prev_txs_state, tx_receipts)
+                (
+                    withdrawal_receipts,
+                    prev_txs_state,
+                    tx_receipts,
+                    tx_access_lists,
+                )
             }
             ApplyBlockResult::Challenge { target, error } => {
                 log::warn!("verify #{} state transition error {}", block_number, error);
@@ -1045,6 +1055,7 @@ impl Chain {
             withdrawal_receipts,
             prev_txs_state,
             tx_receipts,
+            tx_access_lists,
             deposit_info_vec,
             withdrawals,
         )?;
@@ -5,7 +5,9 @@ use ckb_types::prelude::{Builder, Entity};
 use gw_common::H256;
 use gw_config::ContractsCellDep;
 use gw_types::core::{DepType, SigningType, Status};
-use gw_types::offchain::{CellInfo, InputCellInfo, RecoverAccount, RollupContext};
+use gw_types::offchain::{
+    BatchWithdrawalSignature, CellInfo, InputCellInfo, RecoverAccount, RollupContext,
+};
 use gw_types::packed::{
     CCTransactionSignatureWitness, CCTransactionWitness, CCWithdrawalWitness, CellDep, CellInput,
     CellOutput, GlobalState, OutPoint, RollupAction, RollupActionUnion, RollupCancelChallenge,
@@ -444,7 +446,12 @@ impl<'a> CancelChallenge<'a, CCWithdrawalWitness> {
     }
 
     fn calc_withdrawal_message(&self) -> [u8; 32] {
-        let raw_withdrawal = self.verify_witness.withdrawal().raw();
+        let withdrawal = self.verify_witness.withdrawal();
+        let signature: Bytes = withdrawal.signature().unpack();
+        if let Some(batch) = BatchWithdrawalSignature::from_slice(&signature) {
+            return batch.signing_message(&self.rollup_type_hash).into();
+        }
+        let raw_withdrawal = withdrawal.raw();
         raw_withdrawal.calc_message(&self.rollup_type_hash).into()
     }
 }
@@ -53,6 +53,19 @@ impl RegistryContext {
                 let addr = RegistryAddress::new(registry_id, address);
                 Ok(addr)
             }
+            Some(AllowedEoaType::Multisig) => {
+                // multisig EOA shares the ETH address format,
+                // the address is the 20 bytes hash of the multisig script
+                let address = crate::registry::eth_registry::extract_eth_address_from_eoa(args)?;
+                let addr = RegistryAddress::new(registry_id, address);
+                Ok(addr)
+            }
+            Some(AllowedEoaType::WebAuthn) => {
+                // the address is the 20 bytes hash of the P-256 pubkey
+                let address = crate::registry::eth_registry::extract_eth_address_from_eoa(args)?;
+                let addr = RegistryAddress::new(registry_id, address);
+                Ok(addr)
+            }
             _ => Err(Error::UnknownEoaCodeHash),
         }
     }
@@ -25,6 +25,11 @@ pub struct Config {
     #[serde(default)]
     pub contract_log_config: ContractLogConfig,
     pub backend_switches: Vec<BackendSwitchConfig>,
+    /// Dir to persist AOT compiled backend generators, compiled in memory on each startup if not set
+    #[serde(default)]
+    pub aot_cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub backend_upgrade: Option<BackendUpgradeConfig>,
     pub genesis: GenesisConfig,
     pub chain: ChainConfig,
     pub rpc_client: RPCClientConfig,
@@ -50,6 +55,9 @@ pub struct Config {
     pub p2p_network_config: Option<P2PNetworkConfig>,
     #[serde(default)]
     pub sync_server: SyncServerConfig,
+    /// What to do on startup if the config drifts from the on-chain rollup state
+    #[serde(default)]
+    pub config_drift: ConfigDriftAction,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Hash)]
@@ -57,6 +65,8 @@ pub struct Config {
 pub enum RPCMethods {
     PProf,
     Test,
+    Debug,
+    Admin,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -64,6 +74,9 @@ pub struct RPCServerConfig {
     pub listen: String,
     #[serde(default)]
     pub enable_methods: HashSet<RPCMethods>,
+    /// Directory of the database checkpoints created by the `gw_create_db_checkpoint` admin RPC
+    #[serde(default)]
+    pub backup_dir: Option<PathBuf>,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -111,8 +124,43 @@ pub struct GenesisConfig {
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WalletConfig {
+    /// A plaintext hex private key file or an encrypted keystore file
+    #[serde(default)]
     pub privkey_path: PathBuf,
     pub lock: Script,
+    /// Where to read the keystore passphrase, default to env `GODWOKEN_KEYSTORE_PASSPHRASE`
+    #[serde(default)]
+    pub passphrase: Option<PassphraseSource>,
+    /// Sign transactions by a remote signer, `privkey_path` is ignored if set
+    #[serde(default)]
+    pub remote_signer: Option<RemoteSignerConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PassphraseSource {
+    /// Read passphrase from the env var
+    Env(String),
+    /// Read passphrase from the inherited file descriptor
+    Fd(i32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteSignerConfig {
+    /// `unix:///path/to/signer.sock` or `https://host:port`
+    pub url: String,
+    /// PEM file contains the client certificate and private key, used for https mTLS
+    #[serde(default)]
+    pub client_identity_path: Option<PathBuf>,
+    /// PEM CA certificate of the signer server, used for https mTLS
+    #[serde(default)]
+    pub ca_cert_path: Option<PathBuf>,
+    #[serde(default = "default_remote_signer_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_remote_signer_timeout_ms() -> u64 {
+    10_000
 }
 
 // NOTE: Rewards receiver lock must be different than lock in WalletConfig,
@@ -265,6 +313,34 @@ pub struct BackendConfig {
     pub backend_type: BackendType,
 }
 
+/// Register backends at runtime without restarting the node
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendUpgradeConfig {
+    /// Type script of the L1 cell announcing backend switches, the cell data is a list of
+    /// `switch_height(u64 LE) | n(u32 LE) | validator_script_type_hash(32 bytes) * n`
+    /// in increasing height order
+    pub signal_cell_type_script: Script,
+    /// Lock script of the signal cell owner, signal cells owned by other locks are ignored
+    pub signal_cell_lock_script: Script,
+    /// Backends staged for upgrades, activated once announced by the signal cell
+    #[serde(default)]
+    pub staged_backends: Vec<StagedBackendConfig>,
+    /// Backends staged by the admin RPC are saved to this file and loaded on startup
+    pub staged_backends_path: PathBuf,
+    /// Registered backend switches are saved to this file and loaded on startup
+    pub registered_switches_path: PathBuf,
+    /// Validators and generators of backends staged by the admin RPC must be in this dir
+    pub backends_dir: PathBuf,
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedBackendConfig {
+    #[serde(flatten)]
+    pub backend: BackendConfig,
+    /// Type script of the validator deployment cell on L1
+    pub validator_script_type: Script,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DebugConfig {
     pub output_l1_tx_cycles: bool,
@@ -364,6 +440,81 @@ pub struct MemBlockConfig {
     pub max_cycles_limit: u64,
     #[serde(default = "default_syscall_cycles")]
     pub syscall_cycles: SyscallCyclesConfig,
+    /// Syscall cycles repriced at fork heights, `syscall_cycles` is used before the first switch
+    #[serde(default)]
+    pub syscall_cycles_switches: Vec<SyscallCyclesSwitchConfig>,
+    /// Execute mem block txs optimistically in parallel, disabled if not set
+    #[serde(default)]
+    pub parallel_execution: Option<ParallelExecutionConfig>,
+}
+
+impl MemBlockConfig {
+    /// Syscall cycles of the block at `block_number`
+    pub fn syscall_cycles_at(&self, block_number: u64) -> &SyscallCyclesConfig {
+        self.syscall_cycles_switches
+            .iter()
+            .filter(|switch| switch.switch_height <= block_number)
+            .max_by_key(|switch| switch.switch_height)
+            .map(|switch| &switch.syscall_cycles)
+            .unwrap_or(&self.syscall_cycles)
+    }
+}
+
+#[test]
+fn test_syscall_cycles_at() {
+    let syscall_cycles = |sys_store_cycles| SyscallCyclesConfig {
+        sys_store_cycles,
+        ..Default::default()
+    };
+    let switch = |switch_height, sys_store_cycles| SyscallCyclesSwitchConfig {
+        switch_height,
+        syscall_cycles: syscall_cycles(sys_store_cycles),
+    };
+    let config = MemBlockConfig {
+        syscall_cycles: syscall_cycles(1),
+        // out of order
+        syscall_cycles_switches: vec![switch(200, 3), switch(100, 2), switch(300, 4)],
+        ..Default::default()
+    };
+
+    let sys_store_cycles_at =
+        |block_number| config.syscall_cycles_at(block_number).sys_store_cycles;
+    assert_eq!(sys_store_cycles_at(0), 1);
+    assert_eq!(sys_store_cycles_at(99), 1);
+    assert_eq!(sys_store_cycles_at(100), 2);
+    assert_eq!(sys_store_cycles_at(199), 2);
+    assert_eq!(sys_store_cycles_at(200), 3);
+    assert_eq!(sys_store_cycles_at(299), 3);
+    assert_eq!(sys_store_cycles_at(300), 4);
+    assert_eq!(sys_store_cycles_at(u64::MAX), 4);
+
+    // no switches
+    let config = MemBlockConfig {
+        syscall_cycles: syscall_cycles(1),
+        ..Default::default()
+    };
+    assert_eq!(config.syscall_cycles_at(u64::MAX).sys_store_cycles, 1);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyscallCyclesSwitchConfig {
+    pub switch_height: u64,
+    pub syscall_cycles: SyscallCyclesConfig,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParallelExecutionConfig {
+    /// Number of worker threads
+    #[serde(default = "default_parallel_execution_workers")]
+    pub workers: usize,
+}
+
+impl Default for ParallelExecutionConfig {
+    fn default() -> Self {
+        Self {
+            workers: default_parallel_execution_workers(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -401,6 +552,12 @@ fn default_syscall_cycles() -> SyscallCyclesConfig {
     SyscallCyclesConfig::default()
 }
 
+fn default_parallel_execution_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
 // Workaround: https://github.com/alexcrichton/toml-rs/issues/256
 // Serialize to string instead
 mod toml_u64_serde_workaround {
@@ -445,10 +602,28 @@ impl Default for MemBlockConfig {
             deposit_timeout_config: Default::default(),
             max_cycles_limit: default_max_block_cycles_limit(),
             syscall_cycles: SyscallCyclesConfig::default(),
+            syscall_cycles_switches: Vec::new(),
+            parallel_execution: None,
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigDriftAction {
+    /// Refuse to start
+    Refuse,
+    /// Log the drifts and start anyway
+    Warn,
+}
+
+// TODO: default to `Refuse` in the next release
+impl Default for ConfigDriftAction {
+    fn default() -> Self {
+        ConfigDriftAction::Warn
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum NodeMode {
@@ -469,6 +644,10 @@ pub struct DBBlockValidatorConfig {
     pub parallel_verify_blocks: bool,
     pub replace_scripts: Option<HashMap<H256, PathBuf>>,
     pub skip_targets: Option<HashSet<(u64, ChallengeTargetType, u32)>>,
+    /// Replay block txs with the syscall cycles of their block heights and check the mem block
+    /// max cycles limit
+    #[serde(default)]
+    pub verify_block_cycles: bool,
 }
 
 impl Default for DBBlockValidatorConfig {
@@ -478,6 +657,7 @@ impl Default for DBBlockValidatorConfig {
             replace_scripts: None,
             skip_targets: None,
             parallel_verify_blocks: true,
+            verify_block_cycles: false,
         }
     }
 }
@@ -492,6 +672,26 @@ pub struct StoreConfig {
     pub options_file: Option<PathBuf>,
     #[serde(default)]
     pub options: HashMap<String, String>,
+    /// Storage engine, `cache_size`, `options_file` and `options` are only used by RocksDB
+    #[serde(default)]
+    pub engine: StoreEngine,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreEngine {
+    RocksDB,
+    /// Keep all data in memory, data is lost after the process exits
+    Memory,
+    /// Keep all data in memory and append writes to a log file in `path`, the whole database
+    /// is loaded into memory on open, so it must fit in RAM
+    Log,
+}
+
+impl Default for StoreEngine {
+    fn default() -> Self {
+        StoreEngine::RocksDB
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -559,6 +759,7 @@ impl Default for ContractLogConfig {
 
 // Cycles config for all db related syscalls
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SyscallCyclesConfig {
     pub sys_store_cycles: u64,
     pub sys_load_cycles: u64,
@@ -575,10 +776,18 @@ pub struct SyscallCyclesConfig {
     pub sys_bn_per_pairing_cycles: u64,
     pub sys_snapshot_cycles: u64,
     pub sys_revert_cycles: u64,
+    pub sys_keccak256_cycles: u64,
+    pub sys_keccak256_per_word_cycles: u64,
+    pub sys_sha256_cycles: u64,
+    pub sys_sha256_per_word_cycles: u64,
+    pub sys_ripemd160_cycles: u64,
+    pub sys_ripemd160_per_word_cycles: u64,
+    pub sys_blake2f_per_round_cycles: u64,
+    pub sys_secp256k1_recover_cycles: u64,
 }
 
-impl SyscallCyclesConfig {
-    pub fn default() -> Self {
+impl Default for SyscallCyclesConfig {
+    fn default() -> Self {
         SyscallCyclesConfig {
             sys_store_cycles: 50000,
             sys_load_cycles: 5000,
@@ -599,6 +808,17 @@ impl SyscallCyclesConfig {
             sys_bn_per_pairing_cycles: 102_000,
             sys_snapshot_cycles: 2000,
             sys_revert_cycles: 2000,
+
+            // default cycles of precompiles
+            // estimated_cycles = 3 * (Gas Cost of Ethereum precompiled contracts)
+            sys_keccak256_cycles: 90,
+            sys_keccak256_per_word_cycles: 18,
+            sys_sha256_cycles: 180,
+            sys_sha256_per_word_cycles: 36,
+            sys_ripemd160_cycles: 1800,
+            sys_ripemd160_per_word_cycles: 360,
+            sys_blake2f_per_round_cycles: 3,
+            sys_secp256k1_recover_cycles: 9000,
         }
     }
 }
@@ -6,13 +6,14 @@ use crate::write_batch::RocksDBWriteBatch;
 use crate::{internal_error, CfMemStat, Result};
 use gw_config::StoreConfig;
 use rocksdb::ops::{
-    CreateCF, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF, IterateCF, OpenCF, Put, SetOptions,
-    WriteOps,
+    CreateCF, CreateCheckpointObject, Delete, DropCF, GetColumnFamilys, GetPinned, GetPinnedCF,
+    IterateCF, OpenCF, Put, SetOptions, WriteOps,
 };
 use rocksdb::{
     ffi, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice, FullOptions, IteratorMode,
     OptimisticTransactionDB, OptimisticTransactionOptions, Options, WriteBatch, WriteOptions,
 };
+use std::path::Path;
 use std::sync::Arc;
 
 /// RocksDB wrapper base on OptimisticTransactionDB
@@ -24,7 +25,7 @@ pub struct RocksDB {
 }
 
 impl RocksDB {
-    pub(crate) fn open_with_check(config: &StoreConfig, columns: u32) -> Result<Self> {
+    pub fn open_with_check(config: &StoreConfig, columns: u32) -> Result<Self> {
         let cf_names: Vec<_> = (0..columns).map(|c| c.to_string()).collect();
 
         let (mut opts, cf_descriptors) = if let Some(ref file) = config.options_file {
@@ -145,6 +146,10 @@ impl RocksDB {
         self.inner.put(key, value).map_err(internal_error)
     }
 
+    pub fn delete_default<K: AsRef<[u8]>>(&self, key: K) -> Result<()> {
+        self.inner.delete(key).map_err(internal_error)
+    }
+
     pub fn traverse<F>(&self, col: Col, mut callback: F) -> Result<()>
     where
         F: FnMut(&[u8], &[u8]) -> Result<()>,
@@ -212,6 +217,20 @@ impl RocksDB {
     pub fn gather_mem_stats(&self) -> Vec<CfMemStat> {
         self.inner.gather_memory_stats()
     }
+
+    /// Create a consistent checkpoint of the database while it's running
+    ///
+    /// SST files are hard linked if the path is on the same filesystem, otherwise they are
+    /// copied. The path must not exist.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let checkpoint = self
+            .inner
+            .create_checkpoint_object()
+            .map_err(internal_error)?;
+        checkpoint
+            .create_checkpoint(path)
+            .map_err(|err| internal_error(format!("failed to create checkpoint: {}", err)))
+    }
 }
 
 #[inline]
@@ -254,6 +273,7 @@ mod tests {
             },
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
         RocksDB::open(&config, 2); // no panic
     }
@@ -269,6 +289,7 @@ mod tests {
             options: HashMap::new(),
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
         RocksDB::open(&config, 2); // no panic
     }
@@ -289,6 +310,7 @@ mod tests {
             },
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
         RocksDB::open(&config, 2); // panic
     }
@@ -322,6 +344,47 @@ mod tests {
         assert_eq!(r.get(&vec![1, 1]), Some(&vec![1, 1, 1]));
     }
 
+    #[test]
+    fn create_checkpoint() {
+        // the checkpoint links the live files, so keep the source directory around
+        let source_dir = tempfile::Builder::new()
+            .prefix("create_checkpoint")
+            .tempdir()
+            .unwrap();
+        let config = StoreConfig {
+            path: source_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let db = RocksDB::open(&config, 2);
+        let txn = db.transaction();
+        txn.put(1, &[1, 1], &[1, 2, 3]).unwrap();
+        txn.commit().unwrap();
+
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("create_checkpoint_target")
+            .tempdir()
+            .unwrap();
+        let path = tmp_dir.path().join("checkpoint");
+        db.create_checkpoint(&path).unwrap();
+        // writes after the checkpoint aren't included
+        let txn = db.transaction();
+        txn.put(1, &[2, 2], &[4, 5, 6]).unwrap();
+        txn.commit().unwrap();
+        // the path must not exist
+        assert!(db.create_checkpoint(&path).is_err());
+
+        let config = StoreConfig {
+            path,
+            ..Default::default()
+        };
+        let checkpoint = RocksDB::open(&config, 2);
+        assert_eq!(
+            checkpoint.get_pinned(1, &[1, 1]).unwrap().unwrap().as_ref(),
+            &[1, 2, 3]
+        );
+        assert!(checkpoint.get_pinned(1, &[2, 2]).unwrap().is_none());
+    }
+
     #[test]
     fn snapshot_isolation() {
         let db = setup_db("snapshot_isolation", 2);
@@ -0,0 +1,419 @@
+//! Log storage engine
+//!
+//! All data is kept in a `MemoryDB`, each write batch is appended to `data.log` and synced to
+//! disk before it is applied. The log is replayed when the database is opened. The log is
+//! compacted on open when most of it is overwritten data.
+//!
+//! Each record is `len: u32 LE | crc32(ops): u32 LE | ops`, the ops of a batch are encoded as:
+//!
+//! - put: `0u8 | col: u8 | key_len: u32 LE | key | value_len: u32 LE | value`
+//! - delete: `1u8 | col: u8 | key_len: u32 LE | key`
+//! - delete range: `2u8 | col: u8 | from_len: u32 LE | from | to_len: u32 LE | to`
+//!
+//! A record is only appended after the previous one is synced, so only the last record can be
+//! torn by a crash or power failure. Replay stops at the first truncated record or checksum
+//! mismatch, the log is truncated there.
+//!
+//! This is not a disk-backed engine: the log only makes the in-memory data durable, the whole
+//! database is loaded into RAM on open and stays there, so it only suits databases which fit
+//! in memory, e.g. tests, devnets and benchmarks.
+use super::memory::{apply_ops, Column, MemoryDB};
+use super::{
+    BatchOp, EngineIter, EngineSlice, EngineSnapshot, EngineTransaction, EngineWriteBatch,
+    StorageEngine,
+};
+use crate::schema::Col;
+use crate::{internal_error, IteratorMode, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+const LOG_FILE_NAME: &str = "data.log";
+// len | crc32
+const RECORD_HEADER_SIZE: usize = 8;
+const TAG_PUT: u8 = 0;
+const TAG_DELETE: u8 = 1;
+const TAG_DELETE_RANGE: u8 = 2;
+// Compact the log on open if it is larger than this size and twice the live data
+const COMPACT_THRESHOLD: u64 = 64 * 1024 * 1024;
+// Max size of a record written by compaction
+const COMPACT_RECORD_SIZE: usize = 4 * 1024 * 1024;
+
+/// Storage engine which keeps all data in memory and persists writes to a log file, the whole
+/// database must fit in RAM.
+#[derive(Clone)]
+pub struct LogDB {
+    inner: MemoryDB,
+}
+
+impl LogDB {
+    pub fn open<P: AsRef<Path>>(path: P, columns: u32) -> Result<Self> {
+        let path = path.as_ref();
+        fs::create_dir_all(path).map_err(|err| {
+            internal_error(format!("failed to create dir {}: {}", path.display(), err))
+        })?;
+        let log_path = path.join(LOG_FILE_NAME);
+        let created = !log_path.exists();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(&log_path)
+            .map_err(|err| io_error(&log_path, err))?;
+        if created {
+            sync_dir(path)?;
+        }
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .map_err(|err| io_error(&log_path, err))?;
+
+        let mut data = vec![Column::new(); columns as usize];
+        let valid_len = replay(&buf, &mut data)?;
+        if valid_len < buf.len() {
+            log::warn!(
+                "discard {} bytes torn record at the end of {}",
+                buf.len() - valid_len,
+                log_path.display()
+            );
+            file.set_len(valid_len as u64)
+                .and_then(|()| file.sync_all())
+                .map_err(|err| io_error(&log_path, err))?;
+        }
+        drop(file);
+
+        let live_size: u64 = data
+            .iter()
+            .flat_map(|column| column.iter())
+            .map(|(k, v)| (k.len() + v.len()) as u64)
+            .sum();
+        let log_size = valid_len as u64;
+        if log_size > COMPACT_THRESHOLD && log_size > live_size * 2 {
+            log::info!(
+                "compact {}, log size {} bytes, live data {} bytes",
+                log_path.display(),
+                log_size,
+                live_size
+            );
+            let tmp_path = path.join(format!("{}.tmp", LOG_FILE_NAME));
+            write_columns(&tmp_path, &data)?;
+            fs::rename(&tmp_path, &log_path).map_err(|err| io_error(&log_path, err))?;
+            sync_dir(path)?;
+        }
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&log_path)
+            .map_err(|err| io_error(&log_path, err))?;
+        let writer = LogWriter { file };
+        Ok(LogDB {
+            inner: MemoryDB::with_columns(data, Some(writer)),
+        })
+    }
+
+    /// Whether the path contains a log database.
+    pub fn exists<P: AsRef<Path>>(path: P) -> bool {
+        path.as_ref().join(LOG_FILE_NAME).is_file()
+    }
+}
+
+impl StorageEngine for LogDB {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        self.inner.get(col, key)
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        self.inner.iter(col, mode)
+    }
+
+    fn transaction(&self) -> Box<dyn EngineTransaction> {
+        self.inner.transaction()
+    }
+
+    fn snapshot(&self) -> Box<dyn EngineSnapshot> {
+        self.inner.snapshot()
+    }
+
+    fn write(&self, batch: &EngineWriteBatch) -> Result<()> {
+        self.inner.write(batch)
+    }
+
+    /// Write the live data of a consistent snapshot to a new log in the path.
+    fn create_checkpoint(&self, path: &Path) -> Result<()> {
+        if path.exists() {
+            return Err(internal_error(format!(
+                "failed to create checkpoint: {} already exists",
+                path.display()
+            )));
+        }
+        fs::create_dir_all(path).map_err(|err| io_error(path, err))?;
+        write_columns(&path.join(LOG_FILE_NAME), &self.inner.columns())?;
+        sync_dir(path)
+    }
+}
+
+pub(crate) struct LogWriter {
+    file: File,
+}
+
+impl LogWriter {
+    /// Append the record and sync it, the batch is durable once this returns
+    pub(crate) fn append(&mut self, ops: &[BatchOp]) -> Result<()> {
+        let record = encode_record(ops);
+        self.file
+            .write_all(&record)
+            .and_then(|()| self.file.sync_data())
+            .map_err(|err| internal_error(format!("failed to append log: {}", err)))
+    }
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> crate::error::Error {
+    internal_error(format!("{}: {}", path.display(), err))
+}
+
+/// Sync the directory, so created and renamed files in it survive a power failure
+fn sync_dir(path: &Path) -> Result<()> {
+    File::open(path)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|err| io_error(path, err))
+}
+
+fn encode_record(ops: &[BatchOp]) -> Vec<u8> {
+    let mut record = vec![0u8; RECORD_HEADER_SIZE];
+    for op in ops {
+        match op {
+            BatchOp::Put { col, key, value } => {
+                record.extend_from_slice(&[TAG_PUT, *col]);
+                put_bytes(&mut record, key);
+                put_bytes(&mut record, value);
+            }
+            BatchOp::Delete { col, key } => {
+                record.extend_from_slice(&[TAG_DELETE, *col]);
+                put_bytes(&mut record, key);
+            }
+            BatchOp::DeleteRange { col, from, to } => {
+                record.extend_from_slice(&[TAG_DELETE_RANGE, *col]);
+                put_bytes(&mut record, from);
+                put_bytes(&mut record, to);
+            }
+        }
+    }
+    let len = (record.len() - RECORD_HEADER_SIZE) as u32;
+    let crc = crc32fast::hash(&record[RECORD_HEADER_SIZE..]);
+    record[..4].copy_from_slice(&len.to_le_bytes());
+    record[4..RECORD_HEADER_SIZE].copy_from_slice(&crc.to_le_bytes());
+    record
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+/// Apply the records in the log, return the length of the valid records.
+fn replay(buf: &[u8], columns: &mut [Column]) -> Result<usize> {
+    let mut offset = 0;
+    while let (Some(len), Some(crc)) = (read_u32(buf, offset), read_u32(buf, offset + 4)) {
+        let start = offset + RECORD_HEADER_SIZE;
+        let end = start + len as usize;
+        // Torn record
+        if end > buf.len() || crc32fast::hash(&buf[start..end]) != crc {
+            break;
+        }
+        let ops = decode_record(&buf[start..end])
+            .ok_or_else(|| internal_error(format!("corrupted log record at {}", offset)))?;
+        if let Some(op) = ops.iter().find(|op| op.col() as usize >= columns.len()) {
+            return Err(internal_error(format!("column {} not found", op.col())));
+        }
+        apply_ops(columns, &ops);
+        offset = end;
+    }
+    Ok(offset)
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> Option<u32> {
+    let bytes = buf.get(offset..offset + 4)?;
+    let mut le = [0u8; 4];
+    le.copy_from_slice(bytes);
+    Some(u32::from_le_bytes(le))
+}
+
+fn decode_record(buf: &[u8]) -> Option<Vec<BatchOp>> {
+    let mut ops = Vec::new();
+    let mut offset = 0;
+    let read_bytes = |offset: &mut usize| -> Option<Box<[u8]>> {
+        let len = read_u32(buf, *offset)? as usize;
+        let bytes = buf.get(*offset + 4..*offset + 4 + len)?;
+        *offset += 4 + len;
+        Some(bytes.into())
+    };
+    while offset < buf.len() {
+        let tag = *buf.get(offset)?;
+        let col = *buf.get(offset + 1)?;
+        offset += 2;
+        let op = match tag {
+            TAG_PUT => BatchOp::Put {
+                col,
+                key: read_bytes(&mut offset)?,
+                value: read_bytes(&mut offset)?,
+            },
+            TAG_DELETE => BatchOp::Delete {
+                col,
+                key: read_bytes(&mut offset)?,
+            },
+            TAG_DELETE_RANGE => BatchOp::DeleteRange {
+                col,
+                from: read_bytes(&mut offset)?,
+                to: read_bytes(&mut offset)?,
+            },
+            _ => return None,
+        };
+        ops.push(op);
+    }
+    Some(ops)
+}
+
+/// Write the columns as put records to a new log file.
+fn write_columns(path: &Path, columns: &[Column]) -> Result<()> {
+    let file = File::create(path).map_err(|err| io_error(path, err))?;
+    let mut writer = BufWriter::new(file);
+    let mut ops = Vec::new();
+    let mut size = 0;
+    for (col, column) in columns.iter().enumerate() {
+        for (key, value) in column.iter() {
+            size += key.len() + value.len();
+            ops.push(BatchOp::Put {
+                col: col as Col,
+                key: key.clone(),
+                value: value.clone(),
+            });
+            if size >= COMPACT_RECORD_SIZE {
+                writer
+                    .write_all(&encode_record(&ops))
+                    .map_err(|err| io_error(path, err))?;
+                ops.clear();
+                size = 0;
+            }
+        }
+    }
+    if !ops.is_empty() {
+        writer
+            .write_all(&encode_record(&ops))
+            .map_err(|err| io_error(path, err))?;
+    }
+    let file = writer
+        .into_inner()
+        .map_err(|err| internal_error(format!("{}: {}", path.display(), err)))?;
+    file.sync_all().map_err(|err| io_error(path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogDB, LOG_FILE_NAME};
+    use crate::engine::{EngineWriteBatch, StorageEngine};
+    use crate::IteratorMode;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    #[test]
+    fn test_replay_log() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("test_replay_log")
+            .tempdir()
+            .unwrap();
+        {
+            let db = LogDB::open(tmp_dir.path(), 2).unwrap();
+            let mut batch = EngineWriteBatch::default();
+            for i in 0u8..4 {
+                batch.put(1, &[i], &[i, i]).unwrap();
+            }
+            batch.delete_range(1, &[0], &[2]).unwrap();
+            db.write(&batch).unwrap();
+
+            let tx = db.transaction();
+            tx.put(0, &[9], &[9]).unwrap();
+            tx.delete(1, &[3]).unwrap();
+            tx.commit().unwrap();
+
+            // uncommitted writes are not logged
+            let tx = db.transaction();
+            tx.put(0, &[8], &[8]).unwrap();
+        }
+
+        // torn record
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(tmp_dir.path().join(LOG_FILE_NAME))
+            .unwrap();
+        file.write_all(&[100, 0, 0, 0, 0, 1]).unwrap();
+        drop(file);
+
+        let db = LogDB::open(tmp_dir.path(), 2).unwrap();
+        let keys: Vec<_> = db
+            .iter(1, IteratorMode::Start)
+            .unwrap()
+            .map(|(k, v)| (k.to_vec(), v.to_vec()))
+            .collect();
+        assert_eq!(keys, vec![(vec![2], vec![2, 2])]);
+        assert_eq!(db.get(0, &[9]).unwrap().as_deref(), Some(&[9u8][..]));
+        assert!(db.get(0, &[8]).unwrap().is_none());
+
+        // the torn record is removed, so new writes are replayed
+        let mut batch = EngineWriteBatch::default();
+        batch.put(0, &[7], &[7]).unwrap();
+        db.write(&batch).unwrap();
+        drop(db);
+        let db = LogDB::open(tmp_dir.path(), 2).unwrap();
+        assert_eq!(db.get(0, &[7]).unwrap().as_deref(), Some(&[7u8][..]));
+
+        // checkpoint
+        let checkpoint_path = tmp_dir.path().join("checkpoint");
+        db.create_checkpoint(&checkpoint_path).unwrap();
+        assert!(db.create_checkpoint(&checkpoint_path).is_err());
+        let checkpoint = LogDB::open(&checkpoint_path, 2).unwrap();
+        assert_eq!(
+            checkpoint.get(1, &[2]).unwrap().as_deref(),
+            Some(&[2u8, 2][..])
+        );
+        assert_eq!(
+            checkpoint.get(0, &[9]).unwrap().as_deref(),
+            Some(&[9u8][..])
+        );
+    }
+
+    #[test]
+    fn test_discard_checksum_mismatch_tail() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("test_discard_checksum_mismatch_tail")
+            .tempdir()
+            .unwrap();
+        {
+            let db = LogDB::open(tmp_dir.path(), 1).unwrap();
+            for i in 0u8..2 {
+                let mut batch = EngineWriteBatch::default();
+                batch.put(0, &[i], &[i]).unwrap();
+                db.write(&batch).unwrap();
+            }
+        }
+
+        // a complete record with corrupted content
+        let log_path = tmp_dir.path().join(LOG_FILE_NAME);
+        let mut log = std::fs::read(&log_path).unwrap();
+        let log_len = log.len();
+        *log.last_mut().unwrap() ^= 1;
+        std::fs::write(&log_path, &log).unwrap();
+
+        let db = LogDB::open(tmp_dir.path(), 1).unwrap();
+        assert_eq!(db.get(0, &[0]).unwrap().as_deref(), Some(&[0u8][..]));
+        assert!(db.get(0, &[1]).unwrap().is_none());
+        let truncated_len = std::fs::metadata(&log_path).unwrap().len() as usize;
+        assert!(truncated_len < log_len);
+
+        // new writes after the truncated tail are replayed
+        let mut batch = EngineWriteBatch::default();
+        batch.put(0, &[2], &[2]).unwrap();
+        db.write(&batch).unwrap();
+        drop(db);
+        let db = LogDB::open(tmp_dir.path(), 1).unwrap();
+        assert_eq!(db.get(0, &[2]).unwrap().as_deref(), Some(&[2u8][..]));
+    }
+}
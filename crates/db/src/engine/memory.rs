@@ -0,0 +1,418 @@
+//! In-memory storage engine
+//!
+//! Columns are persistent ordered maps, so taking a snapshot or starting a transaction only
+//! clones the map roots.
+use super::log_db::LogWriter;
+use super::{
+    BatchOp, EngineIter, EngineSlice, EngineSnapshot, EngineTransaction, EngineWriteBatch,
+    StorageEngine,
+};
+use crate::iter::DBIterItem;
+use crate::schema::Col;
+use crate::{internal_error, Direction, IteratorMode, Result};
+use im::OrdMap;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+pub(crate) type Column = OrdMap<Box<[u8]>, Box<[u8]>>;
+// None means the key is deleted
+type Writes = BTreeMap<(Col, Box<[u8]>), Option<Box<[u8]>>>;
+
+struct State {
+    columns: Vec<Column>,
+    // Writes are appended to the log before they are applied, see `LogDB`
+    log: Option<LogWriter>,
+}
+
+/// Storage engine which keeps all data in memory, the data is lost on drop.
+#[derive(Clone)]
+pub struct MemoryDB {
+    state: Arc<RwLock<State>>,
+}
+
+impl MemoryDB {
+    pub fn open(columns: u32) -> Self {
+        Self::with_columns(vec![Column::new(); columns as usize], None)
+    }
+
+    pub(crate) fn with_columns(columns: Vec<Column>, log: Option<LogWriter>) -> Self {
+        let state = State { columns, log };
+        MemoryDB {
+            state: Arc::new(RwLock::new(state)),
+        }
+    }
+
+    /// A consistent copy of all columns.
+    pub(crate) fn columns(&self) -> Vec<Column> {
+        self.state.read().unwrap().columns.clone()
+    }
+
+    fn column(&self, col: Col) -> Result<Column> {
+        column(&self.state.read().unwrap().columns, col).map(Clone::clone)
+    }
+}
+
+impl StorageEngine for MemoryDB {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        let state = self.state.read().unwrap();
+        Ok(column(&state.columns, col)?
+            .get(key)
+            .cloned()
+            .map(EngineSlice::Owned))
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        let iter = MergeIter::new(self.column(col)?, BTreeMap::new(), mode);
+        Ok(Box::new(iter))
+    }
+
+    fn transaction(&self) -> Box<dyn EngineTransaction> {
+        let tx = TxState {
+            snapshot: self.columns(),
+            writes: BTreeMap::new(),
+        };
+        Box::new(MemoryTransaction {
+            db: self.clone(),
+            tx: Mutex::new(tx),
+        })
+    }
+
+    fn snapshot(&self) -> Box<dyn EngineSnapshot> {
+        Box::new(MemorySnapshot {
+            columns: self.columns(),
+        })
+    }
+
+    fn write(&self, batch: &EngineWriteBatch) -> Result<()> {
+        apply(&mut self.state.write().unwrap(), batch.ops())
+    }
+
+    fn create_checkpoint(&self, _path: &Path) -> Result<()> {
+        Err(internal_error("memory engine doesn't support checkpoint"))
+    }
+}
+
+struct TxState {
+    // Committed data when the transaction began, used to detect conflicts
+    snapshot: Vec<Column>,
+    writes: Writes,
+}
+
+pub struct MemoryTransaction {
+    db: MemoryDB,
+    tx: Mutex<TxState>,
+}
+
+impl EngineTransaction for MemoryTransaction {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        {
+            let tx = self.tx.lock().unwrap();
+            if let Some(value) = tx.writes.get(&(col, Box::from(key))) {
+                return Ok(value.clone().map(EngineSlice::Owned));
+            }
+        }
+        self.db.get(col, key)
+    }
+
+    fn put(&self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        column(&tx.snapshot, col)?;
+        tx.writes.insert((col, key.into()), Some(value.into()));
+        Ok(())
+    }
+
+    fn delete(&self, col: Col, key: &[u8]) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        column(&tx.snapshot, col)?;
+        tx.writes.insert((col, key.into()), None);
+        Ok(())
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        let overlay = {
+            let tx = self.tx.lock().unwrap();
+            let from = (col, Box::<[u8]>::default());
+            tx.writes
+                .range(from..)
+                .take_while(|((c, _), _)| *c == col)
+                .map(|((_, k), v)| (k.clone(), v.clone()))
+                .collect()
+        };
+        let iter = MergeIter::new(self.db.column(col)?, overlay, mode);
+        Ok(Box::new(iter))
+    }
+
+    fn commit(&self) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        let mut state = self.db.state.write().unwrap();
+        for (col, key) in tx.writes.keys() {
+            if column(&state.columns, *col)?.get(key) != column(&tx.snapshot, *col)?.get(key) {
+                return Err(internal_error("Resource busy: write conflict"));
+            }
+        }
+        let ops: Vec<BatchOp> = tx
+            .writes
+            .iter()
+            .map(|((col, key), value)| match value {
+                Some(value) => BatchOp::Put {
+                    col: *col,
+                    key: key.clone(),
+                    value: value.clone(),
+                },
+                None => BatchOp::Delete {
+                    col: *col,
+                    key: key.clone(),
+                },
+            })
+            .collect();
+        apply(&mut state, &ops)?;
+        tx.writes.clear();
+        tx.snapshot = state.columns.clone();
+        Ok(())
+    }
+
+    fn rollback(&self) -> Result<()> {
+        self.tx.lock().unwrap().writes.clear();
+        Ok(())
+    }
+}
+
+pub struct MemorySnapshot {
+    columns: Vec<Column>,
+}
+
+impl EngineSnapshot for MemorySnapshot {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        Ok(column(&self.columns, col)?
+            .get(key)
+            .cloned()
+            .map(EngineSlice::Owned))
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        let base = column(&self.columns, col)?.clone();
+        Ok(Box::new(MergeIter::new(base, BTreeMap::new(), mode)))
+    }
+}
+
+fn column(columns: &[Column], col: Col) -> Result<&Column> {
+    columns
+        .get(col as usize)
+        .ok_or_else(|| internal_error(format!("column {} not found", col)))
+}
+
+fn apply(state: &mut State, ops: &[BatchOp]) -> Result<()> {
+    if ops.is_empty() {
+        return Ok(());
+    }
+    // Check all columns first, so the batch is either fully applied or not at all
+    for op in ops {
+        column(&state.columns, op.col())?;
+    }
+    if let Some(log) = state.log.as_mut() {
+        log.append(ops)?;
+    }
+    apply_ops(&mut state.columns, ops);
+    Ok(())
+}
+
+pub(crate) fn apply_ops(columns: &mut [Column], ops: &[BatchOp]) {
+    for op in ops {
+        let column = &mut columns[op.col() as usize];
+        match op {
+            BatchOp::Put { key, value, .. } => {
+                column.insert(key.clone(), value.clone());
+            }
+            BatchOp::Delete { key, .. } => {
+                column.remove(key);
+            }
+            BatchOp::DeleteRange { from, to, .. } => {
+                if from >= to {
+                    continue;
+                }
+                let range = (Bound::Included(from.as_ref()), Bound::Excluded(to.as_ref()));
+                let keys: Vec<Box<[u8]>> = column
+                    .range::<_, [u8]>(range)
+                    .map(|(k, _)| k.clone())
+                    .collect();
+                for key in keys {
+                    column.remove(&key);
+                }
+            }
+        }
+    }
+}
+
+/// Iterate a column merged with uncommitted writes.
+///
+/// The iterator owns a copy of the column, it seeks the next key after the last returned one
+/// on each step.
+struct MergeIter {
+    base: Column,
+    // None means the key is deleted
+    overlay: BTreeMap<Box<[u8]>, Option<Box<[u8]>>>,
+    direction: Direction,
+    bound: Bound<Box<[u8]>>,
+}
+
+impl MergeIter {
+    fn new(
+        base: Column,
+        overlay: BTreeMap<Box<[u8]>, Option<Box<[u8]>>>,
+        mode: IteratorMode,
+    ) -> Self {
+        let (direction, bound) = match mode {
+            IteratorMode::Start => (Direction::Forward, Bound::Unbounded),
+            IteratorMode::End => (Direction::Reverse, Bound::Unbounded),
+            IteratorMode::From(key, direction) => (direction, Bound::Included(key.into())),
+        };
+        MergeIter {
+            base,
+            overlay,
+            direction,
+            bound,
+        }
+    }
+}
+
+impl Iterator for MergeIter {
+    type Item = DBIterItem;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let bound = match &self.bound {
+                Bound::Included(key) => Bound::Included(key.as_ref()),
+                Bound::Excluded(key) => Bound::Excluded(key.as_ref()),
+                Bound::Unbounded => Bound::Unbounded,
+            };
+            let (base, overlay) = match self.direction {
+                Direction::Forward => {
+                    let range = (bound, Bound::Unbounded);
+                    (
+                        self.base.range::<_, [u8]>(range).next(),
+                        self.overlay.range::<[u8], _>(range).next(),
+                    )
+                }
+                Direction::Reverse => {
+                    let range = (Bound::Unbounded, bound);
+                    (
+                        self.base.range::<_, [u8]>(range).next_back(),
+                        self.overlay.range::<[u8], _>(range).next_back(),
+                    )
+                }
+            };
+            let (key, value) = match (base, overlay) {
+                (None, None) => return None,
+                (Some((k, v)), None) => (k.clone(), Some(v.clone())),
+                (None, Some((k, v))) => (k.clone(), v.clone()),
+                (Some((base_key, base_value)), Some((k, v))) => {
+                    let base_first = match self.direction {
+                        Direction::Forward => base_key < k,
+                        Direction::Reverse => base_key > k,
+                    };
+                    if base_first {
+                        (base_key.clone(), Some(base_value.clone()))
+                    } else {
+                        (k.clone(), v.clone())
+                    }
+                }
+            };
+            self.bound = Bound::Excluded(key.clone());
+            if let Some(value) = value {
+                return Some((key, value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryDB;
+    use crate::engine::{EngineWriteBatch, StorageEngine};
+    use crate::{Direction, IteratorMode};
+
+    fn keys(iter: impl Iterator<Item = (Box<[u8]>, Box<[u8]>)>) -> Vec<Vec<u8>> {
+        iter.map(|(k, _)| k.to_vec()).collect()
+    }
+
+    #[test]
+    fn test_transaction_reads_own_writes() {
+        let db = MemoryDB::open(2);
+        let mut batch = EngineWriteBatch::default();
+        for key in [[1u8], [3], [5]] {
+            batch.put(1, &key, &key).unwrap();
+        }
+        db.write(&batch).unwrap();
+
+        let tx = db.transaction();
+        tx.put(1, &[2], &[2]).unwrap();
+        tx.put(1, &[3], &[33]).unwrap();
+        tx.delete(1, &[5]).unwrap();
+        assert_eq!(tx.get(1, &[3]).unwrap().as_deref(), Some(&[33u8][..]));
+        assert!(tx.get(1, &[5]).unwrap().is_none());
+        // uncommitted writes are invisible to others
+        assert_eq!(db.get(1, &[3]).unwrap().as_deref(), Some(&[3u8][..]));
+
+        let iter = tx.iter(1, IteratorMode::Start).unwrap();
+        assert_eq!(keys(iter), vec![vec![1], vec![2], vec![3]]);
+        let iter = tx.iter(1, IteratorMode::End).unwrap();
+        assert_eq!(keys(iter), vec![vec![3], vec![2], vec![1]]);
+        let iter = tx
+            .iter(1, IteratorMode::From(&[4], Direction::Reverse))
+            .unwrap();
+        assert_eq!(keys(iter), vec![vec![3], vec![2], vec![1]]);
+        let iter = tx
+            .iter(1, IteratorMode::From(&[2], Direction::Forward))
+            .unwrap();
+        assert_eq!(keys(iter), vec![vec![2], vec![3]]);
+
+        tx.commit().unwrap();
+        let iter = db.iter(1, IteratorMode::Start).unwrap();
+        assert_eq!(keys(iter), vec![vec![1], vec![2], vec![3]]);
+        assert!(db.get(2, &[1]).is_err());
+    }
+
+    #[test]
+    fn test_transaction_conflict() {
+        let db = MemoryDB::open(1);
+        let tx_1 = db.transaction();
+        let tx_2 = db.transaction();
+        tx_1.put(0, &[1], &[1]).unwrap();
+        tx_2.put(0, &[1], &[2]).unwrap();
+        tx_2.put(0, &[2], &[2]).unwrap();
+        tx_1.commit().unwrap();
+        assert!(tx_2.commit().is_err());
+        assert_eq!(db.get(0, &[1]).unwrap().as_deref(), Some(&[1u8][..]));
+        assert!(db.get(0, &[2]).unwrap().is_none());
+
+        // rollback discards writes
+        let tx = db.transaction();
+        tx.put(0, &[3], &[3]).unwrap();
+        tx.rollback().unwrap();
+        tx.commit().unwrap();
+        assert!(db.get(0, &[3]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_and_delete_range() {
+        let db = MemoryDB::open(1);
+        let mut batch = EngineWriteBatch::default();
+        for i in 0u8..5 {
+            batch.put(0, &[i], &[i]).unwrap();
+        }
+        db.write(&batch).unwrap();
+        let snapshot = db.snapshot();
+
+        let mut batch = EngineWriteBatch::default();
+        batch.delete_range(0, &[1], &[4]).unwrap();
+        db.write(&batch).unwrap();
+
+        let iter = db.iter(0, IteratorMode::Start).unwrap();
+        assert_eq!(keys(iter), vec![vec![0], vec![4]]);
+        let iter = snapshot.iter(0, IteratorMode::Start).unwrap();
+        assert_eq!(keys(iter).len(), 5);
+        assert_eq!(snapshot.get(0, &[2]).unwrap().as_deref(), Some(&[2u8][..]));
+    }
+}
@@ -0,0 +1,300 @@
+//! Storage engines
+//!
+//! `gw-store` accesses the database through the `StorageEngine` trait, so the engine can be
+//! selected by `StoreConfig::engine`:
+//!
+//! - `rocksdb`: RocksDB `OptimisticTransactionDB`, the default engine
+//! - `memory`: keep all data in memory, useful for tests
+//! - `log`: keep all data in memory and append writes to a log file, the log is replayed when
+//!   the database is opened. It isn't a disk-backed engine, the whole database must fit in RAM.
+
+pub mod log_db;
+pub mod memory;
+mod rocks;
+
+use crate::iter::DBIterItem;
+use crate::migrate::open_or_create_db;
+use crate::schema::{Col, COLUMNS};
+use crate::{CfMemStat, DBPinnableSlice, DBVector, IteratorMode, Result, RocksDB};
+use gw_config::{StoreConfig, StoreEngine};
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+
+pub use self::log_db::LogDB;
+pub use self::memory::MemoryDB;
+
+pub type EngineIter<'a> = Box<dyn Iterator<Item = DBIterItem> + 'a>;
+
+/// A value read from an engine. RocksDB values are pinned in its block cache instead of being
+/// copied, convert it into `Box<[u8]>` only if an owned value is needed.
+pub enum EngineSlice<'a> {
+    Pinned(DBPinnableSlice<'a>),
+    Vector(DBVector),
+    Owned(Box<[u8]>),
+}
+
+impl<'a> Deref for EngineSlice<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            EngineSlice::Pinned(slice) => slice.as_ref(),
+            EngineSlice::Vector(vector) => vector.as_ref(),
+            EngineSlice::Owned(value) => value,
+        }
+    }
+}
+
+impl<'a> AsRef<[u8]> for EngineSlice<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<'a> From<EngineSlice<'a>> for Box<[u8]> {
+    fn from(slice: EngineSlice<'a>) -> Self {
+        match slice {
+            EngineSlice::Owned(value) => value,
+            slice => Box::from(&*slice),
+        }
+    }
+}
+
+/// A seekable cursor over an `EngineIter` created with `IteratorMode::Start`, like RocksDB's
+/// raw iterator.
+///
+/// Scanned entries are buffered, use `IteratorMode::From` to seek in large columns.
+pub struct DBRawIterator<'a> {
+    iter: EngineIter<'a>,
+    buf: Vec<DBIterItem>,
+    exhausted: bool,
+    pos: Option<usize>,
+}
+
+impl<'a> From<EngineIter<'a>> for DBRawIterator<'a> {
+    fn from(iter: EngineIter<'a>) -> Self {
+        DBRawIterator {
+            iter,
+            buf: Vec::new(),
+            exhausted: false,
+            pos: None,
+        }
+    }
+}
+
+impl<'a> DBRawIterator<'a> {
+    pub fn valid(&self) -> bool {
+        self.pos.is_some()
+    }
+
+    pub fn key(&self) -> Option<&[u8]> {
+        self.pos.map(|pos| &self.buf[pos].0[..])
+    }
+
+    pub fn value(&self) -> Option<&[u8]> {
+        self.pos.map(|pos| &self.buf[pos].1[..])
+    }
+
+    /// Seek to the first key greater than or equal to the key.
+    pub fn seek<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = key.as_ref();
+        self.fill_past(key);
+        let index = self.buf.partition_point(|(k, _)| &k[..] < key);
+        self.pos = if index < self.buf.len() {
+            Some(index)
+        } else {
+            None
+        };
+    }
+
+    /// Seek to the last key less than or equal to the key.
+    pub fn seek_for_prev<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = key.as_ref();
+        self.fill_past(key);
+        let index = self.buf.partition_point(|(k, _)| &k[..] <= key);
+        self.pos = index.checked_sub(1);
+    }
+
+    // Buffer entries until a key greater than the key is scanned.
+    fn fill_past(&mut self, key: &[u8]) {
+        while !self.exhausted && self.buf.last().map_or(true, |(k, _)| &k[..] <= key) {
+            match self.iter.next() {
+                Some(item) => {
+                    debug_assert!(self.buf.last().map_or(true, |(k, _)| k < &item.0));
+                    self.buf.push(item);
+                }
+                None => self.exhausted = true,
+            }
+        }
+    }
+}
+
+/// A key-value storage engine with column families.
+pub trait StorageEngine: Send + Sync {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>>;
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>>;
+
+    /// Begin an optimistic transaction, writes are checked for conflicts on commit.
+    fn transaction(&self) -> Box<dyn EngineTransaction>;
+
+    /// A consistent read-only view of the database.
+    fn snapshot(&self) -> Box<dyn EngineSnapshot>;
+
+    /// Write the batch atomically.
+    fn write(&self, batch: &EngineWriteBatch) -> Result<()>;
+
+    fn gather_mem_stats(&self) -> Vec<CfMemStat> {
+        Vec::new()
+    }
+
+    /// Create a consistent checkpoint of the database, the path must not exist.
+    fn create_checkpoint(&self, path: &Path) -> Result<()>;
+}
+
+/// Transaction isolation level is read committed: reads see the writes of this transaction
+/// and the latest committed data.
+pub trait EngineTransaction: Send + Sync {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>>;
+
+    fn put(&self, col: Col, key: &[u8], value: &[u8]) -> Result<()>;
+
+    fn delete(&self, col: Col, key: &[u8]) -> Result<()>;
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>>;
+
+    /// Fails if a key written by this transaction is changed by others since it began.
+    fn commit(&self) -> Result<()>;
+
+    fn rollback(&self) -> Result<()>;
+}
+
+pub trait EngineSnapshot: Send + Sync {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>>;
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOp {
+    Put {
+        col: Col,
+        key: Box<[u8]>,
+        value: Box<[u8]>,
+    },
+    Delete {
+        col: Col,
+        key: Box<[u8]>,
+    },
+    /// Delete keys in the range [from, to)
+    DeleteRange {
+        col: Col,
+        from: Box<[u8]>,
+        to: Box<[u8]>,
+    },
+}
+
+impl BatchOp {
+    pub fn col(&self) -> Col {
+        match self {
+            BatchOp::Put { col, .. }
+            | BatchOp::Delete { col, .. }
+            | BatchOp::DeleteRange { col, .. } => *col,
+        }
+    }
+}
+
+/// Engine independent write batch
+#[derive(Debug, Default, Clone)]
+pub struct EngineWriteBatch {
+    ops: Vec<BatchOp>,
+    size_in_bytes: usize,
+}
+
+impl EngineWriteBatch {
+    pub fn ops(&self) -> &[BatchOp] {
+        &self.ops
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Return the approximate size of the batch (in bytes).
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_in_bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn put(&mut self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        self.push(BatchOp::Put {
+            col,
+            key: key.into(),
+            value: value.into(),
+        });
+        Ok(())
+    }
+
+    pub fn delete(&mut self, col: Col, key: &[u8]) -> Result<()> {
+        self.push(BatchOp::Delete {
+            col,
+            key: key.into(),
+        });
+        Ok(())
+    }
+
+    /// Remove database entries from start key to end key.
+    ///
+    /// Removes the database entries in the range ["begin_key", "end_key"), i.e.,
+    /// including "begin_key" and excluding "end_key". It is not an error if no
+    /// keys exist in the range ["begin_key", "end_key").
+    pub fn delete_range(&mut self, col: Col, from: &[u8], to: &[u8]) -> Result<()> {
+        self.push(BatchOp::DeleteRange {
+            col,
+            from: from.into(),
+            to: to.into(),
+        });
+        Ok(())
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        self.ops.clear();
+        self.size_in_bytes = 0;
+        Ok(())
+    }
+
+    fn push(&mut self, op: BatchOp) {
+        // op tag, column and lengths of key and value
+        const OP_OVERHEAD: usize = 10;
+        self.size_in_bytes += OP_OVERHEAD
+            + match &op {
+                BatchOp::Put { key, value, .. } => key.len() + value.len(),
+                BatchOp::Delete { key, .. } => key.len(),
+                BatchOp::DeleteRange { from, to, .. } => from.len() + to.len(),
+            };
+        self.ops.push(op);
+    }
+}
+
+/// Open the storage engine selected by the config, RocksDB databases are migrated to the
+/// current version.
+pub fn open_engine(config: &StoreConfig) -> Result<Arc<dyn StorageEngine>> {
+    let engine: Arc<dyn StorageEngine> = match config.engine {
+        StoreEngine::RocksDB => Arc::new(open_or_create_db(config)?),
+        StoreEngine::Memory => Arc::new(MemoryDB::open(COLUMNS)),
+        StoreEngine::Log => Arc::new(LogDB::open(&config.path, COLUMNS)?),
+    };
+    Ok(engine)
+}
+
+/// Open the storage engine selected by the config without running migrations.
+pub fn open_engine_with_check(config: &StoreConfig) -> Result<Arc<dyn StorageEngine>> {
+    match config.engine {
+        StoreEngine::RocksDB => Ok(Arc::new(RocksDB::open_with_check(config, COLUMNS)?)),
+        StoreEngine::Memory | StoreEngine::Log => open_engine(config),
+    }
+}
@@ -0,0 +1,90 @@
+//! RocksDB storage engine
+use super::{
+    BatchOp, EngineIter, EngineSlice, EngineSnapshot, EngineTransaction, EngineWriteBatch,
+    StorageEngine,
+};
+use crate::schema::Col;
+use crate::{
+    CfMemStat, DBIterator, IteratorMode, Result, RocksDB, RocksDBSnapshot, RocksDBTransaction,
+};
+use std::path::Path;
+
+impl StorageEngine for RocksDB {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        let value = self.get_pinned(col, key)?;
+        Ok(value.map(EngineSlice::Pinned))
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        let iter = DBIterator::iter(self, col, mode)?;
+        Ok(Box::new(iter))
+    }
+
+    fn transaction(&self) -> Box<dyn EngineTransaction> {
+        Box::new(RocksDB::transaction(self))
+    }
+
+    fn snapshot(&self) -> Box<dyn EngineSnapshot> {
+        Box::new(self.get_snapshot())
+    }
+
+    fn write(&self, batch: &EngineWriteBatch) -> Result<()> {
+        let mut wb = self.new_write_batch();
+        for op in batch.ops() {
+            match op {
+                BatchOp::Put { col, key, value } => wb.put(*col, key, value)?,
+                BatchOp::Delete { col, key } => wb.delete(*col, key)?,
+                BatchOp::DeleteRange { col, from, to } => wb.delete_range(*col, from, to)?,
+            }
+        }
+        RocksDB::write(self, &wb)
+    }
+
+    fn gather_mem_stats(&self) -> Vec<CfMemStat> {
+        RocksDB::gather_mem_stats(self)
+    }
+
+    fn create_checkpoint(&self, path: &Path) -> Result<()> {
+        RocksDB::create_checkpoint(self, path)
+    }
+}
+
+impl EngineTransaction for RocksDBTransaction {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        let value = RocksDBTransaction::get(self, col, key)?;
+        Ok(value.map(EngineSlice::Vector))
+    }
+
+    fn put(&self, col: Col, key: &[u8], value: &[u8]) -> Result<()> {
+        RocksDBTransaction::put(self, col, key, value)
+    }
+
+    fn delete(&self, col: Col, key: &[u8]) -> Result<()> {
+        RocksDBTransaction::delete(self, col, key)
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        let iter = DBIterator::iter(self, col, mode)?;
+        Ok(Box::new(iter))
+    }
+
+    fn commit(&self) -> Result<()> {
+        RocksDBTransaction::commit(self)
+    }
+
+    fn rollback(&self) -> Result<()> {
+        RocksDBTransaction::rollback(self)
+    }
+}
+
+impl EngineSnapshot for RocksDBSnapshot {
+    fn get(&self, col: Col, key: &[u8]) -> Result<Option<EngineSlice<'_>>> {
+        let value = self.get_pinned(col, key)?;
+        Ok(value.map(EngineSlice::Pinned))
+    }
+
+    fn iter(&self, col: Col, mode: IteratorMode) -> Result<EngineIter<'_>> {
+        let iter = DBIterator::iter(self, col, mode)?;
+        Ok(Box::new(iter))
+    }
+}
@@ -1,4 +1,5 @@
 pub mod db;
+pub mod engine;
 pub mod error;
 pub mod iter;
 pub mod memory_stats;
@@ -11,14 +12,15 @@ pub mod write_batch;
 
 // re-exports
 pub use crate::db::RocksDB;
+pub use crate::engine::DBRawIterator;
 pub use crate::iter::DBIterator;
 pub use crate::memory_stats::CfMemStat;
 pub use crate::snapshot::RocksDBSnapshot;
 pub use crate::transaction::{RocksDBTransaction, RocksDBTransactionSnapshot};
 pub use crate::write_batch::RocksDBWriteBatch;
 pub use rocksdb::{
-    self as internal, DBPinnableSlice, DBRawIterator, DBVector, Direction, Error as DBError,
-    IteratorMode, ReadOptions, WriteBatch,
+    self as internal, DBPinnableSlice, DBVector, Direction, Error as DBError, IteratorMode,
+    ReadOptions, WriteBatch,
 };
 
 use error::Error;
@@ -5,13 +5,13 @@ use crate::{
     error::Error,
     read_only_db::{self, ReadOnlyDB},
     schema::{
-        COLUMN_BAD_BLOCK, COLUMN_BLOCK, COLUMN_META, META_LAST_VALID_TIP_BLOCK_HASH_KEY,
-        META_TIP_BLOCK_HASH_KEY, REMOVED_COLUMN_BLOCK_DEPOSIT_REQUESTS,
-        REMOVED_COLUMN_L2BLOCK_COMMITTED_INFO,
+        Col, COLUMN_BAD_BLOCK, COLUMN_BLOCK, COLUMN_META, COLUMN_TRANSACTION_ACCESS_LIST,
+        META_LAST_VALID_TIP_BLOCK_HASH_KEY, META_TIP_BLOCK_HASH_KEY,
+        REMOVED_COLUMN_BLOCK_DEPOSIT_REQUESTS, REMOVED_COLUMN_L2BLOCK_COMMITTED_INFO,
     },
     DBIterator, Result,
 };
-use std::{cmp::Ordering, collections::BTreeMap};
+use std::{cmp::Ordering, collections::BTreeMap, ops::Bound, time::Duration};
 
 use gw_config::StoreConfig;
 
@@ -30,7 +30,8 @@ pub fn open_or_create_db(config: &StoreConfig) -> Result<RocksDB> {
                 eprintln!(
                     "The database is created by a higher version executable binary, \n\
                      so that the current executable binary couldn't open this database.\n\
-                     Please download the latest executable binary."
+                     Please download the latest executable binary, or roll back the database\n\
+                     with `godwoken migrate rollback` of the higher version executable binary."
                 );
                 Err(Error {
                     message: "The database is created by a higher version executable binary"
@@ -39,12 +40,24 @@ pub fn open_or_create_db(config: &StoreConfig) -> Result<RocksDB> {
             }
             Ordering::Equal => Ok(RocksDB::open(config, COLUMNS)),
             Ordering::Less => {
+                let mut db = RocksDB::open(config, COLUMNS);
+                let db_version = get_db_version(&db)?.unwrap_or_default();
+                if factory
+                    .pending_migrations(&db_version)
+                    .any(|migration| migration.expensive())
+                {
+                    eprintln!(
+                        "The database needs expensive migrations, \n\
+                         please run `godwoken migrate run` to migrate it first."
+                    );
+                    return Err(Error {
+                        message: "The database needs expensive migrations".to_string(),
+                    });
+                }
+
                 log::info!("process fast migrations ...");
-                // TODO: Currently, there is only one migration: Add db version.
-                // So we can add db version by running default migration here.
-                // We should stop the process if we have more migrations and use **migration** command to run migration instead.
-                let db = RocksDB::open(config, COLUMNS);
-                let _ = factory.migrate(db)?;
+                factory.migrate(&mut db, &|_| {})?;
+                drop(db);
 
                 Ok(RocksDB::open(config, COLUMNS))
             }
@@ -92,17 +105,44 @@ fn is_non_empty_rdb(db: &ReadOnlyDB) -> bool {
     false
 }
 
+// Rough cost of rewriting a key, used to estimate the time of a migration.
+const ESTIMATED_MICROS_PER_KEY: u64 = 20;
+
 trait Migration {
-    fn migrate(&self, db: RocksDB) -> Result<RocksDB>;
+    /// Migrate the database to this version. `progress` is called with the number of keys
+    /// processed since the last call.
+    fn migrate(&self, db: &mut RocksDB, progress: &dyn Fn(u64)) -> Result<()>;
+    /// Revert the changes of `migrate`, so the database can be opened by the executable
+    /// binary of the previous version.
+    fn migrate_down(&self, db: &mut RocksDB, progress: &dyn Fn(u64)) -> Result<()>;
     // Version can be genereated with: date '+%Y%m%d%H%M%S'
     fn version(&self) -> &str;
     fn expensive(&self) -> bool;
+    /// What the migration changes, reported by dry run.
+    fn description(&self) -> &str;
+    /// Number of keys the migration rewrites, used to estimate time and report progress.
+    fn affected_keys(&self, db: &RocksDB) -> Result<u64>;
+}
+
+// Number of keys in the column, 0 if the column doesn't exist.
+fn count_keys(db: &RocksDB, col: Col) -> Result<u64> {
+    match db.iter(col, rocksdb::IteratorMode::Start) {
+        Ok(iter) => Ok(iter.count() as u64),
+        Err(_) => Ok(0),
+    }
+}
+
+fn has_column(db: &RocksDB, col: Col) -> bool {
+    db.iter(col, rocksdb::IteratorMode::Start).is_ok()
 }
 
 struct DefaultMigration;
 impl Migration for DefaultMigration {
-    fn migrate(&self, db: RocksDB) -> Result<RocksDB> {
-        Ok(db)
+    fn migrate(&self, _db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+        Ok(())
+    }
+    fn migrate_down(&self, _db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+        Ok(())
     }
     #[allow(clippy::needless_return)]
     fn version(&self) -> &str {
@@ -111,12 +151,18 @@ impl Migration for DefaultMigration {
     fn expensive(&self) -> bool {
         false
     }
+    fn description(&self) -> &str {
+        "Add db version"
+    }
+    fn affected_keys(&self, _db: &RocksDB) -> Result<u64> {
+        Ok(0)
+    }
 }
 
 struct DecoupleBlockProducingSubmissionAndConfirmationMigration;
 
 impl Migration for DecoupleBlockProducingSubmissionAndConfirmationMigration {
-    fn migrate(&self, mut db: RocksDB) -> Result<RocksDB> {
+    fn migrate(&self, db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
         if db
             .iter(COLUMN_BLOCK, rocksdb::IteratorMode::Start)?
             .next()
@@ -127,7 +173,12 @@ impl Migration for DecoupleBlockProducingSubmissionAndConfirmationMigration {
 
         db.drop_cf(REMOVED_COLUMN_L2BLOCK_COMMITTED_INFO)?;
         db.drop_cf(REMOVED_COLUMN_BLOCK_DEPOSIT_REQUESTS)?;
-        Ok(db)
+        Ok(())
+    }
+    // The database is empty, the dropped columns are created again when an older executable
+    // binary opens it.
+    fn migrate_down(&self, _db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+        Ok(())
     }
     fn version(&self) -> &str {
         "20220517"
@@ -135,13 +186,20 @@ impl Migration for DecoupleBlockProducingSubmissionAndConfirmationMigration {
     fn expensive(&self) -> bool {
         false
     }
+    fn description(&self) -> &str {
+        "Drop the l2block committed info and block deposit requests columns"
+    }
+    fn affected_keys(&self, db: &RocksDB) -> Result<u64> {
+        Ok(count_keys(db, REMOVED_COLUMN_L2BLOCK_COMMITTED_INFO)?
+            + count_keys(db, REMOVED_COLUMN_BLOCK_DEPOSIT_REQUESTS)?)
+    }
 }
 
 struct BadBlockColumnMigration;
 
-impl Migration for BadBlockColumnMigration {
-    fn migrate(&self, mut db: RocksDB) -> Result<RocksDB> {
-        // Check that there are no bad blocks.
+impl BadBlockColumnMigration {
+    // Check that there are no bad blocks.
+    fn check_no_bad_blocks(db: &RocksDB) -> Result<()> {
         if db
             .get_pinned(COLUMN_META, META_TIP_BLOCK_HASH_KEY)?
             .as_deref()
@@ -155,10 +213,24 @@ impl Migration for BadBlockColumnMigration {
                     .into(),
             );
         }
+        Ok(())
+    }
+}
+
+impl Migration for BadBlockColumnMigration {
+    fn migrate(&self, db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+        Self::check_no_bad_blocks(db)?;
 
         // Clear this reused column.
         db.drop_cf(COLUMN_BAD_BLOCK)?;
-        Ok(db)
+        Ok(())
+    }
+    // Bad blocks are stored in the reused column, clear it for the previous version.
+    fn migrate_down(&self, db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+        Self::check_no_bad_blocks(db)?;
+
+        db.drop_cf(COLUMN_BAD_BLOCK)?;
+        Ok(())
     }
     fn version(&self) -> &str {
         "20221024"
@@ -166,6 +238,159 @@ impl Migration for BadBlockColumnMigration {
     fn expensive(&self) -> bool {
         false
     }
+    fn description(&self) -> &str {
+        "Clear the reused column for bad blocks"
+    }
+    fn affected_keys(&self, db: &RocksDB) -> Result<u64> {
+        count_keys(db, COLUMN_BAD_BLOCK)
+    }
+}
+
+struct TransactionAccessListMigration;
+
+impl Migration for TransactionAccessListMigration {
+    // The column is created when the database is opened with the new columns, create it here in
+    // case the database is opened with the columns of the previous version.
+    fn migrate(&self, db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+        if !has_column(db, COLUMN_TRANSACTION_ACCESS_LIST) {
+            db.create_cf(COLUMN_TRANSACTION_ACCESS_LIST)?;
+        }
+        Ok(())
+    }
+    // The executable binary of the previous version can't open a database with unknown columns.
+    fn migrate_down(&self, db: &mut RocksDB, progress: &dyn Fn(u64)) -> Result<()> {
+        if has_column(db, COLUMN_TRANSACTION_ACCESS_LIST) {
+            let keys = count_keys(db, COLUMN_TRANSACTION_ACCESS_LIST)?;
+            db.drop_cf(COLUMN_TRANSACTION_ACCESS_LIST)?;
+            progress(keys);
+        }
+        Ok(())
+    }
+    fn version(&self) -> &str {
+        "20221115"
+    }
+    fn expensive(&self) -> bool {
+        false
+    }
+    fn description(&self) -> &str {
+        "Add the transaction access list column"
+    }
+    fn affected_keys(&self, db: &RocksDB) -> Result<u64> {
+        count_keys(db, COLUMN_TRANSACTION_ACCESS_LIST)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationDirection {
+    Up,
+    Down,
+}
+
+/// A migration to run, reported by dry run
+#[derive(Debug, Clone)]
+pub struct MigrationPlan {
+    pub version: String,
+    pub direction: MigrationDirection,
+    pub description: String,
+    pub expensive: bool,
+    pub affected_keys: u64,
+    pub estimated_time: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    /// None if the database has no version
+    pub db_version: Option<String>,
+    /// The version of the last migration known by this executable binary
+    pub last_version: Option<String>,
+    pub applied: Vec<String>,
+    pub pending: Vec<String>,
+}
+
+/// Return the version of the database and the migrations applied or pending.
+pub fn migration_status(db: &RocksDB) -> Result<MigrationStatus> {
+    let factory = init_migration_factory();
+    let db_version = get_db_version(db)?;
+    let v = db_version.as_deref().unwrap_or_default();
+    let (applied, pending): (Vec<String>, Vec<String>) = factory
+        .migration_map
+        .keys()
+        .cloned()
+        .partition(|mv| mv.as_str() <= v);
+    Ok(MigrationStatus {
+        last_version: factory.last_db_version().map(ToString::to_string),
+        db_version,
+        applied,
+        pending,
+    })
+}
+
+/// Dry run of `run_migrations`.
+pub fn plan_migrations(db: &RocksDB) -> Result<Vec<MigrationPlan>> {
+    let factory = init_migration_factory();
+    let db_version = get_db_version(db)?.unwrap_or_default();
+    factory
+        .pending_migrations(&db_version)
+        .map(|migration| build_plan(db, migration, MigrationDirection::Up))
+        .collect()
+}
+
+/// Dry run of `rollback_migrations`.
+pub fn plan_rollback(db: &RocksDB, target_version: &str) -> Result<Vec<MigrationPlan>> {
+    let factory = init_migration_factory();
+    let db_version = get_db_version(db)?.unwrap_or_default();
+    let migrations = factory.rollback_migrations(&db_version, target_version)?;
+    migrations
+        .map(|migration| build_plan(db, migration, MigrationDirection::Down))
+        .collect()
+}
+
+/// Run pending migrations. If a migration fails, the migrations already run are rolled back
+/// and the database version is restored.
+pub fn run_migrations(db: &mut RocksDB, progress: &dyn Fn(u64)) -> Result<()> {
+    init_migration_factory().migrate(db, progress)
+}
+
+/// Roll back migrations after the target version, the database can be opened by the
+/// executable binary of the target version afterwards.
+pub fn rollback_migrations(
+    db: &mut RocksDB,
+    target_version: &str,
+    progress: &dyn Fn(u64),
+) -> Result<()> {
+    init_migration_factory().rollback(db, target_version, progress)
+}
+
+fn get_db_version(db: &RocksDB) -> Result<Option<String>> {
+    let version = db
+        .get_pinned_default(MIGRATION_VERSION_KEY)?
+        .map(|v| String::from_utf8(v.to_vec()).expect("version bytes to utf8"));
+    Ok(version)
+}
+
+fn set_db_version(db: &RocksDB, version: Option<&str>) -> Result<()> {
+    match version {
+        Some(v) => db.put_default(MIGRATION_VERSION_KEY, v),
+        None => db.delete_default(MIGRATION_VERSION_KEY),
+    }
+}
+
+fn build_plan(
+    db: &RocksDB,
+    migration: &dyn Migration,
+    direction: MigrationDirection,
+) -> Result<MigrationPlan> {
+    let affected_keys = migration.affected_keys(db)?;
+    Ok(MigrationPlan {
+        version: migration.version().to_string(),
+        direction,
+        description: migration.description().to_string(),
+        expensive: migration.expensive(),
+        affected_keys,
+        estimated_time: Duration::from_micros(
+            affected_keys.saturating_mul(ESTIMATED_MICROS_PER_KEY),
+        ),
+    })
 }
 
 struct MigrationFactory {
@@ -179,6 +404,7 @@ fn init_migration_factory() -> MigrationFactory {
     factory.insert(Box::new(
         DecoupleBlockProducingSubmissionAndConfirmationMigration,
     ));
+    factory.insert(Box::new(TransactionAccessListMigration));
     factory
 }
 
@@ -193,26 +419,95 @@ impl MigrationFactory {
             .insert(migration.version().to_string(), migration);
     }
 
-    fn migrate(&self, db: RocksDB) -> Result<RocksDB> {
-        let db_version = db
-            .get_pinned_default(MIGRATION_VERSION_KEY)?
-            .map(|v| String::from_utf8(v.to_vec()).expect("version bytes to utf8"))
-            .unwrap_or_else(|| "".to_string());
-        let mut db = db;
-        let v = db_version.as_str();
-        let mut last_version = None;
-        for (mv, migration) in &self.migration_map {
-            let mv = mv.as_str();
-            if mv > v {
-                db = migration.migrate(db)?;
-                last_version = Some(mv);
+    fn pending_migrations<'a>(
+        &'a self,
+        db_version: &'a str,
+    ) -> impl Iterator<Item = &'a dyn Migration> + 'a {
+        self.migration_map
+            .iter()
+            .filter(move |(mv, _)| mv.as_str() > db_version)
+            .map(|(_, migration)| migration.as_ref())
+    }
+
+    /// Migrations to roll back from the db version to the target version, in reverse order.
+    fn rollback_migrations<'a>(
+        &'a self,
+        db_version: &'a str,
+        target_version: &'a str,
+    ) -> Result<impl Iterator<Item = &'a dyn Migration> + 'a> {
+        if !self.migration_map.contains_key(target_version) {
+            return Err(format!("Unknown db version {}", target_version).into());
+        }
+        if target_version > db_version {
+            return Err(format!(
+                "Cannot roll back to version {} from a lower version {}",
+                target_version, db_version
+            )
+            .into());
+        }
+        Ok(self
+            .migration_map
+            .iter()
+            .rev()
+            .filter(move |(mv, _)| mv.as_str() > target_version && mv.as_str() <= db_version)
+            .map(|(_, migration)| migration.as_ref()))
+    }
+
+    fn migrate(&self, db: &mut RocksDB, progress: &dyn Fn(u64)) -> Result<()> {
+        let db_version = get_db_version(db)?;
+        let v = db_version.as_deref().unwrap_or_default();
+        let mut done: Vec<&dyn Migration> = Vec::new();
+        for migration in self.pending_migrations(v) {
+            log::info!("Migrating db to version {}", migration.version());
+            if let Err(err) = migration.migrate(db, progress) {
+                log::error!(
+                    "Migrate db to version {} failed: {}, rolling back",
+                    migration.version(),
+                    err
+                );
+                // Revert the partial changes of the failed migration first.
+                if let Err(err) = migration.migrate_down(db, progress) {
+                    log::error!("Roll back version {} failed: {}", migration.version(), err);
+                }
+                for migration in done.iter().rev() {
+                    migration.migrate_down(db, progress)?;
+                }
+                set_db_version(db, db_version.as_deref())?;
+                return Err(err);
             }
+            // Save the version after each migration, so an interrupted run resumes from it.
+            db.put_default(MIGRATION_VERSION_KEY, migration.version())?;
+            done.push(migration);
         }
-        if let Some(v) = last_version {
-            db.put_default(MIGRATION_VERSION_KEY, v)?;
-            log::info!("Current db version is: {}", v);
+        if let Some(migration) = done.last() {
+            log::info!("Current db version is: {}", migration.version());
         }
-        Ok(db)
+        Ok(())
+    }
+
+    fn rollback(
+        &self,
+        db: &mut RocksDB,
+        target_version: &str,
+        progress: &dyn Fn(u64),
+    ) -> Result<()> {
+        let db_version = get_db_version(db)?.unwrap_or_default();
+        let migrations: Vec<_> = self
+            .rollback_migrations(&db_version, target_version)?
+            .collect();
+        for migration in migrations {
+            log::info!("Rolling back db version {}", migration.version());
+            migration.migrate_down(db, progress)?;
+            // Version before this migration
+            let prev_version = self
+                .migration_map
+                .range::<str, _>((Bound::Unbounded, Bound::Excluded(migration.version())))
+                .next_back()
+                .map(|(mv, _)| mv.as_str());
+            set_db_version(db, prev_version)?;
+        }
+        log::info!("Current db version is: {}", target_version);
+        Ok(())
     }
 
     fn last_db_version(&self) -> Option<&str> {
@@ -228,11 +523,39 @@ mod tests {
     use gw_config::StoreConfig;
 
     use crate::{
-        schema::{COLUMNS, MIGRATION_VERSION_KEY},
+        schema::{COLUMNS, COLUMN_TRANSACTION_ACCESS_LIST, MIGRATION_VERSION_KEY},
         RocksDB,
     };
 
-    use super::{init_migration_factory, open_or_create_db};
+    use super::{
+        get_db_version, has_column, init_migration_factory, open_or_create_db, plan_migrations,
+        plan_rollback, rollback_migrations, DefaultMigration, Migration, MigrationDirection,
+        MigrationFactory, TransactionAccessListMigration,
+    };
+
+    struct FailedMigration;
+
+    impl Migration for FailedMigration {
+        fn migrate(&self, _db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+            Err("failed".to_string().into())
+        }
+        fn migrate_down(&self, _db: &mut RocksDB, _progress: &dyn Fn(u64)) -> Result<()> {
+            Ok(())
+        }
+        fn version(&self) -> &str {
+            "20991231000000"
+        }
+        fn expensive(&self) -> bool {
+            false
+        }
+        fn description(&self) -> &str {
+            "Always fail"
+        }
+        fn affected_keys(&self, _db: &RocksDB) -> Result<u64> {
+            Ok(0)
+        }
+    }
+
     #[test]
     fn test_migration() -> Result<()> {
         let dir = tempfile::tempdir().expect("create temp dir");
@@ -242,15 +565,15 @@ mod tests {
             options: HashMap::new(),
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
-        let old_db = RocksDB::open(&config, COLUMNS);
+        let mut db = RocksDB::open(&config, COLUMNS);
         let factory = init_migration_factory();
         assert!(factory.last_db_version().is_some());
 
-        let db = factory.migrate(old_db);
+        let result = factory.migrate(&mut db, &|_| {});
 
-        assert!(db.is_ok());
-        let db = db.unwrap();
+        assert!(result.is_ok());
         let v = db
             .get_pinned_default(MIGRATION_VERSION_KEY)?
             .map(|v| String::from_utf8(v.to_vec()));
@@ -268,6 +591,7 @@ mod tests {
             options: HashMap::new(),
             options_file: None,
             cache_size: None,
+            ..Default::default()
         };
         let db = open_or_create_db(&config)?;
         let v = db.get_pinned_default(MIGRATION_VERSION_KEY)?;
@@ -281,4 +605,80 @@ mod tests {
         assert_eq!(v, Some(Ok(factory.last_db_version().unwrap().to_string())));
         Ok(())
     }
+
+    #[test]
+    fn test_rollback_migration() -> Result<()> {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = StoreConfig {
+            path: dir.path().to_owned(),
+            ..Default::default()
+        };
+        let mut db = open_or_create_db(&config)?;
+        let factory = init_migration_factory();
+        let last_version = factory.last_db_version().unwrap().to_string();
+        assert!(plan_migrations(&db)?.is_empty());
+
+        let first_version = DefaultMigration.version();
+        let plans = plan_rollback(&db, first_version)?;
+        assert_eq!(plans.len(), factory.migration_map.len() - 1);
+        assert_eq!(plans[0].version, last_version);
+        assert!(plans
+            .iter()
+            .all(|p| p.direction == MigrationDirection::Down));
+        // dry run doesn't change the version
+        assert_eq!(get_db_version(&db)?, Some(last_version.clone()));
+
+        rollback_migrations(&mut db, first_version, &|_| {})?;
+        assert_eq!(get_db_version(&db)?, Some(first_version.to_string()));
+        assert_eq!(plan_migrations(&db)?.len(), plans.len());
+
+        // unknown or higher versions are rejected
+        assert!(rollback_migrations(&mut db, "1", &|_| {}).is_err());
+        assert!(rollback_migrations(&mut db, &last_version, &|_| {}).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_migration_restores_version() -> Result<()> {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = StoreConfig {
+            path: dir.path().to_owned(),
+            ..Default::default()
+        };
+        let mut db = RocksDB::open(&config, COLUMNS);
+        let mut factory = MigrationFactory::create();
+        factory.insert(Box::new(DefaultMigration));
+        factory.insert(Box::new(FailedMigration));
+
+        assert!(factory.migrate(&mut db, &|_| {}).is_err());
+        assert_eq!(get_db_version(&db)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_access_list_migration() -> Result<()> {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = StoreConfig {
+            path: dir.path().to_owned(),
+            ..Default::default()
+        };
+        let mut db = open_or_create_db(&config)?;
+        {
+            // the transaction holds the db, drop it before dropping the column
+            let tx = db.transaction();
+            tx.put(COLUMN_TRANSACTION_ACCESS_LIST, &[0u8; 32], &[])?;
+            tx.commit()?;
+        }
+
+        let migration = TransactionAccessListMigration;
+        assert_eq!(migration.affected_keys(&db)?, 1);
+        migration.migrate_down(&mut db, &|_| {})?;
+        assert!(!has_column(&db, COLUMN_TRANSACTION_ACCESS_LIST));
+        assert_eq!(migration.affected_keys(&db)?, 0);
+
+        migration.migrate(&mut db, &|_| {})?;
+        assert!(has_column(&db, COLUMN_TRANSACTION_ACCESS_LIST));
+        assert_eq!(migration.affected_keys(&db)?, 0);
+        Ok(())
+    }
 }
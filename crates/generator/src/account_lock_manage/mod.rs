@@ -10,7 +10,9 @@ use gw_types::{
 #[cfg(any(debug_assertions, feature = "enable-always-success-lock"))]
 pub mod always_success;
 pub mod eip712;
+pub mod multisig;
 pub mod secp256k1;
+pub mod webauthn;
 
 use crate::error::LockAlgorithmError;
 
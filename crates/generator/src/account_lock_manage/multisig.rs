@@ -0,0 +1,423 @@
+//! M-of-N multisig account lock
+//!
+//! Lock args: rollup_script_hash(32 bytes) | multisig_hash(20 bytes)
+//!
+//! Multisig script:
+//! reserved(1 byte, must be 0) | require_first_n(1 byte) | threshold(1 byte) |
+//! pubkeys_cnt(1 byte) | eth_address(20 bytes) * pubkeys_cnt
+//!
+//! multisig_hash = blake2b(multisig script)[..20]
+//!
+//! Signature: multisig script | secp256k1 recoverable signature(65 bytes) * threshold
+//!
+//! The signing message is the same as the `Secp256k1Eth` lock.
+
+use super::secp256k1::Secp256k1Eth;
+use super::LockAlgorithm;
+use crate::error::LockAlgorithmError;
+use gw_common::blake2b::new_blake2b;
+use gw_common::registry_address::RegistryAddress;
+use gw_common::H256;
+use gw_types::offchain::RollupContext;
+use gw_types::packed::WithdrawalRequestExtra;
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    packed::{L2Transaction, Script},
+};
+
+pub const MULTISIG_HEADER_SIZE: usize = 4;
+pub const ETH_ADDRESS_SIZE: usize = 20;
+pub const SIGNATURE_SIZE: usize = 65;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigScript {
+    pub require_first_n: u8,
+    pub threshold: u8,
+    pub addresses: Vec<[u8; 20]>,
+}
+
+impl MultisigScript {
+    pub fn new(
+        require_first_n: u8,
+        threshold: u8,
+        addresses: Vec<[u8; 20]>,
+    ) -> Result<Self, LockAlgorithmError> {
+        let script = MultisigScript {
+            require_first_n,
+            threshold,
+            addresses,
+        };
+        script.check()?;
+        Ok(script)
+    }
+
+    /// Parse multisig script from the head of `data`, returns the script and the remain bytes
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), LockAlgorithmError> {
+        if data.len() < MULTISIG_HEADER_SIZE {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Multisig: invalid script header".to_string(),
+            ));
+        }
+        if data[0] != 0 {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Multisig: invalid reserved field".to_string(),
+            ));
+        }
+        let require_first_n = data[1];
+        let threshold = data[2];
+        let pubkeys_cnt = data[3] as usize;
+        let script_size = MULTISIG_HEADER_SIZE + ETH_ADDRESS_SIZE * pubkeys_cnt;
+        if data.len() < script_size {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Multisig: invalid script length".to_string(),
+            ));
+        }
+        let addresses = data[MULTISIG_HEADER_SIZE..script_size]
+            .chunks_exact(ETH_ADDRESS_SIZE)
+            .map(|addr| {
+                let mut buf = [0u8; 20];
+                buf.copy_from_slice(addr);
+                buf
+            })
+            .collect();
+        let script = Self::new(require_first_n, threshold, addresses)?;
+        Ok((script, &data[script_size..]))
+    }
+
+    fn check(&self) -> Result<(), LockAlgorithmError> {
+        let pubkeys_cnt = self.addresses.len();
+        if pubkeys_cnt == 0 || pubkeys_cnt > u8::MAX as usize {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Multisig: invalid pubkeys count {}",
+                pubkeys_cnt
+            )));
+        }
+        if self.threshold == 0 || self.threshold as usize > pubkeys_cnt {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Multisig: invalid threshold {} of {}",
+                self.threshold, pubkeys_cnt
+            )));
+        }
+        if self.require_first_n > self.threshold {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Multisig: invalid require_first_n {}",
+                self.require_first_n
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf =
+            Vec::with_capacity(MULTISIG_HEADER_SIZE + ETH_ADDRESS_SIZE * self.addresses.len());
+        buf.push(0);
+        buf.push(self.require_first_n);
+        buf.push(self.threshold);
+        buf.push(self.addresses.len() as u8);
+        for addr in &self.addresses {
+            buf.extend_from_slice(addr);
+        }
+        buf
+    }
+
+    /// The 20 bytes multisig hash used in the lock args
+    pub fn hash(&self) -> [u8; 20] {
+        let mut hasher = new_blake2b();
+        hasher.update(&self.serialize());
+        let mut buf = [0u8; 32];
+        hasher.finalize(&mut buf);
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&buf[..20]);
+        hash
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Secp256k1Multisig {
+    secp256k1: Secp256k1Eth,
+}
+
+impl Secp256k1Multisig {
+    fn verify_alone(
+        &self,
+        lock_args: Bytes,
+        signature: Bytes,
+        message: H256,
+    ) -> Result<(), LockAlgorithmError> {
+        if lock_args.len() != 52 {
+            return Err(LockAlgorithmError::InvalidLockArgs);
+        }
+
+        let multisig_hash = self.recover(message, signature.as_ref())?;
+        if multisig_hash.as_ref() != &lock_args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Multisig: Mismatch multisig hash".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl LockAlgorithm for Secp256k1Multisig {
+    /// Returns multisig hash if the signatures reach the threshold
+    fn recover(&self, message: H256, signature: &[u8]) -> Result<Bytes, LockAlgorithmError> {
+        let (script, signatures) = MultisigScript::parse(signature)?;
+        let threshold = script.threshold as usize;
+        if signatures.len() != SIGNATURE_SIZE * threshold {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Multisig: expect {} signatures, got {} bytes",
+                threshold,
+                signatures.len()
+            )));
+        }
+
+        let mut signed = vec![false; script.addresses.len()];
+        for sig in signatures.chunks_exact(SIGNATURE_SIZE) {
+            let eth_address = self.secp256k1.recover(message, sig)?;
+            let index = script
+                .addresses
+                .iter()
+                .position(|addr| addr == eth_address.as_ref())
+                .ok_or_else(|| {
+                    LockAlgorithmError::InvalidSignature(format!(
+                        "Multisig: unknown signer 0x{}",
+                        hex::encode(&eth_address)
+                    ))
+                })?;
+            if signed[index] {
+                return Err(LockAlgorithmError::InvalidSignature(format!(
+                    "Multisig: duplicate signer 0x{}",
+                    hex::encode(&eth_address)
+                )));
+            }
+            signed[index] = true;
+        }
+
+        let require_first_n = script.require_first_n as usize;
+        if signed[..require_first_n].iter().any(|signed| !signed) {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Multisig: the first {} signers are required",
+                require_first_n
+            )));
+        }
+
+        Ok(Bytes::copy_from_slice(&script.hash()))
+    }
+
+    fn verify_tx(
+        &self,
+        ctx: &RollupContext,
+        sender_address: RegistryAddress,
+        sender_script: Script,
+        receiver_script: Script,
+        tx: L2Transaction,
+    ) -> Result<(), LockAlgorithmError> {
+        let message =
+            Secp256k1Eth::tx_signing_message(ctx, sender_address, &receiver_script, &tx.raw())?;
+        self.verify_alone(
+            sender_script.args().unpack(),
+            tx.signature().unpack(),
+            message,
+        )
+    }
+
+    fn verify_withdrawal(
+        &self,
+        ctx: &RollupContext,
+        sender_script: Script,
+        withdrawal: &WithdrawalRequestExtra,
+        address: RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        let message = Secp256k1Eth::withdrawal_signing_message(ctx, withdrawal, address)?;
+        self.verify_alone(
+            sender_script.args().unpack(),
+            withdrawal.request().signature().unpack(),
+            message,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account_lock_manage::secp256k1::SECP256K1;
+    use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+    use gw_types::packed::{RawL2Transaction, RollupConfig};
+    use secp256k1::{PublicKey, SecretKey};
+    use sha3::{Digest, Keccak256};
+
+    fn privkey(n: u8) -> SecretKey {
+        SecretKey::from_slice(&[n; 32]).expect("privkey")
+    }
+
+    fn eth_address(privkey: &SecretKey) -> [u8; 20] {
+        let pubkey = PublicKey::from_secret_key(&SECP256K1, privkey);
+        let mut hasher = Keccak256::new();
+        hasher.update(&pubkey.serialize_uncompressed()[1..]);
+        let buf = hasher.finalize();
+        let mut addr = [0u8; 20];
+        addr.copy_from_slice(&buf[12..]);
+        addr
+    }
+
+    fn sign(privkey: &SecretKey, message: &H256) -> [u8; 65] {
+        let msg = secp256k1::Message::from_slice(message.as_slice()).expect("message");
+        let (rec_id, data) = SECP256K1
+            .sign_recoverable(&msg, privkey)
+            .serialize_compact();
+        let mut sig = [0u8; 65];
+        sig[..64].copy_from_slice(&data);
+        sig[64] = rec_id.to_i32() as u8;
+        sig
+    }
+
+    fn multisig_signature(script: &MultisigScript, signers: &[u8], message: &H256) -> Vec<u8> {
+        let mut signature = script.serialize();
+        for n in signers {
+            signature.extend_from_slice(&sign(&privkey(*n), message));
+        }
+        signature
+    }
+
+    fn script_2_of_3(require_first_n: u8) -> MultisigScript {
+        let addresses = (1..=3).map(|n| eth_address(&privkey(n))).collect();
+        MultisigScript::new(require_first_n, 2, addresses).expect("multisig script")
+    }
+
+    #[test]
+    fn test_multisig_recover() {
+        let script = script_2_of_3(0);
+        let message = H256::from([42u8; 32]);
+        let multisig = Secp256k1Multisig::default();
+
+        for signers in [[1, 2], [2, 3], [3, 1]] {
+            let signature = multisig_signature(&script, &signers, &message);
+            let hash = multisig.recover(message, &signature).expect("recover");
+            assert_eq!(hash.as_ref(), &script.hash());
+        }
+    }
+
+    #[test]
+    fn test_multisig_below_threshold() {
+        let script = script_2_of_3(0);
+        let message = H256::from([42u8; 32]);
+        let signature = multisig_signature(&script, &[1], &message);
+        let err = Secp256k1Multisig::default()
+            .recover(message, &signature)
+            .unwrap_err();
+        assert!(matches!(err, LockAlgorithmError::InvalidSignature(_)));
+    }
+
+    #[test]
+    fn test_multisig_duplicate_signer() {
+        let script = script_2_of_3(0);
+        let message = H256::from([42u8; 32]);
+        let signature = multisig_signature(&script, &[1, 1], &message);
+        let err = Secp256k1Multisig::default()
+            .recover(message, &signature)
+            .unwrap_err();
+        match err {
+            LockAlgorithmError::InvalidSignature(msg) => assert!(msg.contains("duplicate")),
+            err => panic!("unexpected error {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_multisig_unknown_signer() {
+        let script = script_2_of_3(0);
+        let message = H256::from([42u8; 32]);
+        let signature = multisig_signature(&script, &[1, 4], &message);
+        let err = Secp256k1Multisig::default()
+            .recover(message, &signature)
+            .unwrap_err();
+        assert!(matches!(err, LockAlgorithmError::InvalidSignature(_)));
+    }
+
+    #[test]
+    fn test_multisig_require_first_n() {
+        let script = script_2_of_3(1);
+        let message = H256::from([42u8; 32]);
+        let multisig = Secp256k1Multisig::default();
+
+        let signature = multisig_signature(&script, &[3, 1], &message);
+        multisig.recover(message, &signature).expect("recover");
+
+        let signature = multisig_signature(&script, &[2, 3], &message);
+        multisig.recover(message, &signature).unwrap_err();
+    }
+
+    #[test]
+    fn test_multisig_invalid_script() {
+        let addresses = vec![[1u8; 20], [2u8; 20]];
+        MultisigScript::new(0, 3, addresses.clone()).unwrap_err();
+        MultisigScript::new(0, 0, addresses.clone()).unwrap_err();
+        MultisigScript::new(2, 1, addresses).unwrap_err();
+        MultisigScript::new(0, 1, vec![]).unwrap_err();
+    }
+
+    #[test]
+    fn test_multisig_verify_tx() {
+        let chain_id = 42u64;
+        let script = script_2_of_3(0);
+        let rollup_type_hash = [0u8; 32];
+
+        let sender_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![7u8; 20]);
+        let mut sender_args = rollup_type_hash.to_vec();
+        sender_args.extend_from_slice(&script.hash());
+        let sender_script = Script::new_builder()
+            .args(Bytes::from(sender_args).pack())
+            .build();
+        let mut receiver_args = rollup_type_hash.to_vec();
+        receiver_args.extend_from_slice(&[3u8; 20]);
+        let receiver_script = Script::new_builder()
+            .args(Bytes::from(receiver_args).pack())
+            .build();
+
+        let ctx = RollupContext {
+            rollup_script_hash: Default::default(),
+            rollup_config: RollupConfig::new_builder()
+                .chain_id(chain_id.pack())
+                .build(),
+        };
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(chain_id.pack())
+            .from_id(3u32.pack())
+            .to_id(4u32.pack())
+            .nonce(1u32.pack())
+            .args(vec![1u8, 2, 3].pack())
+            .build();
+        let message = Secp256k1Eth::tx_signing_message(
+            &ctx,
+            sender_address.clone(),
+            &receiver_script,
+            &raw_tx,
+        )
+        .expect("signing message");
+        let signature = multisig_signature(&script, &[2, 3], &message);
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx)
+            .signature(signature.pack())
+            .build();
+
+        let multisig = Secp256k1Multisig::default();
+        multisig
+            .verify_tx(
+                &ctx,
+                sender_address,
+                sender_script.clone(),
+                receiver_script.clone(),
+                tx.clone(),
+            )
+            .expect("verify tx");
+
+        // sign with another multisig script
+        let other_address = RegistryAddress::new(ETH_REGISTRY_ACCOUNT_ID, vec![7u8; 20]);
+        let other = MultisigScript::new(0, 1, vec![eth_address(&privkey(1))]).unwrap();
+        let signature = multisig_signature(&other, &[1], &message);
+        let tx = tx.as_builder().signature(signature.pack()).build();
+        multisig
+            .verify_tx(&ctx, other_address, sender_script, receiver_script, tx)
+            .unwrap_err();
+    }
+}
@@ -84,6 +84,76 @@ impl Secp256k1Eth {
         }
     }
 
+    /// Signing message of a layer2 transaction
+    ///
+    /// Polyjuice transactions are signed in the Ethereum RLP format,
+    /// other transactions are signed in the EIP-712 format.
+    pub fn tx_signing_message(
+        ctx: &RollupContext,
+        sender_address: RegistryAddress,
+        receiver_script: &Script,
+        raw_tx: &RawL2Transaction,
+    ) -> Result<H256, LockAlgorithmError> {
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        let chain_id = raw_tx.chain_id().unpack();
+        // Non EIP-155 transaction's chain_id is zero.
+        // We support non EIP-155 for the compatibility.
+        // Related issue: https://github.com/nervosnetwork/godwoken/issues/775
+        let is_protected = raw_tx.is_chain_id_protected();
+        // check protected chain id
+        if is_protected && expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidTransactionArgs);
+        }
+        if let Ok(message) =
+            Self::polyjuice_tx_signing_message(expected_chain_id, raw_tx, receiver_script)
+        {
+            return Ok(message);
+        }
+
+        // Try verify transaction with EIP-712 message
+        // Reject transaction without chain_id protection
+        if !is_protected {
+            return Err(LockAlgorithmError::InvalidTransactionArgs);
+        }
+
+        let to_script_hash = receiver_script.hash().into();
+        Self::eip712_signing_message(chain_id, raw_tx, sender_address, to_script_hash).map_err(
+            |err| {
+                LockAlgorithmError::InvalidSignature(format!(
+                    "Invalid l2 transaction format {}",
+                    err
+                ))
+            },
+        )
+    }
+
+    /// Signing message of a withdrawal request in the EIP-712 format
+    pub fn withdrawal_signing_message(
+        ctx: &RollupContext,
+        withdrawal: &WithdrawalRequestExtra,
+        address: RegistryAddress,
+    ) -> Result<H256, LockAlgorithmError> {
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        let chain_id = withdrawal.raw().chain_id().unpack();
+        if expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Invalid chain id {} expected {}",
+                chain_id, expected_chain_id
+            )));
+        }
+        let typed_message = Withdrawal::from_raw(
+            withdrawal.raw(),
+            withdrawal.owner_lock(),
+            address,
+        )
+        .map_err(|err| {
+            LockAlgorithmError::InvalidSignature(format!("Invalid withdrawal format {}", err))
+        })?;
+        let message =
+            typed_message.eip712_message(Self::domain_with_chain_id(chain_id).hash_struct());
+        Ok(message.into())
+    }
+
     fn verify_alone(
         &self,
         lock_args: Bytes,
@@ -150,53 +220,11 @@ impl LockAlgorithm for Secp256k1Eth {
         receiver_script: Script,
         tx: L2Transaction,
     ) -> Result<(), LockAlgorithmError> {
-        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
-        let chain_id = tx.raw().chain_id().unpack();
-        // Non EIP-155 transaction's chain_id is zero.
-        // We support non EIP-155 for the compatibility.
-        // Related issue: https://github.com/nervosnetwork/godwoken/issues/775
-        let is_protected = tx.raw().is_chain_id_protected();
-        // check protected chain id
-        if is_protected && expected_chain_id != chain_id {
-            return Err(LockAlgorithmError::InvalidTransactionArgs);
-        }
-        if let Some(rlp_data) = try_assemble_polyjuice_args(&tx.raw(), &receiver_script) {
-            let mut hasher = Keccak256::new();
-            hasher.update(&rlp_data);
-            let signing_message: [u8; 32] = hasher.finalize().into();
-            let signing_message = H256::from(signing_message);
-            self.verify_alone(
-                sender_script.args().unpack(),
-                tx.signature().unpack(),
-                signing_message,
-            )?;
-            return Ok(());
-        }
-
-        // Try verify transaction with EIP-712 message
-        // Reject transaction without chain_id protection
-        if !is_protected {
-            return Err(LockAlgorithmError::InvalidTransactionArgs);
-        }
-
-        let raw_tx = tx.raw();
-        let chain_id = raw_tx.chain_id().unpack();
-
-        let to_script_hash = receiver_script.hash().into();
-
-        let typed_tx = crate::account_lock_manage::eip712::types::L2Transaction::from_raw(
-            &raw_tx,
-            sender_address,
-            to_script_hash,
-        )
-        .map_err(|err| {
-            LockAlgorithmError::InvalidSignature(format!("Invalid l2 transaction format {}", err))
-        })?;
-        let message = typed_tx.eip712_message(Self::domain_with_chain_id(chain_id).hash_struct());
+        let message = Self::tx_signing_message(ctx, sender_address, &receiver_script, &tx.raw())?;
         self.verify_alone(
             sender_script.args().unpack(),
             tx.signature().unpack(),
-            message.into(),
+            message,
         )?;
         Ok(())
     }
@@ -208,28 +236,11 @@ impl LockAlgorithm for Secp256k1Eth {
         withdrawal: &WithdrawalRequestExtra,
         address: RegistryAddress,
     ) -> Result<(), LockAlgorithmError> {
-        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
-        let chain_id = withdrawal.raw().chain_id().unpack();
-        if expected_chain_id != chain_id {
-            return Err(LockAlgorithmError::InvalidSignature(format!(
-                "Invalid chain id {} expected {}",
-                chain_id, expected_chain_id
-            )));
-        }
-        let typed_message = Withdrawal::from_raw(
-            withdrawal.raw(),
-            withdrawal.owner_lock(),
-            address,
-        )
-        .map_err(|err| {
-            LockAlgorithmError::InvalidSignature(format!("Invalid withdrawal format {}", err))
-        })?;
-        let message =
-            typed_message.eip712_message(Self::domain_with_chain_id(chain_id).hash_struct());
+        let message = Self::withdrawal_signing_message(ctx, withdrawal, address)?;
         self.verify_alone(
             sender_script.args().unpack(),
             withdrawal.request().signature().unpack(),
-            message.into(),
+            message,
         )?;
         Ok(())
     }
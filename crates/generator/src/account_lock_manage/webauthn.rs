@@ -0,0 +1,439 @@
+//! WebAuthn (passkey) account lock, assertions are signed with secp256r1 (P-256)
+//!
+//! Lock args: rollup_script_hash(32 bytes) | pubkey_hash(20 bytes)
+//!
+//! pubkey_hash = blake2b(uncompressed P-256 pubkey)[..20]
+//!
+//! Signature: molecule `WebAuthnSignature`
+//!
+//! The authenticator signs `authenticator_data | sha256(client_data_json)`, the
+//! client_data_json is rebuilt from the signing message and the
+//! `client_data_json_suffix`, see `client_data_json`. The on-chain lock rebuilds it
+//! in the same way, so there is no JSON parsing on either side.
+
+use super::LockAlgorithm;
+use crate::error::LockAlgorithmError;
+use gw_common::blake2b::new_blake2b;
+use gw_common::registry_address::RegistryAddress;
+use gw_common::H256;
+use gw_types::offchain::RollupContext;
+use gw_types::packed::{WebAuthnSignatureReader, WithdrawalRequestExtra};
+use gw_types::prelude::*;
+use gw_types::{
+    bytes::Bytes,
+    packed::{L2Transaction, Script},
+};
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED};
+
+pub const PUBKEY_SIZE: usize = 65;
+pub const SIGNATURE_SIZE: usize = 64;
+/// rp_id_hash(32 bytes) | flags(1 byte) | sign_count(4 bytes)
+pub const MIN_AUTHENTICATOR_DATA_SIZE: usize = 37;
+/// User present flag of the authenticator data
+pub const FLAG_USER_PRESENT: u8 = 0x01;
+/// Browsers serialize `type` and `challenge` first, in this exact form
+/// https://www.w3.org/TR/webauthn-2/#clientdatajson-serialization
+pub const CLIENT_DATA_JSON_PREFIX: &[u8] = br#"{"type":"webauthn.get","challenge":""#;
+
+/// The `challenge` field of client data
+pub fn encode_challenge(message: &H256) -> String {
+    base64::encode_config(message.as_slice(), base64::URL_SAFE_NO_PAD)
+}
+
+/// Rebuild the client data JSON signed by the authenticator
+pub fn client_data_json(message: &H256, client_data_json_suffix: &[u8]) -> Vec<u8> {
+    let challenge = encode_challenge(message);
+    let mut buf = Vec::with_capacity(
+        CLIENT_DATA_JSON_PREFIX.len() + challenge.len() + 1 + client_data_json_suffix.len(),
+    );
+    buf.extend_from_slice(CLIENT_DATA_JSON_PREFIX);
+    buf.extend_from_slice(challenge.as_bytes());
+    buf.push(b'"');
+    buf.extend_from_slice(client_data_json_suffix);
+    buf
+}
+
+/// The 20 bytes pubkey hash used in the lock args
+pub fn pubkey_hash(pubkey: &[u8]) -> [u8; 20] {
+    let mut hasher = new_blake2b();
+    hasher.update(pubkey);
+    let mut buf = [0u8; 32];
+    hasher.finalize(&mut buf);
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&buf[..20]);
+    hash
+}
+
+#[derive(Debug, Default)]
+pub struct WebAuthn;
+
+impl WebAuthn {
+    fn verify_alone(
+        &self,
+        lock_args: Bytes,
+        signature: Bytes,
+        message: H256,
+    ) -> Result<(), LockAlgorithmError> {
+        if lock_args.len() != 52 {
+            return Err(LockAlgorithmError::InvalidLockArgs);
+        }
+
+        let pubkey_hash = self.recover(message, signature.as_ref())?;
+        if pubkey_hash.as_ref() != &lock_args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "WebAuthn: Mismatch pubkey hash".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_chain_id(ctx: &RollupContext, chain_id: u64) -> Result<(), LockAlgorithmError> {
+        let expected_chain_id = ctx.rollup_config.chain_id().unpack();
+        if expected_chain_id != chain_id {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "Invalid chain id {} expected {}",
+                chain_id, expected_chain_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl LockAlgorithm for WebAuthn {
+    /// Returns pubkey hash if the assertion is valid
+    fn recover(&self, message: H256, signature: &[u8]) -> Result<Bytes, LockAlgorithmError> {
+        let sig = WebAuthnSignatureReader::from_slice(signature).map_err(|err| {
+            LockAlgorithmError::InvalidSignature(format!("WebAuthn: invalid signature {}", err))
+        })?;
+        let pubkey = sig.pubkey().raw_data();
+        let signature = sig.signature().raw_data();
+        let authenticator_data = sig.authenticator_data().raw_data();
+        if pubkey.len() != PUBKEY_SIZE || signature.len() != SIGNATURE_SIZE {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "WebAuthn: pubkey length is {}, signature length is {}",
+                pubkey.len(),
+                signature.len()
+            )));
+        }
+
+        // check authenticator data
+        if authenticator_data.len() < MIN_AUTHENTICATOR_DATA_SIZE {
+            return Err(LockAlgorithmError::InvalidSignature(format!(
+                "WebAuthn: authenticator data length is {}, expect at least {}",
+                authenticator_data.len(),
+                MIN_AUTHENTICATOR_DATA_SIZE
+            )));
+        }
+        let flags = authenticator_data[32];
+        if flags & FLAG_USER_PRESENT == 0 {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "WebAuthn: user not present".to_string(),
+            ));
+        }
+
+        // verify signature
+        let client_data_json = client_data_json(&message, sig.client_data_json_suffix().raw_data());
+        let client_data_hash = ring::digest::digest(&ring::digest::SHA256, &client_data_json);
+        let mut signed_data = authenticator_data.to_vec();
+        signed_data.extend_from_slice(client_data_hash.as_ref());
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, pubkey)
+            .verify(&signed_data, signature)
+            .map_err(|_| {
+                LockAlgorithmError::InvalidSignature(
+                    "WebAuthn: failed to verify signature".to_string(),
+                )
+            })?;
+
+        Ok(Bytes::copy_from_slice(&pubkey_hash(pubkey)))
+    }
+
+    fn verify_tx(
+        &self,
+        ctx: &RollupContext,
+        _sender_address: RegistryAddress,
+        sender_script: Script,
+        receiver_script: Script,
+        tx: L2Transaction,
+    ) -> Result<(), LockAlgorithmError> {
+        let raw_tx = tx.raw();
+        Self::check_chain_id(ctx, raw_tx.chain_id().unpack())?;
+        let message = raw_tx.calc_message(
+            &ctx.rollup_script_hash,
+            &sender_script.hash().into(),
+            &receiver_script.hash().into(),
+        );
+        self.verify_alone(
+            sender_script.args().unpack(),
+            tx.signature().unpack(),
+            message,
+        )
+    }
+
+    fn verify_withdrawal(
+        &self,
+        ctx: &RollupContext,
+        sender_script: Script,
+        withdrawal: &WithdrawalRequestExtra,
+        _address: RegistryAddress,
+    ) -> Result<(), LockAlgorithmError> {
+        let raw = withdrawal.raw();
+        Self::check_chain_id(ctx, raw.chain_id().unpack())?;
+        let message = raw.calc_message(&ctx.rollup_script_hash);
+        self.verify_alone(
+            sender_script.args().unpack(),
+            withdrawal.request().signature().unpack(),
+            message,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
+    use gw_types::packed::{RawL2Transaction, RollupConfig, WebAuthnSignature};
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+
+    /// Software authenticator
+    struct Credential {
+        key_pair: EcdsaKeyPair,
+        rng: SystemRandom,
+    }
+
+    impl Credential {
+        fn generate() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+                .expect("generate credential");
+            let key_pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref())
+                    .expect("credential");
+            Credential { key_pair, rng }
+        }
+
+        fn pubkey(&self) -> &[u8] {
+            self.key_pair.public_key().as_ref()
+        }
+
+        /// Sign `signed_client_data_json`, the signature carries `client_data_json_suffix`
+        fn assert_with(
+            &self,
+            flags: u8,
+            signed_client_data_json: &[u8],
+            client_data_json_suffix: &[u8],
+        ) -> Vec<u8> {
+            let mut authenticator_data = [0u8; MIN_AUTHENTICATOR_DATA_SIZE].to_vec();
+            authenticator_data[..32].copy_from_slice(&[7u8; 32]);
+            authenticator_data[32] = flags;
+            authenticator_data[33..].copy_from_slice(&1u32.to_be_bytes());
+
+            let client_data_hash =
+                ring::digest::digest(&ring::digest::SHA256, signed_client_data_json);
+            let mut signed_data = authenticator_data.clone();
+            signed_data.extend_from_slice(client_data_hash.as_ref());
+            let signature = self.key_pair.sign(&self.rng, &signed_data).expect("sign");
+
+            WebAuthnSignature::new_builder()
+                .pubkey(Bytes::copy_from_slice(self.pubkey()).pack())
+                .signature(Bytes::copy_from_slice(signature.as_ref()).pack())
+                .authenticator_data(Bytes::from(authenticator_data).pack())
+                .client_data_json_suffix(Bytes::copy_from_slice(client_data_json_suffix).pack())
+                .build()
+                .as_slice()
+                .to_vec()
+        }
+
+        fn assert(&self, message: &H256) -> Vec<u8> {
+            let suffix = SUFFIX.as_bytes();
+            self.assert_with(
+                FLAG_USER_PRESENT,
+                &client_data_json(message, suffix),
+                suffix,
+            )
+        }
+    }
+
+    const SUFFIX: &str = r#","origin":"https://example.com","crossOrigin":false}"#;
+
+    #[test]
+    fn test_webauthn_recover() {
+        let credential = Credential::generate();
+        let message = H256::from([42u8; 32]);
+        let signature = credential.assert(&message);
+        let hash = WebAuthn::default()
+            .recover(message, &signature)
+            .expect("recover");
+        assert_eq!(hash.as_ref(), &pubkey_hash(credential.pubkey()));
+    }
+
+    #[test]
+    fn test_webauthn_mismatch_challenge() {
+        let credential = Credential::generate();
+        let signature = credential.assert(&H256::from([1u8; 32]));
+        let err = WebAuthn::default()
+            .recover(H256::from([2u8; 32]), &signature)
+            .unwrap_err();
+        assert!(matches!(err, LockAlgorithmError::InvalidSignature(_)));
+    }
+
+    #[test]
+    fn test_webauthn_invalid_client_data() {
+        let credential = Credential::generate();
+        let message = H256::from([42u8; 32]);
+        let challenge = encode_challenge(&message);
+
+        // wrong type
+        let json = format!(
+            r#"{{"type":"webauthn.create","challenge":"{}"{}"#,
+            challenge, SUFFIX
+        );
+        let signature = credential.assert_with(
+            FLAG_USER_PRESENT,
+            json.as_bytes(),
+            SUFFIX.as_bytes(),
+        );
+        WebAuthn::default()
+            .recover(message, &signature)
+            .unwrap_err();
+
+        // not the canonical serialization
+        let json = format!(
+            r#"{{"type": "webauthn.get", "challenge": "{}"{}"#,
+            challenge, SUFFIX
+        );
+        let signature = credential.assert_with(
+            FLAG_USER_PRESENT,
+            json.as_bytes(),
+            SUFFIX.as_bytes(),
+        );
+        WebAuthn::default()
+            .recover(message, &signature)
+            .unwrap_err();
+
+        // user not present
+        let suffix = SUFFIX.as_bytes();
+        let signature = credential.assert_with(0, &client_data_json(&message, suffix), suffix);
+        WebAuthn::default()
+            .recover(message, &signature)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_webauthn_tampered_signature() {
+        let credential = Credential::generate();
+        let message = H256::from([42u8; 32]);
+        let signature = WebAuthnSignature::from_slice(&credential.assert(&message)).unwrap();
+
+        // flip a bit of the authenticator data sign count
+        let mut authenticator_data = signature.authenticator_data().raw_data().to_vec();
+        authenticator_data[33] ^= 1;
+        let tampered = signature
+            .clone()
+            .as_builder()
+            .authenticator_data(Bytes::from(authenticator_data).pack())
+            .build();
+        WebAuthn::default()
+            .recover(message, tampered.as_slice())
+            .unwrap_err();
+
+        // append to the client data
+        let mut suffix = signature.client_data_json_suffix().raw_data().to_vec();
+        suffix.push(b' ');
+        let tampered = signature
+            .as_builder()
+            .client_data_json_suffix(Bytes::from(suffix).pack())
+            .build();
+        WebAuthn::default()
+            .recover(message, tampered.as_slice())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_webauthn_invalid_signature_format() {
+        let credential = Credential::generate();
+        let message = H256::from([42u8; 32]);
+        let signature = credential.assert(&message);
+
+        // trailing bytes
+        let mut invalid = signature.clone();
+        invalid.push(0);
+        WebAuthn::default().recover(message, &invalid).unwrap_err();
+
+        // wrong pubkey length
+        let signature = WebAuthnSignature::from_slice(&signature).unwrap();
+        let invalid = signature
+            .as_builder()
+            .pubkey(Bytes::copy_from_slice(&credential.pubkey()[1..]).pack())
+            .build();
+        WebAuthn::default()
+            .recover(message, invalid.as_slice())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_webauthn_verify_tx() {
+        let chain_id = 42u64;
+        let credential = Credential::generate();
+        let rollup_script_hash = H256::from([5u8; 32]);
+
+        let mut sender_args = rollup_script_hash.as_slice().to_vec();
+        sender_args.extend_from_slice(&pubkey_hash(credential.pubkey()));
+        let sender_script = Script::new_builder()
+            .args(Bytes::from(sender_args).pack())
+            .build();
+        let sender_address = RegistryAddress::new(
+            ETH_REGISTRY_ACCOUNT_ID,
+            pubkey_hash(credential.pubkey()).to_vec(),
+        );
+        let mut receiver_args = rollup_script_hash.as_slice().to_vec();
+        receiver_args.extend_from_slice(&[3u8; 20]);
+        let receiver_script = Script::new_builder()
+            .args(Bytes::from(receiver_args).pack())
+            .build();
+
+        let ctx = RollupContext {
+            rollup_script_hash,
+            rollup_config: RollupConfig::new_builder()
+                .chain_id(chain_id.pack())
+                .build(),
+        };
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(chain_id.pack())
+            .from_id(3u32.pack())
+            .to_id(4u32.pack())
+            .nonce(1u32.pack())
+            .args(vec![1u8, 2, 3].pack())
+            .build();
+        let message = raw_tx.calc_message(
+            &rollup_script_hash,
+            &sender_script.hash().into(),
+            &receiver_script.hash().into(),
+        );
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx.clone())
+            .signature(credential.assert(&message).pack())
+            .build();
+
+        let webauthn = WebAuthn::default();
+        webauthn
+            .verify_tx(
+                &ctx,
+                sender_address.clone(),
+                sender_script.clone(),
+                receiver_script.clone(),
+                tx,
+            )
+            .expect("verify tx");
+
+        // replay the signature with another nonce
+        let raw_tx = raw_tx.as_builder().nonce(2u32.pack()).build();
+        let tx = L2Transaction::new_builder()
+            .raw(raw_tx)
+            .signature(credential.assert(&message).pack())
+            .build();
+        webauthn
+            .verify_tx(&ctx, sender_address, sender_script, receiver_script, tx)
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,346 @@
+//! Persistent cache of AOT compiled backend generators
+//!
+//! Cache files are keyed by the backend code hash, the generator checksum, the
+//! VM version and the ckb-vm version. The code in a file is only accepted if its
+//! hash, bound to the code hash and the generator checksum of the backend being
+//! loaded, matches. A file that fails to decode or doesn't match is removed and the
+//! generator is compiled again.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use gw_common::{blake2b::new_blake2b, H256};
+use gw_types::bytes::Bytes;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use crate::{
+    backend_manage::Backend,
+    types::vm::{AotCode, VMVersion},
+};
+
+/// Must be kept in sync with the ckb-vm version in Cargo.toml
+const CKB_VM_VERSION: &str = "0.21.3";
+const CACHE_FILE_MAGIC: &[u8; 8] = b"GWAOTC02";
+
+/// Identifies a compiled generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    /// On-chain code hash of the backend, accounts of the backend use it as the script code hash
+    code_hash: H256,
+    generator_checksum: H256,
+    vm_version: u32,
+}
+
+impl CacheKey {
+    fn new(backend: &Backend, vm_version: u32) -> Self {
+        CacheKey {
+            code_hash: backend.validator_script_type_hash,
+            generator_checksum: backend.checksum.generator,
+            vm_version,
+        }
+    }
+}
+
+/// Compiled code of a generator, the lock is held while loading or compiling it
+type AotCodeSlot = Arc<Mutex<Option<Arc<AotCode>>>>;
+
+#[derive(Default)]
+pub(crate) struct AotCodeCache {
+    dir: Option<PathBuf>,
+    codes: RwLock<HashMap<CacheKey, AotCodeSlot>>,
+}
+
+impl AotCodeCache {
+    pub(crate) fn new(dir: Option<PathBuf>) -> Self {
+        AotCodeCache {
+            dir,
+            codes: Default::default(),
+        }
+    }
+
+    /// Get AOT code of the backend generator, load it from the cache dir or compile it on the
+    /// first call.
+    ///
+    /// Only callers of the same generator wait for the compilation, the map of codes is never
+    /// locked while compiling.
+    pub(crate) fn get_or_load(&self, backend: &Backend, vm_version: u32) -> Option<Arc<AotCode>> {
+        let key = CacheKey::new(backend, vm_version);
+        let slot = self.codes.read().expect("aot codes").get(&key).cloned();
+        let slot = match slot {
+            Some(slot) => slot,
+            None => {
+                let mut codes = self.codes.write().expect("aot codes");
+                Arc::clone(codes.entry(key).or_default())
+            }
+        };
+
+        let mut slot = slot.lock().expect("aot code");
+        if let Some(ref code) = *slot {
+            return Some(Arc::clone(code));
+        }
+        match self.load_or_compile(&key, &backend.generator) {
+            Ok(code) => {
+                let code = Arc::new(code);
+                *slot = Some(Arc::clone(&code));
+                Some(code)
+            }
+            Err(err) => {
+                log::error!(
+                    "AOT compile generator {} with VMVersion::V{}: {:#}",
+                    hex::encode(key.generator_checksum.as_slice()),
+                    vm_version,
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    fn load_or_compile(&self, key: &CacheKey, generator: &Bytes) -> Result<AotCode> {
+        // never load or compile code for a generator which doesn't match the backend
+        ensure!(
+            blake2b_hash(generator) == key.generator_checksum.as_slice(),
+            "generator doesn't match checksum"
+        );
+        let dir = match self.dir {
+            Some(ref dir) => dir,
+            None => return aot_compile(generator, key.vm_version),
+        };
+        let path = cache_file_path(dir, key);
+        if path.exists() {
+            match read_cache_file(&path, key) {
+                Ok(code) => {
+                    log::info!("Load AotCode from {}", path.display());
+                    return Ok(code);
+                }
+                Err(err) => {
+                    log::warn!("Invalidate AOT cache {}: {:#}", path.display(), err);
+                    if let Err(err) = fs::remove_file(&path) {
+                        log::warn!("Remove AOT cache {}: {}", path.display(), err);
+                    }
+                }
+            }
+        }
+
+        let code = aot_compile(generator, key.vm_version)?;
+        // the cache is an optimization, compiled code is still usable if we fail to save it
+        if let Err(err) = write_cache_file(dir, &path, key, &code) {
+            log::warn!("Save AOT cache {}: {:#}", path.display(), err);
+        }
+        Ok(code)
+    }
+}
+
+fn aot_compile(code_bytes: &Bytes, vm_version: u32) -> Result<AotCode> {
+    log::info!("Compile AotCode with VMVersion::V{}", vm_version);
+    let vm_version = match vm_version {
+        0 => VMVersion::V0,
+        1 => VMVersion::V1,
+        ver => bail!("Unsupport VMVersion: {}", ver),
+    };
+    let mut aot_machine = ckb_vm::machine::aot::AotCompilingMachine::load(
+        code_bytes,
+        Some(Box::new(crate::vm_cost_model::instruction_cycles)),
+        vm_version.vm_isa(),
+        vm_version.vm_version(),
+    )
+    .map_err(|err| anyhow!("load generator: {:?}", err))?;
+    aot_machine
+        .compile()
+        .map_err(|err| anyhow!("compile generator: {:?}", err))
+}
+
+fn cache_file_path(dir: &Path, key: &CacheKey) -> PathBuf {
+    dir.join(format!(
+        "{}-{}-v{}-ckb-vm-{}.aot",
+        hex::encode(key.code_hash.as_slice()),
+        hex::encode(key.generator_checksum.as_slice()),
+        key.vm_version,
+        CKB_VM_VERSION
+    ))
+}
+
+fn blake2b_hash(data: &[u8]) -> [u8; 32] {
+    let mut hasher = new_blake2b();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// Hash of the compiled code bound to the backend it is compiled from
+///
+/// It's computed from the key of the backend being loaded, not the key in the file header.
+fn code_hash(key: &CacheKey, payload: &[u8]) -> [u8; 32] {
+    let mut hasher = new_blake2b();
+    hasher.update(key.code_hash.as_slice());
+    hasher.update(key.generator_checksum.as_slice());
+    hasher.update(&key.vm_version.to_le_bytes());
+    hasher.update(CKB_VM_VERSION.as_bytes());
+    hasher.update(payload);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+// Layout: magic | ckb-vm version len(u32) | ckb-vm version | backend code hash |
+// generator checksum | vm version(u32) | code hash | payload
+//
+// Payload: code len(u64) | code | labels count(u64) | (pc(u64), offset(u64))*
+fn encode_cache_file(key: &CacheKey, code: &AotCode) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(code.code.len() + code.labels.len() * 16 + 16);
+    payload.extend_from_slice(&(code.code.len() as u64).to_le_bytes());
+    payload.extend_from_slice(&code.code[..]);
+    payload.extend_from_slice(&(code.labels.len() as u64).to_le_bytes());
+    for (pc, offset) in code.labels.iter() {
+        payload.extend_from_slice(&pc.to_le_bytes());
+        payload.extend_from_slice(&u64::from(*offset).to_le_bytes());
+    }
+
+    let mut buf = Vec::with_capacity(payload.len() + 160);
+    buf.extend_from_slice(CACHE_FILE_MAGIC);
+    buf.extend_from_slice(&(CKB_VM_VERSION.len() as u32).to_le_bytes());
+    buf.extend_from_slice(CKB_VM_VERSION.as_bytes());
+    buf.extend_from_slice(key.code_hash.as_slice());
+    buf.extend_from_slice(key.generator_checksum.as_slice());
+    buf.extend_from_slice(&key.vm_version.to_le_bytes());
+    buf.extend_from_slice(&code_hash(key, &payload));
+    buf.extend_from_slice(&payload);
+    buf
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        ensure!(self.buf.len() >= len, "unexpected end of file");
+        let (data, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(data)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
+
+fn decode_cache_file(buf: &[u8], key: &CacheKey) -> Result<AotCode> {
+    let mut reader = Reader { buf };
+    ensure!(reader.take(8)? == CACHE_FILE_MAGIC, "invalid magic");
+    let version_len = reader.u32()? as usize;
+    ensure!(
+        reader.take(version_len)? == CKB_VM_VERSION.as_bytes(),
+        "ckb-vm version mismatch"
+    );
+    ensure!(
+        reader.take(32)? == key.code_hash.as_slice(),
+        "backend code hash mismatch"
+    );
+    ensure!(
+        reader.take(32)? == key.generator_checksum.as_slice(),
+        "generator checksum mismatch"
+    );
+    ensure!(reader.u32()? == key.vm_version, "vm version mismatch");
+    let hash = reader.take(32)?;
+    ensure!(hash == code_hash(key, reader.buf), "code hash mismatch");
+
+    let code_len = reader.u64()? as usize;
+    let code = reader.take(code_len)?;
+    let labels_count = reader.u64()? as usize;
+    ensure!(
+        reader.buf.len() == labels_count.saturating_mul(16),
+        "invalid labels"
+    );
+    let mut labels = HashMap::with_capacity(labels_count);
+    for _ in 0..labels_count {
+        let pc = reader.u64()?;
+        let offset = reader.u64()?;
+        labels.insert(pc, offset.try_into()?);
+    }
+
+    // The AOT code emitted by ckb-vm only uses relative addressing, it can be mapped anywhere.
+    let mut mmap = mapr::MmapMut::map_anon(code.len())?;
+    mmap.copy_from_slice(code);
+    let code = mmap.make_exec()?;
+    Ok(AotCode { code, labels })
+}
+
+fn read_cache_file(path: &Path, key: &CacheKey) -> Result<AotCode> {
+    let buf = fs::read(path)?;
+    decode_cache_file(&buf, key)
+}
+
+fn write_cache_file(dir: &Path, path: &Path, key: &CacheKey, code: &AotCode) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("create dir {}", dir.display()))?;
+    // write to a temporary file first, so a crash never leaves a partial cache file
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, encode_cache_file(key, code))?;
+    fs::rename(&tmp_path, path)?;
+    log::info!("Save AotCode to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the file format doesn't care about the content of the code
+    fn dummy_aot_code() -> AotCode {
+        let mut mmap = mapr::MmapMut::map_anon(16).unwrap();
+        mmap.copy_from_slice(&[0xc3; 16]);
+        let mut labels = HashMap::new();
+        labels.insert(0x10000, 0u32);
+        labels.insert(0x10004, 8u32);
+        AotCode {
+            code: mmap.make_exec().unwrap(),
+            labels,
+        }
+    }
+
+    fn cache_key(code_hash: u8, generator_checksum: u8, vm_version: u32) -> CacheKey {
+        CacheKey {
+            code_hash: [code_hash; 32].into(),
+            generator_checksum: [generator_checksum; 32].into(),
+            vm_version,
+        }
+    }
+
+    #[test]
+    fn test_aot_cache_file() {
+        let key = cache_key(41, 42, 1);
+        let code = dummy_aot_code();
+        let buf = encode_cache_file(&key, &code);
+
+        let decoded = decode_cache_file(&buf, &key).unwrap();
+        assert_eq!(&decoded.code[..], &code.code[..]);
+        assert_eq!(decoded.labels, code.labels);
+
+        // mismatched key
+        assert!(decode_cache_file(&buf, &cache_key(40, 42, 1)).is_err());
+        assert!(decode_cache_file(&buf, &cache_key(41, 43, 1)).is_err());
+        assert!(decode_cache_file(&buf, &cache_key(41, 42, 0)).is_err());
+
+        // corrupted
+        let mut corrupted = buf.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(decode_cache_file(&corrupted, &key).is_err());
+        assert!(decode_cache_file(&buf[..buf.len() - 1], &key).is_err());
+
+        // a file rewritten for another backend is rejected even if its header is patched
+        let other_key = cache_key(40, 42, 1);
+        let mut forged = encode_cache_file(&other_key, &code);
+        let header_len = 8 + 4 + CKB_VM_VERSION.len();
+        forged[header_len..header_len + 32].copy_from_slice(key.code_hash.as_slice());
+        assert!(decode_cache_file(&forged, &key).is_err());
+    }
+}
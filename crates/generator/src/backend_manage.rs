@@ -2,10 +2,15 @@ use anyhow::{bail, Context, Result};
 use gw_common::{blake2b::new_blake2b, H256};
 use gw_config::{BackendConfig, BackendSwitchConfig, BackendType};
 use gw_types::bytes::Bytes;
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
 
 #[cfg(has_asm)]
-use crate::types::vm::AotCode;
+use crate::{aot_cache::AotCodeCache, types::vm::AotCode};
 
 #[derive(Default, Clone)]
 pub struct BackendCheckSum {
@@ -70,46 +75,45 @@ impl Backend {
     }
 }
 
+/// Backends of a switch height, shared with the executing transactions
+pub type Backends = Arc<HashMap<H256, Arc<Backend>>>;
+
 #[derive(Default)]
 pub struct BackendManage {
-    backend_switches: Vec<(u64, HashMap<H256, Backend>)>,
+    /// backend switches can be registered at runtime by backend upgrades
+    backend_switches: RwLock<Vec<(u64, Backends)>>,
     /// define here not in backends,
     /// so we don't need to implement the trait `Clone` of AotCode
     #[cfg(has_asm)]
-    aot_codes: (HashMap<H256, AotCode>, HashMap<H256, AotCode>),
+    aot_codes: AotCodeCache,
 }
 
 impl BackendManage {
     pub fn from_config(configs: Vec<BackendSwitchConfig>) -> Result<Self> {
-        let mut backend_manage: BackendManage = Default::default();
+        let backend_manage: BackendManage = Default::default();
         for config in configs {
-            backend_manage.register_backend_switch(config, true)?;
+            backend_manage.register_backend_switch(config)?;
         }
 
         Ok(backend_manage)
     }
 
-    fn register_backend_switch(
-        &mut self,
-        config: BackendSwitchConfig,
-        #[allow(unused_variables)] compile: bool,
-    ) -> Result<()> {
-        if let Some((height, _backends)) = self.backend_switches.last() {
-            if config.switch_height <= *height {
-                bail!("BackendSwitchConfig with switch_height {} is less or equals to the last switch_height {}", config.switch_height, height);
-            }
+    /// Persist AOT compiled generators in the dir, generators are compiled in memory if it's None
+    #[allow(unused_variables, unused_mut)]
+    pub fn with_aot_cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        #[cfg(has_asm)]
+        {
+            self.aot_codes = AotCodeCache::new(dir);
         }
-        // inherit backends
-        let mut backends = self
-            .backend_switches
-            .last()
-            .map(|(_height, backends)| backends)
-            .cloned()
-            .unwrap_or_default();
+        self
+    }
 
+    /// Register a backend switch, the switch_height must be greater than the last one
+    pub fn register_backend_switch(&self, config: BackendSwitchConfig) -> Result<()> {
         let switch_height = config.switch_height;
 
-        // register backends
+        // load binaries before taking the lock
+        let mut new_backends = Vec::with_capacity(config.backends.len());
         for config in config.backends {
             let BackendConfig {
                 validator_path,
@@ -137,11 +141,23 @@ impl BackendManage {
                 validator,
                 generator,
             );
-            #[cfg(has_asm)]
-            if compile {
-                self.compile_backend(&backend);
+            new_backends.push(Arc::new(backend));
+        }
+
+        let mut backend_switches = self.backend_switches.write().expect("backend switches");
+        if let Some((height, _backends)) = backend_switches.last() {
+            if switch_height <= *height {
+                bail!("BackendSwitchConfig with switch_height {} is less or equals to the last switch_height {}", switch_height, height);
             }
+        }
+        // inherit backends
+        let mut backends = backend_switches
+            .last()
+            .map(|(_height, backends)| HashMap::clone(backends))
+            .unwrap_or_default();
 
+        // register backends
+        for backend in new_backends {
             log::debug!(
                 "registry backend {:?}({:?}) at height {}",
                 backend.backend_type,
@@ -152,36 +168,48 @@ impl BackendManage {
             backends.insert(backend.validator_script_type_hash, backend);
         }
 
-        self.backend_switches.push((config.switch_height, backends));
+        backend_switches.push((switch_height, Arc::new(backends)));
         Ok(())
     }
 
-    #[cfg(has_asm)]
-    fn compile_backend(&mut self, backend: &Backend) {
-        self.aot_codes.0.insert(
-            backend.checksum.generator,
-            self.aot_compile(&backend.generator, 0)
-                .expect("Ahead-of-time compile"),
-        );
-        self.aot_codes.1.insert(
-            backend.checksum.generator,
-            self.aot_compile(&backend.generator, 1)
-                .expect("Ahead-of-time compile"),
-        );
+    /// Register backend switches saved by backend upgrades, switches not higher than the last
+    /// switch_height are skipped since they have been merged into the config.
+    pub fn register_saved_backend_switches(&self, path: &Path) -> Result<()> {
+        for config in load_registered_backend_switches(path)? {
+            match self.last_switch_height() {
+                Some(height) if config.switch_height <= height => {
+                    log::info!(
+                        "skip saved backend switch at height {}, already in the config",
+                        config.switch_height
+                    );
+                }
+                _ => self.register_backend_switch(config)?,
+            }
+        }
+        Ok(())
     }
 
-    pub fn get_backends_at_height(
-        &self,
-        block_number: u64,
-    ) -> Option<&(u64, HashMap<H256, Backend>)> {
-        self.backend_switches
+    /// Height of the last registered backend switch
+    pub fn last_switch_height(&self) -> Option<u64> {
+        let backend_switches = self.backend_switches.read().expect("backend switches");
+        backend_switches.last().map(|(height, _)| *height)
+    }
+
+    pub fn get_backends_at_height(&self, block_number: u64) -> Option<(u64, Backends)> {
+        let backend_switches = self.backend_switches.read().expect("backend switches");
+        backend_switches
             .iter()
             .rev()
             .find(|(height, _)| block_number >= *height)
+            .map(|(height, backends)| (*height, Arc::clone(backends)))
     }
 
-    pub fn get_backend(&self, block_number: u64, code_hash: &H256) -> Option<&Backend> {
-        self.get_backends_at_height(block_number)
+    pub fn get_backend(&self, block_number: u64, code_hash: &H256) -> Option<Arc<Backend>> {
+        let backend_switches = self.backend_switches.read().expect("backend switches");
+        backend_switches
+            .iter()
+            .rev()
+            .find(|(height, _)| block_number >= *height)
             .and_then(|(_number, backends)| backends.get(code_hash))
             .map(|backend| {
                 log::debug!(
@@ -190,46 +218,52 @@ impl BackendManage {
                     backend.checksum,
                     block_number
                 );
-                backend
+                Arc::clone(backend)
             })
     }
 
-    #[cfg(has_asm)]
-    fn aot_compile(&self, code_bytes: &Bytes, vm_version: u32) -> Result<AotCode, ckb_vm::Error> {
-        log::info!("Compile AotCode with VMVersion::V{}", vm_version);
-        let vm_version = match vm_version {
-            0 => crate::types::vm::VMVersion::V0,
-            1 => crate::types::vm::VMVersion::V1,
-            ver => panic!("Unsupport VMVersion: {}", ver),
-        };
-        let mut aot_machine = ckb_vm::machine::aot::AotCompilingMachine::load(
-            code_bytes,
-            Some(Box::new(crate::vm_cost_model::instruction_cycles)),
-            vm_version.vm_isa(),
-            vm_version.vm_version(),
-        )?;
-        aot_machine.compile()
-    }
-
     /// get aot_code according to special VM version
+    ///
+    /// The generator is loaded from the AOT cache or compiled on the first call.
     #[cfg(has_asm)]
-    pub(crate) fn get_aot_code(&self, code_hash: &H256, vm_version: u32) -> Option<&AotCode> {
+    pub(crate) fn get_aot_code(&self, backend: &Backend, vm_version: u32) -> Option<Arc<AotCode>> {
         log::debug!(
             "get_aot_code hash: {} version: {}",
-            hex::encode(code_hash.as_slice()),
+            hex::encode(backend.checksum.generator.as_slice()),
             vm_version
         );
-        match vm_version {
-            0 => self.aot_codes.0.get(code_hash),
-            1 => self.aot_codes.1.get(code_hash),
-            ver => {
-                log::error!("Unsupport VMVersion: {}", ver);
-                None
-            }
-        }
+        self.aot_codes.get_or_load(backend, vm_version)
     }
 }
 
+/// Load backend switches registered at runtime
+///
+/// The file is a cache of the switches announced on L1, a missing file is empty.
+pub fn load_registered_backend_switches(path: &Path) -> Result<Vec<BackendSwitchConfig>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read(path)
+        .with_context(|| format!("load backend switches from {}", path.to_string_lossy()))?;
+    let switches = serde_json::from_slice(&content)
+        .with_context(|| format!("parse backend switches from {}", path.to_string_lossy()))?;
+    Ok(switches)
+}
+
+/// Save backend switches registered at runtime
+pub fn save_registered_backend_switches(
+    path: &Path,
+    switches: &[BackendSwitchConfig],
+) -> Result<()> {
+    let content = serde_json::to_vec_pretty(switches)?;
+    // write to a temporary file first, so a crash never leaves a partial file
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("save backend switches to {}", path.to_string_lossy()))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use gw_config::{BackendConfig, BackendSwitchConfig, BackendType};
@@ -238,7 +272,7 @@ mod tests {
 
     #[test]
     fn test_get_backend() {
-        let mut m = BackendManage::default();
+        let m = BackendManage::default();
         // prepare fake binaries
         let dir = tempfile::tempdir().unwrap().into_path();
         let sudt_v0 = dir.join("sudt_v0");
@@ -267,7 +301,7 @@ mod tests {
                 },
             ],
         };
-        m.register_backend_switch(config, false).unwrap();
+        m.register_backend_switch(config).unwrap();
         assert!(m.get_backends_at_height(0).is_none(), "no backends at 0");
         assert!(
             m.get_backend(1, &[42u8; 32].into()).is_some(),
@@ -307,7 +341,7 @@ mod tests {
                 },
             ],
         };
-        m.register_backend_switch(config, false).unwrap();
+        m.register_backend_switch(config).unwrap();
         assert!(m.get_backends_at_height(0).is_none(), "no backends at 0");
         // sudt
         assert_eq!(
@@ -5,11 +5,13 @@ use std::{
 };
 
 use crate::{
-    account_lock_manage::AccountLockManage,
+    account_lock_manage::{AccountLockManage, LockAlgorithm},
     backend_manage::BackendManage,
     constants::{L2TX_MAX_CYCLES, MAX_READ_DATA_BYTES_LIMIT, MAX_WRITE_DATA_BYTES_LIMIT},
     error::{BlockError, TransactionValidateError, WithdrawalError},
+    profile::CyclesProfiler,
     syscalls::RunContext,
+    trace::SyscallTracer,
     typed_transaction::types::TypedRawTransaction,
     types::vm::VMVersion,
     utils::{get_polyjuice_creator_id, get_tx_type},
@@ -40,11 +42,13 @@ use gw_traits::{ChainView, CodeStore};
 use gw_types::{
     bytes::Bytes,
     core::{ChallengeTargetType, ScriptHashType},
-    offchain::{CycleMeter, RollupContext, RunResult},
+    offchain::{
+        BatchWithdrawalSignature, CycleMeter, RollupContext, RunResult, SponsoredSignature,
+    },
     packed::{
         AccountMerkleState, BlockInfo, ChallengeTarget, DepositInfoVec, L2Block, L2Transaction,
-        LogItem, RawL2Block, RawL2Transaction, TxReceipt, WithdrawalReceipt,
-        WithdrawalRequestExtra,
+        LogItem, RawL2Block, RawL2Transaction, RawWithdrawalRequest, Script, TxAccessList,
+        TxReceipt, WithdrawalReceipt, WithdrawalRequestExtra,
     },
     prelude::*,
 };
@@ -67,6 +71,7 @@ pub enum ApplyBlockResult {
         withdrawal_receipts: Vec<WithdrawalReceipt>,
         prev_txs_state: AccountMerkleState,
         tx_receipts: Vec<TxReceipt>,
+        tx_access_lists: Vec<TxAccessList>,
         offchain_used_cycles: u64,
     },
     Challenge {
@@ -143,8 +148,10 @@ pub struct MachineRunArgs<'a, C, S> {
     block_info: &'a BlockInfo,
     raw_tx: &'a RawL2Transaction,
     max_cycles: u64,
-    backend: Backend,
+    backend: Arc<Backend>,
     cycles_pool: Option<&'a mut CyclesPool>,
+    tracer: Option<&'a mut SyscallTracer>,
+    profiler: Option<&'a mut CyclesProfiler>,
 }
 
 pub struct Generator {
@@ -194,6 +201,8 @@ impl Generator {
             max_cycles,
             backend,
             mut cycles_pool,
+            mut tracer,
+            profiler,
         } = args;
 
         let mut context = RunContext::default();
@@ -221,29 +230,43 @@ impl Generator {
                     cycles_pool: &mut cycles_pool,
                     log_buf: &mut sys_log_buf,
                     context: &mut context,
+                    tracer: tracer.as_deref_mut(),
                 }))
                 .instruction_cycle_func(Box::new(instruction_cycles));
-            let default_machine = machine_builder.build();
-
-            #[cfg(has_asm)]
-            let aot_code_opt = self
-                .backend_manage
-                .get_aot_code(&backend.checksum.generator, global_vm_version);
-            #[cfg(has_asm)]
-            if aot_code_opt.is_none() {
-                log::warn!("[machine_run] Not AOT mode!");
-            }
+            let mut default_machine = machine_builder.build();
+
+            let maybe_ok;
+            let execution_cycles;
+            if let Some(profiler) = profiler {
+                // profile with the interpreter, the program has to be run step by step
+                default_machine.load_program(&backend.generator, &[])?;
+                maybe_ok = profiler.run(&mut default_machine, &backend.generator);
+                execution_cycles = default_machine.cycles();
+                drop(default_machine);
+            } else {
+                #[cfg(has_asm)]
+                let aot_code_opt = self
+                    .backend_manage
+                    .get_aot_code(&backend, global_vm_version);
+                #[cfg(has_asm)]
+                if aot_code_opt.is_none() {
+                    log::warn!("[machine_run] Not AOT mode!");
+                }
 
-            #[cfg(has_asm)]
-            let mut machine = ckb_vm::machine::asm::AsmMachine::new(default_machine, aot_code_opt);
+                #[cfg(has_asm)]
+                let mut machine =
+                    ckb_vm::machine::asm::AsmMachine::new(default_machine, aot_code_opt.as_deref());
 
-            #[cfg(not(has_asm))]
-            let mut machine = TraceMachine::new(default_machine);
+                #[cfg(not(has_asm))]
+                let mut machine = TraceMachine::new(default_machine);
 
-            machine.load_program(&backend.generator, &[])?;
-            let maybe_ok = machine.run();
-            let execution_cycles = machine.machine.cycles();
-            drop(machine);
+                machine.load_program(&backend.generator, &[])?;
+                maybe_ok = machine.run();
+                execution_cycles = machine.machine.cycles();
+            }
+            if let Some(tracer) = tracer.as_mut() {
+                tracer.set_debug_log(&sys_log_buf);
+            }
 
             // Subtract tx execution cycles.
             if let Some(cycles_pool) = &mut cycles_pool {
@@ -329,17 +352,104 @@ impl Generator {
             )?
             .ok_or(AccountError::RegistryAddressNotFound)?;
 
+        if let Some(batch) = BatchWithdrawalSignature::from_withdrawal(withdrawal) {
+            return self.check_batch_withdrawal_signature(
+                lock_algo.as_ref(),
+                &account_script,
+                &raw,
+                &batch,
+            );
+        }
+
         lock_algo.verify_withdrawal(self.rollup_context(), account_script, withdrawal, address)?;
 
         Ok(())
     }
 
+    // Check the owner signature of a batch withdrawal
+    fn check_batch_withdrawal_signature(
+        &self,
+        lock_algo: &(dyn LockAlgorithm + Send + Sync),
+        account_script: &Script,
+        raw: &RawWithdrawalRequest,
+        batch: &BatchWithdrawalSignature,
+    ) -> Result<(), Error> {
+        if !batch.contains(raw) {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Withdrawal isn't in the batch".to_string(),
+            )
+            .into());
+        }
+
+        let lock_args: Bytes = account_script.args().unpack();
+        if lock_args.len() != 52 {
+            return Err(LockAlgorithmError::InvalidLockArgs.into());
+        }
+        let message = batch.signing_message(&self.rollup_context.rollup_script_hash);
+        let signer = lock_algo.recover(message, &batch.signature)?;
+        if signer.as_ref() != &lock_args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Mismatch batch withdrawal signature".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
     // Check transaction signature
     #[instrument(skip_all)]
     pub fn check_transaction_signature<S: State + CodeStore>(
         &self,
         state: &S,
         tx: &L2Transaction,
+    ) -> Result<(), TransactionValidateError> {
+        if let Some(sponsored) = SponsoredSignature::from_tx(tx) {
+            self.check_sponsor_signature(state, tx, &sponsored)?;
+            return self.check_sender_signature(state, &sponsored.sender_signed_tx(tx));
+        }
+        self.check_sender_signature(state, tx)
+    }
+
+    // Check sponsor signature of a sponsored transaction
+    fn check_sponsor_signature<S: State + CodeStore>(
+        &self,
+        state: &S,
+        tx: &L2Transaction,
+        sponsored: &SponsoredSignature,
+    ) -> Result<(), TransactionValidateError> {
+        let script_hash = state.get_script_hash(sponsored.sponsor_id)?;
+        if script_hash.is_zero() {
+            return Err(AccountError::ScriptNotFound {
+                account_id: sponsored.sponsor_id,
+            }
+            .into());
+        }
+        let script = state.get_script(&script_hash).expect("get script");
+        let lock_code_hash: [u8; 32] = script.code_hash().unpack();
+        let lock_algo = self
+            .account_lock_manage()
+            .get_lock_algorithm(&lock_code_hash.into())
+            .ok_or(LockAlgorithmError::UnknownAccountLock)?;
+
+        let lock_args: Bytes = script.args().unpack();
+        if lock_args.len() != 52 {
+            return Err(LockAlgorithmError::InvalidLockArgs.into());
+        }
+        let message = sponsored.signing_message(&self.rollup_context.rollup_script_hash, &tx.raw());
+        let signer = lock_algo.recover(message, &sponsored.sponsor_signature)?;
+        if signer.as_ref() != &lock_args[32..52] {
+            return Err(LockAlgorithmError::InvalidSignature(
+                "Mismatch sponsor signature".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_sender_signature<S: State + CodeStore>(
+        &self,
+        state: &S,
+        tx: &L2Transaction,
     ) -> Result<(), TransactionValidateError> {
         let raw_tx = tx.raw();
         let sender_id: u32 = raw_tx.from_id().unpack();
@@ -502,6 +612,7 @@ impl Generator {
         // handle transactions
         let mut offchain_used_cycles: u64 = 0;
         let mut tx_receipts = Vec::with_capacity(args.l2block.transactions().len());
+        let mut tx_access_lists = Vec::with_capacity(args.l2block.transactions().len());
         let skip_checkpoint_check = skipped_invalid_block_list.contains(&block_hash.into());
         if skip_checkpoint_check {
             log::warn!(
@@ -558,11 +669,11 @@ impl Generator {
             let now = Instant::now();
 
             // skip whitelist validate since we are validating a committed block
-            let run_result = match self.execute_transaction(
+            let run_result = match self.execute_l2transaction(
                 chain,
                 &mut state,
                 &block_info,
-                &raw_tx,
+                &tx,
                 L2TX_MAX_CYCLES,
                 None,
             ) {
@@ -626,6 +737,7 @@ impl Generator {
                     Ok(merkle_state) => merkle_state,
                     Err(err) => return ApplyBlockResult::Error(err),
                 };
+                tx_access_lists.push((&run_result.access_list).into());
                 let tx_receipt =
                     TxReceipt::build_receipt(tx.witness_hash().into(), run_result, post_state);
 
@@ -661,6 +773,7 @@ impl Generator {
             withdrawal_receipts,
             prev_txs_state,
             tx_receipts,
+            tx_access_lists,
             offchain_used_cycles,
         }
     }
@@ -671,29 +784,26 @@ impl Generator {
         block_number: u64,
         state: &S,
         script_hash: &H256,
-    ) -> Option<Backend> {
+    ) -> Option<Arc<Backend>> {
         log::debug!(
             "load_backend for script_hash: {}",
             hex::encode(script_hash.as_slice())
         );
-        state
-            .get_script(script_hash)
-            .and_then(|script| {
-                // only accept type script hash type for now
-                if script.hash_type() == ScriptHashType::Type.into() {
-                    let code_hash: [u8; 32] = script.code_hash().unpack();
-                    log::debug!("load_backend by code_hash: {}", hex::encode(code_hash));
-                    self.backend_manage
-                        .get_backend(block_number, &code_hash.into())
-                } else {
-                    log::error!(
-                        "Found a invalid account script which hash_type is data: {:?}",
-                        script
-                    );
-                    None
-                }
-            })
-            .cloned()
+        state.get_script(script_hash).and_then(|script| {
+            // only accept type script hash type for now
+            if script.hash_type() == ScriptHashType::Type.into() {
+                let code_hash: [u8; 32] = script.code_hash().unpack();
+                log::debug!("load_backend by code_hash: {}", hex::encode(code_hash));
+                self.backend_manage
+                    .get_backend(block_number, &code_hash.into())
+            } else {
+                log::error!(
+                    "Found a invalid account script which hash_type is data: {:?}",
+                    script
+                );
+                None
+            }
+        })
     }
 
     /// execute a layer2 tx
@@ -718,6 +828,115 @@ impl Generator {
         Ok(run_result)
     }
 
+    /// execute a layer2 tx, the sponsor of a sponsored tx pays the fee before the execution
+    #[instrument(skip_all)]
+    pub fn execute_l2transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        max_cycles: u64,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult, TransactionError> {
+        let run_result = self.unchecked_execute_l2transaction(
+            chain,
+            state,
+            block_info,
+            tx,
+            max_cycles,
+            cycles_pool,
+        )?;
+        Ok(run_result)
+    }
+
+    /// execute a layer2 tx, doesn't check exit code
+    ///
+    /// The sponsor fee payment and the tx execution are atomic,
+    /// the payment is reverted if the tx can't be executed.
+    pub fn unchecked_execute_l2transaction<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        max_cycles: u64,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<RunResult, TransactionError> {
+        self.execute_l2transaction_inner(
+            chain,
+            state,
+            block_info,
+            tx,
+            max_cycles,
+            cycles_pool,
+            None,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn execute_l2transaction_inner<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        max_cycles: u64,
+        cycles_pool: Option<&mut CyclesPool>,
+        tracer: Option<&mut SyscallTracer>,
+        profiler: Option<&mut CyclesProfiler>,
+    ) -> Result<RunResult, TransactionError> {
+        let raw_tx = tx.raw();
+        let sponsored = match SponsoredSignature::from_tx(tx) {
+            Some(sponsored) => sponsored,
+            None => {
+                return self.execute_transaction_inner(
+                    chain,
+                    state,
+                    block_info,
+                    &raw_tx,
+                    max_cycles,
+                    cycles_pool,
+                    tracer,
+                    profiler,
+                )
+            }
+        };
+
+        let snap = state.snapshot();
+        // the fee payment is a part of the tx, record its access list too
+        state.set_state_tracker(Default::default());
+        let paid = self.pay_sponsor_fee(state, block_info, &sponsored);
+        let fee_access_list = state
+            .take_state_tracker()
+            .unwrap()
+            .access_list()
+            .lock()
+            .unwrap()
+            .clone();
+        paid.and_then(|_| {
+            self.execute_transaction_inner(
+                chain,
+                state,
+                block_info,
+                &raw_tx,
+                max_cycles,
+                cycles_pool,
+                tracer,
+                profiler,
+            )
+        })
+        .map(|mut run_result| {
+            run_result.access_list.merge(fee_access_list);
+            run_result
+        })
+        .map_err(|err| {
+            state.revert(snap).expect("revert");
+            err
+        })
+    }
+
     /// execute a layer2 tx, doesn't check exit code
     #[instrument(skip_all, fields(block = block_info.number().unpack(), tx_hash = %raw_tx.hash().pack()))]
     pub fn unchecked_execute_transaction<S: State + CodeStore + JournalDB, C: ChainView>(
@@ -729,6 +948,31 @@ impl Generator {
         max_cycles: u64,
         cycles_pool: Option<&mut CyclesPool>,
     ) -> Result<RunResult, TransactionError> {
+        self.execute_transaction_inner(
+            chain,
+            state,
+            block_info,
+            raw_tx,
+            max_cycles,
+            cycles_pool,
+            None,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_transaction_inner<S: State + CodeStore + JournalDB, C: ChainView>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        raw_tx: &RawL2Transaction,
+        max_cycles: u64,
+        cycles_pool: Option<&mut CyclesPool>,
+        tracer: Option<&mut SyscallTracer>,
+        profiler: Option<&mut CyclesProfiler>,
+    ) -> Result<RunResult, TransactionError> {
+        state.set_state_tracker(Default::default());
         let account_id = raw_tx.to_id().unpack();
         let script_hash = state.get_script_hash(account_id)?;
         let backend = self
@@ -738,7 +982,6 @@ impl Generator {
         let snap = state.snapshot();
         let sender_id: u32 = raw_tx.from_id().unpack();
         let nonce_before = state.get_nonce(sender_id)?;
-        state.set_state_tracker(Default::default());
 
         let args = MachineRunArgs {
             chain,
@@ -748,6 +991,8 @@ impl Generator {
             max_cycles,
             backend,
             cycles_pool,
+            tracer,
+            profiler,
         };
 
         let run_context = self.machine_run(args).map_err(|err| {
@@ -821,6 +1066,7 @@ impl Generator {
                 .into_iter()
                 .cloned()
                 .collect(),
+            access_list: state_tracker.access_list().lock().unwrap().clone(),
         };
         Ok(r)
     }
@@ -842,6 +1088,34 @@ impl Generator {
         Ok(self.polyjuice_creator_id.load_full().map(|id| *id))
     }
 
+    // Sponsor pays the fee of a sponsored transaction to the block producer
+    fn pay_sponsor_fee<S: State + CodeStore + JournalDB>(
+        &self,
+        state: &mut S,
+        block_info: &BlockInfo,
+        sponsored: &SponsoredSignature,
+    ) -> Result<(), TransactionError> {
+        let payer = {
+            let script_hash = state.get_script_hash(sponsored.sponsor_id)?;
+            state
+                .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &script_hash)?
+                .ok_or(TransactionError::ScriptHashNotFound)?
+        };
+        let block_producer = RegistryAddress::from_slice(&block_info.block_producer().raw_data())
+            .unwrap_or_default();
+        state
+            .pay_fee(
+                &payer,
+                &block_producer,
+                CKB_SUDT_ACCOUNT_ID,
+                sponsored.fee.into(),
+            )
+            .map_err(|err| {
+                log::error!("[gw-generator] failed to pay sponsor fee, err: {}", err);
+                TransactionError::InsufficientBalance
+            })
+    }
+
     // Handle failed transaction
     fn handle_failed_transaction<S: State + CodeStore + JournalDB>(
         &self,
@@ -939,7 +1213,7 @@ impl Generator {
     }
 }
 
-fn get_block_info(l2block: &RawL2Block) -> BlockInfo {
+pub(crate) fn get_block_info(l2block: &RawL2Block) -> BlockInfo {
     BlockInfo::new_builder()
         .block_producer(l2block.block_producer())
         .number(l2block.number())
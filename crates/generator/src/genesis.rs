@@ -235,6 +235,7 @@ pub fn init_genesis(
         Vec::new(),
         prev_txs_state,
         Vec::new(),
+        Vec::new(),
         Default::default(),
         Vec::new(),
     )?;
@@ -2,13 +2,17 @@
 //! and generate new status that can be committed to layer1
 
 pub mod account_lock_manage;
+#[cfg(has_asm)]
+mod aot_cache;
 pub mod backend_manage;
 pub mod constants;
 pub mod error;
 pub mod generator;
 pub mod genesis;
+pub mod profile;
 pub mod sudt;
 pub mod syscalls;
+mod trace;
 pub mod traits;
 pub mod typed_transaction;
 pub mod types;
@@ -0,0 +1,272 @@
+//! Cycles profiler of layer2 transactions
+//!
+//! The profiler runs the generator instruction by instruction and attributes the execution
+//! cycles of each step to the call stack at that moment. Calls and returns are detected from
+//! the return address register, so no symbols are needed while profiling. The call frames are
+//! named by the function symbols of the generator ELF, frames of a stripped generator are
+//! named by their entry addresses and can be symbolized later with the unstripped ELF by
+//! `symbolize_folded`.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use ckb_vm::{
+    decoder::build_decoder, registers::RA, CoreMachine, DefaultMachine, Error as VMError, Register,
+    SupportMachine,
+};
+use gw_common::{state::State, H256};
+use gw_store::{state::traits::JournalDB, transaction::StoreTransaction};
+use gw_traits::{ChainView as ChainViewTrait, CodeStore};
+use gw_types::{
+    bytes::Bytes,
+    offchain::{CyclesProfile, RunResult},
+    packed::{BlockInfo, L2Transaction},
+};
+
+use crate::{
+    constants::L2TX_MAX_CYCLES, error::TransactionError, generator::CyclesPool, Generator,
+};
+
+/// Attributes execution cycles to call stacks
+#[derive(Debug, Default)]
+pub(crate) struct CyclesProfiler {
+    program: Bytes,
+    /// Entry addresses of the functions in the call stack
+    stack: Vec<u64>,
+    /// Cycles of the current stack which are not added to `stacks` yet
+    stack_cycles: u64,
+    stacks: HashMap<Vec<u64>, u64>,
+}
+
+impl CyclesProfiler {
+    /// Run a loaded program step by step and profile it
+    pub(crate) fn run<Inner: SupportMachine>(
+        &mut self,
+        machine: &mut DefaultMachine<'_, Inner>,
+        program: &Bytes,
+    ) -> Result<i8, VMError> {
+        self.program = program.clone();
+        self.stack = vec![machine.pc().to_u64()];
+
+        let mut decoder = build_decoder::<Inner::REG>(machine.isa(), machine.version());
+        machine.set_running(true);
+        while machine.running() {
+            let pc = machine.pc().to_u64();
+            let ra = machine.registers()[RA].to_u64();
+            let cycles = machine.cycles();
+            let result = machine.step(&mut decoder);
+            self.stack_cycles += machine.cycles().saturating_sub(cycles);
+            if let Err(err) = result {
+                self.flush();
+                return Err(err);
+            }
+
+            // calls set the return address right after the call instructions, a fused
+            // `auipc + jalr` call is 8 bytes
+            let next_pc = machine.pc().to_u64();
+            let next_ra = machine.registers()[RA].to_u64();
+            let is_sequential = next_pc > pc && next_pc <= pc + 8;
+            if !is_sequential && next_ra > pc && next_ra <= pc + 8 {
+                self.flush();
+                self.stack.push(next_pc);
+            } else if !is_sequential && next_pc == ra && self.stack.len() > 1 {
+                self.flush();
+                self.stack.pop();
+            }
+        }
+        self.flush();
+        Ok(machine.exit_code())
+    }
+
+    fn flush(&mut self) {
+        if self.stack_cycles > 0 {
+            *self.stacks.entry(self.stack.clone()).or_default() += self.stack_cycles;
+            self.stack_cycles = 0;
+        }
+    }
+
+    fn into_profile(
+        self,
+        tx_hash: H256,
+        result: Result<RunResult, TransactionError>,
+    ) -> CyclesProfile {
+        let symbols = Symbols::parse(&self.program).unwrap_or_default();
+        let mut stacks: Vec<_> = self
+            .stacks
+            .into_iter()
+            .map(|(stack, cycles)| {
+                let frames = stack.into_iter().map(|addr| symbols.frame_name(addr));
+                (frames.collect(), cycles)
+            })
+            .collect();
+        stacks.sort_unstable();
+
+        let mut profile = CyclesProfile {
+            tx_hash,
+            stacks,
+            ..Default::default()
+        };
+        match result {
+            Ok(run_result) => {
+                profile.exit_code = run_result.exit_code;
+                profile.cycles = run_result.cycles;
+            }
+            Err(err) => profile.error = Some(err.to_string()),
+        }
+        profile
+    }
+}
+
+/// Function symbols of an ELF
+#[derive(Debug, Default)]
+pub struct Symbols {
+    /// Sorted (start, end, name) of functions
+    funcs: Vec<(u64, u64, String)>,
+}
+
+impl Symbols {
+    /// Parse function symbols of an ELF, the table is empty if the ELF is stripped
+    pub fn parse(elf: &[u8]) -> Result<Self> {
+        let elf = goblin::elf::Elf::parse(elf)?;
+        let mut funcs: Vec<_> = elf
+            .syms
+            .iter()
+            .filter(|sym| sym.st_type() == goblin::elf::sym::STT_FUNC && sym.st_value != 0)
+            .filter_map(|sym| {
+                let name = elf.strtab.get(sym.st_name)?.ok()?;
+                // `;` separates frames in the folded format
+                let name = format!("{:#}", rustc_demangle::demangle(name)).replace(';', ":");
+                Some((sym.st_value, sym.st_value + sym.st_size.max(1), name))
+            })
+            .collect();
+        funcs.sort_unstable();
+        Ok(Symbols { funcs })
+    }
+
+    /// Name of the function containing the address
+    pub fn lookup(&self, addr: u64) -> Option<&str> {
+        let index = match self
+            .funcs
+            .binary_search_by_key(&addr, |(start, _, _)| *start)
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let (_start, end, name) = &self.funcs[index];
+        if addr < *end {
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    fn frame_name(&self, addr: u64) -> String {
+        match self.lookup(addr) {
+            Some(name) => name.to_string(),
+            None => format!("{:#x}", addr),
+        }
+    }
+}
+
+/// Replace the address frames of folded stacks by function names
+pub fn symbolize_folded(folded: &str, symbols: &Symbols) -> String {
+    let symbolize_frame = |frame: &str| {
+        frame
+            .strip_prefix("0x")
+            .and_then(|addr| u64::from_str_radix(addr, 16).ok())
+            .and_then(|addr| symbols.lookup(addr))
+            .unwrap_or(frame)
+            .to_string()
+    };
+    folded
+        .lines()
+        .map(|line| match line.rsplit_once(' ') {
+            Some((stack, cycles)) => {
+                let frames: Vec<_> = stack.split(';').map(symbolize_frame).collect();
+                format!("{} {}\n", frames.join(";"), cycles)
+            }
+            None => format!("{}\n", line),
+        })
+        .collect()
+}
+
+impl Generator {
+    /// execute a layer2 tx and profile the execution cycles of its generator, doesn't check
+    /// exit code
+    ///
+    /// Errors of the execution are returned in the profile.
+    pub fn profile_execute_l2transaction<S: State + CodeStore + JournalDB, C: ChainViewTrait>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        max_cycles: u64,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> CyclesProfile {
+        let mut profiler = CyclesProfiler::default();
+        let result = self.execute_l2transaction_inner(
+            chain,
+            state,
+            block_info,
+            tx,
+            max_cycles,
+            cycles_pool,
+            None,
+            Some(&mut profiler),
+        );
+        profiler.into_profile(tx.hash().into(), result)
+    }
+
+    /// Replay a committed tx on top of its parent block and profile its execution cycles
+    ///
+    /// Returns None if the tx isn't committed.
+    pub fn profile_committed_transaction(
+        &self,
+        db: &StoreTransaction,
+        tx_hash: &H256,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<Option<CyclesProfile>> {
+        let replayed =
+            self.replay_committed_transaction(db, tx_hash, |chain_view, state, block_info, tx| {
+                self.profile_execute_l2transaction(
+                    chain_view,
+                    state,
+                    block_info,
+                    tx,
+                    L2TX_MAX_CYCLES,
+                    cycles_pool,
+                )
+            })?;
+        Ok(replayed.map(|(mut profile, replay_errors)| {
+            profile.replay_errors = replay_errors;
+            profile
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{symbolize_folded, Symbols};
+
+    #[test]
+    fn test_symbolize_folded() {
+        let symbols = Symbols {
+            funcs: vec![
+                (0x100, 0x200, "main".to_string()),
+                (0x200, 0x280, "run_polyjuice".to_string()),
+                (0x300, 0x310, "sys_load".to_string()),
+            ],
+        };
+        assert_eq!(symbols.lookup(0x1ff), Some("main"));
+        assert_eq!(symbols.lookup(0x280), None);
+        assert_eq!(symbols.lookup(0x80), None);
+
+        let folded = "0x100 10\n0x100;0x200 25\n0x100;0x200;0x290 7\n";
+        assert_eq!(
+            symbolize_folded(folded, &symbols),
+            "main 10\nmain;run_polyjuice 25\nmain;run_polyjuice;0x290 7\n"
+        );
+    }
+}
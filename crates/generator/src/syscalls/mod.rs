@@ -1,6 +1,6 @@
 use crate::{
     account_lock_manage::AccountLockManage, generator::CyclesPool,
-    syscalls::error_codes::GW_FATAL_UNKNOWN_ARGS,
+    syscalls::error_codes::GW_FATAL_UNKNOWN_ARGS, trace::SyscallTracer,
 };
 use ckb_vm::{
     memory::Memory,
@@ -23,7 +23,7 @@ use gw_traits::{ChainView, CodeStore};
 use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
-    offchain::{CycleMeter, RollupContext},
+    offchain::{CycleMeter, RollupContext, SyscallOp},
     packed::{BlockInfo, LogItem, RawL2Transaction, Script},
     prelude::*,
 };
@@ -31,11 +31,13 @@ use std::cmp;
 
 use self::error_codes::{
     GW_ERROR_ACCOUNT_NOT_FOUND, GW_ERROR_DUPLICATED_SCRIPT_HASH, GW_ERROR_INVALID_ACCOUNT_SCRIPT,
-    GW_ERROR_NOT_FOUND, GW_ERROR_RECOVER, GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH, SUCCESS,
+    GW_ERROR_NOT_FOUND, GW_ERROR_RECOVER, GW_ERROR_UNKNOWN_SCRIPT_CODE_HASH, GW_FATAL_INVALID_DATA,
+    SUCCESS,
 };
 
 mod bn;
 pub mod error_codes;
+mod precompiles;
 
 /* Constants */
 // Increasing from 25k(ethereum contract code size) to 128k.
@@ -67,6 +69,12 @@ const SYS_BN_PAIRING: u64 = 3603;
 /* Syscall state revert */
 const SYS_SNAPSHOT: u64 = 3701;
 const SYS_REVERT: u64 = 3702;
+/* Syscall precompiles */
+const SYS_KECCAK256: u64 = 3801;
+const SYS_SHA256: u64 = 3802;
+const SYS_RIPEMD160: u64 = 3803;
+const SYS_BLAKE2F: u64 = 3804;
+const SYS_SECP256K1_RECOVER: u64 = 3805;
 /* CKB compatible syscalls */
 const DEBUG_PRINT_SYSCALL_NUMBER: u64 = 2177;
 
@@ -93,6 +101,7 @@ pub(crate) struct L2Syscalls<'a, 'b, S, C> {
     pub(crate) context: &'b mut RunContext,
     pub(crate) cycles_pool: &'b mut Option<&'a mut CyclesPool>,
     pub(crate) log_buf: &'b mut Vec<u8>,
+    pub(crate) tracer: Option<&'b mut SyscallTracer>,
 }
 
 #[allow(dead_code)]
@@ -161,6 +170,8 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
 
     fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
         let code = machine.registers()[A7].to_u64();
+        let execution_cycles = machine.cycles();
+        let virtual_cycles = self.context.cycle_meter.r#virtual;
 
         if let Some(cycles_pool) = self.cycles_pool {
             let syscall_cycles = Self::get_syscall_cycles(code, cycles_pool.syscall_config());
@@ -183,12 +194,44 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
             }
         }
 
+        let handled = self.execute_syscall(machine, code)?;
+        if handled && code != DEBUG_PRINT_SYSCALL_NUMBER {
+            if let Some(tracer) = self.tracer.as_mut() {
+                tracer.finish_syscall(
+                    code,
+                    machine.cycles().saturating_sub(execution_cycles),
+                    self.context.cycle_meter.r#virtual - virtual_cycles,
+                    machine.registers()[A0].to_i8(),
+                );
+            }
+        }
+        Ok(handled)
+    }
+}
+
+impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView> L2Syscalls<'a, 'b, S, C> {
+    fn execute_syscall<Mac: SupportMachine>(
+        &mut self,
+        machine: &mut Mac,
+        code: u64,
+    ) -> Result<bool, VMError> {
         match code {
             SYS_STORE => {
                 let key_addr = machine.registers()[A0].to_u64();
                 let key = load_data_h256(machine, key_addr)?;
                 let value_addr = machine.registers()[A1].to_u64();
                 let value = load_data_h256(machine, value_addr)?;
+                if self.tracer.is_some() {
+                    let before = self
+                        .state
+                        .get_raw(&key)
+                        .map_err(|err| VMError::Unexpected(format!("get raw: {}", err)))?;
+                    self.trace(|| SyscallOp::Store {
+                        key,
+                        before,
+                        after: value,
+                    });
+                }
                 self.state
                     .update_raw(key, value)
                     .map_err(|err| VMError::Unexpected(format!("store kv error: {}", err)))?;
@@ -203,6 +246,7 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                     .state
                     .get_raw(&key)
                     .map_err(|err| VMError::Unexpected(format!("get raw: {}", err)))?;
+                self.trace(|| SyscallOp::Load { key, value });
                 machine
                     .memory_mut()
                     .store_bytes(value_addr, value.as_slice())?;
@@ -236,6 +280,10 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                     }
                 };
                 let script_hash = script.hash();
+                self.trace(|| SyscallOp::Create {
+                    script_hash: script_hash.into(),
+                    account_id: None,
+                });
 
                 // Return error if script_hash is exists
                 if self
@@ -342,6 +390,10 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 self.state
                     .set_account_count(id + 1)
                     .map_err(|err| VMError::Unexpected(format!("set acccount: {}", err)))?;
+                self.trace(|| SyscallOp::Create {
+                    script_hash: script_hash.into(),
+                    account_id: Some(id),
+                });
                 machine
                     .memory_mut()
                     .store32(&account_id_addr, &Mac::REG::from_u32(id))?;
@@ -455,6 +507,11 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 let msg = load_data_h256(machine, msg_addr)?;
                 let signature = load_bytes(machine, signature_addr, signature_len as usize)?;
                 let code_hash = load_data_h256(machine, code_hash_addr)?;
+                self.trace(|| SyscallOp::RecoverAccount {
+                    message: msg,
+                    code_hash,
+                    script_hash: None,
+                });
 
                 if let Some(lock_algo) = self.account_lock_manage.get_lock_algorithm(&code_hash) {
                     if let Ok(lock_args) = lock_algo.recover(msg, &signature) {
@@ -467,6 +524,11 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                             .hash_type(ScriptHashType::Type.into())
                             .args(Bytes::from(script_args).pack())
                             .build();
+                        self.trace(|| SyscallOp::RecoverAccount {
+                            message: msg,
+                            code_hash,
+                            script_hash: Some(account_script.hash().into()),
+                        });
 
                         machine.memory_mut().store64(
                             &script_len_addr,
@@ -493,6 +555,11 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 let data_addr = machine.registers()[A3].to_u64();
 
                 let data = load_bytes(machine, data_addr, data_len as usize)?;
+                self.trace(|| SyscallOp::Log {
+                    account_id,
+                    service_flag,
+                    data: Bytes::from(data.clone()),
+                });
                 self.state.append_log(
                     LogItem::new_builder()
                         .account_id(account_id.pack())
@@ -588,30 +655,11 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 let input_size = machine.registers()[A4].to_u64();
                 let input = load_bytes(machine, input_addr, input_size as usize)?;
 
-                if let Some(cycles_pool) = self.cycles_pool {
-                    // k is the number of pairings being computed
-                    let k: u64 = input_size / 192;
-                    if k > 0 {
-                        // Subtract additional cycles per pairing
-                        let additional_cycles =
-                            k * cycles_pool.syscall_config().sys_bn_per_pairing_cycles;
-                        self.context.cycle_meter.r#virtual = self
-                            .context
-                            .cycle_meter
-                            .r#virtual
-                            .saturating_add(additional_cycles);
-                        let execution_and_virtual = machine
-                            .cycles()
-                            .saturating_add(self.context.cycle_meter.r#virtual);
-                        if cycles_pool.consume_cycles(additional_cycles).is_none()
-                            || execution_and_virtual > cycles_pool.limit()
-                        {
-                            return Err(VMError::Unexpected(
-                                "cycles pool limit reached".to_owned(),
-                            ));
-                        }
-                    }
-                }
+                // k is the number of pairings being computed
+                let k: u64 = input_size / 192;
+                self.charge_additional_cycles(machine, |config| {
+                    k.saturating_mul(config.sys_bn_per_pairing_cycles)
+                })?;
 
                 let output = bn::pairing(&input).map_err(|err| {
                     let err_msg = format!("syscall SYS_BN_PAIRING error: {:?}", err.0);
@@ -622,10 +670,97 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
                 machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
                 Ok(true)
             }
+            SYS_KECCAK256 | SYS_SHA256 | SYS_RIPEMD160 => {
+                let input_addr = machine.registers()[A3].to_u64();
+                let input_size = machine.registers()[A4].to_u64();
+                if input_size > precompiles::MAX_HASH_INPUT_SIZE as u64 {
+                    log::debug!("syscall {} error: input is too long", syscall_name(code));
+                    machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                    return Ok(true);
+                }
+                let words = (input_size / 32).saturating_add((input_size % 32 != 0) as u64);
+                self.charge_additional_cycles(machine, |config| {
+                    let per_word_cycles = match code {
+                        SYS_KECCAK256 => config.sys_keccak256_per_word_cycles,
+                        SYS_SHA256 => config.sys_sha256_per_word_cycles,
+                        _ => config.sys_ripemd160_per_word_cycles,
+                    };
+                    words.saturating_mul(per_word_cycles)
+                })?;
+                let input = load_bytes(machine, input_addr, input_size as usize)?;
+
+                let output = match code {
+                    SYS_KECCAK256 => precompiles::keccak256(&input).to_vec(),
+                    SYS_SHA256 => precompiles::sha256(&input).to_vec(),
+                    _ => precompiles::ripemd160(&input).to_vec(),
+                };
+                store_data(machine, &output)?;
+                machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                Ok(true)
+            }
+            SYS_BLAKE2F => {
+                let input_addr = machine.registers()[A3].to_u64();
+                let input_size = machine.registers()[A4].to_u64();
+                if input_size != precompiles::BLAKE2F_INPUT_SIZE as u64 {
+                    log::debug!("syscall SYS_BLAKE2F error: invalid input length");
+                    machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                    return Ok(true);
+                }
+                let input = load_bytes(machine, input_addr, input_size as usize)?;
+                let rounds = precompiles::blake2f_rounds(&input).map_err(|err| {
+                    VMError::Unexpected(format!("syscall SYS_BLAKE2F error: {:?}", err.0))
+                })?;
+                if rounds > precompiles::MAX_BLAKE2F_ROUNDS {
+                    log::debug!("syscall SYS_BLAKE2F error: too many rounds");
+                    machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                    return Ok(true);
+                }
+                self.charge_additional_cycles(machine, |config| {
+                    u64::from(rounds).saturating_mul(config.sys_blake2f_per_round_cycles)
+                })?;
+
+                match precompiles::blake2f(&input) {
+                    Ok(output) => {
+                        store_data(machine, output.as_slice())?;
+                        machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                    }
+                    Err(err) => {
+                        log::debug!("syscall SYS_BLAKE2F error: {:?}", err.0);
+                        machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                    }
+                }
+                Ok(true)
+            }
+            SYS_SECP256K1_RECOVER => {
+                let input_addr = machine.registers()[A3].to_u64();
+                let input_size = machine.registers()[A4].to_u64();
+                if input_size != precompiles::SECP256K1_RECOVER_INPUT_SIZE as u64 {
+                    log::debug!("syscall SYS_SECP256K1_RECOVER error: invalid input length");
+                    machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                    return Ok(true);
+                }
+                let input = load_bytes(machine, input_addr, input_size as usize)?;
+
+                match precompiles::secp256k1_recover(&input) {
+                    Ok(Some(pubkey)) => {
+                        store_data(machine, pubkey.as_slice())?;
+                        machine.set_register(A0, Mac::REG::from_u8(SUCCESS));
+                    }
+                    Ok(None) => {
+                        machine.set_register(A0, Mac::REG::from_i8(GW_ERROR_RECOVER));
+                    }
+                    Err(err) => {
+                        log::debug!("syscall SYS_SECP256K1_RECOVER error: {:?}", err.0);
+                        machine.set_register(A0, Mac::REG::from_i8(GW_FATAL_INVALID_DATA));
+                    }
+                }
+                Ok(true)
+            }
             SYS_SNAPSHOT => {
                 let snapshot_addr = machine.registers()[A0].clone();
                 // create snapshot
                 let snapshot_id = self.state.snapshot() as u32;
+                self.trace(|| SyscallOp::Snapshot { snapshot_id });
                 machine
                     .memory_mut()
                     .store32(&snapshot_addr, &Mac::REG::from_u32(snapshot_id))?;
@@ -634,6 +769,7 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
             }
             SYS_REVERT => {
                 let snapshot_id = machine.registers()[A0].to_u32();
+                self.trace(|| SyscallOp::Revert { snapshot_id });
                 self.state
                     .revert(snapshot_id as usize)
                     .map_err(|err| VMError::Unexpected(format!("revert: {}", err)))?;
@@ -650,6 +786,12 @@ impl<'a, 'b, S: State + CodeStore + JournalDB, C: ChainView, Mac: SupportMachine
 }
 
 impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
+    fn trace<F: FnOnce() -> SyscallOp>(&mut self, op: F) {
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record(op());
+        }
+    }
+
     fn get_script_hash(&mut self, id: u32) -> Result<H256, VMError> {
         let value = self
             .state
@@ -700,6 +842,44 @@ impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
         Ok(())
     }
 
+    /// Charge virtual cycles depending on the syscall input, in addition to the fixed cycles
+    /// charged by `ecall`
+    ///
+    /// The charge always counts toward the cycles limit of the tx, the default syscall cycles
+    /// are used to price it if the tx isn't executed with a cycles pool.
+    fn charge_additional_cycles<Mac: SupportMachine>(
+        &mut self,
+        machine: &mut Mac,
+        f: impl FnOnce(&SyscallCyclesConfig) -> u64,
+    ) -> Result<(), VMError> {
+        let additional_cycles = match self.cycles_pool {
+            Some(cycles_pool) => f(cycles_pool.syscall_config()),
+            None => f(&SyscallCyclesConfig::default()),
+        };
+        if additional_cycles == 0 {
+            return Ok(());
+        }
+        self.context.cycle_meter.r#virtual = self
+            .context
+            .cycle_meter
+            .r#virtual
+            .saturating_add(additional_cycles);
+        let execution_and_virtual = machine
+            .cycles()
+            .saturating_add(self.context.cycle_meter.r#virtual);
+        if execution_and_virtual > machine.max_cycles() {
+            return Err(VMError::CyclesExceeded);
+        }
+        if let Some(cycles_pool) = self.cycles_pool {
+            if cycles_pool.consume_cycles(additional_cycles).is_none()
+                || execution_and_virtual > cycles_pool.limit()
+            {
+                return Err(VMError::Unexpected("cycles pool limit reached".to_owned()));
+            }
+        }
+        Ok(())
+    }
+
     fn get_syscall_cycles(syscall: u64, cycles_config: &SyscallCyclesConfig) -> u64 {
         match syscall {
             SYS_STORE => cycles_config.sys_store_cycles,
@@ -716,7 +896,42 @@ impl<'a, 'b, S: State, C: ChainView> L2Syscalls<'a, 'b, S, C> {
             SYS_BN_PAIRING => cycles_config.sys_bn_fixed_pairing_cycles,
             SYS_SNAPSHOT => cycles_config.sys_snapshot_cycles,
             SYS_REVERT => cycles_config.sys_revert_cycles,
+            SYS_KECCAK256 => cycles_config.sys_keccak256_cycles,
+            SYS_SHA256 => cycles_config.sys_sha256_cycles,
+            SYS_RIPEMD160 => cycles_config.sys_ripemd160_cycles,
+            SYS_SECP256K1_RECOVER => cycles_config.sys_secp256k1_recover_cycles,
             _ => 0,
         }
     }
 }
+
+pub(crate) fn syscall_name(code: u64) -> &'static str {
+    match code {
+        SYS_CREATE => "create",
+        SYS_STORE => "store",
+        SYS_LOAD => "load",
+        SYS_LOAD_ACCOUNT_SCRIPT => "load_account_script",
+        SYS_SET_RETURN_DATA => "set_return_data",
+        SYS_STORE_DATA => "store_data",
+        SYS_LOAD_DATA => "load_data",
+        SYS_LOAD_ROLLUP_CONFIG => "load_rollup_config",
+        SYS_LOAD_TRANSACTION => "load_transaction",
+        SYS_LOAD_BLOCKINFO => "load_blockinfo",
+        SYS_GET_BLOCK_HASH => "get_block_hash",
+        SYS_PAY_FEE => "pay_fee",
+        SYS_LOG => "log",
+        SYS_RECOVER_ACCOUNT => "recover_account",
+        SYS_BN_ADD => "bn_add",
+        SYS_BN_MUL => "bn_mul",
+        SYS_BN_PAIRING => "bn_pairing",
+        SYS_SNAPSHOT => "snapshot",
+        SYS_REVERT => "revert",
+        SYS_KECCAK256 => "keccak256",
+        SYS_SHA256 => "sha256",
+        SYS_RIPEMD160 => "ripemd160",
+        SYS_BLAKE2F => "blake2f",
+        SYS_SECP256K1_RECOVER => "secp256k1_recover",
+        DEBUG_PRINT_SYSCALL_NUMBER => "debug_print",
+        _ => "unknown",
+    }
+}
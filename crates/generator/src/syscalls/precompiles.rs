@@ -0,0 +1,226 @@
+//! Host side implementations of the precompiled contracts of Ethereum
+//!
+//! The validator computes the same functions inside the VM (see `gwos/c/gw_precompiles.h`),
+//! so the results must only depend on the inputs.
+
+use secp256k1::{
+    recovery::{RecoverableSignature, RecoveryId},
+    Message,
+};
+
+use crate::account_lock_manage::secp256k1::SECP256K1;
+
+pub struct Error(pub &'static str);
+
+/// Input size of blake2f: rounds(4) | h(64) | m(128) | t(16) | f(1)
+pub const BLAKE2F_INPUT_SIZE: usize = 213;
+/// Input size of secp256k1 recover: message(32) | signature(65)
+pub const SECP256K1_RECOVER_INPUT_SIZE: usize = 97;
+/// Max input size of keccak256, sha256 and ripemd160, same as `GW_MAX_HASH_INPUT_SIZE` of the
+/// validator
+pub const MAX_HASH_INPUT_SIZE: usize = 128 * 1024;
+/// Max rounds of blake2f, same as `GW_MAX_BLAKE2F_ROUNDS` of the validator
+pub const MAX_BLAKE2F_ROUNDS: u32 = 65536;
+
+pub fn keccak256(input: &[u8]) -> [u8; 32] {
+    use sha3::{Digest, Keccak256};
+
+    let mut output = [0u8; 32];
+    output.copy_from_slice(&Keccak256::digest(input));
+    output
+}
+
+pub fn sha256(input: &[u8]) -> [u8; 32] {
+    let digest = ring::digest::digest(&ring::digest::SHA256, input);
+    let mut output = [0u8; 32];
+    output.copy_from_slice(digest.as_ref());
+    output
+}
+
+pub fn ripemd160(input: &[u8]) -> [u8; 20] {
+    use ripemd::{Digest, Ripemd160};
+
+    let mut output = [0u8; 20];
+    output.copy_from_slice(&Ripemd160::digest(input));
+    output
+}
+
+/// Number of rounds of a blake2f input
+pub fn blake2f_rounds(input: &[u8]) -> Result<u32, Error> {
+    if input.len() != BLAKE2F_INPUT_SIZE {
+        return Err(Error("invalid input length, must be 213"));
+    }
+    let mut rounds = [0u8; 4];
+    rounds.copy_from_slice(&input[..4]);
+    Ok(u32::from_be_bytes(rounds))
+}
+
+/// The compression function F of BLAKE2b, see EIP-152
+pub fn blake2f(input: &[u8]) -> Result<[u8; 64], Error> {
+    let rounds = blake2f_rounds(input)?;
+    if rounds > MAX_BLAKE2F_ROUNDS {
+        return Err(Error("too many rounds"));
+    }
+    let read_u64 = |offset: usize| {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&input[offset..offset + 8]);
+        u64::from_le_bytes(buf)
+    };
+
+    let mut h = [0u64; 8];
+    for (i, word) in h.iter_mut().enumerate() {
+        *word = read_u64(4 + i * 8);
+    }
+    let mut m = [0u64; 16];
+    for (i, word) in m.iter_mut().enumerate() {
+        *word = read_u64(68 + i * 8);
+    }
+    let t = [read_u64(196), read_u64(204)];
+    let f = match input[212] {
+        0 => false,
+        1 => true,
+        _ => return Err(Error("invalid final block indicator flag")),
+    };
+
+    blake2b_compress(&mut h, &m, t, f, rounds);
+
+    let mut output = [0u8; 64];
+    for (i, word) in h.iter().enumerate() {
+        output[i * 8..i * 8 + 8].copy_from_slice(&word.to_le_bytes());
+    }
+    Ok(output)
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+#[allow(clippy::many_single_char_names)]
+fn blake2b_g(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+fn blake2b_compress(h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], f: bool, rounds: u32) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if f {
+        v[14] = !v[14];
+    }
+
+    for i in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[i % 10];
+        blake2b_g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        blake2b_g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        blake2b_g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        blake2b_g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        blake2b_g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        blake2b_g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        blake2b_g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        blake2b_g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+/// Recover the uncompressed public key without the `0x04` prefix, the input is
+/// `message(32) | r(32) | s(32) | recovery_id(1)`
+///
+/// Returns `None` if the signature can't be recovered.
+pub fn secp256k1_recover(input: &[u8]) -> Result<Option<[u8; 64]>, Error> {
+    if input.len() != SECP256K1_RECOVER_INPUT_SIZE {
+        return Err(Error("invalid input length, must be 97"));
+    }
+    let recover = || {
+        let msg = Message::from_slice(&input[..32]).ok()?;
+        let recid = RecoveryId::from_i32(input[96].into()).ok()?;
+        let signature = RecoverableSignature::from_compact(&input[32..96], recid).ok()?;
+        SECP256K1.recover(&msg, &signature).ok()
+    };
+
+    Ok(recover().map(|pubkey| {
+        let mut output = [0u8; 64];
+        output.copy_from_slice(&pubkey.serialize_uncompressed()[1..]);
+        output
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blake2f, keccak256, ripemd160, sha256, MAX_BLAKE2F_ROUNDS};
+
+    #[test]
+    fn test_hash_precompiles() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex::encode(ripemd160(b"abc")),
+            "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"
+        );
+    }
+
+    #[test]
+    fn test_blake2f() {
+        // test vector 5 of EIP-152, equals to the blake2b hash of "abc"
+        let mut input = hex::decode(concat!(
+            "0000000c",
+            "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5",
+            "d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b",
+        ))
+        .unwrap();
+        input.extend_from_slice(b"abc");
+        input.resize(4 + 64 + 128, 0);
+        input.extend_from_slice(&3u64.to_le_bytes());
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.push(1);
+        assert_eq!(
+            hex::encode(blake2f(&input).ok().unwrap()),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+
+        let mut invalid = input.clone();
+        invalid[212] = 2;
+        assert!(blake2f(&invalid).is_err());
+
+        let mut too_many_rounds = input;
+        too_many_rounds[..4].copy_from_slice(&(MAX_BLAKE2F_ROUNDS + 1).to_be_bytes());
+        assert!(blake2f(&too_many_rounds).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Syscall level tracer of layer2 transactions
+
+use anyhow::{anyhow, Result};
+use gw_common::{registry_address::RegistryAddress, state::State, H256};
+use gw_store::{
+    chain_view::ChainView,
+    state::{history::history_state::RWConfig, traits::JournalDB, BlockStateDB},
+    traits::chain_store::ChainStore,
+    transaction::StoreTransaction,
+};
+use gw_traits::{ChainView as ChainViewTrait, CodeStore};
+use gw_types::{
+    bytes::Bytes,
+    offchain::{ExecutionTrace, ReplayError, RunResult, SyscallOp, SyscallTrace},
+    packed::{BlockInfo, L2Transaction},
+    prelude::*,
+};
+
+use crate::{
+    constants::L2TX_MAX_CYCLES,
+    error::TransactionError,
+    generator::{get_block_info, CyclesPool},
+    syscalls::syscall_name,
+    traits::StateExt,
+    Generator,
+};
+
+/// Collects the syscalls made by a transaction
+#[derive(Debug, Default)]
+pub(crate) struct SyscallTracer {
+    op: Option<SyscallOp>,
+    syscalls: Vec<SyscallTrace>,
+    debug_log: String,
+}
+
+impl SyscallTracer {
+    /// Record the arguments of the current syscall
+    pub(crate) fn record(&mut self, op: SyscallOp) {
+        self.op = Some(op);
+    }
+
+    /// Record the current syscall with the cycles spent on it
+    pub(crate) fn finish_syscall(
+        &mut self,
+        code: u64,
+        execution_cycles: u64,
+        virtual_cycles: u64,
+        ret: i8,
+    ) {
+        self.syscalls.push(SyscallTrace {
+            code,
+            name: syscall_name(code),
+            execution_cycles,
+            virtual_cycles,
+            ret,
+            op: self.op.take(),
+        });
+    }
+
+    pub(crate) fn set_debug_log(&mut self, log_buf: &[u8]) {
+        self.debug_log = String::from_utf8_lossy(log_buf).into_owned();
+    }
+
+    fn into_trace(
+        self,
+        tx_hash: H256,
+        result: Result<RunResult, TransactionError>,
+    ) -> ExecutionTrace {
+        let mut trace = ExecutionTrace {
+            tx_hash,
+            debug_log: self.debug_log,
+            syscalls: self.syscalls,
+            ..Default::default()
+        };
+        match result {
+            Ok(run_result) => {
+                trace.exit_code = run_result.exit_code;
+                trace.cycles = run_result.cycles;
+                trace.return_data = run_result.return_data;
+            }
+            Err(err) => trace.error = Some(err.to_string()),
+        }
+        trace
+    }
+}
+
+impl Generator {
+    /// execute a layer2 tx and trace its syscalls, doesn't check exit code
+    ///
+    /// Errors of the execution are returned in the trace.
+    pub fn trace_execute_l2transaction<S: State + CodeStore + JournalDB, C: ChainViewTrait>(
+        &self,
+        chain: &C,
+        state: &mut S,
+        block_info: &BlockInfo,
+        tx: &L2Transaction,
+        max_cycles: u64,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> ExecutionTrace {
+        let mut tracer = SyscallTracer::default();
+        let result = self.execute_l2transaction_inner(
+            chain,
+            state,
+            block_info,
+            tx,
+            max_cycles,
+            cycles_pool,
+            Some(&mut tracer),
+            None,
+        );
+        tracer.into_trace(tx.hash().into(), result)
+    }
+
+    /// Replay a committed tx on top of its parent block and trace its syscalls
+    ///
+    /// Returns None if the tx isn't committed.
+    pub fn trace_committed_transaction(
+        &self,
+        db: &StoreTransaction,
+        tx_hash: &H256,
+        cycles_pool: Option<&mut CyclesPool>,
+    ) -> Result<Option<ExecutionTrace>> {
+        let replayed =
+            self.replay_committed_transaction(db, tx_hash, |chain_view, state, block_info, tx| {
+                self.trace_execute_l2transaction(
+                    chain_view,
+                    state,
+                    block_info,
+                    tx,
+                    L2TX_MAX_CYCLES,
+                    cycles_pool,
+                )
+            })?;
+        Ok(replayed.map(|(mut trace, replay_errors)| {
+            trace.replay_errors = replay_errors;
+            trace
+        }))
+    }
+
+    /// Replay the txs of a committed block with `cycles_pool`
+    ///
+    /// Fails if a tx fails or the txs exceed the cycles limit of the pool.
+    pub fn replay_block_with_cycles_pool(
+        &self,
+        db: &StoreTransaction,
+        block_number: u64,
+        cycles_pool: &mut CyclesPool,
+    ) -> Result<()> {
+        self.replay_block(db, block_number, |chain_view, state, block_info, tx| {
+            self.execute_l2transaction(
+                chain_view,
+                state,
+                block_info,
+                tx,
+                L2TX_MAX_CYCLES,
+                Some(&mut *cycles_pool),
+            )?;
+            Ok(false)
+        })
+    }
+
+    /// Replay the txs before a committed tx in its block, then call `f` to execute the tx
+    ///
+    /// An earlier tx which fails is recorded in the returned errors and its changes are dropped,
+    /// the replay goes on with the next tx. Returns None if the tx isn't committed.
+    pub(crate) fn replay_committed_transaction<T>(
+        &self,
+        db: &StoreTransaction,
+        tx_hash: &H256,
+        f: impl FnOnce(
+            &ChainView<'_, &StoreTransaction>,
+            &mut BlockStateDB<&StoreTransaction>,
+            &BlockInfo,
+            &L2Transaction,
+        ) -> T,
+    ) -> Result<Option<(T, Vec<ReplayError>)>> {
+        let tx_info = match db.get_transaction_info(tx_hash)? {
+            Some(tx_info) => tx_info,
+            None => return Ok(None),
+        };
+        let block_number: u64 = tx_info.block_number().unpack();
+
+        let mut f = Some(f);
+        let mut output = None;
+        let mut replay_errors = Vec::new();
+        self.replay_block(db, block_number, |chain_view, state, block_info, tx| {
+            let hash = H256::from(tx.hash());
+            if hash == *tx_hash {
+                let f = f.take().expect("call once");
+                output = Some(f(chain_view, state, block_info, tx));
+                return Ok(true);
+            }
+            let snap = state.snapshot();
+            if let Err(err) =
+                self.execute_l2transaction(chain_view, state, block_info, tx, L2TX_MAX_CYCLES, None)
+            {
+                state.revert(snap)?;
+                log::warn!("replay tx {:?} of block {}: {}", hash, block_number, err);
+                replay_errors.push(ReplayError {
+                    tx_hash: hash,
+                    error: err.to_string(),
+                });
+            }
+            Ok(false)
+        })?;
+        match output {
+            Some(output) => Ok(Some((output, replay_errors))),
+            None => Err(anyhow!("tx not found in block {}", block_number)),
+        }
+    }
+
+    /// Apply the withdrawals and deposits of a committed block on top of its parent block, then
+    /// call `f` on the txs in order until it returns true
+    fn replay_block(
+        &self,
+        db: &StoreTransaction,
+        block_number: u64,
+        mut f: impl FnMut(
+            &ChainView<'_, &StoreTransaction>,
+            &mut BlockStateDB<&StoreTransaction>,
+            &BlockInfo,
+            &L2Transaction,
+        ) -> Result<bool>,
+    ) -> Result<()> {
+        let block_hash = db
+            .get_block_hash_by_number(block_number)?
+            .ok_or_else(|| anyhow!("block {} not found", block_number))?;
+        let block = db
+            .get_block(&block_hash)?
+            .ok_or_else(|| anyhow!("block {} not found", block_number))?;
+        let raw_block = block.raw();
+        let parent_block_hash: H256 = raw_block.parent_block_hash().unpack();
+        let parent_block = db
+            .get_block(&parent_block_hash)?
+            .ok_or_else(|| anyhow!("parent block of {} not found", block_number))?;
+
+        // The history state is read only, the replayed changes stay in the dirty state and
+        // are never finalised.
+        let mut state =
+            BlockStateDB::from_store(db, RWConfig::history_block(block_number.saturating_sub(1)))?;
+        state.set_account_count(parent_block.raw().post_account().count().unpack())?;
+
+        let block_info = get_block_info(&raw_block);
+        let block_producer = {
+            let block_producer: Bytes = block_info.block_producer().unpack();
+            RegistryAddress::from_slice(&block_producer)
+                .ok_or_else(|| anyhow!("invalid block producer address"))?
+        };
+        for withdrawal in block.withdrawals() {
+            state.apply_withdrawal_request(self.rollup_context(), &block_producer, &withdrawal)?;
+        }
+        if let Some(deposit_info_vec) = db.get_block_deposit_info_vec(block_number) {
+            for deposit_info in deposit_info_vec {
+                state.apply_deposit_request(self.rollup_context(), &deposit_info.request())?;
+            }
+        }
+
+        let chain_view = ChainView::new(&db, parent_block_hash);
+        for tx in block.transactions() {
+            if f(&chain_view, &mut state, &block_info, &tx)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
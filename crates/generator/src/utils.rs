@@ -116,6 +116,7 @@ pub fn get_polyjuice_creator_id<S: State + CodeStore>(
                 backends
                     .values()
                     .find(|backend| backend.backend_type == BackendType::Polyjuice)
+                    .cloned()
             });
     if let Some(backend) = polyjuice_backend {
         let mut args = rollup_context.rollup_script_hash.as_slice().to_vec();
@@ -3,7 +3,12 @@ use gw_common::{
     state::State,
 };
 use gw_traits::CodeStore;
-use gw_types::{offchain::RollupContext, packed::L2Transaction, prelude::*};
+use gw_types::{
+    offchain::{RollupContext, SponsoredSignature},
+    packed::L2Transaction,
+    prelude::*,
+    U256,
+};
 use tracing::instrument;
 
 use crate::{
@@ -80,6 +85,20 @@ impl<'a, S: State + CodeStore> TransactionVerifier<'a, S> {
         if balance < tx_cost {
             return Err(TransactionError::InsufficientBalance.into());
         }
+        // verify sponsor's balance
+        if let Some(sponsored) = SponsoredSignature::from_tx(tx) {
+            let sponsor_script_hash = self.state.get_script_hash(sponsored.sponsor_id)?;
+            let sponsor_address = self
+                .state
+                .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sponsor_script_hash)?
+                .ok_or(AccountError::RegistryAddressNotFound)?;
+            let sponsor_balance = self
+                .state
+                .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &sponsor_address)?;
+            if sponsor_balance < U256::from(sponsored.fee) {
+                return Err(TransactionError::InsufficientBalance.into());
+            }
+        }
         if let TypedRawTransaction::Polyjuice(tx) = typed_tx {
             // Intrinsic Gas
             let p = tx
@@ -4,9 +4,15 @@ static GLOBAL_ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 use anyhow::{Context, Result};
 use clap::{Arg, Command, CommandFactory, Parser};
+use godwoken_bin::subcommand::backup::{BackupCommand, COMMAND_BACKUP};
 use godwoken_bin::subcommand::db_block_validator;
+use godwoken_bin::subcommand::db_check::{DbCheckCommand, COMMAND_DB_CHECK};
 use godwoken_bin::subcommand::export_block::{ExportArgs, ExportBlock};
+use godwoken_bin::subcommand::export_parquet::{ExportParquetCommand, COMMAND_EXPORT_PARQUET};
+use godwoken_bin::subcommand::export_segments::{ExportSegmentsCommand, COMMAND_EXPORT_SEGMENTS};
 use godwoken_bin::subcommand::import_block::{ImportArgs, ImportBlock};
+use godwoken_bin::subcommand::import_segments::{ImportSegmentsCommand, COMMAND_IMPORT_SEGMENTS};
+use godwoken_bin::subcommand::migrate::{MigrateCommand, COMMAND_MIGRATE};
 use godwoken_bin::subcommand::peer_id::{PeerIdCommand, COMMAND_PEER_ID};
 use godwoken_bin::subcommand::rewind_to_last_valid_block::{
     RewindToLastValidBlockCommand, COMMAND_REWIND_TO_LAST_VALID_BLOCK,
@@ -206,7 +212,13 @@ async fn run_cli() -> Result<()> {
                 .display_order(4),
         )
         .subcommand(PeerIdCommand::command())
-        .subcommand(RewindToLastValidBlockCommand::command());
+        .subcommand(RewindToLastValidBlockCommand::command())
+        .subcommand(BackupCommand::command())
+        .subcommand(DbCheckCommand::command())
+        .subcommand(MigrateCommand::command())
+        .subcommand(ExportParquetCommand::command())
+        .subcommand(ExportSegmentsCommand::command())
+        .subcommand(ImportSegmentsCommand::command());
 
     // handle subcommands
     let matches = app.clone().get_matches();
@@ -275,6 +287,24 @@ async fn run_cli() -> Result<()> {
         Some((COMMAND_REWIND_TO_LAST_VALID_BLOCK, m)) => {
             RewindToLastValidBlockCommand::from_clap(m).run().await?;
         }
+        Some((COMMAND_BACKUP, m)) => {
+            BackupCommand::from_clap(m).run().await?;
+        }
+        Some((COMMAND_DB_CHECK, m)) => {
+            DbCheckCommand::from_clap(m).run().await?;
+        }
+        Some((COMMAND_MIGRATE, m)) => {
+            MigrateCommand::from_clap(m).run()?;
+        }
+        Some((COMMAND_EXPORT_PARQUET, m)) => {
+            ExportParquetCommand::from_clap(m).run()?;
+        }
+        Some((COMMAND_EXPORT_SEGMENTS, m)) => {
+            ExportSegmentsCommand::from_clap(m).run()?;
+        }
+        Some((COMMAND_IMPORT_SEGMENTS, m)) => {
+            ImportSegmentsCommand::from_clap(m).run().await?;
+        }
         _ => {
             // default command: start a Godwoken node
             let config_path = "./config.toml";
@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_jsonrpc_client::{HttpClient, Output, Transport};
+use clap::Parser;
+use gw_config::Config;
+use gw_jsonrpc_types::godwoken::DBCheckpoint;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::prelude::Unpack;
+
+pub const COMMAND_BACKUP: &str = "backup";
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Database backup related commands.
+#[derive(Parser)]
+#[clap(name = COMMAND_BACKUP)]
+pub enum BackupCommand {
+    /// Create a checkpoint of the running node with the `gw_create_db_checkpoint` admin RPC,
+    /// the checkpoint is created in the `rpc_server.backup_dir` of the node.
+    Create {
+        /// The RPC url of the node, admin methods must be enabled
+        #[clap(long)]
+        rpc_url: String,
+    },
+    /// Create a checkpoint of a stopped node.
+    CreateOffline {
+        /// The config file path
+        #[clap(short, long, default_value = "./config.toml")]
+        config_path: PathBuf,
+        /// The checkpoint path, must not exist
+        #[clap(short, long)]
+        output_path: PathBuf,
+    },
+    /// Restore the database of a stopped node from a checkpoint, the restored database is
+    /// verified before use.
+    Restore {
+        /// The config file path
+        #[clap(short, long, default_value = "./config.toml")]
+        config_path: PathBuf,
+        /// The checkpoint path
+        #[clap(short, long)]
+        source_path: PathBuf,
+    },
+}
+
+impl BackupCommand {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            BackupCommand::Create { rpc_url } => {
+                let checkpoint = create_checkpoint_by_rpc(&rpc_url).await?;
+                println!(
+                    "checkpoint {} created at block #{} {:#x}",
+                    checkpoint.path,
+                    checkpoint.tip_block_number.value(),
+                    checkpoint.tip_block_hash
+                );
+            }
+            BackupCommand::CreateOffline {
+                config_path,
+                output_path,
+            } => {
+                let config = read_config(&config_path)?;
+                let store = Store::open_with_check(&config.store)?;
+                store.create_checkpoint(&output_path)?;
+                let tip_number = check_checkpoint(&output_path)?;
+                println!(
+                    "checkpoint {} created at block #{}",
+                    output_path.to_string_lossy(),
+                    tip_number
+                );
+            }
+            BackupCommand::Restore {
+                config_path,
+                source_path,
+            } => {
+                let config = read_config(&config_path)?;
+                let tip_number = restore(&source_path, &config.store.path)?;
+                println!(
+                    "database {} restored at block #{}",
+                    config.store.path.to_string_lossy(),
+                    tip_number
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Restore a checkpoint to the store path, the store path must be empty.
+///
+/// Return the tip block number of the restored database.
+pub fn restore(source: &Path, store_path: &Path) -> Result<u64> {
+    if store_path.exists() && fs::read_dir(store_path)?.next().is_some() {
+        bail!(
+            "store path {} isn't empty, remove it before restoring",
+            store_path.to_string_lossy()
+        );
+    }
+
+    copy_dir(source, store_path).context("copy checkpoint")?;
+    match check_checkpoint(store_path) {
+        Ok(tip_number) => Ok(tip_number),
+        Err(err) => {
+            fs::remove_dir_all(store_path)?;
+            Err(err.context("verify restored database"))
+        }
+    }
+}
+
+/// Check the tip global state of a checkpoint, return the tip block number
+fn check_checkpoint(path: &Path) -> Result<u64> {
+    let store = Store::open_checkpoint(path)?;
+    store.check_tip_global_state()?;
+    Ok(store.get_tip_block()?.raw().number().unpack())
+}
+
+async fn create_checkpoint_by_rpc(rpc_url: &str) -> Result<DBCheckpoint> {
+    let client = HttpClient::builder().timeout(RPC_TIMEOUT).build(rpc_url)?;
+    let output = client
+        .request("gw_create_db_checkpoint", None)
+        .await
+        .map_err(|err| anyhow!("gw_create_db_checkpoint: {}", err))?;
+    match output {
+        Output::Success(success) => Ok(serde_json::from_value(success.result)?),
+        Output::Failure(failure) => Err(failure.error.into()),
+    }
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target_path = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target_path)?;
+        } else {
+            fs::copy(entry.path(), target_path)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let content = fs::read(path)
+        .with_context(|| format!("read config file from {}", path.to_string_lossy()))?;
+    let config = toml::from_slice(&content).context("parse config file")?;
+    Ok(config)
+}
@@ -10,8 +10,8 @@ use gw_challenge::{
     },
 };
 use gw_common::H256;
-use gw_config::{Config, DBBlockValidatorConfig, DebugConfig};
-use gw_generator::Generator;
+use gw_config::{Config, DBBlockValidatorConfig, DebugConfig, MemBlockConfig};
+use gw_generator::{generator::CyclesPool, Generator};
 use gw_jsonrpc_types::godwoken::ChallengeTargetType as JsonChallengeTargetType;
 use gw_store::{traits::chain_store::ChainStore, Store};
 use gw_types::{
@@ -63,6 +63,7 @@ async fn build_validator(config: Config) -> Result<DBBlockCancelChallengeValidat
         offchain_mock_context,
         config.debug,
         config.db_block_validator.unwrap_or_default(),
+        config.mem_pool.mem_block,
     );
 
     Ok(validator)
@@ -74,6 +75,7 @@ struct DBBlockCancelChallengeValidator {
     mock_ctx: OffChainMockContext,
     debug_config: DebugConfig,
     config: DBBlockValidatorConfig,
+    mem_block_config: MemBlockConfig,
 }
 
 impl DBBlockCancelChallengeValidator {
@@ -83,6 +85,7 @@ impl DBBlockCancelChallengeValidator {
         mock_ctx: OffChainMockContext,
         debug_config: DebugConfig,
         config: DBBlockValidatorConfig,
+        mem_block_config: MemBlockConfig,
     ) -> Self {
         DBBlockCancelChallengeValidator {
             generator,
@@ -90,6 +93,7 @@ impl DBBlockCancelChallengeValidator {
             mock_ctx,
             debug_config,
             config,
+            mem_block_config,
         }
     }
 
@@ -136,6 +140,32 @@ impl DBBlockCancelChallengeValidator {
 
         self.verify_withdrawals(global_state.clone(), &block)?;
         self.verify_txs(global_state, &block)?;
+        // genesis has no parent block to replay on
+        if self.config.verify_block_cycles && block_number > 0 {
+            self.verify_block_cycles(block_number)?;
+        }
+
+        Ok(())
+    }
+
+    fn verify_block_cycles(&self, block_number: u64) -> Result<()> {
+        let db = &self.store.begin_transaction();
+        let mut cycles_pool = CyclesPool::new(
+            self.mem_block_config.max_cycles_limit,
+            self.mem_block_config
+                .syscall_cycles_at(block_number)
+                .clone(),
+        );
+
+        // replayed changes are dropped with the db transaction
+        self.generator
+            .replay_block_with_cycles_pool(db, block_number, &mut cycles_pool)
+            .map_err(|err| anyhow!("block #{} cycles: {}", block_number, err))?;
+        log::info!(
+            "block #{} used cycles {}",
+            block_number,
+            cycles_pool.cycles_used()
+        );
 
         Ok(())
     }
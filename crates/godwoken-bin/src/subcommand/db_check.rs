@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use gw_block_producer::runner::BaseInitComponents;
+use gw_chain::chain::Chain;
+use gw_config::Config;
+use gw_store::{
+    integrity::{check_integrity, repair, IntegrityReport},
+    Store,
+};
+
+use super::import_block::{insert_block, set_last_submitted_block};
+
+pub const COMMAND_DB_CHECK: &str = "db-check";
+
+/// Check the integrity of the database of a stopped node
+#[derive(Parser)]
+#[clap(name = COMMAND_DB_CHECK)]
+pub struct DbCheckCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// Detach blocks down to the last consistent block, remove dangling records and replay the
+    /// detached blocks, blocks that can't be replayed are synced from L1 again after the node
+    /// restarts
+    #[clap(long)]
+    repair: bool,
+}
+
+impl DbCheckCommand {
+    pub async fn run(self) -> Result<()> {
+        let content = std::fs::read(&self.config_path).with_context(|| {
+            format!(
+                "read config file from {}",
+                self.config_path.to_string_lossy()
+            )
+        })?;
+        let config: Config = toml::from_slice(&content).context("parse config file")?;
+        let store = Store::open_with_check(&config.store)?;
+
+        let report = check_integrity(&store)?;
+        print_report(&report);
+        if report.is_consistent() {
+            return Ok(());
+        }
+        if !self.repair {
+            bail!(
+                "found {} inconsistencies, run with --repair to fix them",
+                report.inconsistencies.len()
+            );
+        }
+
+        // Replaying blocks needs the generator, reopen the store with the node components.
+        drop(store);
+        let base = BaseInitComponents::init(&config, true).await?;
+        let mut chain: Option<Chain> = None;
+        let mut last_submitted_block = None;
+        let report = repair(&base.store, |block| {
+            // Create the chain after blocks are detached, it loads the tip block on creation.
+            if chain.is_none() {
+                chain = Some(Chain::create(
+                    &base.rollup_config,
+                    &base.rollup_type_script,
+                    &config.chain,
+                    base.store.clone(),
+                    base.generator.clone(),
+                    None,
+                )?);
+            }
+            let chain = chain.as_mut().expect("chain");
+            let block_number = block.block_number();
+            insert_block(chain, block, &mut last_submitted_block)?;
+            println!("replayed block #{}", block_number);
+            Ok(())
+        })?;
+        if let Some(last_submitted_block) = last_submitted_block {
+            set_last_submitted_block(&base.store, last_submitted_block)?;
+        }
+        println!(
+            "repaired, last valid tip block #{}",
+            report.last_valid_tip_block_number
+        );
+        if !report.is_consistent() {
+            print_report(&report);
+            bail!("database is still inconsistent, restore it from a backup");
+        }
+        Ok(())
+    }
+}
+
+fn print_report(report: &IntegrityReport) {
+    println!(
+        "tip block #{}, last valid tip block #{}",
+        report.tip_block_number, report.last_valid_tip_block_number
+    );
+    for inconsistency in &report.inconsistencies {
+        println!("{}", inconsistency);
+    }
+    match report.first_bad_block() {
+        Some(number) => println!(
+            "found {} inconsistencies, first bad block #{}",
+            report.inconsistencies.len(),
+            number
+        ),
+        None => println!("found {} inconsistencies", report.inconsistencies.len()),
+    }
+}
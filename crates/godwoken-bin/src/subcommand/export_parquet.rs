@@ -0,0 +1,902 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use gw_config::Config;
+use gw_db::{read_only_db::ReadOnlyDB, schema::COLUMNS};
+use gw_store::{readonly::StoreReadonly, traits::chain_store::ChainStore};
+use gw_types::{
+    offchain::ExportedBlock,
+    packed::{self, TransactionKey},
+    prelude::*,
+};
+use gw_utils::{
+    export_block::{export_block, ExportedBlockReader},
+    script_log::{parse_log, GwLog},
+};
+use indicatif::{ProgressBar, ProgressStyle};
+use parquet::{
+    basic::Compression,
+    data_type::{ByteArray, ByteArrayType, Int64Type},
+    file::{
+        properties::WriterProperties,
+        reader::{FileReader, SerializedFileReader},
+        writer::SerializedFileWriter,
+    },
+    record::RowAccessor,
+    schema::parser::parse_message_type,
+};
+
+pub const COMMAND_EXPORT_PARQUET: &str = "export-parquet";
+
+/// Name of the table which records the export progress, it's written after the other tables.
+const BLOCKS_TABLE: &str = "blocks";
+const PARQUET_EXTENSION: &str = "parquet";
+
+/// Export blocks, transactions, receipts, logs, deposits and withdrawals as Parquet tables
+/// partitioned by block range. Blocks after the last exported block are appended.
+#[derive(Parser)]
+#[clap(name = COMMAND_EXPORT_PARQUET)]
+pub struct ExportParquetCommand {
+    /// The config file path, blocks are read from the database of the node which can keep
+    /// running
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// Read blocks from a file created by export-block instead of the database, receipts and
+    /// logs are not exported since the file doesn't contain them
+    #[clap(short, long)]
+    source_path: Option<PathBuf>,
+    /// The output dir
+    #[clap(short, long)]
+    output_dir: PathBuf,
+    /// Export until this block, default to the last finalized block or the last block in the
+    /// source file. Blocks after the last finalized block may be reverted, so they can't be
+    /// exported from the database
+    #[clap(long)]
+    to_block: Option<u64>,
+    /// Number of blocks in a partition
+    #[clap(long, default_value = "10000")]
+    partition_blocks: u64,
+    #[clap(long)]
+    show_progress: bool,
+}
+
+impl ExportParquetCommand {
+    pub fn run(self) -> Result<()> {
+        let exporter = ParquetExporter::new(&self.output_dir, self.partition_blocks)?;
+        let from_block = exporter.last_exported_block()?.map_or(0, |n| n + 1);
+
+        let progress_bar = self.show_progress.then(|| {
+            let bar = ProgressBar::new_spinner();
+            bar.set_style(
+                ProgressStyle::default_spinner().template("[{elapsed_precise}] {pos} blocks {msg}"),
+            );
+            bar
+        });
+        let progress = |_block_number: u64| {
+            if let Some(ref bar) = progress_bar {
+                bar.inc(1);
+            }
+        };
+
+        let exported = match self.source_path {
+            Some(source_path) => {
+                let blocks = file_blocks(&source_path, from_block, self.to_block)?;
+                exporter.export(blocks, progress)?
+            }
+            None => {
+                let content = fs::read(&self.config_path).with_context(|| {
+                    format!(
+                        "read config file from {}",
+                        self.config_path.to_string_lossy()
+                    )
+                })?;
+                let config: Config = toml::from_slice(&content).context("parse config file")?;
+                let snap = {
+                    let cf_names = (0..COLUMNS).map(|c| c.to_string());
+                    let db = ReadOnlyDB::open_cf(&config.store.path, cf_names)?
+                        .ok_or_else(|| anyhow!("no database"))?;
+                    StoreReadonly::new(db)
+                };
+                let last_valid_tip_block_hash = snap.get_last_valid_tip_block_hash()?;
+                let last_finalized_block_number: u64 = snap
+                    .get_block_post_global_state(&last_valid_tip_block_hash)?
+                    .ok_or_else(|| anyhow!("last valid tip block global state not found"))?
+                    .last_finalized_block_number()
+                    .unpack();
+                let to_block = match self.to_block {
+                    Some(to) if to > last_finalized_block_number => bail!(
+                        "block {} isn't finalized, to block must not be bigger than {}",
+                        to,
+                        last_finalized_block_number
+                    ),
+                    Some(to) => to,
+                    None => last_finalized_block_number,
+                };
+                exporter.export(db_blocks(&snap, from_block, to_block), progress)?
+            }
+        };
+
+        if let Some(bar) = progress_bar {
+            bar.finish_with_message("done");
+        }
+        match exported {
+            Some((from, to)) => println!("exported block {} to {}", from, to),
+            None => println!("no new block after block {}", from_block.saturating_sub(1)),
+        }
+        Ok(())
+    }
+}
+
+pub struct ArchiveBlock {
+    pub exported: ExportedBlock,
+    /// Receipts of the transactions, None if the source doesn't contain receipts
+    pub receipts: Option<Vec<packed::TxReceipt>>,
+}
+
+/// Read blocks and their receipts from the database.
+pub fn db_blocks(
+    snap: &StoreReadonly,
+    from_block: u64,
+    to_block: u64,
+) -> impl Iterator<Item = Result<ArchiveBlock>> + '_ {
+    (from_block..=to_block).map(move |block_number| {
+        let exported = export_block(snap, block_number)?;
+        let block_hash = exported.block.hash();
+        let tx_count = exported.block.transactions().len() as u32;
+        let receipts = (0..tx_count)
+            .map(|index| {
+                let key = TransactionKey::build_transaction_key(block_hash.pack(), index);
+                snap.get_transaction_receipt_by_key(&key)?
+                    .ok_or_else(|| anyhow!("block {} tx {} receipt not found", block_number, index))
+            })
+            .collect::<Result<_>>()?;
+        Ok(ArchiveBlock {
+            exported,
+            receipts: Some(receipts),
+        })
+    })
+}
+
+/// Read blocks from a file created by export-block, starting from `from_block`.
+pub fn file_blocks(
+    path: &Path,
+    from_block: u64,
+    to_block: Option<u64>,
+) -> Result<impl Iterator<Item = Result<ArchiveBlock>>> {
+    let f = File::open(path).with_context(|| format!("open {}", path.to_string_lossy()))?;
+    let mut reader = ExportedBlockReader::new(BufReader::new(f));
+    if let Some((first_block, _size)) = reader.peek_block()? {
+        let first_block_number = first_block.block_number();
+        if first_block_number > from_block {
+            bail!(
+                "missing blocks from {} to {}",
+                from_block,
+                first_block_number - 1
+            );
+        }
+        reader.skip_blocks(from_block - first_block_number)?;
+    }
+
+    let blocks = reader
+        .map(|block| {
+            block.map(|(exported, _size)| ArchiveBlock {
+                exported,
+                receipts: None,
+            })
+        })
+        .take_while(move |block| match (block, to_block) {
+            (Ok(block), Some(to)) => block.exported.block_number() <= to,
+            _ => true,
+        });
+    Ok(blocks)
+}
+
+/// Write blocks to Parquet tables.
+///
+/// Each table is a dir in the output dir, files are placed in Hive style partition dirs
+/// `block_range=<first>_<last>` and named by the blocks they contain, e.g.
+/// `logs/block_range=0_9999/0_4999.parquet`. Files are written to a temporary path and renamed,
+/// the blocks table is written last, so it records the last exported block.
+pub struct ParquetExporter {
+    output_dir: PathBuf,
+    partition_blocks: u64,
+}
+
+impl ParquetExporter {
+    pub fn new<P: AsRef<Path>>(output_dir: P, partition_blocks: u64) -> Result<Self> {
+        if partition_blocks == 0 {
+            bail!("partition blocks must be bigger than 0");
+        }
+        let output_dir = output_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&output_dir)?;
+        Ok(ParquetExporter {
+            output_dir,
+            partition_blocks,
+        })
+    }
+
+    /// The last block in the blocks table.
+    pub fn last_exported_block(&self) -> Result<Option<u64>> {
+        let files = self.table_files(BLOCKS_TABLE)?;
+        Ok(files.iter().map(|(_from, to, _path)| *to).max())
+    }
+
+    /// Export the blocks, blocks must follow the last exported block. Return the range of the
+    /// exported blocks.
+    ///
+    /// Exported blocks are never rewritten, a first block whose parent isn't the last exported
+    /// block, e.g. the source chain is reorganized, is rejected.
+    pub fn export(
+        &self,
+        blocks: impl Iterator<Item = Result<ArchiveBlock>>,
+        progress: impl Fn(u64),
+    ) -> Result<Option<(u64, u64)>> {
+        let last_exported_block = self.last_exported_block()?;
+        self.remove_unfinished_files(last_exported_block)?;
+        let mut parent_block_hash = match last_exported_block {
+            Some(block_number) => Some(self.exported_block_hash(block_number)?),
+            None => None,
+        };
+
+        let mut next_block_number = last_exported_block.map_or(0, |n| n + 1);
+        let first_block_number = next_block_number;
+        let mut tables = ArchiveTables::default();
+        let mut chunk_from = next_block_number;
+        for block in blocks {
+            let block = block?;
+            let block_number = block.exported.block_number();
+            if block_number != next_block_number {
+                bail!(
+                    "expect block {}, got block {}",
+                    next_block_number,
+                    block_number
+                );
+            }
+            if let Some(parent_block_hash) = parent_block_hash.take() {
+                let raw = block.exported.block.raw();
+                if hex(raw.parent_block_hash().as_slice()) != parent_block_hash {
+                    bail!(
+                        "block {} doesn't follow exported block {} {}, the chain is reorganized",
+                        block_number,
+                        block_number - 1,
+                        parent_block_hash
+                    );
+                }
+            }
+            tables.append(&block)?;
+            progress(block_number);
+            next_block_number += 1;
+
+            if (block_number + 1) % self.partition_blocks == 0 {
+                self.write_chunk(&tables, chunk_from, block_number)?;
+                tables = ArchiveTables::default();
+                chunk_from = next_block_number;
+            }
+        }
+        if chunk_from < next_block_number {
+            self.write_chunk(&tables, chunk_from, next_block_number - 1)?;
+        }
+
+        if next_block_number == first_block_number {
+            return Ok(None);
+        }
+        Ok(Some((first_block_number, next_block_number - 1)))
+    }
+
+    fn write_chunk(&self, tables: &ArchiveTables, from: u64, to: u64) -> Result<()> {
+        let partition_from = from / self.partition_blocks * self.partition_blocks;
+        let partition_to = partition_from + self.partition_blocks - 1;
+        let partition = format!("block_range={}_{}", partition_from, partition_to);
+        let file_name = format!("{}_{}.{}", from, to, PARQUET_EXTENSION);
+        for table in tables.in_write_order() {
+            let dir = self.output_dir.join(table.name).join(&partition);
+            fs::create_dir_all(&dir)?;
+            let path = dir.join(&file_name);
+            let tmp_path = path.with_extension("tmp");
+            table
+                .write(&tmp_path)
+                .with_context(|| format!("write {}", path.to_string_lossy()))?;
+            fs::rename(&tmp_path, &path)?;
+        }
+        log::info!("exported block {} to {}", from, to);
+        Ok(())
+    }
+
+    /// Hash of an exported block in the blocks table.
+    fn exported_block_hash(&self, block_number: u64) -> Result<String> {
+        let (from, _to, path) = self
+            .table_files(BLOCKS_TABLE)?
+            .into_iter()
+            .find(|(from, to, _path)| (*from..=*to).contains(&block_number))
+            .ok_or_else(|| anyhow!("exported block {} not found", block_number))?;
+        let hash_index = BLOCK_FIELDS
+            .iter()
+            .position(|field| field.name == "block_hash")
+            .expect("block hash field");
+        let reader = SerializedFileReader::new(File::open(&path)?)?;
+        let row = reader
+            .get_row_iter(None)?
+            .nth((block_number - from) as usize)
+            .ok_or_else(|| anyhow!("block {} not found in {}", block_number, path.display()))?;
+        Ok(row.get_string(hash_index)?.clone())
+    }
+
+    /// Remove files written by an interrupted export, they're after the last exported block.
+    fn remove_unfinished_files(&self, last_exported_block: Option<u64>) -> Result<()> {
+        for table in ArchiveTables::default().in_write_order() {
+            for (from, _to, path) in self.table_files(table.name)? {
+                if last_exported_block.map_or(true, |last| from > last) {
+                    log::warn!("remove unfinished file {}", path.to_string_lossy());
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return (from, to, path) of the files in the table.
+    fn table_files(&self, table: &str) -> Result<Vec<(u64, u64, PathBuf)>> {
+        let table_dir = self.output_dir.join(table);
+        if !table_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for partition in fs::read_dir(table_dir)? {
+            let partition = partition?.path();
+            if !partition.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(partition)? {
+                let path = file?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some(PARQUET_EXTENSION) {
+                    continue;
+                }
+                let range = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.split_once('_'))
+                    .and_then(|(from, to)| Some((from.parse().ok()?, to.parse().ok()?)));
+                match range {
+                    Some((from, to)) => files.push((from, to, path)),
+                    None => log::warn!("ignore unknown file {}", path.to_string_lossy()),
+                }
+            }
+        }
+        Ok(files)
+    }
+}
+
+struct ArchiveTables {
+    blocks: Table,
+    transactions: Table,
+    receipts: Table,
+    logs: Table,
+    deposits: Table,
+    withdrawals: Table,
+}
+
+impl Default for ArchiveTables {
+    fn default() -> Self {
+        ArchiveTables {
+            blocks: Table::new(BLOCKS_TABLE, BLOCK_FIELDS),
+            transactions: Table::new("transactions", TRANSACTION_FIELDS),
+            receipts: Table::new("receipts", RECEIPT_FIELDS),
+            logs: Table::new("logs", LOG_FIELDS),
+            deposits: Table::new("deposits", DEPOSIT_FIELDS),
+            withdrawals: Table::new("withdrawals", WITHDRAWAL_FIELDS),
+        }
+    }
+}
+
+impl ArchiveTables {
+    fn in_write_order(&self) -> [&Table; 6] {
+        [
+            &self.transactions,
+            &self.receipts,
+            &self.logs,
+            &self.deposits,
+            &self.withdrawals,
+            &self.blocks,
+        ]
+    }
+
+    fn append(&mut self, archive_block: &ArchiveBlock) -> Result<()> {
+        let exported = &archive_block.exported;
+        let block = &exported.block;
+        let raw = block.raw();
+        let block_number: u64 = raw.number().unpack();
+        let timestamp: u64 = raw.timestamp().unpack();
+        let prev_account = raw.prev_account();
+        let post_account = raw.post_account();
+        let prev_account_count: u32 = prev_account.count().unpack();
+        let post_account_count: u32 = post_account.count().unpack();
+        let last_finalized_block_number: u64 = exported
+            .post_global_state
+            .last_finalized_block_number()
+            .unpack();
+        self.blocks.push(vec![
+            block_number.into(),
+            hex(&block.hash()).into(),
+            hex(raw.parent_block_hash().as_slice()).into(),
+            timestamp.into(),
+            hex(&raw.block_producer().raw_data()).into(),
+            hex(prev_account.merkle_root().as_slice()).into(),
+            prev_account_count.into(),
+            hex(post_account.merkle_root().as_slice()).into(),
+            post_account_count.into(),
+            (block.transactions().len() as u64).into(),
+            (block.withdrawals().len() as u64).into(),
+            (exported.deposit_info_vec.len() as u64).into(),
+            last_finalized_block_number.into(),
+            exported.submit_tx_hash.map(|h| hex(h.as_slice())).into(),
+        ])?;
+
+        if let Some(ref receipts) = archive_block.receipts {
+            if receipts.len() != block.transactions().len() {
+                bail!(
+                    "block {} has {} txs but {} receipts",
+                    block_number,
+                    block.transactions().len(),
+                    receipts.len()
+                );
+            }
+        }
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            let raw_tx = tx.raw();
+            let tx_hash = hex(&tx.hash());
+            let chain_id: u64 = raw_tx.chain_id().unpack();
+            let from_id: u32 = raw_tx.from_id().unpack();
+            let to_id: u32 = raw_tx.to_id().unpack();
+            let nonce: u32 = raw_tx.nonce().unpack();
+            self.transactions.push(vec![
+                block_number.into(),
+                (index as u64).into(),
+                tx_hash.clone().into(),
+                chain_id.into(),
+                from_id.into(),
+                to_id.into(),
+                nonce.into(),
+                hex(&raw_tx.args().raw_data()).into(),
+            ])?;
+
+            let receipt = match archive_block.receipts {
+                Some(ref receipts) => &receipts[index],
+                None => continue,
+            };
+            let post_state = receipt.post_state();
+            let post_account_count: u32 = post_state.count().unpack();
+            let exit_code: u8 = receipt.exit_code().into();
+            self.receipts.push(vec![
+                block_number.into(),
+                (index as u64).into(),
+                tx_hash.clone().into(),
+                exit_code.into(),
+                hex(post_state.merkle_root().as_slice()).into(),
+                post_account_count.into(),
+                (receipt.logs().len() as u64).into(),
+            ])?;
+
+            for (log_index, item) in receipt.logs().into_iter().enumerate() {
+                let account_id: u32 = item.account_id().unpack();
+                let service_flag: u8 = item.service_flag().into();
+                let mut row = vec![
+                    block_number.into(),
+                    (index as u64).into(),
+                    tx_hash.clone().into(),
+                    (log_index as u64).into(),
+                    account_id.into(),
+                    service_flag.into(),
+                ];
+                row.extend(decode_log(&item));
+                self.logs.push(row)?;
+            }
+        }
+
+        for (index, info) in exported.deposit_info_vec.clone().into_iter().enumerate() {
+            let request = info.request();
+            let out_point = info.cell().out_point();
+            let out_point_index: u32 = out_point.index().unpack();
+            let capacity: u64 = request.capacity().unpack();
+            let amount: u128 = request.amount().unpack();
+            let registry_id: u32 = request.registry_id().unpack();
+            self.deposits.push(vec![
+                block_number.into(),
+                (index as u64).into(),
+                hex(out_point.tx_hash().as_slice()).into(),
+                out_point_index.into(),
+                capacity.into(),
+                amount.to_string().into(),
+                hex(request.sudt_script_hash().as_slice()).into(),
+                hex(&request.script().hash()).into(),
+                registry_id.into(),
+            ])?;
+        }
+
+        for (index, withdrawal) in block.withdrawals().into_iter().enumerate() {
+            let raw_withdrawal = withdrawal.raw();
+            let nonce: u32 = raw_withdrawal.nonce().unpack();
+            let chain_id: u64 = raw_withdrawal.chain_id().unpack();
+            let capacity: u64 = raw_withdrawal.capacity().unpack();
+            let amount: u128 = raw_withdrawal.amount().unpack();
+            let registry_id: u32 = raw_withdrawal.registry_id().unpack();
+            let fee: u128 = raw_withdrawal.fee().unpack();
+            self.withdrawals.push(vec![
+                block_number.into(),
+                (index as u64).into(),
+                hex(&withdrawal.hash()).into(),
+                hex(raw_withdrawal.account_script_hash().as_slice()).into(),
+                nonce.into(),
+                chain_id.into(),
+                capacity.into(),
+                amount.to_string().into(),
+                hex(raw_withdrawal.sudt_script_hash().as_slice()).into(),
+                registry_id.into(),
+                hex(raw_withdrawal.owner_lock_hash().as_slice()).into(),
+                fee.to_string().into(),
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+/// Decoded columns of a log, all null if the log can't be decoded.
+fn decode_log(item: &packed::LogItem) -> Vec<Value> {
+    let mut values: Vec<Value> = (0..LOG_FIELDS.len() - 6).map(|_| Value::Null).collect();
+    let log = match parse_log(item) {
+        Ok(log) => log,
+        Err(_) => return values,
+    };
+    let (kind, columns) = match log {
+        GwLog::SudtTransfer {
+            sudt_id,
+            from_address,
+            to_address,
+            amount,
+        } => (
+            "sudt_transfer",
+            vec![
+                (1, sudt_id.into()),
+                (2, hex(&from_address.to_bytes()).into()),
+                (3, hex(&to_address.to_bytes()).into()),
+                (4, amount.to_string().into()),
+            ],
+        ),
+        GwLog::SudtPayFee {
+            sudt_id,
+            from_address,
+            block_producer_address,
+            amount,
+        } => (
+            "sudt_pay_fee",
+            vec![
+                (1, sudt_id.into()),
+                (2, hex(&from_address.to_bytes()).into()),
+                (3, hex(&block_producer_address.to_bytes()).into()),
+                (4, amount.to_string().into()),
+            ],
+        ),
+        GwLog::PolyjuiceSystem {
+            gas_used,
+            cumulative_gas_used,
+            created_address,
+            status_code,
+        } => (
+            "polyjuice_system",
+            vec![
+                (5, gas_used.into()),
+                (6, cumulative_gas_used.into()),
+                (7, hex(&created_address).into()),
+                (8, status_code.into()),
+            ],
+        ),
+        GwLog::PolyjuiceUser {
+            address,
+            data,
+            topics,
+        } => {
+            let topics: Vec<String> = topics.iter().map(|t| hex(t.as_slice())).collect();
+            (
+                "polyjuice_user",
+                vec![
+                    (9, hex(&address).into()),
+                    (10, hex(&data).into()),
+                    (11, topics.join(",").into()),
+                ],
+            )
+        }
+    };
+    values[0] = kind.to_string().into();
+    for (index, value) in columns {
+        values[index] = value;
+    }
+    values
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 + bytes.len() * 2);
+    s.push_str("0x");
+    for b in bytes {
+        write!(s, "{:02x}", b).expect("write hex");
+    }
+    s
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Int64,
+    Utf8,
+}
+
+struct Field {
+    name: &'static str,
+    ty: ColumnType,
+    optional: bool,
+}
+
+const fn required(name: &'static str, ty: ColumnType) -> Field {
+    Field {
+        name,
+        ty,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, ty: ColumnType) -> Field {
+    Field {
+        name,
+        ty,
+        optional: true,
+    }
+}
+
+// Hashes, addresses and bytes are 0x-prefixed hex strings, u128 amounts are decimal strings.
+const BLOCK_FIELDS: &[Field] = &[
+    required("block_number", ColumnType::Int64),
+    required("block_hash", ColumnType::Utf8),
+    required("parent_block_hash", ColumnType::Utf8),
+    required("timestamp", ColumnType::Int64),
+    required("block_producer", ColumnType::Utf8),
+    required("prev_account_root", ColumnType::Utf8),
+    required("prev_account_count", ColumnType::Int64),
+    required("post_account_root", ColumnType::Utf8),
+    required("post_account_count", ColumnType::Int64),
+    required("tx_count", ColumnType::Int64),
+    required("withdrawal_count", ColumnType::Int64),
+    required("deposit_count", ColumnType::Int64),
+    required("last_finalized_block_number", ColumnType::Int64),
+    optional("submit_tx_hash", ColumnType::Utf8),
+];
+
+const TRANSACTION_FIELDS: &[Field] = &[
+    required("block_number", ColumnType::Int64),
+    required("tx_index", ColumnType::Int64),
+    required("tx_hash", ColumnType::Utf8),
+    required("chain_id", ColumnType::Int64),
+    required("from_id", ColumnType::Int64),
+    required("to_id", ColumnType::Int64),
+    required("nonce", ColumnType::Int64),
+    required("args", ColumnType::Utf8),
+];
+
+const RECEIPT_FIELDS: &[Field] = &[
+    required("block_number", ColumnType::Int64),
+    required("tx_index", ColumnType::Int64),
+    required("tx_hash", ColumnType::Utf8),
+    required("exit_code", ColumnType::Int64),
+    required("post_account_root", ColumnType::Utf8),
+    required("post_account_count", ColumnType::Int64),
+    required("log_count", ColumnType::Int64),
+];
+
+// The fields after `service_flag` are decoded by `decode_log`.
+const LOG_FIELDS: &[Field] = &[
+    required("block_number", ColumnType::Int64),
+    required("tx_index", ColumnType::Int64),
+    required("tx_hash", ColumnType::Utf8),
+    required("log_index", ColumnType::Int64),
+    required("account_id", ColumnType::Int64),
+    required("service_flag", ColumnType::Int64),
+    optional("kind", ColumnType::Utf8),
+    optional("sudt_id", ColumnType::Int64),
+    optional("from_address", ColumnType::Utf8),
+    optional("to_address", ColumnType::Utf8),
+    optional("amount", ColumnType::Utf8),
+    optional("gas_used", ColumnType::Int64),
+    optional("cumulative_gas_used", ColumnType::Int64),
+    optional("created_address", ColumnType::Utf8),
+    optional("status_code", ColumnType::Int64),
+    optional("address", ColumnType::Utf8),
+    optional("data", ColumnType::Utf8),
+    optional("topics", ColumnType::Utf8),
+];
+
+const DEPOSIT_FIELDS: &[Field] = &[
+    required("block_number", ColumnType::Int64),
+    required("deposit_index", ColumnType::Int64),
+    required("out_point_tx_hash", ColumnType::Utf8),
+    required("out_point_index", ColumnType::Int64),
+    required("capacity", ColumnType::Int64),
+    required("amount", ColumnType::Utf8),
+    required("sudt_script_hash", ColumnType::Utf8),
+    required("account_script_hash", ColumnType::Utf8),
+    required("registry_id", ColumnType::Int64),
+];
+
+const WITHDRAWAL_FIELDS: &[Field] = &[
+    required("block_number", ColumnType::Int64),
+    required("withdrawal_index", ColumnType::Int64),
+    required("withdrawal_hash", ColumnType::Utf8),
+    required("account_script_hash", ColumnType::Utf8),
+    required("nonce", ColumnType::Int64),
+    required("chain_id", ColumnType::Int64),
+    required("capacity", ColumnType::Int64),
+    required("amount", ColumnType::Utf8),
+    required("sudt_script_hash", ColumnType::Utf8),
+    required("registry_id", ColumnType::Int64),
+    required("owner_lock_hash", ColumnType::Utf8),
+    required("fee", ColumnType::Utf8),
+];
+
+enum Value {
+    Int64(i64),
+    Utf8(String),
+    Null,
+}
+
+impl From<u64> for Value {
+    fn from(v: u64) -> Self {
+        Value::Int64(v as i64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(v: u32) -> Self {
+        Value::Int64(v.into())
+    }
+}
+
+impl From<u8> for Value {
+    fn from(v: u8) -> Self {
+        Value::Int64(v.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Utf8(v)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+/// Rows of a table, written as a Parquet file with one row group.
+struct Table {
+    name: &'static str,
+    fields: &'static [Field],
+    rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn new(name: &'static str, fields: &'static [Field]) -> Self {
+        Table {
+            name,
+            fields,
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, row: Vec<Value>) -> Result<()> {
+        if row.len() != self.fields.len() {
+            bail!(
+                "table {} expect {} columns, got {}",
+                self.name,
+                self.fields.len(),
+                row.len()
+            );
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    fn message_type(&self) -> String {
+        let mut message = format!("message {} {{\n", self.name);
+        for field in self.fields {
+            let repetition = if field.optional {
+                "optional"
+            } else {
+                "required"
+            };
+            let ty = match field.ty {
+                ColumnType::Int64 => "int64",
+                ColumnType::Utf8 => "binary",
+            };
+            let annotation = match field.ty {
+                ColumnType::Int64 => "",
+                ColumnType::Utf8 => " (UTF8)",
+            };
+            writeln!(
+                message,
+                "  {} {} {}{};",
+                repetition, ty, field.name, annotation
+            )
+            .expect("write to string");
+        }
+        message.push('}');
+        message
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        let schema = Arc::new(parse_message_type(&self.message_type())?);
+        let props = Arc::new(
+            WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .build(),
+        );
+        let mut writer = SerializedFileWriter::new(File::create(path)?, schema, props)?;
+        if !self.rows.is_empty() {
+            let mut row_group = writer.next_row_group()?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column()? {
+                let field = &self.fields[index];
+                let mut def_levels = Vec::with_capacity(self.rows.len());
+                match field.ty {
+                    ColumnType::Int64 => {
+                        let mut values = Vec::with_capacity(self.rows.len());
+                        for row in &self.rows {
+                            match &row[index] {
+                                Value::Int64(v) => {
+                                    values.push(*v);
+                                    def_levels.push(1);
+                                }
+                                value => def_levels.push(self.check_null(field, value)?),
+                            }
+                        }
+                        let def_levels = field.optional.then(|| def_levels.as_slice());
+                        column
+                            .typed::<Int64Type>()
+                            .write_batch(&values, def_levels, None)?;
+                    }
+                    ColumnType::Utf8 => {
+                        let mut values = Vec::with_capacity(self.rows.len());
+                        for row in &self.rows {
+                            match &row[index] {
+                                Value::Utf8(v) => {
+                                    values.push(ByteArray::from(v.as_str()));
+                                    def_levels.push(1);
+                                }
+                                value => def_levels.push(self.check_null(field, value)?),
+                            }
+                        }
+                        let def_levels = field.optional.then(|| def_levels.as_slice());
+                        column
+                            .typed::<ByteArrayType>()
+                            .write_batch(&values, def_levels, None)?;
+                    }
+                }
+                column.close()?;
+                index += 1;
+            }
+            row_group.close()?;
+        }
+        writer.close()?;
+        Ok(())
+    }
+
+    // Return the definition level of a null value.
+    fn check_null(&self, field: &Field, value: &Value) -> Result<i16> {
+        match value {
+            Value::Null if field.optional => Ok(0),
+            Value::Null => bail!("table {} column {} is required", self.name, field.name),
+            _ => bail!("table {} column {} type mismatch", self.name, field.name),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use gw_common::H256;
+use gw_config::Config;
+use gw_db::{read_only_db::ReadOnlyDB, schema::COLUMNS};
+use gw_store::{readonly::StoreReadonly, traits::chain_store::ChainStore};
+use gw_types::prelude::Unpack;
+use gw_utils::export_block::export_block;
+use gw_utils::export_segment::{SegmentManifest, SegmentWriter, DEFAULT_COMPRESSION_LEVEL};
+use indicatif::{ProgressBar, ProgressStyle};
+
+pub const COMMAND_EXPORT_SEGMENTS: &str = "export-segments";
+
+/// Export blocks to compressed segments with a manifest, blocks after the last exported segment
+/// are appended
+#[derive(Parser)]
+#[clap(name = COMMAND_EXPORT_SEGMENTS)]
+pub struct ExportSegmentsCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// The output dir for segments and the manifest
+    #[clap(short, long)]
+    output_dir: PathBuf,
+    /// Export until this block, default to the last valid tip block
+    #[clap(short, long)]
+    to_block: Option<u64>,
+    /// Number of blocks in a segment
+    #[clap(long, default_value = "1000")]
+    segment_blocks: u64,
+    /// Zstd compression level
+    #[clap(long, default_value = "3")]
+    compression_level: i32,
+    /// Show progress bar
+    #[clap(short = 'p', long)]
+    show_progress: bool,
+}
+
+impl ExportSegmentsCommand {
+    pub fn run(self) -> Result<()> {
+        let content = fs::read(&self.config_path).with_context(|| {
+            format!(
+                "read config file from {}",
+                self.config_path.to_string_lossy()
+            )
+        })?;
+        let config: Config = toml::from_slice(&content).context("parse config file")?;
+        let snap = {
+            let cf_names = (0..COLUMNS).map(|c| c.to_string());
+            let db = ReadOnlyDB::open_cf(&config.store.path, cf_names)?
+                .ok_or_else(|| anyhow!("no database"))?;
+            StoreReadonly::new(db)
+        };
+
+        let db_last_valid_tip_block_number =
+            snap.get_last_valid_tip_block()?.raw().number().unpack();
+        let to_block = match self.to_block {
+            Some(to) if to > db_last_valid_tip_block_number => bail!(
+                "bad block found, to block must not be bigger than {}",
+                db_last_valid_tip_block_number
+            ),
+            Some(to) => to,
+            None => db_last_valid_tip_block_number,
+        };
+        let rollup_type_hash: [u8; 32] = config.genesis.rollup_type_hash.into();
+
+        let mut export = ExportSegments::new_unchecked(
+            snap,
+            self.output_dir,
+            rollup_type_hash.into(),
+            to_block,
+            self.segment_blocks,
+        );
+        export.compression_level = self.compression_level;
+        export.show_progress = self.show_progress;
+        export.execute()
+    }
+}
+
+/// ExportSegments
+///
+/// Export blocks from readonly database (don't need to exit node process). Each segment is
+/// written and recorded in the manifest before the next one, so an interrupted export resumes
+/// from the last recorded segment.
+pub struct ExportSegments {
+    snap: StoreReadonly,
+    output_dir: PathBuf,
+    rollup_type_hash: H256,
+    to_block: u64,
+    segment_blocks: u64,
+    compression_level: i32,
+    show_progress: bool,
+}
+
+impl ExportSegments {
+    pub fn new_unchecked(
+        snap: StoreReadonly,
+        output_dir: PathBuf,
+        rollup_type_hash: H256,
+        to_block: u64,
+        segment_blocks: u64,
+    ) -> Self {
+        ExportSegments {
+            snap,
+            output_dir,
+            rollup_type_hash,
+            to_block,
+            segment_blocks,
+            compression_level: DEFAULT_COMPRESSION_LEVEL,
+            show_progress: false,
+        }
+    }
+
+    pub fn execute(self) -> Result<()> {
+        if self.segment_blocks == 0 {
+            bail!("segment blocks must be bigger than 0");
+        }
+        fs::create_dir_all(&self.output_dir)?;
+        let mut manifest = match SegmentManifest::load(&self.output_dir)? {
+            Some(manifest) => {
+                manifest.check_rollup_type_hash(self.rollup_type_hash.as_slice())?;
+                manifest
+            }
+            None => SegmentManifest::new(self.rollup_type_hash.as_slice())?,
+        };
+
+        let from_block = manifest.next_block_number().unwrap_or(0);
+        if from_block > self.to_block {
+            println!("no new block after block {}", from_block.saturating_sub(1));
+            return Ok(());
+        }
+        if from_block > 0 {
+            println!("resume from block {}", from_block);
+        }
+
+        let progress_bar = if self.show_progress {
+            let bar = ProgressBar::new(self.to_block - from_block + 1);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+                    .progress_chars("##-"),
+            );
+            Some(bar)
+        } else {
+            None
+        };
+
+        let mut segment_from = from_block;
+        while segment_from <= self.to_block {
+            let segment_to = self
+                .to_block
+                .min(segment_from.saturating_add(self.segment_blocks - 1));
+            let mut writer = SegmentWriter::new(self.compression_level)?;
+            for block_number in segment_from..=segment_to {
+                writer.push(export_block(&self.snap, block_number)?)?;
+                if let Some(ref progress_bar) = progress_bar {
+                    progress_bar.inc(1)
+                }
+            }
+            let (info, data) = writer.finish()?;
+
+            let path = self.output_dir.join(&info.file_name);
+            let tmp_path = path.with_extension("tmp");
+            fs::write(&tmp_path, &data)?;
+            fs::rename(&tmp_path, &path)?;
+            manifest.push(info)?;
+            manifest.save(&self.output_dir)?;
+
+            segment_from = segment_to + 1;
+        }
+
+        if let Some(ref progress_bar) = progress_bar {
+            progress_bar.finish_with_message("done");
+        }
+
+        Ok(())
+    }
+}
@@ -203,18 +203,7 @@ impl ImportBlock {
         // When the node starts it will sync with L1 and correct the last
         // confirmed block.
         if let Some(last_submitted_block) = last_submitted_block {
-            let tx_db = &self.chain.store().begin_transaction();
-            let block_hash = tx_db
-                .get_block_hash_by_number(last_submitted_block)?
-                .context("get block hash")?;
-            let nh = NumberHash::new_builder()
-                .number(last_submitted_block.pack())
-                .block_hash(block_hash.pack())
-                .build();
-            let nh = nh.as_reader();
-            tx_db.set_last_submitted_block_number_hash(&nh)?;
-            tx_db.set_last_confirmed_block_number_hash(&nh)?;
-            tx_db.commit()?;
+            set_last_submitted_block(self.chain.store(), last_submitted_block)?;
         }
 
         if let Some(ref progress_bar) = self.progress_bar {
@@ -227,7 +216,7 @@ impl ImportBlock {
     }
 }
 
-fn insert_block(
+pub(crate) fn insert_block(
     chain: &mut Chain,
     exported: ExportedBlock,
     last_submitted_block: &mut Option<u64>,
@@ -263,3 +252,19 @@ fn insert_block(
 
     Ok(())
 }
+
+pub(crate) fn set_last_submitted_block(store: &Store, block_number: u64) -> Result<()> {
+    let tx_db = &store.begin_transaction();
+    let block_hash = tx_db
+        .get_block_hash_by_number(block_number)?
+        .context("get block hash")?;
+    let nh = NumberHash::new_builder()
+        .number(block_number.pack())
+        .block_hash(block_hash.pack())
+        .build();
+    let nh = nh.as_reader();
+    tx_db.set_last_submitted_block_number_hash(&nh)?;
+    tx_db.set_last_confirmed_block_number_hash(&nh)?;
+    tx_db.commit()?;
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use gw_block_producer::runner::BaseInitComponents;
+use gw_chain::chain::Chain;
+use gw_common::H256;
+use gw_config::Config;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{offchain::ExportedBlock, prelude::Unpack};
+use gw_utils::export_segment::{read_segment, SegmentInfo, SegmentManifest, MANIFEST_FILE_NAME};
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::{Client, Url};
+
+use super::import_block::{insert_block, set_last_submitted_block};
+
+pub const COMMAND_IMPORT_SEGMENTS: &str = "import-segments";
+pub const DEFAULT_FETCH_JOBS: usize = 4;
+
+/// Import blocks from segments created by export-segments, import resumes from the segment
+/// which contains the block after the database tip
+#[derive(Parser)]
+#[clap(name = COMMAND_IMPORT_SEGMENTS)]
+pub struct ImportSegmentsCommand {
+    /// The config file path
+    #[clap(short, long, default_value = "./config.toml")]
+    config_path: PathBuf,
+    /// Segment sources, a dir or a http(s) url. Segments are fetched from the sources in
+    /// turn and fetched from another source if it fails
+    #[clap(short, long = "source", required = true)]
+    sources: Vec<String>,
+    /// Import until this block
+    #[clap(short, long)]
+    to_block: Option<u64>,
+    /// Number of segments fetched in parallel
+    #[clap(short, long, default_value = "4")]
+    jobs: usize,
+    /// Show progress bar
+    #[clap(short = 'p', long)]
+    show_progress: bool,
+}
+
+impl ImportSegmentsCommand {
+    pub async fn run(self) -> Result<()> {
+        let content = fs::read(&self.config_path).with_context(|| {
+            format!(
+                "read config file from {}",
+                self.config_path.to_string_lossy()
+            )
+        })?;
+        let config: Config = toml::from_slice(&content).context("parse config file")?;
+        let sources = self
+            .sources
+            .iter()
+            .map(|s| SegmentSource::parse(s))
+            .collect::<Result<_>>()?;
+
+        let base = BaseInitComponents::init(&config, true).await?;
+        let chain = Chain::create(
+            &base.rollup_config,
+            &base.rollup_type_script,
+            &config.chain,
+            base.store,
+            base.generator,
+            None,
+        )?;
+
+        let mut import = ImportSegments::new_unchecked(chain, sources);
+        import.to_block = self.to_block;
+        import.jobs = self.jobs;
+        import.show_progress = self.show_progress;
+        import.execute().await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SegmentSource {
+    Dir(PathBuf),
+    Http(Url),
+}
+
+impl SegmentSource {
+    pub fn parse(source: &str) -> Result<Self> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            let mut url = Url::parse(source)?;
+            // Join file names to the url instead of replacing its last path segment
+            if !url.path().ends_with('/') {
+                let path = format!("{}/", url.path());
+                url.set_path(&path);
+            }
+            Ok(SegmentSource::Http(url))
+        } else {
+            Ok(SegmentSource::Dir(source.into()))
+        }
+    }
+
+    async fn fetch(&self, client: &Client, file_name: &str) -> Result<Vec<u8>> {
+        match self {
+            SegmentSource::Dir(dir) => Ok(tokio::fs::read(dir.join(file_name)).await?),
+            SegmentSource::Http(url) => {
+                let resp = client.get(url.join(file_name)?).send().await?;
+                Ok(resp.error_for_status()?.bytes().await?.to_vec())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SegmentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentSource::Dir(dir) => write!(f, "{}", dir.to_string_lossy()),
+            SegmentSource::Http(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+pub struct ImportSegments {
+    chain: Chain,
+    sources: Vec<SegmentSource>,
+    to_block: Option<u64>,
+    jobs: usize,
+    show_progress: bool,
+}
+
+impl ImportSegments {
+    pub fn new_unchecked(chain: Chain, sources: Vec<SegmentSource>) -> Self {
+        ImportSegments {
+            chain,
+            sources,
+            to_block: None,
+            jobs: DEFAULT_FETCH_JOBS,
+            show_progress: false,
+        }
+    }
+
+    // Disable warning for bin
+    #[allow(dead_code)]
+    pub fn store(&self) -> &Store {
+        self.chain.store()
+    }
+
+    pub async fn execute(mut self) -> Result<()> {
+        if self.sources.is_empty() {
+            bail!("no segment source");
+        }
+        let store = self.chain.store();
+        store.check_state()?;
+        if store.get_last_valid_tip_block_hash()? != store.get_tip_block_hash()? {
+            bail!("database with tip bad block");
+        }
+
+        let client = Client::new();
+        let manifest = self.fetch_manifest(&client).await?;
+        let rollup_type_hash = self.chain.generator().rollup_context().rollup_script_hash;
+        manifest.check_rollup_type_hash(rollup_type_hash.as_slice())?;
+
+        let db_tip_block = self.chain.store().get_tip_block()?;
+        let db_tip_block_number: u64 = db_tip_block.raw().number().unpack();
+        let to_block = self.to_block.unwrap_or(u64::MAX);
+        let segments: Vec<SegmentInfo> = manifest
+            .segments_after(db_tip_block_number)
+            .take_while(|s| s.from_block <= to_block)
+            .cloned()
+            .collect();
+        let first_segment = match segments.first() {
+            Some(segment) => segment,
+            None => {
+                println!("no new block");
+                return Ok(());
+            }
+        };
+        if first_segment.from_block > db_tip_block_number + 1 {
+            bail!(
+                "missing blocks from {} to {}",
+                db_tip_block_number + 1,
+                first_segment.from_block - 1
+            );
+        }
+        println!(
+            "resume from block {} in segment {}",
+            db_tip_block_number + 1,
+            first_segment.file_name
+        );
+
+        let progress_bar = if self.show_progress {
+            let last_block = segments.last().map_or(0, |s| s.to_block.min(to_block));
+            let bar = ProgressBar::new(last_block - db_tip_block_number);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+                    .progress_chars("##-"),
+            );
+            Some(bar)
+        } else {
+            None
+        };
+
+        // Fetch and verify segments in background, insert them in order
+        let sources = Arc::new(self.sources.clone());
+        let mut segments = segments.into_iter().enumerate();
+        let mut fetching = VecDeque::with_capacity(self.jobs);
+        let mut last_submitted_block = None;
+        let mut parent_block_hash: H256 = db_tip_block.hash().into();
+        loop {
+            while fetching.len() < self.jobs.max(1) {
+                match segments.next() {
+                    Some((index, segment)) => {
+                        let fetch =
+                            fetch_segment(client.clone(), Arc::clone(&sources), index, segment);
+                        fetching.push_back(tokio::spawn(fetch));
+                    }
+                    None => break,
+                }
+            }
+            let (segment, blocks) = match fetching.pop_front() {
+                Some(fetch) => fetch.await??,
+                None => break,
+            };
+
+            for block in blocks {
+                let block_number = block.block_number();
+                if block_number <= db_tip_block_number {
+                    continue;
+                }
+                if block_number > to_block {
+                    break;
+                }
+                if block.parent_block_hash() != parent_block_hash {
+                    bail!("diff parent block {}", block_number - 1);
+                }
+                parent_block_hash = block.block_hash();
+
+                insert_block(&mut self.chain, block, &mut last_submitted_block)
+                    .map_err(|err| anyhow!("insert block {} {}", block_number, err))?;
+
+                if let Some(ref progress_bar) = progress_bar {
+                    progress_bar.inc(1)
+                }
+            }
+            log::info!("imported segment {}", segment.file_name);
+        }
+
+        // See `ImportBlock::read_from_mol`
+        if let Some(last_submitted_block) = last_submitted_block {
+            set_last_submitted_block(self.chain.store(), last_submitted_block)?;
+        }
+
+        if let Some(ref progress_bar) = progress_bar {
+            progress_bar.finish_with_message("done");
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_manifest(&self, client: &Client) -> Result<SegmentManifest> {
+        for source in self.sources.iter() {
+            match source.fetch(client, MANIFEST_FILE_NAME).await {
+                Ok(content) => return SegmentManifest::from_slice(&content),
+                Err(err) => log::warn!("fetch manifest from {}: {}", source, err),
+            }
+        }
+        bail!("manifest not found in sources")
+    }
+}
+
+/// Fetch the segment from the source picked by its index, other sources are tried if the
+/// segment can't be fetched or verified.
+async fn fetch_segment(
+    client: Client,
+    sources: Arc<Vec<SegmentSource>>,
+    index: usize,
+    segment: SegmentInfo,
+) -> Result<(SegmentInfo, Vec<ExportedBlock>)> {
+    for offset in 0..sources.len() {
+        let source = &sources[(index + offset) % sources.len()];
+        let result = match source.fetch(&client, &segment.file_name).await {
+            Ok(data) => {
+                let segment = segment.clone();
+                tokio::task::spawn_blocking(move || read_segment(&segment, &data)).await?
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(blocks) => return Ok((segment, blocks)),
+            Err(err) => log::warn!("segment {} from {}: {}", segment.file_name, source, err),
+        }
+    }
+    bail!("segment {} is unavailable in sources", segment.file_name)
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use gw_config::Config;
+use gw_db::{
+    migrate::{
+        migration_status, plan_migrations, plan_rollback, rollback_migrations, run_migrations,
+        MigrationDirection, MigrationPlan,
+    },
+    schema::COLUMNS,
+    RocksDB,
+};
+use indicatif::{ProgressBar, ProgressStyle};
+
+pub const COMMAND_MIGRATE: &str = "migrate";
+
+/// Database migration related commands, the node must be stopped.
+#[derive(Parser)]
+#[clap(name = COMMAND_MIGRATE)]
+pub enum MigrateCommand {
+    /// Show the database version and the applied and pending migrations
+    Status {
+        /// The config file path
+        #[clap(short, long, default_value = "./config.toml")]
+        config_path: PathBuf,
+    },
+    /// Run pending migrations, migrations already run are rolled back if one fails
+    Run {
+        /// The config file path
+        #[clap(short, long, default_value = "./config.toml")]
+        config_path: PathBuf,
+        /// Report the migrations to run without changing the database
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Roll back migrations after the target version, so the database can be opened by the
+    /// executable binary of that version
+    Rollback {
+        /// The config file path
+        #[clap(short, long, default_value = "./config.toml")]
+        config_path: PathBuf,
+        /// The target db version
+        #[clap(long)]
+        to: String,
+        /// Report the migrations to roll back without changing the database
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+impl MigrateCommand {
+    pub fn run(self) -> Result<()> {
+        match self {
+            MigrateCommand::Status { config_path } => {
+                let db = open_db(&config_path)?;
+                let status = migration_status(&db)?;
+                println!(
+                    "db version: {}",
+                    status.db_version.as_deref().unwrap_or("none")
+                );
+                println!(
+                    "last version: {}",
+                    status.last_version.as_deref().unwrap_or("none")
+                );
+                for version in status.applied {
+                    println!("applied {}", version);
+                }
+                for version in status.pending {
+                    println!("pending {}", version);
+                }
+            }
+            MigrateCommand::Run {
+                config_path,
+                dry_run,
+            } => {
+                let mut db = open_db(&config_path)?;
+                let plans = plan_migrations(&db)?;
+                print_plans(&plans);
+                if dry_run || plans.is_empty() {
+                    return Ok(());
+                }
+                let bar = progress_bar(&plans);
+                run_migrations(&mut db, &|n| bar.inc(n))?;
+                bar.finish();
+                println!(
+                    "migrated to version {}",
+                    plans.last().expect("plan").version
+                );
+            }
+            MigrateCommand::Rollback {
+                config_path,
+                to,
+                dry_run,
+            } => {
+                let mut db = open_db(&config_path)?;
+                let plans = plan_rollback(&db, &to)?;
+                print_plans(&plans);
+                if dry_run || plans.is_empty() {
+                    return Ok(());
+                }
+                let bar = progress_bar(&plans);
+                rollback_migrations(&mut db, &to, &|n| bar.inc(n))?;
+                bar.finish();
+                println!("rolled back to version {}", to);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_plans(plans: &[MigrationPlan]) {
+    if plans.is_empty() {
+        println!("no migrations to run");
+        return;
+    }
+    for plan in plans {
+        let direction = match plan.direction {
+            MigrationDirection::Up => "migrate",
+            MigrationDirection::Down => "roll back",
+        };
+        println!(
+            "{} {}: {}, {} keys, estimated {:?}{}",
+            direction,
+            plan.version,
+            plan.description,
+            plan.affected_keys,
+            plan.estimated_time,
+            if plan.expensive { " (expensive)" } else { "" }
+        );
+    }
+}
+
+fn progress_bar(plans: &[MigrationPlan]) -> ProgressBar {
+    let bar = ProgressBar::new(plans.iter().map(|plan| plan.affected_keys).sum());
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}")
+            .progress_chars("##-"),
+    );
+    bar
+}
+
+fn open_db(config_path: &Path) -> Result<RocksDB> {
+    let content = std::fs::read(config_path)
+        .with_context(|| format!("read config file from {}", config_path.to_string_lossy()))?;
+    let config: Config = toml::from_slice(&content).context("parse config file")?;
+    if !config.store.path.exists() {
+        bail!("database {} not found", config.store.path.to_string_lossy());
+    }
+    Ok(RocksDB::open_with_check(&config.store, COLUMNS)?)
+}
@@ -1,5 +1,11 @@
+pub mod backup;
 pub mod db_block_validator;
+pub mod db_check;
 pub mod export_block;
+pub mod export_parquet;
+pub mod export_segments;
 pub mod import_block;
+pub mod import_segments;
+pub mod migrate;
 pub mod peer_id;
 pub mod rewind_to_last_valid_block;
@@ -2,6 +2,7 @@ use crate::godwoken::ChallengeTargetType;
 
 use ckb_fixed_hash::H256 as JsonH256;
 use ckb_jsonrpc_types as json_types;
+use gw_types::offchain;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -44,3 +45,191 @@ pub enum DumpChallengeTarget {
         target_type: ChallengeTargetType,
     },
 }
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyscallOp {
+    Store {
+        key: JsonH256,
+        before: JsonH256,
+        after: JsonH256,
+    },
+    Load {
+        key: JsonH256,
+        value: JsonH256,
+    },
+    Create {
+        script_hash: JsonH256,
+        account_id: Option<json_types::Uint32>,
+    },
+    Log {
+        account_id: json_types::Uint32,
+        // The actual type is `u8`
+        service_flag: json_types::Uint32,
+        data: json_types::JsonBytes,
+    },
+    RecoverAccount {
+        message: JsonH256,
+        code_hash: JsonH256,
+        script_hash: Option<JsonH256>,
+    },
+    Snapshot {
+        snapshot_id: json_types::Uint32,
+    },
+    Revert {
+        snapshot_id: json_types::Uint32,
+    },
+}
+
+impl From<offchain::SyscallOp> for SyscallOp {
+    fn from(op: offchain::SyscallOp) -> Self {
+        match op {
+            offchain::SyscallOp::Store { key, before, after } => SyscallOp::Store {
+                key: to_json_h256(key),
+                before: to_json_h256(before),
+                after: to_json_h256(after),
+            },
+            offchain::SyscallOp::Load { key, value } => SyscallOp::Load {
+                key: to_json_h256(key),
+                value: to_json_h256(value),
+            },
+            offchain::SyscallOp::Create {
+                script_hash,
+                account_id,
+            } => SyscallOp::Create {
+                script_hash: to_json_h256(script_hash),
+                account_id: account_id.map(Into::into),
+            },
+            offchain::SyscallOp::Log {
+                account_id,
+                service_flag,
+                data,
+            } => SyscallOp::Log {
+                account_id: account_id.into(),
+                service_flag: (service_flag as u32).into(),
+                data: json_types::JsonBytes::from_bytes(data),
+            },
+            offchain::SyscallOp::RecoverAccount {
+                message,
+                code_hash,
+                script_hash,
+            } => SyscallOp::RecoverAccount {
+                message: to_json_h256(message),
+                code_hash: to_json_h256(code_hash),
+                script_hash: script_hash.map(to_json_h256),
+            },
+            offchain::SyscallOp::Snapshot { snapshot_id } => SyscallOp::Snapshot {
+                snapshot_id: snapshot_id.into(),
+            },
+            offchain::SyscallOp::Revert { snapshot_id } => SyscallOp::Revert {
+                snapshot_id: snapshot_id.into(),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SyscallTrace {
+    pub code: json_types::Uint64,
+    pub name: String,
+    pub execution_cycles: json_types::Uint64,
+    pub virtual_cycles: json_types::Uint64,
+    // i8 -> u32, actual u8
+    pub ret: json_types::Uint32,
+    pub op: Option<SyscallOp>,
+}
+
+impl From<offchain::SyscallTrace> for SyscallTrace {
+    fn from(trace: offchain::SyscallTrace) -> Self {
+        SyscallTrace {
+            code: trace.code.into(),
+            name: trace.name.to_string(),
+            execution_cycles: trace.execution_cycles.into(),
+            virtual_cycles: trace.virtual_cycles.into(),
+            ret: (trace.ret as u8 as u32).into(),
+            op: trace.op.map(Into::into),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ExecutionTrace {
+    pub tx_hash: JsonH256,
+    pub error: Option<String>,
+    // i8 -> u32, actual u8
+    pub exit_code: json_types::Uint32,
+    pub execution_cycles: json_types::Uint64,
+    pub virtual_cycles: json_types::Uint64,
+    pub return_data: json_types::JsonBytes,
+    pub debug_log: String,
+    pub syscalls: Vec<SyscallTrace>,
+    pub replay_errors: Vec<ReplayError>,
+}
+
+impl From<offchain::ExecutionTrace> for ExecutionTrace {
+    fn from(trace: offchain::ExecutionTrace) -> Self {
+        ExecutionTrace {
+            tx_hash: to_json_h256(trace.tx_hash),
+            error: trace.error,
+            exit_code: (trace.exit_code as u8 as u32).into(),
+            execution_cycles: trace.cycles.execution.into(),
+            virtual_cycles: trace.cycles.r#virtual.into(),
+            return_data: json_types::JsonBytes::from_bytes(trace.return_data),
+            debug_log: trace.debug_log,
+            syscalls: trace.syscalls.into_iter().map(Into::into).collect(),
+            replay_errors: trace.replay_errors.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct CyclesProfile {
+    pub tx_hash: JsonH256,
+    pub error: Option<String>,
+    // i8 -> u32, actual u8
+    pub exit_code: json_types::Uint32,
+    pub execution_cycles: json_types::Uint64,
+    pub virtual_cycles: json_types::Uint64,
+    /// Call stacks in the folded format of flamegraph tools
+    pub folded_stacks: String,
+    pub replay_errors: Vec<ReplayError>,
+}
+
+impl From<offchain::CyclesProfile> for CyclesProfile {
+    fn from(profile: offchain::CyclesProfile) -> Self {
+        let folded_stacks = profile.to_folded();
+        let replay_errors = profile.replay_errors.into_iter().map(Into::into).collect();
+        CyclesProfile {
+            tx_hash: to_json_h256(profile.tx_hash),
+            error: profile.error,
+            exit_code: (profile.exit_code as u8 as u32).into(),
+            execution_cycles: profile.cycles.execution.into(),
+            virtual_cycles: profile.cycles.r#virtual.into(),
+            folded_stacks,
+            replay_errors,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplayError {
+    pub tx_hash: JsonH256,
+    pub error: String,
+}
+
+impl From<offchain::ReplayError> for ReplayError {
+    fn from(err: offchain::ReplayError) -> Self {
+        ReplayError {
+            tx_hash: to_json_h256(err.tx_hash),
+            error: err.error,
+        }
+    }
+}
+
+fn to_json_h256(hash: impl Into<[u8; 32]>) -> JsonH256 {
+    JsonH256::from(hash.into())
+}
@@ -1,4 +1,4 @@
-use crate::blockchain::Script;
+use crate::blockchain::{OutPoint, Script};
 use anyhow::{anyhow, Error as JsonError};
 use ckb_fixed_hash::H256;
 use ckb_jsonrpc_types::{JsonBytes, Uint128, Uint32, Uint64};
@@ -194,6 +194,37 @@ impl From<packed::TxReceipt> for TxReceipt {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct TxAccessList {
+    pub read_keys: Vec<H256>,
+    pub write_keys: Vec<H256>,
+    pub account_count_read: bool,
+    pub account_count_write: bool,
+}
+
+impl From<packed::TxAccessList> for TxAccessList {
+    fn from(data: packed::TxAccessList) -> TxAccessList {
+        let to_json_keys = |keys: packed::Byte32Vec| -> Vec<H256> {
+            keys.into_iter()
+                .map(|key| {
+                    let key: [u8; 32] = key.unpack();
+                    key.into()
+                })
+                .collect()
+        };
+        let account_count_access: u8 = data.account_count_access().into();
+        TxAccessList {
+            read_keys: to_json_keys(data.read_keys()),
+            write_keys: to_json_keys(data.write_keys()),
+            account_count_read: account_count_access & offchain::AccessList::ACCOUNT_COUNT_READ
+                != 0,
+            account_count_write: account_count_access & offchain::AccessList::ACCOUNT_COUNT_WRITE
+                != 0,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum ChallengeTargetType {
@@ -906,6 +937,14 @@ pub struct LastL2BlockCommittedInfo {
     pub transaction_hash: H256,
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct DBCheckpoint {
+    pub path: String,
+    pub tip_block_number: Uint64,
+    pub tip_block_hash: H256,
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct L2BlockCommittedInfo {
@@ -920,6 +959,8 @@ pub enum AllowedEoaType {
     Unknown,
     Eth,
     Tron,
+    Multisig,
+    WebAuthn,
 }
 
 impl From<AllowedEoaType> for packed::Byte {
@@ -928,6 +969,8 @@ impl From<AllowedEoaType> for packed::Byte {
             AllowedEoaType::Unknown => packed::Byte::new(0),
             AllowedEoaType::Eth => packed::Byte::new(1),
             AllowedEoaType::Tron => packed::Byte::new(2),
+            AllowedEoaType::Multisig => packed::Byte::new(3),
+            AllowedEoaType::WebAuthn => packed::Byte::new(4),
         }
     }
 }
@@ -940,6 +983,8 @@ impl TryFrom<packed::Byte> for AllowedEoaType {
             0 => Ok(AllowedEoaType::Unknown),
             1 => Ok(AllowedEoaType::Eth),
             2 => Ok(AllowedEoaType::Tron),
+            3 => Ok(AllowedEoaType::Multisig),
+            4 => Ok(AllowedEoaType::WebAuthn),
             _ => Err(anyhow!("invalid allowed eoa type {}", v)),
         }
     }
@@ -1357,6 +1402,17 @@ impl From<packed::WithdrawalLockArgs> for WithdrawalLockArgs {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingWithdrawalCell {
+    pub out_point: OutPoint,
+    pub capacity: Uint64,
+    pub sudt_script: Option<Script>,
+    pub amount: Uint128,
+    pub lock_args: WithdrawalLockArgs,
+    pub owner_lock: Script,
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct RegistryAddress {
@@ -154,7 +154,7 @@ impl<T> Default for FeeQueue<T> {
 #[cfg(test)]
 mod tests {
     use gw_common::{h256_ext::H256Ext, state::State, H256};
-    use gw_config::GenesisConfig;
+    use gw_config::{BackendType, FeeConfig, GenesisConfig};
     use gw_generator::genesis::init_genesis;
     use gw_store::{
         state::{history::history_state::RWConfig, BlockStateDB, MemStateDB},
@@ -163,7 +163,11 @@ mod tests {
     };
     use gw_types::{
         bytes::Bytes,
-        packed::{AllowedTypeHash, L2Transaction, RawL2Transaction, RollupConfig},
+        offchain::SponsoredSignature,
+        packed::{
+            AllowedTypeHash, Fee, L2Transaction, RawL2Transaction, RollupConfig, SUDTArgs,
+            SUDTTransfer,
+        },
         prelude::{Builder, Entity, Pack, PackVec, Unpack},
     };
 
@@ -821,6 +825,64 @@ mod tests {
 
     const ALWAYS_SUCCESS_CODE_HASH: [u8; 32] = [42u8; 32];
 
+    #[test]
+    fn test_sponsored_tx_fee_rate() {
+        let fee_config = FeeConfig {
+            meta_cycles_limit: 1000,
+            sudt_cycles_limit: 1000,
+            eth_addr_reg_cycles_limit: 1000,
+            withdraw_cycles_limit: 1000,
+        };
+        let sudt_transfer = |fee: u128| -> Bytes {
+            SUDTArgs::new_builder()
+                .set(
+                    SUDTTransfer::new_builder()
+                        .fee(Fee::new_builder().amount(fee.pack()).build())
+                        .build(),
+                )
+                .build()
+                .as_bytes()
+        };
+
+        // sender pays the fee
+        let tx1 = L2Transaction::new_builder()
+            .raw(
+                RawL2Transaction::new_builder()
+                    .from_id(2u32.pack())
+                    .args(sudt_transfer(100 * 1000).pack())
+                    .build(),
+            )
+            .build();
+        let entry1 = FeeEntry::from_tx(tx1, &fee_config, BackendType::Sudt, 0).unwrap();
+        assert_eq!(entry1.fee, 100 * 1000);
+
+        // sponsor pays the fee for a zero-fee tx
+        let sponsored = SponsoredSignature {
+            sponsor_id: 3,
+            fee: 101 * 1000,
+            sender_signature: vec![1u8; 65].into(),
+            sponsor_signature: vec![2u8; 65].into(),
+        };
+        let tx2 = L2Transaction::new_builder()
+            .raw(
+                RawL2Transaction::new_builder()
+                    .from_id(4u32.pack())
+                    .args(sudt_transfer(0).pack())
+                    .build(),
+            )
+            .signature(sponsored.as_bytes().pack())
+            .build();
+        let entry2 = FeeEntry::from_tx(tx2.clone(), &fee_config, BackendType::Sudt, 1).unwrap();
+        assert_eq!(entry2.fee, 101 * 1000);
+        assert_eq!(entry2.sender, FeeItemSender::AccountId(4));
+
+        let mut queue = FeeQueue::new();
+        queue.add(entry1, ());
+        queue.add(entry2, ());
+        let entry = queue.pop_last().expect("entry").0;
+        assert!(entry.item == FeeItem::Tx(tx2));
+    }
+
     fn setup_genesis(store: &Store) {
         let rollup_type_hash = H256::from_u32(42);
         let rollup_config = RollupConfig::new_builder()
@@ -2,6 +2,7 @@ use anyhow::{anyhow, Result};
 use gw_common::H256;
 use gw_config::{BackendType, FeeConfig};
 use gw_types::{
+    offchain::SponsoredSignature,
     packed::{
         ETHAddrRegArgs, ETHAddrRegArgsUnion, L2Transaction, MetaContractArgs,
         MetaContractArgsUnion, SUDTArgs, SUDTArgsUnion, WithdrawalRequestExtra,
@@ -135,7 +136,11 @@ impl FeeEntry {
         order: usize,
     ) -> Result<Self> {
         let raw_l2tx = tx.raw();
-        let fee = parse_l2tx_fee_rate(fee_config, &raw_l2tx, backend_type)?;
+        let mut fee = parse_l2tx_fee_rate(fee_config, &raw_l2tx, backend_type)?;
+        // sponsored tx is ordered by the sponsor's fee rate
+        if let Some(sponsored) = SponsoredSignature::from_tx(&tx) {
+            fee.fee = sponsored.fee;
+        }
         let item = FeeItem::Tx(tx);
 
         let from_id: u32 = raw_l2tx.from_id().unpack();
@@ -10,6 +10,7 @@ pub mod default_provider;
 mod deposit;
 pub mod fee;
 pub mod mem_block;
+mod parallel;
 pub mod pool;
 pub mod restore_manager;
 pub mod traits;
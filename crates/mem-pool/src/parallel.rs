@@ -0,0 +1,312 @@
+//! Optimistic parallel execution of mem block txs
+//!
+//! Txs are executed speculatively on clones of the same mem state by several worker threads.
+//! The results are committed later one by one in the original order. A speculative result is
+//! only used if the tx doesn't touch any state changed by the txs committed after the
+//! speculation, otherwise the tx is executed again on the latest state. So the post state is
+//! exactly the same as executing the txs sequentially.
+//!
+//! Txs touching the same keys are serialized, except the balance of the block producer. Paying
+//! fees only adds to it, so a tx which accesses it once to pay the fee keeps the fee as a credit
+//! outside the conflict set, and the credit is added to the latest balance when the tx is
+//! committed. Txs accessing the balance more than once, e.g. sent by the block producer or
+//! failed txs paying fees by burning and minting, are still serialized.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use gw_common::{
+    builtins::CKB_SUDT_ACCOUNT_ID,
+    h256_ext::H256Ext,
+    registry_address::RegistryAddress,
+    state::{build_account_key, build_sudt_key, State, SUDT_KEY_FLAG_BALANCE},
+    H256,
+};
+use gw_generator::{constants::L2TX_MAX_CYCLES, generator::CyclesPool, Generator};
+use gw_store::{
+    chain_view::ChainView, snapshot::StoreSnapshot, state::MemStateDB,
+    traits::chain_store::ChainStore,
+};
+use gw_types::{
+    offchain::{AccessList, RunResult},
+    packed::{BlockInfo, L2Transaction},
+    U256,
+};
+
+pub(crate) struct SpeculativeResult {
+    pub run_result: RunResult,
+    /// Post values of the written keys
+    pub writes: Vec<(H256, H256)>,
+    /// Fees added to the block producer balance, not included in `writes`
+    pub credits: Vec<(H256, U256)>,
+    pub used_cycles: u64,
+}
+
+/// Speculative results and the state changes committed after the speculation
+pub(crate) struct Speculation {
+    tip_block_hash: H256,
+    results: HashMap<H256, SpeculativeResult>,
+    written_keys: HashSet<H256>,
+    written_data_hashes: HashSet<H256>,
+    account_count_written: bool,
+}
+
+impl Speculation {
+    /// Execute txs in parallel, each tx is executed on a clone of the finalised `state`
+    pub fn execute(
+        generator: &Generator,
+        snapshot: &StoreSnapshot,
+        state: &MemStateDB,
+        block_info: &BlockInfo,
+        txs: &[L2Transaction],
+        cycles_pool: &CyclesPool,
+        workers: usize,
+    ) -> Result<Self> {
+        let tip_block_hash = snapshot.get_tip_block_hash()?;
+        let chunk_size = (txs.len() + workers.max(1) - 1) / workers.max(1);
+
+        let results = std::thread::scope(|s| {
+            let handles: Vec<_> = txs
+                .chunks(chunk_size.max(1))
+                .map(|chunk| {
+                    s.spawn(move || {
+                        let chain_view = ChainView::new(snapshot, tip_block_hash);
+                        chunk
+                            .iter()
+                            .filter_map(|tx| {
+                                let tx_hash: H256 = tx.witness_hash().into();
+                                let result = speculate_tx(
+                                    generator,
+                                    &chain_view,
+                                    state,
+                                    block_info,
+                                    tx,
+                                    cycles_pool,
+                                )?;
+                                Some((tx_hash, result))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            let mut results = HashMap::with_capacity(txs.len());
+            for handle in handles {
+                let chunk_results = handle
+                    .join()
+                    .map_err(|_| anyhow!("speculative execution worker panicked"))?;
+                results.extend(chunk_results);
+            }
+            Result::<_>::Ok(results)
+        })?;
+
+        Ok(Speculation {
+            tip_block_hash,
+            results,
+            written_keys: Default::default(),
+            written_data_hashes: Default::default(),
+            account_count_written: false,
+        })
+    }
+
+    /// Take the speculative result of tx, return `None` if there is no valid result
+    pub fn take(
+        &mut self,
+        tip_block_hash: &H256,
+        tx_hash: &H256,
+        available_cycles: u64,
+    ) -> Option<SpeculativeResult> {
+        let result = self.results.remove(tx_hash)?;
+        if &self.tip_block_hash != tip_block_hash || result.used_cycles > available_cycles {
+            return None;
+        }
+
+        // writes are replayed, so a write conflicts with earlier writes as well
+        let access_list = &result.run_result.access_list;
+        let is_credit = |key: &H256| result.credits.iter().any(|(k, _)| k == key);
+        let is_stale = (self.account_count_written && access_list.account_count_access != 0)
+            || access_list
+                .keys()
+                .any(|key| !is_credit(key) && self.written_keys.contains(key))
+            || !result
+                .run_result
+                .read_data_hashes
+                .is_disjoint(&self.written_data_hashes);
+        if is_stale {
+            return None;
+        }
+        Some(result)
+    }
+
+    /// Record state changes of a committed tx
+    pub fn record(&mut self, access_list: &AccessList, write_data_hashes: &HashSet<H256>) {
+        self.written_keys
+            .extend(access_list.write_keys.iter().cloned());
+        self.written_data_hashes
+            .extend(write_data_hashes.iter().cloned());
+        if access_list.account_count_access & AccessList::ACCOUNT_COUNT_WRITE != 0 {
+            self.account_count_written = true;
+        }
+    }
+}
+
+/// Execute a tx on a clone of state, txs which can't be replayed from written keys or failed
+/// to execute are left to sequential execution.
+fn speculate_tx(
+    generator: &Generator,
+    chain_view: &ChainView<StoreSnapshot>,
+    state: &MemStateDB,
+    block_info: &BlockInfo,
+    tx: &L2Transaction,
+    cycles_pool: &CyclesPool,
+) -> Option<SpeculativeResult> {
+    let base_state = state;
+    let mut state = base_state.clone();
+    let mut cycles_pool = cycles_pool.clone();
+    let available_cycles = cycles_pool.available_cycles();
+    let run_result = generator
+        .unchecked_execute_l2transaction(
+            chain_view,
+            &mut state,
+            block_info,
+            tx,
+            L2TX_MAX_CYCLES,
+            Some(&mut cycles_pool),
+        )
+        .ok()?;
+
+    // account creations insert scripts and data are not tracked as keys
+    let access_list = &run_result.access_list;
+    if access_list.account_count_access & AccessList::ACCOUNT_COUNT_WRITE != 0
+        || !run_result.write_data_hashes.is_empty()
+    {
+        return None;
+    }
+
+    // the fee paid to the block producer is kept as a credit if the balance is only read and
+    // written once for paying it
+    let producer_balance_key = RegistryAddress::from_slice(&block_info.block_producer().raw_data())
+        .map(|producer| {
+            build_account_key(
+                CKB_SUDT_ACCOUNT_ID,
+                &build_sudt_key(SUDT_KEY_FLAG_BALANCE, &producer),
+            )
+        });
+    let mut credits = Vec::new();
+    if let Some(key) = producer_balance_key.filter(|key| {
+        access_list.read_keys.contains(key)
+            && access_list.write_keys.contains(key)
+            && !access_list.repeated_keys.contains(key)
+    }) {
+        let pre = base_state.get_raw(&key).ok()?.to_u256();
+        let post = state.get_raw(&key).ok()?.to_u256();
+        if post >= pre {
+            credits.push((key, post - pre));
+        }
+    }
+
+    let writes = access_list
+        .write_keys
+        .iter()
+        .filter(|key| !credits.iter().any(|(k, _)| k == *key))
+        .map(|key| state.get_raw(key).map(|value| (*key, value)))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let used_cycles = available_cycles - cycles_pool.available_cycles();
+
+    Some(SpeculativeResult {
+        run_result,
+        writes,
+        credits,
+        used_cycles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use gw_common::{h256_ext::H256Ext, H256};
+    use gw_types::offchain::{AccessList, RunResult};
+
+    use super::{Speculation, SpeculativeResult};
+
+    fn speculative_result(read_keys: Vec<H256>, write_keys: Vec<H256>) -> SpeculativeResult {
+        let access_list = AccessList {
+            read_keys: read_keys.into_iter().collect(),
+            write_keys: write_keys.into_iter().collect(),
+            ..Default::default()
+        };
+        SpeculativeResult {
+            run_result: RunResult {
+                access_list,
+                ..Default::default()
+            },
+            writes: vec![],
+            credits: vec![],
+            used_cycles: 100,
+        }
+    }
+
+    #[test]
+    fn test_take_speculative_result() {
+        let tip_block_hash = H256::from_u32(1);
+        let (tx_a, tx_b, tx_c, tx_d) = (
+            H256::from_u32(10),
+            H256::from_u32(11),
+            H256::from_u32(12),
+            H256::from_u32(13),
+        );
+        let (key_a, key_b, key_c) = (H256::from_u32(20), H256::from_u32(21), H256::from_u32(22));
+
+        let mut results = HashMap::new();
+        results.insert(tx_a, speculative_result(vec![key_a], vec![key_a]));
+        results.insert(tx_b, speculative_result(vec![key_b], vec![]));
+        results.insert(tx_c, speculative_result(vec![], vec![key_c]));
+        results.insert(tx_d, speculative_result(vec![key_b], vec![key_b]));
+        let mut speculation = Speculation {
+            tip_block_hash,
+            results,
+            written_keys: Default::default(),
+            written_data_hashes: Default::default(),
+            account_count_written: false,
+        };
+
+        // stale tip or insufficient cycles
+        assert!(speculation.take(&H256::zero(), &tx_a, 100).is_none());
+        assert!(speculation.take(&tip_block_hash, &tx_b, 99).is_none());
+
+        let mut committed = AccessList::default();
+        committed.write_keys.insert(key_a);
+        committed.write_keys.insert(key_c);
+        speculation.record(&committed, &Default::default());
+
+        // conflicts with the committed writes
+        assert!(speculation.take(&tip_block_hash, &tx_c, 100).is_none());
+        let result = speculation.take(&tip_block_hash, &tx_d, 100).unwrap();
+        assert!(result.run_result.access_list.write_keys.contains(&key_b));
+        assert!(speculation.take(&tip_block_hash, &tx_d, 100).is_none());
+
+        // account count
+        speculation
+            .results
+            .insert(tx_a, speculative_result(vec![], vec![]));
+        speculation
+            .results
+            .get_mut(&tx_a)
+            .unwrap()
+            .run_result
+            .access_list
+            .account_count_access = AccessList::ACCOUNT_COUNT_READ;
+        committed.account_count_access = AccessList::ACCOUNT_COUNT_WRITE;
+        speculation.record(&committed, &Default::default());
+        assert!(speculation.take(&tip_block_hash, &tx_a, 100).is_none());
+
+        // credited keys are out of the conflict set
+        let mut result = speculative_result(vec![key_a], vec![key_a]);
+        result.credits.push((key_a, 1u32.into()));
+        speculation.results.insert(tx_a, result);
+        let result = speculation.take(&tip_block_hash, &tx_a, 100).unwrap();
+        assert_eq!(result.credits, vec![(key_a, 1u32.into())]);
+    }
+}
@@ -10,10 +10,10 @@
 
 use anyhow::{anyhow, Context, Result};
 use gw_common::{
-    builtins::CKB_SUDT_ACCOUNT_ID, ckb_decimal::CKBCapacity, registry_address::RegistryAddress,
-    state::State, H256,
+    builtins::CKB_SUDT_ACCOUNT_ID, ckb_decimal::CKBCapacity, h256_ext::H256Ext,
+    registry_address::RegistryAddress, state::State, H256,
 };
-use gw_config::{MemBlockConfig, MemPoolConfig, NodeMode, SyscallCyclesConfig};
+use gw_config::{MemBlockConfig, MemPoolConfig, NodeMode};
 use gw_dynamic_config::manager::DynamicConfigManager;
 use gw_generator::{
     constants::L2TX_MAX_CYCLES,
@@ -33,7 +33,7 @@ use gw_store::{
 };
 use gw_traits::CodeStore;
 use gw_types::{
-    offchain::{DepositInfo, FinalizedCustodianCapacity},
+    offchain::{AccessList, BatchWithdrawalSignature, DepositInfo, FinalizedCustodianCapacity},
     packed::{
         AccountMerkleState, BlockInfo, L2Block, L2Transaction, NextMemBlock, Script, TxReceipt,
         WithdrawalKey, WithdrawalRequest, WithdrawalRequestExtra,
@@ -53,8 +53,13 @@ use tokio::task::block_in_place;
 use tracing::instrument;
 
 use crate::{
-    block_sync_server::BlockSyncServerState, mem_block::MemBlock, restore_manager::RestoreManager,
-    traits::MemPoolProvider, types::EntryList, withdrawal::Generator as WithdrawalGenerator,
+    block_sync_server::BlockSyncServerState,
+    mem_block::MemBlock,
+    parallel::{Speculation, SpeculativeResult},
+    restore_manager::RestoreManager,
+    traits::MemPoolProvider,
+    types::EntryList,
+    withdrawal::Generator as WithdrawalGenerator,
 };
 
 type StateDB = gw_store::state::MemStateDB;
@@ -96,6 +101,10 @@ pub struct MemPool {
     mem_block_config: MemBlockConfig,
     /// Cycles Pool
     cycles_pool: CyclesPool,
+    /// Access lists of mem block txs
+    tx_access_lists: HashMap<H256, AccessList>,
+    /// Results of txs executed in parallel
+    speculation: Option<Speculation>,
 }
 
 pub struct MemPoolCreateArgs {
@@ -163,7 +172,7 @@ impl MemPool {
 
         let cycles_pool = CyclesPool::new(
             config.mem_block.max_cycles_limit,
-            config.mem_block.syscall_cycles.clone(),
+            config.mem_block.syscall_cycles_at(tip.1 + 1).clone(),
         );
 
         let mut mem_pool = MemPool {
@@ -181,6 +190,8 @@ impl MemPool {
             sync_server,
             mem_block_config: config.mem_block,
             cycles_pool,
+            tx_access_lists: Default::default(),
+            speculation: None,
         };
         mem_pool.restore_pending_withdrawals().await?;
         mem_pool.remove_reinjected_failed_txs()?;
@@ -272,6 +283,58 @@ impl MemPool {
         &mut self.pending_restored_tx_hashes
     }
 
+    /// Whether two txs of the mem block are independent, return `None` if any of them isn't
+    /// executed in the current mem block
+    pub fn is_independent_tx(&self, tx_hash: &H256, other_tx_hash: &H256) -> Option<bool> {
+        let access_list = self.tx_access_lists.get(tx_hash)?;
+        let other_access_list = self.tx_access_lists.get(other_tx_hash)?;
+        Some(access_list.is_independent(other_access_list))
+    }
+
+    /// Execute txs in parallel on the current mem state if parallel execution is enabled. The
+    /// results are used by the following `push_transaction` calls unless they conflict with
+    /// txs pushed in between. Call `clear_speculation` once the txs are pushed, so results left
+    /// by the batch aren't reused after the mem pool is unlocked.
+    #[instrument(skip_all, fields(txs_count = txs.len()))]
+    pub fn speculate_transactions(&mut self, txs: &[L2Transaction]) {
+        let state = self.mem_pool_state.load_state_db();
+        block_in_place(|| self.speculate_transactions_with_state(&state, txs))
+    }
+
+    /// Drop the results of `speculate_transactions`
+    pub fn clear_speculation(&mut self) {
+        self.speculation = None;
+    }
+
+    fn speculate_transactions_with_state(&mut self, state: &StateDB, txs: &[L2Transaction]) {
+        self.speculation = None;
+        let workers = match self.mem_block_config.parallel_execution {
+            Some(ref config) if txs.len() > 1 => config.workers,
+            _ => return,
+        };
+
+        let t = Instant::now();
+        match Speculation::execute(
+            &self.generator,
+            &self.store.get_snapshot(),
+            state,
+            self.mem_block.block_info(),
+            txs,
+            &self.cycles_pool,
+            workers,
+        ) {
+            Ok(speculation) => {
+                log::debug!(
+                    "[mem-pool] speculatively execute {} txs: {}ms",
+                    txs.len(),
+                    t.elapsed().as_millis()
+                );
+                self.speculation = Some(speculation);
+            }
+            Err(err) => log::warn!("[mem-pool] speculative execution error: {}", err),
+        }
+    }
+
     /// Push a layer2 tx into pool
     #[instrument(skip_all)]
     pub fn push_transaction(&mut self, tx: L2Transaction) -> Result<()> {
@@ -319,13 +382,14 @@ impl MemPool {
 
         // instantly run tx in background & update local state
         let t = Instant::now();
-        let tx_receipt = self.execute_tx(db, state, tx.clone())?;
+        let (tx_receipt, access_list) = self.execute_tx(db, state, tx.clone())?;
         log::debug!("[push tx] finalize tx time: {}ms", t.elapsed().as_millis());
 
         // save tx receipt in mem pool
         let post_state = tx_receipt.post_state();
         self.mem_block.push_tx(tx_hash, post_state);
-        db.insert_mem_pool_transaction_receipt(&tx_hash, tx_receipt)?;
+        db.insert_mem_pool_transaction_receipt(&tx_hash, tx_receipt, (&access_list).into())?;
+        self.tx_access_lists.insert(tx_hash, access_list);
 
         // Add to pool
         let account_id: u32 = tx.raw().from_id().unpack();
@@ -350,7 +414,11 @@ impl MemPool {
 
         // basic verification without write into state
         // withdrawals will be write into state in the finalize_withdrawals function
-        let state = self.mem_pool_state.load_state_db();
+        let mut state = self.mem_pool_state.load_state_db();
+        // withdrawals of a batch have consecutive nonces, verify on top of the pending ones
+        if let Some(batch) = BatchWithdrawalSignature::from_withdrawal(&withdrawal) {
+            self.apply_pending_batch_withdrawals(&mut state, &withdrawal, &batch)?;
+        }
         self.verify_withdrawal_request(&withdrawal, &state).await?;
 
         // Check replace-by-fee
@@ -369,6 +437,42 @@ impl MemPool {
         Ok(())
     }
 
+    /// Apply pending withdrawals which belong to the same batch to a temporary state
+    fn apply_pending_batch_withdrawals(
+        &self,
+        state: &mut MemStateDB,
+        withdrawal: &WithdrawalRequestExtra,
+        batch: &BatchWithdrawalSignature,
+    ) -> Result<()> {
+        let account_script_hash: H256 = withdrawal.raw().account_script_hash().unpack();
+        let account_id = match state.get_account_id_by_script_hash(&account_script_hash)? {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        let pending = match self.pending.get(&account_id) {
+            Some(entry_list) => &entry_list.withdrawals,
+            None => return Ok(()),
+        };
+        for pending_withdrawal in pending {
+            let nonce = state.get_nonce(account_id)?;
+            let pending_nonce: u32 = pending_withdrawal.raw().nonce().unpack();
+            if pending_nonce != nonce {
+                continue;
+            }
+            match BatchWithdrawalSignature::from_withdrawal(pending_withdrawal) {
+                Some(pending_batch)
+                    if pending_batch.raw_request_hashes == batch.raw_request_hashes => {}
+                _ => break,
+            }
+            state.apply_withdrawal_request(
+                self.generator.rollup_context(),
+                self.mem_block.block_producer(),
+                &pending_withdrawal.request(),
+            )?;
+        }
+        Ok(())
+    }
+
     // TODO: @sopium optimization: collect on reset and cache.
     fn collect_finalized_custodian_capacity(&self) -> Result<FinalizedCustodianCapacity> {
         let tip = self.current_tip.1;
@@ -522,6 +626,8 @@ impl MemPool {
             // re-injecting discarded txs/withdrawals.
             let snapshot = self.store.get_snapshot();
             self.mem_block.reset(&new_tip_block, Duration::ZERO);
+            self.tx_access_lists.clear();
+            self.speculation = None;
             let shared = mem_pool_state::Shared {
                 state_db: MemStateDB::from_store(snapshot)?,
                 mem_block: Some(self.mem_block.block_info().to_owned()),
@@ -667,6 +773,8 @@ impl MemPool {
             assert_eq!(snap_last_valid_tip, new_tip, "set new snapshot");
 
             let mem_block_content = self.mem_block.reset(&new_tip_block, estimated_timestamp);
+            self.tx_access_lists.clear();
+            self.speculation = None;
 
             // set tip
             self.current_tip = (new_tip, new_tip_block.raw().number().unpack());
@@ -715,7 +823,9 @@ impl MemPool {
             }
 
             // To simplify logic, don't restrict reinjected txs
-            self.cycles_pool = CyclesPool::new(u64::MAX, SyscallCyclesConfig::default());
+            let mem_block_number: u64 = mem_block.number().unpack();
+            let syscall_cycles = self.mem_block_config.syscall_cycles_at(mem_block_number);
+            self.cycles_pool = CyclesPool::new(u64::MAX, syscall_cycles.clone());
 
             self.prepare_next_mem_block(
                 db,
@@ -729,7 +839,9 @@ impl MemPool {
             let used_cycles = self.cycles_pool.cycles_used();
             self.cycles_pool = CyclesPool::new(
                 self.mem_block_config.max_cycles_limit,
-                self.mem_block_config.syscall_cycles.clone(),
+                self.mem_block_config
+                    .syscall_cycles_at(mem_block_number)
+                    .clone(),
             );
             self.cycles_pool.consume_cycles(used_cycles);
 
@@ -768,6 +880,13 @@ impl MemPool {
                 if let Some(withdrawal) = entry.withdrawals.first() {
                     if filter_withdrawals(state, withdrawal) {
                         withdrawals.push(withdrawal.clone());
+                        // package the rest withdrawals of the same batch together
+                        let remained = self
+                            .mem_block_config
+                            .max_withdrawals
+                            .saturating_sub(withdrawals.len());
+                        let batch_withdrawals = batch_successors(withdrawal, &entry.withdrawals);
+                        withdrawals.extend(batch_withdrawals.take(remained).cloned());
                     }
                     if withdrawals.len() >= self.mem_block_config.max_withdrawals {
                         break;
@@ -877,6 +996,7 @@ impl MemPool {
         }
 
         // re-inject txs
+        self.speculate_transactions_with_state(state, &txs);
         for tx in txs {
             if let Err(err) = self.push_transaction_with_db(db, state, tx.clone()) {
                 let tx_hash = tx.hash();
@@ -887,6 +1007,7 @@ impl MemPool {
                 );
             }
         }
+        self.speculation = None;
 
         Ok(())
     }
@@ -1089,7 +1210,7 @@ impl MemPool {
         db: &StoreTransaction,
         state: &mut StateDB,
         tx: L2Transaction,
-    ) -> Result<TxReceipt> {
+    ) -> Result<(TxReceipt, AccessList)> {
         let tip_block_hash = db.get_tip_block_hash()?;
         let chain_view = ChainView::new(&db, tip_block_hash);
 
@@ -1125,20 +1246,50 @@ impl MemPool {
         // execute tx
         let raw_tx = tx.raw();
         let snap = state.snapshot();
-        let run_result = generator
-            .unchecked_execute_transaction(
-                &chain_view,
-                state,
-                block_info,
-                &raw_tx,
-                L2TX_MAX_CYCLES,
-                Some(cycles_pool),
+        let speculative_result = self.speculation.as_mut().and_then(|speculation| {
+            speculation.take(
+                &tip_block_hash,
+                &tx.witness_hash().into(),
+                cycles_pool.available_cycles(),
             )
-            .map_err(|err| {
-                // revert state
-                state.revert(snap).unwrap();
-                err
-            })?;
+        });
+        let mut run_result = match speculative_result {
+            // nothing touched by the tx is changed since the speculation, replay its writes
+            Some(SpeculativeResult {
+                run_result,
+                writes,
+                credits,
+                used_cycles,
+            }) => {
+                for (key, value) in writes {
+                    state.update_raw(key, value)?;
+                }
+                for (key, amount) in credits {
+                    let balance = state
+                        .get_raw(&key)?
+                        .to_u256()
+                        .checked_add(amount)
+                        .ok_or_else(|| anyhow!("block producer balance overflow"))?;
+                    state.update_raw(key, H256::from_u256(balance))?;
+                }
+                cycles_pool.consume_cycles(used_cycles);
+                run_result
+            }
+            None => generator
+                .unchecked_execute_l2transaction(
+                    &chain_view,
+                    state,
+                    block_info,
+                    &tx,
+                    L2TX_MAX_CYCLES,
+                    Some(cycles_pool),
+                )
+                .map_err(|err| {
+                    // revert state
+                    state.revert(snap).unwrap();
+                    err
+                })?,
+        };
 
         // check account id of sudt proxy contract creator is from whitelist
         {
@@ -1158,11 +1309,16 @@ impl MemPool {
             }
         }
 
+        if let Some(ref mut speculation) = self.speculation {
+            speculation.record(&run_result.access_list, &run_result.write_data_hashes);
+        }
+
         state.finalise()?;
         // finalise dirty state
         let merkle_state = state.calculate_merkle_state()?;
 
         // generate tx receipt
+        let access_list = std::mem::take(&mut run_result.access_list);
         let tx_receipt =
             TxReceipt::build_receipt(tx.witness_hash().into(), run_result, merkle_state);
 
@@ -1170,7 +1326,7 @@ impl MemPool {
             sync_server.lock().unwrap().publish_transaction(tx);
         }
 
-        Ok(tx_receipt)
+        Ok((tx_receipt, access_list))
     }
 
     async fn restore_pending_withdrawals(&mut self) -> Result<()> {
@@ -1276,7 +1432,9 @@ impl MemPool {
             self.remove_unexecutables(&mut state, &db)?;
 
             // reset cycles pool available cycles.
-            self.cycles_pool = CyclesPool::new(u64::MAX, SyscallCyclesConfig::default());
+            let mem_block_number: u64 = mem_block.number().unpack();
+            let syscall_cycles = self.mem_block_config.syscall_cycles_at(mem_block_number);
+            self.cycles_pool = CyclesPool::new(u64::MAX, syscall_cycles.clone());
 
             // prepare next mem block
             self.try_package_more_withdrawals(&state, &mut withdrawals);
@@ -1327,6 +1485,33 @@ pub(crate) fn repackage_count(
     (withdrawals_count, deposits_count, txs_count)
 }
 
+/// Withdrawals following `first` in the pending list which belong to the same batch and have
+/// consecutive nonces
+pub(crate) fn batch_successors<'a>(
+    first: &WithdrawalRequestExtra,
+    pending: &'a [WithdrawalRequestExtra],
+) -> impl Iterator<Item = &'a WithdrawalRequestExtra> {
+    let batch = BatchWithdrawalSignature::from_withdrawal(first);
+    let first_hash = first.hash();
+    let mut nonce: u32 = first.raw().nonce().unpack();
+    pending
+        .iter()
+        .skip_while(move |w| w.hash() != first_hash)
+        .skip(1)
+        .take_while(move |w| {
+            let same_batch = match (&batch, BatchWithdrawalSignature::from_withdrawal(w)) {
+                (Some(batch), Some(w_batch)) => {
+                    batch.raw_request_hashes == w_batch.raw_request_hashes
+                }
+                _ => false,
+            };
+            let w_nonce: u32 = w.raw().nonce().unpack();
+            let consecutive = Some(w_nonce) == nonce.checked_add(1);
+            nonce = w_nonce;
+            same_batch && consecutive
+        })
+}
+
 #[cfg(test)]
 mod test {
     use std::ops::Shr;
@@ -1334,12 +1519,16 @@ mod test {
     use gw_common::merkle_utils::calculate_state_checkpoint;
     use gw_common::registry_address::RegistryAddress;
     use gw_common::H256;
-    use gw_types::offchain::{DepositInfo, FinalizedCustodianCapacity};
-    use gw_types::packed::{AccountMerkleState, BlockInfo, DepositRequest};
+    use gw_types::bytes::Bytes;
+    use gw_types::offchain::{BatchWithdrawalSignature, DepositInfo, FinalizedCustodianCapacity};
+    use gw_types::packed::{
+        AccountMerkleState, BlockInfo, DepositRequest, RawWithdrawalRequest, WithdrawalRequest,
+        WithdrawalRequestExtra,
+    };
     use gw_types::prelude::{Builder, Entity, Pack, Unpack};
 
     use crate::mem_block::{MemBlock, MemBlockCmp};
-    use crate::pool::{repackage_count, MemPool, OutputParam};
+    use crate::pool::{batch_successors, repackage_count, MemPool, OutputParam};
 
     #[test]
     fn test_package_mem_block() {
@@ -1563,6 +1752,57 @@ mod test {
         assert_eq!(post_block_state, expected_post_state);
     }
 
+    #[test]
+    fn test_batch_successors() {
+        let build_withdrawal = |nonce: u32, signature: Bytes| {
+            let raw = RawWithdrawalRequest::new_builder()
+                .nonce(nonce.pack())
+                .build();
+            let request = WithdrawalRequest::new_builder()
+                .raw(raw)
+                .signature(signature.pack())
+                .build();
+            WithdrawalRequestExtra::new_builder()
+                .request(request)
+                .build()
+        };
+        let raw_hashes: Vec<H256> = (0..3u32)
+            .map(|nonce| {
+                let raw = RawWithdrawalRequest::new_builder()
+                    .nonce(nonce.pack())
+                    .build();
+                raw.hash().into()
+            })
+            .collect();
+        let batch_signature = |index: u32| {
+            BatchWithdrawalSignature {
+                index,
+                raw_request_hashes: raw_hashes.clone(),
+                signature: vec![1u8; 65].into(),
+            }
+            .as_bytes()
+        };
+
+        let pending = vec![
+            build_withdrawal(0, batch_signature(0)),
+            build_withdrawal(1, batch_signature(1)),
+            build_withdrawal(2, batch_signature(2)),
+            build_withdrawal(3, vec![1u8; 65].into()),
+        ];
+        let successors: Vec<_> = batch_successors(&pending[0], &pending).collect();
+        assert_eq!(successors, vec![&pending[1], &pending[2]]);
+
+        // Not a batch withdrawal
+        assert_eq!(batch_successors(&pending[3], &pending).count(), 0);
+
+        // Nonce isn't consecutive
+        let pending = vec![
+            build_withdrawal(0, batch_signature(0)),
+            build_withdrawal(2, batch_signature(2)),
+        ];
+        assert_eq!(batch_successors(&pending[0], &pending).count(), 0);
+    }
+
     fn random_hash() -> H256 {
         rand::random::<[u8; 32]>().into()
     }
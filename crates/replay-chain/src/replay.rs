@@ -62,6 +62,7 @@ pub fn replay_chain(ctx: ChainContext) -> Result<()> {
             })
             .collect();
         let load_block_ms = now.elapsed().as_millis();
+        local_store.prefetch_state(&from_store.get_block_access_keys(&block)?);
 
         let txs_len = block.transactions().item_count();
         let deposits_len = deposit_requests.len();
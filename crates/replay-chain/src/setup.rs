@@ -45,6 +45,7 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
         options: config.store.options.clone(),
         options_file: config.store.options_file.clone(),
         cache_size: config.store.cache_size,
+        ..Default::default()
     };
     let local_store = Store::new(RocksDB::open(&store_config, COLUMNS));
     let rollup_type_script = {
@@ -95,7 +96,13 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
         .with_context(|| "init genesis")?;
     let generator = {
         let backend_manage = BackendManage::from_config(config.backend_switches.clone())
-            .with_context(|| "config backends")?;
+            .with_context(|| "config backends")?
+            .with_aot_cache_dir(config.aot_cache_dir.clone());
+        if let Some(ref backend_upgrade) = config.backend_upgrade {
+            backend_manage
+                .register_saved_backend_switches(&backend_upgrade.registered_switches_path)
+                .with_context(|| "saved backend switches")?;
+        }
         let mut account_lock_manage = AccountLockManage::default();
         let allowed_eoa_type_hashes = rollup_config.as_reader().allowed_eoa_type_hashes();
         let eth_lock_script_type_hash = allowed_eoa_type_hashes
@@ -129,6 +136,7 @@ pub async fn setup(args: SetupArgs) -> Result<Context> {
             options: config.store.options.clone(),
             options_file: config.store.options_file.clone(),
             cache_size: config.store.cache_size,
+            ..Default::default()
         };
         Store::new(RocksDB::open(&store_config, from_db_columns))
     };
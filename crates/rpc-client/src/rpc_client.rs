@@ -4,6 +4,7 @@ use crate::ckb_client::CKBClient;
 use crate::indexer_client::CKBIndexerClient;
 use crate::indexer_types::{Cell, Order, Pagination, ScriptType, SearchKey, SearchKeyFilter};
 use crate::utils::{to_h256, to_jsonh256, DEFAULT_QUERY_LIMIT, TYPE_ID_CODE_HASH};
+use crate::withdrawal::PendingWithdrawal;
 use anyhow::{anyhow, Result};
 use async_jsonrpc_client::Params as ClientParams;
 use ckb_types::core::hardfork::HardForkSwitch;
@@ -824,6 +825,75 @@ impl RPCClient {
         Ok((collected, collected_block_hashes))
     }
 
+    /// this function queries the latest live cell by type script
+    #[instrument(skip_all)]
+    pub async fn query_cell_by_type_script(&self, type_script: Script) -> Result<Option<CellInfo>> {
+        let search_key = SearchKey {
+            script: {
+                let type_script = ckb_types::packed::Script::new_unchecked(type_script.as_bytes());
+                type_script.into()
+            },
+            script_type: ScriptType::Type,
+            filter: None,
+        };
+        let order = Order::Desc;
+        let limit = Uint32::from(1);
+
+        let mut cells: Pagination<Cell> = self
+            .indexer
+            .request(
+                "get_cells",
+                Some(ClientParams::Array(vec![
+                    json!(search_key),
+                    json!(order),
+                    json!(limit),
+                ])),
+            )
+            .await?;
+        Ok(cells.objects.pop().map(to_cell_info))
+    }
+
+    /// this function queries the latest live cell by lock script and type script
+    #[instrument(skip_all)]
+    pub async fn query_cell_by_lock_and_type_script(
+        &self,
+        lock: Script,
+        type_script: Script,
+    ) -> Result<Option<CellInfo>> {
+        let search_key = SearchKey {
+            script: {
+                let lock = ckb_types::packed::Script::new_unchecked(lock.as_bytes());
+                lock.into()
+            },
+            script_type: ScriptType::Lock,
+            filter: Some(SearchKeyFilter {
+                script: {
+                    let type_script =
+                        ckb_types::packed::Script::new_unchecked(type_script.as_bytes());
+                    Some(type_script.into())
+                },
+                output_data_len_range: None,
+                output_capacity_range: None,
+                block_range: None,
+            }),
+        };
+        let order = Order::Desc;
+        let limit = Uint32::from(1);
+
+        let mut cells: Pagination<Cell> = self
+            .indexer
+            .request(
+                "get_cells",
+                Some(ClientParams::Array(vec![
+                    json!(search_key),
+                    json!(order),
+                    json!(limit),
+                ])),
+            )
+            .await?;
+        Ok(cells.objects.pop().map(to_cell_info))
+    }
+
     #[instrument(skip_all)]
     pub async fn query_verifier_cell(
         &self,
@@ -963,6 +1033,86 @@ impl RPCClient {
         Ok(collected)
     }
 
+    /// Query unfinalized withdrawal cells which can be traded to liquidity providers, filter by
+    /// owner lock hash if given
+    #[instrument(skip_all)]
+    pub async fn query_pending_withdrawal_cells(
+        &self,
+        last_finalized_block_number: u64,
+        owner_lock_hash: Option<H256>,
+        max_cells: usize,
+    ) -> Result<Vec<PendingWithdrawal>> {
+        let rollup_context = &self.rollup_context;
+
+        let withdrawal_lock = Script::new_builder()
+            .code_hash(rollup_context.rollup_config.withdrawal_script_type_hash())
+            .hash_type(ScriptHashType::Type.into())
+            .args(rollup_context.rollup_script_hash.as_slice().pack())
+            .build();
+
+        let search_key = SearchKey {
+            script: ckb_types::packed::Script::new_unchecked(withdrawal_lock.as_bytes()).into(),
+            script_type: ScriptType::Lock,
+            filter: None,
+        };
+        let order = Order::Desc;
+        let limit = Uint32::from(DEFAULT_QUERY_LIMIT as u32);
+
+        let mut collected = vec![];
+        let mut cursor = None;
+
+        while collected.len() < max_cells {
+            let cells: Pagination<Cell> = self
+                .indexer
+                .request(
+                    "get_cells",
+                    Some(ClientParams::Array(vec![
+                        json!(search_key),
+                        json!(order),
+                        json!(limit),
+                        json!(cursor),
+                    ])),
+                )
+                .await?;
+
+            for cell in cells.objects.into_iter() {
+                let info = to_cell_info(cell);
+                let (lock_args, owner_lock) = match crate::withdrawal::verify_tradable(
+                    &info,
+                    last_finalized_block_number,
+                    &rollup_context.rollup_config.l1_sudt_script_type_hash(),
+                ) {
+                    Ok(parsed) => parsed,
+                    Err(err) => {
+                        log::debug!("[pending withdrawal] skip, verify failed {}", err);
+                        continue;
+                    }
+                };
+                if let Some(ref owner_lock_hash) = owner_lock_hash {
+                    if lock_args.owner_lock_hash().as_slice() != owner_lock_hash.as_slice() {
+                        continue;
+                    }
+                }
+
+                collected.push(PendingWithdrawal {
+                    cell: info,
+                    lock_args,
+                    owner_lock,
+                });
+                if collected.len() >= max_cells {
+                    break;
+                }
+            }
+
+            if cells.last_cursor.is_empty() {
+                break;
+            }
+            cursor = Some(cells.last_cursor);
+        }
+
+        Ok(collected)
+    }
+
     #[instrument(skip_all, fields(block_hash = %block_hash.pack()))]
     pub async fn get_header(
         &self,
@@ -8,6 +8,14 @@ use gw_types::packed::{
 };
 use gw_types::prelude::{Pack, Unpack};
 
+/// Unfinalized withdrawal cell which can be traded to a liquidity provider
+#[derive(Debug, Clone)]
+pub struct PendingWithdrawal {
+    pub cell: CellInfo,
+    pub lock_args: WithdrawalLockArgs,
+    pub owner_lock: Script,
+}
+
 pub fn verify_unlockable_to_owner(
     info: &CellInfo,
     last_finalized_block_number: u64,
@@ -33,7 +41,32 @@ fn verify_l1_sudt_script(info: &CellInfo, l1_sudt_script_hash: &Byte32) -> Resul
     Ok(())
 }
 
+/// Verify a pending withdrawal cell can be traded to a liquidity provider, returns the parsed
+/// withdrawal lock args and owner lock
+pub fn verify_tradable(
+    info: &CellInfo,
+    last_finalized_block_number: u64,
+    l1_sudt_script_hash: &Byte32,
+) -> Result<(WithdrawalLockArgs, Script)> {
+    verify_l1_sudt_script(info, l1_sudt_script_hash)?;
+    let (lock_args, owner_lock) = parse_owner_lock(info)?;
+    if lock_args.withdrawal_block_number().unpack() <= last_finalized_block_number {
+        bail!("finalized");
+    }
+
+    Ok((lock_args, owner_lock))
+}
+
 fn verify_finalized_owner_lock(info: &CellInfo, last_finalized_block_number: u64) -> Result<()> {
+    let (lock_args, _owner_lock) = parse_owner_lock(info)?;
+    if lock_args.withdrawal_block_number().unpack() > last_finalized_block_number {
+        bail!("unfinalized");
+    }
+
+    Ok(())
+}
+
+fn parse_owner_lock(info: &CellInfo) -> Result<(WithdrawalLockArgs, Script)> {
     let args: Bytes = info.output.lock().args().unpack();
 
     let lock_args_end = 32 + WithdrawalLockArgs::TOTAL_SIZE;
@@ -47,10 +80,6 @@ fn verify_finalized_owner_lock(info: &CellInfo, last_finalized_block_number: u64
         Err(_) => bail!("invalid withdrawal lock args"),
     };
 
-    if lock_args.withdrawal_block_number().unpack() > last_finalized_block_number {
-        bail!("unfinalized");
-    }
-
     let mut owner_lock_len_buf = [0u8; 4];
     owner_lock_len_buf.copy_from_slice(&args.slice(lock_args_end..owner_lock_start));
     let owner_lock_len = u32::from_be_bytes(owner_lock_len_buf) as usize;
@@ -67,7 +96,7 @@ fn verify_finalized_owner_lock(info: &CellInfo, last_finalized_block_number: u64
         bail!("owner lock not match");
     }
 
-    Ok(())
+    Ok((lock_args, owner_lock))
 }
 
 #[cfg(test)]
@@ -79,7 +108,7 @@ mod test {
     use gw_types::packed::{CellOutput, Script, WithdrawalLockArgs};
     use gw_types::prelude::{Builder, Entity, Pack};
 
-    use super::{verify_finalized_owner_lock, verify_l1_sudt_script};
+    use super::{verify_finalized_owner_lock, verify_l1_sudt_script, verify_tradable};
 
     #[test]
     fn test_verify_finalized_owner_lock() {
@@ -193,6 +222,45 @@ mod test {
         assert!(err.to_string().contains("owner lock not match"));
     }
 
+    #[test]
+    fn test_verify_tradable() {
+        let owner_lock = Script::new_builder()
+            .code_hash(H256::from_u32(1).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(vec![2u8; 32].pack())
+            .build();
+
+        let rollup_type_hash = [3u8; 32];
+
+        let last_finalized_block_number = 100u64;
+        let lock_args = WithdrawalLockArgs::new_builder()
+            .owner_lock_hash(owner_lock.hash().pack())
+            .withdrawal_block_number((last_finalized_block_number + 1).pack())
+            .build();
+
+        let mut args = rollup_type_hash.to_vec();
+        args.extend_from_slice(&lock_args.as_bytes());
+        args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&owner_lock.as_bytes());
+
+        let lock = Script::new_builder().args(args.pack()).build();
+        let info = CellInfo {
+            output: CellOutput::new_builder().lock(lock).build(),
+            ..Default::default()
+        };
+        let l1_sudt_script_hash = [4u8; 32].pack();
+        let (parsed_lock_args, parsed_owner_lock) =
+            verify_tradable(&info, last_finalized_block_number, &l1_sudt_script_hash)
+                .expect("pass");
+        assert_eq!(parsed_lock_args.as_slice(), lock_args.as_slice());
+        assert_eq!(parsed_owner_lock.as_slice(), owner_lock.as_slice());
+
+        // # finalized
+        let err = verify_tradable(&info, last_finalized_block_number + 1, &l1_sudt_script_hash)
+            .unwrap_err();
+        assert!(err.to_string().contains("finalized"));
+    }
+
     #[test]
     fn test_verify_l1_sudt_script() {
         let rollup_type_hash = [3u8; 32];
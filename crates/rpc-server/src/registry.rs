@@ -6,7 +6,7 @@ use gw_common::builtins::{CKB_SUDT_ACCOUNT_ID, ETH_REGISTRY_ACCOUNT_ID};
 use gw_common::{state::State, H256};
 use gw_config::{
     ChainConfig, ConsensusConfig, FeeConfig, MemPoolConfig, NodeMode, RPCMethods, RPCRateLimit,
-    RPCServerConfig, SyscallCyclesConfig,
+    RPCServerConfig, StagedBackendConfig,
 };
 use gw_dynamic_config::manager::{DynamicConfigManager, DynamicConfigReloadResponse};
 use gw_generator::generator::CyclesPool;
@@ -19,12 +19,13 @@ use gw_jsonrpc_types::godwoken::L2WithdrawalCommittedInfo;
 use gw_jsonrpc_types::{
     blockchain::Script,
     ckb_jsonrpc_types::{JsonBytes, Uint32},
+    debugger::{CyclesProfile, ExecutionTrace},
     godwoken::{
-        BackendInfo, BackendType, EoaScript, EoaScriptType, ErrorTxReceipt, GlobalState, GwScript,
-        GwScriptType, L2BlockCommittedInfo, L2BlockStatus, L2BlockView, L2BlockWithStatus,
-        L2TransactionStatus, L2TransactionWithStatus, LastL2BlockCommittedInfo, NodeInfo,
-        NodeRollupConfig, RegistryAddress, RollupCell, RunResult, TxReceipt, WithdrawalStatus,
-        WithdrawalWithStatus,
+        BackendInfo, BackendType, DBCheckpoint, EoaScript, EoaScriptType, ErrorTxReceipt,
+        GlobalState, GwScript, GwScriptType, L2BlockCommittedInfo, L2BlockStatus, L2BlockView,
+        L2BlockWithStatus, L2TransactionStatus, L2TransactionWithStatus, LastL2BlockCommittedInfo,
+        NodeInfo, NodeRollupConfig, PendingWithdrawalCell, RegistryAddress, RollupCell, RunResult,
+        TxAccessList, TxReceipt, WithdrawalStatus, WithdrawalWithStatus,
     },
     test_mode::TestModePayload,
 };
@@ -41,7 +42,7 @@ use gw_store::{
     CfMemStat, Store,
 };
 use gw_traits::CodeStore;
-use gw_types::offchain::RollupContext;
+use gw_types::offchain::{BatchWithdrawalSignature, RollupContext, SponsoredSignature};
 use gw_types::packed::RawL2Transaction;
 use gw_types::{
     bytes::Bytes,
@@ -56,6 +57,7 @@ use once_cell::sync::Lazy;
 use pprof::ProfilerGuard;
 use std::{
     convert::{TryFrom, TryInto},
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -73,6 +75,7 @@ type MemPool = Option<Arc<Mutex<gw_mem_pool::pool::MemPool>>>;
 type AccountID = Uint32;
 type JsonH256 = ckb_fixed_hash::H256;
 type BoxedTestsRPCImpl = Box<dyn TestModeRPC + Send + Sync>;
+type BackendUpgradeRPCImpl = Arc<dyn BackendUpgradeRPC + Send + Sync>;
 type GwUint64 = gw_jsonrpc_types::ckb_jsonrpc_types::Uint64;
 type GwUint32 = gw_jsonrpc_types::ckb_jsonrpc_types::Uint32;
 type RpcNodeMode = gw_jsonrpc_types::godwoken::NodeMode;
@@ -124,6 +127,11 @@ pub trait TestModeRPC {
     async fn produce_block(&self, payload: TestModePayload) -> Result<()>;
 }
 
+#[async_trait]
+pub trait BackendUpgradeRPC {
+    async fn stage_backends(&self, backends: Vec<StagedBackendConfig>) -> Result<()>;
+}
+
 fn to_h256(v: JsonH256) -> H256 {
     let h: [u8; 32] = v.into();
     h.into()
@@ -157,6 +165,7 @@ pub struct RegistryArgs<T> {
     pub mem_pool: MemPool,
     pub generator: Arc<Generator>,
     pub tests_rpc_impl: Option<Box<T>>,
+    pub backend_upgrade_rpc_impl: Option<BackendUpgradeRPCImpl>,
     pub rollup_config: RollupConfig,
     pub mem_pool_config: MemPoolConfig,
     pub node_mode: NodeMode,
@@ -174,6 +183,7 @@ pub struct Registry {
     mem_pool: MemPool,
     store: Store,
     tests_rpc_impl: Option<Arc<BoxedTestsRPCImpl>>,
+    backend_upgrade_rpc_impl: Option<BackendUpgradeRPCImpl>,
     rollup_config: RollupConfig,
     mem_pool_config: MemPoolConfig,
     backend_info: Vec<BackendInfo>,
@@ -200,6 +210,7 @@ impl Registry {
             mem_pool,
             store,
             tests_rpc_impl,
+            backend_upgrade_rpc_impl,
             rollup_config,
             mem_pool_config,
             node_mode,
@@ -252,6 +263,7 @@ impl Registry {
             generator,
             tests_rpc_impl: tests_rpc_impl
                 .map(|r| Arc::new(r as Box<dyn TestModeRPC + Sync + Send + 'static>)),
+            backend_upgrade_rpc_impl,
             rollup_config,
             mem_pool_config,
             backend_info,
@@ -334,7 +346,15 @@ impl Registry {
             .with_method("gw_get_data", get_data)
             .with_method("gw_get_transaction", get_transaction)
             .with_method("gw_get_transaction_receipt", get_transaction_receipt)
+            .with_method(
+                "gw_get_transaction_access_list",
+                get_transaction_access_list,
+            )
             .with_method("gw_get_withdrawal", get_withdrawal)
+            .with_method(
+                "gw_get_pending_withdrawal_cells",
+                get_pending_withdrawal_cells,
+            )
             .with_method("gw_get_pending_tx_hashes", get_pending_tx_hashes)
             .with_method("gw_execute_l2transaction", execute_l2transaction)
             .with_method("gw_execute_raw_l2transaction", execute_raw_l2transaction)
@@ -353,6 +373,7 @@ impl Registry {
             server = server
                 .with_method("gw_submit_l2transaction", submit_l2transaction)
                 .with_method("gw_submit_withdrawal_request", submit_withdrawal_request)
+                .with_method("gw_submit_withdrawal_requests", submit_withdrawal_requests)
                 .with_method("gw_is_request_in_queue", is_request_in_queue);
         }
 
@@ -377,6 +398,36 @@ impl Registry {
                         .with_method("gw_get_rocksdb_mem_stats", get_rocksdb_memory_stats)
                         .with_method("gw_dump_jemalloc_profiling", dump_jemalloc_profiling)
                 }
+                RPCMethods::Debug => {
+                    server = server
+                        .with_method("gw_debug_trace_l2transaction", debug_trace_l2transaction)
+                        .with_method("gw_debug_trace_transaction", debug_trace_transaction)
+                        .with_method(
+                            "gw_debug_profile_l2transaction",
+                            debug_profile_l2transaction,
+                        )
+                        .with_method("gw_debug_profile_transaction", debug_profile_transaction);
+                }
+                RPCMethods::Admin => {
+                    if let Some(ref backend_upgrade_rpc_impl) = self.backend_upgrade_rpc_impl {
+                        server = server
+                            .with_data(Data::new(Arc::clone(backend_upgrade_rpc_impl)))
+                            .with_method("gw_stage_backends", stage_backends);
+                    } else {
+                        log::warn!(
+                            "gw_stage_backends is disabled, backend upgrade isn't configured"
+                        );
+                    }
+                    if let Some(ref backup_dir) = self.server_config.backup_dir {
+                        server = server
+                            .with_data(Data::new(backup_dir.clone()))
+                            .with_method("gw_create_db_checkpoint", create_db_checkpoint);
+                    } else {
+                        log::warn!(
+                            "gw_create_db_checkpoint is disabled, backup_dir isn't configured"
+                        );
+                    }
+                }
             }
         }
 
@@ -433,7 +484,8 @@ fn req_to_entry(
             let backend_type = generator
                 .load_backend(0, state, &script_hash)
                 .ok_or_else(|| anyhow!("can't find backend for receiver: {}", receiver))?
-                .backend_type;
+                .backend_type
+                .clone();
             FeeEntry::from_tx(tx, fee_config, backend_type, order)
         }
         Request::Withdrawal(withdraw) => {
@@ -469,7 +521,8 @@ impl RequestSubmitter {
 
             // Use unlimit to ensure all exists mem pool transactions are included
             let mut org_cycles_pool = mem_pool.cycles_pool().clone();
-            *mem_pool.cycles_pool_mut() = CyclesPool::new(u64::MAX, SyscallCyclesConfig::default());
+            let syscall_cycles = org_cycles_pool.syscall_config().clone();
+            *mem_pool.cycles_pool_mut() = CyclesPool::new(u64::MAX, syscall_cycles);
 
             while let Some(hash) = mem_pool.pending_restored_tx_hashes().pop_front() {
                 match db.get_mem_pool_transaction(&hash) {
@@ -644,6 +697,19 @@ impl RequestSubmitter {
                     log::error!("[tx from zero] create account {}", err);
                 }
 
+                let txs: Vec<_> = items
+                    .iter()
+                    .filter_map(|(entry, _handle)| match entry.item {
+                        FeeItem::Tx(ref tx)
+                            if !matches!(entry.sender, FeeItemSender::PendingCreate(_)) =>
+                        {
+                            Some(tx.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                mem_pool.speculate_transactions(&txs);
+
                 let state = self.mem_pool_state.load_state_db();
                 let mut block_cycles_limit_reached = false;
 
@@ -712,6 +778,8 @@ impl RequestSubmitter {
                         log::info!("push {:?} {} failed {}", entry.item.kind(), hash, err);
                     }
                 }
+                // results of this batch must not be reused by the next one
+                mem_pool.clear_speculation();
 
                 if block_cycles_limit_reached {
                     drop(mem_pool);
@@ -947,10 +1015,27 @@ async fn get_transaction_receipt(
         .map(Into::into))
 }
 
+async fn get_transaction_access_list(
+    Params((tx_hash,)): Params<(JsonH256,)>,
+    store: Data<Store>,
+) -> Result<Option<TxAccessList>> {
+    let tx_hash = to_h256(tx_hash);
+    let db = store.get_snapshot();
+    // search from db
+    if let Some(access_list) = db.get_transaction_access_list(&tx_hash)? {
+        return Ok(Some(access_list.into()));
+    }
+    // search from mem pool
+    Ok(db
+        .get_mem_pool_transaction_access_list(&tx_hash)?
+        .map(Into::into))
+}
+
 fn verify_sender_balance<S: State + CodeStore>(
     ctx: &RollupContext,
     state: &S,
     raw_tx: &RawL2Transaction,
+    sponsored: Option<&SponsoredSignature>,
 ) -> Result<()> {
     use gw_generator::typed_transaction::types::TypedRawTransaction;
 
@@ -973,6 +1058,17 @@ fn verify_sender_balance<S: State + CodeStore>(
     if balance < tx_cost {
         return Err(TransactionError::InsufficientBalance.into());
     }
+    // the fee of a sponsored tx is paid by the sponsor
+    if let Some(sponsored) = sponsored {
+        let sponsor_script_hash = state.get_script_hash(sponsored.sponsor_id)?;
+        let sponsor_address = state
+            .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sponsor_script_hash)?
+            .ok_or_else(|| anyhow!("Can't find address for sponsor: {}", sponsored.sponsor_id))?;
+        let sponsor_balance = state.get_sudt_balance(CKB_SUDT_ACCOUNT_ID, &sponsor_address)?;
+        if sponsor_balance < U256::from(sponsored.fee) {
+            return Err(TransactionError::InsufficientBalance.into());
+        }
+    }
     Ok(())
 }
 
@@ -1009,7 +1105,13 @@ async fn execute_l2transaction(
     let from_id: u32 = tx.raw().from_id().unpack();
     if 0 != from_id {
         let state = ctx.mem_pool_state.load_state_db();
-        if let Err(err) = verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw()) {
+        let sponsored = SponsoredSignature::from_tx(&tx);
+        if let Err(err) = verify_sender_balance(
+            ctx.generator.rollup_context(),
+            &state,
+            &tx.raw(),
+            sponsored.as_ref(),
+        ) {
             return Err(RpcError::Full {
                 code: INVALID_REQUEST,
                 message: format!("check balance err: {}", err),
@@ -1025,15 +1127,24 @@ async fn execute_l2transaction(
         let mut state = ctx.mem_pool_state.load_state_db();
         let mut cycles_pool = CyclesPool::new(
             ctx.mem_pool_config.mem_block.max_cycles_limit,
-            ctx.mem_pool_config.mem_block.syscall_cycles.clone(),
+            ctx.mem_pool_config
+                .mem_block
+                .syscall_cycles_at(number)
+                .clone(),
         );
 
         // Mock sender account if not exists
         let eth_recover = &ctx.polyjuice_sender_recover.eth;
         let tx = eth_recover.mock_sender_if_not_exists(tx, &mut state)?;
         if 0 == from_id {
-            verify_sender_balance(ctx.generator.rollup_context(), &state, &tx.raw())
-                .map_err(|err| anyhow!("check balance err: {}", err))?;
+            let sponsored = SponsoredSignature::from_tx(&tx);
+            verify_sender_balance(
+                ctx.generator.rollup_context(),
+                &state,
+                &tx.raw(),
+                sponsored.as_ref(),
+            )
+            .map_err(|err| anyhow!("check balance err: {}", err))?;
         }
 
         // tx basic verification
@@ -1043,12 +1154,11 @@ async fn execute_l2transaction(
         // verify tx signature
         ctx.generator.check_transaction_signature(&state, &tx)?;
         // execute tx
-        let raw_tx = tx.raw();
-        let run_result = ctx.generator.unchecked_execute_transaction(
+        let run_result = ctx.generator.unchecked_execute_l2transaction(
             &chain_view,
             &mut state,
             &block_info,
-            &raw_tx,
+            &tx,
             100000000,
             Some(&mut cycles_pool),
         )?;
@@ -1141,7 +1251,10 @@ async fn execute_raw_l2transaction(
     let block_number: u64 = block_info.number().unpack();
     let mut cycles_pool = CyclesPool::new(
         ctx.mem_pool_config.mem_block.max_cycles_limit,
-        ctx.mem_pool_config.mem_block.syscall_cycles.clone(),
+        ctx.mem_pool_config
+            .mem_block
+            .syscall_cycles_at(block_number)
+            .clone(),
     );
 
     // check sender's balance
@@ -1152,11 +1265,11 @@ async fn execute_raw_l2transaction(
             Some(block_number) => {
                 let db = &db_txn;
                 let state = BlockStateDB::from_store(db, RWConfig::history_block(block_number))?;
-                verify_sender_balance(ctx.generator.rollup_context(), &state, &raw_l2tx)
+                verify_sender_balance(ctx.generator.rollup_context(), &state, &raw_l2tx, None)
             }
             None => {
                 let state = ctx.mem_pool_state.load_state_db();
-                verify_sender_balance(ctx.generator.rollup_context(), &state, &raw_l2tx)
+                verify_sender_balance(ctx.generator.rollup_context(), &state, &raw_l2tx, None)
             }
         };
         if let Err(err) = check_balance_result {
@@ -1188,7 +1301,7 @@ async fn execute_raw_l2transaction(
                     &mut state,
                 )?;
                 if 0 == from_id {
-                    verify_sender_balance(rollup_context, &state, &raw_l2tx)
+                    verify_sender_balance(rollup_context, &state, &raw_l2tx, None)
                         .map_err(|err| anyhow!("check balance err {}", err))?;
                 }
 
@@ -1209,7 +1322,7 @@ async fn execute_raw_l2transaction(
                     &mut state,
                 )?;
                 if 0 == from_id {
-                    verify_sender_balance(rollup_context, &state, &raw_l2tx)
+                    verify_sender_balance(rollup_context, &state, &raw_l2tx, None)
                         .map_err(|err| anyhow!("check balance err {}", err))?;
                 }
 
@@ -1246,6 +1359,176 @@ async fn execute_raw_l2transaction(
     Ok(run_result.into())
 }
 
+#[instrument(skip_all)]
+async fn debug_trace_l2transaction(
+    Params((l2tx,)): Params<(JsonBytes,)>,
+    ctx: Data<ExecutionTransactionContext>,
+) -> Result<ExecutionTrace, RpcError> {
+    if ctx.mem_pool.is_none() {
+        return Err(mem_pool_is_disabled_err());
+    }
+
+    let l2tx_bytes = l2tx.into_bytes();
+    let tx = packed::L2Transaction::from_slice(&l2tx_bytes)?;
+    let block_info = ctx
+        .mem_pool_state
+        .get_mem_pool_block_info()
+        .expect("get mem pool block info");
+
+    let trace = tokio::task::spawn_blocking(move || {
+        let db = ctx.store.get_snapshot();
+        let tip_block_hash = db.get_last_valid_tip_block_hash()?;
+        let chain_view = ChainView::new(&db, tip_block_hash);
+        let mut state = ctx.mem_pool_state.load_state_db();
+        let block_number: u64 = block_info.number().unpack();
+        let mut cycles_pool = CyclesPool::new(
+            ctx.mem_pool_config.mem_block.max_cycles_limit,
+            ctx.mem_pool_config
+                .mem_block
+                .syscall_cycles_at(block_number)
+                .clone(),
+        );
+
+        // Mock sender account if not exists
+        let eth_recover = &ctx.polyjuice_sender_recover.eth;
+        let tx = eth_recover.mock_sender_if_not_exists(tx, &mut state)?;
+
+        // tx basic verification
+        let polyjuice_creator_id = ctx.generator.get_polyjuice_creator_id(&state)?;
+        TransactionVerifier::new(&state, ctx.generator.rollup_context(), polyjuice_creator_id)
+            .verify(&tx)?;
+        // verify tx signature
+        ctx.generator.check_transaction_signature(&state, &tx)?;
+        // trace tx
+        let trace = ctx.generator.trace_execute_l2transaction(
+            &chain_view,
+            &mut state,
+            &block_info,
+            &tx,
+            ctx.mem_pool_config.execute_l2tx_max_cycles,
+            Some(&mut cycles_pool),
+        );
+
+        Result::<_, anyhow::Error>::Ok(trace)
+    })
+    .await??;
+
+    Ok(trace.into())
+}
+
+#[instrument(skip_all)]
+async fn debug_trace_transaction(
+    Params((tx_hash,)): Params<(JsonH256,)>,
+    ctx: Data<ExecutionTransactionContext>,
+) -> Result<Option<ExecutionTrace>> {
+    let tx_hash = to_h256(tx_hash);
+    let trace = tokio::task::spawn_blocking(move || {
+        let db = &ctx.store.begin_transaction();
+        let block_number: u64 = match db.get_transaction_info(&tx_hash)? {
+            Some(tx_info) => tx_info.block_number().unpack(),
+            None => return Ok(None),
+        };
+        let mut cycles_pool = CyclesPool::new(
+            ctx.mem_pool_config.mem_block.max_cycles_limit,
+            ctx.mem_pool_config
+                .mem_block
+                .syscall_cycles_at(block_number)
+                .clone(),
+        );
+        // replayed changes are dropped with the db transaction
+        ctx.generator
+            .trace_committed_transaction(db, &tx_hash, Some(&mut cycles_pool))
+    })
+    .await??;
+
+    Ok(trace.map(Into::into))
+}
+
+#[instrument(skip_all)]
+async fn debug_profile_l2transaction(
+    Params((l2tx,)): Params<(JsonBytes,)>,
+    ctx: Data<ExecutionTransactionContext>,
+) -> Result<CyclesProfile, RpcError> {
+    if ctx.mem_pool.is_none() {
+        return Err(mem_pool_is_disabled_err());
+    }
+
+    let l2tx_bytes = l2tx.into_bytes();
+    let tx = packed::L2Transaction::from_slice(&l2tx_bytes)?;
+    let block_info = ctx
+        .mem_pool_state
+        .get_mem_pool_block_info()
+        .expect("get mem pool block info");
+
+    let profile = tokio::task::spawn_blocking(move || {
+        let db = ctx.store.get_snapshot();
+        let tip_block_hash = db.get_last_valid_tip_block_hash()?;
+        let chain_view = ChainView::new(&db, tip_block_hash);
+        let mut state = ctx.mem_pool_state.load_state_db();
+        let block_number: u64 = block_info.number().unpack();
+        let mut cycles_pool = CyclesPool::new(
+            ctx.mem_pool_config.mem_block.max_cycles_limit,
+            ctx.mem_pool_config
+                .mem_block
+                .syscall_cycles_at(block_number)
+                .clone(),
+        );
+
+        // Mock sender account if not exists
+        let eth_recover = &ctx.polyjuice_sender_recover.eth;
+        let tx = eth_recover.mock_sender_if_not_exists(tx, &mut state)?;
+
+        // tx basic verification
+        let polyjuice_creator_id = ctx.generator.get_polyjuice_creator_id(&state)?;
+        TransactionVerifier::new(&state, ctx.generator.rollup_context(), polyjuice_creator_id)
+            .verify(&tx)?;
+        // verify tx signature
+        ctx.generator.check_transaction_signature(&state, &tx)?;
+        // profile tx
+        let profile = ctx.generator.profile_execute_l2transaction(
+            &chain_view,
+            &mut state,
+            &block_info,
+            &tx,
+            ctx.mem_pool_config.execute_l2tx_max_cycles,
+            Some(&mut cycles_pool),
+        );
+
+        Result::<_, anyhow::Error>::Ok(profile)
+    })
+    .await??;
+
+    Ok(profile.into())
+}
+
+#[instrument(skip_all)]
+async fn debug_profile_transaction(
+    Params((tx_hash,)): Params<(JsonH256,)>,
+    ctx: Data<ExecutionTransactionContext>,
+) -> Result<Option<CyclesProfile>> {
+    let tx_hash = to_h256(tx_hash);
+    let profile = tokio::task::spawn_blocking(move || {
+        let db = &ctx.store.begin_transaction();
+        let block_number: u64 = match db.get_transaction_info(&tx_hash)? {
+            Some(tx_info) => tx_info.block_number().unpack(),
+            None => return Ok(None),
+        };
+        let mut cycles_pool = CyclesPool::new(
+            ctx.mem_pool_config.mem_block.max_cycles_limit,
+            ctx.mem_pool_config
+                .mem_block
+                .syscall_cycles_at(block_number)
+                .clone(),
+        );
+        // replayed changes are dropped with the db transaction
+        ctx.generator
+            .profile_committed_transaction(db, &tx_hash, Some(&mut cycles_pool))
+    })
+    .await??;
+
+    Ok(profile.map(Into::into))
+}
+
 #[allow(clippy::type_complexity)]
 #[instrument(skip_all)]
 async fn submit_l2transaction(
@@ -1370,8 +1653,64 @@ async fn submit_withdrawal_request(
 ) -> Result<JsonH256, RpcError> {
     let withdrawal_bytes = withdrawal_request.into_bytes();
     let withdrawal = packed::WithdrawalRequestExtra::from_slice(&withdrawal_bytes)?;
-    let withdrawal_hash = withdrawal.hash();
 
+    verify_withdrawal_submission(&generator, &store, &withdrawal)?;
+    enqueue_withdrawal_request(&in_queue_request_map, &submit_tx, withdrawal)
+}
+
+/// Submit all withdrawal requests of a batch, which are signed once by the owner
+#[allow(clippy::type_complexity)]
+#[instrument(skip_all)]
+async fn submit_withdrawal_requests(
+    Params((withdrawal_requests,)): Params<(Vec<JsonBytes>,)>,
+    generator: Data<Generator>,
+    store: Data<Store>,
+    in_queue_request_map: Data<Option<Arc<InQueueRequestMap>>>,
+    submit_tx: Data<mpsc::Sender<(InQueueRequestHandle, Request)>>,
+) -> Result<Vec<JsonH256>, RpcError> {
+    let mut withdrawals = Vec::with_capacity(withdrawal_requests.len());
+    for withdrawal_request in withdrawal_requests {
+        let withdrawal_bytes = withdrawal_request.into_bytes();
+        withdrawals.push(packed::WithdrawalRequestExtra::from_slice(
+            &withdrawal_bytes,
+        )?);
+    }
+
+    // all requests of the batch must be submitted in order
+    let mut batch_hashes = None;
+    for (index, withdrawal) in withdrawals.iter().enumerate() {
+        let batch = BatchWithdrawalSignature::from_withdrawal(withdrawal)
+            .ok_or_else(|| invalid_param_err("not a batch withdrawal request"))?;
+        if batch.index as usize != index
+            || batch.raw_request_hashes.len() != withdrawals.len()
+            || !batch.contains(&withdrawal.raw())
+        {
+            return Err(invalid_param_err("incomplete batch withdrawal requests"));
+        }
+        match batch_hashes {
+            Some(ref hashes) if hashes != &batch.raw_request_hashes => {
+                return Err(invalid_param_err("mismatch batch withdrawal requests"));
+            }
+            Some(_) => {}
+            None => batch_hashes = Some(batch.raw_request_hashes),
+        }
+        verify_withdrawal_submission(&generator, &store, withdrawal)?;
+    }
+
+    let mut withdrawal_hashes = Vec::with_capacity(withdrawals.len());
+    for withdrawal in withdrawals {
+        let withdrawal_hash =
+            enqueue_withdrawal_request(&in_queue_request_map, &submit_tx, withdrawal)?;
+        withdrawal_hashes.push(withdrawal_hash);
+    }
+    Ok(withdrawal_hashes)
+}
+
+fn verify_withdrawal_submission(
+    generator: &Generator,
+    store: &Store,
+    withdrawal: &packed::WithdrawalRequestExtra,
+) -> Result<(), RpcError> {
     let last_valid = store.get_last_valid_tip_block_hash()?;
     let last_valid = store
         .get_block_number(&last_valid)?
@@ -1393,14 +1732,23 @@ async fn submit_withdrawal_request(
             data: None,
         });
     }
-    if let Err(err) = withdrawal_generator.verified_output(&withdrawal, &Default::default()) {
+    if let Err(err) = withdrawal_generator.verified_output(withdrawal, &Default::default()) {
         return Err(RpcError::Full {
             code: INVALID_REQUEST,
             message: err.to_string(),
             data: None,
         });
     }
+    Ok(())
+}
 
+#[allow(clippy::type_complexity)]
+fn enqueue_withdrawal_request(
+    in_queue_request_map: &Option<Arc<InQueueRequestMap>>,
+    submit_tx: &mpsc::Sender<(InQueueRequestHandle, Request)>,
+    withdrawal: packed::WithdrawalRequestExtra,
+) -> Result<JsonH256, RpcError> {
+    let withdrawal_hash = withdrawal.hash();
     let permit = submit_tx.try_reserve().map_err(|err| match err {
         mpsc::error::TrySendError::Closed(_) => RpcError::Provided {
             code: INTERNAL_ERROR_ERR_CODE,
@@ -1524,6 +1872,64 @@ async fn get_withdrawal(
     Ok(None)
 }
 
+const MAX_PENDING_WITHDRAWAL_CELLS: u32 = 100;
+
+// owner_lock_hash, limit
+async fn get_pending_withdrawal_cells(
+    Params((owner_lock_hash, limit)): Params<(Option<JsonH256>, Uint32)>,
+    store: Data<Store>,
+    rpc_client: Data<RPCClient>,
+) -> Result<Vec<PendingWithdrawalCell>, RpcError> {
+    let limit = limit.value();
+    if limit == 0 || limit > MAX_PENDING_WITHDRAWAL_CELLS {
+        return Err(invalid_param_err("limit should be between 1 and 100"));
+    }
+
+    let last_finalized_block_number: u64 = {
+        let db = store.get_snapshot();
+        let tip_block_hash = db.get_last_valid_tip_block_hash()?;
+        let global_state = db
+            .get_block_post_global_state(&tip_block_hash)?
+            .context("tip block global state")?;
+        global_state.last_finalized_block_number().unpack()
+    };
+
+    let withdrawals = rpc_client
+        .query_pending_withdrawal_cells(
+            last_finalized_block_number,
+            owner_lock_hash.map(to_h256),
+            limit as usize,
+        )
+        .await?;
+
+    let cells = withdrawals
+        .into_iter()
+        .map(|withdrawal| {
+            let output = &withdrawal.cell.output;
+            let sudt_script = output.type_().to_opt();
+            let amount = match sudt_script {
+                Some(_) => {
+                    let mut buf = [0u8; 16];
+                    buf.copy_from_slice(&withdrawal.cell.data[..16]);
+                    u128::from_le_bytes(buf)
+                }
+                None => 0,
+            };
+            let capacity: u64 = output.capacity().unpack();
+            PendingWithdrawalCell {
+                out_point: withdrawal.cell.out_point.clone().into(),
+                capacity: capacity.into(),
+                sudt_script: sudt_script.map(Into::into),
+                amount: amount.into(),
+                lock_args: withdrawal.lock_args.into(),
+                owner_lock: withdrawal.owner_lock.into(),
+            }
+        })
+        .collect();
+
+    Ok(cells)
+}
+
 // registry address, sudt_id, block_number
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(untagged)]
@@ -2056,6 +2462,45 @@ async fn dump_jemalloc_profiling() -> Result<()> {
     Ok(())
 }
 
+// Stage backends at runtime, they are activated once announced by the signal cell on L1.
+// Validators are verified against the deployment cells on L1.
+async fn stage_backends(
+    Params((backends,)): Params<(Vec<StagedBackendConfig>,)>,
+    backend_upgrade_rpc_impl: Data<BackendUpgradeRPCImpl>,
+) -> Result<()> {
+    backend_upgrade_rpc_impl.stage_backends(backends).await
+}
+
+// Create a checkpoint of the database in the backup dir while the node is running, the
+// checkpoint is verified before returning.
+async fn create_db_checkpoint(
+    store: Data<Store>,
+    backup_dir: Data<PathBuf>,
+) -> Result<DBCheckpoint> {
+    let store = Store::clone(&store);
+    let backup_dir = PathBuf::clone(&backup_dir);
+    tokio::task::spawn_blocking(move || {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)?
+            .as_millis();
+        let path = backup_dir.join(format!("checkpoint-{}", timestamp));
+        log::info!("create db checkpoint: {}", path.to_string_lossy());
+        store.create_checkpoint(&path)?;
+
+        let checkpoint = Store::open_checkpoint(&path)?;
+        checkpoint.check_tip_global_state()?;
+        let tip_block = checkpoint.get_tip_block()?;
+        let tip_block_number: u64 = tip_block.raw().number().unpack();
+        let tip_block_hash: [u8; 32] = tip_block.hash();
+        Result::<_, anyhow::Error>::Ok(DBCheckpoint {
+            path: path.to_string_lossy().into_owned(),
+            tip_block_number: tip_block_number.into(),
+            tip_block_hash: tip_block_hash.into(),
+        })
+    })
+    .await?
+}
+
 // Reload config dynamically and return the difference between two configs.
 async fn reload_config(
     dynamic_config_manager: Data<Arc<ArcSwap<DynamicConfigManager>>>,
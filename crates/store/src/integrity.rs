@@ -0,0 +1,957 @@
+//! Offline database integrity check and repair
+//!
+//! The check walks the main chain from the genesis block to the last valid tip block and
+//! verifies the cross references between columns: the number and hash index, block SMT
+//! leaves, global states, transactions, receipts and access lists, withdrawals and their
+//! infos, and the block state records. Bad blocks, the reverted block SMT, mem pool records,
+//! scripts and data are checked too. Records that don't belong to the main chain are reported
+//! as dangling.
+//!
+//! Repair detaches blocks down to the last consistent block, removes dangling records, then
+//! replays the detached blocks from their stored bodies. Blocks which can't be replayed are
+//! synced from L1 again when the node restarts.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use anyhow::{anyhow, ensure, Result};
+use gw_common::{
+    blake2b::new_blake2b, h256_ext::H256Ext, merkle_utils::calculate_state_checkpoint,
+    state::State, H256,
+};
+use gw_db::schema::{
+    Col, COLUMN_ASSET_SCRIPT, COLUMN_BAD_BLOCK, COLUMN_BAD_BLOCK_CHALLENGE_TARGET, COLUMN_BLOCK,
+    COLUMN_BLOCK_DEPOSIT_INFO_VEC, COLUMN_BLOCK_GLOBAL_STATE,
+    COLUMN_BLOCK_POST_FINALIZED_CUSTODIAN_CAPACITY, COLUMN_BLOCK_STATE_RECORD,
+    COLUMN_BLOCK_STATE_REVERSE_RECORD, COLUMN_BLOCK_SUBMIT_TX, COLUMN_BLOCK_SUBMIT_TX_HASH,
+    COLUMN_DATA, COLUMN_INDEX, COLUMN_MEM_POOL_TRANSACTION, COLUMN_MEM_POOL_TRANSACTION_RECEIPT,
+    COLUMN_MEM_POOL_WITHDRAWAL, COLUMN_META, COLUMN_REVERTED_BLOCK_SMT_ROOT, COLUMN_SCRIPT,
+    COLUMN_TRANSACTION, COLUMN_TRANSACTION_ACCESS_LIST, COLUMN_TRANSACTION_INFO,
+    COLUMN_TRANSACTION_RECEIPT, COLUMN_WITHDRAWAL, COLUMN_WITHDRAWAL_INFO,
+    META_LAST_VALID_TIP_BLOCK_HASH_KEY, META_TIP_BLOCK_HASH_KEY,
+};
+use gw_db::IteratorMode;
+use gw_types::{
+    offchain::{global_state_from_slice, ExportedBlock},
+    packed::{self, RawL2Block, TransactionKey, WithdrawalKey},
+    prelude::*,
+};
+
+use crate::state::history::block_state_record::{BlockStateRecordKey, BlockStateRecordKeyReverse};
+use crate::state::history::history_state::RWConfig;
+use crate::state::BlockStateDB;
+use crate::traits::chain_store::ChainStore;
+use crate::traits::kv_store::{KVStoreRead, KVStoreWrite};
+use crate::transaction::StoreTransaction;
+use crate::Store;
+
+/// Columns keyed by block number in big endian
+const BLOCK_NUMBER_COLUMNS: [Col; 4] = [
+    COLUMN_BLOCK_SUBMIT_TX,
+    COLUMN_BLOCK_SUBMIT_TX_HASH,
+    COLUMN_BLOCK_DEPOSIT_INFO_VEC,
+    COLUMN_BLOCK_POST_FINALIZED_CUSTODIAN_CAPACITY,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// The block or the records derived from it are corrupted, the block must be detached.
+    Block { number: u64, reason: String },
+    /// The record doesn't belong to the main chain, it can be removed.
+    Dangling {
+        col: Col,
+        key: Vec<u8>,
+        reason: String,
+    },
+    /// The record can't be rebuilt by detaching blocks, the database must be restored.
+    Unrecoverable { reason: String },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inconsistency::Block { number, reason } => write!(f, "block #{}: {}", number, reason),
+            Inconsistency::Dangling { col, key, reason } => {
+                let key: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+                write!(f, "column {} key 0x{}: {}", col, key, reason)
+            }
+            Inconsistency::Unrecoverable { reason } => write!(f, "unrecoverable: {}", reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IntegrityReport {
+    pub tip_block_number: u64,
+    pub last_valid_tip_block_number: u64,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl IntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+
+    /// The lowest corrupted block number, blocks before it are consistent.
+    pub fn first_bad_block(&self) -> Option<u64> {
+        self.inconsistencies
+            .iter()
+            .filter_map(|inconsistency| match inconsistency {
+                Inconsistency::Block { number, .. } => Some(*number),
+                Inconsistency::Dangling { .. } | Inconsistency::Unrecoverable { .. } => None,
+            })
+            .min()
+    }
+
+    pub fn is_recoverable(&self) -> bool {
+        !self
+            .inconsistencies
+            .iter()
+            .any(|inconsistency| matches!(inconsistency, Inconsistency::Unrecoverable { .. }))
+    }
+}
+
+/// Transaction and withdrawal counts of a main chain block
+struct MainChainBlock {
+    txs: u32,
+    withdrawals: u32,
+}
+
+/// Check the integrity of the whole database, the node must be stopped.
+pub fn check_integrity(store: &Store) -> Result<IntegrityReport> {
+    let db = &store.begin_transaction();
+    let tip_block = get_meta_block(db, META_TIP_BLOCK_HASH_KEY)?;
+    let last_valid_tip_block = get_meta_block(db, META_LAST_VALID_TIP_BLOCK_HASH_KEY)?;
+    let tip_block_number: u64 = tip_block.raw().number().unpack();
+    let last_valid_tip_block_number: u64 = last_valid_tip_block.raw().number().unpack();
+
+    let mut inconsistencies = Vec::new();
+
+    // main chain
+    let mut main_chain = HashMap::new();
+    {
+        let block_smt = db.block_smt()?;
+        for number in 0..=last_valid_tip_block_number {
+            let smt_leaf = block_smt.get(&RawL2Block::compute_smt_key(number).into())?;
+            for reason in check_block(db, number, smt_leaf, &mut main_chain)? {
+                inconsistencies.push(Inconsistency::Block { number, reason });
+            }
+        }
+    }
+
+    // account state
+    {
+        let tree = BlockStateDB::from_store(db, RWConfig::readonly())?;
+        let post_account = last_valid_tip_block.raw().post_account();
+        let account_root: H256 = post_account.merkle_root().unpack();
+        let account_count: u32 = post_account.count().unpack();
+        if tree.calculate_root()? != account_root || tree.get_account_count()? != account_count {
+            inconsistencies.push(Inconsistency::Block {
+                number: last_valid_tip_block_number,
+                reason: "account state mismatch with post account".to_string(),
+            });
+        }
+    }
+
+    check_index(db, last_valid_tip_block_number, &mut inconsistencies)?;
+    check_infos(db, last_valid_tip_block_number, &mut inconsistencies)?;
+    check_state_records(db, last_valid_tip_block_number, &mut inconsistencies);
+    check_block_records(db, &main_chain, &mut inconsistencies);
+    check_bad_blocks(
+        db,
+        last_valid_tip_block_number,
+        tip_block_number,
+        &mut inconsistencies,
+    )?;
+    check_reverted_block_smt(db, &last_valid_tip_block, &mut inconsistencies)?;
+    check_mem_pool(db, &mut inconsistencies);
+    check_scripts_and_data(db, &mut inconsistencies);
+
+    // records of blocks after the tip block
+    for col in BLOCK_NUMBER_COLUMNS {
+        for (key, _value) in db.get_iter(col, IteratorMode::Start) {
+            if key.len() != 8 {
+                inconsistencies.push(dangling(col, &key, "malformed key"));
+                continue;
+            }
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&key);
+            let number = u64::from_be_bytes(buf);
+            if number > tip_block_number {
+                inconsistencies.push(dangling(col, &key, "block is after the tip"));
+            }
+        }
+    }
+
+    Ok(IntegrityReport {
+        tip_block_number,
+        last_valid_tip_block_number,
+        inconsistencies,
+    })
+}
+
+/// Repair the database by detaching blocks down to the last consistent block, removing
+/// dangling records and replaying the detached blocks, the node must be stopped.
+///
+/// `replay` applies an exported block on top of the tip block, the replay stops at the first
+/// failed block and the rest blocks are synced from L1 again when the node restarts.
+///
+/// Return the report of the repaired database.
+pub fn repair<F>(store: &Store, mut replay: F) -> Result<IntegrityReport>
+where
+    F: FnMut(ExportedBlock) -> Result<()>,
+{
+    let mut report = check_integrity(store)?;
+    ensure!(
+        report.is_recoverable(),
+        "found unrecoverable inconsistencies, restore the database from a backup"
+    );
+
+    // Detached blocks in ascending order, from the block after the last consistent block.
+    let mut detached_blocks: Vec<ExportedBlock> = Vec::new();
+    while let Some(first_bad_block) = report.first_bad_block() {
+        ensure!(
+            first_bad_block > 0,
+            "genesis block is corrupted, restore the database from a backup"
+        );
+        ensure!(
+            report.tip_block_number == report.last_valid_tip_block_number,
+            "bad blocks found after the last valid tip block, rewind to the last valid block first"
+        );
+        let target_block_number = first_bad_block - 1;
+        let detached_count = report.last_valid_tip_block_number - target_block_number;
+        let mut blocks = detach_to(store, target_block_number)?;
+        // Blocks detached before follow the ones detached now, unless there is a gap.
+        if blocks.len() as u64 == detached_count {
+            blocks.append(&mut detached_blocks);
+        }
+        detached_blocks = blocks;
+        // Detaching blocks with corrupted state records may leave the account state
+        // inconsistent, check again and detach more blocks.
+        report = check_integrity(store)?;
+    }
+
+    if !report.is_consistent() {
+        let db = &store.begin_transaction();
+        for inconsistency in &report.inconsistencies {
+            if let Inconsistency::Dangling { col, key, .. } = inconsistency {
+                db.delete(*col, key)?;
+            }
+        }
+        db.commit()?;
+    }
+
+    for block in detached_blocks {
+        let block_number = block.block_number();
+        if let Err(err) = replay(block) {
+            log::warn!(
+                "replay block #{} failed: {}, the rest blocks will be synced from L1",
+                block_number,
+                err
+            );
+            break;
+        }
+    }
+    check_integrity(store)
+}
+
+fn get_meta_block(db: &StoreTransaction, meta_key: &[u8]) -> Result<packed::L2Block> {
+    let meta_name = String::from_utf8_lossy(meta_key);
+    let block_hash = db
+        .get(COLUMN_META, meta_key)
+        .ok_or_else(|| anyhow!("{} not found", meta_name))?;
+    let block_hash: H256 = packed::Byte32Reader::from_slice(&block_hash)
+        .map_err(|_| anyhow!("malformed {}", meta_name))?
+        .unpack();
+    db.get_block(&block_hash)?
+        .or_else(|| db.get_bad_block(&block_hash))
+        .ok_or_else(|| anyhow!("{} block {:?} not found", meta_name, block_hash))
+}
+
+fn check_block(
+    db: &StoreTransaction,
+    number: u64,
+    smt_leaf: H256,
+    main_chain: &mut HashMap<H256, MainChainBlock>,
+) -> Result<Vec<String>> {
+    let mut reasons = Vec::new();
+    let block_hash = match db.get_block_hash_by_number(number)? {
+        Some(block_hash) => block_hash,
+        None => {
+            reasons.push("block number index not found".to_string());
+            return Ok(reasons);
+        }
+    };
+    if db.get_block_number(&block_hash)? != Some(number) {
+        reasons.push(format!("block hash index of {:?} mismatch", block_hash));
+    }
+    let block = match db.get_block(&block_hash)? {
+        Some(block) => block,
+        None => {
+            reasons.push(format!("block {:?} not found", block_hash));
+            return Ok(reasons);
+        }
+    };
+    let block_number: u64 = block.raw().number().unpack();
+    if block_number != number || H256::from(block.hash()) != block_hash {
+        reasons.push(format!("block {:?} mismatch with the index", block_hash));
+    }
+    main_chain.insert(
+        block_hash,
+        MainChainBlock {
+            txs: block.transactions().len() as u32,
+            withdrawals: block.withdrawals().len() as u32,
+        },
+    );
+    if number > 0 {
+        let parent_block_hash: H256 = block.raw().parent_block_hash().unpack();
+        if db.get_block_hash_by_number(number - 1)? != Some(parent_block_hash) {
+            reasons.push(format!("parent block {:?} mismatch", parent_block_hash));
+        }
+    }
+    if smt_leaf != block_hash {
+        reasons.push(format!("block smt leaf {:?} mismatch", smt_leaf));
+    }
+    match db.get_block_post_global_state(&block_hash)? {
+        Some(global_state) => {
+            let tip_block_hash: H256 = global_state.tip_block_hash().unpack();
+            let block_count: u64 = global_state.block().count().unpack();
+            if tip_block_hash != block_hash
+                || block_count != number + 1
+                || global_state.account().as_slice() != block.raw().post_account().as_slice()
+            {
+                reasons.push("post global state mismatch".to_string());
+            }
+        }
+        None => reasons.push("post global state not found".to_string()),
+    }
+
+    let state_checkpoint_list = block.raw().state_checkpoint_list();
+    let withdrawals_len = block.withdrawals().len();
+    for (index, tx) in block.transactions().into_iter().enumerate() {
+        let tx_hash: H256 = tx.hash().into();
+        let key = TransactionKey::build_transaction_key(block_hash.pack(), index as u32);
+        match db.get_transaction_info(&tx_hash)? {
+            Some(info) => {
+                let info_block_number: u64 = info.block_number().unpack();
+                if info.key().as_slice() != key.as_slice() || info_block_number != number {
+                    reasons.push(format!("transaction info of {:?} mismatch", tx_hash));
+                }
+            }
+            None => reasons.push(format!("transaction info of {:?} not found", tx_hash)),
+        }
+        match db.get(COLUMN_TRANSACTION, key.as_slice()) {
+            Some(value) if value.as_ref() == tx.as_slice() => {}
+            Some(_) => reasons.push(format!("transaction {:?} mismatch", tx_hash)),
+            None => reasons.push(format!("transaction {:?} not found", tx_hash)),
+        }
+        let receipt = match db.get(COLUMN_TRANSACTION_RECEIPT, key.as_slice()) {
+            Some(receipt) => receipt,
+            None => {
+                reasons.push(format!("transaction receipt of {:?} not found", tx_hash));
+                continue;
+            }
+        };
+        let receipt = match packed::TxReceiptReader::from_slice(&receipt) {
+            Ok(receipt) => receipt,
+            Err(_) => {
+                reasons.push(format!("transaction receipt of {:?} is malformed", tx_hash));
+                continue;
+            }
+        };
+        let post_state = receipt.post_state();
+        let checkpoint = calculate_state_checkpoint(
+            &post_state.merkle_root().unpack(),
+            post_state.count().unpack(),
+        );
+        let block_checkpoint: Option<H256> = state_checkpoint_list
+            .get(withdrawals_len + index)
+            .map(|checkpoint| checkpoint.unpack());
+        if receipt.tx_witness_hash().as_slice() != tx.witness_hash()
+            || block_checkpoint != Some(checkpoint)
+        {
+            reasons.push(format!("transaction receipt of {:?} mismatch", tx_hash));
+        }
+    }
+
+    for (index, withdrawal) in block.withdrawals().into_iter().enumerate() {
+        let withdrawal_hash: H256 = withdrawal.hash().into();
+        let key = WithdrawalKey::build_withdrawal_key(block_hash.pack(), index as u32);
+        match db.get_withdrawal_info(&withdrawal_hash)? {
+            Some(info) => {
+                let info_block_number: u64 = info.block_number().unpack();
+                if info.key().as_slice() != key.as_slice() || info_block_number != number {
+                    reasons.push(format!("withdrawal info of {:?} mismatch", withdrawal_hash));
+                }
+            }
+            None => reasons.push(format!(
+                "withdrawal info of {:?} not found",
+                withdrawal_hash
+            )),
+        }
+        match db.get(COLUMN_WITHDRAWAL, key.as_slice()) {
+            Some(value) => match packed::WithdrawalRequestExtraReader::from_slice(&value) {
+                Ok(extra) if extra.request().as_slice() == withdrawal.as_slice() => {}
+                _ => reasons.push(format!("withdrawal {:?} mismatch", withdrawal_hash)),
+            },
+            None => reasons.push(format!("withdrawal {:?} not found", withdrawal_hash)),
+        }
+    }
+
+    Ok(reasons)
+}
+
+/// Check index entries which aren't checked by walking the main chain.
+fn check_index(
+    db: &StoreTransaction,
+    last_valid_tip_block_number: u64,
+    inconsistencies: &mut Vec<Inconsistency>,
+) -> Result<()> {
+    for (key, value) in db.get_iter(COLUMN_INDEX, IteratorMode::Start) {
+        match key.len() {
+            // block number -> block hash
+            8 => {
+                let number: u64 = packed::Uint64Reader::from_slice_should_be_ok(&key).unpack();
+                if number > last_valid_tip_block_number {
+                    inconsistencies.push(dangling(COLUMN_INDEX, &key, "block is after the tip"));
+                }
+            }
+            // block hash -> block number
+            32 => {
+                let number: u64 = match packed::Uint64Reader::from_slice(&value) {
+                    Ok(number) => number.unpack(),
+                    Err(_) => {
+                        inconsistencies.push(dangling(COLUMN_INDEX, &key, "malformed value"));
+                        continue;
+                    }
+                };
+                let block_hash: H256 = packed::Byte32Reader::from_slice_should_be_ok(&key).unpack();
+                if number > last_valid_tip_block_number {
+                    inconsistencies.push(dangling(COLUMN_INDEX, &key, "block is after the tip"));
+                } else if db.get_block_hash_by_number(number)? != Some(block_hash) {
+                    inconsistencies.push(dangling(COLUMN_INDEX, &key, "block isn't on main chain"));
+                }
+            }
+            _ => inconsistencies.push(dangling(COLUMN_INDEX, &key, "malformed key")),
+        }
+    }
+    Ok(())
+}
+
+/// Check transaction and withdrawal infos point to the main chain.
+fn check_infos(
+    db: &StoreTransaction,
+    last_valid_tip_block_number: u64,
+    inconsistencies: &mut Vec<Inconsistency>,
+) -> Result<()> {
+    for (key, value) in db.get_iter(COLUMN_TRANSACTION_INFO, IteratorMode::Start) {
+        let info = match packed::TransactionInfoReader::from_slice(&value) {
+            Ok(info) => info.to_entity(),
+            Err(_) => {
+                inconsistencies.push(dangling(COLUMN_TRANSACTION_INFO, &key, "malformed value"));
+                continue;
+            }
+        };
+        let number: u64 = info.block_number().unpack();
+        let on_main_chain = number <= last_valid_tip_block_number
+            && db
+                .get_block_hash_by_number(number)?
+                .map(|h| h.as_slice().to_vec())
+                == Some(info.key().as_slice()[..32].to_vec())
+            && db
+                .get_transaction_by_key(&info.key())?
+                .map(|tx| tx.hash().to_vec())
+                == Some(key.to_vec());
+        if !on_main_chain {
+            let reason = "transaction isn't on main chain";
+            inconsistencies.push(dangling(COLUMN_TRANSACTION_INFO, &key, reason));
+        }
+    }
+
+    for (key, value) in db.get_iter(COLUMN_WITHDRAWAL_INFO, IteratorMode::Start) {
+        let info = match packed::WithdrawalInfoReader::from_slice(&value) {
+            Ok(info) => info.to_entity(),
+            Err(_) => {
+                inconsistencies.push(dangling(COLUMN_WITHDRAWAL_INFO, &key, "malformed value"));
+                continue;
+            }
+        };
+        let number: u64 = info.block_number().unpack();
+        let on_main_chain = number <= last_valid_tip_block_number
+            && db
+                .get_block_hash_by_number(number)?
+                .map(|h| h.as_slice().to_vec())
+                == Some(info.key().as_slice()[..32].to_vec())
+            && db
+                .get_withdrawal_by_key(&info.key())?
+                .map(|withdrawal| withdrawal.request().hash().to_vec())
+                == Some(key.to_vec());
+        if !on_main_chain {
+            let reason = "withdrawal isn't on main chain";
+            inconsistencies.push(dangling(COLUMN_WITHDRAWAL_INFO, &key, reason));
+        }
+    }
+    Ok(())
+}
+
+/// Check every block state record has a reverse record and vice versa.
+fn check_state_records(
+    db: &StoreTransaction,
+    last_valid_tip_block_number: u64,
+    inconsistencies: &mut Vec<Inconsistency>,
+) {
+    for (key, _value) in db.get_iter(COLUMN_BLOCK_STATE_RECORD, IteratorMode::Start) {
+        if key.len() != 40 {
+            inconsistencies.push(dangling(COLUMN_BLOCK_STATE_RECORD, &key, "malformed key"));
+            continue;
+        }
+        let record_key = BlockStateRecordKey::from_slice(&key);
+        let number = record_key.block_number();
+        if number > last_valid_tip_block_number {
+            let reason = "block is after the tip";
+            inconsistencies.push(dangling(COLUMN_BLOCK_STATE_RECORD, &key, reason));
+            continue;
+        }
+        let reverse_key = BlockStateRecordKeyReverse::new(number, &record_key.state_key());
+        if db
+            .get(COLUMN_BLOCK_STATE_REVERSE_RECORD, reverse_key.as_slice())
+            .is_none()
+        {
+            inconsistencies.push(Inconsistency::Block {
+                number,
+                reason: format!(
+                    "reverse state record of {:?} not found",
+                    record_key.state_key()
+                ),
+            });
+        }
+    }
+
+    for (key, _value) in db.get_iter(COLUMN_BLOCK_STATE_REVERSE_RECORD, IteratorMode::Start) {
+        if key.len() != 40 {
+            let reason = "malformed key";
+            inconsistencies.push(dangling(COLUMN_BLOCK_STATE_REVERSE_RECORD, &key, reason));
+            continue;
+        }
+        let reverse_key = BlockStateRecordKeyReverse::from_slice(&key);
+        let number = reverse_key.block_number();
+        if number > last_valid_tip_block_number {
+            let reason = "block is after the tip";
+            inconsistencies.push(dangling(COLUMN_BLOCK_STATE_REVERSE_RECORD, &key, reason));
+            continue;
+        }
+        let record_key = BlockStateRecordKey::new(number, &reverse_key.state_key());
+        if db
+            .get(COLUMN_BLOCK_STATE_RECORD, record_key.as_slice())
+            .is_none()
+        {
+            inconsistencies.push(Inconsistency::Block {
+                number,
+                reason: format!("state record of {:?} not found", reverse_key.state_key()),
+            });
+        }
+    }
+}
+
+/// Check records keyed by block hash belong to the main chain, the bodies of detached blocks
+/// are left as orphans by `detach_block`.
+fn check_block_records(
+    db: &StoreTransaction,
+    main_chain: &HashMap<H256, MainChainBlock>,
+    inconsistencies: &mut Vec<Inconsistency>,
+) {
+    let is_main_chain = |key: &[u8]| key.len() == 32 && main_chain.contains_key(&to_h256(key));
+    for (key, _value) in db.get_iter(COLUMN_BLOCK, IteratorMode::Start) {
+        if !is_main_chain(&key) {
+            inconsistencies.push(dangling(COLUMN_BLOCK, &key, "block isn't on main chain"));
+        }
+    }
+    for (key, value) in db.get_iter(COLUMN_BLOCK_GLOBAL_STATE, IteratorMode::Start) {
+        if global_state_from_slice(&value).is_err() {
+            let reason = "malformed value";
+            inconsistencies.push(dangling(COLUMN_BLOCK_GLOBAL_STATE, &key, reason));
+        } else if !is_main_chain(&key) && db.get(COLUMN_BAD_BLOCK, &key).is_none() {
+            let reason = "block isn't on main chain";
+            inconsistencies.push(dangling(COLUMN_BLOCK_GLOBAL_STATE, &key, reason));
+        }
+    }
+
+    // keyed by `block hash | index`
+    let txs: fn(&MainChainBlock) -> u32 = |block| block.txs;
+    let withdrawals: fn(&MainChainBlock) -> u32 = |block| block.withdrawals;
+    let cols = [
+        (COLUMN_TRANSACTION, txs),
+        (COLUMN_TRANSACTION_RECEIPT, txs),
+        (COLUMN_WITHDRAWAL, withdrawals),
+    ];
+    for (col, count) in cols {
+        for (key, _value) in db.get_iter(col, IteratorMode::Start) {
+            if key.len() != 36 {
+                inconsistencies.push(dangling(col, &key, "malformed key"));
+            } else if !is_indexed_by_main_chain(main_chain, &key, count) {
+                inconsistencies.push(dangling(col, &key, "block isn't on main chain"));
+            }
+        }
+    }
+
+    // Access lists of committed txs are keyed by the tx key, and the ones of mem pool txs are
+    // keyed by the tx hash.
+    let col = COLUMN_TRANSACTION_ACCESS_LIST;
+    for (key, value) in db.get_iter(col, IteratorMode::Start) {
+        if packed::TxAccessListReader::from_slice(&value).is_err() {
+            inconsistencies.push(dangling(col, &key, "malformed value"));
+            continue;
+        }
+        match key.len() {
+            36 => {
+                if !is_indexed_by_main_chain(main_chain, &key, txs) {
+                    inconsistencies.push(dangling(col, &key, "transaction isn't on main chain"));
+                }
+            }
+            32 => {
+                if db.get(COLUMN_MEM_POOL_TRANSACTION, &key).is_none() {
+                    inconsistencies.push(dangling(col, &key, "transaction isn't in mem pool"));
+                }
+            }
+            _ => inconsistencies.push(dangling(col, &key, "malformed key")),
+        }
+    }
+}
+
+/// Check bad blocks are either after the last valid tip block or reverted.
+fn check_bad_blocks(
+    db: &StoreTransaction,
+    last_valid_tip_block_number: u64,
+    tip_block_number: u64,
+    inconsistencies: &mut Vec<Inconsistency>,
+) -> Result<()> {
+    let reverted_block_smt = db.reverted_block_smt()?;
+    for (key, value) in db.get_iter(COLUMN_BAD_BLOCK, IteratorMode::Start) {
+        let block = match packed::L2BlockReader::from_slice(&value) {
+            Ok(block) if block.raw().hash()[..] == key[..] => block,
+            _ => {
+                inconsistencies.push(dangling(COLUMN_BAD_BLOCK, &key, "malformed value"));
+                continue;
+            }
+        };
+        let number: u64 = block.raw().number().unpack();
+        if number > last_valid_tip_block_number && number <= tip_block_number {
+            if db.get(COLUMN_BLOCK_GLOBAL_STATE, &key).is_none() {
+                inconsistencies.push(Inconsistency::Unrecoverable {
+                    reason: format!("post global state of bad block #{} not found", number),
+                });
+            }
+        } else if reverted_block_smt.get(&to_h256(&key))? != H256::one() {
+            let reason = "block is neither after the last valid tip nor reverted";
+            inconsistencies.push(dangling(COLUMN_BAD_BLOCK, &key, reason));
+        }
+    }
+
+    let col = COLUMN_BAD_BLOCK_CHALLENGE_TARGET;
+    for (key, value) in db.get_iter(col, IteratorMode::Start) {
+        if packed::ChallengeTargetReader::from_slice(&value).is_err() {
+            inconsistencies.push(dangling(col, &key, "malformed value"));
+        } else if db.get(COLUMN_BAD_BLOCK, &key).is_none() {
+            inconsistencies.push(dangling(col, &key, "bad block not found"));
+        }
+    }
+    Ok(())
+}
+
+/// Walk the reverted block SMT roots from the current root to the genesis root, every reverted
+/// block hash must be in the SMT.
+fn check_reverted_block_smt(
+    db: &StoreTransaction,
+    last_valid_tip_block: &packed::L2Block,
+    inconsistencies: &mut Vec<Inconsistency>,
+) -> Result<()> {
+    let reverted_block_smt = db.reverted_block_smt()?;
+    let tip_reverted_block_root: Option<H256> = db
+        .get_block_post_global_state(&last_valid_tip_block.hash().into())?
+        .map(|global_state| global_state.reverted_block_root().unpack());
+
+    let mut visited = HashSet::new();
+    let mut root = *reverted_block_smt.root();
+    while !root.is_zero() && visited.insert(root) {
+        let value = match db.get(COLUMN_REVERTED_BLOCK_SMT_ROOT, root.as_slice()) {
+            Some(value) => value,
+            None => {
+                inconsistencies.push(Inconsistency::Unrecoverable {
+                    reason: format!("reverted block hashes of root {:?} not found", root),
+                });
+                break;
+            }
+        };
+        let hashes: Vec<H256> = match packed::Byte32VecReader::from_slice(&value) {
+            Ok(hashes) if hashes.len() > 1 => hashes.to_entity().unpack(),
+            _ => {
+                inconsistencies.push(Inconsistency::Unrecoverable {
+                    reason: format!("reverted block hashes of root {:?} are malformed", root),
+                });
+                break;
+            }
+        };
+        for block_hash in &hashes[1..] {
+            if reverted_block_smt.get(block_hash)? != H256::one() {
+                inconsistencies.push(Inconsistency::Unrecoverable {
+                    reason: format!("reverted block {:?} not found in the smt", block_hash),
+                });
+            }
+        }
+        root = hashes[0];
+    }
+    if let Some(tip_reverted_block_root) = tip_reverted_block_root {
+        if !tip_reverted_block_root.is_zero() && !visited.contains(&tip_reverted_block_root) {
+            inconsistencies.push(Inconsistency::Unrecoverable {
+                reason: format!(
+                    "reverted block root {:?} of the last valid tip isn't in the history",
+                    tip_reverted_block_root
+                ),
+            });
+        }
+    }
+
+    // Roots left by rewinding the reverted block smt
+    for (key, _value) in db.get_iter(COLUMN_REVERTED_BLOCK_SMT_ROOT, IteratorMode::Start) {
+        if key.len() != 32 || !visited.contains(&to_h256(&key)) {
+            let reason = "root isn't in the history";
+            inconsistencies.push(dangling(COLUMN_REVERTED_BLOCK_SMT_ROOT, &key, reason));
+        }
+    }
+    Ok(())
+}
+
+/// Check mem pool records are keyed by their hashes.
+fn check_mem_pool(db: &StoreTransaction, inconsistencies: &mut Vec<Inconsistency>) {
+    let col = COLUMN_MEM_POOL_TRANSACTION;
+    for (key, value) in db.get_iter(col, IteratorMode::Start) {
+        match packed::L2TransactionReader::from_slice(&value) {
+            Ok(tx) if tx.hash()[..] == key[..] => {}
+            _ => inconsistencies.push(dangling(col, &key, "malformed value")),
+        }
+    }
+
+    let col = COLUMN_MEM_POOL_TRANSACTION_RECEIPT;
+    for (key, value) in db.get_iter(col, IteratorMode::Start) {
+        if packed::TxReceiptReader::from_slice(&value).is_err() {
+            inconsistencies.push(dangling(col, &key, "malformed value"));
+        } else if db.get(COLUMN_MEM_POOL_TRANSACTION, &key).is_none() {
+            inconsistencies.push(dangling(col, &key, "transaction isn't in mem pool"));
+        }
+    }
+
+    let col = COLUMN_MEM_POOL_WITHDRAWAL;
+    for (key, value) in db.get_iter(col, IteratorMode::Start) {
+        match packed::WithdrawalRequestExtraReader::from_slice(&value) {
+            Ok(withdrawal) if withdrawal.request().hash()[..] == key[..] => {}
+            _ => inconsistencies.push(dangling(col, &key, "malformed value")),
+        }
+    }
+}
+
+/// Check scripts and data are keyed by their hashes.
+fn check_scripts_and_data(db: &StoreTransaction, inconsistencies: &mut Vec<Inconsistency>) {
+    for col in [COLUMN_SCRIPT, COLUMN_ASSET_SCRIPT] {
+        for (key, value) in db.get_iter(col, IteratorMode::Start) {
+            match packed::ScriptReader::from_slice(&value) {
+                Ok(script) if script.hash()[..] == key[..] => {}
+                _ => inconsistencies.push(dangling(col, &key, "malformed value")),
+            }
+        }
+    }
+
+    for (key, value) in db.get_iter(COLUMN_DATA, IteratorMode::Start) {
+        let mut data_hash = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&value);
+        hasher.finalize(&mut data_hash);
+        if data_hash[..] != key[..] {
+            inconsistencies.push(dangling(COLUMN_DATA, &key, "data hash mismatch"));
+        }
+    }
+}
+
+fn is_indexed_by_main_chain(
+    main_chain: &HashMap<H256, MainChainBlock>,
+    key: &[u8],
+    count: fn(&MainChainBlock) -> u32,
+) -> bool {
+    let mut index = [0u8; 4];
+    index.copy_from_slice(&key[32..]);
+    match main_chain.get(&to_h256(&key[..32])) {
+        Some(block) => u32::from_be_bytes(index) < count(block),
+        None => false,
+    }
+}
+
+/// Detach blocks after the target block from the main chain.
+///
+/// Return the exported detached blocks in ascending order, the export stops at the first block
+/// whose records are incomplete.
+fn detach_to(store: &Store, target_block_number: u64) -> Result<Vec<ExportedBlock>> {
+    // Walk the main chain by parent block hash, the number index may be corrupted.
+    let mut blocks: Vec<packed::L2Block> = Vec::new();
+    let exported_blocks: Vec<ExportedBlock>;
+    {
+        let db = &store.begin_transaction();
+        let mut block_hash = db.get_last_valid_tip_block_hash()?;
+        loop {
+            let block = db.get_block(&block_hash)?.ok_or_else(|| {
+                anyhow!(
+                    "block {:?} not found, restore the database from a backup",
+                    block_hash
+                )
+            })?;
+            let number: u64 = block.raw().number().unpack();
+            if let Some(child) = blocks.last() {
+                let child_number: u64 = child.raw().number().unpack();
+                ensure!(
+                    number + 1 == child_number,
+                    "block {:?} number mismatch, restore the database from a backup",
+                    block_hash
+                );
+            }
+            block_hash = block.raw().parent_block_hash().unpack();
+            blocks.push(block);
+            if number <= target_block_number {
+                break;
+            }
+        }
+
+        // `detach_block` looks up the parent block by number, rebuild the index first.
+        for block in &blocks {
+            let raw_number = block.raw().number();
+            let block_hash = block.hash();
+            db.insert_raw(COLUMN_INDEX, raw_number.as_slice(), &block_hash)?;
+            db.insert_raw(COLUMN_INDEX, &block_hash, raw_number.as_slice())?;
+        }
+
+        // Export blocks before `detach_block` deletes their deposits and submit txs.
+        exported_blocks = blocks
+            .windows(2)
+            .rev()
+            .map_while(|pair| export_block(db, &pair[0], &pair[1]))
+            .collect();
+        if exported_blocks.len() + 1 < blocks.len() {
+            log::warn!(
+                "records of block #{} are incomplete, it can't be replayed",
+                target_block_number + 1 + exported_blocks.len() as u64
+            );
+        }
+        db.commit()?;
+    }
+
+    // The last one is the target block
+    blocks.pop();
+    for block in blocks {
+        let number: u64 = block.raw().number().unpack();
+        let db = &store.begin_transaction();
+        db.detach_block(&block)?;
+        {
+            let mut state = BlockStateDB::from_store(db, RWConfig::detach_block())?;
+            state.detach_block_state(number)?;
+        }
+        db.commit()?;
+    }
+    Ok(exported_blocks)
+}
+
+/// Export a stored block with the records needed to replay it.
+fn export_block(
+    db: &StoreTransaction,
+    block: &packed::L2Block,
+    parent_block: &packed::L2Block,
+) -> Option<ExportedBlock> {
+    let block_number: u64 = block.raw().number().unpack();
+    let block_hash = block.hash();
+    let get_global_state = |block_hash: &[u8]| {
+        let value = db.get(COLUMN_BLOCK_GLOBAL_STATE, block_hash)?;
+        global_state_from_slice(&value).ok()
+    };
+    let post_global_state = get_global_state(&block_hash[..])?;
+    let parent_global_state = get_global_state(&parent_block.hash()[..])?;
+
+    let deposit_info_vec = {
+        let value = db.get(COLUMN_BLOCK_DEPOSIT_INFO_VEC, &block_number.to_be_bytes())?;
+        packed::DepositInfoVecReader::from_slice(&value)
+            .ok()?
+            .to_entity()
+    };
+    let mut deposit_asset_scripts = Vec::new();
+    for deposit in deposit_info_vec.as_reader().iter() {
+        let script_hash: H256 = deposit.request().sudt_script_hash().unpack();
+        if script_hash.is_zero() {
+            continue;
+        }
+        let value = db.get(COLUMN_ASSET_SCRIPT, script_hash.as_slice())?;
+        let script = packed::ScriptReader::from_slice(&value).ok()?;
+        deposit_asset_scripts.push(script.to_entity());
+    }
+
+    let mut withdrawals = Vec::new();
+    for index in 0..block.withdrawals().len() {
+        let key = WithdrawalKey::build_withdrawal_key(block_hash.pack(), index as u32);
+        let value = db.get(COLUMN_WITHDRAWAL, key.as_slice())?;
+        let withdrawal = packed::WithdrawalRequestExtraReader::from_slice(&value).ok()?;
+        withdrawals.push(withdrawal.to_entity());
+    }
+
+    // Bad blocks reverted since the parent block
+    let parent_reverted_block_root: H256 = parent_global_state.reverted_block_root().unpack();
+    let mut reverted_block_root: H256 = post_global_state.reverted_block_root().unpack();
+    let bad_block_hashes = if reverted_block_root == parent_reverted_block_root {
+        None
+    } else {
+        let mut bad_block_hashes = Vec::new();
+        let mut visited = HashSet::new();
+        while reverted_block_root != parent_reverted_block_root {
+            if reverted_block_root.is_zero() || !visited.insert(reverted_block_root) {
+                return None;
+            }
+            let value = db.get(
+                COLUMN_REVERTED_BLOCK_SMT_ROOT,
+                reverted_block_root.as_slice(),
+            )?;
+            let mut block_hashes: Vec<H256> = packed::Byte32VecReader::from_slice(&value)
+                .ok()?
+                .to_entity()
+                .unpack();
+            // Same layout as `set_reverted_block_hashes`, the prev root is at index 0
+            let last_hash_idx = block_hashes.len().saturating_sub(1);
+            block_hashes.swap(0, last_hash_idx);
+            reverted_block_root = block_hashes.pop()?;
+            bad_block_hashes.push(block_hashes);
+        }
+        bad_block_hashes.reverse();
+        Some(bad_block_hashes)
+    };
+
+    let submit_tx_hash = match db.get(COLUMN_BLOCK_SUBMIT_TX_HASH, &block_number.to_be_bytes()) {
+        Some(value) => Some(packed::Byte32Reader::from_slice(&value).ok()?.unpack()),
+        None => None,
+    };
+
+    Some(ExportedBlock {
+        block: block.to_owned(),
+        post_global_state,
+        deposit_info_vec,
+        deposit_asset_scripts,
+        withdrawals,
+        bad_block_hashes,
+        submit_tx_hash,
+    })
+}
+
+fn dangling(col: Col, key: &[u8], reason: &str) -> Inconsistency {
+    Inconsistency::Dangling {
+        col,
+        key: key.to_vec(),
+        reason: reason.to_string(),
+    }
+}
+
+fn to_h256(slice: &[u8]) -> H256 {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(slice);
+    buf.into()
+}
@@ -1,4 +1,5 @@
 pub mod chain_view;
+pub mod integrity;
 pub mod mem_pool_state;
 pub mod readonly;
 pub mod smt;
@@ -1,6 +1,6 @@
 use gw_db::{
+    engine::EngineSnapshot,
     schema::{Col, COLUMN_MEM_POOL_TRANSACTION},
-    DBIterator, RocksDBSnapshot,
 };
 
 use crate::traits::{
@@ -9,11 +9,11 @@ use crate::traits::{
 };
 
 pub struct StoreSnapshot {
-    inner: RocksDBSnapshot,
+    inner: Box<dyn EngineSnapshot>,
 }
 
 impl StoreSnapshot {
-    pub(crate) fn new(inner: RocksDBSnapshot) -> Self {
+    pub(crate) fn new(inner: Box<dyn EngineSnapshot>) -> Self {
         Self { inner }
     }
 }
@@ -23,9 +23,9 @@ impl ChainStore for StoreSnapshot {}
 impl KVStoreRead for StoreSnapshot {
     fn get(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>> {
         self.inner
-            .get_pinned(col, key)
+            .get(col, key)
             .expect("db operation should be ok")
-            .map(|v| Box::<[u8]>::from(v.as_ref()))
+            .map(Box::from)
     }
 }
 
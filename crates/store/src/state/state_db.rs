@@ -17,6 +17,7 @@ use anyhow::Result;
 use gw_traits::CodeStore;
 use gw_types::{
     bytes::Bytes,
+    offchain::AccessList,
     packed::{self, LogItem},
     prelude::{Pack, Unpack},
 };
@@ -86,6 +87,7 @@ pub struct StateTracker {
     touched_keys: Mutex<HashSet<H256>>,
     write_data: Mutex<HashMap<H256, Bytes>>,
     read_data: Mutex<HashMap<H256, Bytes>>,
+    access_list: Mutex<AccessList>,
 }
 
 impl StateTracker {
@@ -106,6 +108,31 @@ impl StateTracker {
     pub fn touch_key(&self, key: &H256) {
         self.touched_keys.lock().unwrap().insert(*key);
     }
+
+    /// Return read and write keys
+    pub fn access_list(&self) -> &Mutex<AccessList> {
+        &self.access_list
+    }
+
+    fn read_key(&self, key: &H256) {
+        self.touch_key(key);
+        let mut access_list = self.access_list.lock().unwrap();
+        if !access_list.read_keys.insert(*key) {
+            access_list.repeated_keys.insert(*key);
+        }
+    }
+
+    fn write_key(&self, key: &H256) {
+        self.touch_key(key);
+        let mut access_list = self.access_list.lock().unwrap();
+        if !access_list.write_keys.insert(*key) {
+            access_list.repeated_keys.insert(*key);
+        }
+    }
+
+    fn access_account_count(&self, flag: u8) {
+        self.access_list.lock().unwrap().account_count_access |= flag;
+    }
 }
 
 pub struct StateDB<S> {
@@ -267,6 +294,20 @@ impl<S: State + CodeStore> StateDB<S> {
         Ok(())
     }
 
+    fn get_raw_untracked(&self, key: &H256) -> Result<H256, StateError> {
+        if let Some(v) = self.dirty_state.get(key) {
+            return Ok(*v);
+        }
+        self.state.get_raw(key)
+    }
+
+    fn get_account_count_untracked(&self) -> Result<u32, StateError> {
+        if let Some(count) = self.dirty_account_count {
+            return Ok(count);
+        }
+        self.state.get_account_count()
+    }
+
     pub fn last_state_root(&self) -> H256 {
         self.last_state_root
     }
@@ -413,36 +454,37 @@ impl<S: State + CodeStore> JournalDB for StateDB<S> {
 impl<S: State + CodeStore> State for StateDB<S> {
     fn get_raw(&self, key: &H256) -> Result<H256, StateError> {
         if let Some(tracker) = self.state_tracker.as_ref() {
-            tracker.touch_key(key);
-        }
-        if let Some(v) = self.dirty_state.get(key) {
-            return Ok(*v);
+            tracker.read_key(key);
         }
-        self.state.get_raw(key)
+        self.get_raw_untracked(key)
     }
 
     fn update_raw(&mut self, key: H256, value: H256) -> Result<(), StateError> {
         if let Some(tracker) = self.state_tracker.as_ref() {
-            tracker.touch_key(&key);
+            tracker.write_key(&key);
         }
+        // the journal entry isn't a read of the transaction
         self.journal.push(JournalEntry::UpdateRaw {
             key,
-            prev_value: self.get_raw(&key)?,
+            prev_value: self.get_raw_untracked(&key)?,
         });
         self.dirty_state.insert(key, value);
         Ok(())
     }
 
     fn get_account_count(&self) -> Result<u32, StateError> {
-        if let Some(count) = self.dirty_account_count {
-            return Ok(count);
+        if let Some(tracker) = self.state_tracker.as_ref() {
+            tracker.access_account_count(AccessList::ACCOUNT_COUNT_READ);
         }
-        self.state.get_account_count()
+        self.get_account_count_untracked()
     }
 
     fn set_account_count(&mut self, count: u32) -> Result<(), StateError> {
+        if let Some(tracker) = self.state_tracker.as_ref() {
+            tracker.access_account_count(AccessList::ACCOUNT_COUNT_WRITE);
+        }
         self.journal.push(JournalEntry::SetAccountCount {
-            prev_count: self.get_account_count()?,
+            prev_count: self.get_account_count_untracked()?,
         });
         self.dirty_account_count = Some(count);
         Ok(())
@@ -512,6 +554,7 @@ impl<S: CodeStore> CodeStore for StateDB<S> {
 mod tests {
     use gw_common::{h256_ext::H256Ext, smt::SMT, state::State, H256};
     use gw_traits::CodeStore;
+    use gw_types::offchain::AccessList;
 
     use crate::{
         smt::smt_store::SMTStateStore,
@@ -670,4 +713,54 @@ mod tests {
         // the dirty state is cleared, but the state is write into the store
         assert!(cmp_dirty_state(&mem_1, &state));
     }
+
+    #[test]
+    fn test_state_tracker_access_list() {
+        let store = Store::open_tmp().unwrap();
+        let mut state = new_state(store.get_snapshot());
+        state.set_state_tracker(Default::default());
+        state.get_raw(&H256::from_u32(1)).unwrap();
+        state.get_raw(&H256::from_u32(1)).unwrap();
+        // the journal reads the previous value, but it isn't a read of the tx
+        state
+            .update_raw(H256::from_u32(2), H256::from_u32(2))
+            .unwrap();
+        let count = state.get_account_count().unwrap();
+        let tracker = state.take_state_tracker().unwrap();
+        let a = tracker.access_list().lock().unwrap().clone();
+        assert_eq!(a.read_keys, vec![H256::from_u32(1)].into_iter().collect());
+        assert_eq!(a.write_keys, vec![H256::from_u32(2)].into_iter().collect());
+        assert_eq!(a.account_count_access, AccessList::ACCOUNT_COUNT_READ);
+        assert_eq!(
+            a.repeated_keys,
+            vec![H256::from_u32(1)].into_iter().collect()
+        );
+        assert_eq!(tracker.touched_keys().lock().unwrap().len(), 2);
+
+        state.set_state_tracker(Default::default());
+        state.get_raw(&H256::from_u32(2)).unwrap();
+        state.set_account_count(count + 1).unwrap();
+        let b = state
+            .take_state_tracker()
+            .unwrap()
+            .access_list()
+            .lock()
+            .unwrap()
+            .clone();
+        assert_eq!(b.account_count_access, AccessList::ACCOUNT_COUNT_WRITE);
+
+        // b reads the key written by a, and creates an account while a reads the count
+        assert!(!a.is_independent(&b));
+        assert!(!b.is_independent(&a));
+        let c = AccessList {
+            read_keys: vec![H256::from_u32(1), H256::from_u32(3)]
+                .into_iter()
+                .collect(),
+            write_keys: vec![H256::from_u32(3)].into_iter().collect(),
+            account_count_access: AccessList::ACCOUNT_COUNT_READ,
+            ..Default::default()
+        };
+        assert!(a.is_independent(&c));
+        assert!(!b.is_independent(&c));
+    }
 }
@@ -7,24 +7,48 @@ use crate::traits::chain_store::ChainStore;
 use crate::traits::kv_store::KVStoreRead;
 use crate::write_batch::StoreWriteBatch;
 use crate::{snapshot::StoreSnapshot, transaction::StoreTransaction};
-use anyhow::Result;
+use anyhow::{anyhow, ensure, Result};
 use gw_common::error::Error;
 use gw_common::smt::Blake2bHasher;
+use gw_common::state::State;
+use gw_common::H256;
+use gw_config::StoreConfig;
 
+use gw_config::StoreEngine;
 use gw_db::{
-    schema::{Col, COLUMNS},
-    CfMemStat, DBPinnableSlice, RocksDB,
+    engine::{
+        open_engine, open_engine_with_check, EngineWriteBatch, LogDB, MemoryDB, StorageEngine,
+    },
+    schema::{Col, COLUMNS, COLUMN_ACCOUNT_SMT_LEAF},
+    CfMemStat, RocksDB,
 };
 use gw_types::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct Store {
-    db: RocksDB,
+    db: Arc<dyn StorageEngine>,
 }
 
-impl<'a> Store {
-    pub fn new(db: RocksDB) -> Self {
-        Store { db }
+impl Store {
+    pub fn new<E: StorageEngine + 'static>(db: E) -> Self {
+        Store { db: Arc::new(db) }
+    }
+
+    /// Open the storage engine selected by `StoreConfig::engine`, RocksDB databases are
+    /// migrated to the current version.
+    pub fn open(config: &StoreConfig) -> Result<Self> {
+        Ok(Store {
+            db: open_engine(config)?,
+        })
+    }
+
+    /// Open the storage engine selected by `StoreConfig::engine` without running migrations.
+    pub fn open_with_check(config: &StoreConfig) -> Result<Self> {
+        Ok(Store {
+            db: open_engine_with_check(config)?,
+        })
     }
 
     pub fn open_tmp() -> Result<Self> {
@@ -32,10 +56,16 @@ impl<'a> Store {
         Ok(Self::new(db))
     }
 
-    fn get(&'a self, col: Col, key: &[u8]) -> Option<DBPinnableSlice<'a>> {
+    /// Open an empty store which keeps all data in memory.
+    pub fn open_memory() -> Self {
+        Self::new(MemoryDB::open(COLUMNS))
+    }
+
+    fn get(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>> {
         self.db
-            .get_pinned(col, key)
+            .get(col, key)
             .expect("db operation should be ok")
+            .map(Box::from)
     }
 
     pub fn begin_transaction(&self) -> StoreTransaction {
@@ -50,7 +80,7 @@ impl<'a> Store {
 
     pub fn new_write_batch(&self) -> StoreWriteBatch {
         StoreWriteBatch {
-            inner: self.db.new_write_batch(),
+            inner: EngineWriteBatch::default(),
         }
     }
 
@@ -88,14 +118,101 @@ impl<'a> Store {
         Ok(())
     }
 
+    /// Check the global state of the tip block matches the stored block smt, and the account
+    /// state matches the last valid tip block, used to verify a restored database.
+    pub fn check_tip_global_state(&self) -> Result<()> {
+        let db = &self.begin_transaction();
+        let tip_block = db.get_tip_block()?;
+        let tip_block_hash: H256 = tip_block.hash().into();
+        let tip_number: u64 = tip_block.raw().number().unpack();
+        let global_state = db
+            .get_block_post_global_state(&tip_block_hash)?
+            .ok_or_else(|| anyhow!("tip block {} post global state not found", tip_number))?;
+
+        let global_state_tip_hash: H256 = global_state.tip_block_hash().unpack();
+        ensure!(
+            global_state_tip_hash == tip_block_hash,
+            "global state tip block hash {:?} mismatch, expected {:?}",
+            global_state_tip_hash,
+            tip_block_hash
+        );
+        let block_count: u64 = global_state.block().count().unpack();
+        ensure!(
+            block_count == tip_number + 1,
+            "global state block count {} mismatch, tip block number {}",
+            block_count,
+            tip_number
+        );
+        let block_smt_root: H256 = global_state.block().merkle_root().unpack();
+        let stored_block_smt_root = db.get_block_smt_root()?;
+        ensure!(
+            block_smt_root == stored_block_smt_root,
+            "global state block smt root {:?} mismatch, stored {:?}",
+            block_smt_root,
+            stored_block_smt_root
+        );
+
+        let last_valid_tip_block = db.get_last_valid_tip_block()?;
+        let post_account = last_valid_tip_block.raw().post_account();
+        let tree = BlockStateDB::from_store(db, RWConfig::readonly())?;
+        let account_root: H256 = post_account.merkle_root().unpack();
+        let account_count: u32 = post_account.count().unpack();
+        ensure!(
+            tree.calculate_root()? == account_root && tree.get_account_count()? == account_count,
+            "account state mismatch with last valid tip block {}",
+            last_valid_tip_block.raw().number().unpack()
+        );
+        Ok(())
+    }
+
+    /// Create a consistent checkpoint of the store while the node is running, the path must
+    /// not exist.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.db.create_checkpoint(path.as_ref())?;
+        Ok(())
+    }
+
+    /// Open a checkpoint created by `create_checkpoint`
+    pub fn open_checkpoint<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let engine = if LogDB::exists(&path) {
+            StoreEngine::Log
+        } else {
+            StoreEngine::RocksDB
+        };
+        let config = StoreConfig {
+            path: path.as_ref().to_path_buf(),
+            engine,
+            ..Default::default()
+        };
+        Self::open_with_check(&config)
+    }
+
     pub fn get_snapshot(&self) -> StoreSnapshot {
-        StoreSnapshot::new(self.db.get_snapshot())
+        StoreSnapshot::new(self.db.snapshot())
+    }
+
+    /// Read state of keys in parallel, so the following execution reads them from the block
+    /// cache instead of the disk
+    pub fn prefetch_state(&self, keys: &[H256]) {
+        const MIN_KEYS_PER_THREAD: usize = 64;
+
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = std::cmp::max(MIN_KEYS_PER_THREAD, (keys.len() + threads - 1) / threads);
+        std::thread::scope(|s| {
+            for chunk in keys.chunks(chunk_size) {
+                s.spawn(move || {
+                    for key in chunk {
+                        let _ = self.db.get(COLUMN_ACCOUNT_SMT_LEAF, key.as_slice());
+                    }
+                });
+            }
+        });
     }
 }
 
 impl ChainStore for Store {}
 impl KVStoreRead for Store {
     fn get(&self, col: Col, key: &[u8]) -> Option<Box<[u8]>> {
-        self.get(col, key).map(|v| Box::<[u8]>::from(v.as_ref()))
+        self.get(col, key)
     }
 }
@@ -2,7 +2,12 @@ use crate::{
     traits::kv_store::{KVStoreRead, KVStoreWrite},
     Store,
 };
-use gw_db::{DBRawIterator, Direction::Forward, IteratorMode};
+use gw_config::{StoreConfig, StoreEngine};
+use gw_db::{
+    DBRawIterator,
+    Direction::{Forward, Reverse},
+    IteratorMode,
+};
 use std::collections::HashMap;
 
 #[test]
@@ -372,3 +377,104 @@ fn range_search_and_delete() {
 
     assert!(store_txn.get(1, &[6, 0]).is_some());
 }
+
+// Stores of every engine, the temp dir of the log engine must outlive its store
+fn engine_stores() -> Vec<(Store, Option<tempfile::TempDir>)> {
+    let log_dir = tempfile::Builder::new().tempdir().unwrap();
+    let log_config = StoreConfig {
+        path: log_dir.path().to_path_buf(),
+        engine: StoreEngine::Log,
+        ..Default::default()
+    };
+    vec![
+        (Store::open_tmp().unwrap(), None),
+        (Store::open_memory(), None),
+        (Store::open(&log_config).unwrap(), Some(log_dir)),
+    ]
+}
+
+#[test]
+fn seek_for_prev_on_engines() {
+    for (store, _dir) in engine_stores() {
+        let store_txn = &store.begin_transaction();
+        store_txn.insert_raw(1, &[0], &[0, 0, 0]).unwrap();
+        store_txn.insert_raw(1, &[1], &[1, 1, 1]).unwrap();
+        store_txn.insert_raw(2, &[2], &[2, 2, 2]).unwrap();
+        store_txn.insert_raw(1, &[3], &[3, 3, 3]).unwrap();
+        store_txn.commit().unwrap();
+
+        let store_txn = store.begin_transaction();
+        let iter = store_txn.get_iter(1, IteratorMode::Start);
+        let mut raw_iter: DBRawIterator = iter.into();
+        raw_iter.seek_for_prev([5]);
+        assert_eq!(&[3], raw_iter.key().unwrap());
+        assert_eq!(&[3, 3, 3], raw_iter.value().unwrap());
+
+        raw_iter.seek_for_prev([2]);
+        assert_eq!(&[1], raw_iter.key().unwrap());
+        assert_eq!(&[1, 1, 1], raw_iter.value().unwrap());
+
+        raw_iter.seek([2]);
+        assert_eq!(&[3], raw_iter.key().unwrap());
+
+        raw_iter.seek([4]);
+        assert!(!raw_iter.valid());
+
+        raw_iter.seek_for_prev([0]);
+        assert_eq!(&[0], raw_iter.key().unwrap());
+
+        // seek with the iterator mode
+        let mut iter = store_txn.get_iter(1, IteratorMode::From(&[2], Reverse));
+        let (key, value) = iter.next().unwrap();
+        assert_eq!(&[1], key.as_ref());
+        assert_eq!(&[1, 1, 1], value.as_ref());
+        let (key, _) = iter.next().unwrap();
+        assert_eq!(&[0], key.as_ref());
+        assert!(iter.next().is_none());
+
+        let mut iter = store_txn.get_iter(1, IteratorMode::From(&[2], Forward));
+        let (key, _) = iter.next().unwrap();
+        assert_eq!(&[3], key.as_ref());
+        assert!(iter.next().is_none());
+    }
+}
+
+#[test]
+fn seek_for_prev_with_suffix_on_engines() {
+    for (store, _dir) in engine_stores() {
+        let store_txn = &store.begin_transaction();
+        let key_1_with_ver_1_5 = [&[1u8][..], &1u64.to_be_bytes(), &5u32.to_be_bytes()].concat();
+        let key_2_with_ver_2_7 = [&[2u8, 2][..], &2u64.to_be_bytes(), &7u32.to_be_bytes()].concat();
+        let key_3_with_ver_256_2 =
+            [&[3u8, 3, 3][..], &256u64.to_be_bytes(), &2u32.to_be_bytes()].concat();
+        store_txn.insert_raw(1, &key_1_with_ver_1_5, &[1]).unwrap();
+        store_txn
+            .insert_raw(1, &key_2_with_ver_2_7, &[2, 2])
+            .unwrap();
+        store_txn
+            .insert_raw(1, &key_3_with_ver_256_2, &[3, 3, 3])
+            .unwrap();
+
+        // seek in the uncommitted transaction
+        let key_3_with_ver_256_1 =
+            [&[3u8, 3, 3][..], &256u64.to_be_bytes(), &1u32.to_be_bytes()].concat();
+        let key_1_with_ver_1_4 = [&[1u8][..], &1u64.to_be_bytes(), &4u32.to_be_bytes()].concat();
+
+        let iter = store_txn.get_iter(1, IteratorMode::Start);
+        let mut raw_iter: DBRawIterator = iter.into();
+        raw_iter.seek_for_prev(&key_3_with_ver_256_2);
+        assert_eq!(&key_3_with_ver_256_2, &raw_iter.key().unwrap());
+
+        raw_iter.seek_for_prev(&key_3_with_ver_256_1);
+        assert_eq!(&key_2_with_ver_2_7, &raw_iter.key().unwrap());
+        assert_eq!(&[2, 2], raw_iter.value().unwrap());
+
+        raw_iter.seek_for_prev(&key_1_with_ver_1_4);
+        assert!(!raw_iter.valid());
+        assert!(raw_iter.key().is_none());
+
+        let mut iter = store_txn.get_iter(1, IteratorMode::From(&key_3_with_ver_256_1, Reverse));
+        let (key, _) = iter.next().unwrap();
+        assert_eq!(&key_2_with_ver_2_7[..], key.as_ref());
+    }
+}
@@ -10,21 +10,22 @@ use gw_db::schema::{
     COLUMN_BLOCK_POST_FINALIZED_CUSTODIAN_CAPACITY, COLUMN_BLOCK_SUBMIT_TX,
     COLUMN_BLOCK_SUBMIT_TX_HASH, COLUMN_INDEX, COLUMN_MEM_POOL_TRANSACTION,
     COLUMN_MEM_POOL_TRANSACTION_RECEIPT, COLUMN_MEM_POOL_WITHDRAWAL, COLUMN_META,
-    COLUMN_REVERTED_BLOCK_SMT_ROOT, COLUMN_TRANSACTION, COLUMN_TRANSACTION_INFO,
-    COLUMN_TRANSACTION_RECEIPT, COLUMN_WITHDRAWAL, COLUMN_WITHDRAWAL_INFO, META_BLOCK_SMT_ROOT_KEY,
-    META_CHAIN_ID_KEY, META_LAST_CONFIRMED_BLOCK_NUMBER_HASH_KEY,
+    COLUMN_REVERTED_BLOCK_SMT_ROOT, COLUMN_TRANSACTION, COLUMN_TRANSACTION_ACCESS_LIST,
+    COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT, COLUMN_WITHDRAWAL, COLUMN_WITHDRAWAL_INFO,
+    META_BLOCK_SMT_ROOT_KEY, META_CHAIN_ID_KEY, META_LAST_CONFIRMED_BLOCK_NUMBER_HASH_KEY,
     META_LAST_SUBMITTED_BLOCK_NUMBER_HASH_KEY, META_LAST_VALID_TIP_BLOCK_HASH_KEY,
     META_REVERTED_BLOCK_SMT_ROOT_KEY, META_TIP_BLOCK_HASH_KEY,
 };
 use gw_types::{
     from_box_should_be_ok,
-    offchain::{global_state_from_slice, SMTRevertedBlockHashes},
+    offchain::{global_state_from_slice, AccessList, SMTRevertedBlockHashes},
     packed::{
         self, ChallengeTarget, DepositInfoVec, FinalizedCustodianCapacity, NumberHash,
         NumberHashReader, Script, Transaction, TransactionKey, WithdrawalKey,
     },
     prelude::*,
 };
+use std::collections::HashSet;
 
 /// L2 block status on L1.
 pub enum BlockStatus {
@@ -250,6 +251,47 @@ pub trait ChainStore: KVStoreRead {
             .map(|slice| from_box_should_be_ok!(packed::TxReceiptReader, slice)))
     }
 
+    fn get_transaction_access_list(
+        &self,
+        tx_hash: &H256,
+    ) -> Result<Option<packed::TxAccessList>, Error> {
+        match self.get_transaction_info(tx_hash)? {
+            Some(tx_info) => self.get_transaction_access_list_by_key(&tx_info.key()),
+            None => Ok(None),
+        }
+    }
+
+    /// State keys accessed by transactions of the block
+    ///
+    /// The access list of a tx is recorded when the tx is executed in the mem pool, or when
+    /// the block was applied before.
+    fn get_block_access_keys(&self, block: &packed::L2Block) -> Result<Vec<H256>, Error> {
+        let block_hash = block.hash().pack();
+        let mut keys = HashSet::new();
+        for (index, tx) in block.transactions().into_iter().enumerate() {
+            let tx_key = TransactionKey::build_transaction_key(block_hash.clone(), index as u32);
+            let access_list = match self.get_transaction_access_list_by_key(&tx_key)? {
+                Some(access_list) => Some(access_list),
+                None => self.get_mem_pool_transaction_access_list(&tx.hash().into())?,
+            };
+            if let Some(access_list) = access_list {
+                let access_list = AccessList::from(access_list);
+                keys.extend(access_list.keys().cloned());
+            }
+        }
+        Ok(keys.into_iter().collect())
+    }
+
+    /// Access lists are only available for transactions executed by this node
+    fn get_transaction_access_list_by_key(
+        &self,
+        key: &TransactionKey,
+    ) -> Result<Option<packed::TxAccessList>, Error> {
+        Ok(self
+            .get(COLUMN_TRANSACTION_ACCESS_LIST, key.as_slice())
+            .map(|slice| from_box_should_be_ok!(packed::TxAccessListReader, slice)))
+    }
+
     fn get_withdrawal(
         &self,
         withdrawal_hash: &H256,
@@ -358,6 +400,15 @@ pub trait ChainStore: KVStoreRead {
             .map(|slice| from_box_should_be_ok!(packed::TxReceiptReader, slice)))
     }
 
+    fn get_mem_pool_transaction_access_list(
+        &self,
+        tx_hash: &H256,
+    ) -> Result<Option<packed::TxAccessList>, Error> {
+        Ok(self
+            .get(COLUMN_TRANSACTION_ACCESS_LIST, tx_hash.as_slice())
+            .map(|slice| from_box_should_be_ok!(packed::TxAccessListReader, slice)))
+    }
+
     fn get_mem_pool_withdrawal(
         &self,
         withdrawal_hash: &H256,
@@ -2,7 +2,7 @@ use anyhow::Error;
 use gw_common::H256;
 use gw_db::{
     schema::{COLUMN_BLOCK_STATE_RECORD, COLUMN_BLOCK_STATE_REVERSE_RECORD},
-    DBRawIterator, Direction, IteratorMode,
+    Direction, IteratorMode,
 };
 
 use crate::{
@@ -44,23 +44,22 @@ impl HistoryStateStore for &StoreTransaction {
 
     fn get_history_state(&self, block_number: u64, state_key: &H256) -> Option<H256> {
         let key = BlockStateRecordKeyReverse::new(block_number, state_key);
-        let mut raw_iter: DBRawIterator = self
-            .get_iter(COLUMN_BLOCK_STATE_REVERSE_RECORD, IteratorMode::Start)
-            .into();
-        raw_iter.seek_for_prev(key.as_slice());
-
-        if !raw_iter.valid() {
-            return None;
-        }
-        match raw_iter.key() {
-            Some(prev_key) => {
+        // seek for the last key less than or equal to the key
+        let prev = self
+            .get_iter(
+                COLUMN_BLOCK_STATE_REVERSE_RECORD,
+                IteratorMode::From(key.as_slice(), Direction::Reverse),
+            )
+            .next();
+        match prev {
+            Some((prev_key, _value)) => {
                 // not a some key
                 if &prev_key[..32] != key.state_key().as_slice() {
                     return None;
                 }
 
                 // get old value
-                let prev_reverse_key = BlockStateRecordKeyReverse::from_slice(prev_key);
+                let prev_reverse_key = BlockStateRecordKeyReverse::from_slice(&prev_key);
                 let prev_key = BlockStateRecordKey::new(
                     prev_reverse_key.block_number(),
                     &prev_reverse_key.state_key(),
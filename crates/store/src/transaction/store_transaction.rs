@@ -13,12 +13,16 @@ use gw_db::schema::{
     COLUMN_BLOCK_SUBMIT_TX_HASH, COLUMN_INDEX, COLUMN_MEM_POOL_TRANSACTION,
     COLUMN_MEM_POOL_TRANSACTION_RECEIPT, COLUMN_MEM_POOL_WITHDRAWAL, COLUMN_META,
     COLUMN_REVERTED_BLOCK_SMT_LEAF, COLUMN_REVERTED_BLOCK_SMT_ROOT, COLUMN_TRANSACTION,
-    COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT, COLUMN_WITHDRAWAL, COLUMN_WITHDRAWAL_INFO,
-    META_BLOCK_SMT_ROOT_KEY, META_CHAIN_ID_KEY, META_LAST_CONFIRMED_BLOCK_NUMBER_HASH_KEY,
-    META_LAST_SUBMITTED_BLOCK_NUMBER_HASH_KEY, META_LAST_VALID_TIP_BLOCK_HASH_KEY,
-    META_REVERTED_BLOCK_SMT_ROOT_KEY, META_TIP_BLOCK_HASH_KEY,
+    COLUMN_TRANSACTION_ACCESS_LIST, COLUMN_TRANSACTION_INFO, COLUMN_TRANSACTION_RECEIPT,
+    COLUMN_WITHDRAWAL, COLUMN_WITHDRAWAL_INFO, META_BLOCK_SMT_ROOT_KEY, META_CHAIN_ID_KEY,
+    META_LAST_CONFIRMED_BLOCK_NUMBER_HASH_KEY, META_LAST_SUBMITTED_BLOCK_NUMBER_HASH_KEY,
+    META_LAST_VALID_TIP_BLOCK_HASH_KEY, META_REVERTED_BLOCK_SMT_ROOT_KEY, META_TIP_BLOCK_HASH_KEY,
+};
+use gw_db::{
+    engine::{EngineIter, EngineTransaction},
+    error::Error,
+    IteratorMode,
 };
-use gw_db::{error::Error, iter::DBIter, DBIterator, IteratorMode, RocksDBTransaction};
 use gw_types::packed::NumberHash;
 use gw_types::{
     from_box_should_be_ok,
@@ -31,7 +35,7 @@ use gw_types::{
 use std::collections::HashSet;
 
 pub struct StoreTransaction {
-    pub(crate) inner: RocksDBTransaction,
+    pub(crate) inner: Box<dyn EngineTransaction>,
 }
 
 impl KVStoreRead for &StoreTransaction {
@@ -39,7 +43,7 @@ impl KVStoreRead for &StoreTransaction {
         self.inner
             .get(col, key)
             .expect("db operation should be ok")
-            .map(|v| Box::<[u8]>::from(v.as_ref()))
+            .map(Box::from)
     }
 }
 
@@ -64,7 +68,7 @@ impl StoreTransaction {
         self.inner.rollback()
     }
 
-    pub(crate) fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
+    pub(crate) fn get_iter(&self, col: Col, mode: IteratorMode) -> EngineIter<'_> {
         self.inner
             .iter(col, mode)
             .expect("db operation should be ok")
@@ -129,10 +133,12 @@ impl StoreTransaction {
         withdrawal_receipts: Vec<WithdrawalReceipt>,
         prev_txs_state: AccountMerkleState,
         tx_receipts: Vec<packed::TxReceipt>,
+        tx_access_lists: Vec<packed::TxAccessList>,
         deposit_info_vec: packed::DepositInfoVec,
         withdrawals: Vec<packed::WithdrawalRequestExtra>,
     ) -> Result<(), Error> {
         debug_assert_eq!(block.transactions().len(), tx_receipts.len());
+        debug_assert_eq!(block.transactions().len(), tx_access_lists.len());
         debug_assert_eq!(block.withdrawals().len(), withdrawals.len());
         let block_hash = block.hash();
         self.insert_raw(COLUMN_BLOCK, &block_hash, block.as_slice())?;
@@ -176,10 +182,11 @@ impl StoreTransaction {
             }
         }
 
-        for (index, (tx, tx_receipt)) in block
+        for (index, ((tx, tx_receipt), access_list)) in block
             .transactions()
             .into_iter()
             .zip(tx_receipts.iter())
+            .zip(tx_access_lists.into_iter())
             .enumerate()
         {
             let key = TransactionKey::build_transaction_key(block_hash.pack(), index as u32);
@@ -189,6 +196,11 @@ impl StoreTransaction {
                 key.as_slice(),
                 tx_receipt.as_slice(),
             )?;
+            self.insert_raw(
+                COLUMN_TRANSACTION_ACCESS_LIST,
+                key.as_slice(),
+                access_list.as_slice(),
+            )?;
         }
         for (index, withdrawal) in withdrawals.into_iter().enumerate() {
             let key = WithdrawalKey::build_withdrawal_key(block_hash.pack(), index as u32);
@@ -503,6 +515,11 @@ impl StoreTransaction {
             let tx_hash = tx.hash();
             self.delete(COLUMN_TRANSACTION_INFO, &tx_hash)?;
         }
+        // access lists are recorded again if the block is re-attached
+        for index in 0..block.transactions().len() {
+            let key = TransactionKey::build_transaction_key(block.hash().pack(), index as u32);
+            self.delete(COLUMN_TRANSACTION_ACCESS_LIST, key.as_slice())?;
+        }
         // withdrawal info
         for withdrawal in block.withdrawals() {
             let withdrawal_hash = withdrawal.hash();
@@ -598,18 +615,27 @@ impl StoreTransaction {
     pub fn remove_mem_pool_transaction(&self, tx_hash: &H256) -> Result<(), Error> {
         self.delete(COLUMN_MEM_POOL_TRANSACTION, tx_hash.as_slice())?;
         self.delete(COLUMN_MEM_POOL_TRANSACTION_RECEIPT, tx_hash.as_slice())?;
+        self.delete(COLUMN_TRANSACTION_ACCESS_LIST, tx_hash.as_slice())?;
         Ok(())
     }
 
+    /// The access list is kept until the tx is removed from the mem pool, so it can be used to
+    /// prefetch state when the block containing the tx is applied.
     pub fn insert_mem_pool_transaction_receipt(
         &self,
         tx_hash: &H256,
         tx_receipt: packed::TxReceipt,
+        access_list: packed::TxAccessList,
     ) -> Result<(), Error> {
         self.insert_raw(
             COLUMN_MEM_POOL_TRANSACTION_RECEIPT,
             tx_hash.as_slice(),
             tx_receipt.as_slice(),
+        )?;
+        self.insert_raw(
+            COLUMN_TRANSACTION_ACCESS_LIST,
+            tx_hash.as_slice(),
+            access_list.as_slice(),
         )
     }
 
@@ -1,7 +1,7 @@
-use gw_db::{error::Error, schema::Col, RocksDBWriteBatch};
+use gw_db::{engine::EngineWriteBatch, error::Error, schema::Col};
 
 pub struct StoreWriteBatch {
-    pub(crate) inner: RocksDBWriteBatch,
+    pub(crate) inner: EngineWriteBatch,
 }
 
 impl StoreWriteBatch {
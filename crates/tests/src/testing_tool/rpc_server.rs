@@ -9,6 +9,7 @@ use gw_config::{NodeMode::FullNode, RPCClientConfig, RPCMethods};
 
 use gw_jsonrpc_types::{
     ckb_jsonrpc_types::{Byte32, JsonBytes, Uint64},
+    debugger::ExecutionTrace,
     godwoken::RunResult,
 };
 use gw_polyjuice_sender_recover::recover::PolyjuiceSenderRecover;
@@ -69,13 +70,14 @@ impl RPCServer {
             mem_pool,
             generator,
             tests_rpc_impl: None,
+            backend_upgrade_rpc_impl: None,
             rollup_config,
             mem_pool_config: Default::default(),
             node_mode: FullNode,
             rpc_client,
             send_tx_rate_limit: Default::default(),
             server_config: gw_config::RPCServerConfig {
-                enable_methods: HashSet::from_iter(vec![RPCMethods::Test]),
+                enable_methods: HashSet::from_iter(vec![RPCMethods::Test, RPCMethods::Debug]),
                 ..Default::default()
             },
             chain_config: Default::default(),
@@ -168,6 +170,22 @@ impl RPCServer {
         Ok(run_result)
     }
 
+    pub async fn debug_trace_l2transaction(&self, tx: &L2Transaction) -> Result<ExecutionTrace> {
+        let params = {
+            let bytes = JsonBytes::from_bytes(tx.as_bytes());
+            serde_json::to_value(&(bytes,))?
+        };
+
+        let req = RequestBuilder::default()
+            .with_id(1)
+            .with_method("gw_debug_trace_l2transaction")
+            .with_params(params)
+            .finish();
+
+        let trace = self.handle_single_request(req).await?;
+        Ok(trace)
+    }
+
     pub async fn is_request_in_queue(&self, hash: H256) -> Result<bool> {
         let fixed_hash = ckb_fixed_hash::H256(hash.into());
         let params = serde_json::to_value(&(fixed_hash,))?;
@@ -0,0 +1,40 @@
+use crate::testing_tool::chain::{produce_empty_block, setup_chain};
+
+use godwoken_bin::subcommand::backup::restore;
+use gw_common::H256;
+use gw_store::{traits::chain_store::ChainStore, Store};
+use gw_types::{packed::Script, prelude::*};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_backup_and_restore() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut chain = setup_chain(Script::default()).await;
+    for _ in 0..3 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    chain.store().check_tip_global_state().unwrap();
+
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("test_backup_and_restore")
+        .tempdir()
+        .unwrap();
+    let checkpoint_path = tmp_dir.path().join("checkpoint");
+    chain.store().create_checkpoint(&checkpoint_path).unwrap();
+
+    // blocks after the checkpoint aren't included
+    let checkpoint_tip = chain.store().get_tip_block().unwrap();
+    produce_empty_block(&mut chain).await.unwrap();
+
+    let store_path = tmp_dir.path().join("store");
+    let tip_number = restore(&checkpoint_path, &store_path).unwrap();
+    assert_eq!(tip_number, checkpoint_tip.raw().number().unpack());
+
+    let restored = Store::open_checkpoint(&store_path).unwrap();
+    let checkpoint_tip_hash: H256 = checkpoint_tip.hash().into();
+    assert_eq!(restored.get_tip_block_hash().unwrap(), checkpoint_tip_hash);
+    drop(restored);
+
+    // refuse to overwrite an existing database
+    assert!(restore(&checkpoint_path, &store_path).is_err());
+}
@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+
+use crate::testing_tool::chain::{chain_generator, produce_empty_block, setup_chain};
+
+use gw_chain::chain::Chain;
+use gw_common::{h256_ext::H256Ext, H256};
+use gw_config::ChainConfig;
+use gw_db::schema::{
+    COLUMN_BLOCK_SUBMIT_TX_HASH, COLUMN_DATA, COLUMN_INDEX, COLUMN_TRANSACTION_ACCESS_LIST,
+};
+use gw_store::{
+    integrity::{check_integrity, repair, Inconsistency},
+    traits::{chain_store::ChainStore, kv_store::KVStoreWrite},
+};
+use gw_types::{
+    packed::{Script, TxAccessList},
+    prelude::*,
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_db_check_and_repair() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut chain = setup_chain(Script::default()).await;
+    for _ in 0..3 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let store = chain.store();
+    let report = check_integrity(store).unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert_eq!(report.last_valid_tip_block_number, 3);
+
+    // corrupt the index of block #2, leave a record after the tip, an access list of a tx
+    // which isn't in the mem pool and data with a wrong hash
+    let block_2_hash = store.get_block_hash_by_number(2).unwrap().unwrap();
+    let block_3_hash = store.get_block_hash_by_number(3).unwrap().unwrap();
+    let dangling_key = 100u64.to_be_bytes();
+    {
+        let db = &store.begin_transaction();
+        db.delete(COLUMN_INDEX, block_2_hash.as_slice()).unwrap();
+        db.insert_raw(
+            COLUMN_BLOCK_SUBMIT_TX_HASH,
+            &dangling_key,
+            H256::one().as_slice(),
+        )
+        .unwrap();
+        db.insert_raw(
+            COLUMN_TRANSACTION_ACCESS_LIST,
+            H256::one().as_slice(),
+            TxAccessList::default().as_slice(),
+        )
+        .unwrap();
+        db.insert_raw(COLUMN_DATA, H256::one().as_slice(), b"data")
+            .unwrap();
+        db.commit().unwrap();
+    }
+
+    let report = check_integrity(store).unwrap();
+    assert_eq!(report.first_bad_block(), Some(2));
+    assert!(report.inconsistencies.contains(&Inconsistency::Dangling {
+        col: COLUMN_BLOCK_SUBMIT_TX_HASH,
+        key: dangling_key.to_vec(),
+        reason: "block is after the tip".to_string(),
+    }));
+    assert!(report.inconsistencies.contains(&Inconsistency::Dangling {
+        col: COLUMN_TRANSACTION_ACCESS_LIST,
+        key: H256::one().as_slice().to_vec(),
+        reason: "transaction isn't in mem pool".to_string(),
+    }));
+    assert!(report.inconsistencies.contains(&Inconsistency::Dangling {
+        col: COLUMN_DATA,
+        key: H256::one().as_slice().to_vec(),
+        reason: "data hash mismatch".to_string(),
+    }));
+
+    // blocks #2 and #3 are detached and replayed from their stored bodies
+    let mut replayed = Vec::new();
+    let mut replay_chain: Option<Chain> = None;
+    let report = repair(store, |exported| {
+        if replay_chain.is_none() {
+            let rollup_config = chain.generator().rollup_context().rollup_config.to_owned();
+            replay_chain = Some(Chain::create(
+                &rollup_config,
+                &Script::default(),
+                &ChainConfig::default(),
+                store.clone(),
+                chain_generator(&chain, Script::default()),
+                None,
+            )?);
+        }
+        let replay_chain = replay_chain.as_mut().expect("chain");
+        replayed.push(exported.block_number());
+        let db = &replay_chain.store().begin_transaction();
+        let challenge_target = replay_chain.process_block(
+            db,
+            exported.block,
+            exported.post_global_state,
+            exported.deposit_info_vec,
+            exported
+                .deposit_asset_scripts
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            exported.withdrawals,
+        )?;
+        assert!(challenge_target.is_none());
+        db.commit()?;
+        Ok(())
+    })
+    .unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+    assert_eq!(replayed, vec![2, 3]);
+    assert_eq!(report.last_valid_tip_block_number, 3);
+    assert_eq!(
+        store.get_block_hash_by_number(2).unwrap(),
+        Some(block_2_hash)
+    );
+    assert_eq!(
+        store.get_block_hash_by_number(3).unwrap(),
+        Some(block_3_hash)
+    );
+    store.check_tip_global_state().unwrap();
+}
@@ -0,0 +1,136 @@
+use std::fs;
+
+use crate::testing_tool::chain::{
+    produce_empty_block, setup_chain_with_account_lock_manage, ALWAYS_SUCCESS_CODE_HASH,
+};
+
+use godwoken_bin::subcommand::{
+    export_segments::ExportSegments,
+    import_segments::{ImportSegments, SegmentSource},
+};
+use gw_chain::chain::Chain;
+use gw_config::StoreConfig;
+use gw_db::{read_only_db::ReadOnlyDB, schema::COLUMNS, RocksDB};
+use gw_generator::account_lock_manage::{always_success::AlwaysSuccess, AccountLockManage};
+use gw_store::{readonly::StoreReadonly, traits::chain_store::ChainStore, Store};
+use gw_types::{
+    core::AllowedEoaType,
+    packed::{AllowedTypeHash, RollupConfig, Script},
+    prelude::*,
+};
+use gw_utils::export_segment::SegmentManifest;
+
+const SEGMENT_BLOCKS: u64 = 2;
+
+async fn setup_chain(store: Store) -> Chain {
+    let rollup_config = RollupConfig::new_builder()
+        .allowed_eoa_type_hashes(
+            vec![AllowedTypeHash::new(
+                AllowedEoaType::Eth,
+                *ALWAYS_SUCCESS_CODE_HASH,
+            )]
+            .pack(),
+        )
+        .build();
+    let mut account_lock_manage = AccountLockManage::default();
+    account_lock_manage
+        .register_lock_algorithm((*ALWAYS_SUCCESS_CODE_HASH).into(), Box::new(AlwaysSuccess));
+    let chain = setup_chain_with_account_lock_manage(
+        Script::default(),
+        rollup_config,
+        account_lock_manage,
+        Some(store),
+        None,
+        None,
+    )
+    .await;
+    chain.notify_new_tip().await.unwrap();
+    chain
+}
+
+fn open_rocksdb_store(path: &std::path::Path) -> Store {
+    let config = StoreConfig {
+        path: path.to_path_buf(),
+        ..Default::default()
+    };
+    Store::new(RocksDB::open(&config, COLUMNS))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_export_import_segments() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let store_dir = tempfile::tempdir().expect("create temp dir");
+    let mut chain = setup_chain(open_rocksdb_store(store_dir.path())).await;
+    let rollup_type_hash = chain.generator().rollup_context().rollup_script_hash;
+    let export = |to_block: u64, output_dir: &std::path::Path| {
+        let snap = {
+            let cf_names = (0..COLUMNS).map(|c| c.to_string());
+            let db = ReadOnlyDB::open_cf(store_dir.path(), cf_names)
+                .unwrap()
+                .unwrap();
+            StoreReadonly::new(db)
+        };
+        ExportSegments::new_unchecked(
+            snap,
+            output_dir.to_path_buf(),
+            rollup_type_hash,
+            to_block,
+            SEGMENT_BLOCKS,
+        )
+        .execute()
+        .unwrap();
+    };
+
+    // Export block #0 to #2, then resume the export to block #4
+    for _ in 0..4 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let export_dir = tempfile::tempdir().expect("create temp dir");
+    export(2, export_dir.path());
+    let manifest = SegmentManifest::load(export_dir.path()).unwrap().unwrap();
+    assert_eq!(manifest.next_block_number(), Some(3));
+    export(4, export_dir.path());
+    let manifest = SegmentManifest::load(export_dir.path()).unwrap().unwrap();
+    let ranges: Vec<_> = manifest
+        .segments
+        .iter()
+        .map(|s| (s.from_block, s.to_block))
+        .collect();
+    assert_eq!(ranges, vec![(0, 1), (2, 2), (3, 4)]);
+
+    // A mirror with a corrupted segment, the segment should be fetched from the other source
+    let mirror_dir = tempfile::tempdir().expect("create temp dir");
+    for entry in fs::read_dir(export_dir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        fs::copy(&path, mirror_dir.path().join(path.file_name().unwrap())).unwrap();
+    }
+    let corrupted_path = mirror_dir.path().join(&manifest.segments[2].file_name);
+    let mut corrupted = fs::read(&corrupted_path).unwrap();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 1;
+    fs::write(&corrupted_path, corrupted).unwrap();
+
+    let import_store_dir = tempfile::tempdir().expect("create temp dir");
+    let import_chain = setup_chain(open_rocksdb_store(import_store_dir.path())).await;
+    let sources = vec![
+        SegmentSource::Dir(mirror_dir.path().to_path_buf()),
+        SegmentSource::Dir(export_dir.path().to_path_buf()),
+    ];
+    let import = ImportSegments::new_unchecked(import_chain, sources);
+    let import_store = import.store().clone();
+    import.execute().await.unwrap();
+
+    let store = chain.store();
+    assert_eq!(
+        import_store.get_tip_block_hash().unwrap(),
+        store.get_tip_block_hash().unwrap()
+    );
+    let tip_block_number: u64 = import_store
+        .get_tip_block()
+        .unwrap()
+        .raw()
+        .number()
+        .unpack();
+    assert_eq!(tip_block_number, 4);
+}
@@ -0,0 +1,137 @@
+use std::fs::File;
+use std::path::Path;
+
+use crate::testing_tool::chain::{
+    produce_empty_block, setup_chain_with_account_lock_manage, ALWAYS_SUCCESS_CODE_HASH,
+};
+
+use godwoken_bin::subcommand::export_parquet::{db_blocks, ParquetExporter};
+use gw_config::StoreConfig;
+use gw_db::{read_only_db::ReadOnlyDB, schema::COLUMNS, RocksDB};
+use gw_generator::account_lock_manage::{always_success::AlwaysSuccess, AccountLockManage};
+use gw_store::{readonly::StoreReadonly, traits::chain_store::ChainStore, Store};
+use gw_types::{
+    core::AllowedEoaType,
+    packed::{AllowedTypeHash, RollupConfig, Script},
+    prelude::*,
+};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+
+const PARTITION_BLOCKS: u64 = 3;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_export_parquet() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let store_dir = tempfile::tempdir().expect("create temp dir");
+    let store = {
+        let config = StoreConfig {
+            path: store_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        Store::new(RocksDB::open(&config, COLUMNS))
+    };
+    let rollup_config = RollupConfig::new_builder()
+        .allowed_eoa_type_hashes(
+            vec![AllowedTypeHash::new(
+                AllowedEoaType::Eth,
+                *ALWAYS_SUCCESS_CODE_HASH,
+            )]
+            .pack(),
+        )
+        .build();
+    let mut chain = {
+        let mut account_lock_manage = AccountLockManage::default();
+        account_lock_manage
+            .register_lock_algorithm((*ALWAYS_SUCCESS_CODE_HASH).into(), Box::new(AlwaysSuccess));
+        setup_chain_with_account_lock_manage(
+            Script::default(),
+            rollup_config,
+            account_lock_manage,
+            Some(store),
+            None,
+            None,
+        )
+        .await
+    };
+    chain.notify_new_tip().await.unwrap();
+
+    let output_dir = tempfile::tempdir().expect("create temp dir");
+    let exporter = ParquetExporter::new(output_dir.path(), PARTITION_BLOCKS).unwrap();
+    assert_eq!(exporter.last_exported_block().unwrap(), None);
+
+    // Export block #0 to #4
+    for _ in 0..4 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let exported = export_to_tip(&exporter, store_dir.path());
+    assert_eq!(exported, Some((0, 4)));
+    assert_eq!(exporter.last_exported_block().unwrap(), Some(4));
+    assert_eq!(count_rows(output_dir.path(), "blocks", "0_2", "0_2"), 3);
+    assert_eq!(count_rows(output_dir.path(), "blocks", "3_5", "3_4"), 2);
+    assert_eq!(
+        count_rows(output_dir.path(), "transactions", "0_2", "0_2"),
+        0
+    );
+    assert_eq!(count_rows(output_dir.path(), "logs", "3_5", "3_4"), 0);
+
+    // Nothing to append
+    assert_eq!(export_to_tip(&exporter, store_dir.path()), None);
+
+    // Append block #5 to #7, the unfinished partition is continued by a new file
+    for _ in 0..3 {
+        produce_empty_block(&mut chain).await.unwrap();
+    }
+    let exported = export_to_tip(&exporter, store_dir.path());
+    assert_eq!(exported, Some((5, 7)));
+    assert_eq!(exporter.last_exported_block().unwrap(), Some(7));
+    assert_eq!(count_rows(output_dir.path(), "blocks", "3_5", "5_5"), 1);
+    assert_eq!(count_rows(output_dir.path(), "blocks", "6_8", "6_7"), 2);
+    assert_eq!(
+        count_rows(output_dir.path(), "withdrawals", "6_8", "6_7"),
+        0
+    );
+
+    // Reject a block which doesn't follow the last exported block
+    produce_empty_block(&mut chain).await.unwrap();
+    let snap = open_snapshot(store_dir.path());
+    let mut block = db_blocks(&snap, 8, 8).next().unwrap().unwrap();
+    let raw = block
+        .exported
+        .block
+        .raw()
+        .as_builder()
+        .parent_block_hash([1u8; 32].pack())
+        .build();
+    block.exported.block = block.exported.block.as_builder().raw(raw).build();
+    let err = exporter
+        .export(std::iter::once(Ok(block)), |_| ())
+        .unwrap_err();
+    assert!(err.to_string().contains("reorganized"));
+    assert_eq!(exporter.last_exported_block().unwrap(), Some(7));
+    assert_eq!(export_to_tip(&exporter, store_dir.path()), Some((8, 8)));
+}
+
+fn open_snapshot(store_path: &Path) -> StoreReadonly {
+    let cf_names = (0..COLUMNS).map(|c| c.to_string());
+    let db = ReadOnlyDB::open_cf(store_path, cf_names).unwrap().unwrap();
+    StoreReadonly::new(db)
+}
+
+fn export_to_tip(exporter: &ParquetExporter, store_path: &Path) -> Option<(u64, u64)> {
+    let snap = open_snapshot(store_path);
+    let tip_block_number = snap.get_tip_block().unwrap().raw().number().unpack();
+    let from_block = exporter.last_exported_block().unwrap().map_or(0, |n| n + 1);
+    exporter
+        .export(db_blocks(&snap, from_block, tip_block_number), |_| ())
+        .unwrap()
+}
+
+fn count_rows(output_dir: &Path, table: &str, partition: &str, blocks: &str) -> i64 {
+    let path = output_dir
+        .join(table)
+        .join(format!("block_range={}", partition))
+        .join(format!("{}.parquet", blocks));
+    let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+    reader.metadata().file_metadata().num_rows()
+}
@@ -1,10 +1,16 @@
+mod backup;
 mod chain;
+mod db_check;
 mod deposit_withdrawal;
 mod export_import_block;
+mod export_import_segments;
+mod export_parquet;
 mod mem_block_repackage;
+mod parallel_execution;
 mod meta_contract_args;
 mod polyjuice_sender_recover;
 mod restore_mem_block;
 mod restore_mem_pool_pending_withdrawal;
 mod rpc_server;
+mod sponsored_tx;
 mod unlock_withdrawal_to_owner;
@@ -0,0 +1,152 @@
+use ckb_types::prelude::{Builder, Entity};
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, registry_address::RegistryAddress, H256};
+use gw_config::{MemBlockConfig, MemPoolConfig, ParallelExecutionConfig};
+use gw_generator::traits::StateExt;
+use gw_store::{state::traits::JournalDB, traits::chain_store::ChainStore};
+use gw_types::{
+    bytes::Bytes,
+    packed::{L2Transaction, RawL2Transaction, Script},
+    prelude::{Pack, Unpack},
+    U256,
+};
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount, PolyjuiceSystemLog},
+};
+
+const WALLETS_COUNT: usize = 8;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_parallel_execution_state_root() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_script_hash: H256 = Script::default().hash().into();
+    let wallets: Vec<_> = (0..WALLETS_COUNT)
+        .map(|_| EthWallet::random(rollup_script_hash))
+        .collect();
+
+    let (serial_post_states, serial_root) = execute_mixed_batch(None, &wallets).await;
+    let parallel_execution = ParallelExecutionConfig { workers: 4 };
+    let (parallel_post_states, parallel_root) =
+        execute_mixed_batch(Some(parallel_execution), &wallets).await;
+
+    assert_eq!(serial_post_states, parallel_post_states);
+    assert_eq!(serial_root, parallel_root);
+}
+
+/// Push a batch of independent and conflicting txs, return post states of the txs and the
+/// merkle state of the mem pool
+async fn execute_mixed_batch(
+    parallel_execution: Option<ParallelExecutionConfig>,
+    wallets: &[EthWallet],
+) -> (Vec<Bytes>, Bytes) {
+    let mem_pool_config = MemPoolConfig {
+        mem_block: MemBlockConfig {
+            parallel_execution,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut chain = {
+        let chain = TestChain::setup(Script::default()).await;
+        chain.update_mem_pool_config(mem_pool_config).await
+    };
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+    let block_producer = {
+        let block_producer: Bytes = chain.last_valid_block().raw().block_producer().unpack();
+        RegistryAddress::from_slice(&block_producer).unwrap()
+    };
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+    let account_ids: Vec<u32> = wallets
+        .iter()
+        .map(|wallet| {
+            wallet
+                .create_account(&mut state, 1000000u128.into())
+                .unwrap()
+        })
+        .collect();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    // Deploy erc20 proxy of CKB
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(account_ids[0].pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = wallets[0].sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let deploy_tx_hash: H256 = deploy_tx.hash().into();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    chain.mem_pool().await.push_transaction(deploy_tx).unwrap();
+
+    let system_log = PolyjuiceSystemLog::parse_from_tx_hash(&chain, deploy_tx_hash).unwrap();
+    assert_eq!(system_log.status_code, 0);
+    let state = mem_pool_state.load_state_db();
+    let erc20_id = system_log.contract_account_id(&state).unwrap();
+
+    let build_tx = |from: usize, nonce: u32, to_id: u32, args: Vec<u8>| -> L2Transaction {
+        let raw_tx = RawL2Transaction::new_builder()
+            .chain_id(chain.chain_id().pack())
+            .from_id(account_ids[from].pack())
+            .to_id(to_id.pack())
+            .nonce(nonce.pack())
+            .args(args.pack())
+            .build();
+        wallets[from].sign_polyjuice_tx(&state, raw_tx).unwrap()
+    };
+    let transfer = |from: usize, nonce: u32, to: &RegistryAddress| -> L2Transaction {
+        let args = SudtErc20ArgsBuilder::transfer(to, U256::from(100u32)).finish();
+        build_tx(from, nonce, erc20_id, args)
+    };
+
+    let txs = vec![
+        // independent transfers
+        transfer(1, 0, wallets[2].reg_address()),
+        transfer(3, 0, wallets[4].reg_address()),
+        // reads the balance changed by the first tx
+        transfer(2, 0, wallets[5].reg_address()),
+        // same receiver
+        transfer(6, 0, wallets[7].reg_address()),
+        transfer(5, 0, wallets[7].reg_address()),
+        // accesses the block producer balance more than once
+        transfer(4, 0, &block_producer),
+        // creates an account
+        build_tx(7, 0, polyjuice_account.id, deploy_args.clone()),
+        transfer(0, 1, wallets[1].reg_address()),
+    ];
+
+    let mut mem_pool = chain.mem_pool().await;
+    mem_pool.speculate_transactions(&txs);
+    let mut post_states = Vec::with_capacity(txs.len());
+    for tx in txs {
+        let tx_hash: H256 = tx.hash().into();
+        mem_pool.push_transaction(tx).unwrap();
+
+        let receipt = chain
+            .store()
+            .get_mem_pool_transaction_receipt(&tx_hash)
+            .unwrap()
+            .unwrap();
+        let system_log = PolyjuiceSystemLog::parse_logs(receipt.logs()).unwrap();
+        assert_eq!(system_log.status_code, 0);
+        post_states.push(receipt.post_state().as_bytes());
+    }
+    drop(mem_pool);
+
+    let merkle_state = mem_pool_state
+        .load_state_db()
+        .calculate_merkle_state()
+        .unwrap();
+    (post_states, merkle_state.as_bytes())
+}
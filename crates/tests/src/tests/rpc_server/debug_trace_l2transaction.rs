@@ -0,0 +1,81 @@
+use gw_common::{builtins::CKB_SUDT_ACCOUNT_ID, state::State};
+use gw_jsonrpc_types::debugger::SyscallOp;
+use gw_store::state::traits::JournalDB;
+use gw_types::{
+    packed::{RawL2Transaction, Script},
+    prelude::{Builder, Entity, Pack},
+};
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount},
+    rpc_server::RPCServer,
+};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_trace_polyjuice_deploy_tx() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let mut chain = TestChain::setup(rollup_type_script).await;
+    let rpc_server = RPCServer::build(&chain, None).await.unwrap();
+
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let test_wallet = EthWallet::random(chain.rollup_type_hash());
+    let test_account_id = test_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(test_account_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let deploy_tx = test_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+
+    state.finalise().unwrap();
+    mem_pool_state.store_state_db(state);
+    let trace = rpc_server
+        .debug_trace_l2transaction(&deploy_tx)
+        .await
+        .unwrap();
+
+    assert!(trace.error.is_none());
+    assert_eq!(u32::from(trace.exit_code), 0);
+    assert_eq!(trace.tx_hash.0, deploy_tx.hash());
+
+    let ops: Vec<_> = trace
+        .syscalls
+        .iter()
+        .filter_map(|s| s.op.as_ref())
+        .collect();
+    let created = ops.iter().any(|op| {
+        matches!(
+            op,
+            SyscallOp::Create {
+                account_id: Some(_),
+                ..
+            }
+        )
+    });
+    assert!(created, "contract account should be created");
+    assert!(ops.iter().any(|op| matches!(op, SyscallOp::Store { .. })));
+    assert!(ops.iter().any(|op| matches!(op, SyscallOp::Log { .. })));
+
+    // The trace doesn't change the mem pool state
+    let state = mem_pool_state.load_state_db();
+    assert_eq!(state.get_nonce(test_account_id).unwrap(), 0);
+}
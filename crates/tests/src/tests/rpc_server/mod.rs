@@ -1,5 +1,6 @@
 pub(crate) const BLOCK_MAX_CYCLES_LIMIT: u64 = 300_0000;
 
+pub mod debug_trace_l2transaction;
 pub mod execute_l2transaction;
 pub mod execute_raw_l2transaction;
 pub mod submit_l2transaction;
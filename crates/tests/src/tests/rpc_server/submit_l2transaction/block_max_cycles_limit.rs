@@ -371,6 +371,14 @@ async fn test_block_max_cycles_limit() {
                 sys_bn_per_pairing_cycles: MAX_CYCLES_LIMIT,
                 sys_revert_cycles: MAX_CYCLES_LIMIT,
                 sys_snapshot_cycles: MAX_CYCLES_LIMIT,
+                sys_keccak256_cycles: MAX_CYCLES_LIMIT,
+                sys_keccak256_per_word_cycles: MAX_CYCLES_LIMIT,
+                sys_sha256_cycles: MAX_CYCLES_LIMIT,
+                sys_sha256_per_word_cycles: MAX_CYCLES_LIMIT,
+                sys_ripemd160_cycles: MAX_CYCLES_LIMIT,
+                sys_ripemd160_per_word_cycles: MAX_CYCLES_LIMIT,
+                sys_blake2f_per_round_cycles: MAX_CYCLES_LIMIT,
+                sys_secp256k1_recover_cycles: MAX_CYCLES_LIMIT,
             },
             ..Default::default()
         },
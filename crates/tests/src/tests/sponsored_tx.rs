@@ -0,0 +1,210 @@
+use ckb_types::prelude::{Builder, Entity};
+use gw_common::{
+    builtins::CKB_SUDT_ACCOUNT_ID, registry_address::RegistryAddress, state::State, H256,
+};
+use gw_generator::{
+    constants::L2TX_MAX_CYCLES,
+    error::{LockAlgorithmError, TransactionValidateError},
+};
+use gw_store::{chain_view::ChainView, traits::chain_store::ChainStore};
+use gw_types::{
+    bytes::Bytes,
+    offchain::SponsoredSignature,
+    packed::{BlockInfo, L2Transaction, RawL2Transaction, Script},
+    prelude::{Pack, Unpack},
+    U256,
+};
+
+use crate::testing_tool::{
+    chain::TestChain,
+    eth_wallet::EthWallet,
+    polyjuice::{erc20::SudtErc20ArgsBuilder, PolyjuiceAccount},
+};
+
+const SPONSOR_FEE: u128 = 1000;
+
+fn sponsor_tx(
+    rollup_script_hash: &H256,
+    tx: &L2Transaction,
+    sponsor_wallet: &EthWallet,
+    sponsor_id: u32,
+    fee: u128,
+) -> L2Transaction {
+    let mut sponsored = SponsoredSignature {
+        sponsor_id,
+        fee,
+        sender_signature: tx.signature().unpack(),
+        sponsor_signature: Bytes::default(),
+    };
+    let message = sponsored.signing_message(rollup_script_hash, &tx.raw());
+    let sig = sponsor_wallet.sign_message(message.into()).unwrap();
+    sponsored.sponsor_signature = Bytes::copy_from_slice(&sig);
+
+    tx.clone()
+        .as_builder()
+        .signature(sponsored.as_bytes().pack())
+        .build()
+}
+
+fn balance_of(state: &impl State, address: &RegistryAddress) -> U256 {
+    state
+        .get_sudt_balance(CKB_SUDT_ACCOUNT_ID, address)
+        .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sponsored_tx_pay_fee() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let mut chain = TestChain::setup(rollup_type_script).await;
+    chain
+        .produce_block(Default::default(), vec![])
+        .await
+        .unwrap();
+    let block_producer: Bytes = chain.last_valid_block().raw().block_producer().unpack();
+    let block_producer = RegistryAddress::from_slice(&block_producer).unwrap();
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let sender_wallet = EthWallet::random(chain.rollup_type_hash());
+    let sender_id = sender_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let sponsor_wallet = EthWallet::random(chain.rollup_type_hash());
+    let sponsor_id = sponsor_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(sender_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let tx = sender_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let tx = sponsor_tx(
+        &chain.rollup_type_hash(),
+        &tx,
+        &sponsor_wallet,
+        sponsor_id,
+        SPONSOR_FEE,
+    );
+
+    let generator = chain.inner.generator();
+    generator.check_transaction_signature(&state, &tx).unwrap();
+
+    let sender_balance = balance_of(&state, sender_wallet.reg_address());
+    let sponsor_balance = balance_of(&state, sponsor_wallet.reg_address());
+    let block_producer_balance = balance_of(&state, &block_producer);
+
+    let block_info = BlockInfo::new_builder()
+        .block_producer(Bytes::from(block_producer.to_bytes()).pack())
+        .number(2u64.pack())
+        .timestamp(0u64.pack())
+        .build();
+    let db = &chain.store().begin_transaction();
+    let tip_block_hash = db.get_last_valid_tip_block_hash().unwrap();
+    let chain_view = ChainView::new(&db, tip_block_hash);
+    let run_result = generator
+        .execute_l2transaction(
+            &chain_view,
+            &mut state,
+            &block_info,
+            &tx,
+            L2TX_MAX_CYCLES,
+            None,
+        )
+        .unwrap();
+    assert_eq!(run_result.exit_code, 0);
+
+    // sponsor pays exactly the sponsor fee, the sender still pays the gas of polyjuice
+    let sender_paid = sender_balance - balance_of(&state, sender_wallet.reg_address());
+    let sponsor_paid = sponsor_balance - balance_of(&state, sponsor_wallet.reg_address());
+    let block_producer_received = balance_of(&state, &block_producer) - block_producer_balance;
+    assert_eq!(sponsor_paid, SPONSOR_FEE.into());
+    assert_eq!(block_producer_received, sponsor_paid + sender_paid);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_sponsored_tx_invalid_sponsor_signature() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let rollup_type_script = Script::default();
+    let chain = TestChain::setup(rollup_type_script).await;
+
+    let mem_pool_state = chain.mem_pool_state().await;
+    let mut state = mem_pool_state.load_state_db();
+
+    let sender_wallet = EthWallet::random(chain.rollup_type_hash());
+    let sender_id = sender_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let sponsor_wallet = EthWallet::random(chain.rollup_type_hash());
+    let sponsor_id = sponsor_wallet
+        .create_account(&mut state, 1000000u128.into())
+        .unwrap();
+    let polyjuice_account = PolyjuiceAccount::create(chain.rollup_type_hash(), &mut state).unwrap();
+
+    let deploy_args = SudtErc20ArgsBuilder::deploy(CKB_SUDT_ACCOUNT_ID, 18).finish();
+    let raw_tx = RawL2Transaction::new_builder()
+        .chain_id(chain.chain_id().pack())
+        .from_id(sender_id.pack())
+        .to_id(polyjuice_account.id.pack())
+        .nonce(0u32.pack())
+        .args(deploy_args.pack())
+        .build();
+    let tx = sender_wallet.sign_polyjuice_tx(&state, raw_tx).unwrap();
+    let generator = chain.inner.generator();
+
+    // signed by the sender instead of the sponsor
+    let tx_signed_by_sender = sponsor_tx(
+        &chain.rollup_type_hash(),
+        &tx,
+        &sender_wallet,
+        sponsor_id,
+        SPONSOR_FEE,
+    );
+    let err = generator
+        .check_transaction_signature(&state, &tx_signed_by_sender)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TransactionValidateError::Unlock(LockAlgorithmError::InvalidSignature(_))
+    ));
+
+    // sponsor signature doesn't cover the fee
+    let valid_tx = sponsor_tx(
+        &chain.rollup_type_hash(),
+        &tx,
+        &sponsor_wallet,
+        sponsor_id,
+        SPONSOR_FEE,
+    );
+    let mut sponsored = SponsoredSignature::from_tx(&valid_tx).unwrap();
+    sponsored.fee = 1;
+    let tampered_fee_tx = valid_tx
+        .as_builder()
+        .signature(sponsored.as_bytes().pack())
+        .build();
+    let err = generator
+        .check_transaction_signature(&state, &tampered_fee_tx)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TransactionValidateError::Unlock(LockAlgorithmError::InvalidSignature(_))
+    ));
+
+    // an envelope with trailing bytes is not a sponsored tx
+    let mut signature = sponsored.as_bytes().to_vec();
+    signature.push(0);
+    let malformed = tx
+        .as_builder()
+        .signature(Bytes::from(signature).pack())
+        .build();
+    assert!(SponsoredSignature::from_tx(&malformed).is_none());
+}
@@ -15,8 +15,7 @@ use gw_types::{
     prelude::Pack as GwPack,
 };
 use sha3::{Digest, Keccak256};
-use std::str::FromStr;
-use std::{fs, path::Path};
+use std::path::Path;
 
 pub fn privkey_to_eth_address(privkey: &H256) -> Result<CKBBytes> {
     let privkey = secp256k1::SecretKey::from_slice(privkey.as_bytes())
@@ -100,12 +99,9 @@ pub async fn parse_account_from_str(
     Ok(script_hash)
 }
 
+/// Read a plaintext hex private key file or an encrypted keystore file, the keystore passphrase
+/// is read from the env `GODWOKEN_KEYSTORE_PASSPHRASE`
 pub fn read_privkey(privkey_path: &Path) -> Result<H256> {
-    let privkey_string = fs::read_to_string(privkey_path)?
-        .split_whitespace()
-        .next()
-        .map(ToOwned::to_owned)
-        .ok_or_else(|| anyhow!("Privkey file is empty"))?;
-    let privkey = H256::from_str(privkey_string.trim().trim_start_matches("0x"))?;
-    Ok(privkey)
+    let privkey = gw_utils::keystore::read_privkey(privkey_path, None)?;
+    Ok(H256(privkey))
 }
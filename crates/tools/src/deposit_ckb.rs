@@ -82,7 +82,7 @@ pub async fn deposit_ckb(
         .registry_id(GwPack::pack(&ETH_REGISTRY_ACCOUNT_ID))
         .build();
 
-    let minimal_capacity = minimal_deposit_capacity(&deposit_lock_args)?;
+    let minimal_capacity = minimal_deposit_capacity(&deposit_lock_args, None)?;
     let capacity_in_shannons = parse_capacity(capacity)?;
     if capacity_in_shannons < minimal_capacity {
         let msg = anyhow!(
@@ -235,8 +235,11 @@ async fn get_balance_by_script_hash(
     Ok(balance)
 }
 
-// only for CKB
-fn minimal_deposit_capacity(deposit_lock_args: &DepositLockArgs) -> Result<u64> {
+/// Minimal capacity of a deposit cell, the custodian cell converted from it must be occupied
+pub(crate) fn minimal_deposit_capacity(
+    deposit_lock_args: &DepositLockArgs,
+    sudt_type: Option<Script>,
+) -> Result<u64> {
     // fixed size, the specific value is not important.
     let dummy_hash = gw_types::core::H256::zero();
     let dummy_block_number = 0u64;
@@ -262,12 +265,14 @@ fn minimal_deposit_capacity(deposit_lock_args: &DepositLockArgs) -> Result<u64>
         .build();
 
     // no type / data when deposit CKB
+    let data_capacity = if sudt_type.is_some() { 16 } else { 0 };
     let output = CellOutput::new_builder()
         .capacity(gw_types::prelude::Pack::pack(&0))
         .lock(lock_script)
+        .type_(gw_types::prelude::Pack::pack(&sudt_type))
         .build();
 
-    let capacity = output.occupied_capacity(0)?;
+    let capacity = output.occupied_capacity(data_capacity)?;
     Ok(capacity)
 }
 
@@ -0,0 +1,497 @@
+use anyhow::{anyhow, bail, Result};
+use ckb_sdk::{Address, HumanCapacity};
+use ckb_types::prelude::Entity as CKBEntity;
+use gw_config::{Config, WalletConfig};
+use gw_rpc_client::{
+    ckb_client::CKBClient, contract::query_cell_deps, indexer_client::CKBIndexerClient,
+    rpc_client::RPCClient,
+};
+use gw_types::{
+    bytes::Bytes,
+    core::DepType,
+    offchain::{
+        global_state_from_slice, CellInfo, CellStatus, CellWithStatus, InputCellInfo, RollupContext,
+    },
+    packed::{
+        CellDep, CellInput, OutPoint, RollupConfig, Script, Transaction,
+        UnlockWithdrawalViaFinalize, UnlockWithdrawalViaTrade, UnlockWithdrawalWitness,
+        UnlockWithdrawalWitnessUnion, WitnessArgs,
+    },
+    prelude::*,
+};
+use gw_utils::{
+    fee::fill_tx_fee, genesis_info::CKBGenesisInfo, transaction_skeleton::TransactionSkeleton,
+    wallet::Wallet,
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::utils::transaction::read_config;
+
+const WAIT_TX_COMMITTED_TIMEOUT_SECS: u64 = 600;
+
+pub struct BuyWithdrawalArgs<'a> {
+    pub config_path: &'a Path,
+    pub privkey_path: PathBuf,
+    pub lp_ckb_address: &'a str,
+    pub withdrawal_out_point: OutPoint,
+    pub price: &'a str,
+    pub fee_rate: u64,
+    pub output_path: &'a Path,
+}
+
+/// Build a transaction which pays `price` CKB to the owner of a pending withdrawal and
+/// transfers the withdrawal to the liquidity provider.
+///
+/// The transaction is signed by the liquidity provider, the owner signs and sends it by
+/// `accept_withdrawal_trade`.
+pub async fn buy_withdrawal(args: BuyWithdrawalArgs<'_>) -> Result<()> {
+    let BuyWithdrawalArgs {
+        config_path,
+        privkey_path,
+        lp_ckb_address,
+        withdrawal_out_point,
+        price,
+        fee_rate,
+        output_path,
+    } = args;
+
+    let config = read_config(config_path)?;
+    let rpc_client = build_rpc_client(&config)?;
+    let rollup_context = rpc_client.rollup_context.clone();
+    let lp_lock = parse_ckb_address(lp_ckb_address)?;
+    let price = parse_capacity(price)?;
+
+    let withdrawal_cell = get_live_cell(&rpc_client, withdrawal_out_point).await?;
+    let last_finalized_block_number = query_last_finalized_block_number(&rpc_client).await?;
+    let (lock_args, owner_lock) = gw_rpc_client::withdrawal::verify_tradable(
+        &withdrawal_cell,
+        last_finalized_block_number,
+        &rollup_context.rollup_config.l1_sudt_script_type_hash(),
+    )?;
+    if owner_lock.hash() == lp_lock.hash() {
+        bail!("withdrawal is already owned by liquidity provider");
+    }
+
+    // The owner signs the trade by unlocking one of its cells
+    let owner_cell = rpc_client
+        .query_owner_cell(owner_lock.clone(), None)
+        .await?
+        .ok_or_else(|| anyhow!("owner cell not found"))?;
+
+    let traded_withdrawal_output = {
+        let lock_args = lock_args
+            .as_builder()
+            .owner_lock_hash(lp_lock.hash().pack())
+            .build();
+
+        let mut args = rollup_context.rollup_script_hash.as_slice().to_vec();
+        args.extend_from_slice(lock_args.as_slice());
+        args.extend_from_slice(&(lp_lock.as_slice().len() as u32).to_be_bytes());
+        args.extend_from_slice(lp_lock.as_slice());
+
+        let lock = withdrawal_cell
+            .output
+            .lock()
+            .as_builder()
+            .args(Bytes::from(args).pack())
+            .build();
+        withdrawal_cell
+            .output
+            .clone()
+            .as_builder()
+            .lock(lock)
+            .build()
+    };
+    let paid_owner_output = {
+        let capacity: u64 = owner_cell.output.capacity().unpack();
+        let capacity = capacity
+            .checked_add(price)
+            .ok_or_else(|| anyhow!("capacity overflow"))?;
+        owner_cell
+            .output
+            .clone()
+            .as_builder()
+            .capacity(capacity.pack())
+            .build()
+    };
+
+    let deps = query_contract_deps(
+        &rpc_client,
+        &config,
+        withdrawal_cell.output.type_().to_opt().is_some(),
+    )
+    .await?;
+    let owner_witness = WitnessArgs::new_builder()
+        .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+        .build();
+
+    // The withdrawal cell must be moved to the output at the same index
+    let mut tx_skeleton = TransactionSkeleton::default();
+    tx_skeleton
+        .inputs_mut()
+        .push(to_input_cell_info(&withdrawal_cell));
+    tx_skeleton
+        .inputs_mut()
+        .push(to_input_cell_info(&owner_cell));
+    tx_skeleton.witnesses_mut().push(unlock_via_trade_witness());
+    tx_skeleton.witnesses_mut().push(owner_witness);
+    tx_skeleton
+        .outputs_mut()
+        .push((traded_withdrawal_output, withdrawal_cell.data.clone()));
+    tx_skeleton
+        .outputs_mut()
+        .push((paid_owner_output, owner_cell.data.clone()));
+    tx_skeleton.cell_deps_mut().extend(deps);
+
+    fill_tx_fee(
+        &mut tx_skeleton,
+        &rpc_client.indexer,
+        lp_lock.clone(),
+        fee_rate,
+    )
+    .await?;
+    let wallet = Wallet::from_config(&WalletConfig {
+        privkey_path,
+        lock: lp_lock.into(),
+        passphrase: None,
+        remote_signer: None,
+    })?;
+    let tx = wallet.sign_tx_skeleton(tx_skeleton)?;
+
+    let json_tx: ckb_jsonrpc_types::Transaction =
+        ckb_types::packed::Transaction::new_unchecked(tx.as_bytes()).into();
+    std::fs::write(output_path, serde_json::to_string_pretty(&json_tx)?)?;
+    println!(
+        "Pay {} to owner {}, trade tx is written to {}",
+        HumanCapacity::from(price),
+        owner_lock,
+        output_path.display()
+    );
+
+    Ok(())
+}
+
+pub struct AcceptWithdrawalTradeArgs<'a> {
+    pub config_path: &'a Path,
+    pub privkey_path: PathBuf,
+    pub owner_ckb_address: &'a str,
+    pub tx_path: &'a Path,
+    pub min_price: &'a str,
+}
+
+/// Sign a trade transaction built by `buy_withdrawal` as the withdrawal owner and send it.
+pub async fn accept_withdrawal_trade(args: AcceptWithdrawalTradeArgs<'_>) -> Result<()> {
+    let AcceptWithdrawalTradeArgs {
+        config_path,
+        privkey_path,
+        owner_ckb_address,
+        tx_path,
+        min_price,
+    } = args;
+
+    let config = read_config(config_path)?;
+    let rpc_client = build_rpc_client(&config)?;
+    let rollup_context = rpc_client.rollup_context.clone();
+    let owner_lock = parse_ckb_address(owner_ckb_address)?;
+    let min_price = parse_capacity(min_price)?;
+
+    let tx = {
+        let content = std::fs::read_to_string(tx_path)?;
+        let json_tx: ckb_jsonrpc_types::Transaction = serde_json::from_str(&content)?;
+        let tx: ckb_types::packed::Transaction = json_tx.into();
+        Transaction::new_unchecked(tx.as_bytes())
+    };
+
+    let mut input_cells = Vec::with_capacity(tx.raw().inputs().len());
+    for input in tx.raw().inputs().into_iter() {
+        input_cells.push(get_live_cell(&rpc_client, input.previous_output()).await?);
+    }
+
+    // Check the trade transfers our withdrawals and pays enough
+    let withdrawal_script_type_hash = rollup_context.rollup_config.withdrawal_script_type_hash();
+    let owned_withdrawals = input_cells.iter().filter(|cell| {
+        let lock = cell.output.lock();
+        if lock.code_hash() != withdrawal_script_type_hash {
+            return false;
+        }
+        let args: Bytes = lock.args().unpack();
+        match gw_utils::withdrawal::parse_lock_args(&args) {
+            Ok(parsed) => parsed.owner_lock.hash() == owner_lock.hash(),
+            Err(_) => false,
+        }
+    });
+    if owned_withdrawals.count() == 0 {
+        bail!("no withdrawal owned by {} in trade tx", owner_ckb_address);
+    }
+
+    let owner_lock_hash = owner_lock.hash();
+    let paid: u64 = tx
+        .raw()
+        .outputs()
+        .into_iter()
+        .filter(|output| output.lock().hash() == owner_lock_hash)
+        .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+        .sum();
+    let spent: u64 = input_cells
+        .iter()
+        .filter(|cell| cell.output.lock().hash() == owner_lock_hash)
+        .map(|cell| Unpack::<u64>::unpack(&cell.output.capacity()))
+        .sum();
+    let price = paid.saturating_sub(spent);
+    if price < min_price {
+        bail!(
+            "trade price {} is less than {}",
+            HumanCapacity::from(price),
+            HumanCapacity::from(min_price)
+        );
+    }
+
+    let owner_input_indexes: Vec<usize> = input_cells
+        .iter()
+        .enumerate()
+        .filter(|(_idx, cell)| cell.output.lock().hash() == owner_lock_hash)
+        .map(|(idx, _cell)| idx)
+        .collect();
+    let wallet = Wallet::from_config(&WalletConfig {
+        privkey_path,
+        lock: owner_lock.into(),
+        passphrase: None,
+        remote_signer: None,
+    })?;
+    let tx = wallet.sign_tx_inputs(tx, &owner_input_indexes)?;
+
+    let tx_hash = rpc_client.send_transaction(&tx).await?;
+    println!(
+        "Sell withdrawal for {}, tx {}",
+        HumanCapacity::from(price),
+        tx_hash.pack()
+    );
+    rpc_client
+        .ckb
+        .wait_tx_committed_with_timeout_and_logging(tx_hash, WAIT_TX_COMMITTED_TIMEOUT_SECS)
+        .await?;
+    println!("Withdrawal is transferred!");
+
+    Ok(())
+}
+
+pub struct SettleWithdrawalArgs<'a> {
+    pub config_path: &'a Path,
+    pub privkey_path: PathBuf,
+    pub lp_ckb_address: &'a str,
+    pub withdrawal_out_point: OutPoint,
+    pub fee_rate: u64,
+}
+
+/// Unlock a finalized withdrawal bought by the liquidity provider.
+///
+/// Block producers with a withdrawal unlocker do this automatically, this is for nodes without
+/// one.
+pub async fn settle_withdrawal(args: SettleWithdrawalArgs<'_>) -> Result<()> {
+    let SettleWithdrawalArgs {
+        config_path,
+        privkey_path,
+        lp_ckb_address,
+        withdrawal_out_point,
+        fee_rate,
+    } = args;
+
+    let config = read_config(config_path)?;
+    let rpc_client = build_rpc_client(&config)?;
+    let rollup_context = rpc_client.rollup_context.clone();
+    let lp_lock = parse_ckb_address(lp_ckb_address)?;
+
+    let withdrawal_cell = get_live_cell(&rpc_client, withdrawal_out_point).await?;
+    let rollup_cell = rpc_client
+        .query_rollup_cell()
+        .await?
+        .ok_or_else(|| anyhow!("rollup cell not found"))?;
+    let last_finalized_block_number: u64 = global_state_from_slice(&rollup_cell.data)?
+        .last_finalized_block_number()
+        .unpack();
+    gw_rpc_client::withdrawal::verify_unlockable_to_owner(
+        &withdrawal_cell,
+        last_finalized_block_number,
+        &rollup_context.rollup_config.l1_sudt_script_type_hash(),
+    )?;
+    let owner_lock = {
+        let args: Bytes = withdrawal_cell.output.lock().args().unpack();
+        gw_utils::withdrawal::parse_lock_args(&args)?.owner_lock
+    };
+    if owner_lock.hash() != lp_lock.hash() {
+        bail!("withdrawal isn't owned by {}", lp_ckb_address);
+    }
+
+    let rollup_dep = CellDep::new_builder()
+        .out_point(rollup_cell.out_point)
+        .dep_type(DepType::Code.into())
+        .build();
+    let deps = query_contract_deps(
+        &rpc_client,
+        &config,
+        withdrawal_cell.output.type_().to_opt().is_some(),
+    )
+    .await?;
+
+    let unlocked_output = withdrawal_cell
+        .output
+        .clone()
+        .as_builder()
+        .lock(lp_lock.clone())
+        .build();
+    let mut tx_skeleton = TransactionSkeleton::default();
+    tx_skeleton
+        .inputs_mut()
+        .push(to_input_cell_info(&withdrawal_cell));
+    tx_skeleton
+        .witnesses_mut()
+        .push(unlock_via_finalize_witness());
+    tx_skeleton
+        .outputs_mut()
+        .push((unlocked_output, withdrawal_cell.data.clone()));
+    tx_skeleton.cell_deps_mut().push(rollup_dep);
+    tx_skeleton.cell_deps_mut().extend(deps);
+
+    fill_tx_fee(
+        &mut tx_skeleton,
+        &rpc_client.indexer,
+        lp_lock.clone(),
+        fee_rate,
+    )
+    .await?;
+    let wallet = Wallet::from_config(&WalletConfig {
+        privkey_path,
+        lock: lp_lock.into(),
+        passphrase: None,
+        remote_signer: None,
+    })?;
+    let tx = wallet.sign_tx_skeleton(tx_skeleton)?;
+
+    let tx_hash = rpc_client.send_transaction(&tx).await?;
+    println!("Settle withdrawal, tx {}", tx_hash.pack());
+    rpc_client
+        .ckb
+        .wait_tx_committed_with_timeout_and_logging(tx_hash, WAIT_TX_COMMITTED_TIMEOUT_SECS)
+        .await?;
+    println!("Withdrawal is settled!");
+
+    Ok(())
+}
+
+pub(crate) fn build_rpc_client(config: &Config) -> Result<RPCClient> {
+    let rollup_type_script: Script = config.chain.rollup_type_script.clone().into();
+    let rollup_config: RollupConfig = config.genesis.rollup_config.clone().into();
+    let rollup_context = RollupContext {
+        rollup_config,
+        rollup_script_hash: {
+            let rollup_script_hash: [u8; 32] = config.genesis.rollup_type_hash.clone().into();
+            rollup_script_hash.into()
+        },
+    };
+    let indexer_client = CKBIndexerClient::with_url(&config.rpc_client.indexer_url)?;
+    let ckb_client = CKBClient::with_url(&config.rpc_client.ckb_url)?;
+
+    Ok(RPCClient::new(
+        ckb_types::packed::Script::new_unchecked(rollup_type_script.as_bytes()),
+        rollup_context,
+        ckb_client,
+        indexer_client,
+    ))
+}
+
+async fn get_live_cell(rpc_client: &RPCClient, out_point: OutPoint) -> Result<CellInfo> {
+    match rpc_client.get_cell(out_point.clone()).await? {
+        Some(CellWithStatus {
+            cell: Some(cell),
+            status: CellStatus::Live,
+        }) => Ok(cell),
+        _ => Err(anyhow!("cell {} isn't live", out_point)),
+    }
+}
+
+async fn query_last_finalized_block_number(rpc_client: &RPCClient) -> Result<u64> {
+    let rollup_cell = rpc_client
+        .query_rollup_cell()
+        .await?
+        .ok_or_else(|| anyhow!("rollup cell not found"))?;
+    let global_state = global_state_from_slice(&rollup_cell.data)?;
+    Ok(global_state.last_finalized_block_number().unpack())
+}
+
+/// Withdrawal lock, secp256k1 sighash lock and l1 sudt (optional) deps
+async fn query_contract_deps(
+    rpc_client: &RPCClient,
+    config: &Config,
+    with_sudt: bool,
+) -> Result<Vec<CellDep>> {
+    let contracts_dep =
+        query_cell_deps(rpc_client, &config.consensus.contract_type_scripts).await?;
+    let ckb_genesis_info = {
+        let block = rpc_client
+            .get_block_by_number(0)
+            .await?
+            .ok_or_else(|| anyhow!("can't found CKB genesis block"))?;
+        CKBGenesisInfo::from_block(&block)?
+    };
+
+    let mut deps = vec![
+        contracts_dep.withdrawal_cell_lock.clone().into(),
+        ckb_genesis_info.sighash_dep(),
+    ];
+    if with_sudt {
+        deps.push(contracts_dep.l1_sudt_type.clone().into());
+    }
+    Ok(deps)
+}
+
+pub(crate) fn to_input_cell_info(cell: &CellInfo) -> InputCellInfo {
+    InputCellInfo {
+        input: CellInput::new_builder()
+            .previous_output(cell.out_point.clone())
+            .build(),
+        cell: cell.clone(),
+    }
+}
+
+fn unlock_via_trade_witness() -> WitnessArgs {
+    let unlock_witness = UnlockWithdrawalWitness::new_builder()
+        .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(
+            UnlockWithdrawalViaTrade::new_builder().build(),
+        ))
+        .build();
+    WitnessArgs::new_builder()
+        .lock(Some(unlock_witness.as_bytes()).pack())
+        .build()
+}
+
+fn unlock_via_finalize_witness() -> WitnessArgs {
+    let unlock_witness = UnlockWithdrawalWitness::new_builder()
+        .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(
+            UnlockWithdrawalViaFinalize::new_builder().build(),
+        ))
+        .build();
+    WitnessArgs::new_builder()
+        .lock(Some(unlock_witness.as_bytes()).pack())
+        .build()
+}
+
+pub fn parse_out_point(tx_hash: &str, index: &str) -> Result<OutPoint> {
+    let tx_hash = crate::utils::cli_args::to_h256(tx_hash)?;
+    let index: u32 = index.parse()?;
+    Ok(OutPoint::new_builder()
+        .tx_hash(tx_hash.pack())
+        .index(index.pack())
+        .build())
+}
+
+pub(crate) fn parse_ckb_address(address: &str) -> Result<Script> {
+    let address = Address::from_str(address).map_err(|err| anyhow!(err))?;
+    let lock = ckb_types::packed::Script::from(address.payload());
+    Ok(Script::new_unchecked(lock.as_bytes()))
+}
+
+pub(crate) fn parse_capacity(capacity: &str) -> Result<u64> {
+    let human_capacity = HumanCapacity::from_str(capacity).map_err(|err| anyhow!("{}", err))?;
+    Ok(human_capacity.into())
+}
@@ -139,6 +139,8 @@ pub async fn generate_node_config(args: GenerateNodeConfigArgs<'_>) -> Result<Co
     let wallet_config: WalletConfig = WalletConfig {
         privkey_path: privkey_path.into(),
         lock,
+        passphrase: None,
+        remote_signer: None,
     };
 
     let backends: Vec<BackendConfig> = vec![
@@ -189,6 +191,7 @@ pub async fn generate_node_config(args: GenerateNodeConfigArgs<'_>) -> Result<Co
         options: HashMap::new(),
         options_file: None,
         cache_size: None,
+        ..Default::default()
     };
     let genesis_committed_info = L2BlockCommittedInfo {
         block_hash,
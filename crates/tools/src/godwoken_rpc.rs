@@ -4,7 +4,7 @@ use ckb_types::H256;
 use gw_common::{builtins::ETH_REGISTRY_ACCOUNT_ID, registry_address::RegistryAddress};
 use gw_jsonrpc_types::{
     ckb_jsonrpc_types::{JsonBytes, Uint32},
-    debugger::{DumpChallengeTarget, ReprMockTransaction},
+    debugger::{CyclesProfile, DumpChallengeTarget, ReprMockTransaction},
     godwoken::{RunResult, TxReceipt},
 };
 use gw_types::U256;
@@ -163,6 +163,18 @@ impl GodwokenRpcClient {
             .map(Into::into)
     }
 
+    pub async fn debug_profile_transaction(&self, tx_hash: &H256) -> Result<Option<CyclesProfile>> {
+        let params = serde_json::to_value((tx_hash,))?;
+        self.rpc::<Option<CyclesProfile>>("debug_profile_transaction", params)
+            .await
+    }
+
+    pub async fn debug_profile_l2transaction(&self, l2tx: JsonBytes) -> Result<CyclesProfile> {
+        let params = serde_json::to_value((l2tx,))?;
+        self.rpc::<CyclesProfile>("debug_profile_l2transaction", params)
+            .await
+    }
+
     async fn rpc<SuccessResponse: serde::de::DeserializeOwned>(
         &self,
         method: &str,
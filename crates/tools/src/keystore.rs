@@ -0,0 +1,44 @@
+use anyhow::{bail, Result};
+use gw_config::PassphraseSource;
+use gw_utils::keystore::{read_passphrase, read_privkey, Keystore, DEFAULT_SCRYPT_LOG_N};
+use std::path::Path;
+
+/// Encrypt a plaintext private key file into a keystore file
+pub fn import_key(
+    privkey_path: &Path,
+    keystore_path: &Path,
+    passphrase: &PassphraseSource,
+) -> Result<()> {
+    if keystore_path.exists() {
+        bail!("keystore file {} already exists", keystore_path.display());
+    }
+    let privkey = read_privkey(privkey_path, None)?;
+    let passphrase = read_passphrase(passphrase)?;
+    if passphrase.is_empty() {
+        bail!("empty passphrase");
+    }
+
+    let keystore = Keystore::encrypt(&privkey, passphrase.as_bytes(), DEFAULT_SCRYPT_LOG_N)?;
+    std::fs::write(keystore_path, serde_json::to_string_pretty(&keystore)?)?;
+    println!(
+        "Private key of address 0x{} is encrypted to {}",
+        keystore.address.unwrap_or_default(),
+        keystore_path.display()
+    );
+    Ok(())
+}
+
+/// Decrypt a keystore file into a plaintext private key file
+pub fn export_key(
+    keystore_path: &Path,
+    privkey_path: &Path,
+    passphrase: &PassphraseSource,
+) -> Result<()> {
+    if privkey_path.exists() {
+        bail!("private key file {} already exists", privkey_path.display());
+    }
+    let privkey = read_privkey(keystore_path, Some(passphrase))?;
+    std::fs::write(privkey_path, format!("0x{}\n", hex::encode(privkey)))?;
+    println!("Private key is exported to {}", privkey_path.display());
+    Ok(())
+}
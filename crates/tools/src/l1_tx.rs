@@ -0,0 +1,1052 @@
+use anyhow::{anyhow, bail, Result};
+use ckb_crypto::secp::{Privkey, Signature as SecpSignature};
+use ckb_fixed_hash::H256;
+use ckb_jsonrpc_types::JsonBytes;
+use ckb_sdk::HumanCapacity;
+use ckb_types::prelude::Entity as CKBEntity;
+use gw_common::{blake2b::new_blake2b, builtins::ETH_REGISTRY_ACCOUNT_ID};
+use gw_config::{Config, ContractsCellDep};
+use gw_rpc_client::{
+    contract::query_cell_deps,
+    indexer_types::{Order, SearchKey, SearchKeyFilter},
+    rpc_client::RPCClient,
+};
+use gw_types::{
+    bytes::Bytes,
+    core::{AllowedEoaType, DepType, ScriptHashType},
+    offchain::CellInfo,
+    packed::{
+        CellDep, CellOutput, DepositLockArgs, OmniLockWitnessLock, OutPoint, Script, Transaction,
+        WitnessArgs,
+    },
+    prelude::*,
+};
+use gw_utils::{
+    fee::fill_tx_fee, genesis_info::CKBGenesisInfo, transaction_skeleton::TransactionSkeleton,
+    wallet::sighash_message,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::account::read_privkey;
+use crate::deposit_ckb::minimal_deposit_capacity;
+use crate::fast_withdrawal::{
+    build_rpc_client, parse_capacity, parse_ckb_address, to_input_cell_info,
+};
+use crate::utils::transaction::read_config;
+
+const WAIT_TX_COMMITTED_TIMEOUT_SECS: u64 = 600;
+// cancel_timeout default to 20 minutes
+const DEPOSIT_CANCEL_TIMEOUT: u64 = 0xc0000000000004b0;
+const SIGNATURE_SIZE: usize = 65;
+const MULTISIG_HEADER_SIZE: usize = 4;
+const BLAKE160_SIZE: usize = 20;
+// Omni-lock args: auth flag | auth content | omni-lock flags
+const OMNI_LOCK_ARGS_SIZE: usize = 22;
+const OMNI_LOCK_AUTH_SECP256K1_BLAKE160: u8 = 0;
+// xUDT args: owner lock hash | flags (optional)
+const XUDT_OWNER_LOCK_HASH_SIZE: usize = 32;
+const XUDT_FLAGS_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum L1LockKind {
+    Sighash,
+    Multisig,
+    OmniLock,
+}
+
+/// Inputs unlocked by the same lock, the signature is put in the witness of the first input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningGroup {
+    pub lock: ckb_jsonrpc_types::Script,
+    pub kind: L1LockKind,
+    pub input_indexes: Vec<usize>,
+    /// Sighash all message, sign it with a secp256k1 recoverable signature
+    pub message: H256,
+    /// Multisig config: 0 | require_first_n | threshold | pubkeys count | blake160 pubkeys
+    #[serde(default)]
+    pub multisig_config: Option<JsonBytes>,
+    /// Collected signatures, multisig lock requires `threshold` signatures
+    #[serde(default)]
+    pub signatures: Vec<JsonBytes>,
+}
+
+/// Unsigned L1 transaction, the witness locks are placeholders used to calculate messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    pub transaction: ckb_jsonrpc_types::Transaction,
+    pub signing_groups: Vec<SigningGroup>,
+}
+
+pub struct BuildDepositTxArgs<'a> {
+    pub config_path: &'a Path,
+    pub from_address: &'a str,
+    pub eth_address: &'a str,
+    pub capacity: &'a str,
+    pub udt_args: Option<&'a str>,
+    pub amount: u128,
+    pub multisig_config: Option<&'a str>,
+    pub fee_rate: u64,
+    pub output_path: &'a Path,
+}
+
+/// Build an unsigned transaction which deposits CKB and sUDT (optional) paid by any
+/// supported L1 lock: secp256k1 sighash, secp256k1 multisig or secp256k1 omni-lock.
+///
+/// The UDT type script uses the l1 sudt type hash of the rollup. xUDT can't be deposited, the
+/// rollup only accepts deposits of the l1 sudt type.
+pub async fn build_deposit_tx(args: BuildDepositTxArgs<'_>) -> Result<()> {
+    let BuildDepositTxArgs {
+        config_path,
+        from_address,
+        eth_address,
+        capacity,
+        udt_args,
+        amount,
+        multisig_config,
+        fee_rate,
+        output_path,
+    } = args;
+
+    let config = read_config(config_path)?;
+    let ctx = L1Context::new(&config).await?;
+    let rollup_context = &ctx.rpc_client.rollup_context;
+    let from_lock = parse_ckb_address(from_address)?;
+    let capacity = parse_capacity(capacity)?;
+    let udt = parse_udt(&ctx, udt_args, amount, None)?;
+
+    let l2_lock = {
+        let eth_address = hex::decode(eth_address.trim_start_matches("0x"))?;
+        if eth_address.len() != 20 {
+            bail!("invalid eth address length {}", eth_address.len());
+        }
+        let eth_lock_type_hash = rollup_context
+            .rollup_config
+            .allowed_eoa_type_hashes()
+            .into_iter()
+            .find(|type_hash| {
+                let type_: u8 = type_hash.type_().into();
+                type_ == AllowedEoaType::Eth as u8
+            })
+            .ok_or_else(|| anyhow!("eth account lock isn't allowed by rollup"))?
+            .hash();
+
+        let mut args = rollup_context.rollup_script_hash.as_slice().to_vec();
+        args.extend_from_slice(&eth_address);
+        Script::new_builder()
+            .code_hash(eth_lock_type_hash)
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(args).pack())
+            .build()
+    };
+    let deposit_lock_args = DepositLockArgs::new_builder()
+        .owner_lock_hash(from_lock.hash().pack())
+        .cancel_timeout(DEPOSIT_CANCEL_TIMEOUT.pack())
+        .layer2_lock(l2_lock)
+        .registry_id(ETH_REGISTRY_ACCOUNT_ID.pack())
+        .build();
+
+    let minimal_capacity = minimal_deposit_capacity(
+        &deposit_lock_args,
+        udt.as_ref().map(|udt| udt.type_script.clone()),
+    )?;
+    if capacity < minimal_capacity {
+        bail!(
+            "deposit required {} CKB at least, provided {}",
+            HumanCapacity::from(minimal_capacity),
+            HumanCapacity::from(capacity)
+        );
+    }
+
+    let deposit_lock = {
+        let mut args = rollup_context.rollup_script_hash.as_slice().to_vec();
+        args.extend_from_slice(deposit_lock_args.as_slice());
+        Script::new_builder()
+            .code_hash(rollup_context.rollup_config.deposit_script_type_hash())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::from(args).pack())
+            .build()
+    };
+    let deposit_output = udt_output(deposit_lock, capacity, udt.as_ref().map(Udt::amount_of));
+
+    let multisig_config = multisig_config.map(parse_hex).transpose()?;
+    let unsigned_tx = ctx
+        .build_unsigned_tx(from_lock, deposit_output, udt, multisig_config, fee_rate)
+        .await?;
+    write_unsigned_tx(output_path, &unsigned_tx)?;
+    println!(
+        "Deposit {} to {}, unsigned tx is written to {}",
+        HumanCapacity::from(capacity),
+        eth_address,
+        output_path.display()
+    );
+
+    Ok(())
+}
+
+pub struct BuildTransferTxArgs<'a> {
+    pub config_path: &'a Path,
+    pub from_address: &'a str,
+    pub to_address: &'a str,
+    pub capacity: &'a str,
+    pub udt_args: Option<&'a str>,
+    pub amount: u128,
+    /// Transfer xUDT instead of sUDT
+    pub xudt: Option<Xudt>,
+    pub multisig_config: Option<&'a str>,
+    pub fee_rate: u64,
+    pub output_path: &'a Path,
+}
+
+/// xUDT isn't in the rollup config, its type script code hash and cell dep are given
+pub struct Xudt {
+    /// Type hash of the xUDT script
+    pub code_hash: [u8; 32],
+    pub cell_dep: OutPoint,
+}
+
+/// Build an unsigned transaction which transfers CKB and sUDT or xUDT (optional) on L1, paid
+/// by any supported L1 lock.
+pub async fn build_transfer_tx(args: BuildTransferTxArgs<'_>) -> Result<()> {
+    let BuildTransferTxArgs {
+        config_path,
+        from_address,
+        to_address,
+        capacity,
+        udt_args,
+        amount,
+        xudt,
+        multisig_config,
+        fee_rate,
+        output_path,
+    } = args;
+
+    let config = read_config(config_path)?;
+    let ctx = L1Context::new(&config).await?;
+    let from_lock = parse_ckb_address(from_address)?;
+    let to_lock = parse_ckb_address(to_address)?;
+    let capacity = parse_capacity(capacity)?;
+    let udt = parse_udt(&ctx, udt_args, amount, xudt.as_ref())?;
+
+    let (output, data) = udt_output(to_lock, capacity, udt.as_ref().map(Udt::amount_of));
+    let occupied_capacity = output.occupied_capacity(data.len())?;
+    if capacity < occupied_capacity {
+        bail!(
+            "transfer required {} CKB at least, provided {}",
+            HumanCapacity::from(occupied_capacity),
+            HumanCapacity::from(capacity)
+        );
+    }
+
+    let multisig_config = multisig_config.map(parse_hex).transpose()?;
+    let unsigned_tx = ctx
+        .build_unsigned_tx(from_lock, (output, data), udt, multisig_config, fee_rate)
+        .await?;
+    write_unsigned_tx(output_path, &unsigned_tx)?;
+    println!(
+        "Transfer {} to {}, unsigned tx is written to {}",
+        HumanCapacity::from(capacity),
+        to_address,
+        output_path.display()
+    );
+
+    Ok(())
+}
+
+pub struct SignTxArgs<'a> {
+    pub tx_path: &'a Path,
+    pub privkey_path: Option<PathBuf>,
+    pub signature: Option<&'a str>,
+    pub group: Option<usize>,
+}
+
+/// Attach a signature to signing groups of an unsigned transaction.
+///
+/// Sign all groups unlockable by the private key, or attach a signature produced offline (e.g.
+/// by a hardware wallet) to the given group. Signatures are verified against the group lock.
+pub fn sign_tx(args: SignTxArgs<'_>) -> Result<()> {
+    let SignTxArgs {
+        tx_path,
+        privkey_path,
+        signature,
+        group,
+    } = args;
+
+    let mut unsigned_tx = read_unsigned_tx(tx_path)?;
+    let tx = to_gw_tx(&unsigned_tx.transaction);
+    for signing_group in unsigned_tx.signing_groups.iter() {
+        check_message(&tx, signing_group)?;
+    }
+
+    let mut signed_groups = Vec::new();
+    match (privkey_path, signature) {
+        (Some(privkey_path), None) => {
+            let privkey = Privkey::from(read_privkey(&privkey_path)?);
+            let pubkey_hash = blake160(&privkey.pubkey()?.serialize());
+            for (index, signing_group) in unsigned_tx.signing_groups.iter_mut().enumerate() {
+                if group.is_some() && group != Some(index) {
+                    continue;
+                }
+                if !signer_pubkey_hashes(signing_group)?.contains(&pubkey_hash) {
+                    continue;
+                }
+                let signature = privkey.sign_recoverable(&signing_group.message)?;
+                attach_signature(signing_group, signature.serialize())?;
+                signed_groups.push(index);
+            }
+        }
+        (None, Some(signature)) => {
+            let index = group.unwrap_or(0);
+            let signing_group = unsigned_tx
+                .signing_groups
+                .get_mut(index)
+                .ok_or_else(|| anyhow!("signing group {} not found", index))?;
+            attach_signature(signing_group, parse_hex(signature)?.to_vec())?;
+            signed_groups.push(index);
+        }
+        _ => bail!("expect one of private key and signature"),
+    }
+    if signed_groups.is_empty() {
+        bail!("no signing group can be signed by the private key");
+    }
+
+    write_unsigned_tx(tx_path, &unsigned_tx)?;
+    for index in signed_groups {
+        let signing_group = &unsigned_tx.signing_groups[index];
+        println!(
+            "Signing group {}: {}/{} signatures",
+            index,
+            signing_group.signatures.len(),
+            required_signatures(signing_group)?
+        );
+    }
+
+    Ok(())
+}
+
+/// Put the collected signatures into witnesses and send the transaction
+pub async fn send_tx(config_path: &Path, tx_path: &Path) -> Result<()> {
+    let config = read_config(config_path)?;
+    let rpc_client = build_rpc_client(&config)?;
+    let unsigned_tx = read_unsigned_tx(tx_path)?;
+    let tx = to_gw_tx(&unsigned_tx.transaction);
+
+    let mut witnesses: Vec<Bytes> = tx.witnesses().into_iter().map(|w| w.unpack()).collect();
+    for (index, signing_group) in unsigned_tx.signing_groups.iter().enumerate() {
+        check_message(&tx, signing_group)?;
+        let required = required_signatures(signing_group)?;
+        if signing_group.signatures.len() < required {
+            bail!(
+                "signing group {} requires {} signatures, got {}",
+                index,
+                required,
+                signing_group.signatures.len()
+            );
+        }
+
+        let lock = match signing_group.kind {
+            L1LockKind::Sighash => signing_group.signatures[0].clone().into_bytes(),
+            L1LockKind::OmniLock => OmniLockWitnessLock::new_builder()
+                .signature(Some(signing_group.signatures[0].clone().into_bytes()).pack())
+                .build()
+                .as_bytes(),
+            L1LockKind::Multisig => multisig_witness_lock(signing_group, required)?,
+        };
+
+        let first_index = signing_group.input_indexes[0];
+        let witness_args = WitnessArgs::from_slice(&witnesses[first_index])?;
+        witnesses[first_index] = witness_args
+            .as_builder()
+            .lock(Some(lock).pack())
+            .build()
+            .as_bytes();
+    }
+    let tx = tx.as_builder().witnesses(witnesses.pack()).build();
+
+    let tx_hash = rpc_client.send_transaction(&tx).await?;
+    println!("Send tx {}", tx_hash.pack());
+    rpc_client
+        .ckb
+        .wait_tx_committed_with_timeout_and_logging(tx_hash, WAIT_TX_COMMITTED_TIMEOUT_SECS)
+        .await?;
+    println!("Tx is committed!");
+
+    Ok(())
+}
+
+struct L1Context {
+    rpc_client: RPCClient,
+    ckb_genesis_info: CKBGenesisInfo,
+    contracts_dep: ContractsCellDep,
+    omni_lock_type_hash: [u8; 32],
+}
+
+impl L1Context {
+    async fn new(config: &Config) -> Result<Self> {
+        let rpc_client = build_rpc_client(config)?;
+        let contracts_dep =
+            query_cell_deps(&rpc_client, &config.consensus.contract_type_scripts).await?;
+        let ckb_genesis_info = {
+            let block = rpc_client
+                .get_block_by_number(0)
+                .await?
+                .ok_or_else(|| anyhow!("can't found CKB genesis block"))?;
+            CKBGenesisInfo::from_block(&block)?
+        };
+        let omni_lock_type_hash = {
+            let omni_lock: Script = config
+                .consensus
+                .contract_type_scripts
+                .omni_lock
+                .clone()
+                .into();
+            omni_lock.hash()
+        };
+
+        Ok(L1Context {
+            rpc_client,
+            ckb_genesis_info,
+            contracts_dep,
+            omni_lock_type_hash,
+        })
+    }
+
+    fn lock_kind(&self, lock: &Script) -> Result<L1LockKind> {
+        if lock.hash_type() != ScriptHashType::Type.into() {
+            bail!("unsupported lock {}", lock);
+        }
+        let args: Bytes = lock.args().unpack();
+        let code_hash = lock.code_hash();
+        if code_hash == self.ckb_genesis_info.sighash_type_hash().pack() {
+            if args.len() != BLAKE160_SIZE {
+                bail!("invalid sighash lock args {}", lock);
+            }
+            Ok(L1LockKind::Sighash)
+        } else if code_hash == self.ckb_genesis_info.multisig_type_hash().pack() {
+            // blake160(multisig config) | since (optional)
+            if args.len() != BLAKE160_SIZE && args.len() != BLAKE160_SIZE + 8 {
+                bail!("invalid multisig lock args {}", lock);
+            }
+            Ok(L1LockKind::Multisig)
+        } else if code_hash == self.omni_lock_type_hash.pack() {
+            if args.len() != OMNI_LOCK_ARGS_SIZE
+                || args[0] != OMNI_LOCK_AUTH_SECP256K1_BLAKE160
+                || args[OMNI_LOCK_ARGS_SIZE - 1] != 0
+            {
+                bail!("only secp256k1 omni-lock without omni-lock flags is supported");
+            }
+            Ok(L1LockKind::OmniLock)
+        } else {
+            bail!("unsupported lock {}", lock)
+        }
+    }
+
+    fn lock_dep(&self, kind: L1LockKind) -> CellDep {
+        match kind {
+            L1LockKind::Sighash => self.ckb_genesis_info.sighash_dep(),
+            L1LockKind::Multisig => self.ckb_genesis_info.multisig_dep(),
+            L1LockKind::OmniLock => self.contracts_dep.omni_lock.clone().into(),
+        }
+    }
+
+    /// Collect UDT and capacity from the lock, the UDT and capacity change are sent back to it
+    async fn build_unsigned_tx(
+        &self,
+        from_lock: Script,
+        output: (CellOutput, Bytes),
+        udt: Option<Udt>,
+        multisig_config: Option<Bytes>,
+        fee_rate: u64,
+    ) -> Result<UnsignedTx> {
+        let kind = self.lock_kind(&from_lock)?;
+        let multisig_config = match (kind, multisig_config) {
+            (L1LockKind::Multisig, Some(config)) => {
+                parse_multisig_config(&config)?;
+                let args: Bytes = from_lock.args().unpack();
+                if blake160(&config) != args[..BLAKE160_SIZE] {
+                    bail!("multisig config doesn't match lock args");
+                }
+                Some(config)
+            }
+            (L1LockKind::Multisig, None) => bail!("multisig config is required"),
+            (_, Some(_)) => bail!("multisig config is only for multisig lock"),
+            (_, None) => None,
+        };
+
+        let mut tx_skeleton = TransactionSkeleton::new(self.omni_lock_type_hash);
+        tx_skeleton.cell_deps_mut().push(self.lock_dep(kind));
+        tx_skeleton.outputs_mut().push(output);
+        if let Some(Udt {
+            type_script: udt_type,
+            amount,
+            cell_dep,
+        }) = udt
+        {
+            let udt_cells = self
+                .collect_udt_cells(from_lock.clone(), udt_type.clone(), amount)
+                .await?;
+            let collected: u128 = udt_cells.iter().map(|cell| udt_amount(&cell.data)).sum();
+            tx_skeleton
+                .inputs_mut()
+                .extend(udt_cells.iter().map(to_input_cell_info));
+            if collected > amount {
+                let change =
+                    udt_output(from_lock.clone(), 0, Some((&udt_type, collected - amount)));
+                let occupied_capacity = change.0.occupied_capacity(change.1.len())?;
+                let change = (
+                    change
+                        .0
+                        .as_builder()
+                        .capacity(occupied_capacity.pack())
+                        .build(),
+                    change.1,
+                );
+                tx_skeleton.outputs_mut().push(change);
+            }
+            tx_skeleton.cell_deps_mut().push(cell_dep);
+        }
+
+        fill_tx_fee(
+            &mut tx_skeleton,
+            &self.rpc_client.indexer,
+            from_lock.clone(),
+            fee_rate,
+        )
+        .await?;
+
+        // Placeholders filled with zero except the multisig config
+        let placeholder = match kind {
+            L1LockKind::Sighash => Bytes::from(vec![0u8; SIGNATURE_SIZE]),
+            L1LockKind::OmniLock => {
+                let lock = OmniLockWitnessLock::new_builder()
+                    .signature(Some(Bytes::from(vec![0u8; SIGNATURE_SIZE])).pack())
+                    .build();
+                Bytes::from(vec![0u8; lock.as_slice().len()])
+            }
+            L1LockKind::Multisig => {
+                let config = multisig_config.clone().expect("multisig config");
+                let (_, threshold, _) = parse_multisig_config(&config)?;
+                let mut lock = config.to_vec();
+                lock.resize(config.len() + SIGNATURE_SIZE * threshold, 0);
+                Bytes::from(lock)
+            }
+        };
+        let input_indexes: Vec<usize> = (0..tx_skeleton.inputs().len()).collect();
+        let entries = tx_skeleton.signature_entries();
+        let seal = |tx_skeleton: &TransactionSkeleton| {
+            let placeholders = entries.iter().map(|_| placeholder.clone()).collect();
+            tx_skeleton.seal(&entries, placeholders)
+        };
+
+        // The fee is estimated with a 65 bytes signature, pay the rest from the change
+        let tx_in_block_size = seal(&tx_skeleton)?.transaction.as_slice().len() + 4;
+        let required_fee = (tx_in_block_size as u64) * fee_rate / 1000;
+        let paid_fee = tx_skeleton.calculate_fee()?;
+        if paid_fee < required_fee {
+            let (change, data) = tx_skeleton
+                .outputs_mut()
+                .last_mut()
+                .ok_or_else(|| anyhow!("change output not found"))?;
+            let capacity: u64 = change.capacity().unpack();
+            let capacity = capacity.saturating_sub(required_fee - paid_fee);
+            if capacity < change.occupied_capacity(data.len())? {
+                bail!("insufficient capacity to pay tx fee");
+            }
+            *change = change
+                .clone()
+                .as_builder()
+                .capacity(capacity.pack())
+                .build();
+        }
+
+        let tx = seal(&tx_skeleton)?.transaction;
+        let message = sighash_message(&tx, &input_indexes);
+        let lock = ckb_types::packed::Script::new_unchecked(from_lock.as_bytes()).into();
+        let signing_group = SigningGroup {
+            lock,
+            kind,
+            input_indexes,
+            message: message.into(),
+            multisig_config: multisig_config.map(JsonBytes::from_bytes),
+            signatures: Vec::new(),
+        };
+
+        Ok(UnsignedTx {
+            transaction: ckb_types::packed::Transaction::new_unchecked(tx.as_bytes()).into(),
+            signing_groups: vec![signing_group],
+        })
+    }
+
+    async fn collect_udt_cells(
+        &self,
+        lock: Script,
+        udt_type: Script,
+        amount: u128,
+    ) -> Result<Vec<CellInfo>> {
+        let search_key = SearchKey::with_lock(lock).with_filter(Some(SearchKeyFilter {
+            script: Some(ckb_types::packed::Script::new_unchecked(udt_type.as_bytes()).into()),
+            ..Default::default()
+        }));
+        let order = Order::Asc;
+
+        let mut collected_cells = Vec::new();
+        let mut collected_amount = 0u128;
+        let mut cursor = None;
+        while collected_amount < amount {
+            let cells = self
+                .rpc_client
+                .indexer
+                .get_cells(&search_key, &order, None, &cursor)
+                .await?;
+            if cells.objects.is_empty() {
+                bail!(
+                    "no enough udt cells, required: {}, collected: {}",
+                    amount,
+                    collected_amount
+                );
+            }
+            cursor = Some(cells.last_cursor);
+
+            // The filter matches type script prefix
+            let udt_type_hash = udt_type.hash();
+            let cells = cells.objects.into_iter().map(|cell| cell.info());
+            for cell in cells.filter(|cell| {
+                let type_hash = cell.output.type_().to_opt().map(|type_| type_.hash());
+                type_hash == Some(udt_type_hash)
+            }) {
+                collected_amount = collected_amount.saturating_add(udt_amount(&cell.data));
+                collected_cells.push(cell);
+                if collected_amount >= amount {
+                    break;
+                }
+            }
+        }
+        Ok(collected_cells)
+    }
+}
+
+/// UDT to send and the cell dep of its type script
+struct Udt {
+    type_script: Script,
+    amount: u128,
+    cell_dep: CellDep,
+}
+
+impl Udt {
+    fn amount_of(&self) -> (&Script, u128) {
+        (&self.type_script, self.amount)
+    }
+}
+
+fn parse_udt(
+    ctx: &L1Context,
+    udt_args: Option<&str>,
+    amount: u128,
+    xudt: Option<&Xudt>,
+) -> Result<Option<Udt>> {
+    let args = match udt_args {
+        Some(args) => parse_hex(args)?,
+        None if amount > 0 => bail!("udt args is required for udt amount"),
+        None if xudt.is_some() => bail!("udt args is required for xudt"),
+        None => return Ok(None),
+    };
+    let (code_hash, cell_dep) = match xudt {
+        Some(xudt) => {
+            check_xudt_args(&args)?;
+            let cell_dep = CellDep::new_builder()
+                .out_point(xudt.cell_dep.clone())
+                .dep_type(DepType::Code.into())
+                .build();
+            (xudt.code_hash.pack(), cell_dep)
+        }
+        None => (
+            ctx.rpc_client
+                .rollup_context
+                .rollup_config
+                .l1_sudt_script_type_hash(),
+            ctx.contracts_dep.l1_sudt_type.clone().into(),
+        ),
+    };
+    let type_script = Script::new_builder()
+        .code_hash(code_hash)
+        .hash_type(ScriptHashType::Type.into())
+        .args(args.pack())
+        .build();
+    Ok(Some(Udt {
+        type_script,
+        amount,
+        cell_dep,
+    }))
+}
+
+/// Only xUDT without extension scripts is supported, extension scripts need their own cell deps
+/// and witnesses
+fn check_xudt_args(args: &[u8]) -> Result<()> {
+    match args.len() {
+        XUDT_OWNER_LOCK_HASH_SIZE => Ok(()),
+        len if len == XUDT_OWNER_LOCK_HASH_SIZE + XUDT_FLAGS_SIZE => {
+            let mut flags = [0u8; XUDT_FLAGS_SIZE];
+            flags.copy_from_slice(&args[XUDT_OWNER_LOCK_HASH_SIZE..]);
+            let flags = u32::from_le_bytes(flags);
+            // the highest 3 bits are owner mode flags, the other bits select extension scripts
+            if flags & 0x1fff_ffff != 0 {
+                bail!(
+                    "xudt extension scripts aren't supported, flags {:#x}",
+                    flags
+                );
+            }
+            Ok(())
+        }
+        len if len > XUDT_OWNER_LOCK_HASH_SIZE + XUDT_FLAGS_SIZE => {
+            bail!("xudt extension scripts aren't supported")
+        }
+        len => bail!("invalid xudt args length {}", len),
+    }
+}
+
+fn udt_output(lock: Script, capacity: u64, udt: Option<(&Script, u128)>) -> (CellOutput, Bytes) {
+    let output = CellOutput::new_builder()
+        .lock(lock)
+        .capacity(capacity.pack());
+    match udt {
+        Some((udt_type, amount)) => {
+            let output = output.type_(Some(udt_type.clone()).pack()).build();
+            (output, Bytes::from(amount.to_le_bytes().to_vec()))
+        }
+        None => (output.build(), Bytes::new()),
+    }
+}
+
+/// sUDT data and xUDT data both start with a 16 bytes amount
+fn udt_amount(data: &[u8]) -> u128 {
+    let mut buf = [0u8; 16];
+    if data.len() >= buf.len() {
+        buf.copy_from_slice(&data[..16]);
+    }
+    u128::from_le_bytes(buf)
+}
+
+/// Returns require_first_n, threshold and pubkey hashes
+fn parse_multisig_config(config: &[u8]) -> Result<(usize, usize, Vec<[u8; 20]>)> {
+    if config.len() < MULTISIG_HEADER_SIZE || config[0] != 0 {
+        bail!("invalid multisig config");
+    }
+    let require_first_n = config[1] as usize;
+    let threshold = config[2] as usize;
+    let pubkeys_count = config[3] as usize;
+    if config.len() != MULTISIG_HEADER_SIZE + BLAKE160_SIZE * pubkeys_count
+        || threshold == 0
+        || threshold > pubkeys_count
+        || require_first_n > threshold
+    {
+        bail!("invalid multisig config");
+    }
+    let pubkey_hashes = config[MULTISIG_HEADER_SIZE..]
+        .chunks(BLAKE160_SIZE)
+        .map(|hash| {
+            let mut buf = [0u8; 20];
+            buf.copy_from_slice(hash);
+            buf
+        })
+        .collect();
+    Ok((require_first_n, threshold, pubkey_hashes))
+}
+
+fn multisig_config(signing_group: &SigningGroup) -> Result<&[u8]> {
+    signing_group
+        .multisig_config
+        .as_ref()
+        .map(|config| config.as_bytes())
+        .ok_or_else(|| anyhow!("multisig config not found"))
+}
+
+fn required_signatures(signing_group: &SigningGroup) -> Result<usize> {
+    match signing_group.kind {
+        L1LockKind::Sighash | L1LockKind::OmniLock => Ok(1),
+        L1LockKind::Multisig => Ok(parse_multisig_config(multisig_config(signing_group)?)?.1),
+    }
+}
+
+/// Blake160 pubkey hashes which can sign the group
+fn signer_pubkey_hashes(signing_group: &SigningGroup) -> Result<Vec<[u8; 20]>> {
+    let args = signing_group.lock.args.as_bytes();
+    let mut buf = [0u8; 20];
+    match signing_group.kind {
+        L1LockKind::Sighash => {
+            buf.copy_from_slice(&args[..BLAKE160_SIZE]);
+            Ok(vec![buf])
+        }
+        L1LockKind::OmniLock => {
+            buf.copy_from_slice(&args[1..1 + BLAKE160_SIZE]);
+            Ok(vec![buf])
+        }
+        L1LockKind::Multisig => {
+            let config = multisig_config(signing_group)?;
+            if blake160(config) != args[..BLAKE160_SIZE] {
+                bail!("multisig config doesn't match lock args");
+            }
+            Ok(parse_multisig_config(config)?.2)
+        }
+    }
+}
+
+fn attach_signature(signing_group: &mut SigningGroup, signature: Vec<u8>) -> Result<()> {
+    let pubkey_hash = recover_pubkey_hash(&signing_group.message, &signature)?;
+    if !signer_pubkey_hashes(signing_group)?.contains(&pubkey_hash) {
+        bail!(
+            "signer isn't allowed by lock 0x{}",
+            hex::encode(signing_group.lock.args.as_bytes())
+        );
+    }
+    for signed in signing_group.signatures.iter() {
+        if recover_pubkey_hash(&signing_group.message, signed.as_bytes())? == pubkey_hash {
+            bail!("duplicate signature");
+        }
+    }
+    if signing_group.signatures.len() >= required_signatures(signing_group)? {
+        bail!("signatures are enough");
+    }
+    signing_group
+        .signatures
+        .push(JsonBytes::from_vec(signature));
+    Ok(())
+}
+
+/// Multisig config followed by the signatures, signatures must be in the order of pubkeys in
+/// multisig config
+fn multisig_witness_lock(signing_group: &SigningGroup, required: usize) -> Result<Bytes> {
+    let pubkey_hashes = signer_pubkey_hashes(signing_group)?;
+    let mut signatures = signing_group
+        .signatures
+        .iter()
+        .map(|signature| {
+            let pubkey_hash = recover_pubkey_hash(&signing_group.message, signature.as_bytes())?;
+            let position = pubkey_hashes
+                .iter()
+                .position(|hash| hash == &pubkey_hash)
+                .ok_or_else(|| anyhow!("unknown multisig signer"))?;
+            Ok((position, signature.as_bytes()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    signatures.sort_by_key(|(position, _)| *position);
+
+    let mut lock = multisig_config(signing_group)?.to_vec();
+    for (_position, signature) in signatures.into_iter().take(required) {
+        lock.extend_from_slice(signature);
+    }
+    Ok(Bytes::from(lock))
+}
+
+fn recover_pubkey_hash(message: &H256, signature: &[u8]) -> Result<[u8; 20]> {
+    if signature.len() != SIGNATURE_SIZE {
+        bail!("invalid signature length {}", signature.len());
+    }
+    let signature = SecpSignature::from_slice(signature)?;
+    let pubkey = signature.recover(message)?;
+    Ok(blake160(&pubkey.serialize()))
+}
+
+/// The message must be calculated from the transaction in file
+fn check_message(tx: &Transaction, signing_group: &SigningGroup) -> Result<()> {
+    if signing_group.input_indexes.is_empty() {
+        bail!("empty signing group");
+    }
+    let message: H256 = sighash_message(tx, &signing_group.input_indexes).into();
+    if message != signing_group.message {
+        bail!("message doesn't match transaction");
+    }
+    Ok(())
+}
+
+fn blake160(data: &[u8]) -> [u8; 20] {
+    let mut hasher = new_blake2b();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    let mut buf = [0u8; 20];
+    buf.copy_from_slice(&hash[..BLAKE160_SIZE]);
+    buf
+}
+
+fn parse_hex(s: &str) -> Result<Bytes> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x"))?;
+    Ok(Bytes::from(bytes))
+}
+
+fn to_gw_tx(json_tx: &ckb_jsonrpc_types::Transaction) -> Transaction {
+    let tx: ckb_types::packed::Transaction = json_tx.clone().into();
+    Transaction::new_unchecked(tx.as_bytes())
+}
+
+fn read_unsigned_tx(tx_path: &Path) -> Result<UnsignedTx> {
+    let content = std::fs::read_to_string(tx_path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn write_unsigned_tx(output_path: &Path, unsigned_tx: &UnsignedTx) -> Result<()> {
+    std::fs::write(output_path, serde_json::to_string_pretty(unsigned_tx)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_xudt_args() {
+        let owner_lock_hash = [1u8; XUDT_OWNER_LOCK_HASH_SIZE];
+        let with_flags = |flags: u32| {
+            let mut args = owner_lock_hash.to_vec();
+            args.extend_from_slice(&flags.to_le_bytes());
+            args
+        };
+        assert!(check_xudt_args(&owner_lock_hash).is_ok());
+        assert!(check_xudt_args(&with_flags(0)).is_ok());
+        // owner mode by input type
+        assert!(check_xudt_args(&with_flags(0x8000_0000)).is_ok());
+        // extension scripts
+        assert!(check_xudt_args(&with_flags(1)).is_err());
+        let mut args = with_flags(2);
+        args.extend_from_slice(&[0u8; 24]);
+        assert!(check_xudt_args(&args).is_err());
+        assert!(check_xudt_args(&owner_lock_hash[..20]).is_err());
+    }
+
+    const MESSAGE: [u8; 32] = [42u8; 32];
+
+    fn privkey(n: u8) -> Privkey {
+        Privkey::from(H256([n; 32]))
+    }
+
+    fn pubkey_hash(privkey: &Privkey) -> [u8; 20] {
+        blake160(&privkey.pubkey().unwrap().serialize())
+    }
+
+    fn sign(privkey: &Privkey) -> Vec<u8> {
+        privkey
+            .sign_recoverable(&H256(MESSAGE))
+            .unwrap()
+            .serialize()
+    }
+
+    // 2 of 3 multisig config of private keys 1, 2 and 3
+    fn multisig_config_2_of_3() -> Vec<u8> {
+        let mut config = vec![0, 0, 2, 3];
+        for n in 1..=3 {
+            config.extend_from_slice(&pubkey_hash(&privkey(n)));
+        }
+        config
+    }
+
+    fn signing_group(kind: L1LockKind, args: Vec<u8>, config: Option<Vec<u8>>) -> SigningGroup {
+        SigningGroup {
+            lock: ckb_jsonrpc_types::Script {
+                code_hash: H256::default(),
+                hash_type: ckb_jsonrpc_types::ScriptHashType::Type,
+                args: JsonBytes::from_vec(args),
+            },
+            kind,
+            input_indexes: vec![0],
+            message: H256(MESSAGE),
+            multisig_config: config.map(JsonBytes::from_vec),
+            signatures: Vec::new(),
+        }
+    }
+
+    fn multisig_group() -> SigningGroup {
+        let config = multisig_config_2_of_3();
+        signing_group(
+            L1LockKind::Multisig,
+            blake160(&config).to_vec(),
+            Some(config),
+        )
+    }
+
+    #[test]
+    fn test_parse_multisig_config() {
+        let config = multisig_config_2_of_3();
+        let (require_first_n, threshold, pubkey_hashes) = parse_multisig_config(&config).unwrap();
+        assert_eq!(require_first_n, 0);
+        assert_eq!(threshold, 2);
+        let expected: Vec<_> = (1..=3).map(|n| pubkey_hash(&privkey(n))).collect();
+        assert_eq!(pubkey_hashes, expected);
+
+        fn invalid(update: impl FnOnce(&mut Vec<u8>)) -> bool {
+            let mut config = multisig_config_2_of_3();
+            update(&mut config);
+            parse_multisig_config(&config).is_err()
+        }
+        // reserved byte
+        assert!(invalid(|config| config[0] = 1));
+        // require first n bigger than threshold
+        assert!(invalid(|config| config[1] = 3));
+        // zero threshold
+        assert!(invalid(|config| config[2] = 0));
+        // threshold bigger than pubkeys count
+        assert!(invalid(|config| config[2] = 4));
+        // pubkeys count doesn't match the length
+        assert!(invalid(|config| config[3] = 2));
+        assert!(invalid(|config| config.push(0)));
+        assert!(invalid(|config| config.truncate(3)));
+    }
+
+    #[test]
+    fn test_recover_pubkey_hash() {
+        let signature = sign(&privkey(1));
+        let recovered = recover_pubkey_hash(&H256(MESSAGE), &signature).unwrap();
+        assert_eq!(recovered, pubkey_hash(&privkey(1)));
+
+        let recovered = recover_pubkey_hash(&H256([0u8; 32]), &signature).unwrap();
+        assert_ne!(recovered, pubkey_hash(&privkey(1)));
+
+        assert!(recover_pubkey_hash(&H256(MESSAGE), &signature[..64]).is_err());
+    }
+
+    #[test]
+    fn test_attach_signature() {
+        let privkey_1 = privkey(1);
+        let mut group = signing_group(L1LockKind::Sighash, pubkey_hash(&privkey_1).to_vec(), None);
+        assert!(attach_signature(&mut group, sign(&privkey(2))).is_err());
+        attach_signature(&mut group, sign(&privkey_1)).unwrap();
+        assert_eq!(group.signatures.len(), 1);
+
+        let mut group = multisig_group();
+        // not a signer
+        assert!(attach_signature(&mut group, sign(&privkey(4))).is_err());
+        attach_signature(&mut group, sign(&privkey(1))).unwrap();
+        // duplicate signature
+        assert!(attach_signature(&mut group, sign(&privkey(1))).is_err());
+        attach_signature(&mut group, sign(&privkey(3))).unwrap();
+        // signatures are enough
+        assert!(attach_signature(&mut group, sign(&privkey(2))).is_err());
+        assert_eq!(group.signatures.len(), 2);
+
+        // multisig config doesn't match lock args
+        let mut group = multisig_group();
+        group.lock.args = JsonBytes::from_vec(vec![0u8; BLAKE160_SIZE]);
+        assert!(attach_signature(&mut group, sign(&privkey(1))).is_err());
+    }
+
+    #[test]
+    fn test_multisig_witness_lock_order() {
+        let mut group = multisig_group();
+        attach_signature(&mut group, sign(&privkey(3))).unwrap();
+        attach_signature(&mut group, sign(&privkey(1))).unwrap();
+
+        let lock = multisig_witness_lock(&group, 2).unwrap();
+        let expected = [
+            multisig_config_2_of_3(),
+            sign(&privkey(1)),
+            sign(&privkey(3)),
+        ]
+        .concat();
+        assert_eq!(lock.to_vec(), expected);
+
+        // signer not in multisig config
+        group
+            .signatures
+            .push(JsonBytes::from_vec(sign(&privkey(4))));
+        assert!(multisig_witness_lock(&group, 2).is_err());
+    }
+}
@@ -7,12 +7,17 @@ mod deploy_genesis;
 mod deploy_scripts;
 mod deposit_ckb;
 mod dump_tx;
+mod fast_withdrawal;
 mod generate_config;
 mod get_balance;
 pub mod godwoken_rpc;
 mod hasher;
+mod keystore;
+mod l1_tx;
+mod multisig;
 mod polyjuice;
 mod prepare_scripts;
+mod profile_tx;
 mod report_accounts;
 mod setup;
 mod stat;
@@ -32,7 +37,7 @@ use dump_tx::ChallengeBlock;
 use generate_config::GenerateNodeConfigArgs;
 use godwoken_rpc::GodwokenRpcClient;
 use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
-use gw_jsonrpc_types::godwoken::ChallengeTargetType;
+use gw_jsonrpc_types::{ckb_jsonrpc_types::JsonBytes, godwoken::ChallengeTargetType};
 use gw_rpc_client::indexer_client::CKBIndexerClient;
 use std::{
     collections::HashMap,
@@ -86,6 +91,15 @@ async fn main() -> Result<()> {
         .takes_value(true)
         .required(true)
         .help("The config.toml file path");
+    let arg_passphrase_env = Arg::with_name("passphrase-env")
+        .long("passphrase-env")
+        .takes_value(true)
+        .default_value(gw_utils::keystore::PASSPHRASE_ENV)
+        .help("The env var of the keystore passphrase");
+    let arg_passphrase_fd = Arg::with_name("passphrase-fd")
+        .long("passphrase-fd")
+        .takes_value(true)
+        .help("Read the keystore passphrase from the file descriptor instead of the env var");
     let arg_godwoken_rpc_url = Arg::with_name("godwoken-rpc-url")
         .short('g')
         .long("godwoken-rpc-url")
@@ -376,6 +390,96 @@ async fn main() -> Result<()> {
                         .help("l1 sudt script hash, default for withdrawal CKB"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("fast-withdrawal-buy")
+                .about("Buy a pending withdrawal as liquidity provider, write the trade tx to a file for the owner to accept")
+                .arg(arg_privkey_path.clone())
+                .arg(arg_config_path.clone())
+                .arg(Arg::with_name("lp-ckb-address").long("lp-ckb-address").takes_value(true).required(true).help("Liquidity provider ckb address, pays the price and takes over the withdrawal"))
+                .arg(Arg::with_name("tx-hash").long("tx-hash").takes_value(true).required(true).help("The tx-hash of the withdrawal cell"))
+                .arg(Arg::with_name("index").long("index").takes_value(true).required(true).help("The index of the withdrawal cell"))
+                .arg(Arg::with_name("price").long("price").takes_value(true).required(true).help("CKB paid to the withdrawal owner"))
+                .arg(Arg::with_name("fee-rate").long("fee-rate").takes_value(true).required(false).default_value("1000").help("tx fee rate"))
+                .arg(Arg::with_name("output-path").long("output-path").takes_value(true).required(true).help("The trade tx json file path"))
+        )
+        .subcommand(
+            SubCommand::with_name("fast-withdrawal-accept")
+                .about("Sign and send a trade tx as withdrawal owner")
+                .arg(arg_privkey_path.clone())
+                .arg(arg_config_path.clone())
+                .arg(Arg::with_name("owner-ckb-address").long("owner-ckb-address").takes_value(true).required(true).help("Withdrawal owner ckb address, only secp256k1 sighash lock is supported"))
+                .arg(Arg::with_name("tx-path").long("tx-path").takes_value(true).required(true).help("The trade tx json file path"))
+                .arg(Arg::with_name("min-price").long("min-price").takes_value(true).required(true).help("Minimal CKB paid to the owner"))
+        )
+        .subcommand(
+            SubCommand::with_name("fast-withdrawal-settle")
+                .about("Unlock a finalized withdrawal bought by liquidity provider")
+                .arg(arg_privkey_path.clone())
+                .arg(arg_config_path.clone())
+                .arg(Arg::with_name("lp-ckb-address").long("lp-ckb-address").takes_value(true).required(true).help("Liquidity provider ckb address"))
+                .arg(Arg::with_name("tx-hash").long("tx-hash").takes_value(true).required(true).help("The tx-hash of the withdrawal cell"))
+                .arg(Arg::with_name("index").long("index").takes_value(true).required(true).help("The index of the withdrawal cell"))
+                .arg(Arg::with_name("fee-rate").long("fee-rate").takes_value(true).required(false).default_value("1000").help("tx fee rate"))
+        )
+        .subcommand(
+            SubCommand::with_name("build-deposit-tx")
+                .about("Build an unsigned tx which deposits CKB / sUDT from a sighash, multisig or omni-lock L1 lock, xUDT can't be deposited")
+                .arg(arg_config_path.clone())
+                .arg(Arg::with_name("from-address").long("from-address").takes_value(true).required(true).help("The ckb address pays the deposit, it owns the deposit cell"))
+                .arg(Arg::with_name("eth-address").short('e').long("eth-address").takes_value(true).required(true).help("Target eth address"))
+                .arg(Arg::with_name("capacity").short('c').long("capacity").takes_value(true).required(true).help("CKB capacity to deposit"))
+                .arg(Arg::with_name("udt-args").long("udt-args").takes_value(true).required(false).help("sUDT type args, the code hash is the l1 sudt type hash of the rollup"))
+                .arg(Arg::with_name("amount").short('m').long("amount").takes_value(true).default_value("0").help("sUDT amount to deposit"))
+                .arg(Arg::with_name("multisig-config").long("multisig-config").takes_value(true).required(false).help("Multisig config of the multisig lock"))
+                .arg(Arg::with_name("fee-rate").long("fee-rate").takes_value(true).required(false).default_value("1000").help("tx fee rate"))
+                .arg(Arg::with_name("output-path").long("output-path").takes_value(true).required(true).help("The unsigned tx json file path"))
+        )
+        .subcommand(
+            SubCommand::with_name("build-l1-transfer-tx")
+                .about("Build an unsigned tx which transfers CKB / sUDT / xUDT from a sighash, multisig or omni-lock L1 lock")
+                .arg(arg_config_path.clone())
+                .arg(Arg::with_name("from-address").long("from-address").takes_value(true).required(true).help("The ckb address pays the transfer"))
+                .arg(Arg::with_name("to-address").long("to-address").takes_value(true).required(true).help("The receiver ckb address"))
+                .arg(Arg::with_name("capacity").short('c').long("capacity").takes_value(true).required(true).help("CKB capacity to transfer"))
+                .arg(Arg::with_name("udt-args").long("udt-args").takes_value(true).required(false).help("UDT type args, the code hash is the l1 sudt type hash of the rollup, or the xudt code hash"))
+                .arg(Arg::with_name("amount").short('m').long("amount").takes_value(true).default_value("0").help("UDT amount to transfer"))
+                .arg(Arg::with_name("xudt-code-hash").long("xudt-code-hash").takes_value(true).required(false).requires_all(&["udt-args", "xudt-dep-tx-hash", "xudt-dep-index"]).help("Transfer xUDT, the type hash of the xUDT script, xUDT with extension scripts isn't supported"))
+                .arg(Arg::with_name("xudt-dep-tx-hash").long("xudt-dep-tx-hash").takes_value(true).required(false).requires("xudt-code-hash").help("The tx hash of the xUDT script cell dep"))
+                .arg(Arg::with_name("xudt-dep-index").long("xudt-dep-index").takes_value(true).required(false).requires("xudt-code-hash").help("The index of the xUDT script cell dep"))
+                .arg(Arg::with_name("multisig-config").long("multisig-config").takes_value(true).required(false).help("Multisig config of the multisig lock"))
+                .arg(Arg::with_name("fee-rate").long("fee-rate").takes_value(true).required(false).default_value("1000").help("tx fee rate"))
+                .arg(Arg::with_name("output-path").long("output-path").takes_value(true).required(true).help("The unsigned tx json file path"))
+        )
+        .subcommand(
+            SubCommand::with_name("sign-l1-tx")
+                .about("Sign an unsigned tx by private key, or attach a signature produced offline")
+                .arg(Arg::with_name("tx-path").long("tx-path").takes_value(true).required(true).help("The unsigned tx json file path, signatures are written back to it"))
+                .arg(Arg::with_name("privkey-path").short('k').long("privkey-path").takes_value(true).required_unless_present("signature").conflicts_with("signature").help("The private key file path"))
+                .arg(Arg::with_name("signature").long("signature").takes_value(true).help("A signature of the signing group message"))
+                .arg(Arg::with_name("group").long("group").takes_value(true).required(false).help("The signing group index, default to groups signable by the private key, or group 0 for signature"))
+        )
+        .subcommand(
+            SubCommand::with_name("send-l1-tx")
+                .about("Put signatures into witnesses and send the tx")
+                .arg(arg_config_path.clone())
+                .arg(Arg::with_name("tx-path").long("tx-path").takes_value(true).required(true).help("The signed tx json file path"))
+        )
+        .subcommand(
+            SubCommand::with_name("keystore-import")
+                .about("Encrypt a plaintext private key file into a keystore file (Ethereum keystore v3)")
+                .arg(arg_privkey_path.clone())
+                .arg(Arg::with_name("keystore-path").long("keystore-path").takes_value(true).required(true).help("The output keystore file path"))
+                .arg(arg_passphrase_env.clone())
+                .arg(arg_passphrase_fd.clone())
+        )
+        .subcommand(
+            SubCommand::with_name("keystore-export")
+                .about("Decrypt a keystore file into a plaintext private key file")
+                .arg(Arg::with_name("keystore-path").long("keystore-path").takes_value(true).required(true).help("The keystore file path"))
+                .arg(Arg::with_name("privkey-path").short('k').long("privkey-path").takes_value(true).required(true).help("The output private key file path"))
+                .arg(arg_passphrase_env.clone())
+                .arg(arg_passphrase_fd.clone())
+        )
         .subcommand(
             SubCommand::with_name("setup")
                 .about("Prepare scripts, deploy scripts, setup nodes, deploy genesis and generate configs")
@@ -799,6 +903,39 @@ async fn main() -> Result<()> {
                         .help("output file"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("profile-tx")
+                .about("Profile execution cycles of a layer2 tx to a flamegraph folded stacks file")
+                .arg(arg_godwoken_rpc_url.clone())
+                .arg(
+                    Arg::with_name("tx-hash")
+                        .long("tx-hash")
+                        .takes_value(true)
+                        .required_unless_present("l2tx")
+                        .conflicts_with("l2tx")
+                        .help("Hash of a committed tx"),
+                )
+                .arg(
+                    Arg::with_name("l2tx")
+                        .long("l2tx")
+                        .takes_value(true)
+                        .help("Serialized tx to execute on the mem pool state"),
+                )
+                .arg(
+                    Arg::with_name("generator-elf")
+                        .long("generator-elf")
+                        .takes_value(true)
+                        .help("Unstripped generator of the tx backend, used to symbolize the stacks"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short('o')
+                        .long("output")
+                        .takes_value(true)
+                        .required(true)
+                        .help("output file"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("report-accounts")
                 .about("Report account to csv files")
@@ -862,6 +999,75 @@ async fn main() -> Result<()> {
                         .required(true)
                         .help("input file"),
                 ))
+        .subcommand(
+            SubCommand::with_name("multisig-address")
+                .about("Output multisig script and layer2 lock args")
+                .arg(
+                    Arg::with_name("require-first-n")
+                        .long("require-first-n")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("The first n signers are required"),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .short('t')
+                        .long("threshold")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Number of signatures required"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .short('a')
+                        .long("address")
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(true)
+                        .help("Eth address of the signer"),
+                )
+                .arg(
+                    Arg::with_name("rollup-type-hash")
+                        .short('r')
+                        .long("rollup-type-hash")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Rollup type hash"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("multisig-sign")
+                .about("Sign a message as a multisig signer")
+                .arg(arg_privkey_path.clone())
+                .arg(
+                    Arg::with_name("message")
+                        .short('m')
+                        .long("message")
+                        .takes_value(true)
+                        .required(true)
+                        .help("32 bytes signing message"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("multisig-combine")
+                .about("Combine multisig script and signatures into the signature field")
+                .arg(
+                    Arg::with_name("multisig-script")
+                        .long("multisig-script")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Multisig script"),
+                )
+                .arg(
+                    Arg::with_name("signature")
+                        .short('s')
+                        .long("signature")
+                        .takes_value(true)
+                        .required(true)
+                        .multiple(true)
+                        .help("Signature of the signer"),
+                ),
+        )
         ;
 
     let matches = app.clone().get_matches();
@@ -1111,6 +1317,144 @@ async fn main() -> Result<()> {
                 std::process::exit(-1);
             };
         }
+        Some(("fast-withdrawal-buy", m)) => {
+            let privkey_path = PathBuf::from(m.value_of("privkey-path").unwrap());
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let lp_ckb_address = m.value_of("lp-ckb-address").unwrap();
+            let withdrawal_out_point = fast_withdrawal::parse_out_point(
+                m.value_of("tx-hash").unwrap(),
+                m.value_of("index").unwrap(),
+            )?;
+            let price = m.value_of("price").unwrap();
+            let fee_rate: u64 = m.value_of("fee-rate").unwrap().parse()?;
+            let output_path = Path::new(m.value_of("output-path").unwrap());
+
+            fast_withdrawal::buy_withdrawal(fast_withdrawal::BuyWithdrawalArgs {
+                config_path,
+                privkey_path,
+                lp_ckb_address,
+                withdrawal_out_point,
+                price,
+                fee_rate,
+                output_path,
+            })
+            .await?;
+        }
+        Some(("fast-withdrawal-accept", m)) => {
+            let privkey_path = PathBuf::from(m.value_of("privkey-path").unwrap());
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let owner_ckb_address = m.value_of("owner-ckb-address").unwrap();
+            let tx_path = Path::new(m.value_of("tx-path").unwrap());
+            let min_price = m.value_of("min-price").unwrap();
+
+            fast_withdrawal::accept_withdrawal_trade(fast_withdrawal::AcceptWithdrawalTradeArgs {
+                config_path,
+                privkey_path,
+                owner_ckb_address,
+                tx_path,
+                min_price,
+            })
+            .await?;
+        }
+        Some(("fast-withdrawal-settle", m)) => {
+            let privkey_path = PathBuf::from(m.value_of("privkey-path").unwrap());
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let lp_ckb_address = m.value_of("lp-ckb-address").unwrap();
+            let withdrawal_out_point = fast_withdrawal::parse_out_point(
+                m.value_of("tx-hash").unwrap(),
+                m.value_of("index").unwrap(),
+            )?;
+            let fee_rate: u64 = m.value_of("fee-rate").unwrap().parse()?;
+
+            fast_withdrawal::settle_withdrawal(fast_withdrawal::SettleWithdrawalArgs {
+                config_path,
+                privkey_path,
+                lp_ckb_address,
+                withdrawal_out_point,
+                fee_rate,
+            })
+            .await?;
+        }
+        Some(("build-deposit-tx", m)) => {
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let amount: u128 = m.value_of("amount").unwrap().parse()?;
+            let fee_rate: u64 = m.value_of("fee-rate").unwrap().parse()?;
+            let output_path = Path::new(m.value_of("output-path").unwrap());
+
+            l1_tx::build_deposit_tx(l1_tx::BuildDepositTxArgs {
+                config_path,
+                from_address: m.value_of("from-address").unwrap(),
+                eth_address: m.value_of("eth-address").unwrap(),
+                capacity: m.value_of("capacity").unwrap(),
+                udt_args: m.value_of("udt-args"),
+                amount,
+                multisig_config: m.value_of("multisig-config"),
+                fee_rate,
+                output_path,
+            })
+            .await?;
+        }
+        Some(("build-l1-transfer-tx", m)) => {
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let amount: u128 = m.value_of("amount").unwrap().parse()?;
+            let fee_rate: u64 = m.value_of("fee-rate").unwrap().parse()?;
+            let output_path = Path::new(m.value_of("output-path").unwrap());
+
+            let xudt = match m.value_of("xudt-code-hash") {
+                Some(code_hash) => Some(l1_tx::Xudt {
+                    code_hash: cli_args::to_h256(code_hash)?,
+                    cell_dep: fast_withdrawal::parse_out_point(
+                        m.value_of("xudt-dep-tx-hash").unwrap(),
+                        m.value_of("xudt-dep-index").unwrap(),
+                    )?,
+                }),
+                None => None,
+            };
+
+            l1_tx::build_transfer_tx(l1_tx::BuildTransferTxArgs {
+                config_path,
+                from_address: m.value_of("from-address").unwrap(),
+                to_address: m.value_of("to-address").unwrap(),
+                capacity: m.value_of("capacity").unwrap(),
+                udt_args: m.value_of("udt-args"),
+                amount,
+                xudt,
+                multisig_config: m.value_of("multisig-config"),
+                fee_rate,
+                output_path,
+            })
+            .await?;
+        }
+        Some(("sign-l1-tx", m)) => {
+            let group = m.value_of("group").map(str::parse).transpose()?;
+
+            l1_tx::sign_tx(l1_tx::SignTxArgs {
+                tx_path: Path::new(m.value_of("tx-path").unwrap()),
+                privkey_path: m.value_of("privkey-path").map(PathBuf::from),
+                signature: m.value_of("signature"),
+                group,
+            })?;
+        }
+        Some(("send-l1-tx", m)) => {
+            let config_path = Path::new(m.value_of("config-path").unwrap());
+            let tx_path = Path::new(m.value_of("tx-path").unwrap());
+
+            l1_tx::send_tx(config_path, tx_path).await?;
+        }
+        Some(("keystore-import", m)) => {
+            let privkey_path = Path::new(m.value_of("privkey-path").unwrap());
+            let keystore_path = Path::new(m.value_of("keystore-path").unwrap());
+            let passphrase = passphrase_source(m)?;
+
+            keystore::import_key(privkey_path, keystore_path, &passphrase)?;
+        }
+        Some(("keystore-export", m)) => {
+            let keystore_path = Path::new(m.value_of("keystore-path").unwrap());
+            let privkey_path = Path::new(m.value_of("privkey-path").unwrap());
+            let passphrase = passphrase_source(m)?;
+
+            keystore::export_key(keystore_path, privkey_path, &passphrase)?;
+        }
         Some(("setup", m)) => {
             let ckb_rpc_url = m.value_of("ckb-rpc-url").unwrap();
             let indexer_url = m.value_of("indexer-rpc-url").unwrap();
@@ -1441,6 +1785,27 @@ async fn main() -> Result<()> {
                 std::process::exit(-1);
             };
         }
+        Some(("profile-tx", m)) => {
+            let godwoken_rpc_url = m.value_of("godwoken-rpc-url").unwrap();
+            let target = match m.value_of("tx-hash") {
+                Some(tx_hash) => {
+                    profile_tx::ProfileTarget::TxHash(cli_args::to_h256(tx_hash)?.into())
+                }
+                None => {
+                    let l2tx = hex::decode(m.value_of("l2tx").unwrap().trim_start_matches("0x"))?;
+                    profile_tx::ProfileTarget::L2Transaction(JsonBytes::from_vec(l2tx))
+                }
+            };
+            let generator_elf = m.value_of("generator-elf").map(Path::new);
+            let output = Path::new(m.value_of("output").unwrap());
+
+            if let Err(err) =
+                profile_tx::profile_tx(godwoken_rpc_url, target, generator_elf, output).await
+            {
+                log::error!("Profile tx: {}", err);
+                std::process::exit(-1);
+            };
+        }
         Some(("stat-custodian-ckb", m)) => {
             let indexer_rpc_url = m.value_of("indexer-rpc-url").unwrap();
             let rollup_type_hash = cli_args::to_h256(m.value_of("rollup-type-hash").unwrap())?;
@@ -1555,6 +1920,45 @@ async fn main() -> Result<()> {
                 std::process::exit(-1);
             };
         }
+        Some(("multisig-address", m)) => {
+            let require_first_n = m
+                .value_of("require-first-n")
+                .unwrap()
+                .parse()
+                .expect("require first n format error");
+            let threshold = m
+                .value_of("threshold")
+                .unwrap()
+                .parse()
+                .expect("threshold format error");
+            let addresses: Vec<_> = m.values_of("address").into_iter().flatten().collect();
+            let rollup_type_hash = m.value_of("rollup-type-hash").unwrap();
+
+            if let Err(err) =
+                multisig::multisig_address(require_first_n, threshold, &addresses, rollup_type_hash)
+            {
+                log::error!("Multisig address error: {}", err);
+                std::process::exit(-1);
+            };
+        }
+        Some(("multisig-sign", m)) => {
+            let privkey_path = Path::new(m.value_of("privkey-path").unwrap());
+            let message = m.value_of("message").unwrap();
+
+            if let Err(err) = multisig::multisig_sign(privkey_path, message) {
+                log::error!("Multisig sign error: {}", err);
+                std::process::exit(-1);
+            };
+        }
+        Some(("multisig-combine", m)) => {
+            let multisig_script = m.value_of("multisig-script").unwrap();
+            let signatures: Vec<_> = m.values_of("signature").into_iter().flatten().collect();
+
+            if let Err(err) = multisig::multisig_combine(multisig_script, &signatures) {
+                log::error!("Multisig combine error: {}", err);
+                std::process::exit(-1);
+            };
+        }
         _ => {
             app.print_help().expect("print help");
         }
@@ -1573,3 +1977,12 @@ where
     std::fs::write(output_path, output_content.as_bytes()).expect("generate json file");
     println!("Generate file {:?}", output_path);
 }
+
+fn passphrase_source(m: &clap::ArgMatches) -> Result<gw_config::PassphraseSource> {
+    match m.value_of("passphrase-fd") {
+        Some(fd) => Ok(gw_config::PassphraseSource::Fd(fd.parse()?)),
+        None => Ok(gw_config::PassphraseSource::Env(
+            m.value_of("passphrase-env").unwrap().to_string(),
+        )),
+    }
+}
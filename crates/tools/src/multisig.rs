@@ -0,0 +1,80 @@
+use crate::account::{eth_sign, read_privkey};
+use anyhow::{anyhow, Result};
+use ckb_fixed_hash::H256;
+use gw_generator::account_lock_manage::multisig::{MultisigScript, SIGNATURE_SIZE};
+use std::{path::Path, str::FromStr};
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x"))?;
+    Ok(bytes)
+}
+
+/// Print multisig script and the layer2 lock args
+pub fn multisig_address(
+    require_first_n: u8,
+    threshold: u8,
+    addresses: &[&str],
+    rollup_type_hash: &str,
+) -> Result<()> {
+    let addresses = addresses
+        .iter()
+        .map(|addr| {
+            let addr = parse_hex(addr)?;
+            let mut buf = [0u8; 20];
+            if addr.len() != buf.len() {
+                return Err(anyhow!("invalid eth address length {}", addr.len()));
+            }
+            buf.copy_from_slice(&addr);
+            Ok(buf)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let script = MultisigScript::new(require_first_n, threshold, addresses)
+        .map_err(|err| anyhow!("{}", err))?;
+    let rollup_type_hash = H256::from_str(rollup_type_hash.trim_start_matches("0x"))?;
+
+    let multisig_hash = script.hash();
+    let mut lock_args = rollup_type_hash.as_bytes().to_vec();
+    lock_args.extend_from_slice(&multisig_hash);
+
+    println!("multisig script: 0x{}", hex::encode(script.serialize()));
+    println!("multisig hash: 0x{}", hex::encode(multisig_hash));
+    println!("lock args: 0x{}", hex::encode(lock_args));
+    Ok(())
+}
+
+/// Sign a 32 bytes signing message as one of the multisig signers
+pub fn multisig_sign(privkey_path: &Path, message: &str) -> Result<()> {
+    let privkey = read_privkey(privkey_path)?;
+    let message = H256::from_str(message.trim_start_matches("0x"))?;
+    let signature = eth_sign(&message, privkey)?;
+    println!("0x{}", hex::encode(signature));
+    Ok(())
+}
+
+/// Combine the multisig script and the collected signatures into the transaction signature
+pub fn multisig_combine(multisig_script: &str, signatures: &[&str]) -> Result<()> {
+    let multisig_script = parse_hex(multisig_script)?;
+    let (script, remain) =
+        MultisigScript::parse(&multisig_script).map_err(|err| anyhow!("{}", err))?;
+    if !remain.is_empty() {
+        return Err(anyhow!("invalid multisig script length"));
+    }
+    if signatures.len() != script.threshold as usize {
+        return Err(anyhow!(
+            "expect {} signatures, got {}",
+            script.threshold,
+            signatures.len()
+        ));
+    }
+
+    let mut signature = script.serialize();
+    for sig in signatures {
+        let sig = parse_hex(sig)?;
+        if sig.len() != SIGNATURE_SIZE {
+            return Err(anyhow!("invalid signature length {}", sig.len()));
+        }
+        signature.extend_from_slice(&sig);
+    }
+    println!("0x{}", hex::encode(signature));
+    Ok(())
+}
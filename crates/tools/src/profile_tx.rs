@@ -0,0 +1,58 @@
+use crate::godwoken_rpc::GodwokenRpcClient;
+
+use anyhow::{anyhow, Result};
+use ckb_fixed_hash::H256;
+use gw_generator::profile::{symbolize_folded, Symbols};
+use gw_jsonrpc_types::ckb_jsonrpc_types::JsonBytes;
+
+use std::{fs, path::Path};
+
+pub enum ProfileTarget {
+    /// Committed tx
+    TxHash(H256),
+    /// Serialized tx executed on the mem pool state
+    L2Transaction(JsonBytes),
+}
+
+/// Profile the execution cycles of a tx and write the folded stacks to output, frames are
+/// symbolized by the unstripped generator if it's given
+pub async fn profile_tx(
+    godwoken_rpc_url: &str,
+    target: ProfileTarget,
+    generator_elf: Option<&Path>,
+    output: &Path,
+) -> Result<()> {
+    let godwoken_rpc_client = GodwokenRpcClient::new(godwoken_rpc_url);
+    let profile = match target {
+        ProfileTarget::TxHash(tx_hash) => godwoken_rpc_client
+            .debug_profile_transaction(&tx_hash)
+            .await?
+            .ok_or_else(|| anyhow!("tx {:#x} isn't committed", tx_hash))?,
+        ProfileTarget::L2Transaction(l2tx) => {
+            godwoken_rpc_client
+                .debug_profile_l2transaction(l2tx)
+                .await?
+        }
+    };
+
+    if let Some(ref err) = profile.error {
+        log::warn!("tx execution error: {}", err);
+    }
+    log::info!(
+        "exit code: {}, execution cycles: {}, virtual cycles: {}",
+        profile.exit_code.value(),
+        profile.execution_cycles.value(),
+        profile.virtual_cycles.value()
+    );
+
+    let folded = match generator_elf {
+        Some(path) => {
+            let symbols = Symbols::parse(&fs::read(path)?)?;
+            symbolize_folded(&profile.folded_stacks, &symbols)
+        }
+        None => profile.folded_stacks,
+    };
+    fs::write(output, folded)?;
+
+    Ok(())
+}
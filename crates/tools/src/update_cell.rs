@@ -130,6 +130,8 @@ pub async fn update_cell<P: AsRef<Path>>(args: UpdateCellArgs<'_, P>) -> Result<
     let wallet = Wallet::from_config(&WalletConfig {
         privkey_path: pk_path,
         lock: payment_lock.into(),
+        passphrase: None,
+        remote_signer: None,
     })?;
     let tx = wallet.sign_tx_skeleton(tx_skeleton)?;
     let update_message = format!(
@@ -201,6 +201,9 @@ impl From<GlobalStateV0> for GlobalState {
 pub enum AllowedEoaType {
     Unknown,
     Eth,
+    Tron,
+    Multisig,
+    WebAuthn,
 }
 
 impl From<AllowedEoaType> for u8 {
@@ -217,6 +220,9 @@ impl TryFrom<u8> for AllowedEoaType {
         match value {
             0 => Ok(AllowedEoaType::Unknown),
             1 => Ok(AllowedEoaType::Eth),
+            2 => Ok(AllowedEoaType::Tron),
+            3 => Ok(AllowedEoaType::Multisig),
+            4 => Ok(AllowedEoaType::WebAuthn),
             n => Err(n),
         }
     }
@@ -8173,7 +8173,7 @@ impl ::core::default::Default for UnlockWithdrawalWitness {
     }
 }
 impl UnlockWithdrawalWitness {
-    pub const ITEMS_COUNT: usize = 2;
+    pub const ITEMS_COUNT: usize = 3;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -8182,6 +8182,7 @@ impl UnlockWithdrawalWitness {
         match self.item_id() {
             0 => UnlockWithdrawalViaFinalize::new_unchecked(inner).into(),
             1 => UnlockWithdrawalViaRevert::new_unchecked(inner).into(),
+            2 => UnlockWithdrawalViaTrade::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -8238,7 +8239,7 @@ impl<'r> ::core::fmt::Display for UnlockWithdrawalWitnessReader<'r> {
     }
 }
 impl<'r> UnlockWithdrawalWitnessReader<'r> {
-    pub const ITEMS_COUNT: usize = 2;
+    pub const ITEMS_COUNT: usize = 3;
     pub fn item_id(&self) -> molecule::Number {
         molecule::unpack_number(self.as_slice())
     }
@@ -8247,6 +8248,7 @@ impl<'r> UnlockWithdrawalWitnessReader<'r> {
         match self.item_id() {
             0 => UnlockWithdrawalViaFinalizeReader::new_unchecked(inner).into(),
             1 => UnlockWithdrawalViaRevertReader::new_unchecked(inner).into(),
+            2 => UnlockWithdrawalViaTradeReader::new_unchecked(inner).into(),
             _ => panic!("{}: invalid data", Self::NAME),
         }
     }
@@ -8274,6 +8276,7 @@ impl<'r> molecule::prelude::Reader<'r> for UnlockWithdrawalWitnessReader<'r> {
         match item_id {
             0 => UnlockWithdrawalViaFinalizeReader::verify(inner_slice, compatible),
             1 => UnlockWithdrawalViaRevertReader::verify(inner_slice, compatible),
+            2 => UnlockWithdrawalViaTradeReader::verify(inner_slice, compatible),
             _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
         }?;
         Ok(())
@@ -8282,7 +8285,7 @@ impl<'r> molecule::prelude::Reader<'r> for UnlockWithdrawalWitnessReader<'r> {
 #[derive(Debug, Default)]
 pub struct UnlockWithdrawalWitnessBuilder(pub(crate) UnlockWithdrawalWitnessUnion);
 impl UnlockWithdrawalWitnessBuilder {
-    pub const ITEMS_COUNT: usize = 2;
+    pub const ITEMS_COUNT: usize = 3;
     pub fn set<I>(mut self, v: I) -> Self
     where
         I: ::core::convert::Into<UnlockWithdrawalWitnessUnion>,
@@ -8312,11 +8315,13 @@ impl molecule::prelude::Builder for UnlockWithdrawalWitnessBuilder {
 pub enum UnlockWithdrawalWitnessUnion {
     UnlockWithdrawalViaFinalize(UnlockWithdrawalViaFinalize),
     UnlockWithdrawalViaRevert(UnlockWithdrawalViaRevert),
+    UnlockWithdrawalViaTrade(UnlockWithdrawalViaTrade),
 }
 #[derive(Debug, Clone, Copy)]
 pub enum UnlockWithdrawalWitnessUnionReader<'r> {
     UnlockWithdrawalViaFinalize(UnlockWithdrawalViaFinalizeReader<'r>),
     UnlockWithdrawalViaRevert(UnlockWithdrawalViaRevertReader<'r>),
+    UnlockWithdrawalViaTrade(UnlockWithdrawalViaTradeReader<'r>),
 }
 impl ::core::default::Default for UnlockWithdrawalWitnessUnion {
     fn default() -> Self {
@@ -8346,6 +8351,15 @@ impl ::core::fmt::Display for UnlockWithdrawalWitnessUnion {
                     item
                 )
             }
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    UnlockWithdrawalViaTrade::NAME,
+                    item
+                )
+            }
         }
     }
 }
@@ -8370,6 +8384,15 @@ impl<'r> ::core::fmt::Display for UnlockWithdrawalWitnessUnionReader<'r> {
                     item
                 )
             }
+            UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaTrade(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    UnlockWithdrawalViaTrade::NAME,
+                    item
+                )
+            }
         }
     }
 }
@@ -8382,6 +8405,9 @@ impl UnlockWithdrawalWitnessUnion {
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(ref item) => {
                 write!(f, "{}", item)
             }
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(ref item) => {
+                write!(f, "{}", item)
+            }
         }
     }
 }
@@ -8394,6 +8420,9 @@ impl<'r> UnlockWithdrawalWitnessUnionReader<'r> {
             UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaRevert(ref item) => {
                 write!(f, "{}", item)
             }
+            UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaTrade(ref item) => {
+                write!(f, "{}", item)
+            }
         }
     }
 }
@@ -8407,6 +8436,11 @@ impl ::core::convert::From<UnlockWithdrawalViaRevert> for UnlockWithdrawalWitnes
         UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(item)
     }
 }
+impl ::core::convert::From<UnlockWithdrawalViaTrade> for UnlockWithdrawalWitnessUnion {
+    fn from(item: UnlockWithdrawalViaTrade) -> Self {
+        UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(item)
+    }
+}
 impl<'r> ::core::convert::From<UnlockWithdrawalViaFinalizeReader<'r>>
     for UnlockWithdrawalWitnessUnionReader<'r>
 {
@@ -8421,24 +8455,34 @@ impl<'r> ::core::convert::From<UnlockWithdrawalViaRevertReader<'r>>
         UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaRevert(item)
     }
 }
+impl<'r> ::core::convert::From<UnlockWithdrawalViaTradeReader<'r>>
+    for UnlockWithdrawalWitnessUnionReader<'r>
+{
+    fn from(item: UnlockWithdrawalViaTradeReader<'r>) -> Self {
+        UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaTrade(item)
+    }
+}
 impl UnlockWithdrawalWitnessUnion {
     pub const NAME: &'static str = "UnlockWithdrawalWitnessUnion";
     pub fn as_bytes(&self) -> molecule::bytes::Bytes {
         match self {
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(item) => item.as_bytes(),
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(item) => item.as_bytes(),
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(item) => item.as_bytes(),
         }
     }
     pub fn as_slice(&self) -> &[u8] {
         match self {
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(item) => item.as_slice(),
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(item) => item.as_slice(),
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
         match self {
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(_) => 0,
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(_) => 1,
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(_) => 2,
         }
     }
     pub fn item_name(&self) -> &str {
@@ -8449,6 +8493,7 @@ impl UnlockWithdrawalWitnessUnion {
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(_) => {
                 "UnlockWithdrawalViaRevert"
             }
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(_) => "UnlockWithdrawalViaTrade",
         }
     }
     pub fn as_reader<'r>(&'r self) -> UnlockWithdrawalWitnessUnionReader<'r> {
@@ -8459,6 +8504,7 @@ impl UnlockWithdrawalWitnessUnion {
             UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaRevert(item) => {
                 item.as_reader().into()
             }
+            UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(item) => item.as_reader().into(),
         }
     }
 }
@@ -8470,12 +8516,14 @@ impl<'r> UnlockWithdrawalWitnessUnionReader<'r> {
                 item.as_slice()
             }
             UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaRevert(item) => item.as_slice(),
+            UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaTrade(item) => item.as_slice(),
         }
     }
     pub fn item_id(&self) -> molecule::Number {
         match self {
             UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaFinalize(_) => 0,
             UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaRevert(_) => 1,
+            UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaTrade(_) => 2,
         }
     }
     pub fn item_name(&self) -> &str {
@@ -8486,6 +8534,9 @@ impl<'r> UnlockWithdrawalWitnessUnionReader<'r> {
             UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaRevert(_) => {
                 "UnlockWithdrawalViaRevert"
             }
+            UnlockWithdrawalWitnessUnionReader::UnlockWithdrawalViaTrade(_) => {
+                "UnlockWithdrawalViaTrade"
+            }
         }
     }
 }
@@ -8828,6 +8879,183 @@ impl molecule::prelude::Builder for UnlockWithdrawalViaRevertBuilder {
     }
 }
 #[derive(Clone)]
+pub struct UnlockWithdrawalViaTrade(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for UnlockWithdrawalViaTrade {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for UnlockWithdrawalViaTrade {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for UnlockWithdrawalViaTrade {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ".. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for UnlockWithdrawalViaTrade {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![4, 0, 0, 0];
+        UnlockWithdrawalViaTrade::new_unchecked(v.into())
+    }
+}
+impl UnlockWithdrawalViaTrade {
+    pub const FIELD_COUNT: usize = 0;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn as_reader<'r>(&'r self) -> UnlockWithdrawalViaTradeReader<'r> {
+        UnlockWithdrawalViaTradeReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for UnlockWithdrawalViaTrade {
+    type Builder = UnlockWithdrawalViaTradeBuilder;
+    const NAME: &'static str = "UnlockWithdrawalViaTrade";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        UnlockWithdrawalViaTrade(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        UnlockWithdrawalViaTradeReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        UnlockWithdrawalViaTradeReader::from_compatible_slice(slice)
+            .map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+    }
+}
+#[derive(Clone, Copy)]
+pub struct UnlockWithdrawalViaTradeReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for UnlockWithdrawalViaTradeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for UnlockWithdrawalViaTradeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for UnlockWithdrawalViaTradeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ".. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> UnlockWithdrawalViaTradeReader<'r> {
+    pub const FIELD_COUNT: usize = 0;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for UnlockWithdrawalViaTradeReader<'r> {
+    type Entity = UnlockWithdrawalViaTrade;
+    const NAME: &'static str = "UnlockWithdrawalViaTradeReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        UnlockWithdrawalViaTradeReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len > molecule::NUMBER_SIZE && !compatible {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, !0);
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct UnlockWithdrawalViaTradeBuilder {}
+impl UnlockWithdrawalViaTradeBuilder {
+    pub const FIELD_COUNT: usize = 0;
+}
+impl molecule::prelude::Builder for UnlockWithdrawalViaTradeBuilder {
+    type Entity = UnlockWithdrawalViaTrade;
+    const NAME: &'static str = "UnlockWithdrawalViaTradeBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(&molecule::pack_number(
+            molecule::NUMBER_SIZE as molecule::Number,
+        ))?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        UnlockWithdrawalViaTrade::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
 pub struct StakeLockArgs(molecule::bytes::Bytes);
 impl ::core::fmt::LowerHex for StakeLockArgs {
     fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
@@ -16950,3 +17178,1528 @@ impl molecule::prelude::Builder for BatchSetMappingBuilder {
         BatchSetMapping::new_unchecked(inner.into())
     }
 }
+#[derive(Clone)]
+pub struct WebAuthnSignature(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for WebAuthnSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for WebAuthnSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for WebAuthnSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "pubkey", self.pubkey())?;
+        write!(f, ", {}: {}", "signature", self.signature())?;
+        write!(
+            f,
+            ", {}: {}",
+            "authenticator_data",
+            self.authenticator_data()
+        )?;
+        write!(
+            f,
+            ", {}: {}",
+            "client_data_json_suffix",
+            self.client_data_json_suffix()
+        )?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for WebAuthnSignature {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            36, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 28, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        WebAuthnSignature::new_unchecked(v.into())
+    }
+}
+impl WebAuthnSignature {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn pubkey(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Bytes::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn signature(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Bytes::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn authenticator_data(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Bytes::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn client_data_json_suffix(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Bytes::new_unchecked(self.0.slice(start..end))
+        } else {
+            Bytes::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> WebAuthnSignatureReader<'r> {
+        WebAuthnSignatureReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for WebAuthnSignature {
+    type Builder = WebAuthnSignatureBuilder;
+    const NAME: &'static str = "WebAuthnSignature";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        WebAuthnSignature(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        WebAuthnSignatureReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        WebAuthnSignatureReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .pubkey(self.pubkey())
+            .signature(self.signature())
+            .authenticator_data(self.authenticator_data())
+            .client_data_json_suffix(self.client_data_json_suffix())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct WebAuthnSignatureReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for WebAuthnSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for WebAuthnSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for WebAuthnSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "pubkey", self.pubkey())?;
+        write!(f, ", {}: {}", "signature", self.signature())?;
+        write!(
+            f,
+            ", {}: {}",
+            "authenticator_data",
+            self.authenticator_data()
+        )?;
+        write!(
+            f,
+            ", {}: {}",
+            "client_data_json_suffix",
+            self.client_data_json_suffix()
+        )?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> WebAuthnSignatureReader<'r> {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn pubkey(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        BytesReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn signature(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        BytesReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn authenticator_data(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        BytesReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn client_data_json_suffix(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            BytesReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            BytesReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for WebAuthnSignatureReader<'r> {
+    type Entity = WebAuthnSignature;
+    const NAME: &'static str = "WebAuthnSignatureReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        WebAuthnSignatureReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len == molecule::NUMBER_SIZE && Self::FIELD_COUNT == 0 {
+            return Ok(());
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        BytesReader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        BytesReader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        BytesReader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        BytesReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct WebAuthnSignatureBuilder {
+    pub(crate) pubkey: Bytes,
+    pub(crate) signature: Bytes,
+    pub(crate) authenticator_data: Bytes,
+    pub(crate) client_data_json_suffix: Bytes,
+}
+impl WebAuthnSignatureBuilder {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn pubkey(mut self, v: Bytes) -> Self {
+        self.pubkey = v;
+        self
+    }
+    pub fn signature(mut self, v: Bytes) -> Self {
+        self.signature = v;
+        self
+    }
+    pub fn authenticator_data(mut self, v: Bytes) -> Self {
+        self.authenticator_data = v;
+        self
+    }
+    pub fn client_data_json_suffix(mut self, v: Bytes) -> Self {
+        self.client_data_json_suffix = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for WebAuthnSignatureBuilder {
+    type Entity = WebAuthnSignature;
+    const NAME: &'static str = "WebAuthnSignatureBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.pubkey.as_slice().len()
+            + self.signature.as_slice().len()
+            + self.authenticator_data.as_slice().len()
+            + self.client_data_json_suffix.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.pubkey.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.signature.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.authenticator_data.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.client_data_json_suffix.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.pubkey.as_slice())?;
+        writer.write_all(self.signature.as_slice())?;
+        writer.write_all(self.authenticator_data.as_slice())?;
+        writer.write_all(self.client_data_json_suffix.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        WebAuthnSignature::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct SponsoredTxSignature(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for SponsoredTxSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for SponsoredTxSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for SponsoredTxSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "sponsor_id", self.sponsor_id())?;
+        write!(f, ", {}: {}", "fee", self.fee())?;
+        write!(f, ", {}: {}", "sender_signature", self.sender_signature())?;
+        write!(f, ", {}: {}", "sponsor_signature", self.sponsor_signature())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for SponsoredTxSignature {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            48, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 40, 0, 0, 0, 44, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        SponsoredTxSignature::new_unchecked(v.into())
+    }
+}
+impl SponsoredTxSignature {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn sponsor_id(&self) -> Uint32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Uint32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn fee(&self) -> Uint128 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Uint128::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn sender_signature(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        Bytes::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn sponsor_signature(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            Bytes::new_unchecked(self.0.slice(start..end))
+        } else {
+            Bytes::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> SponsoredTxSignatureReader<'r> {
+        SponsoredTxSignatureReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for SponsoredTxSignature {
+    type Builder = SponsoredTxSignatureBuilder;
+    const NAME: &'static str = "SponsoredTxSignature";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        SponsoredTxSignature(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        SponsoredTxSignatureReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        SponsoredTxSignatureReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .sponsor_id(self.sponsor_id())
+            .fee(self.fee())
+            .sender_signature(self.sender_signature())
+            .sponsor_signature(self.sponsor_signature())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct SponsoredTxSignatureReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for SponsoredTxSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for SponsoredTxSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for SponsoredTxSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "sponsor_id", self.sponsor_id())?;
+        write!(f, ", {}: {}", "fee", self.fee())?;
+        write!(f, ", {}: {}", "sender_signature", self.sender_signature())?;
+        write!(f, ", {}: {}", "sponsor_signature", self.sponsor_signature())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> SponsoredTxSignatureReader<'r> {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn sponsor_id(&self) -> Uint32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Uint32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn fee(&self) -> Uint128Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Uint128Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn sender_signature(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        let end = molecule::unpack_number(&slice[16..]) as usize;
+        BytesReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn sponsor_signature(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[16..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[20..]) as usize;
+            BytesReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            BytesReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for SponsoredTxSignatureReader<'r> {
+    type Entity = SponsoredTxSignature;
+    const NAME: &'static str = "SponsoredTxSignatureReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        SponsoredTxSignatureReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len == molecule::NUMBER_SIZE && Self::FIELD_COUNT == 0 {
+            return Ok(());
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Uint32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Uint128Reader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        BytesReader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        BytesReader::verify(&slice[offsets[3]..offsets[4]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct SponsoredTxSignatureBuilder {
+    pub(crate) sponsor_id: Uint32,
+    pub(crate) fee: Uint128,
+    pub(crate) sender_signature: Bytes,
+    pub(crate) sponsor_signature: Bytes,
+}
+impl SponsoredTxSignatureBuilder {
+    pub const FIELD_COUNT: usize = 4;
+    pub fn sponsor_id(mut self, v: Uint32) -> Self {
+        self.sponsor_id = v;
+        self
+    }
+    pub fn fee(mut self, v: Uint128) -> Self {
+        self.fee = v;
+        self
+    }
+    pub fn sender_signature(mut self, v: Bytes) -> Self {
+        self.sender_signature = v;
+        self
+    }
+    pub fn sponsor_signature(mut self, v: Bytes) -> Self {
+        self.sponsor_signature = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for SponsoredTxSignatureBuilder {
+    type Entity = SponsoredTxSignature;
+    const NAME: &'static str = "SponsoredTxSignatureBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.sponsor_id.as_slice().len()
+            + self.fee.as_slice().len()
+            + self.sender_signature.as_slice().len()
+            + self.sponsor_signature.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.sponsor_id.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.fee.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.sender_signature.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.sponsor_signature.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.sponsor_id.as_slice())?;
+        writer.write_all(self.fee.as_slice())?;
+        writer.write_all(self.sender_signature.as_slice())?;
+        writer.write_all(self.sponsor_signature.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        SponsoredTxSignature::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct TxSignatureEnvelope(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for TxSignatureEnvelope {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for TxSignatureEnvelope {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for TxSignatureEnvelope {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}(", Self::NAME)?;
+        self.to_enum().display_inner(f)?;
+        write!(f, ")")
+    }
+}
+impl ::core::default::Default for TxSignatureEnvelope {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            0, 0, 0, 0, 48, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 40, 0, 0, 0, 44, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        TxSignatureEnvelope::new_unchecked(v.into())
+    }
+}
+impl TxSignatureEnvelope {
+    pub const ITEMS_COUNT: usize = 1;
+    pub fn item_id(&self) -> molecule::Number {
+        molecule::unpack_number(self.as_slice())
+    }
+    pub fn to_enum(&self) -> TxSignatureEnvelopeUnion {
+        let inner = self.0.slice(molecule::NUMBER_SIZE..);
+        match self.item_id() {
+            0 => SponsoredTxSignature::new_unchecked(inner).into(),
+            _ => panic!("{}: invalid data", Self::NAME),
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> TxSignatureEnvelopeReader<'r> {
+        TxSignatureEnvelopeReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for TxSignatureEnvelope {
+    type Builder = TxSignatureEnvelopeBuilder;
+    const NAME: &'static str = "TxSignatureEnvelope";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        TxSignatureEnvelope(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        TxSignatureEnvelopeReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        TxSignatureEnvelopeReader::from_compatible_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().set(self.to_enum())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct TxSignatureEnvelopeReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for TxSignatureEnvelopeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for TxSignatureEnvelopeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for TxSignatureEnvelopeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}(", Self::NAME)?;
+        self.to_enum().display_inner(f)?;
+        write!(f, ")")
+    }
+}
+impl<'r> TxSignatureEnvelopeReader<'r> {
+    pub const ITEMS_COUNT: usize = 1;
+    pub fn item_id(&self) -> molecule::Number {
+        molecule::unpack_number(self.as_slice())
+    }
+    pub fn to_enum(&self) -> TxSignatureEnvelopeUnionReader<'r> {
+        let inner = &self.as_slice()[molecule::NUMBER_SIZE..];
+        match self.item_id() {
+            0 => SponsoredTxSignatureReader::new_unchecked(inner).into(),
+            _ => panic!("{}: invalid data", Self::NAME),
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for TxSignatureEnvelopeReader<'r> {
+    type Entity = TxSignatureEnvelope;
+    const NAME: &'static str = "TxSignatureEnvelopeReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        TxSignatureEnvelopeReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let item_id = molecule::unpack_number(slice);
+        let inner_slice = &slice[molecule::NUMBER_SIZE..];
+        match item_id {
+            0 => SponsoredTxSignatureReader::verify(inner_slice, compatible),
+            _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
+        }?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct TxSignatureEnvelopeBuilder(pub(crate) TxSignatureEnvelopeUnion);
+impl TxSignatureEnvelopeBuilder {
+    pub const ITEMS_COUNT: usize = 1;
+    pub fn set<I>(mut self, v: I) -> Self
+    where
+        I: ::core::convert::Into<TxSignatureEnvelopeUnion>,
+    {
+        self.0 = v.into();
+        self
+    }
+}
+impl molecule::prelude::Builder for TxSignatureEnvelopeBuilder {
+    type Entity = TxSignatureEnvelope;
+    const NAME: &'static str = "TxSignatureEnvelopeBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE + self.0.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(&molecule::pack_number(self.0.item_id()))?;
+        writer.write_all(self.0.as_slice())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        TxSignatureEnvelope::new_unchecked(inner.into())
+    }
+}
+#[derive(Debug, Clone)]
+pub enum TxSignatureEnvelopeUnion {
+    SponsoredTxSignature(SponsoredTxSignature),
+}
+#[derive(Debug, Clone, Copy)]
+pub enum TxSignatureEnvelopeUnionReader<'r> {
+    SponsoredTxSignature(SponsoredTxSignatureReader<'r>),
+}
+impl ::core::default::Default for TxSignatureEnvelopeUnion {
+    fn default() -> Self {
+        TxSignatureEnvelopeUnion::SponsoredTxSignature(::core::default::Default::default())
+    }
+}
+impl ::core::fmt::Display for TxSignatureEnvelopeUnion {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    SponsoredTxSignature::NAME,
+                    item
+                )
+            }
+        }
+    }
+}
+impl<'r> ::core::fmt::Display for TxSignatureEnvelopeUnionReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    SponsoredTxSignature::NAME,
+                    item
+                )
+            }
+        }
+    }
+}
+impl TxSignatureEnvelopeUnion {
+    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(ref item) => write!(f, "{}", item),
+        }
+    }
+}
+impl<'r> TxSignatureEnvelopeUnionReader<'r> {
+    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(ref item) => write!(f, "{}", item),
+        }
+    }
+}
+impl ::core::convert::From<SponsoredTxSignature> for TxSignatureEnvelopeUnion {
+    fn from(item: SponsoredTxSignature) -> Self {
+        TxSignatureEnvelopeUnion::SponsoredTxSignature(item)
+    }
+}
+impl<'r> ::core::convert::From<SponsoredTxSignatureReader<'r>>
+    for TxSignatureEnvelopeUnionReader<'r>
+{
+    fn from(item: SponsoredTxSignatureReader<'r>) -> Self {
+        TxSignatureEnvelopeUnionReader::SponsoredTxSignature(item)
+    }
+}
+impl TxSignatureEnvelopeUnion {
+    pub const NAME: &'static str = "TxSignatureEnvelopeUnion";
+    pub fn as_bytes(&self) -> molecule::bytes::Bytes {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(item) => item.as_bytes(),
+        }
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(item) => item.as_slice(),
+        }
+    }
+    pub fn item_id(&self) -> molecule::Number {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(_) => 0,
+        }
+    }
+    pub fn item_name(&self) -> &str {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(_) => "SponsoredTxSignature",
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> TxSignatureEnvelopeUnionReader<'r> {
+        match self {
+            TxSignatureEnvelopeUnion::SponsoredTxSignature(item) => item.as_reader().into(),
+        }
+    }
+}
+impl<'r> TxSignatureEnvelopeUnionReader<'r> {
+    pub const NAME: &'r str = "TxSignatureEnvelopeUnionReader";
+    pub fn as_slice(&self) -> &'r [u8] {
+        match self {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(item) => item.as_slice(),
+        }
+    }
+    pub fn item_id(&self) -> molecule::Number {
+        match self {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(_) => 0,
+        }
+    }
+    pub fn item_name(&self) -> &str {
+        match self {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(_) => "SponsoredTxSignature",
+        }
+    }
+}
+#[derive(Clone)]
+pub struct BatchWithdrawalSignature(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for BatchWithdrawalSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for BatchWithdrawalSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for BatchWithdrawalSignature {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "index", self.index())?;
+        write!(
+            f,
+            ", {}: {}",
+            "raw_request_hashes",
+            self.raw_request_hashes()
+        )?;
+        write!(f, ", {}: {}", "signature", self.signature())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl ::core::default::Default for BatchWithdrawalSignature {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            28, 0, 0, 0, 16, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        BatchWithdrawalSignature::new_unchecked(v.into())
+    }
+}
+impl BatchWithdrawalSignature {
+    pub const FIELD_COUNT: usize = 3;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn index(&self) -> Uint32 {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Uint32::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn raw_request_hashes(&self) -> Byte32Vec {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32Vec::new_unchecked(self.0.slice(start..end))
+    }
+    pub fn signature(&self) -> Bytes {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[16..]) as usize;
+            Bytes::new_unchecked(self.0.slice(start..end))
+        } else {
+            Bytes::new_unchecked(self.0.slice(start..))
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> BatchWithdrawalSignatureReader<'r> {
+        BatchWithdrawalSignatureReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for BatchWithdrawalSignature {
+    type Builder = BatchWithdrawalSignatureBuilder;
+    const NAME: &'static str = "BatchWithdrawalSignature";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        BatchWithdrawalSignature(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        BatchWithdrawalSignatureReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        BatchWithdrawalSignatureReader::from_compatible_slice(slice)
+            .map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder()
+            .index(self.index())
+            .raw_request_hashes(self.raw_request_hashes())
+            .signature(self.signature())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct BatchWithdrawalSignatureReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for BatchWithdrawalSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for BatchWithdrawalSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for BatchWithdrawalSignatureReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{} {{ ", Self::NAME)?;
+        write!(f, "{}: {}", "index", self.index())?;
+        write!(
+            f,
+            ", {}: {}",
+            "raw_request_hashes",
+            self.raw_request_hashes()
+        )?;
+        write!(f, ", {}: {}", "signature", self.signature())?;
+        let extra_count = self.count_extra_fields();
+        if extra_count != 0 {
+            write!(f, ", .. ({} fields)", extra_count)?;
+        }
+        write!(f, " }}")
+    }
+}
+impl<'r> BatchWithdrawalSignatureReader<'r> {
+    pub const FIELD_COUNT: usize = 3;
+    pub fn total_size(&self) -> usize {
+        molecule::unpack_number(self.as_slice()) as usize
+    }
+    pub fn field_count(&self) -> usize {
+        if self.total_size() == molecule::NUMBER_SIZE {
+            0
+        } else {
+            (molecule::unpack_number(&self.as_slice()[molecule::NUMBER_SIZE..]) as usize / 4) - 1
+        }
+    }
+    pub fn count_extra_fields(&self) -> usize {
+        self.field_count() - Self::FIELD_COUNT
+    }
+    pub fn has_extra_fields(&self) -> bool {
+        Self::FIELD_COUNT != self.field_count()
+    }
+    pub fn index(&self) -> Uint32Reader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[4..]) as usize;
+        let end = molecule::unpack_number(&slice[8..]) as usize;
+        Uint32Reader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn raw_request_hashes(&self) -> Byte32VecReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[8..]) as usize;
+        let end = molecule::unpack_number(&slice[12..]) as usize;
+        Byte32VecReader::new_unchecked(&self.as_slice()[start..end])
+    }
+    pub fn signature(&self) -> BytesReader<'r> {
+        let slice = self.as_slice();
+        let start = molecule::unpack_number(&slice[12..]) as usize;
+        if self.has_extra_fields() {
+            let end = molecule::unpack_number(&slice[16..]) as usize;
+            BytesReader::new_unchecked(&self.as_slice()[start..end])
+        } else {
+            BytesReader::new_unchecked(&self.as_slice()[start..])
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for BatchWithdrawalSignatureReader<'r> {
+    type Entity = BatchWithdrawalSignature;
+    const NAME: &'static str = "BatchWithdrawalSignatureReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        BatchWithdrawalSignatureReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let total_size = molecule::unpack_number(slice) as usize;
+        if slice_len != total_size {
+            return ve!(Self, TotalSizeNotMatch, total_size, slice_len);
+        }
+        if slice_len == molecule::NUMBER_SIZE && Self::FIELD_COUNT == 0 {
+            return Ok(());
+        }
+        if slice_len < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE * 2, slice_len);
+        }
+        let offset_first = molecule::unpack_number(&slice[molecule::NUMBER_SIZE..]) as usize;
+        if offset_first % molecule::NUMBER_SIZE != 0 || offset_first < molecule::NUMBER_SIZE * 2 {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        if slice_len < offset_first {
+            return ve!(Self, HeaderIsBroken, offset_first, slice_len);
+        }
+        let field_count = offset_first / molecule::NUMBER_SIZE - 1;
+        if field_count < Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        } else if !compatible && field_count > Self::FIELD_COUNT {
+            return ve!(Self, FieldCountNotMatch, Self::FIELD_COUNT, field_count);
+        };
+        let mut offsets: Vec<usize> = slice[molecule::NUMBER_SIZE..offset_first]
+            .chunks_exact(molecule::NUMBER_SIZE)
+            .map(|x| molecule::unpack_number(x) as usize)
+            .collect();
+        offsets.push(total_size);
+        if offsets.windows(2).any(|i| i[0] > i[1]) {
+            return ve!(Self, OffsetsNotMatch);
+        }
+        Uint32Reader::verify(&slice[offsets[0]..offsets[1]], compatible)?;
+        Byte32VecReader::verify(&slice[offsets[1]..offsets[2]], compatible)?;
+        BytesReader::verify(&slice[offsets[2]..offsets[3]], compatible)?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct BatchWithdrawalSignatureBuilder {
+    pub(crate) index: Uint32,
+    pub(crate) raw_request_hashes: Byte32Vec,
+    pub(crate) signature: Bytes,
+}
+impl BatchWithdrawalSignatureBuilder {
+    pub const FIELD_COUNT: usize = 3;
+    pub fn index(mut self, v: Uint32) -> Self {
+        self.index = v;
+        self
+    }
+    pub fn raw_request_hashes(mut self, v: Byte32Vec) -> Self {
+        self.raw_request_hashes = v;
+        self
+    }
+    pub fn signature(mut self, v: Bytes) -> Self {
+        self.signature = v;
+        self
+    }
+}
+impl molecule::prelude::Builder for BatchWithdrawalSignatureBuilder {
+    type Entity = BatchWithdrawalSignature;
+    const NAME: &'static str = "BatchWithdrawalSignatureBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1)
+            + self.index.as_slice().len()
+            + self.raw_request_hashes.as_slice().len()
+            + self.signature.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        let mut total_size = molecule::NUMBER_SIZE * (Self::FIELD_COUNT + 1);
+        let mut offsets = Vec::with_capacity(Self::FIELD_COUNT);
+        offsets.push(total_size);
+        total_size += self.index.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.raw_request_hashes.as_slice().len();
+        offsets.push(total_size);
+        total_size += self.signature.as_slice().len();
+        writer.write_all(&molecule::pack_number(total_size as molecule::Number))?;
+        for offset in offsets.into_iter() {
+            writer.write_all(&molecule::pack_number(offset as molecule::Number))?;
+        }
+        writer.write_all(self.index.as_slice())?;
+        writer.write_all(self.raw_request_hashes.as_slice())?;
+        writer.write_all(self.signature.as_slice())?;
+        Ok(())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        BatchWithdrawalSignature::new_unchecked(inner.into())
+    }
+}
+#[derive(Clone)]
+pub struct WithdrawalSignatureEnvelope(molecule::bytes::Bytes);
+impl ::core::fmt::LowerHex for WithdrawalSignatureEnvelope {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl ::core::fmt::Debug for WithdrawalSignatureEnvelope {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl ::core::fmt::Display for WithdrawalSignatureEnvelope {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}(", Self::NAME)?;
+        self.to_enum().display_inner(f)?;
+        write!(f, ")")
+    }
+}
+impl ::core::default::Default for WithdrawalSignatureEnvelope {
+    fn default() -> Self {
+        let v: Vec<u8> = vec![
+            0, 0, 0, 0, 28, 0, 0, 0, 16, 0, 0, 0, 20, 0, 0, 0, 24, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0,
+        ];
+        WithdrawalSignatureEnvelope::new_unchecked(v.into())
+    }
+}
+impl WithdrawalSignatureEnvelope {
+    pub const ITEMS_COUNT: usize = 1;
+    pub fn item_id(&self) -> molecule::Number {
+        molecule::unpack_number(self.as_slice())
+    }
+    pub fn to_enum(&self) -> WithdrawalSignatureEnvelopeUnion {
+        let inner = self.0.slice(molecule::NUMBER_SIZE..);
+        match self.item_id() {
+            0 => BatchWithdrawalSignature::new_unchecked(inner).into(),
+            _ => panic!("{}: invalid data", Self::NAME),
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> WithdrawalSignatureEnvelopeReader<'r> {
+        WithdrawalSignatureEnvelopeReader::new_unchecked(self.as_slice())
+    }
+}
+impl molecule::prelude::Entity for WithdrawalSignatureEnvelope {
+    type Builder = WithdrawalSignatureEnvelopeBuilder;
+    const NAME: &'static str = "WithdrawalSignatureEnvelope";
+    fn new_unchecked(data: molecule::bytes::Bytes) -> Self {
+        WithdrawalSignatureEnvelope(data)
+    }
+    fn as_bytes(&self) -> molecule::bytes::Bytes {
+        self.0.clone()
+    }
+    fn as_slice(&self) -> &[u8] {
+        &self.0[..]
+    }
+    fn from_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        WithdrawalSignatureEnvelopeReader::from_slice(slice).map(|reader| reader.to_entity())
+    }
+    fn from_compatible_slice(slice: &[u8]) -> molecule::error::VerificationResult<Self> {
+        WithdrawalSignatureEnvelopeReader::from_compatible_slice(slice)
+            .map(|reader| reader.to_entity())
+    }
+    fn new_builder() -> Self::Builder {
+        ::core::default::Default::default()
+    }
+    fn as_builder(self) -> Self::Builder {
+        Self::new_builder().set(self.to_enum())
+    }
+}
+#[derive(Clone, Copy)]
+pub struct WithdrawalSignatureEnvelopeReader<'r>(&'r [u8]);
+impl<'r> ::core::fmt::LowerHex for WithdrawalSignatureEnvelopeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        use molecule::hex_string;
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        write!(f, "{}", hex_string(self.as_slice()))
+    }
+}
+impl<'r> ::core::fmt::Debug for WithdrawalSignatureEnvelopeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}({:#x})", Self::NAME, self)
+    }
+}
+impl<'r> ::core::fmt::Display for WithdrawalSignatureEnvelopeReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        write!(f, "{}(", Self::NAME)?;
+        self.to_enum().display_inner(f)?;
+        write!(f, ")")
+    }
+}
+impl<'r> WithdrawalSignatureEnvelopeReader<'r> {
+    pub const ITEMS_COUNT: usize = 1;
+    pub fn item_id(&self) -> molecule::Number {
+        molecule::unpack_number(self.as_slice())
+    }
+    pub fn to_enum(&self) -> WithdrawalSignatureEnvelopeUnionReader<'r> {
+        let inner = &self.as_slice()[molecule::NUMBER_SIZE..];
+        match self.item_id() {
+            0 => BatchWithdrawalSignatureReader::new_unchecked(inner).into(),
+            _ => panic!("{}: invalid data", Self::NAME),
+        }
+    }
+}
+impl<'r> molecule::prelude::Reader<'r> for WithdrawalSignatureEnvelopeReader<'r> {
+    type Entity = WithdrawalSignatureEnvelope;
+    const NAME: &'static str = "WithdrawalSignatureEnvelopeReader";
+    fn to_entity(&self) -> Self::Entity {
+        Self::Entity::new_unchecked(self.as_slice().to_owned().into())
+    }
+    fn new_unchecked(slice: &'r [u8]) -> Self {
+        WithdrawalSignatureEnvelopeReader(slice)
+    }
+    fn as_slice(&self) -> &'r [u8] {
+        self.0
+    }
+    fn verify(slice: &[u8], compatible: bool) -> molecule::error::VerificationResult<()> {
+        use molecule::verification_error as ve;
+        let slice_len = slice.len();
+        if slice_len < molecule::NUMBER_SIZE {
+            return ve!(Self, HeaderIsBroken, molecule::NUMBER_SIZE, slice_len);
+        }
+        let item_id = molecule::unpack_number(slice);
+        let inner_slice = &slice[molecule::NUMBER_SIZE..];
+        match item_id {
+            0 => BatchWithdrawalSignatureReader::verify(inner_slice, compatible),
+            _ => ve!(Self, UnknownItem, Self::ITEMS_COUNT, item_id),
+        }?;
+        Ok(())
+    }
+}
+#[derive(Debug, Default)]
+pub struct WithdrawalSignatureEnvelopeBuilder(pub(crate) WithdrawalSignatureEnvelopeUnion);
+impl WithdrawalSignatureEnvelopeBuilder {
+    pub const ITEMS_COUNT: usize = 1;
+    pub fn set<I>(mut self, v: I) -> Self
+    where
+        I: ::core::convert::Into<WithdrawalSignatureEnvelopeUnion>,
+    {
+        self.0 = v.into();
+        self
+    }
+}
+impl molecule::prelude::Builder for WithdrawalSignatureEnvelopeBuilder {
+    type Entity = WithdrawalSignatureEnvelope;
+    const NAME: &'static str = "WithdrawalSignatureEnvelopeBuilder";
+    fn expected_length(&self) -> usize {
+        molecule::NUMBER_SIZE + self.0.as_slice().len()
+    }
+    fn write<W: molecule::io::Write>(&self, writer: &mut W) -> molecule::io::Result<()> {
+        writer.write_all(&molecule::pack_number(self.0.item_id()))?;
+        writer.write_all(self.0.as_slice())
+    }
+    fn build(&self) -> Self::Entity {
+        let mut inner = Vec::with_capacity(self.expected_length());
+        self.write(&mut inner)
+            .unwrap_or_else(|_| panic!("{} build should be ok", Self::NAME));
+        WithdrawalSignatureEnvelope::new_unchecked(inner.into())
+    }
+}
+#[derive(Debug, Clone)]
+pub enum WithdrawalSignatureEnvelopeUnion {
+    BatchWithdrawalSignature(BatchWithdrawalSignature),
+}
+#[derive(Debug, Clone, Copy)]
+pub enum WithdrawalSignatureEnvelopeUnionReader<'r> {
+    BatchWithdrawalSignature(BatchWithdrawalSignatureReader<'r>),
+}
+impl ::core::default::Default for WithdrawalSignatureEnvelopeUnion {
+    fn default() -> Self {
+        WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(
+            ::core::default::Default::default(),
+        )
+    }
+}
+impl ::core::fmt::Display for WithdrawalSignatureEnvelopeUnion {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    BatchWithdrawalSignature::NAME,
+                    item
+                )
+            }
+        }
+    }
+}
+impl<'r> ::core::fmt::Display for WithdrawalSignatureEnvelopeUnionReader<'r> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(ref item) => {
+                write!(
+                    f,
+                    "{}::{}({})",
+                    Self::NAME,
+                    BatchWithdrawalSignature::NAME,
+                    item
+                )
+            }
+        }
+    }
+}
+impl WithdrawalSignatureEnvelopeUnion {
+    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(ref item) => {
+                write!(f, "{}", item)
+            }
+        }
+    }
+}
+impl<'r> WithdrawalSignatureEnvelopeUnionReader<'r> {
+    pub(crate) fn display_inner(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(ref item) => {
+                write!(f, "{}", item)
+            }
+        }
+    }
+}
+impl ::core::convert::From<BatchWithdrawalSignature> for WithdrawalSignatureEnvelopeUnion {
+    fn from(item: BatchWithdrawalSignature) -> Self {
+        WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(item)
+    }
+}
+impl<'r> ::core::convert::From<BatchWithdrawalSignatureReader<'r>>
+    for WithdrawalSignatureEnvelopeUnionReader<'r>
+{
+    fn from(item: BatchWithdrawalSignatureReader<'r>) -> Self {
+        WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(item)
+    }
+}
+impl WithdrawalSignatureEnvelopeUnion {
+    pub const NAME: &'static str = "WithdrawalSignatureEnvelopeUnion";
+    pub fn as_bytes(&self) -> molecule::bytes::Bytes {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(item) => item.as_bytes(),
+        }
+    }
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(item) => item.as_slice(),
+        }
+    }
+    pub fn item_id(&self) -> molecule::Number {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(_) => 0,
+        }
+    }
+    pub fn item_name(&self) -> &str {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(_) => {
+                "BatchWithdrawalSignature"
+            }
+        }
+    }
+    pub fn as_reader<'r>(&'r self) -> WithdrawalSignatureEnvelopeUnionReader<'r> {
+        match self {
+            WithdrawalSignatureEnvelopeUnion::BatchWithdrawalSignature(item) => {
+                item.as_reader().into()
+            }
+        }
+    }
+}
+impl<'r> WithdrawalSignatureEnvelopeUnionReader<'r> {
+    pub const NAME: &'r str = "WithdrawalSignatureEnvelopeUnionReader";
+    pub fn as_slice(&self) -> &'r [u8] {
+        match self {
+            WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(item) => {
+                item.as_slice()
+            }
+        }
+    }
+    pub fn item_id(&self) -> molecule::Number {
+        match self {
+            WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(_) => 0,
+        }
+    }
+    pub fn item_name(&self) -> &str {
+        match self {
+            WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(_) => {
+                "BatchWithdrawalSignature"
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::bytes::Bytes;
+use sparse_merkle_tree::H256;
+
+use super::CycleMeter;
+
+/// Arguments and effects of a traced syscall
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyscallOp {
+    Store {
+        key: H256,
+        before: H256,
+        after: H256,
+    },
+    Load {
+        key: H256,
+        value: H256,
+    },
+    Create {
+        script_hash: H256,
+        // None if the account isn't created
+        account_id: Option<u32>,
+    },
+    Log {
+        account_id: u32,
+        service_flag: u8,
+        data: Bytes,
+    },
+    RecoverAccount {
+        message: H256,
+        code_hash: H256,
+        // None if the signature can't be recovered
+        script_hash: Option<H256>,
+    },
+    Snapshot {
+        snapshot_id: u32,
+    },
+    Revert {
+        snapshot_id: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallTrace {
+    pub code: u64,
+    pub name: &'static str,
+    /// Execution cycles consumed by the syscall
+    pub execution_cycles: u64,
+    /// Virtual cycles charged for the syscall
+    pub virtual_cycles: u64,
+    /// Return code of the syscall
+    pub ret: i8,
+    /// None for syscalls which don't record their arguments
+    pub op: Option<SyscallOp>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    pub tx_hash: H256,
+    /// Set if the tx is aborted before exit, the exit code and cycles are meaningless then
+    pub error: Option<String>,
+    pub exit_code: i8,
+    pub cycles: CycleMeter,
+    pub return_data: Bytes,
+    /// Output of contract debug prints
+    pub debug_log: String,
+    pub syscalls: Vec<SyscallTrace>,
+    /// Earlier txs of the block which failed when replaying a committed tx
+    pub replay_errors: Vec<ReplayError>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CyclesProfile {
+    pub tx_hash: H256,
+    /// Set if the tx is aborted before exit, the exit code and cycles are meaningless then
+    pub error: Option<String>,
+    pub exit_code: i8,
+    pub cycles: CycleMeter,
+    /// Call stacks from the entry function and the execution cycles spent in the last frame
+    pub stacks: Vec<(Vec<String>, u64)>,
+    /// Earlier txs of the block which failed when replaying a committed tx
+    pub replay_errors: Vec<ReplayError>,
+}
+
+/// An earlier tx failed when replaying the txs before a committed tx, its changes are dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayError {
+    pub tx_hash: H256,
+    pub error: String,
+}
+
+impl CyclesProfile {
+    /// Stacks in the folded format of flamegraph tools, one `frame;frame cycles` per line
+    pub fn to_folded(&self) -> String {
+        let mut folded = String::new();
+        for (frames, cycles) in self.stacks.iter() {
+            folded.push_str(&frames.join(";"));
+            folded.push(' ');
+            folded.push_str(&cycles.to_string());
+            folded.push('\n');
+        }
+        folded
+    }
+}
@@ -1,12 +1,13 @@
 use crate::packed::{
     AccountMerkleState, Byte32, CompactMemBlock, GlobalState, GlobalStateV0, MemBlock,
-    RawWithdrawalRequest, TransactionKey, TxReceipt, WithdrawalKey, WithdrawalRequestExtra,
+    RawWithdrawalRequest, TransactionKey, TxAccessList, TxReceipt, WithdrawalKey,
+    WithdrawalRequestExtra,
 };
 use crate::prelude::*;
 use ckb_types::error::VerificationError;
 use sparse_merkle_tree::H256;
 
-use super::RunResult;
+use super::{AccessList, RunResult};
 
 impl TransactionKey {
     pub fn build_transaction_key(block_hash: Byte32, index: u32) -> Self {
@@ -50,6 +51,41 @@ impl TxReceipt {
     }
 }
 
+impl From<&AccessList> for TxAccessList {
+    fn from(access_list: &AccessList) -> Self {
+        // sort keys, so the same access list always has the same bytes
+        let sorted = |keys: &std::collections::HashSet<H256>| {
+            let mut keys: Vec<_> = keys.iter().cloned().collect();
+            keys.sort_unstable();
+            keys.pack()
+        };
+        TxAccessList::new_builder()
+            .read_keys(sorted(&access_list.read_keys))
+            .write_keys(sorted(&access_list.write_keys))
+            .account_count_access(access_list.account_count_access.into())
+            .build()
+    }
+}
+
+impl From<TxAccessList> for AccessList {
+    fn from(access_list: TxAccessList) -> Self {
+        AccessList {
+            read_keys: access_list
+                .read_keys()
+                .into_iter()
+                .map(|k| k.unpack())
+                .collect(),
+            write_keys: access_list
+                .write_keys()
+                .into_iter()
+                .map(|k| k.unpack())
+                .collect(),
+            account_count_access: access_list.account_count_access().into(),
+            ..Default::default()
+        }
+    }
+}
+
 pub fn global_state_from_slice(slice: &[u8]) -> Result<GlobalState, VerificationError> {
     match GlobalState::from_slice(slice) {
         Ok(state) => Ok(state),
@@ -1,4 +1,5 @@
 mod error_receipt;
+mod execution_trace;
 mod exported_block;
 mod extension;
 mod generator;
@@ -7,9 +8,12 @@ mod pool;
 mod rollup_context;
 mod rpc;
 mod run_result;
+mod sponsor;
 mod store;
+mod withdrawal_batch;
 
 pub use error_receipt::*;
+pub use execution_trace::*;
 pub use exported_block::*;
 pub use extension::global_state_from_slice;
 pub use generator::CycleMeter;
@@ -18,4 +22,6 @@ pub use pool::*;
 pub use rollup_context::*;
 pub use rpc::*;
 pub use run_result::*;
+pub use sponsor::*;
 pub use store::*;
+pub use withdrawal_batch::*;
@@ -25,6 +25,58 @@ impl RunResultCycles {
     }
 }
 
+/// State keys read and written by a transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessList {
+    pub read_keys: HashSet<H256>,
+    pub write_keys: HashSet<H256>,
+    /// Bit flags of `ACCOUNT_COUNT_READ` and `ACCOUNT_COUNT_WRITE`
+    pub account_count_access: u8,
+    /// Keys read or written more than once, only tracked in memory
+    pub repeated_keys: HashSet<H256>,
+}
+
+impl AccessList {
+    pub const ACCOUNT_COUNT_READ: u8 = 1;
+    pub const ACCOUNT_COUNT_WRITE: u8 = 2;
+
+    pub fn merge(&mut self, other: AccessList) {
+        let repeated_reads = self.read_keys.intersection(&other.read_keys).cloned();
+        let repeated_writes = self.write_keys.intersection(&other.write_keys).cloned();
+        let repeated_keys: Vec<H256> = repeated_reads.chain(repeated_writes).collect();
+        self.repeated_keys.extend(repeated_keys);
+        self.repeated_keys.extend(other.repeated_keys);
+        self.read_keys.extend(other.read_keys);
+        self.write_keys.extend(other.write_keys);
+        self.account_count_access |= other.account_count_access;
+    }
+
+    /// Keys should be loaded before executing the transaction
+    pub fn keys(&self) -> impl Iterator<Item = &H256> {
+        self.read_keys
+            .iter()
+            .chain(self.write_keys.difference(&self.read_keys))
+    }
+
+    /// Two transactions are independent if neither of them writes state the other one accesses,
+    /// they can be executed in any order with the same result.
+    pub fn is_independent(&self, other: &AccessList) -> bool {
+        let writes_account_count = |access: u8| access & Self::ACCOUNT_COUNT_WRITE != 0;
+        if (writes_account_count(self.account_count_access) && other.account_count_access != 0)
+            || (writes_account_count(other.account_count_access) && self.account_count_access != 0)
+        {
+            return false;
+        }
+
+        let conflict = |writes: &HashSet<H256>, accessor: &AccessList| {
+            writes
+                .iter()
+                .any(|key| accessor.read_keys.contains(key) || accessor.write_keys.contains(key))
+        };
+        !conflict(&self.write_keys, other) && !conflict(&other.write_keys, self)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct RunResult {
     pub return_data: Bytes,
@@ -33,6 +85,7 @@ pub struct RunResult {
     pub cycles: CycleMeter,
     pub read_data_hashes: HashSet<H256>,
     pub write_data_hashes: HashSet<H256>,
+    pub access_list: AccessList,
 }
 
 impl CellOutput {
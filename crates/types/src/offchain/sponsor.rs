@@ -0,0 +1,84 @@
+use gw_hash::blake2b::new_blake2b;
+use sparse_merkle_tree::H256;
+
+use crate::bytes::Bytes;
+use crate::packed::{
+    L2Transaction, RawL2Transaction, SponsoredTxSignature, TxSignatureEnvelope,
+    TxSignatureEnvelopeReader, TxSignatureEnvelopeUnionReader,
+};
+use crate::prelude::*;
+
+/// Sponsored (fee-payer) transaction
+///
+/// A sponsored transaction carries a `TxSignatureEnvelope` molecule union in the
+/// `signature` field of `L2Transaction`, see `SponsoredTxSignature` in godwoken.mol.
+///
+/// The sender signs the transaction as usual, the sponsor signs the `signing_message`,
+/// then the sponsor pays `fee` in CKB to the block producer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SponsoredSignature {
+    pub sponsor_id: u32,
+    pub fee: u128,
+    pub sender_signature: Bytes,
+    pub sponsor_signature: Bytes,
+}
+
+impl SponsoredSignature {
+    /// Returns `None` if the transaction is not sponsored
+    pub fn from_tx(tx: &L2Transaction) -> Option<Self> {
+        let signature: Bytes = tx.signature().unpack();
+        Self::from_slice(&signature)
+    }
+
+    /// Returns `None` unless `signature` is exactly a `TxSignatureEnvelope`
+    pub fn from_slice(signature: &[u8]) -> Option<Self> {
+        let envelope = TxSignatureEnvelopeReader::from_slice(signature).ok()?;
+        match envelope.to_enum() {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(sponsored) => {
+                Some(SponsoredSignature {
+                    sponsor_id: sponsored.sponsor_id().unpack(),
+                    fee: sponsored.fee().unpack(),
+                    sender_signature: sponsored.sender_signature().unpack(),
+                    sponsor_signature: sponsored.sponsor_signature().unpack(),
+                })
+            }
+        }
+    }
+
+    pub fn as_bytes(&self) -> Bytes {
+        let sponsored = SponsoredTxSignature::new_builder()
+            .sponsor_id(self.sponsor_id.pack())
+            .fee(self.fee.pack())
+            .sender_signature(self.sender_signature.pack())
+            .sponsor_signature(self.sponsor_signature.pack())
+            .build();
+        TxSignatureEnvelope::new_builder()
+            .set(sponsored)
+            .build()
+            .as_bytes()
+    }
+
+    /// The transaction signed by the sender, used to verify the sender's signature
+    pub fn sender_signed_tx(&self, tx: &L2Transaction) -> L2Transaction {
+        tx.clone()
+            .as_builder()
+            .signature(self.sender_signature.pack())
+            .build()
+    }
+
+    /// Message signed by the sponsor
+    pub fn signing_message(
+        &self,
+        rollup_type_script_hash: &H256,
+        raw_tx: &RawL2Transaction,
+    ) -> H256 {
+        let mut hasher = new_blake2b();
+        hasher.update(rollup_type_script_hash.as_slice());
+        hasher.update(&raw_tx.hash());
+        hasher.update(&self.sponsor_id.to_le_bytes());
+        hasher.update(&self.fee.to_le_bytes());
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        message.into()
+    }
+}
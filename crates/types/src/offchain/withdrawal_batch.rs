@@ -0,0 +1,86 @@
+use gw_hash::blake2b::new_blake2b;
+use sparse_merkle_tree::H256;
+
+use crate::bytes::Bytes;
+use crate::packed::{
+    self, RawWithdrawalRequest, WithdrawalRequestExtra, WithdrawalSignatureEnvelope,
+    WithdrawalSignatureEnvelopeReader, WithdrawalSignatureEnvelopeUnionReader,
+};
+use crate::prelude::*;
+
+/// Batch withdrawal signature
+///
+/// A batch of withdrawal requests is signed once, every request of the batch carries a
+/// `WithdrawalSignatureEnvelope` molecule union in the `signature` field of
+/// `WithdrawalRequest`, see `BatchWithdrawalSignature` in godwoken.mol.
+///
+/// The owner signs the `signing_message` which commits to all requests of the batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchWithdrawalSignature {
+    pub index: u32,
+    pub raw_request_hashes: Vec<H256>,
+    pub signature: Bytes,
+}
+
+impl BatchWithdrawalSignature {
+    /// Returns `None` if the withdrawal isn't part of a batch
+    pub fn from_withdrawal(withdrawal: &WithdrawalRequestExtra) -> Option<Self> {
+        let signature: Bytes = withdrawal.request().signature().unpack();
+        Self::from_slice(&signature)
+    }
+
+    /// Returns `None` unless `signature` is exactly a `WithdrawalSignatureEnvelope`
+    pub fn from_slice(signature: &[u8]) -> Option<Self> {
+        let envelope = WithdrawalSignatureEnvelopeReader::from_slice(signature).ok()?;
+        let WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(batch) =
+            envelope.to_enum();
+        let index: u32 = batch.index().unpack();
+        let raw_request_hashes: Vec<H256> = batch.raw_request_hashes().unpack();
+        if index as usize >= raw_request_hashes.len() {
+            return None;
+        }
+        Some(BatchWithdrawalSignature {
+            index,
+            raw_request_hashes,
+            signature: batch.signature().unpack(),
+        })
+    }
+
+    pub fn as_bytes(&self) -> Bytes {
+        let batch = packed::BatchWithdrawalSignature::new_builder()
+            .index(self.index.pack())
+            .raw_request_hashes(self.raw_request_hashes.as_slice().pack())
+            .signature(self.signature.pack())
+            .build();
+        WithdrawalSignatureEnvelope::new_builder()
+            .set(batch)
+            .build()
+            .as_bytes()
+    }
+
+    /// Whether the raw request is the one at `index` of the batch
+    pub fn contains(&self, raw: &RawWithdrawalRequest) -> bool {
+        let raw_hash: H256 = raw.hash().into();
+        self.raw_request_hashes.get(self.index as usize) == Some(&raw_hash)
+    }
+
+    /// Message signed by the owner, commits to all requests of the batch
+    pub fn signing_message(&self, rollup_type_script_hash: &H256) -> H256 {
+        Self::calc_signing_message(rollup_type_script_hash, &self.raw_request_hashes)
+    }
+
+    pub fn calc_signing_message(
+        rollup_type_script_hash: &H256,
+        raw_request_hashes: &[H256],
+    ) -> H256 {
+        let mut hasher = new_blake2b();
+        hasher.update(rollup_type_script_hash.as_slice());
+        hasher.update(&(raw_request_hashes.len() as u32).to_le_bytes());
+        for hash in raw_request_hashes {
+            hasher.update(hash.as_slice());
+        }
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        message.into()
+    }
+}
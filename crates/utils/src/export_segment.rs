@@ -0,0 +1,392 @@
+//! Segmented block export format
+//!
+//! Blocks are exported to a dir of segment files and a `manifest.json`. A segment contains a
+//! consecutive range of blocks, each block is a zstd compressed chunk prefixed by its length:
+//!
+//! ```text
+//! [u32 le compressed size][compressed packed::ExportedBlock] ...
+//! ```
+//!
+//! Chunks in a segment share one zstd stream, so a segment must be decoded from the start, but
+//! segments are independent. The manifest records the block range, size and blake2b checksum
+//! of every segment, so segments can be fetched from different sources and verified before
+//! import.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use gw_common::{blake2b::new_blake2b, H256};
+use gw_types::{
+    bytes::Bytes,
+    offchain::ExportedBlock,
+    packed,
+    prelude::{Entity, Reader},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::compression::{StreamDecoder, StreamEncoder};
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+pub const SEGMENT_FORMAT_VERSION: u32 = 1;
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentInfo {
+    pub file_name: String,
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Parent hash of the first block, used to check segments are linked
+    pub parent_block_hash: String,
+    pub last_block_hash: String,
+    /// Size of the segment file in bytes
+    pub size: u64,
+    /// Blake2b hash of the segment file
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentManifest {
+    pub version: u32,
+    pub rollup_type_hash: String,
+    pub segments: Vec<SegmentInfo>,
+}
+
+impl SegmentManifest {
+    pub fn new(rollup_type_hash: &[u8]) -> Result<Self> {
+        Ok(SegmentManifest {
+            version: SEGMENT_FORMAT_VERSION,
+            rollup_type_hash: to_hex(rollup_type_hash)?,
+            segments: Vec::new(),
+        })
+    }
+
+    pub fn from_slice(slice: &[u8]) -> Result<Self> {
+        let manifest: SegmentManifest = serde_json::from_slice(slice).context("parse manifest")?;
+        if manifest.version != SEGMENT_FORMAT_VERSION {
+            bail!("unsupported segment format version {}", manifest.version);
+        }
+        for (prev, segment) in manifest
+            .segments
+            .iter()
+            .zip(manifest.segments.iter().skip(1))
+        {
+            check_linked(prev, segment)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Load the manifest in the dir, return None if it doesn't exist.
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read(&path)?;
+        Self::from_slice(&content).map(Some)
+    }
+
+    /// Save the manifest in the dir, it's replaced atomically.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE_NAME);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    pub fn check_rollup_type_hash(&self, rollup_type_hash: &[u8]) -> Result<()> {
+        let expected = to_hex(rollup_type_hash)?;
+        if self.rollup_type_hash != expected {
+            bail!(
+                "segments are exported from rollup {}, expect {}",
+                self.rollup_type_hash,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    /// The block after the last segment.
+    pub fn next_block_number(&self) -> Option<u64> {
+        self.segments.last().map(|s| s.to_block + 1)
+    }
+
+    pub fn push(&mut self, segment: SegmentInfo) -> Result<()> {
+        if let Some(prev) = self.segments.last() {
+            check_linked(prev, &segment)?;
+        }
+        self.segments.push(segment);
+        Ok(())
+    }
+
+    /// Segments which contain blocks after the block number.
+    pub fn segments_after(&self, block_number: u64) -> impl Iterator<Item = &SegmentInfo> {
+        self.segments
+            .iter()
+            .filter(move |s| s.to_block > block_number)
+    }
+}
+
+fn check_linked(prev: &SegmentInfo, segment: &SegmentInfo) -> Result<()> {
+    ensure!(
+        segment.from_block == prev.to_block + 1,
+        "segment {} doesn't follow segment {}",
+        segment.file_name,
+        prev.file_name
+    );
+    ensure!(
+        segment.parent_block_hash == prev.last_block_hash,
+        "segment {} isn't linked to segment {}",
+        segment.file_name,
+        prev.file_name
+    );
+    Ok(())
+}
+
+/// Encode consecutive blocks into a segment.
+pub struct SegmentWriter {
+    encoder: StreamEncoder<'static>,
+    data: Vec<u8>,
+    from_block: Option<u64>,
+    parent_block_hash: H256,
+    last_block: Option<(u64, H256)>,
+}
+
+impl SegmentWriter {
+    pub fn new(level: i32) -> Result<Self> {
+        Ok(SegmentWriter {
+            encoder: StreamEncoder::new(level)?,
+            data: Vec::new(),
+            from_block: None,
+            parent_block_hash: H256::zero(),
+            last_block: None,
+        })
+    }
+
+    pub fn block_count(&self) -> u64 {
+        match (self.from_block, self.last_block) {
+            (Some(from), Some((to, _))) => to - from + 1,
+            _ => 0,
+        }
+    }
+
+    pub fn push(&mut self, block: ExportedBlock) -> Result<()> {
+        let block_number = block.block_number();
+        let block_hash = block.block_hash();
+        match self.last_block {
+            Some((last_number, last_hash)) => {
+                ensure!(
+                    block_number == last_number + 1 && block.parent_block_hash() == last_hash,
+                    "block {} doesn't follow block {}",
+                    block_number,
+                    last_number
+                );
+            }
+            None => {
+                self.from_block = Some(block_number);
+                self.parent_block_hash = block.parent_block_hash();
+            }
+        }
+
+        let packed: packed::ExportedBlock = block.into();
+        let compressed = self.encoder.encode(packed.as_slice())?;
+        self.data
+            .extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(&compressed);
+        self.last_block = Some((block_number, block_hash));
+        Ok(())
+    }
+
+    /// Return the segment info and the segment file content.
+    pub fn finish(self) -> Result<(SegmentInfo, Vec<u8>)> {
+        let (from_block, (to_block, last_block_hash)) = match (self.from_block, self.last_block) {
+            (Some(from), Some(last)) => (from, last),
+            _ => bail!("empty segment"),
+        };
+        let info = SegmentInfo {
+            file_name: format!("segment_{}_{}.gwseg", from_block, to_block),
+            from_block,
+            to_block,
+            parent_block_hash: to_hex(self.parent_block_hash.as_slice())?,
+            last_block_hash: to_hex(last_block_hash.as_slice())?,
+            size: self.data.len() as u64,
+            checksum: checksum(&self.data)?,
+        };
+        Ok((info, self.data))
+    }
+}
+
+/// Verify the segment file content against the manifest and decode its blocks.
+pub fn read_segment(info: &SegmentInfo, data: &[u8]) -> Result<Vec<ExportedBlock>> {
+    ensure!(
+        data.len() as u64 == info.size,
+        "segment {} size {} mismatch, expect {}",
+        info.file_name,
+        data.len(),
+        info.size
+    );
+    let actual_checksum = checksum(data)?;
+    ensure!(
+        actual_checksum == info.checksum,
+        "segment {} checksum {} mismatch, expect {}",
+        info.file_name,
+        actual_checksum,
+        info.checksum
+    );
+
+    let mut decoder = StreamDecoder::new();
+    let mut blocks: Vec<ExportedBlock> = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        ensure!(rest.len() >= 4, "segment {} corrupted", info.file_name);
+        let mut size_buf = [0u8; 4];
+        size_buf.copy_from_slice(&rest[..4]);
+        let size = u32::from_le_bytes(size_buf) as usize;
+        let chunk = rest
+            .get(4..4 + size)
+            .ok_or_else(|| anyhow!("segment {} corrupted", info.file_name))?;
+        rest = &rest[4 + size..];
+
+        let buf = decoder.decode(chunk)?;
+        packed::ExportedBlockReader::verify(&buf, false)?;
+        let block: ExportedBlock = packed::ExportedBlock::new_unchecked(Bytes::from(buf)).into();
+
+        let expected_number = info.from_block + blocks.len() as u64;
+        ensure!(
+            block.block_number() == expected_number,
+            "segment {} expect block {}, got block {}",
+            info.file_name,
+            expected_number,
+            block.block_number()
+        );
+        let parent_block_hash = match blocks.last() {
+            Some(prev) => to_hex(prev.block_hash().as_slice())?,
+            None => info.parent_block_hash.clone(),
+        };
+        ensure!(
+            to_hex(block.parent_block_hash().as_slice())? == parent_block_hash,
+            "segment {} block {} isn't linked to its parent",
+            info.file_name,
+            expected_number
+        );
+        blocks.push(block);
+    }
+
+    let last_block = blocks
+        .last()
+        .ok_or_else(|| anyhow!("segment {} is empty", info.file_name))?;
+    ensure!(
+        last_block.block_number() == info.to_block
+            && to_hex(last_block.block_hash().as_slice())? == info.last_block_hash,
+        "segment {} last block mismatch",
+        info.file_name
+    );
+    Ok(blocks)
+}
+
+pub fn checksum(data: &[u8]) -> Result<String> {
+    let mut hasher = new_blake2b();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    to_hex(&hash)
+}
+
+fn to_hex(bytes: &[u8]) -> Result<String> {
+    Ok(format!("0x{}", faster_hex::hex_string(bytes)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use gw_common::{h256_ext::H256Ext, H256};
+    use gw_types::{
+        offchain::ExportedBlock,
+        packed::{L2Block, RawL2Block},
+        prelude::*,
+    };
+
+    use super::*;
+
+    fn build_blocks(count: u64) -> Vec<ExportedBlock> {
+        let mut parent_block_hash = H256::zero();
+        (0..count)
+            .map(|number| {
+                let raw = RawL2Block::new_builder()
+                    .number(number.pack())
+                    .parent_block_hash(parent_block_hash.pack())
+                    .build();
+                let block = L2Block::new_builder().raw(raw).build();
+                parent_block_hash = block.hash().into();
+                ExportedBlock {
+                    block,
+                    post_global_state: Default::default(),
+                    deposit_info_vec: Default::default(),
+                    deposit_asset_scripts: Default::default(),
+                    withdrawals: Default::default(),
+                    bad_block_hashes: None,
+                    submit_tx_hash: Some(H256::from_u32(number as u32)),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_write_and_read_segments() {
+        let mut manifest = SegmentManifest::new(&[1u8; 32]).unwrap();
+        let mut segments = Vec::new();
+        let mut blocks = build_blocks(5).into_iter();
+        for segment_blocks in [3, 2] {
+            let mut writer = SegmentWriter::new(DEFAULT_COMPRESSION_LEVEL).unwrap();
+            for block in blocks.by_ref().take(segment_blocks) {
+                writer.push(block).unwrap();
+            }
+            assert_eq!(writer.block_count(), segment_blocks as u64);
+            let (info, data) = writer.finish().unwrap();
+            manifest.push(info.clone()).unwrap();
+            segments.push((info, data));
+        }
+        assert_eq!(manifest.next_block_number(), Some(5));
+        assert_eq!(manifest.segments_after(2).count(), 1);
+
+        let manifest =
+            SegmentManifest::from_slice(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+        manifest.check_rollup_type_hash(&[1u8; 32]).unwrap();
+        assert!(manifest.check_rollup_type_hash(&[2u8; 32]).is_err());
+
+        let mut read_blocks = Vec::new();
+        for (info, data) in segments.iter() {
+            read_blocks.extend(read_segment(info, data).unwrap());
+        }
+        assert_eq!(read_blocks, build_blocks(5));
+
+        // corrupted segment
+        let (info, data) = &segments[0];
+        let mut corrupted = data.clone();
+        corrupted[5] ^= 1;
+        assert!(read_segment(info, &corrupted).is_err());
+    }
+
+    #[test]
+    fn test_segments_must_be_linked() {
+        let mut blocks = build_blocks(4).into_iter();
+        let mut writer = SegmentWriter::new(DEFAULT_COMPRESSION_LEVEL).unwrap();
+        writer.push(blocks.next().unwrap()).unwrap();
+        let second = blocks.next().unwrap();
+        assert!(writer.push(blocks.next().unwrap()).is_err());
+        let (first, _) = writer.finish().unwrap();
+
+        let mut writer = SegmentWriter::new(DEFAULT_COMPRESSION_LEVEL).unwrap();
+        writer.push(blocks.next().unwrap()).unwrap();
+        let (fourth, _) = writer.finish().unwrap();
+
+        let mut manifest = SegmentManifest::new(&[0u8; 32]).unwrap();
+        manifest.push(first).unwrap();
+        assert!(manifest.push(fourth).is_err());
+
+        let mut writer = SegmentWriter::new(DEFAULT_COMPRESSION_LEVEL).unwrap();
+        writer.push(second).unwrap();
+        let (second, _) = writer.finish().unwrap();
+        manifest.push(second).unwrap();
+    }
+}
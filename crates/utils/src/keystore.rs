@@ -0,0 +1,444 @@
+//! Encrypted private key files, compatible with Ethereum keystore v3
+//!
+//! The private key is encrypted by aes-128-ctr with a key derived from the passphrase by
+//! scrypt (or pbkdf2 when decrypting), the mac is keccak256(derived_key[16..32] | ciphertext).
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use aes::Aes128;
+use anyhow::{anyhow, bail, Context, Result};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use gw_config::PassphraseSource;
+use hmac::Hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use subtle::ConstantTimeEq;
+
+pub const KEYSTORE_VERSION: u8 = 3;
+pub const PASSPHRASE_ENV: &str = "GODWOKEN_KEYSTORE_PASSPHRASE";
+/// scrypt n = 2^18, r = 8, p = 1, same as geth
+pub const DEFAULT_SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+/// Memory used by scrypt is 128 * r * n bytes, keystores asking for more than 1 GiB are rejected
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+const DKLEN: usize = 32;
+const CIPHER: &str = "aes-128-ctr";
+const KDF_SCRYPT: &str = "scrypt";
+const KDF_PBKDF2: &str = "pbkdf2";
+const PRF_HMAC_SHA256: &str = "hmac-sha256";
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+lazy_static::lazy_static! {
+    // An inherited fd can only be read once, wallets sharing a passphrase fd read it from here
+    static ref FD_PASSPHRASES: Mutex<HashMap<i32, String>> = Default::default();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u8,
+    pub id: String,
+    /// Eth address of the private key, without 0x prefix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(alias = "Crypto")]
+    pub crypto: KeystoreCrypto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u64,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl Keystore {
+    /// Encrypt the private key with scrypt n = 2^log_n
+    pub fn encrypt(privkey: &[u8; 32], passphrase: &[u8], scrypt_log_n: u8) -> Result<Self> {
+        let salt: [u8; 32] = rand::random();
+        let iv: [u8; 16] = rand::random();
+
+        let mut derived_key = [0u8; DKLEN];
+        let params = scrypt::Params::new(scrypt_log_n, SCRYPT_R, SCRYPT_P)
+            .map_err(|err| anyhow!("scrypt params: {}", err))?;
+        scrypt::scrypt(passphrase, &salt, &params, &mut derived_key)
+            .map_err(|err| anyhow!("scrypt: {}", err))?;
+
+        let mut ciphertext = privkey.to_vec();
+        Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+            .map_err(|err| anyhow!("aes: {}", err))?
+            .apply_keystream(&mut ciphertext);
+        let mac = keystore_mac(&derived_key, &ciphertext);
+
+        let crypto = KeystoreCrypto {
+            cipher: CIPHER.to_string(),
+            cipherparams: CipherParams { iv: to_hex(&iv)? },
+            ciphertext: to_hex(&ciphertext)?,
+            kdf: KDF_SCRYPT.to_string(),
+            kdfparams: KdfParams::Scrypt {
+                dklen: DKLEN,
+                n: 1u64 << scrypt_log_n,
+                r: SCRYPT_R,
+                p: SCRYPT_P,
+                salt: to_hex(&salt)?,
+            },
+            mac: to_hex(&mac)?,
+        };
+        Ok(Keystore {
+            version: KEYSTORE_VERSION,
+            id: random_uuid()?,
+            address: Some(to_hex(&eth_address(privkey)?)?),
+            crypto,
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &[u8]) -> Result<[u8; 32]> {
+        if self.version != KEYSTORE_VERSION {
+            bail!("unsupported keystore version {}", self.version);
+        }
+        let crypto = &self.crypto;
+        if crypto.cipher != CIPHER {
+            bail!("unsupported keystore cipher {}", crypto.cipher);
+        }
+
+        let mut derived_key = [0u8; DKLEN];
+        match (crypto.kdf.as_str(), &crypto.kdfparams) {
+            (
+                KDF_SCRYPT,
+                KdfParams::Scrypt {
+                    dklen,
+                    n,
+                    r,
+                    p,
+                    salt,
+                },
+            ) if *dklen == DKLEN => {
+                if !n.is_power_of_two() || *n < 2 {
+                    bail!("invalid scrypt n {}", n);
+                }
+                if n.checked_mul(128 * u64::from(*r))
+                    .map_or(true, |mem| mem > MAX_SCRYPT_MEMORY)
+                {
+                    bail!("scrypt n {} r {} exceed the memory limit", n, r);
+                }
+                let log_n = n.trailing_zeros() as u8;
+                // RFC 7914 requires n < 2^(16 * r), keystores breaking it aren't supported
+                let params = scrypt::Params::new(log_n, *r, *p)
+                    .map_err(|err| anyhow!("scrypt params: {}", err))?;
+                scrypt::scrypt(passphrase, &from_hex(salt)?, &params, &mut derived_key)
+                    .map_err(|err| anyhow!("scrypt: {}", err))?;
+            }
+            (
+                KDF_PBKDF2,
+                KdfParams::Pbkdf2 {
+                    dklen,
+                    c,
+                    prf,
+                    salt,
+                },
+            ) if *dklen == DKLEN && prf == PRF_HMAC_SHA256 => {
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase, &from_hex(salt)?, *c, &mut derived_key);
+            }
+            (kdf, _) => bail!("unsupported keystore kdf {}", kdf),
+        }
+
+        let ciphertext = from_hex(&crypto.ciphertext)?;
+        let mac = keystore_mac(&derived_key, &ciphertext);
+        if !bool::from(mac[..].ct_eq(&from_hex(&crypto.mac)?)) {
+            bail!("keystore mac mismatch, wrong passphrase?");
+        }
+        if ciphertext.len() != 32 {
+            bail!("invalid keystore ciphertext length {}", ciphertext.len());
+        }
+
+        let mut privkey = [0u8; 32];
+        privkey.copy_from_slice(&ciphertext);
+        Aes128Ctr::new_from_slices(&derived_key[..16], &from_hex(&crypto.cipherparams.iv)?)
+            .map_err(|err| anyhow!("aes: {}", err))?
+            .apply_keystream(&mut privkey);
+        Ok(privkey)
+    }
+}
+
+/// Read a private key from a plaintext hex file or an encrypted keystore file.
+///
+/// The passphrase of a keystore file is read from the env `GODWOKEN_KEYSTORE_PASSPHRASE` if
+/// the source is not set.
+pub fn read_privkey(path: &Path, passphrase: Option<&PassphraseSource>) -> Result<[u8; 32]> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("read private key from {}", path.to_string_lossy()))?;
+    if content.trim_start().starts_with('{') {
+        let keystore: Keystore = serde_json::from_str(&content).context("parse keystore")?;
+        let passphrase = match passphrase {
+            Some(source) => read_passphrase(source)?,
+            None => read_passphrase(&PassphraseSource::Env(PASSPHRASE_ENV.to_string()))?,
+        };
+        return keystore.decrypt(passphrase.as_bytes());
+    }
+    if passphrase.is_some() {
+        bail!("expect a keystore file at {}", path.to_string_lossy());
+    }
+
+    // Private key files exported by ckb-cli contain the chain code in the second line
+    let privkey = content
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("private key file is empty"))?;
+    let decoded = from_hex(privkey.trim_start_matches("0x"))?;
+    if decoded.len() != 32 {
+        bail!("invalid private key length {}", decoded.len());
+    }
+    let mut privkey = [0u8; 32];
+    privkey.copy_from_slice(&decoded);
+    Ok(privkey)
+}
+
+pub fn read_passphrase(source: &PassphraseSource) -> Result<String> {
+    match source {
+        PassphraseSource::Env(name) => {
+            std::env::var(name).with_context(|| format!("read passphrase from env {}", name))
+        }
+        PassphraseSource::Fd(fd) => {
+            let mut passphrases = FD_PASSPHRASES.lock().expect("lock passphrases");
+            if let Some(passphrase) = passphrases.get(fd) {
+                return Ok(passphrase.clone());
+            }
+            let passphrase = read_fd(*fd)?;
+            passphrases.insert(*fd, passphrase.clone());
+            Ok(passphrase)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32) -> Result<String> {
+    use std::io::Read;
+    use std::os::unix::io::FromRawFd;
+
+    // Safety: the fd is passed to us by the parent process and only read here
+    let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .with_context(|| format!("read passphrase from fd {}", fd))?;
+    // Passphrase written by `echo` ends with a newline
+    Ok(content.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+#[cfg(not(unix))]
+fn read_fd(fd: i32) -> Result<String> {
+    bail!("read passphrase from fd {} is only supported on unix", fd)
+}
+
+fn keystore_mac(derived_key: &[u8; DKLEN], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived_key[16..32]);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+fn eth_address(privkey: &[u8; 32]) -> Result<[u8; 20]> {
+    let privkey = secp256k1::SecretKey::from_slice(privkey)?;
+    let pubkey = secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), &privkey);
+    let hash = Keccak256::digest(&pubkey.serialize_uncompressed()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Ok(address)
+}
+
+fn random_uuid() -> Result<String> {
+    let mut bytes: [u8; 16] = rand::random();
+    // Version 4, variant 1
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = to_hex(&bytes)?;
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+fn to_hex(data: &[u8]) -> Result<String> {
+    Ok(faster_hex::hex_string(data)?)
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>> {
+    let s = s.trim_start_matches("0x");
+    if s.len() % 2 != 0 {
+        bail!("invalid hex length {}", s.len());
+    }
+    let mut buf = vec![0u8; s.len() / 2];
+    faster_hex::hex_decode(s.as_bytes(), &mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fast scrypt params for tests
+    const TEST_SCRYPT_LOG_N: u8 = 10;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let privkey = [7u8; 32];
+        let keystore = Keystore::encrypt(&privkey, b"passphrase", TEST_SCRYPT_LOG_N).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        let keystore: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(keystore.decrypt(b"passphrase").unwrap(), privkey);
+        assert!(keystore.decrypt(b"wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_read_privkey() {
+        let dir = tempfile::tempdir().unwrap();
+        let privkey = [9u8; 32];
+
+        let plain_path = dir.path().join("privkey");
+        std::fs::write(&plain_path, format!("0x{}\n", to_hex(&privkey).unwrap())).unwrap();
+        assert_eq!(read_privkey(&plain_path, None).unwrap(), privkey);
+
+        let keystore_path = dir.path().join("keystore.json");
+        let keystore = Keystore::encrypt(&privkey, b"passphrase", TEST_SCRYPT_LOG_N).unwrap();
+        std::fs::write(&keystore_path, serde_json::to_string(&keystore).unwrap()).unwrap();
+        let env = "GODWOKEN_TEST_KEYSTORE_PASSPHRASE";
+        std::env::set_var(env, "passphrase");
+        let source = PassphraseSource::Env(env.to_string());
+        assert_eq!(
+            read_privkey(&keystore_path, Some(&source)).unwrap(),
+            privkey
+        );
+        assert!(read_privkey(&plain_path, Some(&source)).is_err());
+    }
+
+    // Test vectors of Web3 Secret Storage Definition, the passphrase is "testpassword"
+    const TEST_VECTOR_PRIVKEY: &str =
+        "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    #[test]
+    fn test_keystore_pbkdf2_test_vector() {
+        let keystore: Keystore = serde_json::from_str(
+            r#"{
+                "crypto" : {
+                    "cipher" : "aes-128-ctr",
+                    "cipherparams" : {
+                        "iv" : "6087dab2f9fdbbfaddc31a909735c1e6"
+                    },
+                    "ciphertext" : "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+                    "kdf" : "pbkdf2",
+                    "kdfparams" : {
+                        "c" : 262144,
+                        "dklen" : 32,
+                        "prf" : "hmac-sha256",
+                        "salt" : "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+                    },
+                    "mac" : "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+                },
+                "id" : "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+                "version" : 3
+            }"#,
+        )
+        .unwrap();
+        let privkey = keystore.decrypt(b"testpassword").unwrap();
+        assert_eq!(to_hex(&privkey).unwrap(), TEST_VECTOR_PRIVKEY);
+        assert!(keystore.decrypt(b"wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_keystore_scrypt_test_vector() {
+        // Keystore of geth with the light scrypt params, the passphrase is "geth"
+        let keystore: Keystore = serde_json::from_str(
+            r#"{
+                "address" : "8e049da484e853d92d118be16377ff616275d470",
+                "crypto" : {
+                    "cipher" : "aes-128-ctr",
+                    "ciphertext" : "7912715bd7c0754f393c83fb76e381e7390cf0d31bdf5b6cfb1e360b765b6afe",
+                    "cipherparams" : {
+                        "iv" : "49e5a2fc31dae4aa0277b8c498c546fd"
+                    },
+                    "kdf" : "scrypt",
+                    "kdfparams" : {
+                        "dklen" : 32,
+                        "n" : 4096,
+                        "p" : 6,
+                        "r" : 8,
+                        "salt" : "5ae79ae9e57fd62902695637cfe134d14a7120fa6dc7bf1e9cf97e636eaccae1"
+                    },
+                    "mac" : "0a5194f97f6ffae80aa15d23f0a11a001b276fe7b76f2f3762b09df77fc81256"
+                },
+                "id" : "afb46908-5019-41d6-bc60-e369d3b5e1a7",
+                "version" : 3
+            }"#,
+        )
+        .unwrap();
+        let privkey = keystore.decrypt(b"geth").unwrap();
+        assert_eq!(
+            to_hex(&eth_address(&privkey).unwrap()).unwrap(),
+            "8e049da484e853d92d118be16377ff616275d470"
+        );
+        assert!(keystore.decrypt(b"wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_keystore_unsupported_scrypt_params() {
+        let keystore = Keystore::encrypt(&[1u8; 32], b"passphrase", TEST_SCRYPT_LOG_N).unwrap();
+        let with_params = |n: u64, r: u32| {
+            let mut keystore = keystore.clone();
+            if let KdfParams::Scrypt {
+                n: ref mut kdf_n,
+                r: ref mut kdf_r,
+                ..
+            } = keystore.crypto.kdfparams
+            {
+                *kdf_n = n;
+                *kdf_r = r;
+            }
+            keystore
+        };
+        // n >= 2^(16 * r)
+        assert!(with_params(1 << 18, 1).decrypt(b"passphrase").is_err());
+        // 128 * r * n > 1 GiB
+        assert!(with_params(1 << 21, 8).decrypt(b"passphrase").is_err());
+        assert!(with_params(1 << 40, 8).decrypt(b"passphrase").is_err());
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(from_hex("0x0102").unwrap(), vec![1, 2]);
+        assert!(from_hex("0x012").is_err());
+        assert!(from_hex("0x01zz").is_err());
+    }
+}
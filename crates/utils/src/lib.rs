@@ -2,13 +2,16 @@ pub mod abort_on_drop;
 pub mod compression;
 pub mod exponential_backoff;
 pub mod export_block;
+pub mod export_segment;
 pub mod fee;
 pub mod genesis_info;
+pub mod keystore;
 pub mod liveness;
 pub mod local_cells;
 pub mod polyjuice_parser;
 mod query_rollup_cell;
 pub mod script_log;
+pub mod signer;
 pub mod since;
 pub mod transaction_skeleton;
 pub mod wallet;
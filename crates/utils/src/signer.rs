@@ -0,0 +1,678 @@
+//! Transaction signers
+//!
+//! `Wallet` signs with a local private key. `RemoteSigner` asks a JSON-RPC signer service
+//! over a unix socket or https with mTLS, so operator keys don't have to sit on the node host.
+//! `PolicySigner` wraps a local wallet with per-request policy checks on input cells resolved
+//! by its own CKB RPC, `serve_unix` exposes any signer as such a service, it is the stand-in
+//! signer service used by tests.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use ckb_types::{prelude::Entity as _, H256};
+use gw_config::{RemoteSignerConfig, WalletConfig};
+use gw_jsonrpc_types::ckb_jsonrpc_types::{self, JsonBytes};
+use gw_rpc_client::ckb_client::CKBClient;
+use gw_types::{
+    bytes::Bytes,
+    offchain::CellInfo,
+    packed::{CellOutput, OutPoint, Script, Transaction},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::transaction_skeleton::{Signature, TransactionSkeleton};
+use crate::wallet::{sighash_message, Wallet};
+
+pub const SIGN_TRANSACTION_METHOD: &str = "sign_transaction";
+const UNIX_URL_PREFIX: &str = "unix://";
+
+#[derive(Debug, Clone)]
+pub struct SignRequest {
+    /// Transaction sealed with zero signature placeholders
+    pub tx: Transaction,
+    /// Cells of the transaction inputs, in inputs order
+    pub input_cells: Vec<CellInfo>,
+    /// Input indexes of each signing group, inputs in a group share the same lock
+    pub groups: Vec<Vec<usize>>,
+}
+
+#[async_trait]
+pub trait Signer: Send + Sync {
+    fn lock_script(&self) -> &Script;
+
+    /// Returns 65 bytes recoverable signatures of the sighash all messages, in groups order
+    async fn sign(&self, request: SignRequest) -> Result<Vec<[u8; 65]>>;
+
+    async fn sign_tx_skeleton(&self, tx_skeleton: TransactionSkeleton) -> Result<Transaction> {
+        let signature_entries = tx_skeleton.signature_entries();
+        let dummy_signatures: Vec<_> = {
+            let entries = signature_entries.iter();
+            entries.map(Signature::zero_bytes_from_entry).collect()
+        };
+
+        // seal a dummy tx for signing
+        let tx = tx_skeleton
+            .seal(&signature_entries, dummy_signatures)?
+            .transaction;
+        let request = SignRequest {
+            tx,
+            input_cells: tx_skeleton
+                .inputs()
+                .iter()
+                .map(|i| i.cell.clone())
+                .collect(),
+            groups: signature_entries
+                .iter()
+                .map(|e| e.indexes.clone())
+                .collect(),
+        };
+        let signatures = self.sign(request).await?;
+        if signatures.len() != signature_entries.len() {
+            bail!(
+                "expect {} signatures, got {}",
+                signature_entries.len(),
+                signatures.len()
+            );
+        }
+        let signatures = signature_entries.iter().zip(signatures);
+        let signatures = signatures.map(|(e, sig)| Signature::new(e.kind, sig).as_bytes());
+
+        // seal
+        let sealed_tx = tx_skeleton.seal(&signature_entries, signatures.collect())?;
+        // check fee rate
+        sealed_tx.check_fee_rate()?;
+        Ok(sealed_tx.transaction)
+    }
+}
+
+#[async_trait]
+impl Signer for Wallet {
+    fn lock_script(&self) -> &Script {
+        Wallet::lock_script(self)
+    }
+
+    async fn sign(&self, request: SignRequest) -> Result<Vec<[u8; 65]>> {
+        let sign_group = |indexes: &Vec<usize>| {
+            let message = sighash_message(&request.tx, indexes);
+            self.sign_message(message)
+        };
+        request.groups.iter().map(sign_group).collect()
+    }
+}
+
+/// Build the signer of a wallet config, a remote signer if configured
+pub fn build_signer(config: &WalletConfig) -> Result<Arc<dyn Signer>> {
+    match config.remote_signer {
+        Some(ref remote) => {
+            let signer = RemoteSigner::new(config.lock.clone().into(), remote)?;
+            Ok(Arc::new(signer))
+        }
+        None => Ok(Arc::new(Wallet::from_config(config)?)),
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignerPolicy {
+    /// Locks of the signing groups, the signer lock is always allowed
+    #[serde(default)]
+    pub allowed_lock_hashes: HashSet<H256>,
+    /// Type scripts of the cells in the signing groups, cells without type are always allowed
+    #[serde(default)]
+    pub allowed_type_hashes: HashSet<H256>,
+    /// Max fee of the transaction in shannons
+    #[serde(default)]
+    pub max_fee: Option<u64>,
+}
+
+/// Resolve cells by out points
+#[async_trait]
+pub trait CellResolver: Send + Sync {
+    async fn resolve_cell(&self, out_point: &OutPoint) -> Result<Option<CellInfo>>;
+}
+
+#[async_trait]
+impl CellResolver for CKBClient {
+    async fn resolve_cell(&self, out_point: &OutPoint) -> Result<Option<CellInfo>> {
+        let tx = match self.get_transaction(out_point.tx_hash().unpack()).await? {
+            Some(tx) => tx,
+            None => return Ok(None),
+        };
+
+        let index: u32 = out_point.index().unpack();
+        let raw_tx = tx.raw();
+        let output = match raw_tx.outputs().get(index as usize) {
+            Some(output) => output,
+            None => return Ok(None),
+        };
+        let data = {
+            let data = raw_tx.outputs_data().get(index as usize);
+            data.map(|b| b.unpack()).unwrap_or_else(Bytes::new)
+        };
+
+        Ok(Some(CellInfo {
+            out_point: out_point.to_owned(),
+            output,
+            data,
+        }))
+    }
+}
+
+/// A local signer refusing requests not allowed by the policy.
+///
+/// Input cells reported by the requester are checked against the cells resolved by the
+/// signer's own CKB RPC, the signature only commits to their out points.
+pub struct PolicySigner {
+    wallet: Wallet,
+    policy: SignerPolicy,
+    cell_resolver: Arc<dyn CellResolver>,
+}
+
+impl PolicySigner {
+    pub fn new(wallet: Wallet, policy: SignerPolicy, cell_resolver: Arc<dyn CellResolver>) -> Self {
+        PolicySigner {
+            wallet,
+            policy,
+            cell_resolver,
+        }
+    }
+
+    pub async fn check(&self, request: &SignRequest) -> Result<()> {
+        let tx_inputs = request.tx.raw().inputs();
+        if request.input_cells.len() != tx_inputs.len() {
+            bail!(
+                "expect {} input cells, got {}",
+                tx_inputs.len(),
+                request.input_cells.len()
+            );
+        }
+        for (index, (input, cell)) in tx_inputs.into_iter().zip(&request.input_cells).enumerate() {
+            if input.previous_output().as_slice() != cell.out_point.as_slice() {
+                bail!("input cell {} out point mismatch", index);
+            }
+            let resolved = self.cell_resolver.resolve_cell(&cell.out_point).await?;
+            match resolved {
+                Some(resolved)
+                    if resolved.output.as_slice() == cell.output.as_slice()
+                        && resolved.data == cell.data => {}
+                Some(_) => bail!("input cell {} mismatch the cell on chain", index),
+                None => bail!("input cell {} not found", index),
+            }
+        }
+
+        let signer_lock_hash = H256(self.wallet.lock_script().hash());
+        let mut signed = HashSet::new();
+        for group in &request.groups {
+            let first = *group
+                .first()
+                .ok_or_else(|| anyhow!("empty signing group"))?;
+            let lock_hash = match request.input_cells.get(first) {
+                Some(cell) => cell.output.lock().hash(),
+                None => bail!("signing group input {} out of bound", first),
+            };
+            let lock_hash = H256(lock_hash);
+            if lock_hash != signer_lock_hash
+                && !self.policy.allowed_lock_hashes.contains(&lock_hash)
+            {
+                bail!("lock {:#x} is not allowed", lock_hash);
+            }
+
+            for &index in group {
+                let cell = match request.input_cells.get(index) {
+                    Some(cell) => cell,
+                    None => bail!("signing group input {} out of bound", index),
+                };
+                if !signed.insert(index) {
+                    bail!("input {} in multiple signing groups", index);
+                }
+                if H256(cell.output.lock().hash()) != lock_hash {
+                    bail!("input {} lock differs from its signing group", index);
+                }
+                if let Some(type_) = cell.output.type_().to_opt() {
+                    if !self
+                        .policy
+                        .allowed_type_hashes
+                        .contains(&H256(type_.hash()))
+                    {
+                        bail!("input {} type is not allowed", index);
+                    }
+                }
+            }
+        }
+
+        if let Some(max_fee) = self.policy.max_fee {
+            let capacity = |output: &CellOutput| -> u64 { output.capacity().unpack() };
+            let inputs_capacity: u64 = request
+                .input_cells
+                .iter()
+                .map(|c| capacity(&c.output))
+                .sum();
+            let outputs_capacity: u64 = request
+                .tx
+                .raw()
+                .outputs()
+                .into_iter()
+                .map(|o| capacity(&o))
+                .sum();
+            let fee = inputs_capacity
+                .checked_sub(outputs_capacity)
+                .ok_or_else(|| anyhow!("outputs capacity exceeds inputs capacity"))?;
+            if fee > max_fee {
+                bail!("tx fee {} exceeds max fee {}", fee, max_fee);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Signer for PolicySigner {
+    fn lock_script(&self) -> &Script {
+        self.wallet.lock_script()
+    }
+
+    async fn sign(&self, request: SignRequest) -> Result<Vec<[u8; 65]>> {
+        self.check(&request)
+            .await
+            .with_context(|| "signer policy")?;
+        Signer::sign(&self.wallet, request).await
+    }
+}
+
+enum Transport {
+    Unix(PathBuf),
+    Https {
+        client: reqwest::Client,
+        url: String,
+    },
+}
+
+/// A signer asking the JSON-RPC signer service.
+///
+/// Unix socket transport sends one JSON-RPC request per line and reads one response line.
+pub struct RemoteSigner {
+    lock: Script,
+    transport: Transport,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn new(lock: Script, config: &RemoteSignerConfig) -> Result<Self> {
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let transport = if let Some(path) = config.url.strip_prefix(UNIX_URL_PREFIX) {
+            Transport::Unix(path.into())
+        } else if config.url.starts_with("https://") {
+            let identity_path = config
+                .client_identity_path
+                .as_ref()
+                .ok_or_else(|| anyhow!("https remote signer requires client identity"))?;
+            let identity = std::fs::read(identity_path)
+                .with_context(|| format!("read {}", identity_path.display()))?;
+            let mut builder = reqwest::Client::builder()
+                .use_rustls_tls()
+                .identity(reqwest::Identity::from_pem(&identity)?)
+                .timeout(timeout);
+            if let Some(ref ca_cert_path) = config.ca_cert_path {
+                let ca_cert = std::fs::read(ca_cert_path)
+                    .with_context(|| format!("read {}", ca_cert_path.display()))?;
+                builder = builder
+                    .tls_built_in_root_certs(false)
+                    .add_root_certificate(reqwest::Certificate::from_pem(&ca_cert)?);
+            }
+            Transport::Https {
+                client: builder.build()?,
+                url: config.url.clone(),
+            }
+        } else {
+            bail!("unsupported remote signer url {}", config.url);
+        };
+
+        Ok(RemoteSigner {
+            lock,
+            transport,
+            timeout,
+        })
+    }
+
+    async fn call(&self, request: &RpcRequest) -> Result<RpcResponse> {
+        match self.transport {
+            Transport::Unix(ref path) => {
+                let call = async {
+                    let mut stream = UnixStream::connect(path).await?;
+                    let mut line = serde_json::to_vec(request)?;
+                    line.push(b'\n');
+                    stream.write_all(&line).await?;
+
+                    let mut response = String::new();
+                    BufReader::new(stream).read_line(&mut response).await?;
+                    Ok::<_, anyhow::Error>(serde_json::from_str(&response)?)
+                };
+                tokio::time::timeout(self.timeout, call)
+                    .await
+                    .map_err(|_| anyhow!("remote signer timeout"))?
+            }
+            Transport::Https {
+                ref client,
+                ref url,
+            } => {
+                let response = client.post(url).json(request).send().await?;
+                Ok(response.error_for_status()?.json().await?)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    fn lock_script(&self) -> &Script {
+        &self.lock
+    }
+
+    async fn sign(&self, request: SignRequest) -> Result<Vec<[u8; 65]>> {
+        let tx_hash = faster_hex::hex_string(&request.tx.hash())?;
+        let request = RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::Value::from(1),
+            method: SIGN_TRANSACTION_METHOD.to_string(),
+            params: vec![request.into()],
+        };
+        let response = self
+            .call(&request)
+            .await
+            .with_context(|| format!("remote sign tx 0x{}", tx_hash))?;
+        match (response.result, response.error) {
+            (_, Some(err)) => bail!("remote signer error {}: {}", err.code, err.message),
+            (Some(signatures), None) => signatures.iter().map(to_signature).collect(),
+            (None, None) => bail!("remote signer returns no result"),
+        }
+    }
+}
+
+/// Serve the signer as a JSON-RPC signer service on the unix socket
+pub async fn serve_unix(listener: UnixListener, signer: Arc<dyn Signer>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let signer = Arc::clone(&signer);
+        tokio::spawn(async move {
+            if let Err(err) = handle_unix_stream(stream, signer.as_ref()).await {
+                log::warn!("[signer] connection error: {}", err);
+            }
+        });
+    }
+}
+
+async fn handle_unix_stream(stream: UnixStream, signer: &dyn Signer) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let response = handle_request(&line, signer).await;
+        let mut line = serde_json::to_vec(&response)?;
+        line.push(b'\n');
+        writer.write_all(&line).await?;
+    }
+    Ok(())
+}
+
+async fn handle_request(line: &str, signer: &dyn Signer) -> RpcResponse {
+    let request: RpcRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return RpcResponse::error(serde_json::Value::Null, -32700, err.to_string()),
+    };
+    if request.method != SIGN_TRANSACTION_METHOD {
+        let msg = format!("method {} not found", request.method);
+        return RpcResponse::error(request.id, -32601, msg);
+    }
+    let mut params = request.params;
+    if params.len() != 1 {
+        return RpcResponse::error(request.id, -32602, "expect 1 param".to_string());
+    }
+    let params = params.remove(0);
+
+    match signer.sign(params.into()).await {
+        Ok(signatures) => RpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(
+                signatures
+                    .iter()
+                    .map(|s| JsonBytes::from_vec(s.to_vec()))
+                    .collect(),
+            ),
+            error: None,
+        },
+        Err(err) => {
+            log::warn!("[signer] refuse to sign: {:#}", err);
+            RpcResponse::error(request.id, -32000, format!("{:#}", err))
+        }
+    }
+}
+
+fn to_signature(bytes: &JsonBytes) -> Result<[u8; 65]> {
+    let bytes = bytes.as_bytes();
+    if bytes.len() != 65 {
+        bail!("invalid signature length {}", bytes.len());
+    }
+    let mut signature = [0u8; 65];
+    signature.copy_from_slice(bytes);
+    Ok(signature)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    id: serde_json::Value,
+    method: String,
+    params: Vec<SignTxParams>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcResponse {
+    jsonrpc: String,
+    id: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Vec<JsonBytes>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl RpcResponse {
+    fn error(id: serde_json::Value, code: i64, message: String) -> Self {
+        RpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(RpcError { code, message }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SignTxParams {
+    tx: ckb_jsonrpc_types::Transaction,
+    input_cells: Vec<InputCell>,
+    groups: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InputCell {
+    out_point: ckb_jsonrpc_types::OutPoint,
+    output: ckb_jsonrpc_types::CellOutput,
+    data: JsonBytes,
+}
+
+impl From<SignRequest> for SignTxParams {
+    fn from(request: SignRequest) -> Self {
+        let tx = ckb_types::packed::Transaction::new_unchecked(request.tx.as_bytes());
+        let to_input_cell = |cell: CellInfo| InputCell {
+            out_point: ckb_types::packed::OutPoint::new_unchecked(cell.out_point.as_bytes()).into(),
+            output: ckb_types::packed::CellOutput::new_unchecked(cell.output.as_bytes()).into(),
+            data: JsonBytes::from_vec(cell.data.to_vec()),
+        };
+
+        SignTxParams {
+            tx: tx.into(),
+            input_cells: request.input_cells.into_iter().map(to_input_cell).collect(),
+            groups: request.groups,
+        }
+    }
+}
+
+impl From<SignTxParams> for SignRequest {
+    fn from(params: SignTxParams) -> Self {
+        let tx: ckb_types::packed::Transaction = params.tx.into();
+        let to_cell_info = |cell: InputCell| {
+            let out_point: ckb_types::packed::OutPoint = cell.out_point.into();
+            let output: ckb_types::packed::CellOutput = cell.output.into();
+            CellInfo {
+                out_point: OutPoint::new_unchecked(out_point.as_bytes()),
+                output: CellOutput::new_unchecked(output.as_bytes()),
+                data: Bytes::from(cell.data.as_bytes().to_vec()),
+            }
+        };
+
+        SignRequest {
+            tx: Transaction::new_unchecked(tx.as_bytes()),
+            input_cells: params.input_cells.into_iter().map(to_cell_info).collect(),
+            groups: params.groups,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_types::packed::{CellInput, RawTransaction, ScriptOpt};
+    use std::collections::HashMap;
+
+    // Cells on chain, keyed by out points
+    #[derive(Default)]
+    struct MockCells(HashMap<Vec<u8>, CellInfo>);
+
+    #[async_trait]
+    impl CellResolver for MockCells {
+        async fn resolve_cell(&self, out_point: &OutPoint) -> Result<Option<CellInfo>> {
+            Ok(self.0.get(out_point.as_slice()).cloned())
+        }
+    }
+
+    fn sign_request(index: u32, lock: &Script, type_: Option<Script>, fee: u64) -> SignRequest {
+        let cell = CellInfo {
+            out_point: OutPoint::new_builder().index(index.pack()).build(),
+            output: CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(lock.to_owned())
+                .type_(ScriptOpt::new_builder().set(type_).build())
+                .build(),
+            data: Bytes::new(),
+        };
+        let input = CellInput::new_builder()
+            .previous_output(cell.out_point.clone())
+            .build();
+        let output = CellOutput::new_builder()
+            .capacity((1000 - fee).pack())
+            .build();
+        let raw = RawTransaction::new_builder()
+            .inputs(vec![input].pack())
+            .outputs(vec![output].pack())
+            .outputs_data(vec![Bytes::new()].pack())
+            .build();
+        let tx = Transaction::new_builder()
+            .raw(raw)
+            .witnesses(vec![Bytes::from(vec![0u8; 85])].pack())
+            .build();
+
+        SignRequest {
+            tx,
+            input_cells: vec![cell],
+            groups: vec![vec![0]],
+        }
+    }
+
+    async fn refused(signer: &RemoteSigner, request: SignRequest) -> String {
+        let err = signer.sign(request).await.unwrap_err();
+        format!("{:#}", err)
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_with_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("signer.sock");
+
+        let wallet = Wallet::random_secp256k1(Script::default()).unwrap();
+        let lock = wallet.lock_script().to_owned();
+        let other_wallet = Wallet::random_secp256k1(Script::default()).unwrap();
+        let type_ = Script::new_builder().args(vec![1u8].pack()).build();
+        let requests = vec![
+            sign_request(0, &lock, None, 100),
+            sign_request(1, &lock, None, 101),
+            sign_request(2, &lock, Some(type_), 100),
+            sign_request(3, other_wallet.lock_script(), None, 100),
+        ];
+        let cells = requests
+            .iter()
+            .flat_map(|request| request.input_cells.clone())
+            .map(|cell| (cell.out_point.as_slice().to_vec(), cell))
+            .collect();
+
+        let policy = SignerPolicy {
+            max_fee: Some(100),
+            ..Default::default()
+        };
+        let local_signer: Arc<dyn Signer> = Arc::new(PolicySigner::new(
+            wallet,
+            policy,
+            Arc::new(MockCells(cells)),
+        ));
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        tokio::spawn(serve_unix(listener, Arc::clone(&local_signer)));
+
+        let config = RemoteSignerConfig {
+            url: format!("unix://{}", socket_path.display()),
+            client_identity_path: None,
+            ca_cert_path: None,
+            timeout_ms: 10_000,
+        };
+        let remote_signer = RemoteSigner::new(lock.clone(), &config).unwrap();
+
+        let request = requests[0].clone();
+        let signatures = remote_signer.sign(request.clone()).await.unwrap();
+        assert_eq!(signatures, local_signer.sign(request).await.unwrap());
+
+        let err = refused(&remote_signer, requests[1].clone()).await;
+        assert!(err.contains("exceeds max fee"), "{}", err);
+
+        let err = refused(&remote_signer, requests[2].clone()).await;
+        assert!(err.contains("type is not allowed"), "{}", err);
+
+        let err = refused(&remote_signer, requests[3].clone()).await;
+        assert!(err.contains("is not allowed"), "{}", err);
+
+        // Hide the fee by reporting a bigger input capacity
+        let mut request = requests[1].clone();
+        let cell = &mut request.input_cells[0];
+        cell.output = cell
+            .output
+            .clone()
+            .as_builder()
+            .capacity(1001u64.pack())
+            .build();
+        let err = refused(&remote_signer, request).await;
+        assert!(err.contains("mismatch the cell on chain"), "{}", err);
+
+        let err = refused(&remote_signer, sign_request(4, &lock, None, 100)).await;
+        assert!(err.contains("not found"), "{}", err);
+    }
+}
@@ -1,16 +1,16 @@
 use anyhow::{anyhow, Context, Result};
 use ckb_crypto::secp::Privkey;
-use faster_hex::hex_decode;
 use gw_common::{blake2b::new_blake2b, H256};
 use gw_config::WalletConfig;
 use gw_types::{
     bytes::Bytes,
     core::ScriptHashType,
-    packed::{Script, Transaction},
+    packed::{Script, Transaction, WitnessArgs},
     prelude::{Builder, Entity, Pack, Unpack},
 };
 use sha3::{Digest, Keccak256};
 
+use crate::keystore;
 use crate::transaction_skeleton::{Signature, TransactionSkeleton};
 
 pub struct Wallet {
@@ -25,19 +25,33 @@ impl Wallet {
 
     pub fn from_config(config: &WalletConfig) -> Result<Self> {
         let lock = config.lock.clone().into();
-        let privkey = {
-            let content = std::fs::read_to_string(&config.privkey_path)
-                .with_context(|| "read wallet privkey")?;
-            let content = content.trim_start_matches("0x").trim();
-            assert_eq!(content.as_bytes().len(), 64, "invalid privkey length");
-            let mut decoded = [0u8; 32];
-            hex_decode(content.as_bytes(), &mut decoded)?;
-            Privkey::from_slice(&decoded)
-        };
+        let privkey = keystore::read_privkey(&config.privkey_path, config.passphrase.as_ref())
+            .with_context(|| "read wallet privkey")?;
+        let privkey = Privkey::from_slice(&privkey);
         let wallet = Self::new(privkey, lock);
         Ok(wallet)
     }
 
+    /// A wallet of a random key, the lock is `lock` with args replaced by the key's blake160
+    pub fn random_secp256k1(lock: Script) -> Result<Self> {
+        let privkey = {
+            let sk = secp256k1::SecretKey::from_slice(&rand::random::<[u8; 32]>())?;
+            Privkey::from_slice(&sk.serialize_secret())
+        };
+        let pubkey_hash = {
+            let mut hasher = new_blake2b();
+            hasher.update(&privkey.pubkey()?.serialize());
+            let mut hash = [0u8; 32];
+            hasher.finalize(&mut hash);
+            hash
+        };
+        let lock = lock
+            .as_builder()
+            .args(pubkey_hash[..20].to_vec().pack())
+            .build();
+        Ok(Self::new(privkey, lock))
+    }
+
     pub fn lock_script(&self) -> &Script {
         &self.lock
     }
@@ -76,40 +90,9 @@ impl Wallet {
         let tx = tx_skeleton
             .seal(&signature_entries, dummy_signatures)?
             .transaction;
-        let tx_hash = {
-            let mut hasher = new_blake2b();
-            hasher.update(tx.raw().as_slice());
-            let mut hash = [0u8; 32];
-            hasher.finalize(&mut hash);
-            hash
-        };
         let mut signatures = Vec::with_capacity(signature_entries.len());
         for entry in &signature_entries {
-            let mut hasher = new_blake2b();
-            // hash tx_hash
-            hasher.update(&tx_hash);
-            // hash the first witness: len | witness
-            let first_witness: Bytes = tx
-                .witnesses()
-                .get(entry.indexes[0])
-                .expect("get first witness")
-                .unpack();
-            hasher.update(&(first_witness.len() as u64).to_le_bytes());
-            hasher.update(&first_witness);
-            // hash the other witnesses in the group
-            for &index in &entry.indexes[1..] {
-                let witness: Bytes = tx.witnesses().get(index).expect("get witness").unpack();
-                hasher.update(&(witness.len() as u64).to_le_bytes());
-                hasher.update(&witness);
-            }
-            // hash witnesses which do not in any input group
-            for index in tx.raw().inputs().len()..tx.witnesses().len() {
-                let witness: Bytes = tx.witnesses().get(index).expect("get witness").unpack();
-                hasher.update(&(witness.len() as u64).to_le_bytes());
-                hasher.update(&witness);
-            }
-            let mut message = [0u8; 32];
-            hasher.finalize(&mut message);
+            let message = sighash_message(&tx, &entry.indexes);
             // sign tx
             let signature = Signature::new(entry.kind, self.sign_message(message)?);
             signatures.push(signature.as_bytes());
@@ -120,6 +103,86 @@ impl Wallet {
         sealed_tx.check_fee_rate()?;
         Ok(sealed_tx.transaction)
     }
+
+    /// Sign inputs of a transaction built by others, the witness lock of the first input must be
+    /// a 65 bytes placeholder
+    pub fn sign_tx_inputs(&self, tx: Transaction, indexes: &[usize]) -> Result<Transaction> {
+        let first_index = *indexes
+            .first()
+            .ok_or_else(|| anyhow!("no inputs to sign"))?;
+        let witness_args = {
+            let witness: Bytes = tx
+                .witnesses()
+                .get(first_index)
+                .ok_or_else(|| anyhow!("witness {} not found", first_index))?
+                .unpack();
+            WitnessArgs::from_slice(&witness)?
+        };
+        match witness_args.lock().to_opt() {
+            Some(lock) if lock.raw_data().len() == 65 => (),
+            _ => return Err(anyhow!("invalid witness placeholder {}", first_index)),
+        }
+
+        let dummy_tx = {
+            let dummy_witness = witness_args
+                .clone()
+                .as_builder()
+                .lock(Some(Bytes::from(vec![0u8; 65])).pack())
+                .build();
+            let mut witnesses: Vec<Bytes> =
+                tx.witnesses().into_iter().map(|w| w.unpack()).collect();
+            witnesses[first_index] = dummy_witness.as_bytes();
+            tx.clone().as_builder().witnesses(witnesses.pack()).build()
+        };
+        let message = sighash_message(&dummy_tx, indexes);
+        let signature = self.sign_message(message)?;
+
+        let signed_witness = witness_args
+            .as_builder()
+            .lock(Some(Bytes::from(signature.to_vec())).pack())
+            .build();
+        let mut witnesses: Vec<Bytes> = tx.witnesses().into_iter().map(|w| w.unpack()).collect();
+        witnesses[first_index] = signed_witness.as_bytes();
+        Ok(tx.as_builder().witnesses(witnesses.pack()).build())
+    }
+}
+
+/// Sighash all message of the input group
+pub fn sighash_message(tx: &Transaction, indexes: &[usize]) -> [u8; 32] {
+    let tx_hash = {
+        let mut hasher = new_blake2b();
+        hasher.update(tx.raw().as_slice());
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        hash
+    };
+
+    let mut hasher = new_blake2b();
+    // hash tx_hash
+    hasher.update(&tx_hash);
+    // hash the first witness: len | witness
+    let first_witness: Bytes = tx
+        .witnesses()
+        .get(indexes[0])
+        .expect("get first witness")
+        .unpack();
+    hasher.update(&(first_witness.len() as u64).to_le_bytes());
+    hasher.update(&first_witness);
+    // hash the other witnesses in the group
+    for &index in &indexes[1..] {
+        let witness: Bytes = tx.witnesses().get(index).expect("get witness").unpack();
+        hasher.update(&(witness.len() as u64).to_le_bytes());
+        hasher.update(&witness);
+    }
+    // hash witnesses which do not in any input group
+    for index in tx.raw().inputs().len()..tx.witnesses().len() {
+        let witness: Bytes = tx.witnesses().get(index).expect("get witness").unpack();
+        hasher.update(&(witness.len() as u64).to_le_bytes());
+        hasher.update(&witness);
+    }
+    let mut message = [0u8; 32];
+    hasher.finalize(&mut message);
+    message
 }
 
 pub fn privkey_to_eth_account_script(
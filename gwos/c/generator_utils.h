@@ -42,6 +42,12 @@
 /* Syscall state */
 #define GW_SYS_SNAPSHOT 3701
 #define GW_SYS_REVERT 3702
+/* Syscall precompiles */
+#define GW_SYS_KECCAK256 3801
+#define GW_SYS_SHA256 3802
+#define GW_SYS_RIPEMD160 3803
+#define GW_SYS_BLAKE2F 3804
+#define GW_SYS_SECP256K1_RECOVER 3805
 
 typedef struct gw_context_t {
   /* verification context */
@@ -67,6 +73,11 @@ typedef struct gw_context_t {
   gw_bn_add sys_bn_add;
   gw_bn_mul sys_bn_mul;
   gw_bn_pairing sys_bn_pairing;
+  gw_keccak256 sys_keccak256;
+  gw_sha256 sys_sha256;
+  gw_ripemd160 sys_ripemd160;
+  gw_blake2f sys_blake2f;
+  gw_secp256k1_recover sys_secp256k1_recover;
   gw_log_fn sys_log;
   gw_pay_fee_fn sys_pay_fee;
   gw_get_registry_address_by_script_hash_fn
@@ -375,6 +386,39 @@ int sys_bn_pairing(const uint8_t *input, const size_t input_size,
                  input, input_size, 0);
 }
 
+int sys_keccak256(const uint8_t *input, const size_t input_size,
+                  uint8_t *output) {
+  volatile uint64_t output_len = 32;
+  return syscall(GW_SYS_KECCAK256, output, &output_len, 0, input, input_size,
+                 0);
+}
+
+int sys_sha256(const uint8_t *input, const size_t input_size,
+               uint8_t *output) {
+  volatile uint64_t output_len = 32;
+  return syscall(GW_SYS_SHA256, output, &output_len, 0, input, input_size, 0);
+}
+
+int sys_ripemd160(const uint8_t *input, const size_t input_size,
+                  uint8_t *output) {
+  volatile uint64_t output_len = 20;
+  return syscall(GW_SYS_RIPEMD160, output, &output_len, 0, input, input_size,
+                 0);
+}
+
+int sys_blake2f(const uint8_t *input, const size_t input_size,
+                uint8_t *output) {
+  volatile uint64_t output_len = 64;
+  return syscall(GW_SYS_BLAKE2F, output, &output_len, 0, input, input_size, 0);
+}
+
+int sys_secp256k1_recover(const uint8_t *input, const size_t input_size,
+                          uint8_t *output) {
+  volatile uint64_t output_len = 64;
+  return syscall(GW_SYS_SECP256K1_RECOVER, output, &output_len, 0, input,
+                 input_size, 0);
+}
+
 int sys_log(gw_context_t *ctx, uint32_t account_id, uint8_t service_flag,
             uint64_t data_length, const uint8_t *data) {
   if (ctx == NULL) {
@@ -463,6 +507,11 @@ int gw_context_init(gw_context_t *ctx) {
   ctx->sys_bn_add = sys_bn_add;
   ctx->sys_bn_mul = sys_bn_mul;
   ctx->sys_bn_pairing = sys_bn_pairing;
+  ctx->sys_keccak256 = sys_keccak256;
+  ctx->sys_sha256 = sys_sha256;
+  ctx->sys_ripemd160 = sys_ripemd160;
+  ctx->sys_blake2f = sys_blake2f;
+  ctx->sys_secp256k1_recover = sys_secp256k1_recover;
   ctx->sys_pay_fee = sys_pay_fee;
   ctx->sys_log = sys_log;
   ctx->sys_get_registry_address_by_script_hash =
@@ -142,6 +142,50 @@ typedef int (*gw_bn_mul)(const uint8_t *input, const size_t input_size,
 typedef int (*gw_bn_pairing)(const uint8_t *input, const size_t input_size,
                              uint8_t *output);
 
+/**
+ * @param input  The message to hash
+ * @param output The keccak256 hash of input (32 bytes)
+ */
+typedef int (*gw_keccak256)(const uint8_t *input, const size_t input_size,
+                            uint8_t *output);
+
+/**
+ * @param input  The message to hash
+ * @param output The sha256 hash of input (32 bytes)
+ */
+typedef int (*gw_sha256)(const uint8_t *input, const size_t input_size,
+                         uint8_t *output);
+
+/**
+ * @param input  The message to hash
+ * @param output The ripemd160 hash of input (20 bytes)
+ */
+typedef int (*gw_ripemd160)(const uint8_t *input, const size_t input_size,
+                            uint8_t *output);
+
+/**
+ * @param input  rounds(4 bytes, big endian) | h(64) | m(128) | t(16) | f(1),
+ *               see EIP-152
+ * @param output The state vector h after compression (64 bytes)
+ *
+ * Fails on:
+ *   1. the input_size is not 213
+ *   2. the final block indicator flag f is not 0 or 1
+ */
+typedef int (*gw_blake2f)(const uint8_t *input, const size_t input_size,
+                          uint8_t *output);
+
+/**
+ * @param input  message(32) | signature(65), the signature is
+ *               r(32) | s(32) | recovery_id(1)
+ * @param output The uncompressed public key without the 0x04 prefix (64
+ * bytes)
+ *
+ * @return GW_ERROR_RECOVER if the signature can't be recovered
+ */
+typedef int (*gw_secp256k1_recover)(const uint8_t *input,
+                                    const size_t input_size, uint8_t *output);
+
 /**
  * Load value by key from current contract account
  *
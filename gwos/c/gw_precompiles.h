@@ -0,0 +1,472 @@
+#ifndef GW_PRECOMPILES_H_
+#define GW_PRECOMPILES_H_
+
+/* Precompiles computed inside the VM,
+   the generator makes host syscalls to compute the same functions, so the
+   challenge of a transaction using the precompiles can be verified by the
+   validator.
+
+   This file should be included in the validator_utils.h after the common.h.
+ */
+
+#include "common.h"
+#include "secp256k1_helper.h"
+
+#define GW_BLAKE2F_INPUT_SIZE 213
+#define GW_SECP256K1_RECOVER_INPUT_SIZE 97
+/* Same limits as the generator, see crates/generator/src/syscalls/precompiles.rs */
+#define GW_MAX_HASH_INPUT_SIZE 131072
+#define GW_MAX_BLAKE2F_ROUNDS 65536
+
+static inline uint32_t _gw_load32_be(const uint8_t *p) {
+  return ((uint32_t)p[0] << 24) | ((uint32_t)p[1] << 16) |
+         ((uint32_t)p[2] << 8) | (uint32_t)p[3];
+}
+
+static inline uint32_t _gw_load32_le(const uint8_t *p) {
+  return (uint32_t)p[0] | ((uint32_t)p[1] << 8) | ((uint32_t)p[2] << 16) |
+         ((uint32_t)p[3] << 24);
+}
+
+static inline uint64_t _gw_load64_le(const uint8_t *p) {
+  return (uint64_t)_gw_load32_le(p) | ((uint64_t)_gw_load32_le(p + 4) << 32);
+}
+
+static inline void _gw_store32_be(uint8_t *p, uint32_t v) {
+  p[0] = (uint8_t)(v >> 24);
+  p[1] = (uint8_t)(v >> 16);
+  p[2] = (uint8_t)(v >> 8);
+  p[3] = (uint8_t)v;
+}
+
+static inline void _gw_store32_le(uint8_t *p, uint32_t v) {
+  p[0] = (uint8_t)v;
+  p[1] = (uint8_t)(v >> 8);
+  p[2] = (uint8_t)(v >> 16);
+  p[3] = (uint8_t)(v >> 24);
+}
+
+static inline void _gw_store64_le(uint8_t *p, uint64_t v) {
+  _gw_store32_le(p, (uint32_t)v);
+  _gw_store32_le(p + 4, (uint32_t)(v >> 32));
+}
+
+#define _GW_ROTL32(x, n) (((x) << (n)) | ((x) >> (32 - (n))))
+#define _GW_ROTR32(x, n) (((x) >> (n)) | ((x) << (32 - (n))))
+#define _GW_ROTL64(x, n) (((x) << (n)) | ((x) >> (64 - (n))))
+#define _GW_ROTR64(x, n) (((x) >> (n)) | ((x) << (64 - (n))))
+
+/* keccak256 */
+
+#define _GW_KECCAK256_RATE 136
+
+static const uint64_t _gw_keccakf_rndc[24] = {
+    0x0000000000000001ULL, 0x0000000000008082ULL, 0x800000000000808aULL,
+    0x8000000080008000ULL, 0x000000000000808bULL, 0x0000000080000001ULL,
+    0x8000000080008081ULL, 0x8000000000008009ULL, 0x000000000000008aULL,
+    0x0000000000000088ULL, 0x0000000080008009ULL, 0x000000008000000aULL,
+    0x000000008000808bULL, 0x800000000000008bULL, 0x8000000000008089ULL,
+    0x8000000000008003ULL, 0x8000000000008002ULL, 0x8000000000000080ULL,
+    0x000000000000800aULL, 0x800000008000000aULL, 0x8000000080008081ULL,
+    0x8000000000008080ULL, 0x0000000080000001ULL, 0x8000000080008008ULL};
+
+static const int _gw_keccakf_rotc[24] = {1,  3,  6,  10, 15, 21, 28, 36,
+                                         45, 55, 2,  14, 27, 41, 56, 8,
+                                         25, 43, 62, 18, 39, 61, 20, 44};
+
+static const int _gw_keccakf_piln[24] = {10, 7,  11, 17, 18, 3,  5,  16,
+                                         8,  21, 24, 4,  15, 23, 19, 13,
+                                         12, 2,  20, 14, 22, 9,  6,  1};
+
+void _gw_keccakf(uint64_t st[25]) {
+  uint64_t t, bc[5];
+  for (int round = 0; round < 24; round++) {
+    /* theta */
+    for (int i = 0; i < 5; i++) {
+      bc[i] = st[i] ^ st[i + 5] ^ st[i + 10] ^ st[i + 15] ^ st[i + 20];
+    }
+    for (int i = 0; i < 5; i++) {
+      t = bc[(i + 4) % 5] ^ _GW_ROTL64(bc[(i + 1) % 5], 1);
+      for (int j = 0; j < 25; j += 5) {
+        st[j + i] ^= t;
+      }
+    }
+    /* rho pi */
+    t = st[1];
+    for (int i = 0; i < 24; i++) {
+      int j = _gw_keccakf_piln[i];
+      bc[0] = st[j];
+      st[j] = _GW_ROTL64(t, _gw_keccakf_rotc[i]);
+      t = bc[0];
+    }
+    /* chi */
+    for (int j = 0; j < 25; j += 5) {
+      for (int i = 0; i < 5; i++) {
+        bc[i] = st[j + i];
+      }
+      for (int i = 0; i < 5; i++) {
+        st[j + i] ^= (~bc[(i + 1) % 5]) & bc[(i + 2) % 5];
+      }
+    }
+    /* iota */
+    st[0] ^= _gw_keccakf_rndc[round];
+  }
+}
+
+void _gw_keccak256_absorb(uint64_t st[25], const uint8_t *block) {
+  for (int i = 0; i < _GW_KECCAK256_RATE / 8; i++) {
+    st[i] ^= _gw_load64_le(block + i * 8);
+  }
+  _gw_keccakf(st);
+}
+
+void gw_keccak256_hash(const uint8_t *input, size_t input_size,
+                       uint8_t output[32]) {
+  uint64_t st[25];
+  _gw_fast_memset(st, 0, sizeof(st));
+  while (input_size >= _GW_KECCAK256_RATE) {
+    _gw_keccak256_absorb(st, input);
+    input += _GW_KECCAK256_RATE;
+    input_size -= _GW_KECCAK256_RATE;
+  }
+
+  uint8_t block[_GW_KECCAK256_RATE];
+  _gw_fast_memset(block, 0, _GW_KECCAK256_RATE);
+  _gw_fast_memcpy(block, input, input_size);
+  block[input_size] ^= 0x01;
+  block[_GW_KECCAK256_RATE - 1] ^= 0x80;
+  _gw_keccak256_absorb(st, block);
+
+  for (int i = 0; i < 4; i++) {
+    _gw_store64_le(output + i * 8, st[i]);
+  }
+}
+
+/* sha256 */
+
+static const uint32_t _gw_sha256_k[64] = {
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1,
+    0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+    0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+    0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+    0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+    0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+    0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+    0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+    0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2};
+
+void _gw_sha256_compress(uint32_t h[8], const uint8_t block[64]) {
+  uint32_t w[64];
+  for (int i = 0; i < 16; i++) {
+    w[i] = _gw_load32_be(block + i * 4);
+  }
+  for (int i = 16; i < 64; i++) {
+    uint32_t s0 = _GW_ROTR32(w[i - 15], 7) ^ _GW_ROTR32(w[i - 15], 18) ^
+                  (w[i - 15] >> 3);
+    uint32_t s1 = _GW_ROTR32(w[i - 2], 17) ^ _GW_ROTR32(w[i - 2], 19) ^
+                  (w[i - 2] >> 10);
+    w[i] = w[i - 16] + s0 + w[i - 7] + s1;
+  }
+
+  uint32_t a = h[0], b = h[1], c = h[2], d = h[3];
+  uint32_t e = h[4], f = h[5], g = h[6], hh = h[7];
+  for (int i = 0; i < 64; i++) {
+    uint32_t s1 = _GW_ROTR32(e, 6) ^ _GW_ROTR32(e, 11) ^ _GW_ROTR32(e, 25);
+    uint32_t ch = (e & f) ^ (~e & g);
+    uint32_t t1 = hh + s1 + ch + _gw_sha256_k[i] + w[i];
+    uint32_t s0 = _GW_ROTR32(a, 2) ^ _GW_ROTR32(a, 13) ^ _GW_ROTR32(a, 22);
+    uint32_t maj = (a & b) ^ (a & c) ^ (b & c);
+    uint32_t t2 = s0 + maj;
+    hh = g;
+    g = f;
+    f = e;
+    e = d + t1;
+    d = c;
+    c = b;
+    b = a;
+    a = t1 + t2;
+  }
+  h[0] += a;
+  h[1] += b;
+  h[2] += c;
+  h[3] += d;
+  h[4] += e;
+  h[5] += f;
+  h[6] += g;
+  h[7] += hh;
+}
+
+void gw_sha256_hash(const uint8_t *input, size_t input_size,
+                    uint8_t output[32]) {
+  uint32_t h[8] = {0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+                   0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19};
+  uint64_t bit_len = (uint64_t)input_size * 8;
+  while (input_size >= 64) {
+    _gw_sha256_compress(h, input);
+    input += 64;
+    input_size -= 64;
+  }
+
+  uint8_t block[64];
+  _gw_fast_memset(block, 0, 64);
+  _gw_fast_memcpy(block, input, input_size);
+  block[input_size] = 0x80;
+  if (input_size >= 56) {
+    _gw_sha256_compress(h, block);
+    _gw_fast_memset(block, 0, 64);
+  }
+  _gw_store32_be(block + 56, (uint32_t)(bit_len >> 32));
+  _gw_store32_be(block + 60, (uint32_t)bit_len);
+  _gw_sha256_compress(h, block);
+
+  for (int i = 0; i < 8; i++) {
+    _gw_store32_be(output + i * 4, h[i]);
+  }
+}
+
+/* ripemd160 */
+
+static const uint8_t _gw_ripemd160_rl[80] = {
+    0, 1,  2,  3,  4,  5,  6,  7,  8,  9,  10, 11, 12, 13, 14, 15,
+    7, 4,  13, 1,  10, 6,  15, 3,  12, 0,  9,  5,  2,  14, 11, 8,
+    3, 10, 14, 4,  9,  15, 8,  1,  2,  7,  0,  6,  13, 11, 5,  12,
+    1, 9,  11, 10, 0,  8,  12, 4,  13, 3,  7,  15, 14, 5,  6,  2,
+    4, 0,  5,  9,  7,  12, 2,  10, 14, 1,  3,  8,  11, 6,  15, 13};
+
+static const uint8_t _gw_ripemd160_rr[80] = {
+    5,  14, 7,  0,  9, 2,  11, 4,  13, 6,  15, 8,  1,  10, 3,  12,
+    6,  11, 3,  7,  0, 13, 5,  10, 14, 15, 8,  12, 4,  9,  1,  2,
+    15, 5,  1,  3,  7, 14, 6,  9,  11, 8,  12, 2,  10, 0,  4,  13,
+    8,  6,  4,  1,  3, 11, 15, 0,  5,  12, 2,  13, 9,  7,  10, 14,
+    12, 15, 10, 4,  1, 5,  8,  7,  6,  2,  13, 14, 0,  3,  9,  11};
+
+static const uint8_t _gw_ripemd160_sl[80] = {
+    11, 14, 15, 12, 5,  8,  7,  9,  11, 13, 14, 15, 6,  7,  9,  8,
+    7,  6,  8,  13, 11, 9,  7,  15, 7,  12, 15, 9,  11, 7,  13, 12,
+    11, 13, 6,  7,  14, 9,  13, 15, 14, 8,  13, 6,  5,  12, 7,  5,
+    11, 12, 14, 15, 14, 15, 9,  8,  9,  14, 5,  6,  8,  6,  5,  12,
+    9,  15, 5,  11, 6,  8,  13, 12, 5,  12, 13, 14, 11, 8,  5,  6};
+
+static const uint8_t _gw_ripemd160_sr[80] = {
+    8,  9,  9,  11, 13, 15, 15, 5,  7,  7,  8,  11, 14, 14, 12, 6,
+    9,  13, 15, 7,  12, 8,  9,  11, 7,  7,  12, 7,  6,  15, 13, 11,
+    9,  7,  15, 11, 8,  6,  6,  14, 12, 13, 5,  14, 13, 13, 7,  5,
+    15, 5,  8,  11, 14, 14, 6,  14, 6,  9,  12, 9,  12, 5,  15, 8,
+    8,  5,  12, 9,  12, 5,  14, 6,  8,  13, 6,  5,  15, 13, 11, 11};
+
+static const uint32_t _gw_ripemd160_kl[5] = {0x00000000, 0x5a827999,
+                                             0x6ed9eba1, 0x8f1bbcdc,
+                                             0xa953fd4e};
+
+static const uint32_t _gw_ripemd160_kr[5] = {0x50a28be6, 0x5c4dd124,
+                                             0x6d703ef3, 0x7a6d76e9,
+                                             0x00000000};
+
+static inline uint32_t _gw_ripemd160_f(int j, uint32_t x, uint32_t y,
+                                       uint32_t z) {
+  switch (j) {
+    case 0:
+      return x ^ y ^ z;
+    case 1:
+      return (x & y) | (~x & z);
+    case 2:
+      return (x | ~y) ^ z;
+    case 3:
+      return (x & z) | (y & ~z);
+    default:
+      return x ^ (y | ~z);
+  }
+}
+
+void _gw_ripemd160_compress(uint32_t h[5], const uint8_t block[64]) {
+  uint32_t x[16];
+  for (int i = 0; i < 16; i++) {
+    x[i] = _gw_load32_le(block + i * 4);
+  }
+
+  uint32_t al = h[0], bl = h[1], cl = h[2], dl = h[3], el = h[4];
+  uint32_t ar = h[0], br = h[1], cr = h[2], dr = h[3], er = h[4];
+  for (int i = 0; i < 80; i++) {
+    int round = i / 16;
+    uint32_t t = al + _gw_ripemd160_f(round, bl, cl, dl) +
+                 x[_gw_ripemd160_rl[i]] + _gw_ripemd160_kl[round];
+    t = _GW_ROTL32(t, _gw_ripemd160_sl[i]) + el;
+    al = el;
+    el = dl;
+    dl = _GW_ROTL32(cl, 10);
+    cl = bl;
+    bl = t;
+
+    t = ar + _gw_ripemd160_f(4 - round, br, cr, dr) + x[_gw_ripemd160_rr[i]] +
+        _gw_ripemd160_kr[round];
+    t = _GW_ROTL32(t, _gw_ripemd160_sr[i]) + er;
+    ar = er;
+    er = dr;
+    dr = _GW_ROTL32(cr, 10);
+    cr = br;
+    br = t;
+  }
+
+  uint32_t t = h[1] + cl + dr;
+  h[1] = h[2] + dl + er;
+  h[2] = h[3] + el + ar;
+  h[3] = h[4] + al + br;
+  h[4] = h[0] + bl + cr;
+  h[0] = t;
+}
+
+void gw_ripemd160_hash(const uint8_t *input, size_t input_size,
+                       uint8_t output[20]) {
+  uint32_t h[5] = {0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476,
+                   0xc3d2e1f0};
+  uint64_t bit_len = (uint64_t)input_size * 8;
+  while (input_size >= 64) {
+    _gw_ripemd160_compress(h, input);
+    input += 64;
+    input_size -= 64;
+  }
+
+  uint8_t block[64];
+  _gw_fast_memset(block, 0, 64);
+  _gw_fast_memcpy(block, input, input_size);
+  block[input_size] = 0x80;
+  if (input_size >= 56) {
+    _gw_ripemd160_compress(h, block);
+    _gw_fast_memset(block, 0, 64);
+  }
+  _gw_store64_le(block + 56, bit_len);
+  _gw_ripemd160_compress(h, block);
+
+  for (int i = 0; i < 5; i++) {
+    _gw_store32_le(output + i * 4, h[i]);
+  }
+}
+
+/* blake2f, the compression function F of BLAKE2b, see EIP-152 */
+
+static const uint64_t _gw_blake2b_iv[8] = {
+    0x6a09e667f3bcc908ULL, 0xbb67ae8584caa73bULL, 0x3c6ef372fe94f82bULL,
+    0xa54ff53a5f1d36f1ULL, 0x510e527fade682d1ULL, 0x9b05688c2b3e6c1fULL,
+    0x1f83d9abfb41bd6bULL, 0x5be0cd19137e2179ULL};
+
+static const uint8_t _gw_blake2b_sigma[10][16] = {
+    {0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15},
+    {14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3},
+    {11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4},
+    {7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8},
+    {9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13},
+    {2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9},
+    {12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11},
+    {13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10},
+    {6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5},
+    {10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0}};
+
+static inline void _gw_blake2b_g(uint64_t v[16], int a, int b, int c, int d,
+                                 uint64_t x, uint64_t y) {
+  v[a] = v[a] + v[b] + x;
+  v[d] = _GW_ROTR64(v[d] ^ v[a], 32);
+  v[c] = v[c] + v[d];
+  v[b] = _GW_ROTR64(v[b] ^ v[c], 24);
+  v[a] = v[a] + v[b] + y;
+  v[d] = _GW_ROTR64(v[d] ^ v[a], 16);
+  v[c] = v[c] + v[d];
+  v[b] = _GW_ROTR64(v[b] ^ v[c], 63);
+}
+
+int gw_blake2f_compress(const uint8_t *input, size_t input_size,
+                        uint8_t output[64]) {
+  if (input_size != GW_BLAKE2F_INPUT_SIZE) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  uint8_t f = input[212];
+  if (f > 1) {
+    return GW_FATAL_INVALID_DATA;
+  }
+
+  uint32_t rounds = _gw_load32_be(input);
+  if (rounds > GW_MAX_BLAKE2F_ROUNDS) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  uint64_t h[8], m[16], v[16];
+  for (int i = 0; i < 8; i++) {
+    h[i] = _gw_load64_le(input + 4 + i * 8);
+  }
+  for (int i = 0; i < 16; i++) {
+    m[i] = _gw_load64_le(input + 68 + i * 8);
+  }
+  for (int i = 0; i < 8; i++) {
+    v[i] = h[i];
+    v[i + 8] = _gw_blake2b_iv[i];
+  }
+  v[12] ^= _gw_load64_le(input + 196);
+  v[13] ^= _gw_load64_le(input + 204);
+  if (f) {
+    v[14] = ~v[14];
+  }
+
+  for (uint32_t i = 0; i < rounds; i++) {
+    const uint8_t *s = _gw_blake2b_sigma[i % 10];
+    _gw_blake2b_g(v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+    _gw_blake2b_g(v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+    _gw_blake2b_g(v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+    _gw_blake2b_g(v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+    _gw_blake2b_g(v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+    _gw_blake2b_g(v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+    _gw_blake2b_g(v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+    _gw_blake2b_g(v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+  }
+
+  for (int i = 0; i < 8; i++) {
+    _gw_store64_le(output + i * 8, h[i] ^ v[i] ^ v[i + 8]);
+  }
+  return 0;
+}
+
+/* secp256k1 recover,
+   the secp256k1 data cell must be in the cell deps of the verification
+   transaction */
+
+int gw_secp256k1_recover_pubkey(const uint8_t *input, size_t input_size,
+                                uint8_t output[64]) {
+  if (input_size != GW_SECP256K1_RECOVER_INPUT_SIZE) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  const uint8_t *message = input;
+  const uint8_t *signature = input + 32;
+  int recid = signature[64];
+  if (recid > 3) {
+    return GW_ERROR_RECOVER;
+  }
+
+  secp256k1_context context;
+  uint8_t secp_data[CKB_SECP256K1_DATA_SIZE];
+  int ret = ckb_secp256k1_custom_verify_only_initialize(&context, secp_data);
+  if (ret != 0) {
+    printf("secp256k1 recover: failed to initialize secp256k1");
+    return GW_FATAL_DATA_CELL_NOT_FOUND;
+  }
+
+  secp256k1_ecdsa_recoverable_signature recoverable_signature;
+  if (secp256k1_ecdsa_recoverable_signature_parse_compact(
+          &context, &recoverable_signature, signature, recid) == 0) {
+    return GW_ERROR_RECOVER;
+  }
+  secp256k1_pubkey pubkey;
+  if (secp256k1_ecdsa_recover(&context, &pubkey, &recoverable_signature,
+                              message) != 1) {
+    return GW_ERROR_RECOVER;
+  }
+
+  uint8_t serialized[65];
+  size_t serialized_size = 65;
+  if (secp256k1_ec_pubkey_serialize(&context, serialized, &serialized_size,
+                                    &pubkey, SECP256K1_EC_UNCOMPRESSED) != 1) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  _gw_fast_memcpy(output, serialized + 1, 64);
+  return 0;
+}
+
+#endif
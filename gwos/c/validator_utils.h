@@ -17,6 +17,11 @@
 #define GW_MAX_CHALLENGE_LOCK_SCRIPT_SIZE 4096
 #define GW_MAX_GET_BLOCK_HASH_DEPTH 256
 
+/* sponsor fee is paid in CKB, same as CKB_SUDT_ACCOUNT_ID and
+ * SUDT_KEY_FLAG_BALANCE in sudt_utils.h */
+#define GW_SPONSOR_FEE_SUDT_ID 1
+#define GW_SPONSOR_SUDT_KEY_FLAG_BALANCE 1
+
 /* functions */
 int _gw_check_account_script_is_allowed(uint8_t rollup_script_hash[32],
                                         mol_seg_t *script_seg,
@@ -74,6 +79,11 @@ typedef struct gw_context_t {
   gw_bn_add sys_bn_add;
   gw_bn_mul sys_bn_mul;
   gw_bn_pairing sys_bn_pairing;
+  gw_keccak256 sys_keccak256;
+  gw_sha256 sys_sha256;
+  gw_ripemd160 sys_ripemd160;
+  gw_blake2f sys_blake2f;
+  gw_secp256k1_recover sys_secp256k1_recover;
   gw_get_registry_address_by_script_hash_fn
       sys_get_registry_address_by_script_hash;
   gw_get_script_hash_by_registry_address_fn
@@ -93,6 +103,11 @@ typedef struct gw_context_t {
   /* sender's original nonce */
   uint32_t original_sender_nonce;
 
+  /* sponsor of the transaction, see SponsoredTxSignature */
+  int is_sponsored;
+  uint32_t sponsor_id;
+  uint256_t sponsor_fee;
+
   /* tx check point */
   uint8_t prev_tx_checkpoint[32];
   uint8_t post_tx_checkpoint[32];
@@ -126,6 +141,7 @@ typedef struct gw_context_t {
 } gw_context_t;
 
 #include "common.h"
+#include "gw_precompiles.h"
 
 int _internal_load_raw(gw_context_t *ctx, const uint8_t raw_key[GW_VALUE_BYTES],
                        uint8_t value[GW_VALUE_BYTES]) {
@@ -569,6 +585,43 @@ int sys_bn_pairing(const uint8_t *input, const size_t input_size,
   return GW_UNIMPLEMENTED;
 }
 
+int sys_keccak256(const uint8_t *input, const size_t input_size,
+                  uint8_t *output) {
+  if (input_size > GW_MAX_HASH_INPUT_SIZE) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  gw_keccak256_hash(input, input_size, output);
+  return 0;
+}
+
+int sys_sha256(const uint8_t *input, const size_t input_size,
+               uint8_t *output) {
+  if (input_size > GW_MAX_HASH_INPUT_SIZE) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  gw_sha256_hash(input, input_size, output);
+  return 0;
+}
+
+int sys_ripemd160(const uint8_t *input, const size_t input_size,
+                  uint8_t *output) {
+  if (input_size > GW_MAX_HASH_INPUT_SIZE) {
+    return GW_FATAL_INVALID_DATA;
+  }
+  gw_ripemd160_hash(input, input_size, output);
+  return 0;
+}
+
+int sys_blake2f(const uint8_t *input, const size_t input_size,
+                uint8_t *output) {
+  return gw_blake2f_compress(input, input_size, output);
+}
+
+int sys_secp256k1_recover(const uint8_t *input, const size_t input_size,
+                          uint8_t *output) {
+  return gw_secp256k1_recover_pubkey(input, input_size, output);
+}
+
 int sys_create(gw_context_t *ctx, uint8_t *script, uint64_t script_len,
                uint32_t *account_id) {
   if (ctx == NULL) {
@@ -1174,6 +1227,25 @@ int _load_verify_transaction_witness(uint8_t rollup_script_hash[32],
     return ret;
   }
 
+  /* load sponsor */
+  mol_seg_t signature_seg = MolReader_L2Transaction_get_signature(&l2tx_seg);
+  mol_seg_t raw_signature_seg = MolReader_Bytes_raw_bytes(&signature_seg);
+  ctx->is_sponsored = 0;
+  if (MolReader_TxSignatureEnvelope_verify(&raw_signature_seg, false) ==
+      MOL_OK) {
+    mol_union_t envelope =
+        MolReader_TxSignatureEnvelope_unpack(&raw_signature_seg);
+    mol_seg_t sponsor_id_seg =
+        MolReader_SponsoredTxSignature_get_sponsor_id(&envelope.seg);
+    mol_seg_t fee_seg = MolReader_SponsoredTxSignature_get_fee(&envelope.seg);
+    ctx->is_sponsored = 1;
+    _gw_fast_memcpy((uint8_t *)(&ctx->sponsor_id), sponsor_id_seg.ptr,
+                    sizeof(uint32_t));
+    gw_uint256_zero(&ctx->sponsor_fee);
+    _gw_fast_memcpy((uint8_t *)(&ctx->sponsor_fee), fee_seg.ptr,
+                    fee_seg.size);
+  }
+
   /* load block info */
   mol_seg_t number_seg = MolReader_RawL2Block_get_number(&raw_l2block_seg);
   uint64_t challenged_block_number = 0;
@@ -1603,6 +1675,69 @@ int _gw_verify_checkpoint(const uint8_t checkpoint[32],
   return 0;
 }
 
+/* Sponsor pays the fee of a sponsored transaction to the block producer,
+ * the same as `Generator::pay_sponsor_fee` */
+int _gw_pay_sponsor_fee(gw_context_t *ctx) {
+  if (!ctx->is_sponsored) {
+    return 0;
+  }
+  uint8_t sponsor_script_hash[32] = {0};
+  int ret = sys_get_script_hash_by_account_id(ctx, ctx->sponsor_id,
+                                              sponsor_script_hash);
+  if (ret != 0) {
+    return ret;
+  }
+  gw_reg_addr_t payer = {0};
+  ret = _gw_get_registry_address_by_script_hash(
+      ctx, sponsor_script_hash, GW_DEFAULT_ETH_REGISTRY_ACCOUNT_ID, &payer);
+  if (ret != 0) {
+    printf("pay sponsor fee: sponsor has no registry address");
+    return ret;
+  }
+
+  uint8_t key[64] = {0};
+  uint32_t key_flag = GW_SPONSOR_SUDT_KEY_FLAG_BALANCE;
+
+  /* burn fee from the sponsor */
+  _gw_fast_memcpy(key, (uint8_t *)(&key_flag), 4);
+  _gw_cpy_addr(key + 4, payer);
+  uint256_t balance = {0};
+  ret = sys_load(ctx, GW_SPONSOR_FEE_SUDT_ID, key, 4 + GW_REG_ADDR_SIZE(payer),
+                 (uint8_t *)(&balance));
+  if (ret != 0) {
+    return ret;
+  }
+  if (gw_uint256_cmp(balance, ctx->sponsor_fee) == GW_UINT256_SMALLER) {
+    printf("pay sponsor fee: insufficient balance");
+    return GW_SUDT_ERROR_INSUFFICIENT_BALANCE;
+  }
+  uint256_t new_balance = {0};
+  gw_uint256_underflow_sub(balance, ctx->sponsor_fee, &new_balance);
+  ret = sys_store(ctx, GW_SPONSOR_FEE_SUDT_ID, key, 4 + GW_REG_ADDR_SIZE(payer),
+                  (uint8_t *)(&new_balance));
+  if (ret != 0) {
+    return ret;
+  }
+
+  /* mint fee to the block producer */
+  gw_reg_addr_t block_producer = ctx->block_info.block_producer;
+  _gw_fast_memset(key, 0, 64);
+  _gw_fast_memcpy(key, (uint8_t *)(&key_flag), 4);
+  _gw_cpy_addr(key + 4, block_producer);
+  ret = sys_load(ctx, GW_SPONSOR_FEE_SUDT_ID, key,
+                 4 + GW_REG_ADDR_SIZE(block_producer), (uint8_t *)(&balance));
+  if (ret != 0) {
+    return ret;
+  }
+  if (gw_uint256_overflow_add(balance, ctx->sponsor_fee, &new_balance)) {
+    printf("pay sponsor fee: balance overflow");
+    return GW_SUDT_ERROR_AMOUNT_OVERFLOW;
+  }
+  return sys_store(ctx, GW_SPONSOR_FEE_SUDT_ID, key,
+                   4 + GW_REG_ADDR_SIZE(block_producer),
+                   (uint8_t *)(&new_balance));
+}
+
 int gw_context_init(gw_context_t *ctx) {
   /* check owner lock */
   int ret = _check_owner_lock_hash();
@@ -1627,6 +1762,11 @@ int gw_context_init(gw_context_t *ctx) {
   ctx->sys_bn_add = sys_bn_add;
   ctx->sys_bn_mul = sys_bn_mul;
   ctx->sys_bn_pairing = sys_bn_pairing;
+  ctx->sys_keccak256 = sys_keccak256;
+  ctx->sys_sha256 = sys_sha256;
+  ctx->sys_ripemd160 = sys_ripemd160;
+  ctx->sys_blake2f = sys_blake2f;
+  ctx->sys_secp256k1_recover = sys_secp256k1_recover;
   ctx->sys_log = sys_log;
   ctx->sys_pay_fee = sys_pay_fee;
   ctx->sys_get_registry_address_by_script_hash =
@@ -1692,6 +1832,13 @@ int gw_context_init(gw_context_t *ctx) {
     return ret;
   }
 
+  /* sponsored transaction pays fee before the execution */
+  ret = _gw_pay_sponsor_fee(ctx);
+  if (ret != 0) {
+    printf("failed to pay sponsor fee");
+    return ret;
+  }
+
   /* init original sender nonce */
   ret = _load_sender_nonce(ctx, &ctx->original_sender_nonce);
   if (ret != 0) {
@@ -6,7 +6,10 @@ use gw_common::{
 use gw_state::kv_state::KVState;
 use gw_types::{
     core::ScriptHashType,
-    packed::{ChallengeTarget, L2Transaction, RawL2Block, RollupConfig, ScriptVec},
+    packed::{
+        ChallengeTarget, L2Transaction, RawL2Block, RollupConfig, ScriptVec,
+        TxSignatureEnvelopeReader, TxSignatureEnvelopeUnionReader,
+    },
     prelude::*,
 };
 use gw_utils::{ckb_std::debug, error::Error, gw_types::packed::Script};
@@ -32,6 +35,14 @@ pub struct TxContext {
     pub sender: Script,
     pub receiver: Script,
     pub sender_address: RegistryAddress,
+    pub sponsor: Option<TxSponsor>,
+}
+
+/// Sponsor of a sponsored transaction, see `SponsoredTxSignature`
+pub struct TxSponsor {
+    pub sponsor_id: u32,
+    pub fee: u128,
+    pub script_hash: H256,
 }
 
 pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
@@ -171,12 +182,31 @@ pub fn verify_tx_context(input: TxContextInput) -> Result<TxContext, Error> {
         .get_registry_address_by_script_hash(ETH_REGISTRY_ACCOUNT_ID, &sender_script_hash)?
         .ok_or(Error::RegistryAddressNotFound)?;
 
+    let sponsor = match TxSignatureEnvelopeReader::from_slice(&tx.signature().raw_data()) {
+        Ok(envelope) => match envelope.to_enum() {
+            TxSignatureEnvelopeUnionReader::SponsoredTxSignature(sponsored) => {
+                let sponsor_id: u32 = sponsored.sponsor_id().unpack();
+                let script_hash = kv_state.get_script_hash(sponsor_id).map_err(|_| {
+                    debug!("get sponsor script_hash");
+                    Error::SMTKeyMissing
+                })?;
+                Some(TxSponsor {
+                    sponsor_id,
+                    fee: sponsored.fee().unpack(),
+                    script_hash,
+                })
+            }
+        },
+        Err(_) => None,
+    };
+
     let tx_ctx = TxContext {
         sender_script_hash,
         receiver_script_hash,
         sender: sender_script,
         receiver: receiver_script,
         sender_address,
+        sponsor,
     };
     Ok(tx_ctx)
 }
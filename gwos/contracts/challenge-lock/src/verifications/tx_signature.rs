@@ -23,14 +23,14 @@ use gw_utils::{
     signature::check_l2_account_signature_cell,
 };
 use gw_utils::{
-    gw_common::H256,
+    gw_common::{blake2b::new_blake2b, H256},
     gw_types::{self, packed::RawL2Transaction},
 };
 use sha3::{Digest, Keccak256};
 
 /// Verify tx signature
 pub fn verify_tx_signature(
-    _rollup_script_hash: &[u8; 32],
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
@@ -90,8 +90,21 @@ pub fn verify_tx_signature(
         receiver,
         sender: _,
         sender_address,
+        sponsor,
     } = verify_tx_context(input)?;
 
+    // verify sponsor's script is in the input
+    if let Some(sponsor) = sponsor {
+        let mut hasher = new_blake2b();
+        hasher.update(rollup_script_hash);
+        hasher.update(&raw_tx.hash());
+        hasher.update(&sponsor.sponsor_id.to_le_bytes());
+        hasher.update(&sponsor.fee.to_le_bytes());
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        check_l2_account_signature_cell(&sponsor.script_hash, SigningType::Raw, message.into())?;
+    }
+
     let (message, signing_type) = match try_assemble_polyjuice_args(&raw_tx, receiver) {
         Some(rlp_data) => {
             let mut hasher = Keccak256::new();
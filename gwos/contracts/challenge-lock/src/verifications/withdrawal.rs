@@ -8,7 +8,10 @@ use gw_state::{ckb_smt::smt::Pair, constants::GW_MAX_KV_PAIRS};
 use gw_types::packed::ChallengeLockArgs;
 use gw_utils::gw_types::{
     self,
-    packed::{RollupConfig, Script, WithdrawalRequest},
+    packed::{
+        RollupConfig, Script, WithdrawalRequest, WithdrawalSignatureEnvelopeReader,
+        WithdrawalSignatureEnvelopeUnionReader,
+    },
     prelude::*,
 };
 use gw_utils::{
@@ -24,6 +27,7 @@ use gw_utils::{
 use gw_utils::{
     gw_common::{
         self,
+        blake2b::new_blake2b,
         merkle_utils::{ckb_merkle_leaf_hash, CBMTMerkleProof},
     },
     gw_types::packed::{CCWithdrawalWitness, CCWithdrawalWitnessReader},
@@ -122,7 +126,7 @@ fn verify_withdrawal_proof(lock_args: &ChallengeLockArgs) -> Result<WithdrawalCo
 
 /// Verify withdrawal signature
 pub fn verify_withdrawal(
-    _rollup_script_hash: &[u8; 32],
+    rollup_script_hash: &[u8; 32],
     rollup_config: &RollupConfig,
     lock_args: &ChallengeLockArgs,
 ) -> Result<(), Error> {
@@ -142,6 +146,37 @@ pub fn verify_withdrawal(
         return Err(Error::WrongSignature);
     }
 
+    // a batch withdrawal is signed once by the owner for all requests of the batch
+    let signature = withdrawal.signature().raw_data();
+    if let Ok(envelope) = WithdrawalSignatureEnvelopeReader::from_slice(&signature) {
+        let batch = match envelope.to_enum() {
+            WithdrawalSignatureEnvelopeUnionReader::BatchWithdrawalSignature(batch) => batch,
+        };
+        let index: u32 = batch.index().unpack();
+        let raw_request_hashes = batch.raw_request_hashes();
+        match raw_request_hashes.get(index as usize) {
+            Some(hash) if hash.as_slice() == raw_withdrawal.hash() => {}
+            _ => {
+                debug!("Withdrawal isn't in the batch");
+                return Err(Error::WrongSignature);
+            }
+        }
+
+        let mut hasher = new_blake2b();
+        hasher.update(rollup_script_hash);
+        hasher.update(&(raw_request_hashes.len() as u32).to_le_bytes());
+        for hash in raw_request_hashes.iter() {
+            hasher.update(hash.as_slice());
+        }
+        let mut message = [0u8; 32];
+        hasher.finalize(&mut message);
+        return check_l2_account_signature_cell(
+            &sender_script_hash,
+            gw_types::core::SigningType::Raw,
+            message.into(),
+        );
+    }
+
     // calculate EIP-712 message
     let typed_message = crate::verifications::eip712::types::Withdrawal::from_raw(
         withdrawal.raw(),
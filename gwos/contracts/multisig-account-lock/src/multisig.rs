@@ -0,0 +1,155 @@
+//! Secp256k1 multisig implementation
+//!
+//! multisig script: reserved(1 byte) | require_first_n(1 byte) | threshold(1 byte) |
+//! pubkeys_cnt(1 byte) | eth_address(20 bytes) * pubkeys_cnt
+//!
+//! multisig hash: blake2b(multisig script)[..20]
+
+use gw_utils::{
+    ckb_std::debug,
+    error::Error,
+    gw_common::{blake2b::new_blake2b, H256},
+    gw_types::bytes::Bytes,
+};
+use secp256k1_utils::recover_uncompressed_key;
+use sha3::{Digest, Keccak256};
+
+pub type MultisigHash = [u8; 20];
+
+const MULTISIG_HEADER_SIZE: usize = 4;
+const ETH_ADDRESS_SIZE: usize = 20;
+const SIGNATURE_SIZE: usize = 65;
+
+pub fn extract_multisig_lock_args(lock_args: Bytes) -> Result<(H256, MultisigHash), Error> {
+    if lock_args.len() != 52 {
+        debug!("Invalid lock args len: {}", lock_args.len());
+        return Err(Error::InvalidArgs);
+    }
+    let rollup_script_hash = {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&lock_args[..32]);
+        buf.into()
+    };
+    let multisig_hash = {
+        let mut buf = [0u8; 20];
+        buf.copy_from_slice(&lock_args[32..]);
+        buf
+    };
+    Ok((rollup_script_hash, multisig_hash))
+}
+
+#[derive(Default)]
+pub struct Secp256k1Multisig;
+
+impl Secp256k1Multisig {
+    /// Verify witness: multisig script | signature(65 bytes) * threshold
+    pub fn verify_alone(
+        &self,
+        multisig_hash: MultisigHash,
+        witness: &[u8],
+        message: H256,
+    ) -> Result<bool, Error> {
+        if witness.len() < MULTISIG_HEADER_SIZE {
+            debug!("Invalid multisig witness len: {}", witness.len());
+            return Err(Error::WrongSignature);
+        }
+        let reserved = witness[0];
+        let require_first_n = witness[1] as usize;
+        let threshold = witness[2] as usize;
+        let pubkeys_cnt = witness[3] as usize;
+        if reserved != 0
+            || pubkeys_cnt == 0
+            || threshold == 0
+            || threshold > pubkeys_cnt
+            || require_first_n > threshold
+        {
+            debug!(
+                "Invalid multisig script, require_first_n: {}, threshold: {}, pubkeys_cnt: {}",
+                require_first_n, threshold, pubkeys_cnt
+            );
+            return Err(Error::WrongSignature);
+        }
+        let script_size = MULTISIG_HEADER_SIZE + ETH_ADDRESS_SIZE * pubkeys_cnt;
+        if witness.len() != script_size + SIGNATURE_SIZE * threshold {
+            debug!(
+                "Invalid multisig witness len: {}, expected: {}",
+                witness.len(),
+                script_size + SIGNATURE_SIZE * threshold
+            );
+            return Err(Error::WrongSignature);
+        }
+
+        // check multisig hash
+        let script_hash = {
+            let mut hasher = new_blake2b();
+            hasher.update(&witness[..script_size]);
+            let mut buf = [0u8; 32];
+            hasher.finalize(&mut buf);
+            buf
+        };
+        if script_hash[..20] != multisig_hash {
+            debug!("Mismatch multisig hash");
+            return Ok(false);
+        }
+
+        let addresses = &witness[MULTISIG_HEADER_SIZE..script_size];
+        let mut signed = [false; 256];
+        for sig in witness[script_size..].chunks_exact(SIGNATURE_SIZE) {
+            let mut signature = [0u8; 65];
+            signature.copy_from_slice(sig);
+            let eth_address = recover_eth_address(signature, message)?;
+            let index = match addresses
+                .chunks_exact(ETH_ADDRESS_SIZE)
+                .position(|addr| addr == eth_address)
+            {
+                Some(index) => index,
+                None => {
+                    debug!("Unknown signer {:?}", eth_address);
+                    return Ok(false);
+                }
+            };
+            if signed[index] {
+                debug!("Duplicate signer {:?}", eth_address);
+                return Err(Error::WrongSignature);
+            }
+            signed[index] = true;
+        }
+
+        if signed[..require_first_n].iter().any(|signed| !signed) {
+            debug!("The first {} signers are required", require_first_n);
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    pub fn verify_message(
+        &self,
+        multisig_hash: MultisigHash,
+        witness: &[u8],
+        message: H256,
+    ) -> Result<bool, Error> {
+        let mut hasher = Keccak256::new();
+        hasher.update("\x19Ethereum Signed Message:\n32");
+        hasher.update(message.as_slice());
+        let buf = hasher.finalize();
+        let mut signing_message = [0u8; 32];
+        signing_message.copy_from_slice(&buf[..]);
+        let signing_message = H256::from(signing_message);
+
+        self.verify_alone(multisig_hash, witness, signing_message)
+    }
+}
+
+fn recover_eth_address(signature: [u8; 65], message: H256) -> Result<[u8; 20], Error> {
+    let pubkey = recover_uncompressed_key(message.into(), signature).map_err(|err| {
+        debug!("failed to recover secp256k1 pubkey, error number: {}", err);
+        Error::WrongSignature
+    })?;
+    let mut hasher = Keccak256::new();
+    hasher.update(&pubkey[1..]);
+    let buf = hasher.finalize();
+    let mut eth_address = [0u8; 20];
+    eth_address.copy_from_slice(&buf[12..]);
+    Ok(eth_address)
+}
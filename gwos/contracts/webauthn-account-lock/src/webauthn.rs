@@ -0,0 +1,145 @@
+//! WebAuthn assertion verification
+//!
+//! witness lock: molecule `WebAuthnSignature`
+//!
+//! The signed client data JSON is rebuilt from the message, in the same way as
+//! the off-chain lock algorithm:
+//! `{"type":"webauthn.get","challenge":"` | base64url(message) | `"` | client_data_json_suffix
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use gw_utils::{
+    ckb_std::debug,
+    error::Error,
+    gw_common::{blake2b::new_blake2b, H256},
+    gw_types::{bytes::Bytes, packed::WebAuthnSignatureReader, prelude::*},
+};
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+pub type PubkeyHash = [u8; 20];
+
+const PUBKEY_SIZE: usize = 65;
+const SIGNATURE_SIZE: usize = 64;
+/// rp_id_hash(32 bytes) | flags(1 byte) | sign_count(4 bytes)
+const MIN_AUTHENTICATOR_DATA_SIZE: usize = 37;
+const FLAG_USER_PRESENT: u8 = 0x01;
+const CLIENT_DATA_JSON_PREFIX: &[u8] = br#"{"type":"webauthn.get","challenge":""#;
+
+pub fn extract_webauthn_lock_args(lock_args: Bytes) -> Result<(H256, PubkeyHash), Error> {
+    if lock_args.len() != 52 {
+        debug!("Invalid lock args len: {}", lock_args.len());
+        return Err(Error::InvalidArgs);
+    }
+    let rollup_script_hash = {
+        let mut buf = [0u8; 32];
+        buf.copy_from_slice(&lock_args[..32]);
+        buf.into()
+    };
+    let pubkey_hash = {
+        let mut buf = [0u8; 20];
+        buf.copy_from_slice(&lock_args[32..]);
+        buf
+    };
+    Ok((rollup_script_hash, pubkey_hash))
+}
+
+#[derive(Default)]
+pub struct WebAuthn;
+
+impl WebAuthn {
+    pub fn verify_alone(
+        &self,
+        pubkey_hash: PubkeyHash,
+        witness: &[u8],
+        message: H256,
+    ) -> Result<bool, Error> {
+        let sig = WebAuthnSignatureReader::from_slice(witness).map_err(|_| {
+            debug!("Invalid witness");
+            Error::WrongSignature
+        })?;
+        let pubkey = sig.pubkey().raw_data();
+        let signature = sig.signature().raw_data();
+        let authenticator_data = sig.authenticator_data().raw_data();
+        if pubkey.len() != PUBKEY_SIZE || signature.len() != SIGNATURE_SIZE {
+            debug!(
+                "Invalid pubkey len: {}, signature len: {}",
+                pubkey.len(),
+                signature.len()
+            );
+            return Err(Error::WrongSignature);
+        }
+        if authenticator_data.len() < MIN_AUTHENTICATOR_DATA_SIZE {
+            debug!(
+                "Invalid authenticator data len: {}",
+                authenticator_data.len()
+            );
+            return Err(Error::WrongSignature);
+        }
+
+        // check pubkey hash
+        let hash = {
+            let mut hasher = new_blake2b();
+            hasher.update(pubkey);
+            let mut buf = [0u8; 32];
+            hasher.finalize(&mut buf);
+            buf
+        };
+        if hash[..20] != pubkey_hash {
+            debug!("Mismatch pubkey hash");
+            return Ok(false);
+        }
+
+        // check authenticator data
+        if authenticator_data[32] & FLAG_USER_PRESENT == 0 {
+            debug!("User not present");
+            return Ok(false);
+        }
+
+        // verify signature
+        let verifying_key = VerifyingKey::from_sec1_bytes(pubkey).map_err(|_| {
+            debug!("Invalid pubkey");
+            Error::WrongSignature
+        })?;
+        let signature = Signature::try_from(signature).map_err(|_| {
+            debug!("Invalid signature");
+            Error::WrongSignature
+        })?;
+        let client_data_json = client_data_json(&message, sig.client_data_json_suffix().raw_data());
+        let mut signed_data = Vec::with_capacity(authenticator_data.len() + 32);
+        signed_data.extend_from_slice(authenticator_data);
+        signed_data.extend_from_slice(&Sha256::digest(&client_data_json));
+        Ok(verifying_key.verify(&signed_data, &signature).is_ok())
+    }
+}
+
+fn client_data_json(message: &H256, client_data_json_suffix: &[u8]) -> Vec<u8> {
+    let challenge = encode_challenge(message.as_slice());
+    let mut buf = Vec::with_capacity(
+        CLIENT_DATA_JSON_PREFIX.len() + challenge.len() + 1 + client_data_json_suffix.len(),
+    );
+    buf.extend_from_slice(CLIENT_DATA_JSON_PREFIX);
+    buf.extend_from_slice(&challenge);
+    buf.push(b'"');
+    buf.extend_from_slice(client_data_json_suffix);
+    buf
+}
+
+/// base64url encoding without padding
+fn encode_challenge(data: &[u8]) -> Vec<u8> {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = Vec::with_capacity((data.len() * 4 + 2) / 3);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        let chars = chunk.len() + 1;
+        for i in 0..chars {
+            out.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize]);
+        }
+    }
+    out
+}
@@ -152,6 +152,9 @@ pub fn main() -> Result<(), Error> {
             check_output_cell_has_same_content(0, Source::GroupInput, custodian_cell_index)?;
             Ok(())
         }
+        UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaTrade(_unlock_args) => {
+            check_withdrawal_trade(&script, &rollup_type_hash, &lock_args, &owner_lock_hash)
+        }
         UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(_unlock_args) => {
             // try search rollup state from deps
             let global_state = match search_rollup_state(&rollup_type_hash, Source::CellDep)? {
@@ -201,6 +204,11 @@ pub fn main() -> Result<(), Error> {
                 }
             }
 
+            // withdrawals to the same owner lock and sUDT may be merged into one owner cell
+            if check_merged_output_cell(&script, &rollup_type_hash, &owner_lock_hash).is_ok() {
+                return Ok(());
+            }
+
             // fallback to input owner cell way
             if search_lock_hash(&lock_args.owner_lock_hash().unpack(), Source::Input).is_none() {
                 return Err(Error::OwnerCellNotFound);
@@ -211,6 +219,144 @@ pub fn main() -> Result<(), Error> {
     }
 }
 
+/// Transfer pending withdrawal cells to a new owner, e.g. a liquidity provider who pays the
+/// owner on layer1 in advance
+///
+/// The current owner must sign the transaction by putting an owner cell in inputs, every
+/// withdrawal cell of the group must be moved to the output at the same index with the same
+/// content, only the owner lock of the withdrawal lock args is allowed to change
+fn check_withdrawal_trade(
+    withdrawal_script: &ckb_types::packed::Script,
+    rollup_type_hash: &[u8; 32],
+    lock_args: &WithdrawalLockArgs,
+    owner_lock_hash: &[u8; 32],
+) -> Result<(), Error> {
+    if search_lock_hash(owner_lock_hash, Source::Input).is_none() {
+        return Err(Error::OwnerCellNotFound);
+    }
+
+    let withdrawal_lock_hash = load_cell_lock_hash(0, Source::GroupInput)?;
+    for (index, _) in QueryIter::new(load_cell_lock_hash, Source::Input)
+        .enumerate()
+        .filter(|(_idx, lock_hash)| lock_hash == &withdrawal_lock_hash)
+    {
+        check_output_cell_has_same_content(index, Source::Input, index)?;
+
+        let output_lock = load_cell_lock(index, Source::Output)?;
+        if output_lock.code_hash().as_slice() != withdrawal_script.code_hash().as_slice()
+            || output_lock.hash_type() != withdrawal_script.hash_type()
+        {
+            debug!("[via trade] output cell isn't a withdrawal cell");
+            return Err(Error::InvalidOutput);
+        }
+
+        let output_args: Bytes = output_lock.args().unpack();
+        if output_args.len() < rollup_type_hash.len() || &output_args[..32] != rollup_type_hash {
+            return Err(Error::InvalidOutput);
+        }
+        let output_lock_args = gw_utils::withdrawal::parse_lock_args(&output_args)?.lock_args;
+        if output_lock_args.withdrawal_block_hash().as_slice()
+            != lock_args.withdrawal_block_hash().as_slice()
+            || output_lock_args.withdrawal_block_number().as_slice()
+                != lock_args.withdrawal_block_number().as_slice()
+            || output_lock_args.account_script_hash().as_slice()
+                != lock_args.account_script_hash().as_slice()
+        {
+            debug!("[via trade] output withdrawal lock args not match");
+            return Err(Error::InvalidOutput);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check there is an output owner cell merged from all withdrawal cells which have the same
+/// owner lock, type and extra data as the current group
+///
+/// The merged cell's capacity and sUDT amount must equal the sum of merged withdrawal cells,
+/// the extra data (sUDT data after the amount, or the whole data of a cell without type) is
+/// kept as is, cells with different extra data are never merged together
+fn check_merged_output_cell(
+    withdrawal_script: &ckb_types::packed::Script,
+    rollup_type_hash: &[u8; 32],
+    owner_lock_hash: &[u8; 32],
+) -> Result<(), Error> {
+    let type_hash = load_cell_type_hash(0, Source::GroupInput)?;
+    let data = load_cell_data(0, Source::GroupInput)?;
+    let extra_data = split_withdrawal_data(type_hash.is_some(), &data)?.1;
+
+    let mut total_capacity = 0u64;
+    let mut total_amount = 0u128;
+    for (index, lock) in QueryIter::new(load_cell_lock, Source::Input).enumerate() {
+        if lock.code_hash().as_slice() != withdrawal_script.code_hash().as_slice()
+            || lock.hash_type() != withdrawal_script.hash_type()
+        {
+            continue;
+        }
+        let args: Bytes = lock.args().unpack();
+        if args.len() < rollup_type_hash.len() || &args[..32] != rollup_type_hash {
+            continue;
+        }
+        match gw_utils::withdrawal::parse_lock_args(&args) {
+            Ok(parsed) if &parsed.owner_lock.hash() == owner_lock_hash => {}
+            _ => continue,
+        }
+        if load_cell_type_hash(index, Source::Input)? != type_hash {
+            continue;
+        }
+        let input_data = load_cell_data(index, Source::Input)?;
+        let (amount, input_extra_data) = split_withdrawal_data(type_hash.is_some(), &input_data)?;
+        if input_extra_data != extra_data {
+            continue;
+        }
+
+        let capacity = load_cell_capacity(index, Source::Input)?;
+        total_capacity = total_capacity
+            .checked_add(capacity)
+            .ok_or(Error::AmountOverflow)?;
+        total_amount = total_amount
+            .checked_add(amount)
+            .ok_or(Error::AmountOverflow)?;
+    }
+
+    for (index, lock_hash) in QueryIter::new(load_cell_lock_hash, Source::Output).enumerate() {
+        if &lock_hash != owner_lock_hash
+            || load_cell_type_hash(index, Source::Output)? != type_hash
+            || load_cell_capacity(index, Source::Output)? != total_capacity
+        {
+            continue;
+        }
+        let output_data = load_cell_data(index, Source::Output)?;
+        let data_matched = match split_withdrawal_data(type_hash.is_some(), &output_data) {
+            Ok((amount, output_extra_data)) => {
+                amount == total_amount && output_extra_data == extra_data
+            }
+            Err(_) => false,
+        };
+        if data_matched {
+            return Ok(());
+        }
+    }
+
+    debug!("[via finalize] merged output cell not found");
+    Err(Error::InvalidOutput)
+}
+
+/// Split withdrawal cell data into the sUDT amount and the extra data
+///
+/// The amount of a cell without type is 0 and the whole data is the extra data
+fn split_withdrawal_data(is_sudt: bool, data: &[u8]) -> Result<(u128, &[u8]), Error> {
+    if !is_sudt {
+        return Ok((0, data));
+    }
+    if data.len() < 16 {
+        return Err(Error::Encoding);
+    }
+    let mut buf = [0u8; 16];
+    buf.copy_from_slice(&data[..16]);
+    Ok((u128::from_le_bytes(buf), &data[16..]))
+}
+
 fn check_output_cell_has_same_content(
     input_index: usize,
     input_source: Source,
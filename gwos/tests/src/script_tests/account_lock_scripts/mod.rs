@@ -1,2 +1,4 @@
 mod eth_account_lock;
+mod multisig_account_lock;
 mod tron_account_lock;
+mod webauthn_account_lock;
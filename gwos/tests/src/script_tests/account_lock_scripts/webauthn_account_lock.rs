@@ -0,0 +1,375 @@
+use crate::script_tests::utils::layer1::*;
+use crate::testing_tool::programs::{
+    ALWAYS_SUCCESS_CODE_HASH, ALWAYS_SUCCESS_PROGRAM, WEBAUTHN_ACCOUNT_LOCK_CODE_HASH,
+    WEBAUTHN_ACCOUNT_LOCK_PROGRAM,
+};
+use ckb_chain_spec::consensus::ConsensusBuilder;
+use ckb_error::assert_error_eq;
+use ckb_script::{ScriptError, TransactionScriptsVerifier, TxVerifyEnv};
+use ckb_types::core::hardfork::HardForkSwitch;
+use ckb_types::core::HeaderView;
+use ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+    prelude::*,
+};
+use gw_ckb_hardfork::{GLOBAL_CURRENT_EPOCH_NUMBER, GLOBAL_HARDFORK_SWITCH};
+use gw_common::blake2b::new_blake2b;
+use gw_types::core::SigningType;
+use p256::ecdsa::{signature::Signer, SigningKey, VerifyingKey};
+use rand::{thread_rng, Rng};
+use sha2::{Digest, Sha256};
+
+use std::sync::atomic::Ordering;
+
+const ERROR_WRONG_SIGNATURE: i8 = 41;
+const FLAG_USER_PRESENT: u8 = 0x01;
+
+fn gen_tx(
+    dummy: &mut DummyDataLoader,
+    lock_args: Bytes,
+    signing_type: SigningType,
+    message: Bytes,
+) -> TransactionView {
+    let mut rng = thread_rng();
+    // setup sighash_all dep
+    let script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    let owner_lock_script_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    // dep contract code
+    // webauthn account lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(WEBAUTHN_ACCOUNT_LOCK_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    let script_cell_data_hash = CellOutput::calc_data_hash(&WEBAUTHN_ACCOUNT_LOCK_PROGRAM);
+    dummy.cells.insert(
+        script_out_point.clone(),
+        (script_cell, WEBAUTHN_ACCOUNT_LOCK_PROGRAM.clone()),
+    );
+    // owner lock
+    let script_cell = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(ALWAYS_SUCCESS_PROGRAM.len())
+                .expect("script capacity")
+                .pack(),
+        )
+        .build();
+    dummy.cells.insert(
+        owner_lock_script_out_point.clone(),
+        (script_cell, ALWAYS_SUCCESS_PROGRAM.clone()),
+    );
+    // owner lock cell
+    let owner_lock_cell = CellOutput::new_builder()
+        .lock(
+            Script::new_builder()
+                .code_hash((*ALWAYS_SUCCESS_CODE_HASH).pack())
+                .hash_type(ScriptHashType::Data.into())
+                .build(),
+        )
+        .build();
+    let owner_lock_hash: [u8; 32] = owner_lock_cell.lock().calc_script_hash().unpack();
+    let owner_lock_cell_out_point = {
+        let tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(tx_hash, 0)
+    };
+    dummy.cells.insert(
+        owner_lock_cell_out_point.clone(),
+        (owner_lock_cell, Bytes::default()),
+    );
+    // setup default tx builder
+    let dummy_capacity = Capacity::shannons(42);
+    let tx_builder = TransactionBuilder::default()
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(owner_lock_script_out_point)
+                .dep_type(DepType::Code.into())
+                .build(),
+        )
+        .output(
+            CellOutput::new_builder()
+                .capacity(dummy_capacity.pack())
+                .build(),
+        )
+        .output_data(Bytes::new().pack());
+
+    let previous_out_point = {
+        let previous_tx_hash = {
+            let mut buf = [0u8; 32];
+            rng.fill(&mut buf);
+            buf.pack()
+        };
+        OutPoint::new(previous_tx_hash, 0)
+    };
+    let previous_output_cell = {
+        let script = Script::new_builder()
+            .args(lock_args.pack())
+            .code_hash(script_cell_data_hash)
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        CellOutput::new_builder()
+            .capacity(dummy_capacity.pack())
+            .lock(script)
+            .build()
+    };
+    let mut input_data = owner_lock_hash.to_vec();
+    input_data.push(signing_type.into());
+    input_data.extend_from_slice(&message);
+    dummy.cells.insert(
+        previous_out_point.clone(),
+        (previous_output_cell, input_data.into()),
+    );
+    tx_builder
+        .input(CellInput::new(previous_out_point, 0))
+        .input(CellInput::new(owner_lock_cell_out_point, 0))
+        .build()
+}
+
+/// Software authenticator
+struct Credential {
+    signing_key: SigningKey,
+}
+
+impl Credential {
+    fn generate() -> Self {
+        let signing_key = SigningKey::random(&mut thread_rng());
+        Credential { signing_key }
+    }
+
+    fn pubkey(&self) -> Vec<u8> {
+        VerifyingKey::from(&self.signing_key)
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn pubkey_hash(&self) -> [u8; 20] {
+        let mut hasher = new_blake2b();
+        hasher.update(&self.pubkey());
+        let mut buf = [0u8; 32];
+        hasher.finalize(&mut buf);
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&buf[..20]);
+        hash
+    }
+
+    /// witness: molecule WebAuthnSignature
+    fn assert(&self, challenge: &[u8], flags: u8) -> Bytes {
+        let suffix = br#","origin":"https://example.com","crossOrigin":false}"#;
+        let client_data_json = format!(
+            r#"{{"type":"webauthn.get","challenge":"{}"{}"#,
+            base64::encode_config(challenge, base64::URL_SAFE_NO_PAD),
+            std::str::from_utf8(suffix).unwrap()
+        );
+        self.assert_with(flags, client_data_json.as_bytes(), suffix)
+    }
+
+    /// Sign `signed_client_data_json`, the witness carries `client_data_json_suffix`
+    fn assert_with(
+        &self,
+        flags: u8,
+        signed_client_data_json: &[u8],
+        client_data_json_suffix: &[u8],
+    ) -> Bytes {
+        let mut authenticator_data = vec![0u8; 37];
+        authenticator_data[32] = flags;
+        let mut signed_data = authenticator_data.clone();
+        signed_data.extend_from_slice(&Sha256::digest(signed_client_data_json));
+        let signature: p256::ecdsa::Signature = self.signing_key.sign(&signed_data);
+
+        pack_webauthn_signature(&[
+            &self.pubkey(),
+            signature.as_ref(),
+            &authenticator_data,
+            client_data_json_suffix,
+        ])
+    }
+}
+
+/// Molecule table `WebAuthnSignature { pubkey, signature, authenticator_data,
+/// client_data_json_suffix }`, all fields are `Bytes`
+fn pack_webauthn_signature(fields: &[&[u8]; 4]) -> Bytes {
+    let header_size = 4 * (fields.len() + 1);
+    let total_size = header_size + fields.iter().map(|f| 4 + f.len()).sum::<usize>();
+    let mut buf = Vec::with_capacity(total_size);
+    buf.extend_from_slice(&(total_size as u32).to_le_bytes());
+    let mut offset = header_size;
+    for field in fields {
+        buf.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += 4 + field.len();
+    }
+    for field in fields {
+        buf.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        buf.extend_from_slice(field);
+    }
+    buf.into()
+}
+
+fn verify_tx(data_loader: &DummyDataLoader, tx: &TransactionView) -> Result<u64, ckb_error::Error> {
+    let hardfork_switch = {
+        let switch = GLOBAL_HARDFORK_SWITCH.load();
+        HardForkSwitch::new_without_any_enabled()
+            .as_builder()
+            .rfc_0028(switch.rfc_0028())
+            .rfc_0029(switch.rfc_0029())
+            .rfc_0030(switch.rfc_0030())
+            .rfc_0031(switch.rfc_0031())
+            .rfc_0032(switch.rfc_0032())
+            .rfc_0036(switch.rfc_0036())
+            .rfc_0038(switch.rfc_0038())
+            .build()
+            .unwrap()
+    };
+    let consensus = ConsensusBuilder::default()
+        .hardfork_switch(hardfork_switch)
+        .build();
+    let current_epoch_number = GLOBAL_CURRENT_EPOCH_NUMBER.load(Ordering::SeqCst);
+    let tx_verify_env = TxVerifyEnv::new_submit(
+        &HeaderView::new_advanced_builder()
+            .epoch(current_epoch_number.pack())
+            .build(),
+    );
+    let resolved_tx = build_resolved_tx(data_loader, tx);
+    let mut verifier =
+        TransactionScriptsVerifier::new(&resolved_tx, &consensus, data_loader, &tx_verify_env);
+    verifier.set_debug_printer(|_script, msg| println!("[script debug] {}", msg));
+    verifier.verify(MAX_CYCLES)
+}
+
+fn gen_signed_tx(
+    data_loader: &mut DummyDataLoader,
+    credential: &Credential,
+    signed_message: &[u8; 32],
+    flags: u8,
+) -> TransactionView {
+    let lock_args = {
+        let rollup_script_hash = [42u8; 32];
+        let mut args = rollup_script_hash.to_vec();
+        args.extend_from_slice(&credential.pubkey_hash());
+        args.into()
+    };
+    let tx = gen_tx(
+        data_loader,
+        lock_args,
+        SigningType::Raw,
+        signed_message.to_vec().into(),
+    );
+    tx.as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(credential.assert(signed_message, flags)).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build()
+}
+
+fn assert_wrong_signature(verify_result: Result<u64, ckb_error::Error>) {
+    let script_cell_index = 0;
+    assert_error_eq!(
+        verify_result.unwrap_err(),
+        ScriptError::ValidationFailure(
+            format!(
+                "by-data-hash/{}",
+                ckb_types::H256(*WEBAUTHN_ACCOUNT_LOCK_CODE_HASH)
+            ),
+            ERROR_WRONG_SIGNATURE
+        )
+        .input_lock_script(script_cell_index)
+    );
+}
+
+#[test]
+fn test_webauthn_assertion() {
+    let mut data_loader = DummyDataLoader::default();
+    let credential = Credential::generate();
+    let message = [7u8; 32];
+    let tx = gen_signed_tx(&mut data_loader, &credential, &message, FLAG_USER_PRESENT);
+    verify_tx(&data_loader, &tx).expect("pass verification");
+}
+
+#[test]
+fn test_webauthn_user_not_present() {
+    let mut data_loader = DummyDataLoader::default();
+    let credential = Credential::generate();
+    let message = [7u8; 32];
+    let tx = gen_signed_tx(&mut data_loader, &credential, &message, 0);
+    assert_wrong_signature(verify_tx(&data_loader, &tx));
+}
+
+#[test]
+fn test_webauthn_wrong_credential() {
+    let mut data_loader = DummyDataLoader::default();
+    let credential = Credential::generate();
+    let message = [7u8; 32];
+    let tx = gen_signed_tx(&mut data_loader, &credential, &message, FLAG_USER_PRESENT);
+    // replace the assertion with another credential
+    let other = Credential::generate();
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(Some(other.assert(&message, FLAG_USER_PRESENT)).pack())
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    assert_wrong_signature(verify_tx(&data_loader, &tx));
+}
+
+#[test]
+fn test_webauthn_non_canonical_client_data() {
+    let mut data_loader = DummyDataLoader::default();
+    let credential = Credential::generate();
+    let message = [7u8; 32];
+    let tx = gen_signed_tx(&mut data_loader, &credential, &message, FLAG_USER_PRESENT);
+    // the same fields with whitespaces, the contract only accepts the
+    // serialization rebuilt from the message
+    let suffix = br#","origin":"https://example.com"}"#;
+    let client_data_json = format!(
+        r#"{{"type": "webauthn.get", "challenge": "{}"{}"#,
+        base64::encode_config(&message, base64::URL_SAFE_NO_PAD),
+        std::str::from_utf8(suffix).unwrap()
+    );
+    let tx = tx
+        .as_advanced_builder()
+        .set_witnesses(vec![WitnessArgs::new_builder()
+            .lock(
+                Some(credential.assert_with(
+                    FLAG_USER_PRESENT,
+                    client_data_json.as_bytes(),
+                    suffix,
+                ))
+                .pack(),
+            )
+            .build()
+            .as_bytes()
+            .pack()])
+        .build();
+    assert_wrong_signature(verify_tx(&data_loader, &tx));
+}
@@ -19,6 +19,7 @@ use ckb_types::{
     packed::{CellInput, CellOutput},
     prelude::{Pack as CKBPack, Unpack as CKBUnpack},
 };
+use gw_common::blake2b::new_blake2b;
 use gw_common::builtins::ETH_REGISTRY_ACCOUNT_ID;
 use gw_common::state::State;
 use gw_common::H256;
@@ -46,8 +47,44 @@ use gw_types::{
     },
 };
 
+/// Hand-encoded `WithdrawalSignatureEnvelope` of a `BatchWithdrawalSignature`
+fn pack_batch_withdrawal_signature(index: u32, raw_request_hashes: &[[u8; 32]]) -> Bytes {
+    let mut hashes = (raw_request_hashes.len() as u32).to_le_bytes().to_vec();
+    for hash in raw_request_hashes {
+        hashes.extend_from_slice(hash);
+    }
+    let mut signature = 65u32.to_le_bytes().to_vec();
+    signature.extend_from_slice(&[0u8; 65]);
+
+    let fields = [index.to_le_bytes().to_vec(), hashes, signature];
+    let header_size = 4 + 4 * fields.len();
+    let total_size = header_size + fields.iter().map(Vec::len).sum::<usize>();
+    let mut table = (total_size as u32).to_le_bytes().to_vec();
+    let mut offset = header_size;
+    for field in fields.iter() {
+        table.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += field.len();
+    }
+    for field in fields.iter() {
+        table.extend_from_slice(field);
+    }
+
+    let mut envelope = 0u32.to_le_bytes().to_vec();
+    envelope.extend_from_slice(&table);
+    envelope.into()
+}
+
 #[tokio::test]
 async fn test_cancel_withdrawal() {
+    cancel_withdrawal(false).await;
+}
+
+#[tokio::test]
+async fn test_cancel_batch_withdrawal() {
+    cancel_withdrawal(true).await;
+}
+
+async fn cancel_withdrawal(is_batch: bool) {
     init_env_log();
     let input_out_point = random_out_point();
     let type_id = calculate_state_validator_type_id(input_out_point.clone());
@@ -145,18 +182,23 @@ async fn test_cancel_withdrawal() {
         let withdrawal_capacity = 400_00000000u64;
         withdrawal_extra = {
             let owner_lock = Script::default();
+            let raw = RawWithdrawalRequest::new_builder()
+                .nonce(Pack::pack(&0u32))
+                .capacity(Pack::pack(&withdrawal_capacity))
+                .account_script_hash(Pack::pack(&sender_script.hash()))
+                .owner_lock_hash(Pack::pack(&owner_lock.hash()))
+                .registry_id(Pack::pack(&eth_registry_id))
+                .build();
+            let signature = if is_batch {
+                pack_batch_withdrawal_signature(0, &[raw.hash()])
+            } else {
+                Bytes::default()
+            };
             WithdrawalRequestExtra::new_builder()
                 .request(
                     WithdrawalRequest::new_builder()
-                        .raw(
-                            RawWithdrawalRequest::new_builder()
-                                .nonce(Pack::pack(&0u32))
-                                .capacity(Pack::pack(&withdrawal_capacity))
-                                .account_script_hash(Pack::pack(&sender_script.hash()))
-                                .owner_lock_hash(Pack::pack(&owner_lock.hash()))
-                                .registry_id(Pack::pack(&eth_registry_id))
-                                .build(),
-                        )
+                        .raw(raw)
+                        .signature(Pack::pack(&signature))
                         .build(),
                 )
                 .owner_lock(owner_lock)
@@ -306,7 +348,16 @@ async fn test_cancel_withdrawal() {
             .build();
         let owner_lock_hash = vec![42u8; 32];
 
-        let message = {
+        let message = if is_batch {
+            // blake2b(rollup_script_hash | count | raw_request_hashes)
+            let mut hasher = new_blake2b();
+            hasher.update(&rollup_script_hash);
+            hasher.update(&1u32.to_le_bytes());
+            hasher.update(&withdrawal.raw().hash());
+            let mut message = [0u8; 32];
+            hasher.finalize(&mut message);
+            message
+        } else {
             let withdrawal = Withdrawal::from_raw(
                 withdrawal.raw(),
                 withdrawal_extra.owner_lock(),
@@ -23,6 +23,7 @@ use gw_types::prelude::Pack;
 use secp256k1::rand::rngs::OsRng;
 use secp256k1::{Message, Secp256k1, SecretKey};
 
+const INVALID_OUTPUT_ERROR: i8 = 7;
 const OWNER_CELL_NOT_FOUND_ERROR: i8 = 8;
 
 #[test]
@@ -333,6 +334,262 @@ fn test_unlock_withdrawal_via_finalize_by_switch_indexed_output_to_owner_lock()
     verify_ctx.verify_tx(tx).expect("success");
 }
 
+#[test]
+fn test_unlock_withdrawal_via_finalize_by_merged_output_to_owner_lock() {
+    init_env_log();
+
+    const DEFAULT_CAPACITY: u64 = 1000 * 10u64.pow(8);
+
+    let rollup_type_script = random_always_success_script();
+    let rollup_type_hash = rollup_type_script.hash();
+    let (mut verify_ctx, script_ctx) = build_verify_context();
+
+    let last_finalized_block_number = rand::random::<u64>() + 100;
+    let rollup_cell = {
+        let global_state = GlobalState::new_builder()
+            .last_finalized_block_number(last_finalized_block_number.pack())
+            .build();
+
+        let output = CellOutput::new_builder()
+            .lock(random_always_success_script())
+            .type_(Some(rollup_type_script).pack())
+            .capacity(DEFAULT_CAPACITY.pack())
+            .build();
+
+        (output, global_state.as_bytes())
+    };
+    let rollup_dep = {
+        let out_point = verify_ctx.insert_cell(rollup_cell.0.to_ckb(), rollup_cell.1);
+        CellDep::new_builder().out_point(out_point.to_gw()).build()
+    };
+
+    let owner_lock = random_always_success_script();
+    let build_finalized_withdrawal_cell = || {
+        let lock_args = WithdrawalLockArgs::new_builder()
+            .account_script_hash(random_always_success_script().hash().pack())
+            .withdrawal_block_hash(random_always_success_script().hash().pack())
+            .withdrawal_block_number(last_finalized_block_number.saturating_sub(1).pack())
+            .owner_lock_hash(owner_lock.hash().pack())
+            .build();
+
+        let mut args = Vec::new();
+        args.extend_from_slice(&lock_args.as_bytes());
+        args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&owner_lock.as_bytes());
+
+        build_rollup_locked_cell(
+            &rollup_type_hash,
+            &script_ctx.withdrawal.script.hash(),
+            DEFAULT_CAPACITY,
+            Bytes::from(args),
+        )
+    };
+    let finalized_withdrawal_cell_1 = (build_finalized_withdrawal_cell(), Bytes::new());
+    let finalized_withdrawal_input_1 = {
+        let out_point = verify_ctx.insert_cell(finalized_withdrawal_cell_1.0.clone(), Bytes::new());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let finalized_withdrawal_input_2 = {
+        let out_point = verify_ctx.insert_cell(finalized_withdrawal_cell_1.0.clone(), Bytes::new());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    // Different withdrawal lock but same owner lock
+    let finalized_withdrawal_input_3 = {
+        let out_point = verify_ctx.insert_cell(build_finalized_withdrawal_cell(), Bytes::new());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let unlock_via_finalize_witness = {
+        let unlock_args = UnlockWithdrawalViaFinalize::new_builder().build();
+        let unlock_witness = UnlockWithdrawalWitness::new_builder()
+            .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(
+                unlock_args,
+            ))
+            .build();
+        WitnessArgs::new_builder()
+            .lock(Some(unlock_witness.as_bytes()).pack())
+            .build()
+    };
+
+    // Merged output must have the sum of withdrawals capacity
+    let err_output_cell = {
+        let output = CellOutput::new_builder()
+            .capacity((DEFAULT_CAPACITY * 2 - 1).pack()) // ERROR: less capacity
+            .lock(owner_lock.clone())
+            .build();
+
+        (output.to_ckb(), Bytes::new())
+    };
+    let err_tx = build_simple_tx_with_out_point(
+        &mut verify_ctx.inner,
+        finalized_withdrawal_cell_1.clone(),
+        finalized_withdrawal_input_1.to_ckb().previous_output(),
+        err_output_cell,
+    )
+    .as_advanced_builder()
+    .input(finalized_withdrawal_input_2.to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .witness(Default::default())
+    .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+    .cell_dep(rollup_dep.to_ckb())
+    .build();
+
+    let err = verify_ctx.verify_tx(err_tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        OWNER_CELL_NOT_FOUND_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+
+    // Merge two withdrawals into one owner cell
+    let merged_output_cell = {
+        let output = CellOutput::new_builder()
+            .capacity((DEFAULT_CAPACITY * 2).pack())
+            .lock(owner_lock)
+            .build();
+
+        (output.to_ckb(), Bytes::new())
+    };
+    let tx = build_simple_tx_with_out_point(
+        &mut verify_ctx.inner,
+        finalized_withdrawal_cell_1,
+        finalized_withdrawal_input_1.to_ckb().previous_output(),
+        merged_output_cell,
+    )
+    .as_advanced_builder()
+    .input(finalized_withdrawal_input_3.to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+    .cell_dep(rollup_dep.to_ckb())
+    .build();
+
+    verify_ctx.verify_tx(tx).expect("success");
+}
+
+#[test]
+fn test_unlock_withdrawal_via_finalize_refuse_to_merge_different_data() {
+    init_env_log();
+
+    const DEFAULT_CAPACITY: u64 = 1000 * 10u64.pow(8);
+
+    let rollup_type_script = random_always_success_script();
+    let rollup_type_hash = rollup_type_script.hash();
+    let (mut verify_ctx, script_ctx) = build_verify_context();
+
+    let last_finalized_block_number = rand::random::<u64>() + 100;
+    let rollup_cell = {
+        let global_state = GlobalState::new_builder()
+            .last_finalized_block_number(last_finalized_block_number.pack())
+            .build();
+
+        let output = CellOutput::new_builder()
+            .lock(random_always_success_script())
+            .type_(Some(rollup_type_script).pack())
+            .capacity(DEFAULT_CAPACITY.pack())
+            .build();
+
+        (output, global_state.as_bytes())
+    };
+    let rollup_dep = {
+        let out_point = verify_ctx.insert_cell(rollup_cell.0.to_ckb(), rollup_cell.1);
+        CellDep::new_builder().out_point(out_point.to_gw()).build()
+    };
+
+    let owner_lock = random_always_success_script();
+    let finalized_withdrawal_cell = {
+        let lock_args = WithdrawalLockArgs::new_builder()
+            .account_script_hash(random_always_success_script().hash().pack())
+            .withdrawal_block_hash(random_always_success_script().hash().pack())
+            .withdrawal_block_number(last_finalized_block_number.saturating_sub(1).pack())
+            .owner_lock_hash(owner_lock.hash().pack())
+            .build();
+
+        let mut args = Vec::new();
+        args.extend_from_slice(&lock_args.as_bytes());
+        args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&owner_lock.as_bytes());
+
+        build_rollup_locked_cell(
+            &rollup_type_hash,
+            &script_ctx.withdrawal.script.hash(),
+            DEFAULT_CAPACITY,
+            Bytes::from(args),
+        )
+    };
+    let finalized_withdrawal_cell_1 = (finalized_withdrawal_cell.clone(), Bytes::from(vec![1]));
+    let finalized_withdrawal_input_1 = {
+        let out_point = verify_ctx.insert_cell(
+            finalized_withdrawal_cell_1.0.clone(),
+            finalized_withdrawal_cell_1.1.clone(),
+        );
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    // Same withdrawal lock but different data
+    let finalized_withdrawal_input_2 = {
+        let out_point = verify_ctx.insert_cell(finalized_withdrawal_cell, Bytes::from(vec![2]));
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let unlock_via_finalize_witness = {
+        let unlock_args = UnlockWithdrawalViaFinalize::new_builder().build();
+        let unlock_witness = UnlockWithdrawalWitness::new_builder()
+            .set(UnlockWithdrawalWitnessUnion::UnlockWithdrawalViaFinalize(
+                unlock_args,
+            ))
+            .build();
+        WitnessArgs::new_builder()
+            .lock(Some(unlock_witness.as_bytes()).pack())
+            .build()
+    };
+
+    // Cells with different data can't be merged into one owner cell
+    let merged_output_cell = {
+        let output = CellOutput::new_builder()
+            .capacity((DEFAULT_CAPACITY * 2).pack())
+            .lock(owner_lock)
+            .build();
+
+        (output.to_ckb(), Bytes::from(vec![1]))
+    };
+    let err_tx = build_simple_tx_with_out_point(
+        &mut verify_ctx.inner,
+        finalized_withdrawal_cell_1,
+        finalized_withdrawal_input_1.to_ckb().previous_output(),
+        merged_output_cell,
+    )
+    .as_advanced_builder()
+    .input(finalized_withdrawal_input_2.to_ckb())
+    .witness(unlock_via_finalize_witness.as_bytes().to_ckb())
+    .witness(Default::default())
+    .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+    .cell_dep(rollup_dep.to_ckb())
+    .build();
+
+    let err = verify_ctx.verify_tx(err_tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        OWNER_CELL_NOT_FOUND_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+}
+
 #[test]
 fn test_unlock_withdrawal_via_finalize_fallback_to_input_owner_cell() {
     init_env_log();
@@ -482,6 +739,130 @@ fn test_unlock_withdrawal_via_finalize_fallback_to_input_owner_cell() {
     verify_ctx.verify_tx(sign_tx).expect("success");
 }
 
+#[test]
+fn test_unlock_withdrawal_via_trade() {
+    init_env_log();
+
+    const DEFAULT_CAPACITY: u64 = 1000 * 10u64.pow(8);
+    // UnlockWithdrawalWitness::UnlockWithdrawalViaTrade, item id 2 with an empty table
+    // (hand encoded since the gw-types pinned by the tests doesn't have this variant yet)
+    const UNLOCK_WITHDRAWAL_VIA_TRADE: [u8; 8] = [2, 0, 0, 0, 4, 0, 0, 0];
+
+    let rollup_type_script = random_always_success_script();
+    let rollup_type_hash = rollup_type_script.hash();
+    let (mut verify_ctx, script_ctx) = build_verify_context();
+
+    let owner_lock = random_always_success_script();
+    let buyer_lock = random_always_success_script();
+    let account_script_hash = random_always_success_script().hash();
+    let withdrawal_block_hash = random_always_success_script().hash();
+    let withdrawal_block_number = rand::random::<u32>() as u64 + 100;
+    let build_withdrawal_cell = |block_number: u64, owner_lock: &Script| {
+        let lock_args = WithdrawalLockArgs::new_builder()
+            .account_script_hash(account_script_hash.pack())
+            .withdrawal_block_hash(withdrawal_block_hash.pack())
+            .withdrawal_block_number(block_number.pack())
+            .owner_lock_hash(owner_lock.hash().pack())
+            .build();
+
+        let mut args = Vec::new();
+        args.extend_from_slice(&lock_args.as_bytes());
+        args.extend_from_slice(&(owner_lock.as_bytes().len() as u32).to_be_bytes());
+        args.extend_from_slice(&owner_lock.as_bytes());
+
+        build_rollup_locked_cell(
+            &rollup_type_hash,
+            &script_ctx.withdrawal.script.hash(),
+            DEFAULT_CAPACITY,
+            Bytes::from(args),
+        )
+    };
+
+    let withdrawal_cell = (
+        build_withdrawal_cell(withdrawal_block_number, &owner_lock),
+        Bytes::new(),
+    );
+    let withdrawal_input = {
+        let out_point = verify_ctx.insert_cell(withdrawal_cell.0.clone(), Bytes::new());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let owner_input = {
+        let output = CellOutput::new_builder()
+            .capacity(DEFAULT_CAPACITY.pack())
+            .lock(owner_lock.clone())
+            .build();
+
+        let out_point = verify_ctx.insert_cell(output.to_ckb(), Bytes::new());
+        CellInput::new_builder()
+            .previous_output(out_point.to_gw())
+            .build()
+    };
+    let unlock_via_trade_witness = WitnessArgs::new_builder()
+        .lock(Some(Bytes::copy_from_slice(&UNLOCK_WITHDRAWAL_VIA_TRADE)).pack())
+        .build();
+    let build_trade_tx =
+        |verify_ctx: &mut CellContext, output_cell: (ckb_types::packed::CellOutput, Bytes)| {
+            build_simple_tx_with_out_point(
+                &mut verify_ctx.inner,
+                withdrawal_cell.clone(),
+                withdrawal_input.to_ckb().previous_output(),
+                output_cell,
+            )
+            .as_advanced_builder()
+            .witness(unlock_via_trade_witness.as_bytes().to_ckb())
+            .cell_dep(script_ctx.withdrawal.dep.to_ckb())
+            .build()
+        };
+    let traded_output_cell = (
+        build_withdrawal_cell(withdrawal_block_number, &buyer_lock),
+        Bytes::new(),
+    );
+
+    // ERROR: owner doesn't sign the trade
+    let tx = build_trade_tx(&mut verify_ctx, traded_output_cell.clone());
+    let err = verify_ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        OWNER_CELL_NOT_FOUND_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+
+    // ERROR: withdrawal block number is changed
+    let err_output_cell = (
+        build_withdrawal_cell(withdrawal_block_number - 1, &buyer_lock),
+        Bytes::new(),
+    );
+    let tx = build_trade_tx(&mut verify_ctx, err_output_cell)
+        .as_advanced_builder()
+        .input(owner_input.to_ckb())
+        .witness(Default::default())
+        .build();
+    let err = verify_ctx.verify_tx(tx).unwrap_err();
+    let expected_err = ScriptError::ValidationFailure(
+        format!(
+            "by-type-hash/{}",
+            ckb_types::H256(script_ctx.withdrawal.script.hash())
+        ),
+        INVALID_OUTPUT_ERROR,
+    )
+    .input_lock_script(0);
+    assert_error_eq!(err, expected_err);
+
+    // Transfer withdrawal to buyer
+    let tx = build_trade_tx(&mut verify_ctx, traded_output_cell)
+        .as_advanced_builder()
+        .input(owner_input.to_ckb())
+        .witness(Default::default())
+        .build();
+    verify_ctx.verify_tx(tx).expect("success");
+}
+
 struct ScriptDep {
     script: Script,
     dep: CellDep,
@@ -17,6 +17,8 @@ const ETH_ADDR_REG_BIN_NAME: &str = "eth-addr-reg-generator";
 // account locks
 const ETH_LOCK_PATH: &str = "eth-account-lock";
 const TRON_LOCK_PATH: &str = "tron-account-lock";
+const MULTISIG_LOCK_PATH: &str = "multisig-account-lock";
+const WEBAUTHN_LOCK_PATH: &str = "webauthn-account-lock";
 
 lazy_static! {
     pub static ref ALWAYS_SUCCESS_PROGRAM: Bytes = {
@@ -99,6 +101,38 @@ lazy_static! {
         hasher.finalize(&mut buf);
         buf
     };
+    pub static ref MULTISIG_ACCOUNT_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&MULTISIG_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref MULTISIG_ACCOUNT_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&MULTISIG_ACCOUNT_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
+    pub static ref WEBAUTHN_ACCOUNT_LOCK_PROGRAM: Bytes = {
+        let mut buf = Vec::new();
+        let mut path = PathBuf::new();
+        path.push(&SCRIPT_DIR);
+        path.push(&WEBAUTHN_LOCK_PATH);
+        let mut f = fs::File::open(&path).expect("load program");
+        f.read_to_end(&mut buf).expect("read program");
+        Bytes::from(buf.to_vec())
+    };
+    pub static ref WEBAUTHN_ACCOUNT_LOCK_CODE_HASH: [u8; 32] = {
+        let mut buf = [0u8; 32];
+        let mut hasher = new_blake2b();
+        hasher.update(&WEBAUTHN_ACCOUNT_LOCK_PROGRAM);
+        hasher.finalize(&mut buf);
+        buf
+    };
     pub static ref SECP256K1_DATA: Bytes = {
         let mut buf = Vec::new();
         let mut f = fs::File::open(&SECP256K1_DATA_PATH).expect("load secp256k1 data");